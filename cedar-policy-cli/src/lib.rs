@@ -95,8 +95,10 @@ pub enum Commands {
     Link(LinkArgs),
     /// Format a policy set
     Format(FormatArgs),
-    /// Translate JSON schema to natural schema syntax and vice versa (except comments)
+    /// Translate JSON schema to natural schema syntax and vice versa
     TranslateSchema(TranslateSchemaArgs),
+    /// Compare two versions of a schema, reporting breaking changes
+    SchemaDiff(SchemaDiffArgs),
     /// Create a Cedar project
     New(NewArgs),
 }
@@ -112,6 +114,23 @@ pub struct TranslateSchemaArgs {
     pub input_file: Option<String>,
 }
 
+#[derive(Args, Debug)]
+pub struct SchemaDiffArgs {
+    /// File containing the old version of the schema
+    #[arg(long = "old", value_name = "FILE")]
+    pub old_schema_file: String,
+    /// File containing the new version of the schema
+    #[arg(long = "new", value_name = "FILE")]
+    pub new_schema_file: String,
+    /// Schema format of both files (Human-readable or json)
+    #[arg(long, value_enum, default_value_t = SchemaFormat::Human)]
+    pub schema_format: SchemaFormat,
+    /// File containing policies to validate against both schemas, reporting
+    /// the policies that the new schema breaks
+    #[arg(short, long = "policies", value_name = "FILE")]
+    pub policies_file: Option<String>,
+}
+
 /// The direction of translation
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum TranslationDirection {
@@ -154,6 +173,16 @@ pub struct ValidateArgs {
     /// Schema format (Human-readable or json)
     #[arg(long, value_enum, default_value_t = SchemaFormat::Human)]
     pub schema_format: SchemaFormat,
+    /// Enable a lint rule, reporting violations as warnings. Given as `RULE`
+    /// or `RULE=CONFIG`, and may be repeated. Available rules:
+    /// `require-annotation[=KEY]`, `unscoped-permit`, `max-complexity[=N]`,
+    /// and `leading-wildcard-like`.
+    #[arg(long = "lint", value_name = "RULE")]
+    pub lints: Vec<String>,
+    /// Enable a lint rule, reporting violations as errors. Takes the same
+    /// values as `--lint`, and may be repeated.
+    #[arg(long = "deny-lint", value_name = "RULE")]
+    pub deny_lints: Vec<String>,
 }
 
 #[derive(Args, Debug)]
@@ -547,8 +576,21 @@ pub fn validate(args: &ValidateArgs) -> CedarExitCode {
         }
     };
 
+    let mut linter = Linter::new();
+    let lints = (args.lints.iter().map(|spec| (spec, LintSeverity::Warning))).chain(
+        args.deny_lints
+            .iter()
+            .map(|spec| (spec, LintSeverity::Error)),
+    );
+    for (spec, severity) in lints {
+        if let Err(e) = linter.add_builtin(spec, severity) {
+            println!("{:?}", Report::new(e));
+            return CedarExitCode::Failure;
+        }
+    }
+
     let validator = Validator::new(schema);
-    let result = validator.validate(&pset, mode);
+    let result = validator.validate_with_linter(&pset, mode, &linter);
 
     if !result.validation_passed()
         || (args.deny_warnings && !result.validation_passed_without_warnings())
@@ -567,6 +609,62 @@ pub fn validate(args: &ValidateArgs) -> CedarExitCode {
     }
 }
 
+pub fn schema_diff(args: &SchemaDiffArgs) -> CedarExitCode {
+    let schemas = read_schema_file(&args.old_schema_file, args.schema_format).and_then(|old| {
+        read_schema_file(&args.new_schema_file, args.schema_format).map(|new| (old, new))
+    });
+    let (old, new) = match schemas {
+        Ok(schemas) => schemas,
+        Err(e) => {
+            println!("{e:?}");
+            return CedarExitCode::Failure;
+        }
+    };
+    let pset = match args
+        .policies_file
+        .as_ref()
+        .map(|f| read_policy_set(Some(f)))
+    {
+        None => None,
+        Some(Ok(pset)) => Some(pset),
+        Some(Err(e)) => {
+            println!("{e:?}");
+            return CedarExitCode::Failure;
+        }
+    };
+
+    let diff = SchemaDiff::new(&old, &new);
+    for change in diff.changes() {
+        let kind = if change.is_breaking() {
+            "breaking"
+        } else {
+            "compatible"
+        };
+        println!("{kind}: {change}");
+    }
+    let broken = pset
+        .map(|pset| {
+            diff.broken_policies(&pset, ValidationMode::default())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    for error in &broken {
+        println!(
+            "{:?}",
+            Report::new(error.clone()).wrap_err(format!(
+                "policy `{}` is broken by the new schema",
+                error.location().policy_id()
+            ))
+        );
+    }
+    if diff.is_backward_compatible() && broken.is_empty() {
+        println!("schema change is backward compatible");
+        CedarExitCode::Success
+    } else {
+        CedarExitCode::ValidationFailure
+    }
+}
+
 pub fn evaluate(args: &EvaluateArgs) -> (CedarExitCode, EvalResult) {
     println!();
     let schema = match args
@@ -20,8 +20,8 @@ use clap::Parser;
 use miette::ErrorHook;
 
 use cedar_policy_cli::{
-    authorize, check_parse, evaluate, format_policies, link, new, translate_schema, validate,
-    CedarExitCode, Cli, Commands, ErrorFormat,
+    authorize, check_parse, evaluate, format_policies, link, new, schema_diff, translate_schema,
+    validate, CedarExitCode, Cli, Commands, ErrorFormat,
 };
 
 fn main() -> CedarExitCode {
@@ -48,6 +48,7 @@ fn main() -> CedarExitCode {
         Commands::Format(args) => format_policies(&args),
         Commands::Link(args) => link(&args),
         Commands::TranslateSchema(args) => translate_schema(&args),
+        Commands::SchemaDiff(args) => schema_diff(&args),
         Commands::New(args) => new(&args),
     }
 }
@@ -485,6 +485,8 @@ fn run_validate_test(
         deny_warnings: false,
         partial_validate: false,
         schema_format: SchemaFormat::Json,
+        lints: vec![],
+        deny_lints: vec![],
     };
     let output = validate(&cmd);
     assert_eq!(exit_code, output, "{:#?}", cmd);
@@ -503,6 +505,8 @@ fn run_validate_test(
         deny_warnings: false,
         partial_validate: false,
         schema_format: SchemaFormat::Human,
+        lints: vec![],
+        deny_lints: vec![],
     };
     let output = validate(&cmd);
     assert_eq!(exit_code, output, "{:#?}", cmd)
@@ -1021,3 +1025,74 @@ fn test_require_policies_for_write() {
             "the following required arguments were not provided:\n  --policies <FILE>",
         ));
 }
+
+#[test]
+fn test_validate_lints() {
+    let validate_with = |lint_args: &[&str]| {
+        assert_cmd::Command::cargo_bin("cedar")
+            .expect("bin exists")
+            .arg("validate")
+            .arg("--schema")
+            .arg("sample-data/sandbox_a/schema.cedarschema")
+            .arg("--policies")
+            .arg("sample-data/sandbox_a/policies_3.cedar")
+            .args(lint_args)
+            .assert()
+    };
+
+    validate_with(&["--lint", "require-annotation=owner"])
+        .code(0)
+        .stdout(predicates::str::contains("`require-annotation`"));
+    validate_with(&["--lint", "require-annotation"]).code(0);
+    validate_with(&["--deny-lint", "require-annotation=owner"])
+        .code(3)
+        .stdout(predicates::str::contains("policy set validation failed"));
+    validate_with(&[
+        "--lint",
+        "max-complexity=1000",
+        "--deny-lint",
+        "unscoped-permit",
+    ])
+    .code(0);
+    validate_with(&["--lint", "no-such-rule"])
+        .code(1)
+        .stdout(predicates::str::contains(
+            "unknown lint rule `no-such-rule`",
+        ));
+}
+
+#[test]
+fn test_schema_diff() {
+    let diff = |old: &str, new: &str| {
+        assert_cmd::Command::cargo_bin("cedar")
+            .expect("bin exists")
+            .arg("schema-diff")
+            .arg("--old")
+            .arg(old)
+            .arg("--new")
+            .arg(new)
+            .arg("--policies")
+            .arg("sample-data/sandbox_a/policies_1.cedar")
+            .assert()
+    };
+
+    diff(
+        "sample-data/sandbox_a/schema.cedarschema",
+        "sample-data/sandbox_a/schema.cedarschema",
+    )
+    .code(0)
+    .stdout(predicates::str::contains(
+        "schema change is backward compatible",
+    ));
+    diff(
+        "sample-data/sandbox_a/schema.cedarschema",
+        "sample-data/sandbox_b/schema.cedarschema",
+    )
+    .code(3)
+    .stdout(predicates::str::contains(
+        "breaking: entity type `Video` was removed",
+    ))
+    .stdout(predicates::str::contains(
+        "compatible: entity type `AccountGroup` was added",
+    ));
+}
@@ -0,0 +1,58 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use miette::Diagnostic;
+use thiserror::Error;
+
+/// Errors generating Rust code from a schema
+#[derive(Debug, Error, Diagnostic)]
+#[non_exhaustive]
+pub enum CodegenError {
+    /// Error reading a JSON schema, or converting a schema to JSON
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Schema(#[from] cedar_policy::SchemaError),
+    /// Error reading a schema in the natural syntax
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    NaturalSchema(#[from] cedar_policy::HumanSchemaError),
+    /// IO error reading the schema or writing the generated code
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// The schema refers to a common type or entity type that it does not
+    /// declare. Code is generated from a single schema fragment, so every
+    /// type it uses must be declared in that fragment.
+    #[error("schema refers to the undeclared type `{0}`")]
+    UndeclaredType(String),
+    /// The schema uses an extension type that has no Rust representation
+    #[error("schema uses the unsupported extension type `{0}`")]
+    UnsupportedExtensionType(String),
+    /// A name in the schema can't be turned into a Rust identifier
+    #[error("`{0}` cannot be turned into a Rust identifier")]
+    #[diagnostic(help(
+        "names must contain an ASCII letter, and may not start with a digit or be a reserved Rust keyword like `self`"
+    ))]
+    InvalidIdentifier(String),
+    /// Two names in the schema are turned into the same Rust identifier
+    #[error("the generated Rust name `{name}` is used more than once in {scope}")]
+    #[diagnostic(help("rename one of the conflicting declarations or attributes"))]
+    NameCollision {
+        /// The Rust identifier used more than once
+        name: String,
+        /// Where the identifier is used, such as a namespace or a struct
+        scope: String,
+    },
+}
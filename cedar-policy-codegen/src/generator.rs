@@ -0,0 +1,1352 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Rendering of a schema fragment as Rust source code.
+//!
+//! Each namespace becomes a module, nested according to the components of
+//! its name, with the items of the empty namespace at the top level. Within
+//! a namespace, each entity type `E` becomes a struct `E` holding an entity
+//! and a type `EUid` identifying one, each record type becomes a struct,
+//! and the actions become variants of an `Action` enum, with a struct
+//! `ARequest` for the requests each action `A` applies to.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use cedar_policy_core::ast::Name;
+use cedar_policy_validator::{
+    ActionType, CommonType, EntityType, SchemaFragment, SchemaType, SchemaTypeVariant,
+    TypeOfAttribute,
+};
+use smol_str::SmolStr;
+
+use crate::idents::{field_ident, type_ident};
+use crate::CodegenError;
+
+/// Types from the standard library prelude which generated code refers to
+/// by name, so generated items may not shadow them
+const PRELUDE_TYPES: &[&str] = &[
+    "Box", "From", "Option", "Result", "Send", "String", "Sync", "TryFrom", "Vec",
+];
+
+/// Extension types, with the `RestrictedExpression` constructor for each.
+/// Values of these types are represented by the strings passed to the
+/// constructor.
+const EXTENSION_TYPES: &[(&str, &str)] = &[
+    ("ipaddr", "new_ip"),
+    ("decimal", "new_decimal"),
+    ("datetime", "new_datetime"),
+    ("duration", "new_duration"),
+];
+
+/// Derived traits of the structs for entities, records, and requests
+const VALUE_DERIVES: &str = "Debug, Clone, PartialEq, Eq";
+
+/// Derived traits of the types for entity UIDs
+const UID_DERIVES: &str = "Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash";
+
+/// Derived traits of the enums for entity UIDs with known IDs
+const ID_ENUM_DERIVES: &str = "Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash";
+
+/// Generate Rust code for all the declarations in `fragment`
+pub(crate) fn generate(fragment: &SchemaFragment) -> Result<String, CodegenError> {
+    let generator = Generator::new(fragment)?;
+    let mut root = Module::default();
+    let mut namespaces = fragment.0.iter().collect::<Vec<_>>();
+    namespaces.sort_by_key(|(namespace, _)| namespace.as_ref().map(ToString::to_string));
+    for (namespace, def) in namespaces {
+        let mut items = Items::new(namespace.as_ref());
+        let mut common_types = def.common_types.iter().collect::<Vec<_>>();
+        common_types.sort_by_key(|(id, _)| *id);
+        for (id, common_type) in common_types {
+            let name = Name::from(id.clone()).prefix_namespace_if_unqualified(namespace.as_ref());
+            generator.common_type_items(&mut items, &name, common_type)?;
+        }
+        let mut entity_types = def.entity_types.iter().collect::<Vec<_>>();
+        entity_types.sort_by_key(|(id, _)| *id);
+        for (id, entity_type) in entity_types {
+            let name = Name::from(id.clone()).prefix_namespace_if_unqualified(namespace.as_ref());
+            generator.entity_type_items(&mut items, &name, entity_type)?;
+        }
+        let mut actions = def.actions.iter().collect::<Vec<_>>();
+        actions.sort_by_key(|(id, _)| *id);
+        generator.action_items(&mut items, &actions)?;
+
+        let mut module = &mut root;
+        for ident in generator.module(namespace.as_ref()) {
+            module = module.children.entry(ident.clone()).or_default();
+        }
+        // distinct namespaces, like `PhotoApp` and `photo_app`, may have
+        // the same module
+        if let Some(other) = &module.namespace {
+            return Err(CodegenError::NameCollision {
+                name: generator.module(namespace.as_ref()).join("::"),
+                scope: format!("{other} and {}", items.scope()),
+            });
+        }
+        module.namespace = Some(items.scope());
+        if let Some(namespace) = namespace {
+            module.doc = def.annotations.get("doc").map_or_else(
+                || format!("Types generated from the `{namespace}` namespace"),
+                ToString::to_string,
+            );
+        }
+        module.items = items.items;
+    }
+    root.check_idents("the empty namespace")?;
+    Ok(format!(
+        "// @generated by cedar-policy-codegen. Do not edit this file by hand.\n\n{}",
+        root.render()
+    ))
+}
+
+/// Rust code, built up line by line
+#[derive(Debug, Default)]
+struct Code {
+    text: String,
+    /// Indentation of the next line, in levels of four spaces
+    depth: usize,
+}
+
+impl Code {
+    /// Add a line, or an empty line if `line` is empty
+    fn line(&mut self, line: &str) -> &mut Self {
+        if !line.is_empty() {
+            self.text.push_str(&"    ".repeat(self.depth));
+            self.text.push_str(line);
+        }
+        self.text.push('\n');
+        self
+    }
+
+    /// Add a line which opens a block
+    fn open(&mut self, line: &str) -> &mut Self {
+        self.line(line);
+        self.depth += 1;
+        self
+    }
+
+    /// Add a line which closes a block
+    fn close(&mut self, line: &str) -> &mut Self {
+        self.depth -= 1;
+        self.line(line)
+    }
+
+    /// Add a line which closes one block and opens another
+    fn reopen(&mut self, line: &str) -> &mut Self {
+        self.depth -= 1;
+        self.open(line)
+    }
+
+    /// Add a doc comment: the `doc` annotation if there is one, or else
+    /// `default`
+    fn doc(&mut self, annotation: Option<&str>, default: &str) -> &mut Self {
+        for line in annotation.unwrap_or(default).lines() {
+            match line.trim_end() {
+                "" => self.line("///"),
+                line => self.line(&format!("/// {line}")),
+            };
+        }
+        self
+    }
+
+    /// Add `code`, which was built separately, at the current indentation
+    fn append(&mut self, code: &str) -> &mut Self {
+        for line in code.lines() {
+            self.line(line);
+        }
+        self
+    }
+}
+
+/// A generated module
+#[derive(Debug, Default)]
+struct Module {
+    /// The namespace the module was generated for, if it was generated for
+    /// one, rather than only containing the modules of nested namespaces
+    namespace: Option<String>,
+    /// Doc comment on the module
+    doc: String,
+    /// The items in the module
+    items: Vec<Item>,
+    /// Submodules, by identifier
+    children: BTreeMap<String, Module>,
+}
+
+impl Module {
+    /// Check that no submodule has the same identifier as an item, in this
+    /// module, described as `scope`, or any submodule
+    fn check_idents(&self, scope: &str) -> Result<(), CodegenError> {
+        for (ident, child) in &self.children {
+            if self.items.iter().any(|item| item.ident == *ident) {
+                return Err(CodegenError::NameCollision {
+                    name: ident.clone(),
+                    scope: scope.to_string(),
+                });
+            }
+            child.check_idents(&format!("module `{ident}`"))?;
+        }
+        Ok(())
+    }
+
+    /// The contents of this module, including its submodules
+    fn render(&self) -> String {
+        let mut blocks = self
+            .items
+            .iter()
+            .map(|item| item.code.text.clone())
+            .collect::<Vec<_>>();
+        for (ident, child) in &self.children {
+            let mut code = Code::default();
+            code.doc(
+                (!child.doc.is_empty()).then_some(child.doc.as_str()),
+                "Types generated from the namespaces nested in this one",
+            )
+            .open(&format!("pub mod {ident} {{"))
+            .append(&child.render())
+            .close("}");
+            blocks.push(code.text);
+        }
+        blocks.join("\n")
+    }
+}
+
+/// A generated item
+#[derive(Debug)]
+struct Item {
+    /// The identifier the item declares
+    ident: String,
+    code: Code,
+}
+
+/// The items generated for one namespace
+#[derive(Debug)]
+struct Items<'a> {
+    /// The namespace
+    namespace: Option<&'a Name>,
+    /// Rust identifiers declared by the items so far, including those of
+    /// the prelude, which must not be shadowed
+    idents: HashSet<String>,
+    /// The items, in the order they're generated
+    items: Vec<Item>,
+}
+
+impl<'a> Items<'a> {
+    fn new(namespace: Option<&'a Name>) -> Self {
+        Self {
+            namespace,
+            idents: PRELUDE_TYPES.iter().map(ToString::to_string).collect(),
+            items: Vec::new(),
+        }
+    }
+
+    /// The namespace, as it appears in error messages
+    fn scope(&self) -> String {
+        match self.namespace {
+            Some(namespace) => format!("namespace `{namespace}`"),
+            None => "the empty namespace".to_string(),
+        }
+    }
+
+    /// Reserve a place for an item declaring `ident`, so that it comes
+    /// before any items generated while building it
+    fn reserve(&mut self, ident: &str) -> Result<usize, CodegenError> {
+        if !self.idents.insert(ident.to_string()) {
+            return Err(CodegenError::NameCollision {
+                name: ident.to_string(),
+                scope: self.scope(),
+            });
+        }
+        self.items.push(Item {
+            ident: ident.to_string(),
+            code: Code::default(),
+        });
+        Ok(self.items.len() - 1)
+    }
+
+    /// Fill in the place reserved for an item
+    fn fill(&mut self, index: usize, code: Code) {
+        // PANIC SAFETY: `index` was returned by `reserve`, and items are
+        // never removed
+        #[allow(clippy::indexing_slicing)]
+        {
+            self.items[index].code = code;
+        }
+    }
+
+    /// Add an item declaring `ident`
+    fn push(&mut self, ident: &str, code: Code) -> Result<(), CodegenError> {
+        let index = self.reserve(ident)?;
+        self.fill(index, code);
+        Ok(())
+    }
+}
+
+/// A common type or entity type declared in the fragment
+#[derive(Debug)]
+struct Decl<'a, T> {
+    /// The namespace the type is declared in
+    namespace: Option<&'a Name>,
+    /// The Rust identifier of the type
+    ident: String,
+    /// The declaration
+    decl: &'a T,
+}
+
+/// A struct field generated for an attribute
+#[derive(Debug)]
+struct Field<'a> {
+    /// The Rust identifier of the field
+    ident: String,
+    /// The attribute name
+    name: &'a SmolStr,
+    /// The attribute
+    attr: &'a TypeOfAttribute,
+    /// The namespace that names in the attribute's type are relative to
+    namespace: Option<&'a Name>,
+    /// The Rust type of the field
+    rust_type: String,
+}
+
+#[derive(Debug)]
+struct Generator<'a> {
+    /// The entity types declared in the fragment, by fully qualified name
+    entity_types: HashMap<Name, Decl<'a, EntityType>>,
+    /// The common types declared in the fragment, by fully qualified name
+    common_types: HashMap<Name, Decl<'a, CommonType>>,
+    /// The path of the module generated for each namespace
+    modules: HashMap<Option<Name>, Vec<String>>,
+}
+
+impl<'a> Generator<'a> {
+    fn new(fragment: &'a SchemaFragment) -> Result<Self, CodegenError> {
+        let mut generator = Self {
+            entity_types: HashMap::new(),
+            common_types: HashMap::new(),
+            modules: HashMap::new(),
+        };
+        for (namespace, def) in &fragment.0 {
+            let module = match namespace {
+                Some(namespace) => namespace
+                    .namespace_components()
+                    .chain(std::iter::once(namespace.basename()))
+                    .map(|id| field_ident(id.as_ref()))
+                    .collect::<Result<_, _>>()?,
+                None => Vec::new(),
+            };
+            generator.modules.insert(namespace.clone(), module);
+            let namespace = namespace.as_ref();
+            for (id, decl) in &def.entity_types {
+                generator.entity_types.insert(
+                    Name::from(id.clone()).prefix_namespace_if_unqualified(namespace),
+                    Decl {
+                        namespace,
+                        ident: type_ident(id.as_ref())?,
+                        decl,
+                    },
+                );
+            }
+            for (id, decl) in &def.common_types {
+                generator.common_types.insert(
+                    Name::from(id.clone()).prefix_namespace_if_unqualified(namespace),
+                    Decl {
+                        namespace,
+                        ident: type_ident(id.as_ref())?,
+                        decl,
+                    },
+                );
+            }
+        }
+        Ok(generator)
+    }
+
+    /// The entity type `name`, which appears in `namespace`
+    fn entity_type(
+        &self,
+        namespace: Option<&Name>,
+        name: &Name,
+    ) -> Result<&Decl<'a, EntityType>, CodegenError> {
+        let name = name.prefix_namespace_if_unqualified(namespace);
+        self.entity_types
+            .get(&name)
+            .ok_or_else(|| CodegenError::UndeclaredType(name.to_string()))
+    }
+
+    /// The common type `name`, which appears in `namespace`
+    fn common_type(
+        &self,
+        namespace: Option<&Name>,
+        name: &Name,
+    ) -> Result<&Decl<'a, CommonType>, CodegenError> {
+        let name = name.prefix_namespace_if_unqualified(namespace);
+        self.common_types
+            .get(&name)
+            .ok_or_else(|| CodegenError::UndeclaredType(name.to_string()))
+    }
+
+    /// The path of the module for `namespace`, which is empty for the empty
+    /// namespace and for namespaces the fragment doesn't declare
+    fn module(&self, namespace: Option<&Name>) -> &[String] {
+        self.modules
+            .get(&namespace.cloned())
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// The path to `ident`, declared in the module for namespace `to`, from
+    /// the module for namespace `from`
+    fn path(&self, from: Option<&Name>, to: Option<&Name>, ident: &str) -> String {
+        if from == to {
+            return ident.to_string();
+        }
+        let mut path = "super::".repeat(self.module(from).len());
+        for module in self.module(to) {
+            path.push_str(module);
+            path.push_str("::");
+        }
+        path + ident
+    }
+
+    /// The path to the UID type of the entity type `name`, which appears in
+    /// `namespace`, from the module for `from`
+    fn uid_path(
+        &self,
+        from: Option<&Name>,
+        namespace: Option<&Name>,
+        name: &Name,
+    ) -> Result<String, CodegenError> {
+        let decl = self.entity_type(namespace, name)?;
+        Ok(self.path(from, decl.namespace, &format!("{}Uid", decl.ident)))
+    }
+
+    /// The attributes of `ty`, which appears in `namespace`, if it is a
+    /// record type or a common type defined as one, with the namespace that
+    /// names in the attribute types are relative to
+    #[allow(clippy::type_complexity)]
+    fn record_attributes(
+        &self,
+        namespace: Option<&'a Name>,
+        ty: &'a SchemaType,
+    ) -> Result<Option<(Option<&'a Name>, &'a BTreeMap<SmolStr, TypeOfAttribute>)>, CodegenError>
+    {
+        match ty {
+            SchemaType::Type(SchemaTypeVariant::Record { attributes, .. }) => {
+                Ok(Some((namespace, attributes)))
+            }
+            SchemaType::TypeDef { type_name } => {
+                let decl = self.common_type(namespace, type_name)?;
+                self.record_attributes(decl.namespace, &decl.decl.ty)
+            }
+            SchemaType::Type(_) => Ok(None),
+        }
+    }
+
+    /// The Rust type representing values of `ty`, which appears in
+    /// `namespace`. If `ty` is a record type, it is represented by a new
+    /// struct named `record`, documented with `doc`.
+    fn rust_type(
+        &self,
+        items: &mut Items<'a>,
+        namespace: Option<&'a Name>,
+        ty: &'a SchemaType,
+        record: &str,
+        doc: &str,
+    ) -> Result<String, CodegenError> {
+        match ty {
+            SchemaType::Type(SchemaTypeVariant::String) => Ok("String".to_string()),
+            SchemaType::Type(SchemaTypeVariant::Long) => Ok("i64".to_string()),
+            SchemaType::Type(SchemaTypeVariant::Boolean) => Ok("bool".to_string()),
+            SchemaType::Type(SchemaTypeVariant::Set { element }) => Ok(format!(
+                "Vec<{}>",
+                self.rust_type(items, namespace, element, record, doc)?
+            )),
+            SchemaType::Type(SchemaTypeVariant::Record { attributes, .. }) => {
+                self.record_items(items, namespace, record, None, doc, attributes)?;
+                Ok(record.to_string())
+            }
+            SchemaType::Type(SchemaTypeVariant::Entity { name }) => {
+                self.uid_path(items.namespace, namespace, name)
+            }
+            SchemaType::Type(SchemaTypeVariant::Extension { name }) => {
+                if EXTENSION_TYPES.iter().any(|(ext, _)| *ext == name.as_ref()) {
+                    Ok("String".to_string())
+                } else {
+                    Err(CodegenError::UnsupportedExtensionType(name.to_string()))
+                }
+            }
+            SchemaType::TypeDef { type_name } => {
+                let decl = self.common_type(namespace, type_name)?;
+                Ok(self.path(items.namespace, decl.namespace, &decl.ident))
+            }
+        }
+    }
+
+    /// A Rust expression converting `value` into a `RestrictedExpression`.
+    /// `value` is a place expression holding a value of the Rust type for
+    /// `ty`, or a reference to one if `is_ref` is set.
+    fn restricted_expr(
+        &self,
+        namespace: Option<&Name>,
+        ty: &SchemaType,
+        value: &str,
+        is_ref: bool,
+    ) -> Result<String, CodegenError> {
+        let by_ref = if is_ref {
+            value.to_string()
+        } else {
+            format!("&{value}")
+        };
+        let by_copy = if is_ref {
+            format!("*{value}")
+        } else {
+            value.to_string()
+        };
+        let expr = "::cedar_policy::RestrictedExpression";
+        match ty {
+            SchemaType::Type(SchemaTypeVariant::String) => {
+                Ok(format!("{expr}::new_string({value}.clone())"))
+            }
+            SchemaType::Type(SchemaTypeVariant::Long) => Ok(format!("{expr}::new_long({by_copy})")),
+            SchemaType::Type(SchemaTypeVariant::Boolean) => {
+                Ok(format!("{expr}::new_bool({by_copy})"))
+            }
+            SchemaType::Type(SchemaTypeVariant::Set { element }) => {
+                let element = self.restricted_expr(namespace, element, "v", true)?;
+                // pass the function itself if the conversion is a single
+                // call, since clippy warns about redundant closures
+                let convert = match element.strip_suffix("(v)") {
+                    Some(function) if !function.contains('(') => function.to_string(),
+                    _ => format!("|v| {element}"),
+                };
+                Ok(format!("{expr}::new_set({value}.iter().map({convert}))"))
+            }
+            SchemaType::Type(SchemaTypeVariant::Record { .. }) => {
+                Ok(format!("{expr}::from({by_ref})"))
+            }
+            SchemaType::Type(SchemaTypeVariant::Entity { .. }) => Ok(format!(
+                "{expr}::new_entity_uid(::cedar_policy::EntityUid::from({by_ref}))"
+            )),
+            SchemaType::Type(SchemaTypeVariant::Extension { name }) => EXTENSION_TYPES
+                .iter()
+                .find(|(ext, _)| *ext == name.as_ref())
+                .map(|(_, constructor)| format!("{expr}::{constructor}({by_ref})"))
+                .ok_or_else(|| CodegenError::UnsupportedExtensionType(name.to_string())),
+            SchemaType::TypeDef { type_name } => {
+                let decl = self.common_type(namespace, type_name)?;
+                self.restricted_expr(decl.namespace, &decl.decl.ty, value, is_ref)
+            }
+        }
+    }
+
+    /// The fields of the struct `owner`, generated for `attributes`, which
+    /// appear in `namespace`. Record types of attributes get structs named
+    /// after `owner` and the attribute, and documented as values of the
+    /// attribute of `owner_desc`. The fields may not be named `reserved`.
+    fn fields(
+        &self,
+        items: &mut Items<'a>,
+        namespace: Option<&'a Name>,
+        owner: &str,
+        owner_desc: &str,
+        attributes: &'a BTreeMap<SmolStr, TypeOfAttribute>,
+        reserved: &[&str],
+    ) -> Result<Vec<Field<'a>>, CodegenError> {
+        let mut idents = reserved
+            .iter()
+            .map(ToString::to_string)
+            .collect::<HashSet<_>>();
+        let mut fields = Vec::new();
+        for (name, attr) in attributes {
+            let ident = field_ident(name)?;
+            if !idents.insert(ident.clone()) {
+                return Err(CodegenError::NameCollision {
+                    name: ident,
+                    scope: format!("struct `{owner}`"),
+                });
+            }
+            let rust_type = self.rust_type(
+                items,
+                namespace,
+                &attr.ty,
+                &format!("{owner}{}", type_ident(name)?),
+                &format!("A value of the `{name}` attribute of {owner_desc}"),
+            )?;
+            fields.push(Field {
+                ident,
+                name,
+                attr,
+                namespace,
+                rust_type: if attr.required {
+                    rust_type
+                } else {
+                    format!("Option<{rust_type}>")
+                },
+            });
+        }
+        Ok(fields)
+    }
+
+    /// Add the fields of a struct to `code`. The `extra` fields, given as
+    /// `(ident, type, doc)`, come before those generated for attributes.
+    fn struct_fields(code: &mut Code, extra: &[(&str, String, &str)], fields: &[Field<'_>]) {
+        for (ident, rust_type, doc) in extra {
+            code.doc(None, doc)
+                .line(&format!("pub {ident}: {rust_type},"));
+        }
+        for field in fields {
+            code.doc(
+                field.attr.annotations.get("doc"),
+                &format!("The `{}` attribute", field.name),
+            )
+            .line(&format!("pub {}: {},", field.ident, field.rust_type));
+        }
+    }
+
+    /// Add the `attributes` method of a struct with `fields` to `code`
+    fn attributes_method(
+        &self,
+        code: &mut Code,
+        fields: &[Field<'_>],
+        doc: &str,
+    ) -> Result<(), CodegenError> {
+        code.doc(None, doc).open(
+            "pub fn attributes(&self) -> Vec<(String, ::cedar_policy::RestrictedExpression)> {",
+        );
+        let (required, optional): (Vec<_>, Vec<_>) =
+            fields.iter().partition(|field| field.attr.required);
+        if optional.is_empty() && required.is_empty() {
+            code.line("Vec::new()");
+        } else {
+            if required.is_empty() {
+                code.line("let mut attributes = Vec::new();");
+            } else {
+                if optional.is_empty() {
+                    code.open("vec![");
+                } else {
+                    code.open("let mut attributes = vec![");
+                }
+                for field in required {
+                    let value = format!("self.{}", field.ident);
+                    let expr =
+                        self.restricted_expr(field.namespace, &field.attr.ty, &value, false)?;
+                    code.line(&format!("({:?}.to_string(), {expr}),", field.name));
+                }
+                code.close(if optional.is_empty() { "]" } else { "];" });
+            }
+            for field in &optional {
+                let expr = self.restricted_expr(field.namespace, &field.attr.ty, "value", true)?;
+                code.open(&format!("if let Some(value) = &self.{} {{", field.ident))
+                    .line(&format!(
+                        "attributes.push(({:?}.to_string(), {expr}));",
+                        field.name
+                    ))
+                    .close("}");
+            }
+            if !optional.is_empty() {
+                code.line("attributes");
+            }
+        }
+        code.close("}");
+        Ok(())
+    }
+
+    /// Add the struct `ident` for a record type with `attributes`, which
+    /// appear in `namespace`, to `items`. Its doc comment is the `doc`
+    /// annotation if there is one, or else `default_doc`.
+    fn record_items(
+        &self,
+        items: &mut Items<'a>,
+        namespace: Option<&'a Name>,
+        ident: &str,
+        doc: Option<&str>,
+        default_doc: &str,
+        attributes: &'a BTreeMap<SmolStr, TypeOfAttribute>,
+    ) -> Result<(), CodegenError> {
+        let index = items.reserve(ident)?;
+        let fields = self.fields(
+            items,
+            namespace,
+            ident,
+            &format!("`{ident}` records"),
+            attributes,
+            &[],
+        )?;
+        let mut code = Code::default();
+        code.doc(doc, default_doc)
+            .line(&format!("#[derive({VALUE_DERIVES})]"));
+        if fields.is_empty() {
+            code.line(&format!("pub struct {ident} {{}}"));
+        } else {
+            code.open(&format!("pub struct {ident} {{"));
+            Self::struct_fields(&mut code, &[], &fields);
+            code.close("}");
+        }
+        code.line("").open(&format!("impl {ident} {{"));
+        self.attributes_method(&mut code, &fields, "The attributes of this record")?;
+        code.close("}")
+            .line("")
+            .open(&format!(
+                "impl From<&{ident}> for ::cedar_policy::RestrictedExpression {{"
+            ))
+            .open(&format!("fn from(record: &{ident}) -> Self {{"))
+            .line("Self::new_record(record.attributes()).expect(\"attribute names are distinct\")")
+            .close("}")
+            .close("}");
+        items.fill(index, code);
+        Ok(())
+    }
+
+    /// Add the items for the common type `name` to `items`
+    fn common_type_items(
+        &self,
+        items: &mut Items<'a>,
+        name: &Name,
+        common_type: &'a CommonType,
+    ) -> Result<(), CodegenError> {
+        let namespace = items.namespace;
+        let ident = type_ident(name.basename().as_ref())?;
+        let doc = common_type.annotations.get("doc");
+        let default_doc = format!("The `{name}` common type");
+        match &common_type.ty {
+            SchemaType::Type(SchemaTypeVariant::Record { attributes, .. }) => {
+                self.record_items(items, namespace, &ident, doc, &default_doc, attributes)
+            }
+            ty => {
+                let index = items.reserve(&ident)?;
+                let rust_type = self.rust_type(
+                    items,
+                    namespace,
+                    ty,
+                    &format!("{ident}Element"),
+                    &format!("An element of the `{name}` common type"),
+                )?;
+                let mut code = Code::default();
+                code.doc(doc, &default_doc)
+                    .line(&format!("pub type {ident} = {rust_type};"));
+                items.fill(index, code);
+                Ok(())
+            }
+        }
+    }
+
+    /// Add the items for the entity type `name` to `items`
+    fn entity_type_items(
+        &self,
+        items: &mut Items<'a>,
+        name: &Name,
+        entity_type: &'a EntityType,
+    ) -> Result<(), CodegenError> {
+        let namespace = items.namespace;
+        let ident = type_ident(name.basename().as_ref())?;
+        let index = items.reserve(&ident)?;
+        let uid = format!("{ident}Uid");
+        let uid_code = match &entity_type.choices {
+            Some(choices) => {
+                let variants = choices
+                    .iter()
+                    .map(|id| {
+                        Ok(Variant {
+                            ident: type_ident(id)?,
+                            id,
+                            doc: None,
+                            default_doc: format!("`{name}::{:?}`", id.as_str()),
+                        })
+                    })
+                    .collect::<Result<Vec<_>, CodegenError>>()?;
+                id_enum(
+                    &uid,
+                    &format!(
+                        "The UID of a `{name}` entity. `{name}` is an enumerated entity type, so these are its only entities."
+                    ),
+                    &name.to_string(),
+                    &variants,
+                )?
+            }
+            None => uid_struct(&uid, &name.to_string()),
+        };
+        items.push(&uid, uid_code)?;
+
+        let mut extra_fields = vec![("uid", uid.clone(), "The UID of this entity")];
+        if !entity_type.member_of_types.is_empty() {
+            let parent = format!("{ident}Parent");
+            let code = self.uid_enum(
+                namespace,
+                &parent,
+                &format!("The UID of a parent of a `{name}` entity"),
+                &entity_type.member_of_types,
+            )?;
+            items.push(&parent, code)?;
+            extra_fields.push((
+                "parents",
+                format!("Vec<{parent}>"),
+                "The parents of this entity",
+            ));
+        }
+        let fields = match self.record_attributes(namespace, &entity_type.shape.0)? {
+            Some((namespace, attributes)) => self.fields(
+                items,
+                namespace,
+                &ident,
+                &format!("`{name}` entities"),
+                attributes,
+                &["uid", "parents", "tags"],
+            )?,
+            None => Vec::new(),
+        };
+        let tags = match &entity_type.tags {
+            Some(ty) => {
+                let rust_type = self.rust_type(
+                    items,
+                    namespace,
+                    ty,
+                    &format!("{ident}Tag"),
+                    &format!("A tag of a `{name}` entity"),
+                )?;
+                let tags = format!("::std::collections::BTreeMap<String, {rust_type}>");
+                Some((ty, tags))
+            }
+            None => None,
+        };
+        let tag_field = tags
+            .as_ref()
+            .map(|(_, rust_type)| ("tags", rust_type.clone(), "The tags of this entity"));
+
+        let mut code = Code::default();
+        code.doc(
+            entity_type.annotations.get("doc"),
+            &format!("A `{name}` entity"),
+        )
+        .line(&format!("#[derive({VALUE_DERIVES})]"))
+        .open(&format!("pub struct {ident} {{"));
+        Self::struct_fields(&mut code, &extra_fields, &fields);
+        if let Some((field, rust_type, doc)) = &tag_field {
+            code.doc(None, doc)
+                .line(&format!("pub {field}: {rust_type},"));
+        }
+        code.close("}").line("").open(&format!("impl {ident} {{"));
+        self.attributes_method(&mut code, &fields, "The attributes of this entity")?;
+        code.close("}").line("");
+
+        let parents = if entity_type.member_of_types.is_empty() {
+            "::std::collections::HashSet::new()"
+        } else {
+            "entity.parents.iter().map(::cedar_policy::EntityUid::from).collect()"
+        };
+        code.open(&format!(
+            "impl TryFrom<&{ident}> for ::cedar_policy::Entity {{"
+        ))
+        .line("type Error = ::cedar_policy::EntityAttrEvaluationError;")
+        .line("")
+        .open(&format!(
+            "fn try_from(entity: &{ident}) -> Result<Self, Self::Error> {{"
+        ));
+        match &tags {
+            Some((ty, _)) => {
+                let tag = self.restricted_expr(namespace, ty, "v", true)?;
+                code.open("Self::new_with_tags(")
+                    .line("::cedar_policy::EntityUid::from(&entity.uid),")
+                    .line("entity.attributes().into_iter().collect(),")
+                    .line(&format!("{parents},"))
+                    .line(&format!(
+                        "entity.tags.iter().map(|(key, v)| (key.clone(), {tag})).collect(),"
+                    ))
+                    .close(")");
+            }
+            None => {
+                code.open("Self::new(")
+                    .line("::cedar_policy::EntityUid::from(&entity.uid),")
+                    .line("entity.attributes().into_iter().collect(),")
+                    .line(&format!("{parents},"))
+                    .close(")");
+            }
+        }
+        code.close("}")
+            .close("}")
+            .line("")
+            .open(&format!(
+                "impl TryFrom<{ident}> for ::cedar_policy::Entity {{"
+            ))
+            .line("type Error = ::cedar_policy::EntityAttrEvaluationError;")
+            .line("")
+            .open(&format!(
+                "fn try_from(entity: {ident}) -> Result<Self, Self::Error> {{"
+            ))
+            .line("Self::try_from(&entity)")
+            .close("}")
+            .close("}");
+        items.fill(index, code);
+        Ok(())
+    }
+
+    /// An enum `ident` with a variant for the UIDs of each of `entity_types`,
+    /// which appear in the namespace of `items`
+    fn uid_enum(
+        &self,
+        namespace: Option<&Name>,
+        ident: &str,
+        doc: &str,
+        entity_types: &[Name],
+    ) -> Result<Code, CodegenError> {
+        // the entity types, without duplicates, with the Rust identifier and
+        // UID type of each
+        let mut variants: Vec<(Name, String, String)> = Vec::new();
+        for name in entity_types {
+            let name = name.prefix_namespace_if_unqualified(namespace);
+            if variants.iter().any(|(other, _, _)| *other == name) {
+                continue;
+            }
+            let decl = self.entity_type(None, &name)?;
+            let uid = self.path(namespace, decl.namespace, &format!("{}Uid", decl.ident));
+            variants.push((name, decl.ident.clone(), uid));
+        }
+        let mut variant_idents = HashSet::new();
+        for (_, variant, _) in &variants {
+            if !variant_idents.insert(variant) {
+                return Err(CodegenError::NameCollision {
+                    name: variant.clone(),
+                    scope: format!("enum `{ident}`"),
+                });
+            }
+        }
+
+        let mut code = Code::default();
+        code.doc(None, doc)
+            .line(&format!("#[derive({UID_DERIVES})]"))
+            .open(&format!("pub enum {ident} {{"));
+        for (name, variant, uid) in &variants {
+            code.doc(None, &format!("The UID of a `{name}` entity"))
+                .line(&format!("{variant}({uid}),"));
+        }
+        code.close("}");
+        for (_, variant, uid) in &variants {
+            code.line("")
+                .open(&format!("impl From<{uid}> for {ident} {{"))
+                .open(&format!("fn from(uid: {uid}) -> Self {{"))
+                .line(&format!("Self::{variant}(uid)"))
+                .close("}")
+                .close("}");
+        }
+        code.line("")
+            .open(&format!(
+                "impl From<&{ident}> for ::cedar_policy::EntityUid {{"
+            ))
+            .open(&format!("fn from(uid: &{ident}) -> Self {{"))
+            .open("match uid {");
+        for (_, variant, _) in &variants {
+            code.line(&format!("{ident}::{variant}(uid) => Self::from(uid),"));
+        }
+        code.close("}").close("}").close("}");
+        from_owned_uid(&mut code, ident);
+        Ok(code)
+    }
+
+    /// Add the `Action` enum and request types for `actions` to `items`
+    fn action_items(
+        &self,
+        items: &mut Items<'a>,
+        actions: &[(&'a SmolStr, &'a ActionType)],
+    ) -> Result<(), CodegenError> {
+        if actions.is_empty() {
+            return Ok(());
+        }
+        let namespace = items.namespace;
+        let action_type = match namespace {
+            Some(namespace) => format!("{namespace}::Action"),
+            None => "Action".to_string(),
+        };
+        let variants = actions
+            .iter()
+            .map(|(id, action)| {
+                Ok(Variant {
+                    ident: type_ident(id)?,
+                    id,
+                    doc: action.annotations.get("doc"),
+                    default_doc: format!("`{action_type}::{:?}`", id.as_str()),
+                })
+            })
+            .collect::<Result<Vec<_>, CodegenError>>()?;
+        let code = id_enum(
+            "Action",
+            "The actions declared in this namespace",
+            &action_type,
+            &variants,
+        )?;
+        items.push("Action", code)?;
+
+        for (variant, (id, action)) in variants.iter().zip(actions) {
+            let (principal_types, resource_types, context) = match &action.applies_to {
+                Some(applies_to) => (
+                    applies_to.principal_types.as_ref(),
+                    applies_to.resource_types.as_ref(),
+                    Some(&applies_to.context.0),
+                ),
+                None => (None, None, None),
+            };
+            // no request is valid for an action that applies to no
+            // principals or no resources
+            if principal_types.is_some_and(Vec::is_empty)
+                || resource_types.is_some_and(Vec::is_empty)
+            {
+                continue;
+            }
+            let action_uid = format!("{action_type}::{:?}", id.as_str());
+            let request = format!("{}Request", variant.ident);
+            let index = items.reserve(&request)?;
+            let mut fields = Vec::new();
+            for (field, types) in [("principal", principal_types), ("resource", resource_types)] {
+                let Some(types) = types else { continue };
+                let rust_type = match types.as_slice() {
+                    [name] => self.uid_path(namespace, namespace, name)?,
+                    types => {
+                        let ident = format!("{}{}", variant.ident, type_ident(field)?);
+                        let code = self.uid_enum(
+                            namespace,
+                            &ident,
+                            &format!("The UID of a {field} of a request to perform `{action_uid}`"),
+                            types,
+                        )?;
+                        items.push(&ident, code)?;
+                        ident
+                    }
+                };
+                fields.push((field, rust_type));
+            }
+            let context_ident = format!("{}Context", variant.ident);
+            let attributes = match context {
+                Some(context) => self.record_attributes(namespace, context)?,
+                None => None,
+            };
+            match attributes {
+                Some((namespace, attributes)) => self.record_items(
+                    items,
+                    namespace,
+                    &context_ident,
+                    None,
+                    &format!("The context of a request to perform `{action_uid}`"),
+                    attributes,
+                )?,
+                None => {
+                    let mut code = Code::default();
+                    code.doc(
+                        None,
+                        &format!("The context of a request to perform `{action_uid}`"),
+                    )
+                    .line(&format!("#[derive({VALUE_DERIVES})]"))
+                    .line(&format!("pub struct {context_ident} {{}}"))
+                    .line("")
+                    .open(&format!("impl {context_ident} {{"));
+                    self.attributes_method(&mut code, &[], "The attributes of this record")?;
+                    code.close("}");
+                    items.push(&context_ident, code)?;
+                }
+            }
+
+            let mut code = Code::default();
+            code.doc(None, &format!("A request to perform `{action_uid}`"))
+                .line(&format!("#[derive({VALUE_DERIVES})]"))
+                .open(&format!("pub struct {request} {{"));
+            for (field, rust_type) in &fields {
+                code.doc(None, &format!("The {field} of the request"))
+                    .line(&format!("pub {field}: {rust_type},"));
+            }
+            code.doc(None, "The context of the request")
+                .line(&format!("pub context: {context_ident},"))
+                .close("}")
+                .line("")
+                .open(&format!("impl {request} {{"))
+                .doc(
+                    None,
+                    "Convert this into a Cedar request, which is validated against `schema` if one is given",
+                )
+                .open("pub fn to_request(")
+                .line("&self,")
+                .line("schema: Option<&::cedar_policy::Schema>,")
+                .reopen(") -> Result<::cedar_policy::Request, Box<dyn ::std::error::Error + Send + Sync>> {");
+            let uid = |field| {
+                if fields.iter().any(|(f, _)| *f == field) {
+                    format!("Some(::cedar_policy::EntityUid::from(&self.{field})),")
+                } else {
+                    "None,".to_string()
+                }
+            };
+            code.open("Ok(::cedar_policy::Request::new(")
+                .line(&uid("principal"))
+                .line(&format!(
+                    "Some(::cedar_policy::EntityUid::from(Action::{})),",
+                    variant.ident
+                ))
+                .line(&uid("resource"))
+                .line("::cedar_policy::Context::from_pairs(self.context.attributes())?,")
+                .line("schema,")
+                .close(")?)")
+                .close("}")
+                .close("}");
+            items.fill(index, code);
+        }
+        Ok(())
+    }
+}
+
+/// A variant of an enum of entity UIDs with known IDs
+#[derive(Debug)]
+struct Variant<'a> {
+    /// The Rust identifier of the variant
+    ident: String,
+    /// The entity ID
+    id: &'a str,
+    /// The `doc` annotation of the entity, if any
+    doc: Option<&'a str>,
+    /// Doc comment for the variant if there is no `doc` annotation
+    default_doc: String,
+}
+
+/// An enum `ident` of the entities of type `entity_type` with the IDs in
+/// `variants`
+fn id_enum(
+    ident: &str,
+    doc: &str,
+    entity_type: &str,
+    variants: &[Variant<'_>],
+) -> Result<Code, CodegenError> {
+    let mut variant_idents = HashSet::new();
+    for variant in variants {
+        if !variant_idents.insert(&variant.ident) {
+            return Err(CodegenError::NameCollision {
+                name: variant.ident.clone(),
+                scope: format!("enum `{ident}`"),
+            });
+        }
+    }
+    let mut code = Code::default();
+    code.doc(None, doc)
+        .line(&format!("#[derive({ID_ENUM_DERIVES})]"))
+        .open(&format!("pub enum {ident} {{"));
+    for variant in variants {
+        code.doc(variant.doc, &variant.default_doc)
+            .line(&format!("{},", variant.ident));
+    }
+    code.close("}")
+        .line("")
+        .open(&format!("impl {ident} {{"))
+        .doc(None, "The name of the entity type")
+        .line(&format!("pub const ENTITY_TYPE: &str = {entity_type:?};"))
+        .line("")
+        .doc(None, "The entity ID")
+        .open("pub fn id(self) -> &'static str {")
+        .open("match self {");
+    for variant in variants {
+        code.line(&format!("Self::{} => {:?},", variant.ident, variant.id));
+    }
+    code.close("}").close("}").close("}");
+    from_uid(&mut code, ident, "uid.id()");
+    from_owned_uid(&mut code, ident);
+    Ok(code)
+}
+
+/// A struct `ident` for the UIDs of entities of type `entity_type`
+fn uid_struct(ident: &str, entity_type: &str) -> Code {
+    let mut code = Code::default();
+    code.doc(None, &format!("The UID of a `{entity_type}` entity"))
+        .line(&format!("#[derive({UID_DERIVES})]"))
+        .line(&format!("pub struct {ident}(pub String);"))
+        .line("")
+        .open(&format!("impl {ident} {{"))
+        .doc(None, "The name of the entity type")
+        .line(&format!("pub const ENTITY_TYPE: &str = {entity_type:?};"))
+        .line("")
+        .doc(None, "The UID of the entity with ID `id`")
+        .open("pub fn new(id: impl Into<String>) -> Self {")
+        .line("Self(id.into())")
+        .close("}")
+        .close("}");
+    from_uid(&mut code, ident, "&uid.0");
+    from_owned_uid(&mut code, ident);
+    code
+}
+
+/// Add the conversion from a reference to the UID type `ident` into an
+/// `EntityUid` to `code`. `id` is an expression for the entity ID of `uid`.
+fn from_uid(code: &mut Code, ident: &str, id: &str) {
+    code.line("")
+        .open(&format!(
+            "impl From<&{ident}> for ::cedar_policy::EntityUid {{"
+        ))
+        .open(&format!("fn from(uid: &{ident}) -> Self {{"))
+        .open("Self::from_type_name_and_id(")
+        .line(&format!(
+            "{ident}::ENTITY_TYPE.parse().expect(\"entity type names are valid\"),"
+        ))
+        .line(&format!("::cedar_policy::EntityId::new({id}),"))
+        .close(")")
+        .close("}")
+        .close("}");
+}
+
+/// Add the conversion from the UID type `ident` into an `EntityUid` to
+/// `code`
+fn from_owned_uid(code: &mut Code, ident: &str) {
+    code.line("")
+        .open(&format!(
+            "impl From<{ident}> for ::cedar_policy::EntityUid {{"
+        ))
+        .open(&format!("fn from(uid: {ident}) -> Self {{"))
+        .line("Self::from(&uid)")
+        .close("}")
+        .close("}");
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cool_asserts::assert_matches;
+    use serde_json::json;
+
+    fn generate_natural(src: &str) -> Result<String, CodegenError> {
+        let (fragment, _) = cedar_policy::SchemaFragment::from_str_natural(src).unwrap();
+        crate::generate(&fragment)
+    }
+
+    #[test]
+    fn nested_namespaces() {
+        let code = generate_natural(
+            r#"
+            namespace Shared { entity Admin; }
+            namespace Org { entity User; }
+            namespace Org::Photos { entity Photo { owner: Org::User, admin: Shared::Admin }; }
+            "#,
+        )
+        .unwrap();
+        assert!(code.contains("pub mod org {"), "{code}");
+        assert!(code.contains("pub mod photos {"), "{code}");
+        assert!(
+            code.contains("pub owner: super::super::org::UserUid,"),
+            "{code}"
+        );
+        assert!(
+            code.contains("pub admin: super::super::shared::AdminUid,"),
+            "{code}"
+        );
+    }
+
+    #[test]
+    fn undeclared_type() {
+        let fragment = SchemaFragment::from_json_value(json!({
+            "": {
+                "entityTypes": {
+                    "User": {
+                        "shape": {
+                            "type": "Record",
+                            "attributes": {
+                                "manager": { "type": "Entity", "name": "Manager" },
+                            },
+                        },
+                    },
+                },
+                "actions": {},
+            },
+        }))
+        .unwrap();
+        assert_matches!(
+            generate(&fragment),
+            Err(CodegenError::UndeclaredType(name)) => assert_eq!(name, "Manager")
+        );
+        let fragment = SchemaFragment::from_json_value(json!({
+            "App": {
+                "entityTypes": {
+                    "User": {
+                        "shape": {
+                            "type": "Record",
+                            "attributes": {
+                                "home": { "type": "Address" },
+                            },
+                        },
+                    },
+                },
+                "actions": {},
+            },
+        }))
+        .unwrap();
+        assert_matches!(
+            generate(&fragment),
+            Err(CodegenError::UndeclaredType(name)) => assert_eq!(name, "App::Address")
+        );
+    }
+
+    #[test]
+    fn unsupported_extension_type() {
+        let fragment = SchemaFragment::from_json_value(json!({
+            "": {
+                "entityTypes": {
+                    "User": {
+                        "shape": {
+                            "type": "Record",
+                            "attributes": {
+                                "location": { "type": "Extension", "name": "geo" },
+                            },
+                        },
+                    },
+                },
+                "actions": {},
+            },
+        }))
+        .unwrap();
+        assert_matches!(
+            generate(&fragment),
+            Err(CodegenError::UnsupportedExtensionType(name)) => assert_eq!(name, "geo")
+        );
+    }
+
+    #[test]
+    fn name_collisions() {
+        assert_matches!(
+            generate_natural("entity User; entity user;"),
+            Err(CodegenError::NameCollision { name, .. }) => assert_eq!(name, "User")
+        );
+        assert_matches!(
+            generate_natural("entity User; entity UserUid;"),
+            Err(CodegenError::NameCollision { name, .. }) => assert_eq!(name, "UserUid")
+        );
+        assert_matches!(
+            generate_natural("entity String;"),
+            Err(CodegenError::NameCollision { name, .. }) => assert_eq!(name, "String")
+        );
+        assert_matches!(
+            generate_natural(r#"entity User { lastLogin: Long, "last login": Long };"#),
+            Err(CodegenError::NameCollision { name, scope }) => {
+                assert_eq!(name, "last_login");
+                assert_eq!(scope, "struct `User`");
+            }
+        );
+        assert_matches!(
+            generate_natural("entity User { uid: String };"),
+            Err(CodegenError::NameCollision { name, .. }) => assert_eq!(name, "uid")
+        );
+        assert_matches!(
+            generate_natural("namespace PhotoApp { entity A; } namespace photo_app { entity B; }"),
+            Err(CodegenError::NameCollision { name, .. }) => assert_eq!(name, "photo_app")
+        );
+    }
+
+    #[test]
+    fn invalid_identifiers() {
+        assert_matches!(
+            generate_natural(r#"entity User { "2fa": Bool };"#),
+            Err(CodegenError::InvalidIdentifier(name)) => assert_eq!(name, "2fa")
+        );
+        assert_matches!(
+            generate_natural(r#"action "-"; "#),
+            Err(CodegenError::InvalidIdentifier(name)) => assert_eq!(name, "-")
+        );
+    }
+}
@@ -0,0 +1,143 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Conversion of names in a Cedar schema into Rust identifiers
+
+use crate::CodegenError;
+
+/// Rust keywords which can be used as identifiers in their raw form
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+    "where", "while", "yield",
+];
+
+/// Rust keywords which can't be used as identifiers, even in their raw form
+const UNUSABLE_KEYWORDS: &[&str] = &["crate", "self", "Self", "super"];
+
+/// Split `name` into words, at non-alphanumeric characters and at changes
+/// from lowercase to uppercase, so that `lastLogin`, `last_login`, and
+/// `last login` all have the words `last` and `login`. A run of uppercase
+/// letters is one word, except for a final letter which starts a lowercase
+/// word, as in `HTTPServer`.
+fn words(name: &str) -> Result<Vec<String>, CodegenError> {
+    if !name.is_ascii() {
+        return Err(CodegenError::InvalidIdentifier(name.to_string()));
+    }
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        let starts_word = match word.chars().last() {
+            None => false,
+            Some(_) if !c.is_ascii_alphanumeric() => true,
+            Some(prev) => {
+                let next_is_lower = chars.get(i + 1).is_some_and(char::is_ascii_lowercase);
+                c.is_ascii_uppercase()
+                    && (prev.is_ascii_lowercase()
+                        || prev.is_ascii_digit()
+                        || (prev.is_ascii_uppercase() && next_is_lower))
+            }
+        };
+        if starts_word {
+            words.push(std::mem::take(&mut word));
+        }
+        if c.is_ascii_alphanumeric() {
+            word.push(c);
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    match words.first() {
+        Some(first) if !first.starts_with(|c: char| c.is_ascii_digit()) => Ok(words),
+        _ => Err(CodegenError::InvalidIdentifier(name.to_string())),
+    }
+}
+
+/// Convert `name` into an `UpperCamelCase` identifier for a type or enum
+/// variant
+pub(crate) fn type_ident(name: &str) -> Result<String, CodegenError> {
+    let ident = words(name)?
+        .iter()
+        .map(|word| {
+            let (first, rest) = word.split_at(1);
+            first.to_ascii_uppercase() + rest
+        })
+        .collect::<String>();
+    if UNUSABLE_KEYWORDS.contains(&ident.as_str()) {
+        Err(CodegenError::InvalidIdentifier(name.to_string()))
+    } else {
+        Ok(ident)
+    }
+}
+
+/// Convert `name` into a `snake_case` identifier for a field or module
+pub(crate) fn field_ident(name: &str) -> Result<String, CodegenError> {
+    let ident = words(name)?
+        .iter()
+        .map(|word| word.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+    if UNUSABLE_KEYWORDS.contains(&ident.as_str()) {
+        Err(CodegenError::InvalidIdentifier(name.to_string()))
+    } else if KEYWORDS.contains(&ident.as_str()) {
+        Ok(format!("r#{ident}"))
+    } else {
+        Ok(ident)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cool_asserts::assert_matches;
+
+    #[test]
+    fn type_idents() {
+        assert_eq!(type_ident("User").unwrap(), "User");
+        assert_eq!(type_ident("user_group").unwrap(), "UserGroup");
+        assert_eq!(type_ident("view photo").unwrap(), "ViewPhoto");
+        assert_eq!(type_ident("viewPhoto").unwrap(), "ViewPhoto");
+        assert_eq!(type_ident("HTTPServer").unwrap(), "HTTPServer");
+        assert_eq!(type_ident("read-v2").unwrap(), "ReadV2");
+        assert_matches!(
+            type_ident("self"),
+            Err(CodegenError::InvalidIdentifier(name)) => assert_eq!(name, "self")
+        );
+        assert_matches!(type_ident("2fa"), Err(CodegenError::InvalidIdentifier(_)));
+        assert_matches!(type_ident("--"), Err(CodegenError::InvalidIdentifier(_)));
+        assert_matches!(type_ident("café"), Err(CodegenError::InvalidIdentifier(_)));
+    }
+
+    #[test]
+    fn field_idents() {
+        assert_eq!(field_ident("name").unwrap(), "name");
+        assert_eq!(field_ident("lastLogin").unwrap(), "last_login");
+        assert_eq!(field_ident("last login").unwrap(), "last_login");
+        assert_eq!(field_ident("HTTPServer").unwrap(), "http_server");
+        assert_eq!(field_ident("PhotoApp").unwrap(), "photo_app");
+        assert_eq!(field_ident("ipV4").unwrap(), "ip_v4");
+        assert_eq!(field_ident("type").unwrap(), "r#type");
+        assert_matches!(
+            field_ident("super"),
+            Err(CodegenError::InvalidIdentifier(_))
+        );
+        assert_matches!(field_ident(""), Err(CodegenError::InvalidIdentifier(_)));
+    }
+}
@@ -0,0 +1,110 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Generates Rust types from a Cedar schema, so that applications can build
+//! entities and requests which are checked against the schema by the Rust
+//! compiler, rather than by entity and request validation at runtime.
+//!
+//! For each entity type `E` in the schema, the generated code declares
+//! * `EUid`, the UID of an `E` entity, which converts into an
+//!   [`EntityUid`](cedar_policy::EntityUid). For an enumerated entity type,
+//!   this is an enum of the declared entities.
+//! * `E`, a struct with the UID, parents, attributes and tags of an `E`
+//!   entity, which converts into an [`Entity`](cedar_policy::Entity).
+//! * `EParent`, the UID of a parent of an `E` entity, if `E` has parent
+//!   types.
+//!
+//! Record types of attributes become structs named after the entity type
+//! and the attribute, and common types become structs or type aliases with
+//! the name of the common type. The actions of each namespace are variants
+//! of an `Action` enum, and each action `A` which applies to some
+//! principals and resources gets an `ARequest` struct, which converts into a
+//! [`Request`](cedar_policy::Request), with its context in an `AContext`
+//! struct. The items for a namespace are declared in a module, named after
+//! the namespace in `snake_case`.
+//!
+//! Code is usually generated by a build script and included with
+//! [`include!`]:
+//!
+//! ```no_run
+//! // build.rs
+//! fn main() {
+//!     println!("cargo:rerun-if-changed=schema.cedarschema");
+//!     let out_dir = std::env::var("OUT_DIR").unwrap();
+//!     cedar_policy_codegen::generate_file("schema.cedarschema", format!("{out_dir}/schema.rs"))
+//!         .unwrap();
+//! }
+//! ```
+//!
+//! ```ignore
+//! // src/schema.rs
+//! include!(concat!(env!("OUT_DIR"), "/schema.rs"));
+//! ```
+//!
+//! The code can also be generated from a [`SchemaFragment`]:
+//!
+//! ```
+//! # use cedar_policy::SchemaFragment;
+//! let (fragment, _) = SchemaFragment::from_str_natural(
+//!     "entity User { name: String, manager?: User };",
+//! )
+//! .unwrap();
+//! let code = cedar_policy_codegen::generate(&fragment).unwrap();
+//! assert!(code.contains("pub struct User {"));
+//! assert!(code.contains("pub manager: Option<UserUid>,"));
+//! ```
+#![forbid(unsafe_code)]
+#![warn(missing_debug_implementations, rust_2018_idioms)]
+#![deny(missing_docs)]
+
+use std::path::Path;
+
+use cedar_policy::SchemaFragment;
+
+mod err;
+pub use err::*;
+mod generator;
+mod idents;
+
+/// Generate Rust code declaring types for the entity types, common types,
+/// and actions in `fragment`
+pub fn generate(fragment: &SchemaFragment) -> Result<String, CodegenError> {
+    let json = fragment.as_json_string()?;
+    let fragment = cedar_policy_validator::SchemaFragment::from_json_str(&json)
+        .map_err(cedar_policy::SchemaError::from)?;
+    generator::generate(&fragment)
+}
+
+/// Generate Rust code for the schema in `schema_file`, as [`generate`] does,
+/// and write it to `out_file`. Files with the `.cedarschema` extension are
+/// read as schemas in the natural syntax, and other files as JSON schemas.
+pub fn generate_file(
+    schema_file: impl AsRef<Path>,
+    out_file: impl AsRef<Path>,
+) -> Result<(), CodegenError> {
+    let schema_file = schema_file.as_ref();
+    let file = std::fs::File::open(schema_file)?;
+    let fragment = if schema_file
+        .extension()
+        .is_some_and(|ext| ext == "cedarschema")
+    {
+        SchemaFragment::from_file_natural(file)?.0
+    } else {
+        SchemaFragment::from_file(file)?
+    };
+    std::fs::write(out_file, generate(&fragment)?)?;
+    Ok(())
+}
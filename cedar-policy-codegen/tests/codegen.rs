@@ -0,0 +1,225 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Tests of the code generated for `data/photo_app.cedarschema`. The
+//! generated code is checked in at `generated/photo_app.rs`, so that these
+//! tests can compile and use it. Set `CEDAR_CODEGEN_BLESS=1` to regenerate it.
+
+// PANIC SAFETY tests
+#![allow(clippy::expect_used)]
+// PANIC SAFETY tests
+#![allow(clippy::unwrap_used)]
+
+use std::collections::BTreeMap;
+
+use cedar_policy::{
+    Authorizer, Decision, Entities, Entity, EntityUid, EvalResult, PolicySet, Schema,
+    SchemaFragment, ValidationMode, Validator,
+};
+
+mod generated {
+    include!("generated/photo_app.rs");
+}
+
+use generated::photo_app::*;
+
+const SCHEMA: &str = include_str!("data/photo_app.cedarschema");
+const GENERATED: &str = include_str!("generated/photo_app.rs");
+
+fn schema() -> Schema {
+    Schema::from_str_natural(SCHEMA).unwrap().0
+}
+
+fn entities() -> Vec<Entity> {
+    let alice = User {
+        uid: UserUid::new("alice"),
+        parents: vec![UserGroupUid::new("editors").into()],
+        active: true,
+        age: Some(30),
+        emails: vec!["alice@example.com".to_string()],
+        home: Address {
+            street: "1 Main St".to_string(),
+            zip: None,
+        },
+        last_login: UserLastLogin {
+            at: "2024-10-15T11:35:00Z".to_string(),
+            ip: "10.0.0.1".to_string(),
+        },
+        manager: None,
+        name: "Alice".to_string(),
+        tags: BTreeMap::from([("team".to_string(), "photos".to_string())]),
+    };
+    let bob = User {
+        uid: UserUid::new("bob"),
+        parents: vec![RoleUid::Viewer.into()],
+        active: false,
+        age: None,
+        emails: Vec::new(),
+        home: Address {
+            street: "2 Main St".to_string(),
+            zip: Some("12345".to_string()),
+        },
+        last_login: UserLastLogin {
+            at: "2024-10-14".to_string(),
+            ip: "10.0.0.2".to_string(),
+        },
+        manager: Some(alice.uid.clone()),
+        name: "Bob".to_string(),
+        tags: BTreeMap::new(),
+    };
+    let editors = UserGroup {
+        uid: UserGroupUid::new("editors"),
+        parents: vec![RoleUid::Admin.into()],
+    };
+    let album = Album {
+        uid: AlbumUid::new("vacation"),
+    };
+    let photo = Photo {
+        uid: PhotoUid::new("beach"),
+        parents: vec![album.uid.clone().into()],
+        owner: alice.uid.clone(),
+        size: "1.5".to_string(),
+        viewers: vec![bob.uid.clone()],
+    };
+    vec![
+        Entity::try_from(&alice).unwrap(),
+        Entity::try_from(bob).unwrap(),
+        Entity::try_from(editors).unwrap(),
+        Entity::try_from(album).unwrap(),
+        Entity::try_from(photo).unwrap(),
+    ]
+}
+
+#[test]
+fn generated_code_is_up_to_date() {
+    let (fragment, _) = SchemaFragment::from_str_natural(SCHEMA).unwrap();
+    let code = cedar_policy_codegen::generate(&fragment).unwrap();
+    if std::env::var_os("CEDAR_CODEGEN_BLESS").is_some() {
+        std::fs::write(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/generated/photo_app.rs"),
+            code,
+        )
+        .unwrap();
+    } else {
+        assert!(
+            code == GENERATED,
+            "`tests/generated/photo_app.rs` is out of date; rerun the tests with `CEDAR_CODEGEN_BLESS=1`"
+        );
+    }
+}
+
+#[test]
+fn uids() {
+    assert_eq!(
+        EntityUid::from(UserUid::new("alice")).to_string(),
+        r#"PhotoApp::User::"alice""#
+    );
+    assert_eq!(
+        EntityUid::from(RoleUid::Admin).to_string(),
+        r#"PhotoApp::Role::"admin""#
+    );
+    assert_eq!(
+        EntityUid::from(Action::ViewPhoto).to_string(),
+        r#"PhotoApp::Action::"view photo""#
+    );
+    assert_eq!(
+        EntityUid::from(UserParent::from(RoleUid::Viewer)),
+        EntityUid::from(RoleUid::Viewer)
+    );
+}
+
+#[test]
+fn entities_conform_to_schema() {
+    let entities = Entities::from_entities(entities(), Some(&schema())).unwrap();
+    let alice = entities.get(&UserUid::new("alice").into()).unwrap();
+    assert_eq!(
+        alice.attr("name").unwrap().unwrap(),
+        EvalResult::String("Alice".to_string())
+    );
+    assert!(alice.attr("age").is_some());
+    assert!(alice.attr("manager").is_none());
+    assert_eq!(
+        alice.tag("team").unwrap().unwrap(),
+        EvalResult::String("photos".to_string())
+    );
+    let bob = entities.get(&UserUid::new("bob").into()).unwrap();
+    assert_eq!(
+        bob.attr("manager").unwrap().unwrap(),
+        EvalResult::EntityUid(UserUid::new("alice").into())
+    );
+}
+
+#[test]
+fn requests_are_authorized() {
+    let schema = schema();
+    let policies: PolicySet = r#"
+        permit(principal, action == PhotoApp::Action::"view photo", resource)
+        when { resource.owner == principal && context.mfa };
+        permit(principal in PhotoApp::Role::"admin", action == PhotoApp::Action::"share", resource);
+    "#
+    .parse()
+    .unwrap();
+    assert!(Validator::new(schema.clone())
+        .validate(&policies, ValidationMode::Strict)
+        .validation_passed());
+    let entities = Entities::from_entities(entities(), Some(&schema)).unwrap();
+    let authorizer = Authorizer::new();
+    let decision = |request: cedar_policy::Request| {
+        authorizer
+            .is_authorized(&request, &policies, &entities)
+            .decision()
+    };
+
+    let view = ViewPhotoRequest {
+        principal: UserUid::new("alice"),
+        resource: PhotoUid::new("beach"),
+        context: ViewPhotoContext {
+            ip: "10.0.0.1".to_string(),
+            mfa: true,
+            reason: None,
+        },
+    };
+    assert_eq!(
+        decision(view.to_request(Some(&schema)).unwrap()),
+        Decision::Allow
+    );
+    let view = ViewPhotoRequest {
+        principal: UserUid::new("bob"),
+        ..view
+    };
+    assert_eq!(
+        decision(view.to_request(Some(&schema)).unwrap()),
+        Decision::Deny
+    );
+
+    let share = ShareRequest {
+        principal: UserGroupUid::new("editors").into(),
+        resource: AlbumUid::new("vacation").into(),
+        context: ShareContext {},
+    };
+    assert_eq!(
+        decision(share.to_request(Some(&schema)).unwrap()),
+        Decision::Allow
+    );
+    let share = ShareRequest {
+        principal: UserUid::new("bob").into(),
+        ..share
+    };
+    assert_eq!(
+        decision(share.to_request(Some(&schema)).unwrap()),
+        Decision::Deny
+    );
+}
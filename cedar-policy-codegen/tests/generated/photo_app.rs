@@ -0,0 +1,730 @@
+// @generated by cedar-policy-codegen. Do not edit this file by hand.
+
+/// A photo sharing application
+pub mod photo_app {
+    /// The `PhotoApp::Address` common type
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Address {
+        /// The `street` attribute
+        pub street: String,
+        /// The `zip` attribute
+        pub zip: Option<String>,
+    }
+
+    impl Address {
+        /// The attributes of this record
+        pub fn attributes(&self) -> Vec<(String, ::cedar_policy::RestrictedExpression)> {
+            let mut attributes = vec![
+                ("street".to_string(), ::cedar_policy::RestrictedExpression::new_string(self.street.clone())),
+            ];
+            if let Some(value) = &self.zip {
+                attributes.push(("zip".to_string(), ::cedar_policy::RestrictedExpression::new_string(value.clone())));
+            }
+            attributes
+        }
+    }
+
+    impl From<&Address> for ::cedar_policy::RestrictedExpression {
+        fn from(record: &Address) -> Self {
+            Self::new_record(record.attributes()).expect("attribute names are distinct")
+        }
+    }
+
+    /// A `PhotoApp::Album` entity
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Album {
+        /// The UID of this entity
+        pub uid: AlbumUid,
+    }
+
+    impl Album {
+        /// The attributes of this entity
+        pub fn attributes(&self) -> Vec<(String, ::cedar_policy::RestrictedExpression)> {
+            Vec::new()
+        }
+    }
+
+    impl TryFrom<&Album> for ::cedar_policy::Entity {
+        type Error = ::cedar_policy::EntityAttrEvaluationError;
+
+        fn try_from(entity: &Album) -> Result<Self, Self::Error> {
+            Self::new(
+                ::cedar_policy::EntityUid::from(&entity.uid),
+                entity.attributes().into_iter().collect(),
+                ::std::collections::HashSet::new(),
+            )
+        }
+    }
+
+    impl TryFrom<Album> for ::cedar_policy::Entity {
+        type Error = ::cedar_policy::EntityAttrEvaluationError;
+
+        fn try_from(entity: Album) -> Result<Self, Self::Error> {
+            Self::try_from(&entity)
+        }
+    }
+
+    /// The UID of a `PhotoApp::Album` entity
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct AlbumUid(pub String);
+
+    impl AlbumUid {
+        /// The name of the entity type
+        pub const ENTITY_TYPE: &str = "PhotoApp::Album";
+
+        /// The UID of the entity with ID `id`
+        pub fn new(id: impl Into<String>) -> Self {
+            Self(id.into())
+        }
+    }
+
+    impl From<&AlbumUid> for ::cedar_policy::EntityUid {
+        fn from(uid: &AlbumUid) -> Self {
+            Self::from_type_name_and_id(
+                AlbumUid::ENTITY_TYPE.parse().expect("entity type names are valid"),
+                ::cedar_policy::EntityId::new(&uid.0),
+            )
+        }
+    }
+
+    impl From<AlbumUid> for ::cedar_policy::EntityUid {
+        fn from(uid: AlbumUid) -> Self {
+            Self::from(&uid)
+        }
+    }
+
+    /// A `PhotoApp::Photo` entity
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Photo {
+        /// The UID of this entity
+        pub uid: PhotoUid,
+        /// The parents of this entity
+        pub parents: Vec<PhotoParent>,
+        /// The `owner` attribute
+        pub owner: UserUid,
+        /// The `size` attribute
+        pub size: String,
+        /// The `viewers` attribute
+        pub viewers: Vec<UserUid>,
+    }
+
+    impl Photo {
+        /// The attributes of this entity
+        pub fn attributes(&self) -> Vec<(String, ::cedar_policy::RestrictedExpression)> {
+            vec![
+                ("owner".to_string(), ::cedar_policy::RestrictedExpression::new_entity_uid(::cedar_policy::EntityUid::from(&self.owner))),
+                ("size".to_string(), ::cedar_policy::RestrictedExpression::new_decimal(&self.size)),
+                ("viewers".to_string(), ::cedar_policy::RestrictedExpression::new_set(self.viewers.iter().map(|v| ::cedar_policy::RestrictedExpression::new_entity_uid(::cedar_policy::EntityUid::from(v))))),
+            ]
+        }
+    }
+
+    impl TryFrom<&Photo> for ::cedar_policy::Entity {
+        type Error = ::cedar_policy::EntityAttrEvaluationError;
+
+        fn try_from(entity: &Photo) -> Result<Self, Self::Error> {
+            Self::new(
+                ::cedar_policy::EntityUid::from(&entity.uid),
+                entity.attributes().into_iter().collect(),
+                entity.parents.iter().map(::cedar_policy::EntityUid::from).collect(),
+            )
+        }
+    }
+
+    impl TryFrom<Photo> for ::cedar_policy::Entity {
+        type Error = ::cedar_policy::EntityAttrEvaluationError;
+
+        fn try_from(entity: Photo) -> Result<Self, Self::Error> {
+            Self::try_from(&entity)
+        }
+    }
+
+    /// The UID of a `PhotoApp::Photo` entity
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct PhotoUid(pub String);
+
+    impl PhotoUid {
+        /// The name of the entity type
+        pub const ENTITY_TYPE: &str = "PhotoApp::Photo";
+
+        /// The UID of the entity with ID `id`
+        pub fn new(id: impl Into<String>) -> Self {
+            Self(id.into())
+        }
+    }
+
+    impl From<&PhotoUid> for ::cedar_policy::EntityUid {
+        fn from(uid: &PhotoUid) -> Self {
+            Self::from_type_name_and_id(
+                PhotoUid::ENTITY_TYPE.parse().expect("entity type names are valid"),
+                ::cedar_policy::EntityId::new(&uid.0),
+            )
+        }
+    }
+
+    impl From<PhotoUid> for ::cedar_policy::EntityUid {
+        fn from(uid: PhotoUid) -> Self {
+            Self::from(&uid)
+        }
+    }
+
+    /// The UID of a parent of a `PhotoApp::Photo` entity
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub enum PhotoParent {
+        /// The UID of a `PhotoApp::Album` entity
+        Album(AlbumUid),
+    }
+
+    impl From<AlbumUid> for PhotoParent {
+        fn from(uid: AlbumUid) -> Self {
+            Self::Album(uid)
+        }
+    }
+
+    impl From<&PhotoParent> for ::cedar_policy::EntityUid {
+        fn from(uid: &PhotoParent) -> Self {
+            match uid {
+                PhotoParent::Album(uid) => Self::from(uid),
+            }
+        }
+    }
+
+    impl From<PhotoParent> for ::cedar_policy::EntityUid {
+        fn from(uid: PhotoParent) -> Self {
+            Self::from(&uid)
+        }
+    }
+
+    /// A `PhotoApp::Role` entity
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Role {
+        /// The UID of this entity
+        pub uid: RoleUid,
+    }
+
+    impl Role {
+        /// The attributes of this entity
+        pub fn attributes(&self) -> Vec<(String, ::cedar_policy::RestrictedExpression)> {
+            Vec::new()
+        }
+    }
+
+    impl TryFrom<&Role> for ::cedar_policy::Entity {
+        type Error = ::cedar_policy::EntityAttrEvaluationError;
+
+        fn try_from(entity: &Role) -> Result<Self, Self::Error> {
+            Self::new(
+                ::cedar_policy::EntityUid::from(&entity.uid),
+                entity.attributes().into_iter().collect(),
+                ::std::collections::HashSet::new(),
+            )
+        }
+    }
+
+    impl TryFrom<Role> for ::cedar_policy::Entity {
+        type Error = ::cedar_policy::EntityAttrEvaluationError;
+
+        fn try_from(entity: Role) -> Result<Self, Self::Error> {
+            Self::try_from(&entity)
+        }
+    }
+
+    /// The UID of a `PhotoApp::Role` entity. `PhotoApp::Role` is an enumerated entity type, so these are its only entities.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub enum RoleUid {
+        /// `PhotoApp::Role::"admin"`
+        Admin,
+        /// `PhotoApp::Role::"viewer"`
+        Viewer,
+    }
+
+    impl RoleUid {
+        /// The name of the entity type
+        pub const ENTITY_TYPE: &str = "PhotoApp::Role";
+
+        /// The entity ID
+        pub fn id(self) -> &'static str {
+            match self {
+                Self::Admin => "admin",
+                Self::Viewer => "viewer",
+            }
+        }
+    }
+
+    impl From<&RoleUid> for ::cedar_policy::EntityUid {
+        fn from(uid: &RoleUid) -> Self {
+            Self::from_type_name_and_id(
+                RoleUid::ENTITY_TYPE.parse().expect("entity type names are valid"),
+                ::cedar_policy::EntityId::new(uid.id()),
+            )
+        }
+    }
+
+    impl From<RoleUid> for ::cedar_policy::EntityUid {
+        fn from(uid: RoleUid) -> Self {
+            Self::from(&uid)
+        }
+    }
+
+    /// A user of the application
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct User {
+        /// The UID of this entity
+        pub uid: UserUid,
+        /// The parents of this entity
+        pub parents: Vec<UserParent>,
+        /// The `active` attribute
+        pub active: bool,
+        /// Age in years
+        pub age: Option<i64>,
+        /// The `emails` attribute
+        pub emails: Vec<String>,
+        /// The `home` attribute
+        pub home: Address,
+        /// The `last login` attribute
+        pub last_login: UserLastLogin,
+        /// The `manager` attribute
+        pub manager: Option<UserUid>,
+        /// The `name` attribute
+        pub name: String,
+        /// The tags of this entity
+        pub tags: ::std::collections::BTreeMap<String, String>,
+    }
+
+    impl User {
+        /// The attributes of this entity
+        pub fn attributes(&self) -> Vec<(String, ::cedar_policy::RestrictedExpression)> {
+            let mut attributes = vec![
+                ("active".to_string(), ::cedar_policy::RestrictedExpression::new_bool(self.active)),
+                ("emails".to_string(), ::cedar_policy::RestrictedExpression::new_set(self.emails.iter().map(|v| ::cedar_policy::RestrictedExpression::new_string(v.clone())))),
+                ("home".to_string(), ::cedar_policy::RestrictedExpression::from(&self.home)),
+                ("last login".to_string(), ::cedar_policy::RestrictedExpression::from(&self.last_login)),
+                ("name".to_string(), ::cedar_policy::RestrictedExpression::new_string(self.name.clone())),
+            ];
+            if let Some(value) = &self.age {
+                attributes.push(("age".to_string(), ::cedar_policy::RestrictedExpression::new_long(*value)));
+            }
+            if let Some(value) = &self.manager {
+                attributes.push(("manager".to_string(), ::cedar_policy::RestrictedExpression::new_entity_uid(::cedar_policy::EntityUid::from(value))));
+            }
+            attributes
+        }
+    }
+
+    impl TryFrom<&User> for ::cedar_policy::Entity {
+        type Error = ::cedar_policy::EntityAttrEvaluationError;
+
+        fn try_from(entity: &User) -> Result<Self, Self::Error> {
+            Self::new_with_tags(
+                ::cedar_policy::EntityUid::from(&entity.uid),
+                entity.attributes().into_iter().collect(),
+                entity.parents.iter().map(::cedar_policy::EntityUid::from).collect(),
+                entity.tags.iter().map(|(key, v)| (key.clone(), ::cedar_policy::RestrictedExpression::new_string(v.clone()))).collect(),
+            )
+        }
+    }
+
+    impl TryFrom<User> for ::cedar_policy::Entity {
+        type Error = ::cedar_policy::EntityAttrEvaluationError;
+
+        fn try_from(entity: User) -> Result<Self, Self::Error> {
+            Self::try_from(&entity)
+        }
+    }
+
+    /// The UID of a `PhotoApp::User` entity
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct UserUid(pub String);
+
+    impl UserUid {
+        /// The name of the entity type
+        pub const ENTITY_TYPE: &str = "PhotoApp::User";
+
+        /// The UID of the entity with ID `id`
+        pub fn new(id: impl Into<String>) -> Self {
+            Self(id.into())
+        }
+    }
+
+    impl From<&UserUid> for ::cedar_policy::EntityUid {
+        fn from(uid: &UserUid) -> Self {
+            Self::from_type_name_and_id(
+                UserUid::ENTITY_TYPE.parse().expect("entity type names are valid"),
+                ::cedar_policy::EntityId::new(&uid.0),
+            )
+        }
+    }
+
+    impl From<UserUid> for ::cedar_policy::EntityUid {
+        fn from(uid: UserUid) -> Self {
+            Self::from(&uid)
+        }
+    }
+
+    /// The UID of a parent of a `PhotoApp::User` entity
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub enum UserParent {
+        /// The UID of a `PhotoApp::UserGroup` entity
+        UserGroup(UserGroupUid),
+        /// The UID of a `PhotoApp::Role` entity
+        Role(RoleUid),
+    }
+
+    impl From<UserGroupUid> for UserParent {
+        fn from(uid: UserGroupUid) -> Self {
+            Self::UserGroup(uid)
+        }
+    }
+
+    impl From<RoleUid> for UserParent {
+        fn from(uid: RoleUid) -> Self {
+            Self::Role(uid)
+        }
+    }
+
+    impl From<&UserParent> for ::cedar_policy::EntityUid {
+        fn from(uid: &UserParent) -> Self {
+            match uid {
+                UserParent::UserGroup(uid) => Self::from(uid),
+                UserParent::Role(uid) => Self::from(uid),
+            }
+        }
+    }
+
+    impl From<UserParent> for ::cedar_policy::EntityUid {
+        fn from(uid: UserParent) -> Self {
+            Self::from(&uid)
+        }
+    }
+
+    /// A value of the `last login` attribute of `PhotoApp::User` entities
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct UserLastLogin {
+        /// The `at` attribute
+        pub at: String,
+        /// The `ip` attribute
+        pub ip: String,
+    }
+
+    impl UserLastLogin {
+        /// The attributes of this record
+        pub fn attributes(&self) -> Vec<(String, ::cedar_policy::RestrictedExpression)> {
+            vec![
+                ("at".to_string(), ::cedar_policy::RestrictedExpression::new_datetime(&self.at)),
+                ("ip".to_string(), ::cedar_policy::RestrictedExpression::new_ip(&self.ip)),
+            ]
+        }
+    }
+
+    impl From<&UserLastLogin> for ::cedar_policy::RestrictedExpression {
+        fn from(record: &UserLastLogin) -> Self {
+            Self::new_record(record.attributes()).expect("attribute names are distinct")
+        }
+    }
+
+    /// A `PhotoApp::UserGroup` entity
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct UserGroup {
+        /// The UID of this entity
+        pub uid: UserGroupUid,
+        /// The parents of this entity
+        pub parents: Vec<UserGroupParent>,
+    }
+
+    impl UserGroup {
+        /// The attributes of this entity
+        pub fn attributes(&self) -> Vec<(String, ::cedar_policy::RestrictedExpression)> {
+            Vec::new()
+        }
+    }
+
+    impl TryFrom<&UserGroup> for ::cedar_policy::Entity {
+        type Error = ::cedar_policy::EntityAttrEvaluationError;
+
+        fn try_from(entity: &UserGroup) -> Result<Self, Self::Error> {
+            Self::new(
+                ::cedar_policy::EntityUid::from(&entity.uid),
+                entity.attributes().into_iter().collect(),
+                entity.parents.iter().map(::cedar_policy::EntityUid::from).collect(),
+            )
+        }
+    }
+
+    impl TryFrom<UserGroup> for ::cedar_policy::Entity {
+        type Error = ::cedar_policy::EntityAttrEvaluationError;
+
+        fn try_from(entity: UserGroup) -> Result<Self, Self::Error> {
+            Self::try_from(&entity)
+        }
+    }
+
+    /// The UID of a `PhotoApp::UserGroup` entity
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct UserGroupUid(pub String);
+
+    impl UserGroupUid {
+        /// The name of the entity type
+        pub const ENTITY_TYPE: &str = "PhotoApp::UserGroup";
+
+        /// The UID of the entity with ID `id`
+        pub fn new(id: impl Into<String>) -> Self {
+            Self(id.into())
+        }
+    }
+
+    impl From<&UserGroupUid> for ::cedar_policy::EntityUid {
+        fn from(uid: &UserGroupUid) -> Self {
+            Self::from_type_name_and_id(
+                UserGroupUid::ENTITY_TYPE.parse().expect("entity type names are valid"),
+                ::cedar_policy::EntityId::new(&uid.0),
+            )
+        }
+    }
+
+    impl From<UserGroupUid> for ::cedar_policy::EntityUid {
+        fn from(uid: UserGroupUid) -> Self {
+            Self::from(&uid)
+        }
+    }
+
+    /// The UID of a parent of a `PhotoApp::UserGroup` entity
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub enum UserGroupParent {
+        /// The UID of a `PhotoApp::Role` entity
+        Role(RoleUid),
+    }
+
+    impl From<RoleUid> for UserGroupParent {
+        fn from(uid: RoleUid) -> Self {
+            Self::Role(uid)
+        }
+    }
+
+    impl From<&UserGroupParent> for ::cedar_policy::EntityUid {
+        fn from(uid: &UserGroupParent) -> Self {
+            match uid {
+                UserGroupParent::Role(uid) => Self::from(uid),
+            }
+        }
+    }
+
+    impl From<UserGroupParent> for ::cedar_policy::EntityUid {
+        fn from(uid: UserGroupParent) -> Self {
+            Self::from(&uid)
+        }
+    }
+
+    /// The actions declared in this namespace
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub enum Action {
+        /// `PhotoApp::Action::"audit"`
+        Audit,
+        /// `PhotoApp::Action::"share"`
+        Share,
+        /// `PhotoApp::Action::"view photo"`
+        ViewPhoto,
+    }
+
+    impl Action {
+        /// The name of the entity type
+        pub const ENTITY_TYPE: &str = "PhotoApp::Action";
+
+        /// The entity ID
+        pub fn id(self) -> &'static str {
+            match self {
+                Self::Audit => "audit",
+                Self::Share => "share",
+                Self::ViewPhoto => "view photo",
+            }
+        }
+    }
+
+    impl From<&Action> for ::cedar_policy::EntityUid {
+        fn from(uid: &Action) -> Self {
+            Self::from_type_name_and_id(
+                Action::ENTITY_TYPE.parse().expect("entity type names are valid"),
+                ::cedar_policy::EntityId::new(uid.id()),
+            )
+        }
+    }
+
+    impl From<Action> for ::cedar_policy::EntityUid {
+        fn from(uid: Action) -> Self {
+            Self::from(&uid)
+        }
+    }
+
+    /// A request to perform `PhotoApp::Action::"share"`
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct ShareRequest {
+        /// The principal of the request
+        pub principal: SharePrincipal,
+        /// The resource of the request
+        pub resource: ShareResource,
+        /// The context of the request
+        pub context: ShareContext,
+    }
+
+    impl ShareRequest {
+        /// Convert this into a Cedar request, which is validated against `schema` if one is given
+        pub fn to_request(
+            &self,
+            schema: Option<&::cedar_policy::Schema>,
+        ) -> Result<::cedar_policy::Request, Box<dyn ::std::error::Error + Send + Sync>> {
+            Ok(::cedar_policy::Request::new(
+                Some(::cedar_policy::EntityUid::from(&self.principal)),
+                Some(::cedar_policy::EntityUid::from(Action::Share)),
+                Some(::cedar_policy::EntityUid::from(&self.resource)),
+                ::cedar_policy::Context::from_pairs(self.context.attributes())?,
+                schema,
+            )?)
+        }
+    }
+
+    /// The UID of a principal of a request to perform `PhotoApp::Action::"share"`
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub enum SharePrincipal {
+        /// The UID of a `PhotoApp::User` entity
+        User(UserUid),
+        /// The UID of a `PhotoApp::UserGroup` entity
+        UserGroup(UserGroupUid),
+    }
+
+    impl From<UserUid> for SharePrincipal {
+        fn from(uid: UserUid) -> Self {
+            Self::User(uid)
+        }
+    }
+
+    impl From<UserGroupUid> for SharePrincipal {
+        fn from(uid: UserGroupUid) -> Self {
+            Self::UserGroup(uid)
+        }
+    }
+
+    impl From<&SharePrincipal> for ::cedar_policy::EntityUid {
+        fn from(uid: &SharePrincipal) -> Self {
+            match uid {
+                SharePrincipal::User(uid) => Self::from(uid),
+                SharePrincipal::UserGroup(uid) => Self::from(uid),
+            }
+        }
+    }
+
+    impl From<SharePrincipal> for ::cedar_policy::EntityUid {
+        fn from(uid: SharePrincipal) -> Self {
+            Self::from(&uid)
+        }
+    }
+
+    /// The UID of a resource of a request to perform `PhotoApp::Action::"share"`
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub enum ShareResource {
+        /// The UID of a `PhotoApp::Photo` entity
+        Photo(PhotoUid),
+        /// The UID of a `PhotoApp::Album` entity
+        Album(AlbumUid),
+    }
+
+    impl From<PhotoUid> for ShareResource {
+        fn from(uid: PhotoUid) -> Self {
+            Self::Photo(uid)
+        }
+    }
+
+    impl From<AlbumUid> for ShareResource {
+        fn from(uid: AlbumUid) -> Self {
+            Self::Album(uid)
+        }
+    }
+
+    impl From<&ShareResource> for ::cedar_policy::EntityUid {
+        fn from(uid: &ShareResource) -> Self {
+            match uid {
+                ShareResource::Photo(uid) => Self::from(uid),
+                ShareResource::Album(uid) => Self::from(uid),
+            }
+        }
+    }
+
+    impl From<ShareResource> for ::cedar_policy::EntityUid {
+        fn from(uid: ShareResource) -> Self {
+            Self::from(&uid)
+        }
+    }
+
+    /// The context of a request to perform `PhotoApp::Action::"share"`
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct ShareContext {}
+
+    impl ShareContext {
+        /// The attributes of this record
+        pub fn attributes(&self) -> Vec<(String, ::cedar_policy::RestrictedExpression)> {
+            Vec::new()
+        }
+    }
+
+    impl From<&ShareContext> for ::cedar_policy::RestrictedExpression {
+        fn from(record: &ShareContext) -> Self {
+            Self::new_record(record.attributes()).expect("attribute names are distinct")
+        }
+    }
+
+    /// A request to perform `PhotoApp::Action::"view photo"`
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct ViewPhotoRequest {
+        /// The principal of the request
+        pub principal: UserUid,
+        /// The resource of the request
+        pub resource: PhotoUid,
+        /// The context of the request
+        pub context: ViewPhotoContext,
+    }
+
+    impl ViewPhotoRequest {
+        /// Convert this into a Cedar request, which is validated against `schema` if one is given
+        pub fn to_request(
+            &self,
+            schema: Option<&::cedar_policy::Schema>,
+        ) -> Result<::cedar_policy::Request, Box<dyn ::std::error::Error + Send + Sync>> {
+            Ok(::cedar_policy::Request::new(
+                Some(::cedar_policy::EntityUid::from(&self.principal)),
+                Some(::cedar_policy::EntityUid::from(Action::ViewPhoto)),
+                Some(::cedar_policy::EntityUid::from(&self.resource)),
+                ::cedar_policy::Context::from_pairs(self.context.attributes())?,
+                schema,
+            )?)
+        }
+    }
+
+    /// The context of a request to perform `PhotoApp::Action::"view photo"`
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct ViewPhotoContext {
+        /// The `ip` attribute
+        pub ip: String,
+        /// The `mfa` attribute
+        pub mfa: bool,
+        /// The `reason` attribute
+        pub reason: Option<String>,
+    }
+
+    impl ViewPhotoContext {
+        /// The attributes of this record
+        pub fn attributes(&self) -> Vec<(String, ::cedar_policy::RestrictedExpression)> {
+            let mut attributes = vec![
+                ("ip".to_string(), ::cedar_policy::RestrictedExpression::new_ip(&self.ip)),
+                ("mfa".to_string(), ::cedar_policy::RestrictedExpression::new_bool(self.mfa)),
+            ];
+            if let Some(value) = &self.reason {
+                attributes.push(("reason".to_string(), ::cedar_policy::RestrictedExpression::new_string(value.clone())));
+            }
+            attributes
+        }
+    }
+
+    impl From<&ViewPhotoContext> for ::cedar_policy::RestrictedExpression {
+        fn from(record: &ViewPhotoContext) -> Self {
+            Self::new_record(record.attributes()).expect("attribute names are distinct")
+        }
+    }
+}
@@ -50,3 +50,4 @@ mod value;
 pub use value::*;
 mod expr_iterator;
 pub use expr_iterator::*;
+mod simplify;
@@ -278,38 +278,67 @@ pub struct Entity {
     /// Set of ancestors of this `Entity` (i.e., all direct and transitive
     /// parents), as UIDs
     ancestors: HashSet<EntityUID>,
+
+    /// Set of parents this `Entity` was declared with, as UIDs. `ancestors`
+    /// is the transitive closure of these, and is what evaluation uses; the
+    /// declared parents are kept so the closure can be recomputed when the
+    /// hierarchy around this entity changes.
+    #[serde(skip)]
+    parents: HashSet<EntityUID>,
+
+    /// For partial evaluation, an unknown standing for the set of any
+    /// ancestors this `Entity` has beyond `ancestors`. `None` if `ancestors`
+    /// is complete.
+    #[serde(skip)]
+    unknown_ancestors: Option<Unknown>,
+
+    /// Internal HashMap of tags.
+    ///
+    /// Unlike attributes, tag keys are not declared in the schema; only the
+    /// type of tag values is. Tag values are serialized the same way as
+    /// attribute values.
+    tags: HashMap<SmolStr, PartialValueSerializedAsExpr>,
 }
 
 impl Entity {
-    /// Create a new `Entity` with this UID, attributes, and ancestors
+    /// Create a new `Entity` with this UID, attributes, ancestors, and tags
     pub fn new(
         uid: EntityUID,
         attrs: HashMap<SmolStr, RestrictedExpr>,
         ancestors: HashSet<EntityUID>,
+        tags: HashMap<SmolStr, RestrictedExpr>,
         extensions: &Extensions<'_>,
     ) -> Result<Self, EntityAttrEvaluationError> {
         let evaluator = RestrictedEvaluator::new(extensions);
-        let evaluated_attrs = attrs
-            .into_iter()
-            .map(|(k, v)| {
-                let attr_val = evaluator
-                    .partial_interpret(v.as_borrowed())
-                    .map_err(|err| EntityAttrEvaluationError {
-                        uid: uid.clone(),
-                        attr: k.clone(),
-                        err,
-                    })?;
-                Ok((k, attr_val.into()))
-            })
-            .collect::<Result<_, EntityAttrEvaluationError>>()?;
+        let evaluate = |map: HashMap<SmolStr, RestrictedExpr>, was_attr: bool| {
+            map.into_iter()
+                .map(|(k, v)| {
+                    let val = evaluator
+                        .partial_interpret(v.as_borrowed())
+                        .map_err(|err| EntityAttrEvaluationError {
+                            uid: uid.clone(),
+                            attr_or_tag: k.clone(),
+                            was_attr,
+                            err,
+                        })?;
+                    Ok((k, val.into()))
+                })
+                .collect::<Result<HashMap<_, _>, EntityAttrEvaluationError>>()
+        };
+        let evaluated_attrs = evaluate(attrs, true)?;
+        let evaluated_tags = evaluate(tags, false)?;
         Ok(Entity {
             uid,
             attrs: evaluated_attrs,
+            parents: ancestors.clone(),
             ancestors,
+            unknown_ancestors: None,
+            tags: evaluated_tags,
         })
     }
 
-    /// Create a new `Entity` with this UID, attributes, and ancestors.
+    /// Create a new `Entity` with this UID, attributes, and ancestors, and no
+    /// tags.
     ///
     /// Unlike in `Entity::new()`, in this constructor, attributes are expressed
     /// as `PartialValue`.
@@ -321,11 +350,35 @@ impl Entity {
         Entity {
             uid,
             attrs: attrs.into_iter().map(|(k, v)| (k, v.into())).collect(), // TODO(#540): can we do this without disassembling and reassembling the HashMap
+            parents: ancestors.clone(),
             ancestors,
+            unknown_ancestors: None,
+            tags: HashMap::new(),
         }
     }
 
-    /// Create a new `Entity` with this UID, attributes, and ancestors.
+    /// Create a new `Entity` with this UID, attributes, ancestors, and tags.
+    ///
+    /// Unlike in `Entity::new()`, in this constructor, attributes and tags are
+    /// expressed as `PartialValue`.
+    pub fn new_with_partial_values(
+        uid: EntityUID,
+        attrs: HashMap<SmolStr, PartialValue>,
+        ancestors: HashSet<EntityUID>,
+        tags: HashMap<SmolStr, PartialValue>,
+    ) -> Self {
+        Entity {
+            uid,
+            attrs: attrs.into_iter().map(|(k, v)| (k, v.into())).collect(),
+            parents: ancestors.clone(),
+            ancestors,
+            unknown_ancestors: None,
+            tags: tags.into_iter().map(|(k, v)| (k, v.into())).collect(),
+        }
+    }
+
+    /// Create a new `Entity` with this UID, attributes, and ancestors, and no
+    /// tags.
     ///
     /// Unlike in `Entity::new()`, in this constructor, attributes are expressed
     /// as `PartialValueSerializedAsExpr`.
@@ -337,7 +390,10 @@ impl Entity {
         Entity {
             uid,
             attrs,
+            parents: ancestors.clone(),
             ancestors,
+            unknown_ancestors: None,
+            tags: HashMap::new(),
         }
     }
 
@@ -351,6 +407,11 @@ impl Entity {
         self.attrs.get(attr).map(|v| v.as_ref())
     }
 
+    /// Get the value for the given tag, or `None` if not present
+    pub fn get_tag(&self, tag: &str) -> Option<&PartialValue> {
+        self.tags.get(tag).map(|v| v.as_ref())
+    }
+
     /// Is this `Entity` a descendant of `e` in the entity hierarchy?
     pub fn is_descendant_of(&self, e: &EntityUID) -> bool {
         self.ancestors.contains(e)
@@ -361,25 +422,68 @@ impl Entity {
         self.ancestors.iter()
     }
 
+    /// Get the unknown standing for any ancestors of this entity beyond
+    /// [`Entity::ancestors()`], if its ancestors are only partially known
+    pub fn unknown_ancestors(&self) -> Option<&Unknown> {
+        self.unknown_ancestors.as_ref()
+    }
+
+    /// Mark the ancestors of this entity as only partially known, for partial
+    /// evaluation. Hierarchy membership tests that aren't decided by the known
+    /// ancestors will produce residuals over an unknown named `name`, which
+    /// should be filled in with the set of the remaining ancestors.
+    pub fn with_unknown_ancestors(self, name: impl Into<SmolStr>) -> Self {
+        Self {
+            unknown_ancestors: Some(Unknown::new_with_type(name, Type::Set)),
+            ..self
+        }
+    }
+
+    /// Set or clear the unknown standing for the ancestors of this entity
+    /// beyond [`Entity::ancestors()`]
+    #[cfg(feature = "partial-eval")]
+    pub(crate) fn set_unknown_ancestors(&mut self, unknown: Option<Unknown>) {
+        self.unknown_ancestors = unknown;
+    }
+
+    /// Iterate over the parents this entity was declared with. Every parent
+    /// is also an ancestor.
+    pub fn parents(&self) -> impl Iterator<Item = &EntityUID> {
+        self.parents.iter()
+    }
+
     /// Iterate over this entity's attributes
     pub fn attrs(&self) -> impl Iterator<Item = (&SmolStr, &PartialValue)> {
         self.attrs.iter().map(|(k, v)| (k, v.as_ref()))
     }
 
-    /// Create an `Entity` with the given UID, no attributes, and no parents.
+    /// Iterate over this entity's tags
+    pub fn tags(&self) -> impl Iterator<Item = (&SmolStr, &PartialValue)> {
+        self.tags.iter().map(|(k, v)| (k, v.as_ref()))
+    }
+
+    /// Create an `Entity` with the given UID, no attributes, no parents, and
+    /// no tags.
     pub fn with_uid(uid: EntityUID) -> Self {
         Self {
             uid,
             attrs: HashMap::new(),
             ancestors: HashSet::new(),
+            parents: HashSet::new(),
+            unknown_ancestors: None,
+            tags: HashMap::new(),
         }
     }
 
     /// Test if two `Entity` objects are deep/structurally equal.
     /// That is, not only do they have the same UID, but also the same
-    /// attributes, attribute values, and ancestors.
+    /// attributes, attribute values, ancestors, and tags.
     pub(crate) fn deep_eq(&self, other: &Self) -> bool {
-        self.uid == other.uid && self.attrs == other.attrs && self.ancestors == other.ancestors
+        self.uid == other.uid
+            && self.attrs == other.attrs
+            && self.ancestors == other.ancestors
+            && self.unknown_ancestors == other.unknown_ancestors
+            && self.tags == other.tags
     }
 
     /// Set the given attribute to the given value.
@@ -396,35 +500,73 @@ impl Entity {
         Ok(())
     }
 
-    /// Mark the given `UID` as an ancestor of this `Entity`.
-    // When fuzzing, `add_ancestor()` is fully `pub`.
-    #[cfg(not(fuzzing))]
+    /// Set the given tag to the given value.
+    // Only used for convenience in some tests and when fuzzing
+    #[cfg(any(test, fuzzing))]
+    pub fn set_tag(
+        &mut self,
+        tag: SmolStr,
+        val: RestrictedExpr,
+        extensions: &Extensions<'_>,
+    ) -> Result<(), EvaluationError> {
+        let val = RestrictedEvaluator::new(extensions).partial_interpret(val.as_borrowed())?;
+        self.tags.insert(tag, val.into());
+        Ok(())
+    }
+
+    /// Mark the given `UID` as a parent (and thus an ancestor) of this
+    /// `Entity`.
+    // When fuzzing, `add_ancestor()` is fully `pub`; otherwise only tests use it.
+    #[cfg(all(test, not(fuzzing)))]
     pub(crate) fn add_ancestor(&mut self, uid: EntityUID) {
-        self.ancestors.insert(uid);
+        self.ancestors.insert(uid.clone());
+        self.parents.insert(uid);
     }
-    /// Mark the given `UID` as an ancestor of this `Entity`
+    /// Mark the given `UID` as a parent (and thus an ancestor) of this
+    /// `Entity`
     #[cfg(fuzzing)]
     pub fn add_ancestor(&mut self, uid: EntityUID) {
-        self.ancestors.insert(uid);
+        self.ancestors.insert(uid.clone());
+        self.parents.insert(uid);
+    }
+
+    /// Replace the parents of this `Entity`. Its ancestors are reset to the
+    /// new parents, so the caller is responsible for recomputing the
+    /// transitive closure afterwards.
+    pub(crate) fn set_parents(&mut self, parents: HashSet<EntityUID>) {
+        self.ancestors = parents.clone();
+        self.parents = parents;
     }
 
-    /// Consume the entity and return the entity's owned Uid, attributes and parents.
+    /// Replace the ancestors of this `Entity` with the given (transitively
+    /// closed) set, leaving its declared parents unchanged.
+    pub(crate) fn set_ancestors(&mut self, ancestors: HashSet<EntityUID>) {
+        self.ancestors = ancestors;
+    }
+
+    /// Consume the entity and return the entity's owned Uid, attributes,
+    /// parents, and tags.
     pub fn into_inner(
         self,
     ) -> (
         EntityUID,
         HashMap<SmolStr, PartialValue>,
         HashSet<EntityUID>,
+        HashMap<SmolStr, PartialValue>,
     ) {
         let Self {
             uid,
             attrs,
             ancestors,
+            parents: _,
+            unknown_ancestors: _,
+            tags,
         } = self;
         (
             uid,
             attrs.into_iter().map(|(k, v)| (k, v.0)).collect(),
             ancestors,
+            tags.into_iter().map(|(k, v)| (k, v.0)).collect(),
         )
     }
 }
@@ -449,7 +591,9 @@ impl TCNode<EntityUID> for Entity {
     }
 
     fn add_edge_to(&mut self, k: EntityUID) {
-        self.add_ancestor(k)
+        // Edges added by the transitive closure computation are not declared
+        // parents, so only `ancestors` is updated
+        self.ancestors.insert(k);
     }
 
     fn out_edges(&self) -> Box<dyn Iterator<Item = &EntityUID> + '_> {
@@ -465,13 +609,17 @@ impl std::fmt::Display for Entity {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{}:\n  attrs:{}\n  ancestors:{}",
+            "{}:\n  attrs:{}\n  ancestors:{}\n  tags:{}",
             self.uid,
             self.attrs
                 .iter()
                 .map(|(k, v)| format!("{}: {}", k, v))
                 .join("; "),
-            self.ancestors.iter().join(", ")
+            self.ancestors.iter().join(", "),
+            self.tags
+                .iter()
+                .map(|(k, v)| format!("{}: {}", k, v))
+                .join("; "),
         )
     }
 }
@@ -517,16 +665,18 @@ impl std::fmt::Display for PartialValueSerializedAsExpr {
     }
 }
 
-/// Error type for evaluation errors when evaluating an entity attribute.
-/// Contains some extra contextual information and the underlying
+/// Error type for evaluation errors when evaluating an entity attribute or
+/// tag. Contains some extra contextual information and the underlying
 /// `EvaluationError`.
 #[derive(Debug, Diagnostic, Error)]
-#[error("failed to evaluate attribute `{attr}` of `{uid}`: {err}")]
+#[error("failed to evaluate {} `{attr_or_tag}` of `{uid}`: {err}", if *.was_attr { "attribute" } else { "tag" })]
 pub struct EntityAttrEvaluationError {
     /// UID of the entity where the error was encountered
     pub uid: EntityUID,
-    /// Attribute of the entity where the error was encountered
-    pub attr: SmolStr,
+    /// Attribute or tag of the entity where the error was encountered
+    pub attr_or_tag: SmolStr,
+    /// If `attr_or_tag` was an attribute (`true`) or tag (`false`)
+    pub was_attr: bool,
     /// Underlying evaluation error
     #[diagnostic(transparent)]
     pub err: EvaluationError,
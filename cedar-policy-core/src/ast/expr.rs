@@ -409,6 +409,18 @@ impl Expr {
         ExprBuilder::new().contains_any(e1, e2)
     }
 
+    /// Create a `getTag` expression.
+    /// `expr` must evaluate to Entity type, `tag` must evaluate to String type.
+    pub fn get_tag(expr: Expr, tag: Expr) -> Self {
+        ExprBuilder::new().get_tag(expr, tag)
+    }
+
+    /// Create a `hasTag` expression.
+    /// `expr` must evaluate to Entity type, `tag` must evaluate to String type.
+    pub fn has_tag(expr: Expr, tag: Expr) -> Self {
+        ExprBuilder::new().has_tag(expr, tag)
+    }
+
     /// Create an `Expr` which evaluates to a Set of the given `Expr`s
     pub fn set(exprs: impl IntoIterator<Item = Expr>) -> Self {
         ExprBuilder::new().set(exprs)
@@ -684,6 +696,20 @@ pub struct Unknown {
     /// If `None`, we have no type annotation, and thus a value of any type can
     /// be substituted.
     pub type_annotation: Option<Type>,
+    /// If this unknown was introduced for data missing from a partial
+    /// [`crate::entities::Entities`], rather than named by the user, what it
+    /// stands for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub missing: Option<MissingData>,
+}
+
+/// What an [`Unknown`] introduced for missing entity data stands for
+#[derive(Serialize, Deserialize, Hash, Debug, Clone, PartialEq, Eq)]
+pub enum MissingData {
+    /// The entity with this UID, which is not in the store
+    Entity(EntityUID),
+    /// The ancestors of the entity with this UID, beyond those in the store
+    Ancestors(EntityUID),
 }
 
 impl Unknown {
@@ -692,6 +718,7 @@ impl Unknown {
         Self {
             name: name.into(),
             type_annotation: None,
+            missing: None,
         }
     }
 
@@ -701,6 +728,29 @@ impl Unknown {
         Self {
             name: name.into(),
             type_annotation: Some(ty),
+            missing: None,
+        }
+    }
+
+    /// Create the `Unknown` standing for the entity `uid`, which is missing
+    /// from a partial store. It is named after the UID.
+    pub fn missing_entity(uid: EntityUID) -> Self {
+        Self {
+            name: uid.to_string().into(),
+            type_annotation: Some(Type::Entity {
+                ty: uid.entity_type().clone(),
+            }),
+            missing: Some(MissingData::Entity(uid)),
+        }
+    }
+
+    /// Create the `Unknown` standing for the set of ancestors of the entity
+    /// `uid` that are not (yet) in the store
+    pub fn missing_ancestors(uid: EntityUID) -> Self {
+        Self {
+            name: format!("{uid}.ancestors").into(),
+            type_annotation: Some(Type::Set),
+            missing: Some(MissingData::Ancestors(uid)),
         }
     }
 }
@@ -963,6 +1013,26 @@ impl<T> ExprBuilder<T> {
         })
     }
 
+    /// Create a `getTag` expression.
+    /// `expr` must evaluate to Entity type, `tag` must evaluate to String type.
+    pub fn get_tag(self, expr: Expr<T>, tag: Expr<T>) -> Expr<T> {
+        self.with_expr_kind(ExprKind::BinaryApp {
+            op: BinaryOp::GetTag,
+            arg1: Arc::new(expr),
+            arg2: Arc::new(tag),
+        })
+    }
+
+    /// Create a `hasTag` expression.
+    /// `expr` must evaluate to Entity type, `tag` must evaluate to String type.
+    pub fn has_tag(self, expr: Expr<T>, tag: Expr<T>) -> Expr<T> {
+        self.with_expr_kind(ExprKind::BinaryApp {
+            op: BinaryOp::HasTag,
+            arg1: Arc::new(expr),
+            arg2: Arc::new(tag),
+        })
+    }
+
     /// Create an `Expr` which evaluates to a Set of the given `Expr`s
     pub fn set(self, exprs: impl IntoIterator<Item = Expr<T>>) -> Expr<T> {
         self.with_expr_kind(ExprKind::Set(Arc::new(exprs.into_iter().collect())))
@@ -1177,12 +1247,14 @@ impl<T> Expr<T> {
                 Unknown(self::Unknown {
                     name: name1,
                     type_annotation: ta_1,
+                    missing: missing_1,
                 }),
                 Unknown(self::Unknown {
                     name: name2,
                     type_annotation: ta_2,
+                    missing: missing_2,
                 }),
-            ) => (name1 == name2) && (ta_1 == ta_2),
+            ) => (name1 == name2) && (ta_1 == ta_2) && (missing_1 == missing_2),
             (
                 If {
                     test_expr,
@@ -1710,6 +1782,14 @@ mod test {
                 ExprBuilder::with_data(1).contains_any(temp.clone(), temp.clone()),
                 Expr::contains_any(Expr::val(1), Expr::val(1)),
             ),
+            (
+                ExprBuilder::with_data(1).get_tag(temp.clone(), temp.clone()),
+                Expr::get_tag(Expr::val(1), Expr::val(1)),
+            ),
+            (
+                ExprBuilder::with_data(1).has_tag(temp.clone(), temp.clone()),
+                Expr::has_tag(Expr::val(1), Expr::val(1)),
+            ),
             (
                 ExprBuilder::with_data(1).set([temp.clone()]),
                 Expr::set([Expr::val(1)]),
@@ -1773,6 +1853,7 @@ mod test {
         let u = Unknown {
             name: "foo".into(),
             type_annotation: None,
+            missing: None,
         };
         let r = UntypedSubstitution::substitute(&u, Some(&Value::new(1, None)));
         match r {
@@ -1786,6 +1867,7 @@ mod test {
         let u = Unknown {
             name: "foo".into(),
             type_annotation: Some(Type::Long),
+            missing: None,
         };
         let r = UntypedSubstitution::substitute(&u, Some(&Value::new(1, None)));
         match r {
@@ -1799,6 +1881,7 @@ mod test {
         let u = Unknown {
             name: "foo".into(),
             type_annotation: Some(Type::Bool),
+            missing: None,
         };
         let r = UntypedSubstitution::substitute(&u, Some(&Value::new(1, None)));
         match r {
@@ -1812,6 +1895,7 @@ mod test {
         let u = Unknown {
             name: "foo".into(),
             type_annotation: Some(Type::Bool),
+            missing: None,
         };
         let r = UntypedSubstitution::substitute(&u, None);
         match r {
@@ -1825,6 +1909,7 @@ mod test {
         let u = Unknown {
             name: "foo".into(),
             type_annotation: None,
+            missing: None,
         };
         let e = TypedSubstitution::substitute(&u, Some(&Value::new(1, None))).unwrap();
         assert_eq!(e, Expr::val(1));
@@ -1835,6 +1920,7 @@ mod test {
         let u = Unknown {
             name: "foo".into(),
             type_annotation: Some(Type::Long),
+            missing: None,
         };
         let e = TypedSubstitution::substitute(&u, Some(&Value::new(1, None))).unwrap();
         assert_eq!(e, Expr::val(1));
@@ -1845,6 +1931,7 @@ mod test {
         let u = Unknown {
             name: "foo".into(),
             type_annotation: Some(Type::Bool),
+            missing: None,
         };
         let r = TypedSubstitution::substitute(&u, Some(&Value::new(1, None))).unwrap_err();
         assert_matches!(
@@ -1861,6 +1948,7 @@ mod test {
         let u = Unknown {
             name: "foo".into(),
             type_annotation: None,
+            missing: None,
         };
         let r = TypedSubstitution::substitute(&u, None).unwrap();
         assert_eq!(r, Expr::unknown(u));
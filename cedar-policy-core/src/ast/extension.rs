@@ -338,6 +338,13 @@ pub trait ExtensionValue: Debug + Display + Send + Sync + UnwindSafe + RefUnwind
     /// Cedar has nominal typing, so two values have the same type iff they
     /// return the same typename here.
     fn typename(&self) -> Name;
+
+    /// If this returns `true`, the comparison operators `<`, `<=`, `>`, and
+    /// `>=` may be applied to two values of this type, and compare them
+    /// according to their `Ord` implementation.
+    fn supports_operator_overloading(&self) -> bool {
+        false
+    }
 }
 
 impl<V: ExtensionValue> StaticallyTyped for V {
@@ -385,6 +392,12 @@ impl ExtensionValueWithArgs {
         self.value.typename()
     }
 
+    /// Whether the comparison operators may be applied to this value. See
+    /// [`ExtensionValue::supports_operator_overloading`].
+    pub fn supports_operator_overloading(&self) -> bool {
+        self.value.supports_operator_overloading()
+    }
+
     /// Get the constructor and args that can reproduce this value
     pub fn constructor_and_args(&self) -> (&Name, &[RestrictedExpr]) {
         (&self.constructor, &self.args)
@@ -177,6 +177,13 @@ impl AnyId {
     }
 }
 
+// Every `Id` is a valid `AnyId`
+impl From<Id> for AnyId {
+    fn from(id: Id) -> AnyId {
+        AnyId(id.into_smolstr())
+    }
+}
+
 impl AsRef<str> for AnyId {
     fn as_ref(&self) -> &str {
         &self.0
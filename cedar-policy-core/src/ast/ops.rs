@@ -43,12 +43,14 @@ pub enum BinaryOp {
 
     /// <
     ///
-    /// Arguments must have Long type
+    /// Arguments must both have Long type, or both have the same extension
+    /// type which supports operator overloading (e.g., `datetime`)
     Less,
 
     /// <=
     ///
-    /// Arguments must have Long type
+    /// Arguments must both have Long type, or both have the same extension
+    /// type which supports operator overloading (e.g., `datetime`)
     LessEq,
 
     /// Integer addition
@@ -89,6 +91,16 @@ pub enum BinaryOp {
     ///
     /// Arguments must have Set type
     ContainsAny,
+
+    /// Get a tag of an entity.
+    ///
+    /// First argument must have Entity type, second argument must have String type.
+    GetTag,
+
+    /// Does the given entity have the given tag?
+    ///
+    /// First argument must have Entity type, second argument must have String type.
+    HasTag,
 }
 
 impl std::fmt::Display for UnaryOp {
@@ -113,6 +125,8 @@ impl std::fmt::Display for BinaryOp {
             BinaryOp::Contains => write!(f, "contains"),
             BinaryOp::ContainsAll => write!(f, "containsAll"),
             BinaryOp::ContainsAny => write!(f, "containsAny"),
+            BinaryOp::GetTag => write!(f, "getTag"),
+            BinaryOp::HasTag => write!(f, "hasTag"),
         }
     }
 }
@@ -18,13 +18,17 @@ use super::{
     EntityUID, LinkingError, LiteralPolicy, Policy, PolicyID, ReificationError, SlotId,
     StaticPolicy, Template,
 };
-use itertools::Itertools;
+use itertools::{Either, Itertools};
 use miette::Diagnostic;
 use serde::{Deserialize, Serialize};
 use std::collections::{hash_map::Entry, HashMap, HashSet};
 use std::{borrow::Borrow, sync::Arc};
 use thiserror::Error;
 
+mod scope_index;
+use scope_index::ScopeIndex;
+pub(crate) use scope_index::ScopeLookup;
+
 /// Represents a set of `Policy`s
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(try_from = "LiteralPolicySet")]
@@ -47,6 +51,10 @@ pub struct PolicySet {
     /// There is a key `t` iff `templates` contains the key `t`. The value of `t` will be a (possibly empty)
     /// set of every `p` in `links` s.t. `p.template().id() == t`.
     template_to_links_map: HashMap<PolicyID, HashSet<PolicyID>>,
+
+    /// Index of the policies in `links` by their scope constraints.
+    /// Every policy in `links` is in the index, and nothing else is.
+    scope_index: ScopeIndex,
 }
 
 /// Converts a LiteralPolicySet into a PolicySet, ensuring the invariants are met
@@ -78,10 +86,16 @@ impl TryFrom<LiteralPolicySet> for PolicySet {
             };
         }
 
+        let mut scope_index = ScopeIndex::default();
+        for link in links.values() {
+            scope_index.insert(link);
+        }
+
         Ok(Self {
             templates,
             links,
             template_to_links_map,
+            scope_index,
         })
     }
 }
@@ -178,6 +192,7 @@ impl PolicySet {
             templates: HashMap::new(),
             links: HashMap::new(),
             template_to_links_map: HashMap::new(),
+            scope_index: ScopeIndex::default(),
         }
     }
 
@@ -228,6 +243,7 @@ impl PolicySet {
                 .insert(policy.id().clone());
         }
         if let Some(ventry) = link_ventry {
+            self.scope_index.insert(&policy);
             ventry.insert(policy);
         }
 
@@ -253,6 +269,7 @@ impl PolicySet {
         match self.templates.remove(policy_id) {
             Some(_) => {
                 self.template_to_links_map.remove(policy_id);
+                self.scope_index.remove(&policy);
                 Ok(policy)
             }
             None => {
@@ -282,6 +299,7 @@ impl PolicySet {
                         .collect::<HashSet<PolicyID>>(),
                 );
                 templates_entry.insert(t);
+                self.scope_index.insert(&p);
                 links_entry.insert(p);
                 Ok(())
             }
@@ -397,6 +415,7 @@ impl PolicySet {
                     .entry(template_id)
                     .or_default()
                     .insert(new_id);
+                self.scope_index.insert(&r);
                 Ok(links_entry.insert(r))
             }
             (Entry::Occupied(oentry), _) => Err(LinkingError::PolicyIdConflict {
@@ -425,6 +444,7 @@ impl PolicySet {
                         panic!("No template found for linked policy")
                     }
                 };
+                self.scope_index.remove(&p);
                 Ok(p)
             }
             None => Err(PolicySetUnlinkError::UnlinkingError(policy_id.clone())),
@@ -436,6 +456,23 @@ impl PolicySet {
         self.links.values()
     }
 
+    /// Iterate over the policies whose scope may match a request with the
+    /// given principal, action and resource, using the scope index to skip
+    /// the others. This may include policies whose scope doesn't match, but
+    /// every policy that isn't included is guaranteed to evaluate to `false`
+    /// for the request.
+    pub(crate) fn policies_in_scope<'a>(
+        &'a self,
+        principal: &ScopeLookup<'_>,
+        action: &ScopeLookup<'_>,
+        resource: &ScopeLookup<'_>,
+    ) -> impl Iterator<Item = &'a Policy> {
+        match self.scope_index.lookup(principal, action, resource) {
+            Some(ids) => Either::Left(ids.into_iter().filter_map(|id| self.links.get(id))),
+            None => Either::Right(self.policies()),
+        }
+    }
+
     /// Iterate over everything stored as template, including static policies.
     /// Ie: all_templates() should equal templates() ++ static_policies().map(|p| p.template())
     pub fn all_templates(&self) -> impl Iterator<Item = &Template> {
@@ -898,4 +935,124 @@ mod test {
         assert!(pset.get(&tid1).is_none());
         assert_eq!(pset.all_templates().count(), 4);
     }
+
+    #[test]
+    fn scope_index() {
+        let mut pset = PolicySet::new();
+        for (id, src) in [
+            (
+                "0",
+                r#"permit(principal == User::"alice", action, resource);"#,
+            ),
+            (
+                "1",
+                r#"permit(principal in Group::"admins", action == Action::"edit", resource);"#,
+            ),
+            (
+                "2",
+                r#"permit(principal is User, action in [Action::"view", Action::"edit"], resource);"#,
+            ),
+            (
+                "3",
+                r#"forbid(principal, action, resource is Doc in Folder::"secret");"#,
+            ),
+        ] {
+            pset.add_static(parser::parse_policy(Some(id.into()), src).unwrap())
+                .unwrap();
+        }
+        let t = parser::parse_policy_template(
+            Some("t".into()),
+            r#"permit(principal == ?principal, action, resource in ?resource);"#,
+        )
+        .unwrap();
+        pset.add_template(t).unwrap();
+        pset.link(
+            PolicyID::from_string("t"),
+            PolicyID::from_string("link"),
+            HashMap::from([
+                (SlotId::principal(), r#"User::"bob""#.parse().unwrap()),
+                (SlotId::resource(), r#"Folder::"secret""#.parse().unwrap()),
+            ]),
+        )
+        .unwrap();
+
+        let uid = |s: &str| s.parse::<EntityUID>().unwrap();
+        let in_scope = |pset: &PolicySet,
+                        principal: (&EntityUID, Vec<&EntityUID>),
+                        action: &EntityUID,
+                        resource: ScopeLookup<'_>| {
+            pset.policies_in_scope(
+                &ScopeLookup::Entity {
+                    uid: principal.0,
+                    ancestors: principal.1,
+                },
+                &ScopeLookup::Entity {
+                    uid: action,
+                    ancestors: vec![],
+                },
+                &resource,
+            )
+            .map(|p| p.id().to_string())
+            .sorted()
+            .collect::<Vec<_>>()
+        };
+        let (alice, bob, admins) = (
+            uid(r#"User::"alice""#),
+            uid(r#"User::"bob""#),
+            uid(r#"Group::"admins""#),
+        );
+        let (view, edit) = (uid(r#"Action::"view""#), uid(r#"Action::"edit""#));
+        let (doc, secret) = (uid(r#"Doc::"plan""#), uid(r#"Folder::"secret""#));
+        let doc_in_secret = || ScopeLookup::Entity {
+            uid: &doc,
+            ancestors: vec![&secret],
+        };
+
+        assert_eq!(
+            in_scope(&pset, (&alice, vec![]), &view, doc_in_secret()),
+            vec!["0", "2", "3"]
+        );
+        assert_eq!(
+            in_scope(&pset, (&alice, vec![&admins]), &edit, doc_in_secret()),
+            vec!["0", "1", "2", "3"]
+        );
+        assert_eq!(
+            in_scope(&pset, (&bob, vec![]), &view, doc_in_secret()),
+            vec!["2", "3", "link"]
+        );
+        assert_eq!(
+            in_scope(
+                &pset,
+                (&bob, vec![]),
+                &view,
+                ScopeLookup::Entity {
+                    uid: &doc,
+                    ancestors: vec![],
+                }
+            ),
+            vec!["2"]
+        );
+        // an unknown variable can't be used to rule out policies
+        assert_eq!(
+            in_scope(&pset, (&bob, vec![]), &view, ScopeLookup::Unknown),
+            vec!["2", "3", "link"]
+        );
+
+        // the index is kept up to date as policies are removed
+        pset.unlink(&PolicyID::from_string("link")).unwrap();
+        pset.remove_static(&PolicyID::from_string("3")).unwrap();
+        assert_eq!(
+            in_scope(&pset, (&bob, vec![]), &view, doc_in_secret()),
+            vec!["2"]
+        );
+        // and doesn't depend on the order of updates
+        let mut fresh = PolicySet::new();
+        for p in pset.policies() {
+            fresh.add(p.clone()).unwrap();
+        }
+        fresh
+            .add_template((*pset.get_template(&PolicyID::from_string("t")).unwrap()).clone())
+            .unwrap();
+        assert_eq!(fresh, pset);
+    }
 }
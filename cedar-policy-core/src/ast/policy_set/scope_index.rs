@@ -0,0 +1,239 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Index of the policies in a `PolicySet` by their scope constraints, used to
+//! skip policies whose scope can't match a request without evaluating them.
+
+use crate::ast::{
+    ActionConstraint, EntityReference, EntityType, EntityUID, Name, Policy, PolicyID,
+    PrincipalOrResourceConstraint,
+};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+use std::hash::Hash;
+
+/// What is known about the principal, action, or resource of a request when
+/// looking up policies in a [`ScopeIndex`]
+#[derive(Debug)]
+pub(crate) enum ScopeLookup<'a> {
+    /// Nothing is known, so no policies can be ruled out based on this
+    /// variable
+    Unknown,
+    /// The variable is this entity, which has (exactly) these ancestors
+    Entity {
+        /// The entity
+        uid: &'a EntityUID,
+        /// All of its ancestors, not just its parents
+        ancestors: Vec<&'a EntityUID>,
+    },
+}
+
+/// Index of policies by the scope constraint on each of `principal`, `action`
+/// and `resource`.
+///
+/// Lookups return a superset of the policies whose scope matches: e.g., a
+/// policy with `principal == User::"alice"` is returned for requests where
+/// `User::"alice"` is any ancestor of the principal. Policies must still be
+/// evaluated, but those that aren't returned are guaranteed to evaluate to
+/// `false`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct ScopeIndex {
+    principal: VarIndex,
+    action: VarIndex,
+    resource: VarIndex,
+}
+
+/// The part of the [`ScopeIndex`] for one scope variable
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct VarIndex {
+    /// Policies that don't constrain this variable
+    unconstrained: HashSet<PolicyID>,
+    /// Policies that only match if the variable is the key entity or one of
+    /// its descendants (`==`, `in` and `is ... in` constraints)
+    by_entity: HashMap<EntityUID, HashSet<PolicyID>>,
+    /// Policies that only match if the variable has the key type (`is`
+    /// constraints)
+    by_type: HashMap<Name, HashSet<PolicyID>>,
+}
+
+/// Key under which a policy is stored in a [`VarIndex`]
+enum Key {
+    Unconstrained,
+    Entity(EntityUID),
+    Type(Name),
+}
+
+impl ScopeIndex {
+    /// Add `policy` to the index
+    pub(crate) fn insert(&mut self, policy: &Policy) {
+        let id = policy.id();
+        let (principal, action, resource) = keys(policy);
+        principal
+            .into_iter()
+            .for_each(|key| self.principal.insert(key, id));
+        action
+            .into_iter()
+            .for_each(|key| self.action.insert(key, id));
+        resource
+            .into_iter()
+            .for_each(|key| self.resource.insert(key, id));
+    }
+
+    /// Remove `policy` from the index
+    pub(crate) fn remove(&mut self, policy: &Policy) {
+        let id = policy.id();
+        let (principal, action, resource) = keys(policy);
+        principal
+            .into_iter()
+            .for_each(|key| self.principal.remove(key, id));
+        action
+            .into_iter()
+            .for_each(|key| self.action.remove(key, id));
+        resource
+            .into_iter()
+            .for_each(|key| self.resource.remove(key, id));
+    }
+
+    /// Get the IDs of the policies whose scope may match a request with the
+    /// given principal, action and resource, or `None` if no policy can be
+    /// ruled out.
+    pub(crate) fn lookup(
+        &self,
+        principal: &ScopeLookup<'_>,
+        action: &ScopeLookup<'_>,
+        resource: &ScopeLookup<'_>,
+    ) -> Option<Vec<&PolicyID>> {
+        let mut candidates = [
+            self.principal.lookup(principal),
+            self.action.lookup(action),
+            self.resource.lookup(resource),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        // Enumerate the candidates for the most selective variable, and check
+        // them against the others
+        candidates.sort_by_key(|sets| sets.iter().map(|set| set.len()).sum::<usize>());
+        let (smallest, others) = candidates.split_first()?;
+        let mut seen = HashSet::new();
+        Some(
+            smallest
+                .iter()
+                .flat_map(|set| set.iter())
+                .filter(|id| seen.insert(*id))
+                .filter(|id| {
+                    others
+                        .iter()
+                        .all(|sets| sets.iter().any(|set| set.contains(*id)))
+                })
+                .collect(),
+        )
+    }
+}
+
+impl VarIndex {
+    fn insert(&mut self, key: Key, id: &PolicyID) {
+        match key {
+            Key::Unconstrained => self.unconstrained.insert(id.clone()),
+            Key::Entity(uid) => self.by_entity.entry(uid).or_default().insert(id.clone()),
+            Key::Type(name) => self.by_type.entry(name).or_default().insert(id.clone()),
+        };
+    }
+
+    /// Remove `id` from the set for `key`, dropping the set if it becomes
+    /// empty so that the index only depends on the policies it contains
+    fn remove(&mut self, key: Key, id: &PolicyID) {
+        match key {
+            Key::Unconstrained => {
+                self.unconstrained.remove(id);
+            }
+            Key::Entity(uid) => remove_from(&mut self.by_entity, uid, id),
+            Key::Type(name) => remove_from(&mut self.by_type, name, id),
+        }
+    }
+
+    /// Get the sets of policies that may match the given value of this
+    /// variable, or `None` if no policy can be ruled out
+    fn lookup(&self, lookup: &ScopeLookup<'_>) -> Option<Vec<&HashSet<PolicyID>>> {
+        match lookup {
+            ScopeLookup::Unknown => None,
+            ScopeLookup::Entity { uid, ancestors } => {
+                let by_type = match uid.entity_type() {
+                    EntityType::Specified(name) => self.by_type.get(name),
+                    EntityType::Unspecified => None,
+                };
+                Some(
+                    std::iter::once(&self.unconstrained)
+                        .chain(by_type)
+                        .chain(
+                            std::iter::once(*uid)
+                                .chain(ancestors.iter().copied())
+                                .filter_map(|uid| self.by_entity.get(uid)),
+                        )
+                        .collect(),
+                )
+            }
+        }
+    }
+}
+
+fn remove_from<K: Eq + Hash>(map: &mut HashMap<K, HashSet<PolicyID>>, key: K, id: &PolicyID) {
+    if let Entry::Occupied(mut entry) = map.entry(key) {
+        entry.get_mut().remove(id);
+        if entry.get().is_empty() {
+            entry.remove();
+        }
+    }
+}
+
+/// The keys `policy` is stored under for each of `principal`, `action` and
+/// `resource`
+fn keys(policy: &Policy) -> (Vec<Key>, Vec<Key>, Vec<Key>) {
+    (
+        principal_or_resource_keys(policy.principal_constraint().as_inner()),
+        action_keys(policy.action_constraint()),
+        principal_or_resource_keys(policy.resource_constraint().as_inner()),
+    )
+}
+
+fn principal_or_resource_keys(constraint: &PrincipalOrResourceConstraint) -> Vec<Key> {
+    match constraint {
+        PrincipalOrResourceConstraint::Any => vec![Key::Unconstrained],
+        PrincipalOrResourceConstraint::Eq(EntityReference::EUID(uid))
+        | PrincipalOrResourceConstraint::In(EntityReference::EUID(uid))
+        | PrincipalOrResourceConstraint::IsIn(_, EntityReference::EUID(uid)) => {
+            vec![Key::Entity(uid.as_ref().clone())]
+        }
+        PrincipalOrResourceConstraint::Is(name) => vec![Key::Type(name.clone())],
+        // Slots are always filled in a linked policy; if one weren't, the
+        // policy couldn't be ruled out
+        PrincipalOrResourceConstraint::Eq(EntityReference::Slot)
+        | PrincipalOrResourceConstraint::In(EntityReference::Slot)
+        | PrincipalOrResourceConstraint::IsIn(_, EntityReference::Slot) => {
+            vec![Key::Unconstrained]
+        }
+    }
+}
+
+fn action_keys(constraint: &ActionConstraint) -> Vec<Key> {
+    match constraint {
+        ActionConstraint::Any => vec![Key::Unconstrained],
+        ActionConstraint::Eq(uid) => vec![Key::Entity(uid.as_ref().clone())],
+        ActionConstraint::In(uids) => uids
+            .iter()
+            .map(|uid| Key::Entity(uid.as_ref().clone()))
+            .collect(),
+    }
+}
@@ -0,0 +1,416 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains a simplifier for expressions, mainly intended for
+//! the residuals produced by partial evaluation.
+
+use super::{BinaryOp, EntityType, Expr, ExprKind, Literal, Type, UnaryOp, Unknown};
+use std::collections::BTreeMap;
+
+impl Expr {
+    /// Simplify this expression into an equivalent one, meant to be easier to
+    /// read and to process: constants are folded, chains of `&&` and `||`
+    /// are flattened (to left-nested form) with duplicate and redundant
+    /// operands removed, `if`s with a constant or repeated condition are
+    /// collapsed, and negations are pushed inwards with De Morgan's laws.
+    ///
+    /// The result is equivalent to `self` in every environment: it evaluates
+    /// to the same value whenever `self` does, and to an error whenever
+    /// `self` does (though not necessarily the same error). Since an operand
+    /// that errors can't be skipped, operands are never reordered, and a
+    /// rewrite like `true && x` to `x` is only made when `x` is known to
+    /// evaluate to a boolean or an error.
+    ///
+    /// Source locations are not preserved.
+    pub fn simplify(&self) -> Expr {
+        match self.expr_kind() {
+            ExprKind::Lit(_) | ExprKind::Var(_) | ExprKind::Slot(_) | ExprKind::Unknown(_) => {
+                self.clone()
+            }
+            ExprKind::And { .. } => {
+                let mut operands = Vec::new();
+                flatten(self, &Connective::And, &mut operands);
+                Connective::And.rebuild(operands)
+            }
+            ExprKind::Or { .. } => {
+                let mut operands = Vec::new();
+                flatten(self, &Connective::Or, &mut operands);
+                Connective::Or.rebuild(operands)
+            }
+            ExprKind::UnaryApp {
+                op: UnaryOp::Not,
+                arg,
+            } => negate(arg.simplify()),
+            ExprKind::UnaryApp {
+                op: UnaryOp::Neg,
+                arg,
+            } => {
+                let arg = arg.simplify();
+                match arg.expr_kind() {
+                    ExprKind::Lit(Literal::Long(i)) => match i.checked_neg() {
+                        Some(i) => Expr::val(i),
+                        // keep the overflow error
+                        None => Expr::neg(arg),
+                    },
+                    _ => Expr::neg(arg),
+                }
+            }
+            ExprKind::If {
+                test_expr,
+                then_expr,
+                else_expr,
+            } => simplify_if(
+                test_expr.simplify(),
+                then_expr.simplify(),
+                else_expr.simplify(),
+            ),
+            ExprKind::BinaryApp { op, arg1, arg2 } => {
+                let (arg1, arg2) = (arg1.simplify(), arg2.simplify());
+                fold_binary(*op, &arg1, &arg2)
+                    .map(Expr::val)
+                    .unwrap_or_else(|| Expr::binary_app(*op, arg1, arg2))
+            }
+            ExprKind::ExtensionFunctionApp { fn_name, args } => {
+                Expr::call_extension_fn(fn_name.clone(), args.iter().map(Expr::simplify).collect())
+            }
+            ExprKind::GetAttr { expr, attr } => Expr::get_attr(expr.simplify(), attr.clone()),
+            ExprKind::HasAttr { expr, attr } => Expr::has_attr(expr.simplify(), attr.clone()),
+            ExprKind::Like { expr, pattern } => {
+                let expr = expr.simplify();
+                match expr.expr_kind() {
+                    ExprKind::Lit(Literal::String(s)) => Expr::val(pattern.wildcard_match(s)),
+                    _ => Expr::like(expr, pattern.iter().cloned()),
+                }
+            }
+            ExprKind::Is { expr, entity_type } => {
+                let expr = expr.simplify();
+                match expr.expr_kind() {
+                    ExprKind::Lit(Literal::EntityUID(uid)) => {
+                        Expr::val(uid.entity_type() == &EntityType::Specified(entity_type.clone()))
+                    }
+                    _ => Expr::is_entity_type(expr, entity_type.clone()),
+                }
+            }
+            ExprKind::Set(elems) => Expr::set(elems.iter().map(Expr::simplify)),
+            ExprKind::Record(attrs) => Expr::record_arc(
+                attrs
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.simplify()))
+                    .collect::<BTreeMap<_, _>>()
+                    .into(),
+            ),
+        }
+    }
+}
+
+/// `&&` or `||`
+enum Connective {
+    And,
+    Or,
+}
+
+impl Connective {
+    /// The operand that decides the result of the whole chain: `false` for
+    /// `&&` and `true` for `||`
+    fn absorbing(&self) -> bool {
+        matches!(self, Self::Or)
+    }
+
+    /// The operands of `e` if it is this connective
+    fn operands<'a>(&self, e: &'a Expr) -> Option<(&'a Expr, &'a Expr)> {
+        match (self, e.expr_kind()) {
+            (Self::And, ExprKind::And { left, right })
+            | (Self::Or, ExprKind::Or { left, right }) => Some((left, right)),
+            _ => None,
+        }
+    }
+
+    fn build(&self, left: Expr, right: Expr) -> Expr {
+        match self {
+            Self::And => Expr::and(left, right),
+            Self::Or => Expr::or(left, right),
+        }
+    }
+
+    /// Build the chain of (already simplified) `operands`.
+    ///
+    /// Each operand of a chain is evaluated in order until one of them is the
+    /// absorbing value or errors, and must evaluate to a boolean. So a later
+    /// operand identical to an earlier one, or the identity value, can be
+    /// dropped, nothing after the absorbing value is ever evaluated, and a
+    /// double negation can be removed from any operand.
+    fn rebuild(&self, operands: Vec<Expr>) -> Expr {
+        let mut kept: Vec<Expr> = Vec::new();
+        for operand in operands {
+            let operand = match double_negation(&operand) {
+                Some(inner) => inner.clone(),
+                None => operand,
+            };
+            match operand.expr_kind() {
+                ExprKind::Lit(Literal::Bool(b)) if *b != self.absorbing() => (),
+                ExprKind::Lit(Literal::Bool(_)) => {
+                    kept.push(operand);
+                    break;
+                }
+                _ if kept.iter().any(|k| k.eq_shape(&operand)) => (),
+                _ => kept.push(operand),
+            }
+        }
+        let mut kept = kept.into_iter();
+        match (kept.next(), kept.len()) {
+            (None, _) => Expr::val(!self.absorbing()),
+            // a lone operand can only stand for the chain if it is a boolean
+            (Some(only), 0) if !is_boolean(&only) => self.build(Expr::val(!self.absorbing()), only),
+            (Some(first), _) => kept.fold(first, |acc, next| self.build(acc, next)),
+        }
+    }
+}
+
+/// Push the simplified operands of the chain of `connective`s rooted at `e`
+/// onto `operands`, flattening nested chains of the same connective
+fn flatten(e: &Expr, connective: &Connective, operands: &mut Vec<Expr>) {
+    match connective.operands(e) {
+        Some((left, right)) => {
+            flatten(left, connective, operands);
+            flatten(right, connective, operands);
+        }
+        None => {
+            let simplified = e.simplify();
+            if connective.operands(&simplified).is_some() {
+                flatten(&simplified, connective, operands);
+            } else {
+                operands.push(simplified);
+            }
+        }
+    }
+}
+
+/// `x` if `e` is `!!x`
+fn double_negation(e: &Expr) -> Option<&Expr> {
+    match e.expr_kind() {
+        ExprKind::UnaryApp {
+            op: UnaryOp::Not,
+            arg,
+        } => match arg.expr_kind() {
+            ExprKind::UnaryApp {
+                op: UnaryOp::Not,
+                arg,
+            } => Some(arg),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Negate the (already simplified) `e`, pushing the negation inwards
+fn negate(e: Expr) -> Expr {
+    match e.expr_kind() {
+        ExprKind::Lit(Literal::Bool(b)) => Expr::val(!*b),
+        ExprKind::UnaryApp {
+            op: UnaryOp::Not,
+            arg,
+        } if is_boolean(arg) => arg.as_ref().clone(),
+        // De Morgan's laws: both sides evaluate the operands in the same
+        // order and stop at the same point
+        ExprKind::And { left, right } => Expr::or(
+            negate(left.as_ref().clone()),
+            negate(right.as_ref().clone()),
+        )
+        .simplify(),
+        ExprKind::Or { left, right } => Expr::and(
+            negate(left.as_ref().clone()),
+            negate(right.as_ref().clone()),
+        )
+        .simplify(),
+        _ => Expr::not(e),
+    }
+}
+
+/// Simplify an `if` with (already simplified) operands
+fn simplify_if(test: Expr, then_expr: Expr, else_expr: Expr) -> Expr {
+    if let ExprKind::Lit(Literal::Bool(b)) = test.expr_kind() {
+        return if *b { then_expr } else { else_expr };
+    }
+    // a nested `if` on the same condition always takes the same branch
+    let then_expr = match then_expr.expr_kind() {
+        ExprKind::If {
+            test_expr,
+            then_expr: inner,
+            ..
+        } if test_expr.eq_shape(&test) => inner.as_ref().clone(),
+        _ => then_expr,
+    };
+    let else_expr = match else_expr.expr_kind() {
+        ExprKind::If {
+            test_expr,
+            else_expr: inner,
+            ..
+        } if test_expr.eq_shape(&test) => inner.as_ref().clone(),
+        _ => else_expr,
+    };
+    match (then_expr.expr_kind(), else_expr.expr_kind()) {
+        (ExprKind::Lit(Literal::Bool(true)), ExprKind::Lit(Literal::Bool(false)))
+            if is_boolean(&test) =>
+        {
+            test
+        }
+        (ExprKind::Lit(Literal::Bool(false)), ExprKind::Lit(Literal::Bool(true))) => negate(test),
+        (_, ExprKind::Lit(Literal::Bool(false))) if is_boolean(&then_expr) => {
+            Connective::And.rebuild(vec![test, then_expr])
+        }
+        (ExprKind::Lit(Literal::Bool(true)), _) if is_boolean(&else_expr) => {
+            Connective::Or.rebuild(vec![test, else_expr])
+        }
+        _ => Expr::ite(test, then_expr, else_expr),
+    }
+}
+
+/// Evaluate a binary operation whose operands are both literals, if that
+/// can be done without entity data and without error
+fn fold_binary(op: BinaryOp, arg1: &Expr, arg2: &Expr) -> Option<Literal> {
+    let (ExprKind::Lit(lhs), ExprKind::Lit(rhs)) = (arg1.expr_kind(), arg2.expr_kind()) else {
+        return None;
+    };
+    match (op, lhs, rhs) {
+        (BinaryOp::Eq, _, _) => Some((lhs == rhs).into()),
+        (BinaryOp::Less, Literal::Long(i), Literal::Long(j)) => Some((i < j).into()),
+        (BinaryOp::LessEq, Literal::Long(i), Literal::Long(j)) => Some((i <= j).into()),
+        (BinaryOp::Add, Literal::Long(i), Literal::Long(j)) => i.checked_add(*j).map(Into::into),
+        (BinaryOp::Sub, Literal::Long(i), Literal::Long(j)) => i.checked_sub(*j).map(Into::into),
+        (BinaryOp::Mul, Literal::Long(i), Literal::Long(j)) => i.checked_mul(*j).map(Into::into),
+        // every entity is `in` itself; anything else needs the hierarchy
+        (BinaryOp::In, Literal::EntityUID(e1), Literal::EntityUID(e2)) if e1 == e2 => {
+            Some(true.into())
+        }
+        _ => None,
+    }
+}
+
+/// Does `e` always evaluate to a boolean (or an error)?
+fn is_boolean(e: &Expr) -> bool {
+    match e.expr_kind() {
+        ExprKind::Lit(lit) => matches!(lit, Literal::Bool(_)),
+        ExprKind::Unknown(Unknown {
+            type_annotation, ..
+        }) => type_annotation == &Some(Type::Bool),
+        ExprKind::And { .. }
+        | ExprKind::Or { .. }
+        | ExprKind::UnaryApp {
+            op: UnaryOp::Not, ..
+        }
+        | ExprKind::HasAttr { .. }
+        | ExprKind::Like { .. }
+        | ExprKind::Is { .. } => true,
+        ExprKind::BinaryApp { op, .. } => matches!(
+            op,
+            BinaryOp::Eq
+                | BinaryOp::Less
+                | BinaryOp::LessEq
+                | BinaryOp::In
+                | BinaryOp::Contains
+                | BinaryOp::ContainsAll
+                | BinaryOp::ContainsAny
+                | BinaryOp::HasTag
+        ),
+        ExprKind::If {
+            then_expr,
+            else_expr,
+            ..
+        } => is_boolean(then_expr) && is_boolean(else_expr),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::parse_expr;
+
+    #[track_caller]
+    fn assert_simplifies(src: &str, expected: &str) {
+        let simplified = parse_expr(src).unwrap().simplify();
+        let expected = parse_expr(expected).unwrap();
+        assert!(
+            simplified.eq_shape(&expected),
+            "`{src}` simplified to `{simplified}`, expected `{expected}`"
+        );
+    }
+
+    #[test]
+    fn connectives() {
+        assert_simplifies(
+            "true && (resource.a && true) && resource.b",
+            "resource.a && resource.b",
+        );
+        assert_simplifies(
+            "resource.a == 1 && (resource.b || resource.a == 1) && resource.a == 1",
+            "resource.a == 1 && (resource.b || resource.a == 1)",
+        );
+        assert_simplifies("resource.a && false && resource.b", "resource.a && false");
+        assert_simplifies("false || true || resource.a", "true");
+        // `resource.a` might not be a boolean, so `true && resource.a` errors
+        // where `resource.a` alone wouldn't
+        assert_simplifies("true && resource.a", "true && resource.a");
+        assert_simplifies("resource.a || false", "false || resource.a");
+        assert_simplifies("resource.a == 1 || false", "resource.a == 1");
+    }
+
+    #[test]
+    fn negations() {
+        assert_simplifies("!!(resource.a == 1)", "resource.a == 1");
+        assert_simplifies("!!resource.a", "!!resource.a");
+        assert_simplifies(
+            "!(resource.a && !(resource.b || resource.c))",
+            "!resource.a || resource.b || resource.c",
+        );
+        // `>` is parsed as the negation of `<=`
+        assert_simplifies("!(resource.n > 3)", "resource.n <= 3");
+    }
+
+    #[test]
+    fn conditionals() {
+        assert_simplifies("if 1 < 2 then resource.a else resource.b", "resource.a");
+        assert_simplifies(
+            "if resource.a then (if resource.a then 1 else 2) else (if resource.a then 3 else 4)",
+            "if resource.a then 1 else 4",
+        );
+        assert_simplifies("if resource.a then true else false", "true && resource.a");
+        assert_simplifies("if resource has a then true else false", "resource has a");
+        assert_simplifies("if resource.a then false else true", "!resource.a");
+        assert_simplifies(
+            "if resource.a then resource.b == 1 else false",
+            "resource.a && resource.b == 1",
+        );
+        assert_simplifies(
+            "if resource.a then true else resource.b == 1",
+            "resource.a || resource.b == 1",
+        );
+    }
+
+    #[test]
+    fn constants() {
+        assert_simplifies("1 + 2 * 3 == 7 && resource.a == -(4)", "resource.a == -4");
+        assert_simplifies(r#""abc" like "a*" && User::"a" is User"#, "true");
+        assert_simplifies(r#"User::"a" in User::"a""#, "true");
+        assert_simplifies(r#"User::"a" in User::"b""#, r#"User::"a" in User::"b""#);
+        // overflow is an error, which is kept
+        assert_simplifies(
+            "9223372036854775807 + 1 == resource.a",
+            "9223372036854775807 + 1 == resource.a",
+        );
+        assert_simplifies(r#"{a: 1 + 1, b: [2 - 1]}"#, "{a: 2, b: [1]}");
+    }
+}
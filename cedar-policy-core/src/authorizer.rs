@@ -21,19 +21,24 @@
 //! the "authorization engine".
 
 use crate::ast::*;
-use crate::entities::Entities;
-use crate::evaluator::Evaluator;
+#[cfg(feature = "partial-eval")]
+use crate::entities::{err::EntitiesError, EntityLoader, NoEntitiesSchema};
+use crate::entities::{Dereference, Entities};
+use crate::evaluator::{EvaluationCache, EvaluationLimits, EvaluationTrace, Evaluator};
 use crate::extensions::Extensions;
 use itertools::{Either, Itertools};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::borrow::Borrow;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 #[cfg(feature = "wasm")]
 extern crate tsify;
 
+mod cache;
 mod err;
 mod partial_response;
+pub use cache::CachingAuthorizer;
 pub use err::AuthorizationError;
 
 pub use partial_response::ErrorState;
@@ -45,6 +50,8 @@ pub struct Authorizer {
     extensions: Extensions<'static>,
     /// Error-handling behavior of this `Authorizer`
     error_handling: ErrorHandling,
+    /// Limits on the evaluation work done for each request
+    limits: EvaluationLimits,
 }
 
 /// Describes the possible Cedar error-handling modes.
@@ -70,15 +77,58 @@ impl Authorizer {
         Self {
             extensions: Extensions::all_available(), // set at compile time
             error_handling: Default::default(),
+            limits: EvaluationLimits::default(),
         }
     }
 
+    /// Enforce the given limits when evaluating the policies for each request.
+    ///
+    /// A policy whose evaluation exceeds a limit produces an
+    /// [`crate::evaluator::LimitExceededError`]. Unlike other errors, this
+    /// doesn't just skip the policy: the policy might have been a satisfied
+    /// forbid, so the decision is `Deny` whatever the other policies evaluate
+    /// to, and no policy is reported as determining it.
+    pub fn with_limits(self, limits: EvaluationLimits) -> Self {
+        Self { limits, ..self }
+    }
+
     /// Returns an authorization response for `q` with respect to the given `Slice`.
     ///
     /// The language spec and formal model give a precise definition of how this is
     /// computed.
     pub fn is_authorized(&self, q: Request, pset: &PolicySet, entities: &Entities) -> Response {
-        self.is_authorized_core(q, pset, entities).concretize()
+        // Policies ruled out by the scope index would evaluate to `false`
+        // without error, so they don't affect the concrete response
+        let policies = policies_in_scope(&q, pset, entities).collect::<Vec<_>>();
+        self.authorize_conditions(
+            &self.evaluator(q, entities),
+            policies.into_iter().map(|p| (p, p.condition())),
+            None,
+        )
+        .concretize()
+    }
+
+    /// Returns an authorization response for `q` with respect to the given
+    /// `PolicySet`, like [`Authorizer::is_authorized()`], but with a trace of
+    /// the evaluation of every policy in the diagnostics.
+    ///
+    /// Every policy is evaluated, including those whose scope rules them out,
+    /// so the trace also explains why a policy didn't apply.
+    pub fn is_authorized_with_trace(
+        &self,
+        q: Request,
+        pset: &PolicySet,
+        entities: &Entities,
+    ) -> Response {
+        let mut traces = HashMap::new();
+        let response = self
+            .authorize_conditions(
+                &self.evaluator(q, entities).with_trace(),
+                pset.policies().map(|p| (p, p.condition())),
+                Some(&mut traces),
+            )
+            .concretize();
+        response.with_trace(traces)
     }
 
     /// Returns an authorization response for `q` with respect to the given `Slice`.
@@ -90,7 +140,66 @@ impl Authorizer {
         pset: &PolicySet,
         entities: &Entities,
     ) -> PartialResponse {
-        let eval = Evaluator::new(q, entities, &self.extensions);
+        self.authorize_conditions(
+            &self.evaluator(q, entities),
+            pset.policies().map(|p| (p, p.condition())),
+            None,
+        )
+    }
+
+    /// Returns authorization responses for each of the requests in `qs`, with
+    /// respect to the same `PolicySet` and `Entities`.
+    ///
+    /// The responses are the same as calling [`Authorizer::is_authorized()`]
+    /// on each request in turn, but the work that doesn't depend on the
+    /// request is shared by the whole batch: the condition of each policy is
+    /// built at most once, and so is the scope index lookup of each
+    /// principal, action and resource, and each entity attribute access and
+    /// `in` test made by a policy condition is evaluated at most once.
+    pub fn is_authorized_batch(
+        &self,
+        qs: impl IntoIterator<Item = Request>,
+        pset: &PolicySet,
+        entities: &Entities,
+    ) -> Vec<Response> {
+        let mut conditions: HashMap<&PolicyID, Expr> = HashMap::new();
+        let mut scopes = ScopeCache::new(entities);
+        let cache = EvaluationCache::default();
+        qs.into_iter()
+            .map(|q| {
+                let policies = scopes.policies_in_scope(&q, pset);
+                for p in &policies {
+                    conditions.entry(p.id()).or_insert_with(|| p.condition());
+                }
+                self.authorize_conditions(
+                    &self.evaluator(q, entities).with_cache(&cache),
+                    policies
+                        .into_iter()
+                        .filter_map(|p| conditions.get(p.id()).map(|condition| (p, condition))),
+                    None,
+                )
+                .concretize()
+            })
+            .collect()
+    }
+
+    /// Create an `Evaluator` for `q`, with the extensions and limits of this
+    /// `Authorizer`
+    fn evaluator<'e>(&'e self, q: Request, entities: &'e Entities) -> Evaluator<'e> {
+        Evaluator::new(q, entities, &self.extensions).with_limits(self.limits)
+    }
+
+    /// Partially evaluate each of the given policies with `eval`, where each
+    /// policy comes with its (already constructed) condition.
+    ///
+    /// If `traces` is provided (and `eval` has tracing enabled), the trace of
+    /// each policy is added to `traces`.
+    fn authorize_conditions<'a>(
+        &self,
+        eval: &Evaluator<'_>,
+        policies: impl IntoIterator<Item = (&'a Policy, impl Borrow<Expr>)>,
+        mut traces: Option<&mut HashMap<PolicyID, EvaluationTrace>>,
+    ) -> PartialResponse {
         let mut true_permits = vec![];
         let mut true_forbids = vec![];
         let mut false_permits = vec![];
@@ -99,9 +208,13 @@ impl Authorizer {
         let mut residual_forbids = vec![];
         let mut errors = vec![];
 
-        for p in pset.policies() {
+        for (p, condition) in policies {
             let (id, annotations) = (p.id().clone(), p.annotations_arc().clone());
-            match eval.partial_evaluate(p) {
+            let res = eval.partial_evaluate_condition(condition.borrow(), p.env());
+            if let (Some(traces), Some(trace)) = (traces.as_deref_mut(), eval.take_trace()) {
+                traces.insert(id.clone(), trace);
+            }
+            match res {
                 Ok(Either::Left(satisfied)) => match (satisfied, p.effect()) {
                     (true, Effect::Permit) => true_permits.push((id, annotations)),
                     (true, Effect::Forbid) => true_forbids.push((id, annotations)),
@@ -152,6 +265,283 @@ impl Authorizer {
             errors,
         )
     }
+
+    /// Returns an authorization response for `q` with respect to the given
+    /// `PolicySet`, fetching entities from `loader` as evaluation reaches them
+    /// instead of taking an [`Entities`] up front.
+    ///
+    /// Evaluation starts with no entities, so policies that reach an entity
+    /// are left with residuals over the missing data. The entities those
+    /// residuals need are requested from `loader` in a single batch, and then
+    /// only the residual policies are evaluated again, until no residual needs
+    /// an entity that hasn't been requested. The parents of a loaded entity are
+    /// only requested once an `in` test can't be decided without them.
+    /// Loaded entities are kept for the rest of the request, and no UID is
+    /// requested twice. Entities the loader doesn't return are handled as
+    /// with [`Entities::partial()`]: policies that depend on them are left as
+    /// residuals.
+    #[cfg(feature = "partial-eval")]
+    pub fn is_authorized_with_loader(
+        &self,
+        q: Request,
+        pset: &PolicySet,
+        loader: &mut impl EntityLoader,
+    ) -> Result<PartialResponse, EntitiesError> {
+        let mut entities = Entities::new().partial();
+        let mut requested: HashSet<EntityUID> = HashSet::new();
+        // loaded entities with parents that haven't been requested yet
+        let mut incomplete: HashSet<EntityUID> = HashSet::new();
+        let mut response = self.is_authorized_core(q.clone(), pset, &entities);
+        loop {
+            let to_load: HashSet<EntityUID> = response
+                .residual_permits
+                .values()
+                .chain(response.residual_forbids.values())
+                .flat_map(|(residual, _)| residual.unknowns())
+                .flat_map(|unknown| match &unknown.missing {
+                    Some(MissingData::Entity(uid)) => vec![uid.clone()],
+                    Some(MissingData::Ancestors(uid)) => match entities.entity(uid) {
+                        Dereference::Data(entity) => entity.parents().cloned().collect(),
+                        Dereference::NoSuchEntity | Dereference::Residual(_) => Vec::new(),
+                    },
+                    None => Vec::new(),
+                })
+                .filter(|uid| !requested.contains(uid))
+                .collect();
+            if to_load.is_empty() {
+                return Ok(response);
+            }
+            let mut loaded = loader
+                .load_entities(&to_load.iter().collect::<Vec<_>>())
+                .map_err(|e| EntitiesError::Loader(Box::new(e)))?;
+            requested.extend(to_load);
+            requested.extend(loaded.iter().map(|e| e.uid().clone()));
+            // entities whose parents have now all been requested no longer
+            // have unknown ancestors
+            let completed: Vec<EntityUID> = incomplete
+                .iter()
+                .filter(|uid| match entities.entity(uid) {
+                    Dereference::Data(entity) => entity.parents().all(|p| requested.contains(p)),
+                    Dereference::NoSuchEntity | Dereference::Residual(_) => true,
+                })
+                .cloned()
+                .collect();
+            for uid in completed {
+                incomplete.remove(&uid);
+                if let Dereference::Data(entity) = entities.entity(&uid) {
+                    let mut entity = entity.clone();
+                    entity.set_unknown_ancestors(None);
+                    loaded.push(entity);
+                }
+            }
+            for entity in &mut loaded {
+                if !entity.parents().all(|p| requested.contains(p)) {
+                    incomplete.insert(entity.uid().clone());
+                    entity.set_unknown_ancestors(Some(Unknown::missing_ancestors(
+                        entity.uid().clone(),
+                    )));
+                }
+            }
+            entities.upsert_entities(loaded, None::<&NoEntitiesSchema>, self.extensions)?;
+
+            let residuals: HashSet<&PolicyID> = response
+                .residual_permits
+                .keys()
+                .chain(response.residual_forbids.keys())
+                .collect();
+            let rerun = self.authorize_conditions(
+                &self.evaluator(q.clone(), &entities),
+                pset.policies()
+                    .filter(|p| residuals.contains(p.id()))
+                    .map(|p| (p, p.condition())),
+                None,
+            );
+            response.replace_residuals(rerun);
+        }
+    }
+
+    /// Returns the UIDs of all entities of type `resource_type` in `entities`
+    /// that `principal` is allowed to perform `action` on, in the given
+    /// `context`.
+    ///
+    /// The policies are first partially evaluated once with an unknown
+    /// resource. If that reaches a decision, it holds for every resource.
+    /// Otherwise, only the residual policies are evaluated for each candidate
+    /// resource, with the unknown replaced by the candidate's UID. A resource
+    /// is only listed if it is definitely allowed, so if `entities` is
+    /// partial, resources whose decision depends on missing data are left out.
+    #[cfg(feature = "partial-eval")]
+    pub fn list_resources(
+        &self,
+        principal: EntityUID,
+        action: EntityUID,
+        resource_type: &Name,
+        context: Context,
+        pset: &PolicySet,
+        entities: &Entities,
+    ) -> HashSet<EntityUID> {
+        let q = Request::new_unchecked(
+            EntityUIDEntry::concrete(principal.clone(), None),
+            EntityUIDEntry::concrete(action.clone(), None),
+            EntityUIDEntry::Unknown { loc: None },
+            Some(context.clone()),
+        );
+        let response = self.is_authorized_core(q, pset, entities);
+        let resource_type = EntityType::Specified(resource_type.clone());
+        let candidates = entities
+            .iter()
+            .map(Entity::uid)
+            .filter(|uid| uid.entity_type() == &resource_type);
+        match response.decision() {
+            Some(Decision::Allow) => return candidates.cloned().collect(),
+            Some(Decision::Deny) => return HashSet::new(),
+            None => (),
+        }
+
+        // No policy was satisfied by a forbid, or else the decision would be
+        // `Deny` for every resource
+        let residuals = response
+            .residual_permits
+            .iter()
+            .chain(response.residual_forbids.iter())
+            .filter_map(|(id, (residual, _))| pset.get(id).map(|p| (p, residual)))
+            .collect::<Vec<_>>();
+        candidates
+            .filter(|uid| {
+                let q = Request::new_unchecked(
+                    EntityUIDEntry::concrete(principal.clone(), None),
+                    EntityUIDEntry::concrete(action.clone(), None),
+                    EntityUIDEntry::concrete((*uid).clone(), None),
+                    Some(context.clone()),
+                );
+                let mapping = HashMap::from([("resource".into(), Value::from((*uid).clone()))]);
+                let candidate = self.authorize_conditions(
+                    &self.evaluator(q, entities),
+                    residuals
+                        .iter()
+                        .map(|(p, residual)| (*p, residual.substitute(&mapping))),
+                    None,
+                );
+                !candidate.limit_exceeded()
+                    && candidate.satisfied_forbids.is_empty()
+                    && candidate.residual_forbids.is_empty()
+                    && !(response.satisfied_permits.is_empty()
+                        && candidate.satisfied_permits.is_empty())
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// The policies in `pset` that may apply to `q`, leaving out those whose scope
+/// the scope index rules out
+fn policies_in_scope<'a>(
+    q: &Request,
+    pset: &'a PolicySet,
+    entities: &Entities,
+) -> impl Iterator<Item = &'a Policy> {
+    select_in_scope(
+        pset,
+        [q.principal(), q.action(), q.resource()].map(|entry| match entry {
+            EntityUIDEntry::Unknown { .. } => ScopeLookup::Unknown,
+            EntityUIDEntry::Known { euid, .. } => match known_ancestors(euid, entities) {
+                Some(ancestors) => ScopeLookup::Entity {
+                    uid: euid,
+                    ancestors,
+                },
+                None => ScopeLookup::Unknown,
+            },
+        }),
+    )
+}
+
+/// The policies in `pset` whose scope may match the given lookups of the
+/// principal, action and resource.
+///
+/// If anything about the principal, action or resource is unknown, every
+/// policy is returned: a policy whose scope is ruled out by the other
+/// variables still evaluates to a residual rather than `false` (the evaluator
+/// doesn't drop the unknown part of the scope), so leaving it out would
+/// change the response.
+fn select_in_scope<'a>(
+    pset: &'a PolicySet,
+    lookups: [ScopeLookup<'_>; 3],
+) -> impl Iterator<Item = &'a Policy> {
+    if lookups.iter().any(|l| matches!(l, ScopeLookup::Unknown)) {
+        Either::Left(pset.policies())
+    } else {
+        let [principal, action, resource] = lookups;
+        Either::Right(pset.policies_in_scope(&principal, &action, &resource))
+    }
+}
+
+/// All the ancestors of `euid` in `entities`, or `None` if what it is in is
+/// not (fully) known
+fn known_ancestors<'e>(euid: &EntityUID, entities: &'e Entities) -> Option<Vec<&'e EntityUID>> {
+    match entities.entity(euid) {
+        // If the ancestors of the entity, or of any of its ancestors, are
+        // only partially known, so is what the entity is in
+        Dereference::Data(entity)
+            if std::iter::once(entity)
+                .chain(
+                    entity
+                        .ancestors()
+                        .filter_map(|ancestor| match entities.entity(ancestor) {
+                            Dereference::Data(ancestor) => Some(ancestor),
+                            _ => None,
+                        }),
+                )
+                .any(|e| e.unknown_ancestors().is_some()) =>
+        {
+            None
+        }
+        Dereference::Data(entity) => Some(entity.ancestors().collect()),
+        Dereference::NoSuchEntity => Some(Vec::new()),
+        // Whether the entity is in anything is itself unknown
+        Dereference::Residual(_) => None,
+    }
+}
+
+/// The ancestors of each principal, action and resource looked up in the scope
+/// index so far, for sharing between the requests of a batch
+struct ScopeCache<'e> {
+    entities: &'e Entities,
+    /// The result of [`known_ancestors()`] for each entity looked up
+    ancestors: HashMap<EntityUID, Option<Vec<&'e EntityUID>>>,
+}
+
+impl<'e> ScopeCache<'e> {
+    fn new(entities: &'e Entities) -> Self {
+        Self {
+            entities,
+            ancestors: HashMap::new(),
+        }
+    }
+
+    /// Like [`policies_in_scope()`], but looking up each entity only the
+    /// first time it's seen
+    fn policies_in_scope<'a>(&mut self, q: &Request, pset: &'a PolicySet) -> Vec<&'a Policy> {
+        let entries = [q.principal(), q.action(), q.resource()];
+        for entry in entries {
+            if let EntityUIDEntry::Known { euid, .. } = entry {
+                if !self.ancestors.contains_key(euid.as_ref()) {
+                    let ancestors = known_ancestors(euid, self.entities);
+                    self.ancestors.insert(euid.as_ref().clone(), ancestors);
+                }
+            }
+        }
+        let lookups = entries.map(|entry| match entry {
+            EntityUIDEntry::Known { euid, .. } => match self.ancestors.get(euid.as_ref()) {
+                Some(Some(ancestors)) => ScopeLookup::Entity {
+                    uid: euid,
+                    ancestors: ancestors.clone(),
+                },
+                _ => ScopeLookup::Unknown,
+            },
+            EntityUIDEntry::Unknown { .. } => ScopeLookup::Unknown,
+        });
+        select_in_scope(pset, lookups).collect()
+    }
 }
 
 impl Default for Authorizer {
@@ -179,7 +569,9 @@ impl std::fmt::Debug for Authorizer {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::evaluator::{EvaluationErrorKind, TraceOutcome};
     use crate::parser;
+    use cool_asserts::assert_matches;
 
     /// Sanity unit test case for is_authorized.
     /// More robust testing is accomplished through the integration tests.
@@ -257,6 +649,47 @@ mod test {
         assert_eq!(ans.decision, Decision::Deny);
     }
 
+    /// A forbid that exceeds the limits denies, even if a permit is satisfied
+    #[test]
+    fn limit_exceeded_denies() {
+        let q = Request::new(
+            (EntityUID::with_eid("p"), None),
+            (EntityUID::with_eid("a"), None),
+            (EntityUID::with_eid("r"), None),
+            Context::empty(),
+            None::<&RequestSchemaAllPass>,
+            Extensions::none(),
+        )
+        .unwrap();
+        let mut pset = PolicySet::new();
+        pset.add_static(true_policy("permit", Effect::Permit))
+            .unwrap();
+        pset.add_static(
+            parser::parse_policy(
+                Some("forbid".into()),
+                r#"forbid(principal, action, resource) when { [1, 2, 3, 4, 5].contains(1) };"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let entities = Entities::new();
+
+        let ans = Authorizer::new().is_authorized(q.clone(), &pset, &entities);
+        assert_eq!(ans.decision, Decision::Deny);
+
+        let a = Authorizer::new().with_limits(EvaluationLimits::new().with_max_collection_size(4));
+        let partial = a.is_authorized_core(q.clone(), &pset, &entities);
+        assert!(partial.limit_exceeded());
+        assert_eq!(partial.decision(), Some(Decision::Deny));
+        let ans = a.is_authorized(q, &pset, &entities);
+        assert_eq!(ans.decision, Decision::Deny);
+        assert!(ans.diagnostics.reason.is_empty());
+        assert_matches!(ans.diagnostics.errors.as_slice(), [AuthorizationError::PolicyEvaluationError { id, error }] => {
+            assert_eq!(id, &PolicyID::from_string("forbid"));
+            assert_matches!(error.error_kind(), EvaluationErrorKind::LimitExceeded(_));
+        });
+    }
+
     fn true_policy(id: &str, e: Effect) -> StaticPolicy {
         let pid = PolicyID::from_string(id);
         StaticPolicy::new(
@@ -593,6 +1026,594 @@ mod test {
         assert!(r.residual_permits.contains_key(&PolicyID::from_string("2")));
         assert!(r.residual_forbids.is_empty());
     }
+
+    #[test]
+    fn batch_matches_individual_requests() {
+        let uid = |s: &str| s.parse::<EntityUID>().unwrap();
+        let entities = Entities::from_entities(
+            [
+                Entity::new_with_attr_partial_value(
+                    uid(r#"User::"alice""#),
+                    [("level".into(), Value::from(5).into())]
+                        .into_iter()
+                        .collect(),
+                    [uid(r#"Group::"admins""#)].into_iter().collect(),
+                ),
+                Entity::new_with_attr_partial_value(
+                    uid(r#"User::"bob""#),
+                    [("level".into(), Value::from(1).into())]
+                        .into_iter()
+                        .collect(),
+                    HashSet::new(),
+                ),
+                Entity::with_uid(uid(r#"Group::"admins""#)),
+            ],
+            None::<&crate::entities::NoEntitiesSchema>,
+            crate::entities::TCComputation::ComputeNow,
+            Extensions::none(),
+        )
+        .unwrap();
+        let mut pset = PolicySet::new();
+        for (id, src) in [
+            (
+                "1",
+                r#"permit(principal in Group::"admins", action, resource);"#,
+            ),
+            (
+                "2",
+                r#"permit(principal, action, resource) when { principal.level > 3 };"#,
+            ),
+            (
+                "3",
+                r#"forbid(principal, action, resource == Doc::"secret");"#,
+            ),
+            (
+                "4",
+                r#"permit(principal, action, resource) when { resource.owner == principal };"#,
+            ),
+        ] {
+            pset.add_static(parser::parse_policy(Some(id.into()), src).unwrap())
+                .unwrap();
+        }
+        let requests = ["alice", "bob", "carol"]
+            .into_iter()
+            .flat_map(|p| ["public", "secret"].map(move |r| (p, r)))
+            .map(|(p, r)| {
+                Request::new(
+                    (uid(&format!(r#"User::"{p}""#)), None),
+                    (uid(r#"Action::"view""#), None),
+                    (uid(&format!(r#"Doc::"{r}""#)), None),
+                    Context::empty(),
+                    None::<&RequestSchemaAllPass>,
+                    Extensions::none(),
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let a = Authorizer::new();
+        let batch = a.is_authorized_batch(requests.clone(), &pset, &entities);
+        assert_eq!(batch.len(), requests.len());
+        for (q, response) in requests.into_iter().zip(batch) {
+            assert_eq!(response, a.is_authorized(q, &pset, &entities));
+        }
+    }
+
+    #[test]
+    fn batch_looks_up_each_entity_once() {
+        let uid = |s: &str| s.parse::<EntityUID>().unwrap();
+        let entities = Entities::from_entities(
+            [Entity::new_with_attr_partial_value(
+                uid(r#"User::"alice""#),
+                HashMap::new(),
+                [uid(r#"Group::"admins""#)].into_iter().collect(),
+            )],
+            None::<&crate::entities::NoEntitiesSchema>,
+            crate::entities::TCComputation::ComputeNow,
+            Extensions::none(),
+        )
+        .unwrap();
+        let mut pset = PolicySet::new();
+        for (id, src) in [
+            (
+                "1",
+                r#"permit(principal in Group::"admins", action, resource);"#,
+            ),
+            ("2", r#"permit(principal, action, resource == Doc::"b");"#),
+        ] {
+            pset.add_static(parser::parse_policy(Some(id.into()), src).unwrap())
+                .unwrap();
+        }
+
+        let mut scopes = ScopeCache::new(&entities);
+        for principal in [r#"User::"alice""#, r#"User::"bob""#] {
+            for resource in [r#"Doc::"a""#, r#"Doc::"b""#, r#"Doc::"c""#] {
+                let q = Request::new(
+                    (uid(principal), None),
+                    (uid(r#"Action::"view""#), None),
+                    (uid(resource), None),
+                    Context::empty(),
+                    None::<&RequestSchemaAllPass>,
+                    Extensions::none(),
+                )
+                .unwrap();
+                let ids = |policies: Vec<&Policy>| {
+                    policies
+                        .into_iter()
+                        .map(|p| p.id().clone())
+                        .collect::<HashSet<_>>()
+                };
+                assert_eq!(
+                    ids(scopes.policies_in_scope(&q, &pset)),
+                    ids(policies_in_scope(&q, &pset, &entities).collect())
+                );
+            }
+        }
+        // six requests, but only two principals, one action and three
+        // resources to look up
+        assert_eq!(scopes.ancestors.len(), 6);
+    }
+
+    #[test]
+    fn scope_index_matches_full_evaluation() {
+        let uid = |s: &str| s.parse::<EntityUID>().unwrap();
+        let entities = Entities::from_entities(
+            [
+                Entity::new_with_attr_partial_value(
+                    uid(r#"User::"alice""#),
+                    std::collections::HashMap::new(),
+                    [uid(r#"Group::"eng""#)].into_iter().collect(),
+                ),
+                Entity::new_with_attr_partial_value(
+                    uid(r#"Group::"eng""#),
+                    std::collections::HashMap::new(),
+                    [uid(r#"Group::"all""#)].into_iter().collect(),
+                ),
+                Entity::new_with_attr_partial_value(
+                    uid(r#"Action::"edit""#),
+                    std::collections::HashMap::new(),
+                    [uid(r#"Action::"write""#)].into_iter().collect(),
+                ),
+                Entity::new_with_attr_partial_value(
+                    uid(r#"User::"carol""#),
+                    std::collections::HashMap::new(),
+                    [uid(r#"Team::"web""#)].into_iter().collect(),
+                ),
+                Entity::with_uid(uid(r#"Team::"web""#)).with_unknown_ancestors("web_groups"),
+            ],
+            None::<&crate::entities::NoEntitiesSchema>,
+            crate::entities::TCComputation::ComputeNow,
+            Extensions::none(),
+        )
+        .unwrap();
+        let mut pset = PolicySet::new();
+        for (id, src) in [
+            (
+                "1",
+                r#"permit(principal in Group::"all", action in Action::"write", resource);"#,
+            ),
+            (
+                "2",
+                r#"permit(principal == User::"bob", action, resource is Doc);"#,
+            ),
+            (
+                "3",
+                r#"forbid(principal is User in Group::"eng", action == Action::"edit", resource);"#,
+            ),
+            (
+                "4",
+                r#"permit(principal, action, resource) when { context.missing };"#,
+            ),
+        ] {
+            pset.add_static(parser::parse_policy(Some(id.into()), src).unwrap())
+                .unwrap();
+        }
+
+        let a = Authorizer::new();
+        for principal in [
+            r#"User::"alice""#,
+            r#"User::"bob""#,
+            r#"User::"carol""#,
+            r#"Team::"web""#,
+            r#"Group::"eng""#,
+        ] {
+            for action in [r#"Action::"edit""#, r#"Action::"view""#] {
+                for resource in [r#"Doc::"a""#, r#"Photo::"b""#] {
+                    let q = Request::new(
+                        (uid(principal), None),
+                        (uid(action), None),
+                        (uid(resource), None),
+                        Context::empty(),
+                        None::<&RequestSchemaAllPass>,
+                        Extensions::none(),
+                    )
+                    .unwrap();
+                    let full = a
+                        .is_authorized_core(q.clone(), &pset, &entities)
+                        .concretize();
+                    let indexed = a.is_authorized(q, &pset, &entities);
+                    assert_eq!(indexed.decision, full.decision);
+                    assert_eq!(indexed.diagnostics.reason, full.diagnostics.reason);
+                    assert_eq!(
+                        indexed.diagnostics.errors.len(),
+                        full.diagnostics.errors.len()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn trace_explains_each_policy() {
+        let mut pset = PolicySet::new();
+        for (id, src) in [
+            (
+                "short",
+                r#"permit(principal, action, resource) when { principal == User::"alice" && context.admin };"#,
+            ),
+            (
+                "error",
+                r#"forbid(principal, action, resource) when { 1 + "one" == 2 };"#,
+            ),
+        ] {
+            pset.add_static(parser::parse_policy(Some(id.into()), src).unwrap())
+                .unwrap();
+        }
+        let q = Request::new(
+            (r#"User::"bob""#.parse().unwrap(), None),
+            (r#"Action::"view""#.parse().unwrap(), None),
+            (r#"Doc::"a""#.parse().unwrap(), None),
+            Context::empty(),
+            None::<&RequestSchemaAllPass>,
+            Extensions::none(),
+        )
+        .unwrap();
+        let entities = Entities::new();
+        let a = Authorizer::new();
+
+        let untraced = a.is_authorized(q.clone(), &pset, &entities);
+        assert_eq!(untraced.diagnostics.trace, None);
+        let traced = a.is_authorized_with_trace(q, &pset, &entities);
+        assert_eq!(traced.decision, untraced.decision);
+        assert_eq!(traced.diagnostics.reason, untraced.diagnostics.reason);
+        let trace = traced.diagnostics.trace.unwrap();
+        assert_eq!(trace.len(), 2);
+
+        // the first step is the whole condition, and the `&&` in the `when`
+        // clause stops after `principal == User::"alice"`
+        let short = trace.get(&PolicyID::from_string("short")).unwrap();
+        let first = short.steps().next().unwrap();
+        assert_eq!(first.depth(), 0);
+        assert_eq!(first.outcome(), &TraceOutcome::Value(false.into()));
+        let stopped = short.steps().filter(|s| s.short_circuited()).collect_vec();
+        assert_eq!(stopped.len(), 1);
+        assert!(stopped[0].expr().to_string().contains(r#"User::"alice""#));
+        assert_eq!(stopped[0].outcome(), &TraceOutcome::Value(false.into()));
+        assert!(short
+            .steps()
+            .all(|s| !s.expr().to_string().starts_with("context")));
+
+        let error = trace.get(&PolicyID::from_string("error")).unwrap();
+        assert_matches!(
+            error.steps().next().unwrap().outcome(),
+            TraceOutcome::Error(_)
+        );
+    }
+
+    /// `EntityLoader` backed by a map, which records the batches it was asked for
+    #[cfg(feature = "partial-eval")]
+    struct MapLoader {
+        entities: std::collections::HashMap<EntityUID, Entity>,
+        batches: Vec<HashSet<EntityUID>>,
+    }
+
+    #[cfg(feature = "partial-eval")]
+    impl MapLoader {
+        fn new(entities: impl IntoIterator<Item = Entity>) -> Self {
+            Self {
+                entities: entities.into_iter().map(|e| (e.uid().clone(), e)).collect(),
+                batches: Vec::new(),
+            }
+        }
+    }
+
+    #[cfg(feature = "partial-eval")]
+    impl EntityLoader for MapLoader {
+        type Error = std::convert::Infallible;
+
+        fn load_entities(&mut self, uids: &[&EntityUID]) -> Result<Vec<Entity>, Self::Error> {
+            self.batches
+                .push(uids.iter().map(|uid| (*uid).clone()).collect());
+            Ok(uids
+                .iter()
+                .filter_map(|uid| self.entities.get(*uid).cloned())
+                .collect())
+        }
+    }
+
+    #[cfg(feature = "partial-eval")]
+    fn loader_entities() -> Vec<Entity> {
+        let uid = |s: &str| s.parse::<EntityUID>().unwrap();
+        vec![
+            Entity::new_with_attr_partial_value(
+                uid(r#"User::"alice""#),
+                [("manager".into(), Value::from(uid(r#"User::"bob""#)).into())]
+                    .into_iter()
+                    .collect(),
+                [uid(r#"Group::"eng""#)].into_iter().collect(),
+            ),
+            Entity::new_with_attr_partial_value(
+                uid(r#"User::"bob""#),
+                [("level".into(), Value::from(5).into())]
+                    .into_iter()
+                    .collect(),
+                HashSet::new(),
+            ),
+            Entity::new_with_attr_partial_value(
+                uid(r#"User::"carol""#),
+                [("level".into(), Value::from(1).into())]
+                    .into_iter()
+                    .collect(),
+                HashSet::new(),
+            ),
+            Entity::with_uid(uid(r#"Group::"all""#)),
+            Entity::new_with_attr_partial_value(
+                uid(r#"Group::"eng""#),
+                std::collections::HashMap::new(),
+                [uid(r#"Group::"all""#)].into_iter().collect(),
+            ),
+        ]
+    }
+
+    #[cfg(feature = "partial-eval")]
+    #[test]
+    fn loader_fetches_reached_entities() {
+        let q = Request::new(
+            (r#"User::"alice""#.parse().unwrap(), None),
+            (r#"Action::"view""#.parse().unwrap(), None),
+            (r#"Photo::"vacation""#.parse().unwrap(), None),
+            Context::empty(),
+            None::<&RequestSchemaAllPass>,
+            Extensions::none(),
+        )
+        .unwrap();
+        let a = Authorizer::new();
+        let mut pset = PolicySet::new();
+        let src = r#"
+        permit(principal in Group::"all", action, resource) when { principal.manager.level > 3 };
+        "#;
+        pset.add_static(parser::parse_policy(Some("1".into()), src).unwrap())
+            .unwrap();
+
+        let mut loader = MapLoader::new(loader_entities());
+        let r = a
+            .is_authorized_with_loader(q.clone(), &pset, &mut loader)
+            .unwrap();
+        assert_eq!(r.decision(), Some(Decision::Allow));
+        // `Group::"all"` is a declared parent of `Group::"eng"`, so `in` is
+        // decided without loading it; no requests for entities the policy
+        // never reaches
+        let batch = |uids: &[&str]| -> HashSet<EntityUID> {
+            uids.iter().map(|uid| uid.parse().unwrap()).collect()
+        };
+        assert_eq!(
+            loader.batches,
+            vec![
+                batch(&[r#"User::"alice""#]),
+                batch(&[r#"User::"bob""#, r#"Group::"eng""#]),
+            ]
+        );
+
+        // deciding that `principal` is *not* in a group needs its whole
+        // hierarchy, which is loaded one level at a time
+        let mut not_in = PolicySet::new();
+        let src = r#"
+        forbid(principal in Group::"banned", action, resource);
+        "#;
+        not_in
+            .add_static(parser::parse_policy(Some("3".into()), src).unwrap())
+            .unwrap();
+        let mut loader = MapLoader::new(loader_entities());
+        let r = a
+            .is_authorized_with_loader(q.clone(), &not_in, &mut loader)
+            .unwrap();
+        assert!(r.false_forbids.contains_key(&PolicyID::from_string("3")));
+        assert_eq!(
+            loader.batches,
+            vec![
+                batch(&[r#"User::"alice""#]),
+                batch(&[r#"Group::"eng""#]),
+                batch(&[r#"Group::"all""#]),
+            ]
+        );
+
+        // the resource doesn't exist, so this policy is left as a residual
+        let src = r#"
+        forbid(principal, action, resource) when { resource.private };
+        "#;
+        pset.add_static(parser::parse_policy(Some("2".into()), src).unwrap())
+            .unwrap();
+        let mut loader = MapLoader::new(loader_entities());
+        let r = a.is_authorized_with_loader(q, &pset, &mut loader).unwrap();
+        assert_eq!(r.decision(), None);
+        assert!(r.residual_forbids.contains_key(&PolicyID::from_string("2")));
+        assert!(r
+            .satisfied_permits
+            .contains_key(&PolicyID::from_string("1")));
+        assert_eq!(
+            loader
+                .batches
+                .iter()
+                .filter(|batch| batch.contains(&r#"Photo::"vacation""#.parse().unwrap()))
+                .count(),
+            1
+        );
+    }
+
+    #[cfg(feature = "partial-eval")]
+    #[test]
+    fn loader_ignores_user_unknowns() {
+        // an unknown the user named like an entity UID is not a missing entity
+        let bob: EntityUID = r#"User::"bob""#.parse().unwrap();
+        let context = Context::from_pairs(
+            [(
+                "manager".into(),
+                RestrictedExpr::unknown(Unknown::new_with_type(
+                    bob.to_string(),
+                    Type::Entity {
+                        ty: bob.entity_type().clone(),
+                    },
+                )),
+            )],
+            Extensions::none(),
+        )
+        .unwrap();
+        let q = Request::new(
+            (r#"User::"carol""#.parse().unwrap(), None),
+            (r#"Action::"view""#.parse().unwrap(), None),
+            (r#"Photo::"vacation""#.parse().unwrap(), None),
+            context,
+            None::<&RequestSchemaAllPass>,
+            Extensions::none(),
+        )
+        .unwrap();
+        let mut pset = PolicySet::new();
+        let src = r#"
+        permit(principal, action, resource) when { context.manager.level > 3 };
+        "#;
+        pset.add_static(parser::parse_policy(Some("1".into()), src).unwrap())
+            .unwrap();
+        let mut loader = MapLoader::new(loader_entities());
+        let r = Authorizer::new()
+            .is_authorized_with_loader(q, &pset, &mut loader)
+            .unwrap();
+        assert_eq!(r.decision(), None);
+        assert!(loader.batches.is_empty());
+    }
+
+    #[cfg(feature = "partial-eval")]
+    #[test]
+    fn loader_errors() {
+        #[derive(Debug, thiserror::Error)]
+        #[error("connection refused")]
+        struct Refused;
+
+        struct FailingLoader;
+        impl EntityLoader for FailingLoader {
+            type Error = Refused;
+            fn load_entities(&mut self, _: &[&EntityUID]) -> Result<Vec<Entity>, Self::Error> {
+                Err(Refused)
+            }
+        }
+
+        let q = Request::new(
+            (EntityUID::with_eid("p"), None),
+            (EntityUID::with_eid("a"), None),
+            (EntityUID::with_eid("r"), None),
+            Context::empty(),
+            None::<&RequestSchemaAllPass>,
+            Extensions::none(),
+        )
+        .unwrap();
+        let mut pset = PolicySet::new();
+        let src = r#"
+        permit(principal, action, resource) when { principal.admin };
+        "#;
+        pset.add_static(parser::parse_policy(Some("1".into()), src).unwrap())
+            .unwrap();
+        let err = Authorizer::new()
+            .is_authorized_with_loader(q, &pset, &mut FailingLoader)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to load entities: connection refused"
+        );
+    }
+
+    #[cfg(feature = "partial-eval")]
+    #[test]
+    fn list_resources() {
+        let uid = |s: &str| s.parse::<EntityUID>().unwrap();
+        let doc = |id: &str, owner: &str, folder: &str| {
+            Entity::new_with_attr_partial_value(
+                uid(id),
+                [("owner".into(), Value::from(uid(owner)).into())]
+                    .into_iter()
+                    .collect(),
+                [uid(folder)].into_iter().collect(),
+            )
+        };
+        let entities = Entities::from_entities(
+            [
+                doc(r#"Doc::"a""#, r#"User::"alice""#, r#"Folder::"public""#),
+                doc(r#"Doc::"b""#, r#"User::"bob""#, r#"Folder::"public""#),
+                doc(r#"Doc::"c""#, r#"User::"bob""#, r#"Folder::"secret""#),
+                doc(r#"Doc::"d""#, r#"User::"bob""#, r#"Folder::"private""#),
+                Entity::with_uid(uid(r#"Folder::"public""#)),
+                Entity::with_uid(uid(r#"Folder::"secret""#)),
+                Entity::with_uid(uid(r#"User::"alice""#)),
+                Entity::with_uid(uid(r#"User::"bob""#)),
+            ],
+            None::<&crate::entities::NoEntitiesSchema>,
+            crate::entities::TCComputation::ComputeNow,
+            Extensions::none(),
+        )
+        .unwrap();
+        let mut pset = PolicySet::new();
+        let list = |pset: &PolicySet, principal: &str| {
+            Authorizer::new().list_resources(
+                uid(principal),
+                uid(r#"Action::"view""#),
+                &"Doc".parse().unwrap(),
+                Context::empty(),
+                pset,
+                &entities,
+            )
+        };
+        let docs = |ids: &[&str]| -> HashSet<EntityUID> {
+            ids.iter()
+                .map(|id| uid(&format!(r#"Doc::"{id}""#)))
+                .collect()
+        };
+        assert_eq!(list(&pset, r#"User::"alice""#), docs(&[]));
+
+        for (id, src) in [
+            (
+                "public",
+                r#"permit(principal, action, resource in Folder::"public");"#,
+            ),
+            (
+                "owner",
+                r#"permit(principal, action, resource) when { resource.owner == principal };"#,
+            ),
+            (
+                "secret",
+                r#"forbid(principal, action, resource in Folder::"secret") unless { principal == User::"bob" };"#,
+            ),
+        ] {
+            pset.add_static(parser::parse_policy(Some(id.into()), src).unwrap())
+                .unwrap();
+        }
+        assert_eq!(list(&pset, r#"User::"alice""#), docs(&["a", "b"]));
+        assert_eq!(list(&pset, r#"User::"bob""#), docs(&["a", "b", "c", "d"]));
+
+        // a decision that doesn't depend on the resource holds for all of them
+        let src = r#"forbid(principal == User::"alice", action, resource);"#;
+        pset.add_static(parser::parse_policy(Some("alice".into()), src).unwrap())
+            .unwrap();
+        let admin = PolicySet::try_from_iter([parser::parse_policy(
+            Some("admin".into()),
+            r#"permit(principal == User::"admin", action, resource);"#,
+        )
+        .unwrap()
+        .into()])
+        .unwrap();
+        assert_eq!(
+            list(&admin, r#"User::"admin""#),
+            docs(&["a", "b", "c", "d"])
+        );
+        assert_eq!(list(&pset, r#"User::"alice""#), docs(&[]));
+    }
 }
 // by default, Coverlay does not track coverage for lines after a line
 // containing #[cfg(test)].
@@ -632,6 +1653,9 @@ pub struct Diagnostics {
     pub reason: HashSet<PolicyID>,
     /// List of errors that occurred
     pub errors: Vec<AuthorizationError>,
+    /// Trace of the evaluation of each policy, if the response was produced
+    /// by [`Authorizer::is_authorized_with_trace()`]
+    pub trace: Option<HashMap<PolicyID, EvaluationTrace>>,
 }
 
 impl Response {
@@ -643,9 +1667,19 @@ impl Response {
     ) -> Self {
         Response {
             decision,
-            diagnostics: Diagnostics { reason, errors },
+            diagnostics: Diagnostics {
+                reason,
+                errors,
+                trace: None,
+            },
         }
     }
+
+    /// Attach the trace of the evaluation of each policy to this `Response`
+    pub fn with_trace(mut self, trace: HashMap<PolicyID, EvaluationTrace>) -> Self {
+        self.diagnostics.trace = Some(trace);
+        self
+    }
 }
 
 /// Decision returned from the `Authorizer`
@@ -0,0 +1,357 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains an authorizer that caches its responses.
+
+use super::{policies_in_scope, AuthorizationError, Authorizer, Response};
+use crate::ast::{Context, Entity, EntityUID, PolicySet, Request};
+use crate::entities::{Dereference, Entities};
+use crate::evaluator::{EvaluationErrorKind, LimitExceededError};
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// An [`Authorizer`] for a fixed [`PolicySet`] that caches its responses.
+///
+/// A cached response is reused for a later request with the same principal,
+/// action, resource and context, but only if every entity that was looked up
+/// while computing it (including entities that turned out not to exist) is
+/// unchanged in the `Entities` given with the later request. So the response
+/// is always the one [`Authorizer::is_authorized()`] would return. Replacing
+/// the policies with [`CachingAuthorizer::set_policies()`] clears the cache.
+///
+/// Requests with unknowns are never cached, and neither are responses in which
+/// a policy hit the evaluation timeout, as that depends on more than the
+/// request and the entities.
+#[derive(Debug)]
+pub struct CachingAuthorizer {
+    authorizer: Authorizer,
+    policies: PolicySet,
+    /// Maximum number of cached responses
+    capacity: usize,
+    cache: Mutex<Cache>,
+}
+
+/// The cached responses of a `CachingAuthorizer`
+#[derive(Debug, Default)]
+struct Cache {
+    /// Cached responses, grouped by principal, action and resource
+    entries: HashMap<(EntityUID, EntityUID, EntityUID), Vec<CachedResponse>>,
+    /// Total number of cached responses
+    len: usize,
+}
+
+/// A response cached by a `CachingAuthorizer`
+#[derive(Debug)]
+struct CachedResponse {
+    /// Context of the request
+    context: Context,
+    /// Every entity looked up while computing `response`, as it was at the
+    /// time, or `None` if it didn't exist
+    reads: Vec<(EntityUID, Option<Entity>)>,
+    response: Response,
+}
+
+impl CachedResponse {
+    /// Are all the entities read for this response the same in `entities`?
+    fn is_valid_for(&self, entities: &Entities) -> bool {
+        self.reads
+            .iter()
+            .all(|(uid, read)| match (entities.entity(uid), read) {
+                (Dereference::Data(current), Some(read)) => current.deep_eq(read),
+                (Dereference::NoSuchEntity, None) => true,
+                _ => false,
+            })
+    }
+}
+
+impl CachingAuthorizer {
+    /// Maximum number of cached responses, unless set with
+    /// [`CachingAuthorizer::with_capacity()`]
+    pub const DEFAULT_CAPACITY: usize = 10_000;
+
+    /// Create a `CachingAuthorizer` which answers requests with `authorizer`
+    /// against `policies`
+    pub fn new(authorizer: Authorizer, policies: PolicySet) -> Self {
+        Self {
+            authorizer,
+            policies,
+            capacity: Self::DEFAULT_CAPACITY,
+            cache: Mutex::new(Cache::default()),
+        }
+    }
+
+    /// Set the maximum number of cached responses. When the cache is full,
+    /// it is emptied before the next response is added.
+    pub fn with_capacity(self, capacity: usize) -> Self {
+        Self { capacity, ..self }
+    }
+
+    /// The policies requests are authorized against
+    pub fn policies(&self) -> &PolicySet {
+        &self.policies
+    }
+
+    /// Replace the policies requests are authorized against, clearing the
+    /// cache
+    pub fn set_policies(&mut self, policies: PolicySet) {
+        self.policies = policies;
+        self.clear();
+    }
+
+    /// Remove all cached responses
+    pub fn clear(&self) {
+        *self.lock() = Cache::default();
+    }
+
+    /// Number of cached responses
+    pub fn len(&self) -> usize {
+        self.lock().len
+    }
+
+    /// Is the cache empty?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an authorization response for `q` with respect to the policies
+    /// of this `CachingAuthorizer` and `entities`, reusing a cached response
+    /// if it is still valid.
+    pub fn is_authorized(&self, q: Request, entities: &Entities) -> Response {
+        let (key, context) = match (
+            q.principal().uid(),
+            q.action().uid(),
+            q.resource().uid(),
+            q.context(),
+        ) {
+            (Some(p), Some(a), Some(r), Some(context)) => {
+                ((p.clone(), a.clone(), r.clone()), context.clone())
+            }
+            _ => return self.authorizer.is_authorized(q, &self.policies, entities),
+        };
+        if let Some(cached) = self.lock().entries.get(&key).and_then(|cached| {
+            cached
+                .iter()
+                .find(|c| c.context == context && c.is_valid_for(entities))
+        }) {
+            return cached.response.clone();
+        }
+
+        // The scope index looks up the principal, action and resource
+        let mut reads: HashSet<EntityUID> = [&key.0, &key.1, &key.2].into_iter().cloned().collect();
+        let policies = policies_in_scope(&q, &self.policies, entities).collect_vec();
+        let eval = self.authorizer.evaluator(q, entities).record_entity_reads();
+        let response = self
+            .authorizer
+            .authorize_conditions(
+                &eval,
+                policies.into_iter().map(|p| (p, p.condition())),
+                None,
+            )
+            .concretize();
+        reads.extend(eval.take_entity_reads().unwrap_or_default());
+
+        if self.capacity > 0 && !timed_out(&response) {
+            let reads = reads
+                .into_iter()
+                .map(|uid| {
+                    let entity = match entities.entity(&uid) {
+                        Dereference::Data(entity) => Some(entity.clone()),
+                        Dereference::NoSuchEntity | Dereference::Residual(_) => None,
+                    };
+                    (uid, entity)
+                })
+                .collect();
+            let mut cache = self.lock();
+            if cache.len >= self.capacity {
+                *cache = Cache::default();
+            }
+            let cached = cache.entries.entry(key).or_default();
+            // drop the stale response for the same request, if any
+            let stale = cached.len();
+            cached.retain(|c| c.context != context);
+            let removed = stale - cached.len();
+            cached.push(CachedResponse {
+                context,
+                reads,
+                response: response.clone(),
+            });
+            cache.len = cache.len - removed + 1;
+        }
+        response
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Cache> {
+        // The cache is never left inconsistent, so it's still usable if
+        // another thread panicked while holding the lock
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Did any policy hit the evaluation timeout while computing `response`?
+fn timed_out(response: &Response) -> bool {
+    response.diagnostics.errors.iter().any(|err| match err {
+        AuthorizationError::PolicyEvaluationError { error, .. } => matches!(
+            error.error_kind(),
+            EvaluationErrorKind::LimitExceeded(LimitExceededError::Timeout { .. })
+        ),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::{PolicyID, RequestSchemaAllPass, Value};
+    use crate::authorizer::Decision;
+    use crate::entities::{NoEntitiesSchema, TCComputation};
+    use crate::extensions::Extensions;
+    use crate::parser;
+
+    fn uid(s: &str) -> EntityUID {
+        s.parse().unwrap()
+    }
+
+    fn doc_owned_by_alice(doc: &str) -> Entity {
+        Entity::new_with_attr_partial_value(
+            uid(doc),
+            [("owner".into(), Value::from(uid(r#"User::"alice""#)).into())]
+                .into_iter()
+                .collect(),
+            HashSet::new(),
+        )
+    }
+
+    fn request(resource: &str, context: Context) -> Request {
+        Request::new(
+            (uid(r#"User::"alice""#), None),
+            (uid(r#"Action::"view""#), None),
+            (uid(resource), None),
+            context,
+            None::<&RequestSchemaAllPass>,
+            Extensions::none(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn responses_follow_entity_changes() {
+        let mut pset = PolicySet::new();
+        for (id, src) in [
+            (
+                "owner",
+                r#"permit(principal, action, resource) when { resource.owner == principal };"#,
+            ),
+            (
+                "banned",
+                r#"forbid(principal in Group::"banned", action, resource);"#,
+            ),
+        ] {
+            pset.add_static(parser::parse_policy(Some(id.into()), src).unwrap())
+                .unwrap();
+        }
+        let entities = Entities::from_entities(
+            [
+                doc_owned_by_alice(r#"Doc::"a""#),
+                Entity::with_uid(uid(r#"User::"alice""#)),
+            ],
+            None::<&NoEntitiesSchema>,
+            TCComputation::ComputeNow,
+            Extensions::none(),
+        )
+        .unwrap();
+        let upsert = |mut entities: Entities, entity: Entity| {
+            entities
+                .upsert_entities([entity], None::<&NoEntitiesSchema>, Extensions::none())
+                .unwrap();
+            entities
+        };
+        let mut cache = CachingAuthorizer::new(Authorizer::new(), pset);
+
+        let allowed = cache.is_authorized(request(r#"Doc::"a""#, Context::empty()), &entities);
+        assert_eq!(allowed.decision, Decision::Allow);
+        assert_eq!(
+            cache.is_authorized(request(r#"Doc::"a""#, Context::empty()), &entities),
+            allowed
+        );
+        assert_eq!(cache.len(), 1);
+
+        // an entity that isn't read doesn't matter
+        let entities = upsert(entities, Entity::with_uid(uid(r#"User::"bob""#)));
+        assert_eq!(
+            cache.is_authorized(request(r#"Doc::"a""#, Context::empty()), &entities),
+            allowed
+        );
+
+        // but an entity that is read does
+        let banned_alice = Entity::new_with_attr_partial_value(
+            uid(r#"User::"alice""#),
+            std::collections::HashMap::new(),
+            [uid(r#"Group::"banned""#)].into_iter().collect(),
+        );
+        let banned = upsert(entities.clone(), banned_alice);
+        let denied = cache.is_authorized(request(r#"Doc::"a""#, Context::empty()), &banned);
+        assert_eq!(denied.decision, Decision::Deny);
+        assert_eq!(
+            denied.diagnostics.reason,
+            HashSet::from([PolicyID::from_string("banned")])
+        );
+        assert_eq!(cache.len(), 1);
+
+        // as does an entity that didn't exist
+        let missing = cache.is_authorized(request(r#"Doc::"b""#, Context::empty()), &entities);
+        assert_eq!(missing.decision, Decision::Deny);
+        assert_eq!(missing.diagnostics.errors.len(), 1);
+        let entities = upsert(entities, doc_owned_by_alice(r#"Doc::"b""#));
+        assert_eq!(
+            cache
+                .is_authorized(request(r#"Doc::"b""#, Context::empty()), &entities)
+                .decision,
+            Decision::Allow
+        );
+
+        // the context is part of the request
+        let context = Context::from_pairs(
+            [("ip".into(), Value::from("10.0.0.1").into())],
+            Extensions::none(),
+        )
+        .unwrap();
+        cache.is_authorized(request(r#"Doc::"a""#, context), &entities);
+        assert_eq!(cache.len(), 3);
+
+        cache.set_policies(PolicySet::new());
+        assert!(cache.is_empty());
+        assert_eq!(
+            cache
+                .is_authorized(request(r#"Doc::"a""#, Context::empty()), &entities)
+                .decision,
+            Decision::Deny
+        );
+    }
+
+    #[test]
+    fn capacity() {
+        let entities = Entities::new();
+        let cache = CachingAuthorizer::new(Authorizer::new(), PolicySet::new()).with_capacity(2);
+        for resource in [r#"Doc::"a""#, r#"Doc::"b""#, r#"Doc::"c""#] {
+            cache.is_authorized(request(resource, Context::empty()), &entities);
+        }
+        assert_eq!(cache.len(), 1);
+
+        let cache = CachingAuthorizer::new(Authorizer::new(), PolicySet::new()).with_capacity(0);
+        cache.is_authorized(request(r#"Doc::"a""#, Context::empty()), &entities);
+        assert!(cache.is_empty());
+    }
+}
@@ -14,17 +14,21 @@
  * limitations under the License.
  */
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use either::Either;
 use smol_str::SmolStr;
 use std::sync::Arc;
 
 use super::{
-    Annotations, AuthorizationError, Authorizer, Decision, Effect, Expr, Policy, PolicySet,
-    PolicySetError, Request, Response, Value,
+    Annotations, AuthorizationError, Authorizer, Decision, Effect, Expr, ExprKind, Literal, Policy,
+    PolicySet, PolicySetError, Request, Response, Value,
+};
+use crate::{
+    ast::PolicyID,
+    entities::Entities,
+    evaluator::{EvaluationError, EvaluationErrorKind},
 };
-use crate::{ast::PolicyID, entities::Entities, evaluator::EvaluationError};
 
 type PolicyComponents<'a> = (Effect, &'a PolicyID, &'a Arc<Expr>, &'a Arc<Annotations>);
 
@@ -89,6 +93,37 @@ impl PartialResponse {
         }
     }
 
+    /// Simplify every residual with [`Expr::simplify()`]. A residual that
+    /// simplifies to `true` or `false` moves to the satisfied or not
+    /// satisfied policies, so the response may now reach a decision.
+    pub fn simplified(&self) -> Self {
+        let mut simplified = self.clone();
+        simplified.residual_permits = simplify_residuals(
+            &self.residual_permits,
+            &mut simplified.satisfied_permits,
+            &mut simplified.false_permits,
+        );
+        simplified.residual_forbids = simplify_residuals(
+            &self.residual_forbids,
+            &mut simplified.satisfied_forbids,
+            &mut simplified.false_forbids,
+        );
+        simplified
+    }
+
+    /// Replace the residual policies of this response with their results in
+    /// `other`, which must be a response for exactly those policies
+    #[cfg(feature = "partial-eval")]
+    pub(crate) fn replace_residuals(&mut self, other: Self) {
+        self.residual_permits = other.residual_permits;
+        self.residual_forbids = other.residual_forbids;
+        self.satisfied_permits.extend(other.satisfied_permits);
+        self.satisfied_forbids.extend(other.satisfied_forbids);
+        self.false_permits.extend(other.false_permits);
+        self.false_forbids.extend(other.false_forbids);
+        self.errors.extend(other.errors);
+    }
+
     /// Convert this response into a concrete evaluation response.
     /// All residuals are treated as errors
     pub fn concretize(self) -> Response {
@@ -98,6 +133,9 @@ impl PartialResponse {
     /// Attempt to reach a partial decision; the presence of residuals may result in returning [`None`],
     /// indicating that a decision could not be reached given the unknowns
     pub fn decision(&self) -> Option<Decision> {
+        if self.limit_exceeded() {
+            return Some(Decision::Deny);
+        }
         match (
             !self.satisfied_forbids.is_empty(),
             !self.satisfied_permits.is_empty(),
@@ -117,6 +155,19 @@ impl PartialResponse {
         }
     }
 
+    /// Returns `true` if evaluating any policy exceeded the
+    /// [`crate::evaluator::EvaluationLimits`] of the `Authorizer`.
+    ///
+    /// Such a policy might have been a satisfied forbid, so the decision is
+    /// `Deny` whatever the other policies evaluated to.
+    pub fn limit_exceeded(&self) -> bool {
+        self.errors.iter().any(|err| match err {
+            AuthorizationError::PolicyEvaluationError { error, .. } => {
+                matches!(error.error_kind(), EvaluationErrorKind::LimitExceeded(_))
+            }
+        })
+    }
+
     /// All of the [`Effect::Permit`] policies that were known to be satisfied
     fn definitely_satisfied_permits(&self) -> impl Iterator<Item = Policy> + '_ {
         self.satisfied_permits.iter().map(|(id, annotations)| {
@@ -343,6 +394,10 @@ impl PartialResponse {
 
 impl From<PartialResponse> for Response {
     fn from(p: PartialResponse) -> Self {
+        if p.limit_exceeded() {
+            // No policy determined the decision
+            return Response::new(Decision::Deny, HashSet::new(), p.errors().collect());
+        }
         let decision = if !p.satisfied_permits.is_empty() && p.satisfied_forbids.is_empty() {
             Decision::Allow
         } else {
@@ -384,6 +439,31 @@ fn map_unknowns<'a>(
     }
 }
 
+/// Simplify `residuals`, moving those that simplify to `true` into `trues`
+/// and those that simplify to `false` into `falses`, and return the rest
+fn simplify_residuals(
+    residuals: &HashMap<PolicyID, (Arc<Expr>, Arc<Annotations>)>,
+    trues: &mut HashMap<PolicyID, Arc<Annotations>>,
+    falses: &mut HashMap<PolicyID, (ErrorState, Arc<Annotations>)>,
+) -> HashMap<PolicyID, (Arc<Expr>, Arc<Annotations>)> {
+    let mut remaining = HashMap::new();
+    for (id, (residual, annotations)) in residuals {
+        let residual = residual.simplify();
+        match residual.expr_kind() {
+            ExprKind::Lit(Literal::Bool(true)) => {
+                trues.insert(id.clone(), annotations.clone());
+            }
+            ExprKind::Lit(Literal::Bool(false)) => {
+                falses.insert(id.clone(), (ErrorState::NoError, annotations.clone()));
+            }
+            _ => {
+                remaining.insert(id.clone(), (Arc::new(residual), annotations.clone()));
+            }
+        }
+    }
+    remaining
+}
+
 /// Checks if a given residual record did error, returning the [`PolicyID`] if it did
 fn did_error<'a>(
     (id, (state, _)): (&'a PolicyID, &'_ (ErrorState, Arc<Annotations>)),
@@ -641,4 +721,46 @@ mod test {
             None,
         );
     }
+
+    #[test]
+    fn simplified() {
+        let x = || Expr::unknown(crate::ast::Unknown::new_untyped("x"));
+        let residual = |e: Expr| (Arc::new(e), Arc::default());
+        let pr = PartialResponse::new(
+            [],
+            [],
+            [(
+                PolicyID::from_string("permit"),
+                residual(Expr::and(
+                    Expr::val(true),
+                    Expr::and(Expr::is_eq(x(), Expr::val(1)), Expr::val(true)),
+                )),
+            )],
+            [],
+            [],
+            [(
+                PolicyID::from_string("forbid"),
+                residual(Expr::ite(
+                    Expr::less(Expr::val(1), Expr::val(2)),
+                    Expr::val(false),
+                    x(),
+                )),
+            )],
+            [],
+        );
+        assert_eq!(pr.decision(), None);
+        let simplified = pr.simplified();
+        assert_eq!(simplified.decision(), None);
+        assert_eq!(
+            simplified
+                .get(&PolicyID::from_string("permit"))
+                .unwrap()
+                .non_scope_constraints(),
+            &Expr::is_eq(x(), Expr::val(1))
+        );
+        assert!(simplified.residual_forbids.is_empty());
+        assert!(simplified
+            .false_forbids
+            .contains_key(&PolicyID::from_string("forbid")));
+    }
 }
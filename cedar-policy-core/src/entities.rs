@@ -18,8 +18,8 @@
 
 use crate::ast::*;
 use crate::extensions::Extensions;
-use crate::transitive_closure::{compute_tc, enforce_tc_and_dag};
-use std::collections::{hash_map, HashMap};
+use crate::transitive_closure::{compute_tc, enforce_tc_and_dag, TcError};
+use std::collections::{hash_map, HashMap, HashSet};
 use std::fmt::Write;
 use std::sync::Arc;
 
@@ -32,6 +32,10 @@ pub mod conformance;
 pub mod err;
 pub mod json;
 use json::err::JsonSerializationError;
+#[cfg(feature = "partial-eval")]
+mod loader;
+#[cfg(feature = "partial-eval")]
+pub use loader::EntityLoader;
 
 pub use json::{
     schematype_of_partialvalue, schematype_of_restricted_expr, AllEntitiesNoAttrsSchema,
@@ -50,7 +54,7 @@ use err::*;
 /// FFI layer in DRT. All others use (and should use) the `from_json_*()` and
 /// `write_to_json()` methods as necessary.
 #[serde_as]
-#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct Entities {
     /// Serde cannot serialize a HashMap to JSON when the key to the map cannot
     /// be serialized to a JSON string. This is a limitation of the JSON format.
@@ -70,14 +74,31 @@ pub struct Entities {
     #[serde(skip_deserializing)]
     #[serde(skip_serializing)]
     mode: Mode,
+
+    /// For each entity UID, the entities which have it among their ancestors.
+    /// Built by the first incremental update of the hierarchy, so that later
+    /// updates only visit the descendants of the entities they change, and
+    /// dropped by anything else that changes the hierarchy.
+    #[serde(skip)]
+    descendants: Option<HashMap<EntityUID, HashSet<EntityUID>>>,
+}
+
+impl PartialEq for Entities {
+    fn eq(&self, other: &Self) -> bool {
+        // `descendants` is derived from `entities`
+        self.entities == other.entities && self.mode == other.mode
+    }
 }
 
+impl Eq for Entities {}
+
 impl Entities {
     /// Create a fresh `Entities` with no entities
     pub fn new() -> Self {
         Self {
             entities: HashMap::new(),
             mode: Mode::default(),
+            descendants: None,
         }
     }
 
@@ -89,6 +110,7 @@ impl Entities {
         Self {
             entities: self.entities,
             mode: Mode::Partial,
+            descendants: self.descendants,
         }
     }
 
@@ -99,12 +121,9 @@ impl Entities {
             None => match self.mode {
                 Mode::Concrete => Dereference::NoSuchEntity,
                 #[cfg(feature = "partial-eval")]
-                Mode::Partial => Dereference::Residual(Expr::unknown(Unknown::new_with_type(
-                    format!("{uid}"),
-                    Type::Entity {
-                        ty: uid.entity_type().clone(),
-                    },
-                ))),
+                Mode::Partial => {
+                    Dereference::Residual(Expr::unknown(Unknown::missing_entity(uid.clone())))
+                }
             },
         }
     }
@@ -151,9 +170,123 @@ impl Entities {
             TCComputation::EnforceAlreadyComputed => enforce_tc_and_dag(&self.entities)?,
             TCComputation::ComputeNow => compute_tc(&mut self.entities, true)?,
         };
+        self.descendants = None;
         Ok(self)
     }
 
+    /// Adds the [`crate::ast::Entity`]s in the iterator to this [`Entities`],
+    /// replacing any existing entities with the same UIDs. If the iterator
+    /// contains several entities with the same UID, the last one wins.
+    ///
+    /// Unlike [`Entities::add_entities()`], this updates the transitive
+    /// closure incrementally: only the ancestors of the upserted entities and
+    /// of their descendants are recomputed from their declared parents. Fails
+    /// if the update would introduce a cycle in the entity hierarchy.
+    ///
+    /// If `schema` is present, then the upserted entities will be validated
+    /// against the `schema`, returning an error if they do not conform to the
+    /// schema.
+    /// (This method will not add action entities from the `schema`.)
+    ///
+    /// If this returns an error, this [`Entities`] is left unchanged.
+    pub fn upsert_entities(
+        &mut self,
+        collection: impl IntoIterator<Item = Entity>,
+        schema: Option<&impl Schema>,
+        extensions: Extensions<'_>,
+    ) -> Result<()> {
+        let collection: Vec<Entity> = collection.into_iter().collect();
+        if let Some(schema) = schema {
+            let checker = EntitySchemaConformanceChecker::new(schema, extensions);
+            for entity in &collection {
+                checker.validate_entity(entity)?;
+            }
+        }
+        let mut previous = HashMap::new();
+        for entity in collection {
+            let uid = entity.uid().clone();
+            let replaced = self.entities.insert(uid.clone(), entity);
+            previous.entry(uid).or_insert(replaced);
+        }
+        self.update_tc_or_restore(previous)
+    }
+
+    /// Removes the entities with the given UIDs from this [`Entities`]. UIDs
+    /// which are not in the [`Entities`] are ignored.
+    ///
+    /// Other entities which had a removed entity as a parent keep it as a
+    /// parent, but are no longer descendants of that entity's ancestors
+    /// (unless they are through some other path in the hierarchy). Ancestors
+    /// which are also declared parents, e.g., of entities parsed from JSON
+    /// written by [`Entities::to_json_value()`] or created for
+    /// [`TCComputation::AssumeAlreadyComputed`], are kept.
+    /// As with [`Entities::upsert_entities()`], the transitive closure is
+    /// updated incrementally, and this [`Entities`] is left unchanged if this
+    /// returns an error.
+    pub fn remove_entities(&mut self, uids: impl IntoIterator<Item = EntityUID>) -> Result<()> {
+        let mut previous = HashMap::new();
+        for uid in uids {
+            if let Some(removed) = self.entities.remove(&uid) {
+                previous.insert(uid, Some(removed));
+            }
+        }
+        self.update_tc_or_restore(previous)
+    }
+
+    /// Replaces the parents of the entity with the given UID, leaving its
+    /// attributes and tags unchanged. Fails if there is no such entity, or if
+    /// the new parents would introduce a cycle in the entity hierarchy.
+    ///
+    /// If `schema` is present, then the updated entity will be validated
+    /// against the `schema`, returning an error if it does not conform to the
+    /// schema (e.g., if one of the new parents has a type which is not allowed
+    /// as a parent).
+    ///
+    /// If this returns an error, this [`Entities`] is left unchanged.
+    pub fn set_parents(
+        &mut self,
+        uid: &EntityUID,
+        parents: impl IntoIterator<Item = EntityUID>,
+        schema: Option<&impl Schema>,
+        extensions: Extensions<'_>,
+    ) -> Result<()> {
+        let mut entity = self
+            .entities
+            .get(uid)
+            .ok_or_else(|| EntitiesError::entity_not_found(uid.clone()))?
+            .clone();
+        entity.set_parents(parents.into_iter().collect());
+        if let Some(schema) = schema {
+            EntitySchemaConformanceChecker::new(schema, extensions).validate_entity(&entity)?;
+        }
+        let replaced = self.entities.insert(uid.clone(), entity);
+        self.update_tc_or_restore(HashMap::from([(uid.clone(), replaced)]))
+    }
+
+    /// Restore the transitive closure after the entities with UIDs in
+    /// `previous` were inserted, replaced, or removed. `previous` maps each
+    /// of them to the entity it replaced (`None` if it is new). If that fails,
+    /// those entities are put back, undoing the change.
+    fn update_tc_or_restore(&mut self, previous: HashMap<EntityUID, Option<Entity>>) -> Result<()> {
+        let descendants = self.descendants.get_or_insert_with(|| {
+            // index the hierarchy as it was before the change
+            descendants_index(
+                self.entities
+                    .values()
+                    .filter(|e| !previous.contains_key(e.uid()))
+                    .chain(previous.values().flatten()),
+            )
+        });
+        update_tc(&mut self.entities, descendants, &previous).inspect_err(|_| {
+            for (uid, entity) in previous {
+                match entity {
+                    Some(entity) => self.entities.insert(uid, entity),
+                    None => self.entities.remove(&uid),
+                };
+            }
+        })
+    }
+
     /// Create an `Entities` object with the given entities.
     ///
     /// If `schema` is present, then action entities from that schema will also
@@ -214,6 +347,7 @@ impl Entities {
         Ok(Self {
             entities: entity_map,
             mode: Mode::default(),
+            descendants: None,
         })
     }
 
@@ -419,6 +553,124 @@ impl Default for Mode {
     }
 }
 
+/// Index the entities by their ancestors: map each UID to the UIDs of the
+/// `entities` which have it among their ancestors
+fn descendants_index<'a>(
+    entities: impl IntoIterator<Item = &'a Entity>,
+) -> HashMap<EntityUID, HashSet<EntityUID>> {
+    let mut index: HashMap<EntityUID, HashSet<EntityUID>> = HashMap::new();
+    for entity in entities {
+        for ancestor in entity.ancestors() {
+            index
+                .entry(ancestor.clone())
+                .or_default()
+                .insert(entity.uid().clone());
+        }
+    }
+    index
+}
+
+/// Restore the transitive closure of the `ancestor` relation in `entities`
+/// after the entities with UIDs in `previous` were inserted, replaced, or
+/// removed, assuming it held before that change. `previous` maps each of
+/// them to the entity it replaced (`None` if it is new), and `descendants`
+/// indexes the hierarchy as it was before the change (see
+/// `descendants_index()`); it is updated along with `entities`.
+///
+/// The only entities whose ancestors can change are the changed entities
+/// themselves and their (previous) descendants, i.e., the entities which
+/// have a changed UID among their ancestors. (This includes entities which
+/// had a new entity as a parent before it existed.) Every other entity keeps
+/// its ancestors, so we recompute the ancestors of the affected entities from
+/// their declared parents, reusing the existing ancestors of unaffected
+/// entities along the way. Fails without modifying `entities` or
+/// `descendants` if the affected entities now form a cycle, so the caller can
+/// undo its change.
+fn update_tc(
+    entities: &mut HashMap<EntityUID, Entity>,
+    descendants: &mut HashMap<EntityUID, HashSet<EntityUID>>,
+    previous: &HashMap<EntityUID, Option<Entity>>,
+) -> Result<()> {
+    if previous.is_empty() {
+        return Ok(());
+    }
+    let affected: HashSet<EntityUID> = previous
+        .keys()
+        .flat_map(|uid| std::iter::once(uid).chain(descendants.get(uid).into_iter().flatten()))
+        .filter(|uid| entities.contains_key(uid))
+        .cloned()
+        .collect();
+    let mut computed: HashMap<EntityUID, HashSet<EntityUID>> = HashMap::new();
+    let mut in_progress: HashSet<EntityUID> = HashSet::new();
+    for uid in &affected {
+        ancestors_of(uid, entities, &affected, &mut computed, &mut in_progress)?;
+    }
+    // move the changed and affected entities from their old ancestors in the
+    // index to their new ones
+    for uid in previous.keys().chain(&affected) {
+        let old = match previous.get(uid) {
+            Some(replaced) => replaced.as_ref(),
+            None => entities.get(uid),
+        };
+        for ancestor in old.into_iter().flat_map(Entity::ancestors) {
+            if let Some(uids) = descendants.get_mut(ancestor) {
+                uids.remove(uid);
+            }
+        }
+    }
+    for (uid, ancestors) in computed {
+        for ancestor in &ancestors {
+            descendants
+                .entry(ancestor.clone())
+                .or_default()
+                .insert(uid.clone());
+        }
+        if let Some(entity) = entities.get_mut(&uid) {
+            entity.set_ancestors(ancestors);
+        }
+    }
+    Ok(())
+}
+
+/// Compute the ancestors of the affected entity `uid` from its declared
+/// parents, memoizing the result in `computed`. `in_progress` holds the
+/// entities on the current search path, so finding one of them again means
+/// the hierarchy has a cycle.
+fn ancestors_of(
+    uid: &EntityUID,
+    entities: &HashMap<EntityUID, Entity>,
+    affected: &HashSet<EntityUID>,
+    computed: &mut HashMap<EntityUID, HashSet<EntityUID>>,
+    in_progress: &mut HashSet<EntityUID>,
+) -> Result<()> {
+    if computed.contains_key(uid) {
+        return Ok(());
+    }
+    let Some(entity) = entities.get(uid) else {
+        return Ok(());
+    };
+    if !in_progress.insert(uid.clone()) {
+        return Err(TcError::has_cycle(uid.clone()).into());
+    }
+    let mut ancestors = HashSet::new();
+    for parent in entity.parents() {
+        ancestors.insert(parent.clone());
+        if affected.contains(parent) {
+            ancestors_of(parent, entities, affected, computed, in_progress)?;
+            if let Some(grandparents) = computed.get(parent) {
+                ancestors.extend(grandparents.iter().cloned());
+            }
+        } else if let Some(parent) = entities.get(parent) {
+            // An unaffected entity can't reach any affected entity (or it
+            // would be affected itself), so its ancestors are still correct
+            ancestors.extend(parent.ancestors().cloned());
+        }
+    }
+    in_progress.remove(uid);
+    computed.insert(uid.clone(), ancestors);
+    Ok(())
+}
+
 /// Describes the option for how the TC (transitive closure) of the entity
 /// hierarchy is computed
 #[allow(dead_code)] // only `ComputeNow` is used currently, that's intentional
@@ -464,6 +716,78 @@ mod json_parsing_tests {
         parser.from_json_value(v).unwrap();
     }
 
+    #[test]
+    fn json_parse_tags() {
+        let v = serde_json::json!(
+            [
+                {
+                    "uid" : { "type" : "A", "id" : "b"},
+                    "attrs" : {},
+                    "parents" : [],
+                    "tags" : {
+                        "owner" : { "__entity": { "type" : "A", "id" : "c" } },
+                        "labels" : [ "x", "y" ],
+                    }
+                },
+                {
+                    "uid" : { "type" : "A", "id" : "c"},
+                    "attrs" : {},
+                    "parents" : []
+                }
+            ]
+        );
+        let parser: EntityJsonParser<'_, '_> =
+            EntityJsonParser::new(None, Extensions::all_available(), TCComputation::ComputeNow);
+        let es = parser.from_json_value(v).unwrap();
+        let b = es
+            .entity(&EntityUID::with_eid_and_type("A", "b").unwrap())
+            .unwrap();
+        assert_eq!(
+            b.get_tag("owner"),
+            Some(&PartialValue::from(
+                EntityUID::with_eid_and_type("A", "c").unwrap()
+            ))
+        );
+        assert_matches!(b.get_tag("labels"), Some(PartialValue::Value(v)) => {
+            assert_eq!(v, &Value::set([Value::from("x"), Value::from("y")], None));
+        });
+        assert_eq!(b.get_tag("missing"), None);
+        // tags are not attributes
+        assert_eq!(b.get("owner"), None);
+        let c = es
+            .entity(&EntityUID::with_eid_and_type("A", "c").unwrap())
+            .unwrap();
+        assert_eq!(c.tags().count(), 0);
+
+        // tags survive a round trip through JSON, and are omitted when empty
+        let json = es.to_json_value().unwrap();
+        let reparsed = parser.from_json_value(json.clone()).unwrap();
+        let b2 = reparsed.entity(b.uid()).unwrap();
+        assert!(b.deep_eq(b2));
+        let serialized_c = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|e| e["uid"]["id"] == "c")
+            .unwrap();
+        assert!(serialized_c.get("tags").is_none());
+    }
+
+    #[test]
+    fn json_parse_duplicate_tags() {
+        let json = r#"[
+            {
+                "uid" : { "type" : "A", "id" : "b"},
+                "attrs" : {},
+                "parents" : [],
+                "tags" : { "t": 1, "t": 2 }
+            }
+        ]"#;
+        let parser: EntityJsonParser<'_, '_> =
+            EntityJsonParser::new(None, Extensions::all_available(), TCComputation::ComputeNow);
+        assert_matches!(parser.from_json_str(json), Err(_));
+    }
+
     #[test]
     fn enforces_tc_fail_cycle_almost() {
         let parser: EntityJsonParser<'_, '_> =
@@ -1681,6 +2005,15 @@ mod json_parsing_tests {
             ]
             .into_iter()
             .collect(),
+            [
+                ("tag1".into(), RestrictedExpr::val("some value")),
+                (
+                    "tag2".into(),
+                    RestrictedExpr::set([RestrictedExpr::val(EntityUID::with_eid("foo"))]),
+                ),
+            ]
+            .into_iter()
+            .collect(),
             &Extensions::all_available(),
         )
         .unwrap();
@@ -1715,6 +2048,7 @@ mod json_parsing_tests {
             ]
             .into_iter()
             .collect(),
+            HashMap::new(),
             &Extensions::all_available(),
         )
         .unwrap();
@@ -1819,6 +2153,7 @@ mod json_parsing_tests {
 #[allow(clippy::panic)]
 mod entities_tests {
     use super::*;
+    use cool_asserts::assert_matches;
 
     #[test]
     fn empty_entities() {
@@ -1901,6 +2236,308 @@ mod entities_tests {
         )
         .expect("Should have succeeded");
     }
+
+    /// helper function: an entity with the given parents
+    fn entity_with_parents(eid: &str, parents: &[&str]) -> Entity {
+        let mut e = Entity::with_uid(EntityUID::with_eid(eid));
+        for parent in parents {
+            e.add_ancestor(EntityUID::with_eid(parent));
+        }
+        e
+    }
+
+    /// helper function: the ancestors of `eid` in `es`, as a sorted list of eids
+    fn ancestor_eids(es: &Entities, eid: &str) -> Vec<String> {
+        let mut ancestors = es
+            .entity(&EntityUID::with_eid(eid))
+            .unwrap()
+            .ancestors()
+            .map(|a| a.eid().to_string())
+            .collect::<Vec<_>>();
+        ancestors.sort();
+        ancestors
+    }
+
+    /// helper function: a -> b -> c -> d, and e -> c
+    fn chain_entities() -> Vec<Entity> {
+        vec![
+            entity_with_parents("a", &["b"]),
+            entity_with_parents("b", &["c"]),
+            entity_with_parents("c", &["d"]),
+            entity_with_parents("d", &[]),
+            entity_with_parents("e", &["c"]),
+        ]
+    }
+
+    /// helper function: `chain_entities()` with the transitive closure computed
+    fn chain() -> Entities {
+        Entities::from_entities(
+            chain_entities(),
+            None::<&NoEntitiesSchema>,
+            TCComputation::ComputeNow,
+            Extensions::all_available(),
+        )
+        .expect("Failed to construct entities")
+    }
+
+    #[test]
+    fn upsert_new_entity_updates_descendants() {
+        // `x` is a parent of `d` before `x` exists
+        let mut es = chain();
+        es.upsert_entities(
+            vec![entity_with_parents("d", &["x"])],
+            None::<&NoEntitiesSchema>,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        es.upsert_entities(
+            vec![entity_with_parents("x", &["y"])],
+            None::<&NoEntitiesSchema>,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        assert_eq!(ancestor_eids(&es, "a"), vec!["b", "c", "d", "x", "y"]);
+        assert_eq!(ancestor_eids(&es, "e"), vec!["c", "d", "x", "y"]);
+        assert_eq!(ancestor_eids(&es, "x"), vec!["y"]);
+    }
+
+    #[test]
+    fn upsert_replaces_parents() {
+        let mut es = chain();
+        es.upsert_entities(
+            vec![entity_with_parents("b", &["e"])],
+            None::<&NoEntitiesSchema>,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        assert_eq!(ancestor_eids(&es, "a"), vec!["b", "c", "d", "e"]);
+        assert_eq!(ancestor_eids(&es, "b"), vec!["c", "d", "e"]);
+        // unaffected
+        assert_eq!(ancestor_eids(&es, "e"), vec!["c", "d"]);
+
+        es.upsert_entities(
+            vec![entity_with_parents("b", &[])],
+            None::<&NoEntitiesSchema>,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        assert_eq!(ancestor_eids(&es, "a"), vec!["b"]);
+        assert_eq!(ancestor_eids(&es, "b"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn upsert_matches_recomputing() {
+        let upserted = vec![
+            entity_with_parents("c", &["f"]),
+            entity_with_parents("f", &["g", "d"]),
+            entity_with_parents("h", &["a", "e"]),
+        ];
+        let mut incremental = chain();
+        incremental
+            .upsert_entities(
+                upserted.clone(),
+                None::<&NoEntitiesSchema>,
+                Extensions::all_available(),
+            )
+            .unwrap();
+        let mut all = chain_entities()
+            .into_iter()
+            .filter(|e| !upserted.iter().any(|u| u.uid() == e.uid()))
+            .collect::<Vec<_>>();
+        all.extend(upserted);
+        let recomputed = Entities::from_entities(
+            all,
+            None::<&NoEntitiesSchema>,
+            TCComputation::ComputeNow,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        assert_eq!(incremental.iter().count(), recomputed.iter().count());
+        for e in recomputed.iter() {
+            let eid = e.uid().eid().to_string();
+            assert_eq!(
+                ancestor_eids(&incremental, &eid),
+                ancestor_eids(&recomputed, &eid)
+            );
+        }
+    }
+
+    #[test]
+    fn remove_entities() {
+        let mut es = chain();
+        es.remove_entities(vec![EntityUID::with_eid("c"), EntityUID::with_eid("zzz")])
+            .unwrap();
+        assert_matches!(
+            es.entity(&EntityUID::with_eid("c")),
+            Dereference::NoSuchEntity
+        );
+        // `c` is still a parent of `b`, but `d` is no longer an ancestor
+        assert_eq!(ancestor_eids(&es, "a"), vec!["b", "c"]);
+        assert_eq!(ancestor_eids(&es, "b"), vec!["c"]);
+        assert_eq!(ancestor_eids(&es, "e"), vec!["c"]);
+        assert_eq!(ancestor_eids(&es, "d"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn json_round_trip_keeps_ancestors() {
+        // the JSON lists every ancestor as a parent, so it is already
+        // transitively closed
+        let json = chain().to_json_value().unwrap();
+        for tc_computation in [
+            TCComputation::ComputeNow,
+            TCComputation::AssumeAlreadyComputed,
+            TCComputation::EnforceAlreadyComputed,
+        ] {
+            let parser: EntityJsonParser<'_, '_> =
+                EntityJsonParser::new(None, Extensions::all_available(), tc_computation);
+            let es = parser.from_json_value(json.clone()).unwrap();
+            for eid in ["a", "b", "c", "d", "e"] {
+                assert_eq!(ancestor_eids(&es, eid), ancestor_eids(&chain(), eid));
+            }
+        }
+    }
+
+    #[test]
+    fn remove_entities_with_indirect_parents() {
+        // after a JSON round trip, `d` is a declared parent of `a`, so it
+        // stays an ancestor of `a` when `c` is removed
+        let parser: EntityJsonParser<'_, '_> =
+            EntityJsonParser::new(None, Extensions::all_available(), TCComputation::ComputeNow);
+        let mut es = parser
+            .from_json_value(chain().to_json_value().unwrap())
+            .unwrap();
+        es.remove_entities(vec![EntityUID::with_eid("c")]).unwrap();
+        assert_eq!(ancestor_eids(&es, "a"), vec!["b", "c", "d"]);
+        assert_eq!(ancestor_eids(&es, "b"), vec!["c", "d"]);
+    }
+
+    #[test]
+    fn updates_match_recomputing() {
+        // each update relies on the index of descendants left by the last one
+        let mut es = chain();
+        es.upsert_entities(
+            vec![
+                entity_with_parents("f", &["a"]),
+                entity_with_parents("d", &["g"]),
+            ],
+            None::<&NoEntitiesSchema>,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        es.remove_entities(vec![EntityUID::with_eid("b")]).unwrap();
+        es.set_parents(
+            &EntityUID::with_eid("a"),
+            vec![EntityUID::with_eid("e")],
+            None::<&NoEntitiesSchema>,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        es.upsert_entities(
+            vec![entity_with_parents("g", &["h"])],
+            None::<&NoEntitiesSchema>,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        es.remove_entities(vec![EntityUID::with_eid("c")]).unwrap();
+        assert_eq!(ancestor_eids(&es, "f"), vec!["a", "c", "e"]);
+
+        let recomputed = Entities::from_entities(
+            es.iter().map(|e| {
+                let parents = e.parents().map(|p| p.eid().to_string()).collect::<Vec<_>>();
+                let parents = parents.iter().map(String::as_str).collect::<Vec<_>>();
+                entity_with_parents(&e.uid().eid().to_string(), &parents)
+            }),
+            None::<&NoEntitiesSchema>,
+            TCComputation::ComputeNow,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        for e in recomputed.iter() {
+            let eid = e.uid().eid().to_string();
+            assert_eq!(ancestor_eids(&es, &eid), ancestor_eids(&recomputed, &eid));
+        }
+    }
+
+    #[test]
+    fn set_parents() {
+        let mut es = chain();
+        es.set_parents(
+            &EntityUID::with_eid("c"),
+            vec![EntityUID::with_eid("x")],
+            None::<&NoEntitiesSchema>,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        assert_eq!(ancestor_eids(&es, "a"), vec!["b", "c", "x"]);
+        assert_eq!(ancestor_eids(&es, "e"), vec!["c", "x"]);
+        assert_eq!(ancestor_eids(&es, "d"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn set_parents_nonexistent() {
+        assert_matches!(
+            chain().set_parents(
+                &EntityUID::with_eid("x"),
+                vec![EntityUID::with_eid("a")],
+                None::<&NoEntitiesSchema>,
+                Extensions::all_available(),
+            ),
+            Err(EntitiesError::EntityNotFound(e)) => {
+                assert_eq!(e.euid(), &EntityUID::with_eid("x"));
+            }
+        );
+    }
+
+    #[test]
+    fn updates_detect_cycles() {
+        let mut es = chain();
+        assert_matches!(
+            es.set_parents(
+                &EntityUID::with_eid("d"),
+                vec![EntityUID::with_eid("a")],
+                None::<&NoEntitiesSchema>,
+                Extensions::all_available(),
+            ),
+            Err(EntitiesError::TransitiveClosureError(_))
+        );
+        assert_matches!(
+            es.upsert_entities(
+                vec![
+                    entity_with_parents("e", &["e"]),
+                    entity_with_parents("z", &[]),
+                ],
+                None::<&NoEntitiesSchema>,
+                Extensions::all_available(),
+            ),
+            Err(EntitiesError::TransitiveClosureError(_))
+        );
+        // a cycle among new entities only
+        assert_matches!(
+            es.upsert_entities(
+                vec![
+                    entity_with_parents("x", &["y"]),
+                    entity_with_parents("y", &["x"])
+                ],
+                None::<&NoEntitiesSchema>,
+                Extensions::all_available(),
+            ),
+            Err(EntitiesError::TransitiveClosureError(_))
+        );
+
+        // the failed updates left the entities as they were
+        let unchanged = chain();
+        assert_eq!(es.iter().count(), unchanged.iter().count());
+        for e in unchanged.iter() {
+            let eid = e.uid().eid().to_string();
+            assert_eq!(ancestor_eids(&es, &eid), ancestor_eids(&unchanged, &eid));
+            assert_matches!(es.entity(e.uid()), Dereference::Data(entity) => {
+                assert_eq!(
+                    entity.parents().collect::<HashSet<_>>(),
+                    e.parents().collect::<HashSet<_>>()
+                );
+            });
+        }
+    }
 }
 
 // PANIC SAFETY: Unit Test Code
@@ -2028,6 +2665,12 @@ mod schema_based_parsing_tests {
             }
         }
 
+        fn tag_type(&self) -> Option<SchemaType> {
+            Some(SchemaType::Set {
+                element_ty: Box::new(SchemaType::String),
+            })
+        }
+
         fn required_attrs(&self) -> Box<dyn Iterator<Item = SmolStr>> {
             Box::new(
                 [
@@ -2707,6 +3350,100 @@ mod schema_based_parsing_tests {
         });
     }
 
+    #[cfg(all(feature = "decimal", feature = "ipaddr"))]
+    /// entity tags conforming to the declared tag type
+    #[test]
+    fn entity_tags() {
+        let entitiesjson = json!(
+            [
+                {
+                    "uid": { "type": "Employee", "id": "12UA45" },
+                    "attrs": {
+                        "isFullTime": true,
+                        "numDirectReports": 3,
+                        "department": "Sales",
+                        "manager": { "type": "Employee", "id": "34FB87" },
+                        "hr_contacts": [
+                            { "type": "HR", "id": "aaaaa" },
+                            { "type": "HR", "id": "bbbbb" }
+                        ],
+                        "json_blob": {
+                            "inner1": false,
+                            "inner2": "-*/",
+                            "inner3": { "innerinner": { "type": "Employee", "id": "09AE76" }},
+                        },
+                        "home_ip": "222.222.222.101",
+                        "work_ip": { "fn": "ip", "arg": "2.2.2.0/24" },
+                        "trust_score": "5.7",
+                        "tricky": { "type": "Employee", "id": "34FB87" },
+                    },
+                    "parents": [],
+                    "tags": { "teams": ["sales", "ops"], "regions": [] }
+                }
+            ]
+        );
+        let eparser = EntityJsonParser::new(
+            Some(&MockSchema),
+            Extensions::all_available(),
+            TCComputation::ComputeNow,
+        );
+        let parsed = eparser
+            .from_json_value(entitiesjson)
+            .expect("Should parse without error");
+        let parsed = parsed
+            .entity(&r#"Employee::"12UA45""#.parse().unwrap())
+            .expect("that should be the employee id");
+        assert_matches!(parsed.get_tag("teams"), Some(PartialValue::Value(v)) => {
+            assert_eq!(v, &Value::set([Value::from("sales"), Value::from("ops")], None));
+        });
+        assert_matches!(parsed.get_tag("regions"), Some(PartialValue::Value(_)));
+        assert_matches!(parsed.get_tag("ssn"), None);
+    }
+
+    #[cfg(all(feature = "decimal", feature = "ipaddr"))]
+    /// entity tag whose value doesn't match the declared tag type
+    #[test]
+    fn type_mismatch_in_entity_tag() {
+        let entitiesjson = json!(
+            [
+                {
+                    "uid": { "type": "Employee", "id": "12UA45" },
+                    "attrs": {
+                        "isFullTime": true,
+                        "numDirectReports": 3,
+                        "department": "Sales",
+                        "manager": { "type": "Employee", "id": "34FB87" },
+                        "hr_contacts": [
+                            { "type": "HR", "id": "aaaaa" },
+                            { "type": "HR", "id": "bbbbb" }
+                        ],
+                        "json_blob": {
+                            "inner1": false,
+                            "inner2": "-*/",
+                            "inner3": { "innerinner": { "type": "Employee", "id": "09AE76" }},
+                        },
+                        "home_ip": "222.222.222.101",
+                        "work_ip": { "fn": "ip", "arg": "2.2.2.0/24" },
+                        "trust_score": "5.7",
+                        "tricky": { "type": "Employee", "id": "34FB87" },
+                    },
+                    "parents": [],
+                    "tags": { "teams": "sales" }
+                }
+            ]
+        );
+        let eparser = EntityJsonParser::new(
+            Some(&MockSchema),
+            Extensions::all_available(),
+            TCComputation::ComputeNow,
+        );
+        assert_matches!(eparser.from_json_value(entitiesjson.clone()), Err(e) => {
+            expect_err(&entitiesjson, &miette::Report::new(e), &ExpectedErrorMessageBuilder::error(
+                r#"error during entity deserialization: in tag `teams` on `Employee::"12UA45"`, type mismatch: value was expected to have type (set of string), but actually has type string: `"sales"`"#
+            ).build());
+        });
+    }
+
     #[cfg(all(feature = "decimal", feature = "ipaddr"))]
     /// Test that involves parents of wrong types
     #[test]
@@ -3063,6 +3800,10 @@ mod schema_based_parsing_tests {
                 }
             }
 
+            fn tag_type(&self) -> Option<SchemaType> {
+                None
+            }
+
             fn required_attrs(&self) -> Box<dyn Iterator<Item = SmolStr>> {
                 Box::new(
                     ["isFullTime", "department", "manager"]
@@ -3164,5 +3905,26 @@ mod schema_based_parsing_tests {
                 "did you mean `XYZCorp::Employee`?",
             ).build());
         });
+
+        let entitiesjson = json!(
+            [
+                {
+                    "uid": { "type": "XYZCorp::Employee", "id": "12UA45" },
+                    "attrs": {
+                        "isFullTime": true,
+                        "department": "Sales",
+                        "manager": { "type": "XYZCorp::Employee", "id": "34FB87" }
+                    },
+                    "parents": [],
+                    "tags": { "team": "sales" }
+                }
+            ]
+        );
+
+        assert_matches!(eparser.from_json_value(entitiesjson.clone()), Err(e) => {
+            expect_err(&entitiesjson, &miette::Report::new(e), &ExpectedErrorMessageBuilder::error(
+                r#"error during entity deserialization: found a tag `team` on `XYZCorp::Employee::"12UA45"`, but no tags should exist on `XYZCorp::Employee::"12UA45"` according to the schema"#,
+            ).build());
+        });
     }
 }
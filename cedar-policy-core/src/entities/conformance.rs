@@ -19,12 +19,13 @@ use super::{
     GetSchemaTypeError, HeterogeneousSetError, Schema, SchemaType,
 };
 use crate::ast::{
-    BorrowedRestrictedExpr, Entity, EntityType, PartialValue, PartialValueToRestrictedExprError,
-    RestrictedExpr,
+    BorrowedRestrictedExpr, Eid, Entity, EntityType, EntityUID, ExprKind, Literal, PartialValue,
+    PartialValueToRestrictedExprError, RestrictedExpr, Value, ValueKind,
 };
 use crate::extensions::{ExtensionFunctionLookupError, Extensions};
 use either::Either;
 use miette::Diagnostic;
+use smol_str::SmolStr;
 use thiserror::Error;
 pub mod err;
 
@@ -75,6 +76,7 @@ impl<'a, S: Schema> EntitySchemaConformanceChecker<'a, S> {
                     suggested_types,
                 }
             })?;
+            Self::validate_enum_entity(uid, &schema_etype)?;
             // Ensure that all required attributes for `etype` are actually
             // included in `entity`
             for required_attr in schema_etype.required_attrs() {
@@ -100,35 +102,24 @@ impl<'a, S: Schema> EntitySchemaConformanceChecker<'a, S> {
                         }
                     }
                     Some(expected_ty) => {
-                        // typecheck: ensure that the entity attribute value matches
-                        // the expected type
-                        match typecheck_value_against_schematype(val, &expected_ty, self.extensions)
-                        {
-                            Ok(()) => {} // typecheck passes
-                            Err(TypecheckError::TypeMismatch(err)) => {
-                                return Err(EntitySchemaConformanceError::type_mistmatch(
-                                    uid.clone(),
-                                    attr.clone(),
-                                    err,
-                                ));
-                            }
-                            Err(TypecheckError::HeterogeneousSet(err)) => {
-                                return Err(EntitySchemaConformanceError::heterogeneous_set(
-                                    uid.clone(),
-                                    attr.clone(),
-                                    err,
-                                ));
-                            }
-                            Err(TypecheckError::ExtensionFunctionLookup(err)) => {
-                                return Err(
-                                    EntitySchemaConformanceError::extension_function_lookup(
-                                        uid.clone(),
-                                        attr.clone(),
-                                        err,
-                                    ),
-                                );
-                            }
-                        }
+                        self.typecheck_attr_or_tag(uid, attr, true, val, &expected_ty)?;
+                    }
+                }
+            }
+            // For each tag that actually appears in `entity`, ensure it
+            // complies with the schema
+            for (tag, val) in entity.tags() {
+                match schema_etype.tag_type() {
+                    None => {
+                        // `None` indicates that entities of this type shouldn't
+                        // have tags -- see docs on the `tag_type()` trait method
+                        return Err(EntitySchemaConformanceError::unexpected_entity_tag(
+                            uid.clone(),
+                            tag.clone(),
+                        ));
+                    }
+                    Some(expected_ty) => {
+                        self.typecheck_attr_or_tag(uid, tag, false, val, &expected_ty)?;
                     }
                 }
             }
@@ -141,6 +132,9 @@ impl<'a, S: Schema> EntitySchemaConformanceChecker<'a, S> {
                     // closed, so it's actually `allowed_ancestor_types()`
                     //
                     // thus, the check passes in this case
+                    if let Some(ancestor_etype) = self.schema.entity_type(ancestor_type) {
+                        Self::validate_enum_entity(ancestor_euid, &ancestor_etype)?;
+                    }
                 } else {
                     return Err(EntitySchemaConformanceError::invalid_ancestor_type(
                         uid.clone(),
@@ -151,6 +145,114 @@ impl<'a, S: Schema> EntitySchemaConformanceChecker<'a, S> {
         }
         Ok(())
     }
+
+    /// Check that `uid`, an entity of the type described by `etype`, has one
+    /// of the allowed IDs if `etype` is an enumerated entity type
+    fn validate_enum_entity(
+        uid: &EntityUID,
+        etype: &S::EntityTypeDescription,
+    ) -> Result<(), EntitySchemaConformanceError> {
+        match etype.enum_entity_eids() {
+            Some(choices) if !choices.contains(uid.eid()) => Err(
+                EntitySchemaConformanceError::invalid_enum_entity(uid.clone(), choices),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Typecheck the value of an entity attribute (`was_attr == true`) or tag
+    /// (`was_attr == false`) against the type expected by the schema, and
+    /// check the IDs of the entities of enumerated entity types in it,
+    /// reporting any failure as an [`EntitySchemaConformanceError`].
+    fn typecheck_attr_or_tag(
+        &self,
+        uid: &EntityUID,
+        attr_or_tag: &SmolStr,
+        was_attr: bool,
+        val: &PartialValue,
+        expected_ty: &SchemaType,
+    ) -> Result<(), EntitySchemaConformanceError> {
+        match typecheck_value_against_schematype(val, expected_ty, self.extensions) {
+            Ok(()) => match find_invalid_enum_entity(self.schema, val) {
+                Some((uid, choices)) => Err(EntitySchemaConformanceError::invalid_enum_entity(
+                    uid.clone(),
+                    choices,
+                )),
+                None => Ok(()),
+            },
+            Err(TypecheckError::TypeMismatch(err)) => {
+                Err(EntitySchemaConformanceError::type_mistmatch(
+                    uid.clone(),
+                    attr_or_tag.clone(),
+                    was_attr,
+                    err,
+                ))
+            }
+            Err(TypecheckError::HeterogeneousSet(err)) => {
+                Err(EntitySchemaConformanceError::heterogeneous_set(
+                    uid.clone(),
+                    attr_or_tag.clone(),
+                    was_attr,
+                    err,
+                ))
+            }
+            Err(TypecheckError::ExtensionFunctionLookup(err)) => {
+                Err(EntitySchemaConformanceError::extension_function_lookup(
+                    uid.clone(),
+                    attr_or_tag.clone(),
+                    was_attr,
+                    err,
+                ))
+            }
+        }
+    }
+}
+
+/// Find an entity in `value`, possibly nested in sets and records, which is
+/// of an enumerated entity type in `schema` but whose ID is not one of the IDs
+/// declared for that type. Returns that entity and the declared IDs.
+///
+/// Typechecking a value only compares entity types, so this is needed in
+/// addition to [`typecheck_value_against_schematype()`] to reject, e.g., an
+/// attribute with value `Role::"superadmin"` when `Role` is an enumerated
+/// entity type without that ID.
+pub fn find_invalid_enum_entity<'v>(
+    schema: &impl Schema,
+    value: &'v PartialValue,
+) -> Option<(&'v EntityUID, Vec<Eid>)> {
+    let mut uids = Vec::new();
+    match value {
+        PartialValue::Value(v) => entity_uids_in_value(v, &mut uids),
+        PartialValue::Residual(expr) => {
+            uids.extend(expr.subexpressions().filter_map(|e| match e.expr_kind() {
+                ExprKind::Lit(Literal::EntityUID(uid)) => Some(uid.as_ref()),
+                _ => None,
+            }))
+        }
+    }
+    uids.into_iter().find_map(|uid| {
+        let choices = schema.entity_type(uid.entity_type())?.enum_entity_eids()?;
+        (!choices.contains(uid.eid())).then_some((uid, choices))
+    })
+}
+
+/// Add the entity UIDs in `value`, including those nested in sets and
+/// records, to `uids`
+fn entity_uids_in_value<'v>(value: &'v Value, uids: &mut Vec<&'v EntityUID>) {
+    match value.value_kind() {
+        ValueKind::Lit(Literal::EntityUID(uid)) => uids.push(uid),
+        ValueKind::Lit(_) | ValueKind::ExtensionValue(_) => (),
+        ValueKind::Set(set) => {
+            for v in set.iter() {
+                entity_uids_in_value(v, uids);
+            }
+        }
+        ValueKind::Record(record) => {
+            for v in record.values() {
+                entity_uids_in_value(v, uids);
+            }
+        }
+    }
 }
 
 /// Check whether the given `PartialValue` typechecks with the given `SchemaType`.
@@ -205,12 +307,23 @@ pub fn typecheck_restricted_expr_against_schematype(
                 }))
             }
         }
-        Err(GetSchemaTypeError::UnknownInsufficientTypeInfo { .. }) => {
-            // in this case we just don't have the information to know whether
+        Err(GetSchemaTypeError::UnknownInsufficientTypeInfo { unknown }) => {
+            // in this case we don't have the full information to know whether
             // the attribute value (an unknown) matches the expected type.
-            // For now we consider this as passing -- we can't really report a
-            // type error.
-            Ok(())
+            // If the unknown has a type annotation (e.g., it is a set of
+            // unknown element type), we can at least check that; otherwise
+            // we consider this as passing -- we can't really report a type
+            // error.
+            match &unknown.type_annotation {
+                Some(ty) if !expected_ty.matches(ty) => {
+                    Err(TypecheckError::TypeMismatch(TypeMismatchError {
+                        expected: Box::new(expected_ty.clone()),
+                        actual_ty: None,
+                        actual_val: Either::Right(Box::new(expr.to_owned())),
+                    }))
+                }
+                _ => Ok(()),
+            }
         }
         Err(GetSchemaTypeError::NontrivialResidual { .. }) => {
             // this case is unreachable according to the invariant in the comments
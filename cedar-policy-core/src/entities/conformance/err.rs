@@ -15,7 +15,7 @@
  */
 //! This module cotnains errors around entities not conforming to schemas
 use super::{HeterogeneousSetError, TypeMismatchError};
-use crate::ast::{EntityType, EntityUID};
+use crate::ast::{Eid, EntityType, EntityUID};
 use crate::extensions::ExtensionFunctionLookupError;
 use miette::Diagnostic;
 use smol_str::SmolStr;
@@ -28,12 +28,16 @@ pub enum EntitySchemaConformanceError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     UnexpectedEntityAttr(UnexpectedEntityAttr),
+    /// Encountered tag that shouldn't exist on entities of this type
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnexpectedEntityTag(UnexpectedEntityTag),
     /// Didn't encounter attribute that should exist
     #[error(transparent)]
     #[diagnostic(transparent)]
     MissingRequiredEntityAttr(MissingRequiredEntityAttr),
-    /// The given attribute on the given entity had a different type than the
-    /// schema indicated
+    /// The given attribute or tag on the given entity had a different type
+    /// than the schema indicated
     #[error(transparent)]
     #[diagnostic(transparent)]
     TypeMismatch(TypeMismatch),
@@ -51,6 +55,11 @@ pub enum EntitySchemaConformanceError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     UnexpectedEntityType(#[from] UnexpectedEntityTypeError),
+    /// Encountered an entity of an enumerated entity type whose entity ID is
+    /// not one of the IDs declared in the schema
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidEnumEntity(InvalidEnumEntity),
     /// Encountered an action which was not declared in the schema
     #[error(transparent)]
     #[diagnostic(transparent)]
@@ -76,6 +85,13 @@ impl EntitySchemaConformanceError {
         })
     }
 
+    pub(crate) fn unexpected_entity_tag(uid: EntityUID, tag: impl Into<SmolStr>) -> Self {
+        Self::UnexpectedEntityTag(UnexpectedEntityTag {
+            uid,
+            tag: tag.into(),
+        })
+    }
+
     pub(crate) fn missing_entity_attr(uid: EntityUID, attr: impl Into<SmolStr>) -> Self {
         Self::MissingRequiredEntityAttr(MissingRequiredEntityAttr {
             uid,
@@ -85,24 +101,28 @@ impl EntitySchemaConformanceError {
 
     pub(crate) fn type_mistmatch(
         uid: EntityUID,
-        attr: impl Into<SmolStr>,
+        attr_or_tag: impl Into<SmolStr>,
+        was_attr: bool,
         err: TypeMismatchError,
     ) -> Self {
         Self::TypeMismatch(TypeMismatch {
             uid,
-            attr: attr.into(),
+            attr_or_tag: attr_or_tag.into(),
+            was_attr,
             err,
         })
     }
 
     pub(crate) fn heterogeneous_set(
         uid: EntityUID,
-        attr: impl Into<SmolStr>,
+        attr_or_tag: impl Into<SmolStr>,
+        was_attr: bool,
         err: HeterogeneousSetError,
     ) -> Self {
         Self::HeterogeneousSet(HeterogeneousSet {
             uid,
-            attr: attr.into(),
+            attr_or_tag: attr_or_tag.into(),
+            was_attr,
             err,
         })
     }
@@ -114,6 +134,10 @@ impl EntitySchemaConformanceError {
         })
     }
 
+    pub(crate) fn invalid_enum_entity(uid: EntityUID, choices: Vec<Eid>) -> Self {
+        Self::InvalidEnumEntity(InvalidEnumEntity { uid, choices })
+    }
+
     pub(crate) fn undeclared_action(uid: EntityUID) -> Self {
         Self::UndeclaredAction(UndeclaredAction { uid })
     }
@@ -124,12 +148,14 @@ impl EntitySchemaConformanceError {
 
     pub(crate) fn extension_function_lookup(
         uid: EntityUID,
-        attr: impl Into<SmolStr>,
+        attr_or_tag: impl Into<SmolStr>,
+        was_attr: bool,
         err: ExtensionFunctionLookupError,
     ) -> Self {
         Self::ExtensionFunctionLookup(ExtensionFunctionLookup {
             uid,
-            attr: attr.into(),
+            attr_or_tag: attr_or_tag.into(),
+            was_attr,
             err,
         })
     }
@@ -139,12 +165,14 @@ impl EntitySchemaConformanceError {
 /// checking entity conformance because that may require getting information
 /// about any extension functions referenced in entity attribute values.
 #[derive(Debug, Error, Diagnostic)]
-#[error("in attribute `{attr}` on `{uid}`, {err}")]
+#[error("in {} `{attr_or_tag}` on `{uid}`, {err}", if *.was_attr { "attribute" } else { "tag" })]
 pub struct ExtensionFunctionLookup {
     /// Entity where the error occurred
     uid: EntityUID,
-    /// Name of the attribute where the error occurred
-    attr: SmolStr,
+    /// Name of the attribute or tag where the error occurred
+    attr_or_tag: SmolStr,
+    /// Whether `attr_or_tag` is an attribute (`true`) or a tag (`false`)
+    was_attr: bool,
     /// Underlying error
     #[diagnostic(transparent)]
     err: ExtensionFunctionLookupError,
@@ -162,6 +190,33 @@ pub struct ActionDeclarationMismatch {
     uid: EntityUID,
 }
 
+/// Encountered an entity of an enumerated entity type whose entity ID is not
+/// one of the IDs declared in the schema
+#[derive(Debug, Error)]
+#[error(
+    "entity `{uid}` is of an enumerated entity type, but its ID is not one of the declared IDs"
+)]
+pub struct InvalidEnumEntity {
+    /// Entity with the invalid ID
+    uid: EntityUID,
+    /// The IDs declared for its entity type
+    choices: Vec<Eid>,
+}
+
+impl Diagnostic for InvalidEnumEntity {
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(format!(
+            "valid IDs for entity type `{}` are: {}",
+            self.uid.entity_type(),
+            self.choices
+                .iter()
+                .map(|eid| format!("{:?}", eid.as_ref() as &str))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )))
+    }
+}
+
 /// Encountered an action which was not declared in the schema
 #[derive(Debug, Error, Diagnostic)]
 #[error("found action entity `{uid}`, but it was not declared as an action in the schema")]
@@ -173,12 +228,14 @@ pub struct UndeclaredAction {
 /// Found a set whose elements don't all have the same type. This doesn't match
 /// any possible schema.
 #[derive(Debug, Error, Diagnostic)]
-#[error("in attribute `{attr}` on `{uid}`, {err}")]
+#[error("in {} `{attr_or_tag}` on `{uid}`, {err}", if *.was_attr { "attribute" } else { "tag" })]
 pub struct HeterogeneousSet {
     /// Entity where the error occurred
     uid: EntityUID,
-    /// Name of the attribute where the error occurred
-    attr: SmolStr,
+    /// Name of the attribute or tag where the error occurred
+    attr_or_tag: SmolStr,
+    /// Whether `attr_or_tag` is an attribute (`true`) or a tag (`false`)
+    was_attr: bool,
     /// Underlying error
     #[diagnostic(transparent)]
     err: HeterogeneousSetError,
@@ -204,6 +261,16 @@ pub struct UnexpectedEntityAttr {
     attr: SmolStr,
 }
 
+/// Encountered tag that shouldn't exist on entities of this type
+#[derive(Debug, Error, Diagnostic)]
+#[error(
+    "found a tag `{tag}` on `{uid}`, but no tags should exist on `{uid}` according to the schema"
+)]
+pub struct UnexpectedEntityTag {
+    uid: EntityUID,
+    tag: SmolStr,
+}
+
 /// Didn't encounter attribute that should exist
 #[derive(Debug, Error, Diagnostic)]
 #[error("expected entity `{uid}` to have attribute `{attr}`, but it does not")]
@@ -213,12 +280,13 @@ pub struct MissingRequiredEntityAttr {
 }
 
 #[derive(Debug, Error, Diagnostic)]
-#[error("in attribute `{attr}` on `{uid}`, {err}")]
-/// The given attribute on the given entity had a different type than the
-/// schema indicated
+#[error("in {} `{attr_or_tag}` on `{uid}`, {err}", if *.was_attr { "attribute" } else { "tag" })]
+/// The given attribute or tag on the given entity had a different type than
+/// the schema indicated
 pub struct TypeMismatch {
     uid: EntityUID,
-    attr: SmolStr,
+    attr_or_tag: SmolStr,
+    was_attr: bool,
     #[diagnostic(transparent)]
     err: TypeMismatchError,
 }
@@ -21,6 +21,7 @@ use thiserror::Error;
 
 /// Errors in serializing, deserializing, and processing of Entities
 #[derive(Debug, Diagnostic, Error)]
+#[non_exhaustive]
 pub enum EntitiesError {
     /// Error occurring in serialization of entities
     #[error("error during entity serialization: {0}")]
@@ -34,6 +35,11 @@ pub enum EntitiesError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     Duplicate(Duplicate),
+    /// Error updating the Entities collection as an entity it should contain
+    /// does not exist
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    EntityNotFound(EntityNotFound),
     /// Errors occurring while computing or enforcing transitive closure on the
     /// entity hierarchy.
     #[error("transitive closure computation/enforcement error: {0}")]
@@ -43,12 +49,20 @@ pub enum EntitiesError {
     #[error("entity does not conform to the schema: {0}")]
     #[diagnostic(transparent)]
     InvalidEntity(#[from] crate::entities::conformance::err::EntitySchemaConformanceError),
+    /// Error reported by an entity loader while loading entities. (Only
+    /// produced with the experimental `partial-eval` feature.)
+    #[error("failed to load entities: {0}")]
+    Loader(Box<dyn std::error::Error + Send + Sync>),
 }
 
 impl EntitiesError {
     pub(crate) fn duplicate(euid: EntityUID) -> Self {
         Self::Duplicate(Duplicate { euid })
     }
+
+    pub(crate) fn entity_not_found(euid: EntityUID) -> Self {
+        Self::EntityNotFound(EntityNotFound { euid })
+    }
 }
 
 impl From<transitive_closure::TcError<EntityUID>> for EntitiesError {
@@ -95,5 +109,20 @@ impl Duplicate {
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Error, Diagnostic)]
+#[error("entity `{}` does not exist", .euid)]
+/// Error type for updates to an entity that is not in the entity set
+pub struct EntityNotFound {
+    /// The [`EntityUID`] that was not found
+    euid: EntityUID,
+}
+
+impl EntityNotFound {
+    /// Get the [`EntityUID`] of the entity that was not found
+    pub fn euid(&self) -> &EntityUID {
+        &self.euid
+    }
+}
+
 /// Type alias for convenience
 pub type Result<T> = std::result::Result<T, EntitiesError>;
@@ -57,6 +57,18 @@ pub struct EntityJson {
     attrs: HashMap<SmolStr, JsonValueWithNoDuplicateKeys>,
     /// Parents of the entity, specified in any form accepted by `EntityUidJson`
     parents: Vec<EntityUidJson>,
+    /// Tags of the entity, whose values can be any JSON value, just like
+    /// attributes. (With a schema, all tag values are parsed according to the
+    /// single tag type declared for the entity type.) This field may be
+    /// omitted, and is omitted when serializing an entity with no tags.
+    #[serde_as(as = "serde_with::MapPreventDuplicates<_,_>")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    #[cfg_attr(
+        feature = "wasm",
+        tsify(optional, type = "Record<string, CedarValueJson>")
+    )]
+    tags: HashMap<SmolStr, JsonValueWithNoDuplicateKeys>,
 }
 
 /// Struct used to parse entities from JSON.
@@ -338,6 +350,7 @@ impl<'e, 's, S: Schema> EntityJsonParser<'e, 's, S> {
                                     EntitySchemaConformanceError::heterogeneous_set(
                                         uid.clone(),
                                         k.clone(),
+                                        true,
                                         err,
                                     ),
                                 ))
@@ -347,6 +360,7 @@ impl<'e, 's, S: Schema> EntityJsonParser<'e, 's, S> {
                                     EntitySchemaConformanceError::extension_function_lookup(
                                         uid.clone(),
                                         k.clone(),
+                                        true,
                                         err,
                                     ),
                                 ))
@@ -400,7 +414,41 @@ impl<'e, 's, S: Schema> EntityJsonParser<'e, 's, S> {
                 })
             })
             .collect::<Result<_, JsonDeserializationError>>()?;
-        Ok(Entity::new(uid, attrs, parents, &self.extensions)?)
+        let tags: HashMap<SmolStr, RestrictedExpr> = ejson
+            .tags
+            .into_iter()
+            .map(|(k, v)| {
+                let ctx = || JsonDeserializationErrorContext::EntityTag {
+                    uid: uid.clone(),
+                    tag: k.clone(),
+                };
+                let rexpr = match &entity_schema_info {
+                    EntitySchemaInfo::NoSchema => {
+                        vparser.val_into_restricted_expr(v.into(), None, ctx)?
+                    }
+                    EntitySchemaInfo::NonAction(desc) => match desc.tag_type() {
+                        // `None` indicates that no tags should exist -- see docs
+                        // on the `tag_type()` trait method
+                        None => {
+                            return Err(JsonDeserializationError::EntitySchemaConformance(
+                                EntitySchemaConformanceError::unexpected_entity_tag(uid.clone(), k),
+                            ))
+                        }
+                        Some(expected_ty) => {
+                            vparser.val_into_restricted_expr(v.into(), Some(&expected_ty), ctx)?
+                        }
+                    },
+                    EntitySchemaInfo::Action(_) => {
+                        // action entities in the schema never have tags
+                        return Err(JsonDeserializationError::EntitySchemaConformance(
+                            EntitySchemaConformanceError::action_declaration_mismatch(uid.clone()),
+                        ));
+                    }
+                };
+                Ok((k, rexpr))
+            })
+            .collect::<Result<_, JsonDeserializationError>>()?;
+        Ok(Entity::new(uid, attrs, parents, tags, &self.extensions)?)
     }
 }
 
@@ -414,26 +462,37 @@ impl EntityJson {
             uid: EntityUidJson::ImplicitEntityEscape(TypeAndId::from(entity.uid())),
             attrs: entity
                 .attrs()
-                .map(|(k, pvalue)| match pvalue {
-                    PartialValue::Value(value) => {
-                        let cedarvaluejson = CedarValueJson::from_value(value.clone())?;
-                        Ok((k.clone(), serde_json::to_value(cedarvaluejson)?.into()))
-                    }
-                    PartialValue::Residual(expr) => match BorrowedRestrictedExpr::new(expr) {
-                        Ok(expr) => {
-                            let cedarvaluejson = CedarValueJson::from_expr(expr)?;
-                            Ok((k.clone(), serde_json::to_value(cedarvaluejson)?.into()))
-                        }
-                        Err(_) => Err(JsonSerializationError::residual(expr.clone())),
-                    },
-                })
+                .map(|(k, pvalue)| Ok((k.clone(), Self::pvalue_to_json(pvalue)?)))
                 .collect::<Result<_, JsonSerializationError>>()?,
             parents: entity
                 .ancestors()
                 .map(|euid| EntityUidJson::ImplicitEntityEscape(TypeAndId::from(euid.clone())))
                 .collect(),
+            tags: entity
+                .tags()
+                .map(|(k, pvalue)| Ok((k.clone(), Self::pvalue_to_json(pvalue)?)))
+                .collect::<Result<_, JsonSerializationError>>()?,
         })
     }
+
+    /// Convert the value of an attribute or tag into its JSON representation
+    fn pvalue_to_json(
+        pvalue: &PartialValue,
+    ) -> Result<JsonValueWithNoDuplicateKeys, JsonSerializationError> {
+        match pvalue {
+            PartialValue::Value(value) => {
+                let cedarvaluejson = CedarValueJson::from_value(value.clone())?;
+                Ok(serde_json::to_value(cedarvaluejson)?.into())
+            }
+            PartialValue::Residual(expr) => match BorrowedRestrictedExpr::new(expr) {
+                Ok(expr) => {
+                    let cedarvaluejson = CedarValueJson::from_expr(expr)?;
+                    Ok(serde_json::to_value(cedarvaluejson)?.into())
+                }
+                Err(_) => Err(JsonSerializationError::residual(expr.clone())),
+            },
+        }
+    }
 }
 
 // PANIC SAFETY unit test code
@@ -15,7 +15,7 @@
  */
 
 use super::SchemaType;
-use crate::ast::{Entity, EntityType, EntityUID, Id, Name};
+use crate::ast::{Eid, Entity, EntityType, EntityUID, Id, Name};
 use smol_str::SmolStr;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
@@ -121,6 +121,11 @@ pub trait EntityTypeDescription {
     /// Returning `None` indicates that attribute should not exist.
     fn attr_type(&self, attr: &str) -> Option<SchemaType>;
 
+    /// If entities of this type may have tags, what type are the tag values?
+    ///
+    /// Returning `None` indicates that no tags should exist for this entity type.
+    fn tag_type(&self) -> Option<SchemaType>;
+
     /// Get the names of all the required attributes for this entity type.
     fn required_attrs<'s>(&'s self) -> Box<dyn Iterator<Item = SmolStr> + 's>;
 
@@ -130,10 +135,18 @@ pub trait EntityTypeDescription {
     /// May entities with this type have attributes other than those specified
     /// in the schema
     fn open_attributes(&self) -> bool;
+
+    /// If this is an enumerated entity type, the entity IDs that entities of
+    /// this type may have.
+    ///
+    /// Returning `None` indicates that entities of this type may have any ID.
+    fn enum_entity_eids(&self) -> Option<Vec<Eid>> {
+        None
+    }
 }
 
 /// Simple type that implements `EntityTypeDescription` by expecting no
-/// attributes to exist
+/// attributes or tags to exist
 #[derive(Debug, Clone)]
 pub struct NullEntityTypeDescription {
     /// null description for this type
@@ -146,6 +159,9 @@ impl EntityTypeDescription for NullEntityTypeDescription {
     fn attr_type(&self, _attr: &str) -> Option<SchemaType> {
         None
     }
+    fn tag_type(&self) -> Option<SchemaType> {
+        None
+    }
     fn required_attrs(&self) -> Box<dyn Iterator<Item = SmolStr>> {
         Box::new(std::iter::empty())
     }
@@ -508,7 +508,12 @@ impl<'e> ValueParser<'e> {
                     match ctx() {
                         JsonDeserializationErrorContext::EntityAttribute { uid, attr } => {
                             Err(JsonDeserializationError::EntitySchemaConformance(
-                                EntitySchemaConformanceError::type_mistmatch(uid, attr, err),
+                                EntitySchemaConformanceError::type_mistmatch(uid, attr, true, err),
+                            ))
+                        }
+                        JsonDeserializationErrorContext::EntityTag { uid, tag } => {
+                            Err(JsonDeserializationError::EntitySchemaConformance(
+                                EntitySchemaConformanceError::type_mistmatch(uid, tag, false, err),
                             ))
                         }
                         ctx => Err(JsonDeserializationError::type_mismatch(ctx, err)),
@@ -583,7 +588,12 @@ impl<'e> ValueParser<'e> {
                     match ctx() {
                         JsonDeserializationErrorContext::EntityAttribute { uid, attr } => {
                             Err(JsonDeserializationError::EntitySchemaConformance(
-                                EntitySchemaConformanceError::type_mistmatch(uid, attr, err),
+                                EntitySchemaConformanceError::type_mistmatch(uid, attr, true, err),
+                            ))
+                        }
+                        JsonDeserializationErrorContext::EntityTag { uid, tag } => {
+                            Err(JsonDeserializationError::EntitySchemaConformance(
+                                EntitySchemaConformanceError::type_mistmatch(uid, tag, false, err),
                             ))
                         }
                         ctx => Err(JsonDeserializationError::type_mismatch(ctx, err)),
@@ -635,7 +645,16 @@ impl<'e> ValueParser<'e> {
                         GetSchemaTypeError::HeterogeneousSet(err) => match ctx() {
                             JsonDeserializationErrorContext::EntityAttribute { uid, attr } => {
                                 JsonDeserializationError::EntitySchemaConformance(
-                                    EntitySchemaConformanceError::heterogeneous_set(uid, attr, err),
+                                    EntitySchemaConformanceError::heterogeneous_set(
+                                        uid, attr, true, err,
+                                    ),
+                                )
+                            }
+                            JsonDeserializationErrorContext::EntityTag { uid, tag } => {
+                                JsonDeserializationError::EntitySchemaConformance(
+                                    EntitySchemaConformanceError::heterogeneous_set(
+                                        uid, tag, false, err,
+                                    ),
                                 )
                             }
                             ctx => JsonDeserializationError::heterogeneous_set(ctx, err),
@@ -644,7 +663,14 @@ impl<'e> ValueParser<'e> {
                             JsonDeserializationErrorContext::EntityAttribute { uid, attr } => {
                                 JsonDeserializationError::EntitySchemaConformance(
                                     EntitySchemaConformanceError::extension_function_lookup(
-                                        uid, attr, err,
+                                        uid, attr, true, err,
+                                    ),
+                                )
+                            }
+                            JsonDeserializationErrorContext::EntityTag { uid, tag } => {
+                                JsonDeserializationError::EntitySchemaConformance(
+                                    EntitySchemaConformanceError::extension_function_lookup(
+                                        uid, tag, false, err,
                                     ),
                                 )
                             }
@@ -0,0 +1,38 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains the [`EntityLoader`] trait, used to fetch entities on
+//! demand during authorization instead of materializing an [`super::Entities`]
+//! up front.
+
+use crate::ast::{Entity, EntityUID};
+
+/// A source of entities that the authorizer queries lazily, as evaluation
+/// reaches entities it doesn't have yet.
+///
+/// See [`crate::authorizer::Authorizer::is_authorized_with_loader()`].
+pub trait EntityLoader {
+    /// Error produced when loading fails
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Load the entities with the given UIDs.
+    ///
+    /// UIDs for which no entity exists should simply be left out of the
+    /// result. Returning an entity that wasn't asked for is allowed; it is
+    /// added to the entities for the request like any other. The parents of
+    /// returned entities don't need to be transitively closed.
+    fn load_entities(&mut self, uids: &[&EntityUID]) -> Result<Vec<Entity>, Self::Error>;
+}
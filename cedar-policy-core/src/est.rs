@@ -2221,6 +2221,86 @@ mod test {
         assert_eq!(circular_roundtrip(est.clone()), est);
     }
 
+    #[test]
+    fn has_tag_get_tag() {
+        let policy = r#"
+            permit(principal, action, resource)
+            when {
+                resource.hasTag("owner") && resource.getTag("owner") == principal
+            };
+        "#;
+        let cst = parser::text_to_cst::parse_policy(policy)
+            .unwrap()
+            .node
+            .unwrap();
+        let est: Policy = cst.try_into().unwrap();
+        let expected_json = json!(
+            {
+                "effect": "permit",
+                "principal": {
+                    "op": "All",
+                },
+                "action": {
+                    "op": "All",
+                },
+                "resource": {
+                    "op": "All",
+                },
+                "conditions": [
+                    {
+                        "kind": "when",
+                        "body": {
+                            "&&": {
+                                "left": {
+                                    "hasTag": {
+                                        "left": {
+                                            "Var": "resource"
+                                        },
+                                        "right": {
+                                            "Value": "owner"
+                                        }
+                                    }
+                                },
+                                "right": {
+                                    "==": {
+                                        "left": {
+                                            "getTag": {
+                                                "left": {
+                                                    "Var": "resource"
+                                                },
+                                                "right": {
+                                                    "Value": "owner"
+                                                }
+                                            }
+                                        },
+                                        "right": {
+                                            "Var": "principal"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                ]
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(&est).unwrap(),
+            expected_json,
+            "\nExpected:\n{}\n\nActual:\n{}\n\n",
+            serde_json::to_string_pretty(&expected_json).unwrap(),
+            serde_json::to_string_pretty(&est).unwrap()
+        );
+        let old_est = est.clone();
+        let roundtripped = est_roundtrip(est);
+        assert_eq!(&old_est, &roundtripped);
+        let est = text_roundtrip(&old_est);
+        assert_eq!(&old_est, &est);
+
+        assert_eq!(ast_roundtrip(est.clone()), est);
+        assert_eq!(circular_roundtrip(est.clone()), est);
+    }
+
     #[test]
     fn like_special_patterns() {
         let policy = r#"
@@ -245,6 +245,22 @@ pub enum ExprNoExt {
         /// Right-hand argument (inside the `()`)
         right: Arc<Expr>,
     },
+    /// `getTag()`
+    #[serde(rename = "getTag")]
+    GetTag {
+        /// Left-hand argument (receiver)
+        left: Arc<Expr>,
+        /// Right-hand argument (inside the `()`)
+        right: Arc<Expr>,
+    },
+    /// `hasTag()`
+    #[serde(rename = "hasTag")]
+    HasTag {
+        /// Left-hand argument (receiver)
+        left: Arc<Expr>,
+        /// Right-hand argument (inside the `()`)
+        right: Arc<Expr>,
+    },
     /// Get-attribute
     #[serde(rename = ".")]
     GetAttr {
@@ -480,6 +496,22 @@ impl Expr {
         })
     }
 
+    /// `left.getTag(right)`
+    pub fn get_tag(left: Arc<Expr>, right: Expr) -> Self {
+        Expr::ExprNoExt(ExprNoExt::GetTag {
+            left,
+            right: Arc::new(right),
+        })
+    }
+
+    /// `left.hasTag(right)`
+    pub fn has_tag(left: Arc<Expr>, right: Expr) -> Self {
+        Expr::ExprNoExt(ExprNoExt::HasTag {
+            left,
+            right: Arc::new(right),
+        })
+    }
+
     /// `left.attr`
     pub fn get_attr(left: Expr, attr: SmolStr) -> Self {
         Expr::ExprNoExt(ExprNoExt::GetAttr {
@@ -638,6 +670,14 @@ impl Expr {
                 (*left).clone().try_into_ast(id.clone())?,
                 (*right).clone().try_into_ast(id)?,
             )),
+            Expr::ExprNoExt(ExprNoExt::GetTag { left, right }) => Ok(ast::Expr::get_tag(
+                (*left).clone().try_into_ast(id.clone())?,
+                (*right).clone().try_into_ast(id)?,
+            )),
+            Expr::ExprNoExt(ExprNoExt::HasTag { left, right }) => Ok(ast::Expr::has_tag(
+                (*left).clone().try_into_ast(id.clone())?,
+                (*right).clone().try_into_ast(id)?,
+            )),
             Expr::ExprNoExt(ExprNoExt::GetAttr { left, attr }) => {
                 Ok(ast::Expr::get_attr((*left).clone().try_into_ast(id)?, attr))
             }
@@ -770,6 +810,8 @@ impl From<ast::Expr> for Expr {
                     ast::BinaryOp::Contains => Expr::contains(Arc::new(arg1), arg2),
                     ast::BinaryOp::ContainsAll => Expr::contains_all(Arc::new(arg1), arg2),
                     ast::BinaryOp::ContainsAny => Expr::contains_any(Arc::new(arg1), arg2),
+                    ast::BinaryOp::GetTag => Expr::get_tag(Arc::new(arg1), arg2),
+                    ast::BinaryOp::HasTag => Expr::has_tag(Arc::new(arg1), arg2),
                 }
             }
             ast::ExprKind::ExtensionFunctionApp { fn_name, args } => {
@@ -1276,6 +1318,14 @@ impl TryFrom<&Node<Option<cst::Member>>> for Expr {
                                     left,
                                     extract_single_argument(args, "containsAny()", &access.loc)?,
                                 )),
+                                "getTag" => Either::Right(Expr::get_tag(
+                                    left,
+                                    extract_single_argument(args, "getTag()", &access.loc)?,
+                                )),
+                                "hasTag" => Either::Right(Expr::has_tag(
+                                    left,
+                                    extract_single_argument(args, "hasTag()", &access.loc)?,
+                                )),
                                 _ => {
                                     // have to add the "receiver" argument as
                                     // first in the list for the method call
@@ -1591,6 +1641,12 @@ impl std::fmt::Display for ExprNoExt {
             ExprNoExt::ContainsAny { left, right } => {
                 write!(f, "{}.containsAny({right})", maybe_with_parens(left))
             }
+            ExprNoExt::GetTag { left, right } => {
+                write!(f, "{}.getTag({right})", maybe_with_parens(left))
+            }
+            ExprNoExt::HasTag { left, right } => {
+                write!(f, "{}.hasTag({right})", maybe_with_parens(left))
+            }
             ExprNoExt::GetAttr { left, attr } => write!(
                 f,
                 "{}[\"{}\"]",
@@ -1713,6 +1769,8 @@ fn maybe_with_parens(expr: &Expr) -> String {
         Expr::ExprNoExt(ExprNoExt::Contains { .. }) => format!("({expr})"),
         Expr::ExprNoExt(ExprNoExt::ContainsAll { .. }) => format!("({expr})"),
         Expr::ExprNoExt(ExprNoExt::ContainsAny { .. }) => format!("({expr})"),
+        Expr::ExprNoExt(ExprNoExt::GetTag { .. }) => format!("({expr})"),
+        Expr::ExprNoExt(ExprNoExt::HasTag { .. }) => format!("({expr})"),
         Expr::ExprNoExt(ExprNoExt::GetAttr { .. }) => format!("({expr})"),
         Expr::ExprNoExt(ExprNoExt::HasAttr { .. }) => format!("({expr})"),
         Expr::ExprNoExt(ExprNoExt::Like { .. }) => format!("({expr})"),
@@ -20,16 +20,24 @@ use crate::ast::*;
 use crate::entities::{Dereference, Entities};
 use crate::extensions::Extensions;
 use crate::parser::Loc;
-#[cfg(test)]
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 mod err;
 pub(crate) use err::*;
-pub use err::{EvaluationError, EvaluationErrorKind};
+pub use err::{EvaluationError, EvaluationErrorKind, LimitExceededError};
 use itertools::Either;
-use nonempty::nonempty;
+use nonempty::{nonempty, NonEmpty};
 use smol_str::SmolStr;
+use std::cell::{Cell, RefCell};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+mod limits;
+pub use limits::EvaluationLimits;
+mod trace;
+use trace::Tracer;
+pub use trace::{EvaluationTrace, TraceOutcome, TraceStep};
 
 #[cfg(not(target_arch = "wasm32"))]
 const REQUIRED_STACK_SPACE: usize = 1024 * 100;
@@ -65,6 +73,35 @@ pub struct Evaluator<'e> {
     entities: &'e Entities,
     /// Extensions which are active for this evaluation
     extensions: &'e Extensions<'e>,
+    /// Records the sub-expressions evaluated, if tracing is enabled with
+    /// `with_trace()`
+    tracer: Option<RefCell<Tracer>>,
+    /// Limits on the work done by this `Evaluator`, set with `with_limits()`
+    limits: EvaluationLimits,
+    /// Number of (sub-)expressions evaluated so far; only counted if
+    /// `limits` has a step limit
+    steps: Cell<u64>,
+    /// When the timeout in `limits` runs out, if there is one
+    #[cfg(not(target_arch = "wasm32"))]
+    deadline: Option<Instant>,
+    /// UIDs of the entities looked up in `entities`, if enabled with
+    /// `record_entity_reads()`
+    entity_reads: Option<RefCell<HashSet<EntityUID>>>,
+    /// Results shared with other `Evaluator`s over the same `entities`, if
+    /// enabled with `with_cache()`
+    cache: Option<&'e EvaluationCache>,
+}
+
+/// Results of entity attribute accesses and `in` tests. These only depend on
+/// the `Entities`, so the `Evaluator`s of different requests against the same
+/// `Entities` can share them.
+#[derive(Debug, Default)]
+pub(crate) struct EvaluationCache {
+    /// Values of entity attributes, by entity and attribute name
+    attrs: RefCell<HashMap<EntityUID, HashMap<SmolStr, PartialValue>>>,
+    /// Results of `in` tests, by the entity on the left and the entities on
+    /// the right
+    ins: RefCell<HashMap<EntityUID, HashMap<Vec<EntityUID>, PartialValue>>>,
 }
 
 /// Evaluator for "restricted" expressions. See notes on `RestrictedExpr`.
@@ -195,7 +232,126 @@ impl<'e> Evaluator<'e> {
             },
             entities,
             extensions,
+            tracer: None,
+            limits: EvaluationLimits::default(),
+            steps: Cell::new(0),
+            #[cfg(not(target_arch = "wasm32"))]
+            deadline: None,
+            entity_reads: None,
+            cache: None,
+        }
+    }
+
+    /// Look up entity attributes and the results of `in` tests in `cache`
+    /// before computing them, and add the ones computed to `cache`. The
+    /// `cache` must only be shared by `Evaluator`s over the same `Entities`.
+    pub(crate) fn with_cache(mut self, cache: &'e EvaluationCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Record the UID of every entity this `Evaluator` looks up, whether or
+    /// not the entity exists, until collected with `take_entity_reads()`.
+    pub(crate) fn record_entity_reads(mut self) -> Self {
+        self.entity_reads = Some(RefCell::new(HashSet::new()));
+        self
+    }
+
+    /// Take the UIDs of the entities looked up since recording was enabled or
+    /// the UIDs were last taken. Returns `None` if recording is not enabled.
+    pub(crate) fn take_entity_reads(&self) -> Option<HashSet<EntityUID>> {
+        self.entity_reads
+            .as_ref()
+            .map(|reads| std::mem::take(&mut *reads.borrow_mut()))
+    }
+
+    /// Look up `uid` in the `Entities`, recording the read if enabled
+    fn entity(&self, uid: &EntityUID) -> Dereference<'e, Entity> {
+        if let Some(reads) = &self.entity_reads {
+            reads.borrow_mut().insert(uid.clone());
+        }
+        self.entities.entity(uid)
+    }
+
+    /// The cache enabled with `with_cache()`, unless entity reads are being
+    /// recorded: a cached result skips the lookups that must be recorded
+    fn cache(&self) -> Option<&'e EvaluationCache> {
+        self.cache.filter(|_| self.entity_reads.is_none())
+    }
+
+    /// The value of attribute `attr` of the entity `uid`, if it is cached
+    fn cached_attr(&self, uid: &EntityUID, attr: &str) -> Option<PartialValue> {
+        self.cache()?
+            .attrs
+            .borrow()
+            .get(uid)
+            .and_then(|attrs| attrs.get(attr).cloned())
+    }
+
+    /// Enforce the given limits on everything this `Evaluator` evaluates from
+    /// now on. The step budget and the timeout are shared by all expressions
+    /// evaluated, so they bound the total cost of a request.
+    pub fn with_limits(mut self, limits: EvaluationLimits) -> Self {
+        self.limits = limits;
+        self.steps.set(0);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.deadline = limits
+                .timeout()
+                .and_then(|timeout| Instant::now().checked_add(timeout));
         }
+        self
+    }
+
+    /// Count one evaluation step, returning an error if this exceeds the step
+    /// budget or the timeout has run out
+    fn check_limits(&self) -> Result<()> {
+        if let Some(limit) = self.limits.max_steps() {
+            let steps = self.steps.get().saturating_add(1);
+            self.steps.set(steps);
+            if steps > limit {
+                return Err(EvaluationError::limit_exceeded(
+                    LimitExceededError::Steps { limit },
+                    None,
+                ));
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let (Some(deadline), Some(timeout)) = (self.deadline, self.limits.timeout()) {
+            if Instant::now() > deadline {
+                return Err(EvaluationError::limit_exceeded(
+                    LimitExceededError::Timeout { timeout },
+                    None,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Return an error if a set or record with `size` elements is larger than
+    /// allowed
+    fn check_collection_size(&self, size: usize, loc: Option<&Loc>) -> Result<()> {
+        match self.limits.max_collection_size() {
+            Some(limit) if size > limit => Err(EvaluationError::limit_exceeded(
+                LimitExceededError::CollectionSize { size, limit },
+                loc.cloned(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Enable tracing: record every sub-expression this `Evaluator` evaluates,
+    /// along with its result, until the trace is collected with
+    /// `take_trace()`.
+    pub fn with_trace(mut self) -> Self {
+        self.tracer = Some(RefCell::new(Tracer::default()));
+        self
+    }
+
+    /// Take the trace recorded since tracing was enabled or the trace was last
+    /// taken. Returns `None` if tracing is not enabled.
+    pub fn take_trace(&self) -> Option<EvaluationTrace> {
+        self.tracer.as_ref().map(|t| t.borrow_mut().take())
     }
 
     /// Evaluate the given `Policy`, returning either a bool or an error.
@@ -218,7 +374,19 @@ impl<'e> Evaluator<'e> {
     /// it doesn't consider whether we're processing a `Permit` policy or a
     /// `Forbid` policy.
     pub fn partial_evaluate(&self, p: &Policy) -> Result<Either<bool, Expr>> {
-        match self.partial_interpret(&p.condition(), p.env())? {
+        self.partial_evaluate_condition(&p.condition(), p.env())
+    }
+
+    /// Partially evaluate a policy condition (as returned by
+    /// [`Policy::condition()`]) with the given slot values, with the same
+    /// results as [`Evaluator::partial_evaluate()`]. This lets callers that
+    /// evaluate the same policy for many requests build its condition once.
+    pub fn partial_evaluate_condition(
+        &self,
+        condition: &Expr,
+        slots: &SlotEnv,
+    ) -> Result<Either<bool, Expr>> {
+        match self.partial_interpret(condition, slots)? {
             PartialValue::Value(v) => v.get_as_bool().map(Either::Left),
             PartialValue::Residual(e) => Ok(Either::Right(e)),
         }
@@ -266,7 +434,11 @@ impl<'e> Evaluator<'e> {
     /// attribute that doesn't exist.
     pub fn partial_interpret(&self, expr: &Expr, slots: &SlotEnv) -> Result<PartialValue> {
         stack_size_check()?;
+        self.check_limits()?;
 
+        if let Some(tracer) = &self.tracer {
+            tracer.borrow_mut().enter(expr);
+        }
         let res = self.partial_interpret_internal(expr, slots);
 
         // set the returned value's source location to the same source location
@@ -276,11 +448,24 @@ impl<'e> Evaluator<'e> {
         // also, if there is an error, set its source location to the source
         // location of the input expression as well, unless it already had a
         // more specific location
-        res.map(|pval| pval.with_maybe_source_loc(expr.source_loc().cloned()))
+        let res = res
+            .map(|pval| pval.with_maybe_source_loc(expr.source_loc().cloned()))
             .map_err(|err| match err.source_loc() {
                 None => err.with_maybe_source_loc(expr.source_loc().cloned()),
                 Some(_) => err,
-            })
+            });
+        if let Some(tracer) = &self.tracer {
+            tracer.borrow_mut().exit(&res);
+        }
+        res
+    }
+
+    /// Mark the sub-expression currently being evaluated as having
+    /// short-circuited, if tracing is enabled
+    fn trace_short_circuit(&self) {
+        if let Some(tracer) = &self.tracer {
+            tracer.borrow_mut().short_circuit();
+        }
     }
 
     /// Internal function to interpret an `Expr`. (External callers, use
@@ -315,10 +500,13 @@ impl<'e> Evaluator<'e> {
             ExprKind::And { left, right } => {
                 match self.partial_interpret(left, slots)? {
                     // PE Case
-                    PartialValue::Residual(e) => Ok(PartialValue::Residual(Expr::and(
-                        e,
-                        self.run_to_error(right.as_ref(), slots).0.into(),
-                    ))),
+                    PartialValue::Residual(e) => {
+                        e.check_unknown_type(nonempty![Type::Bool])?;
+                        Ok(PartialValue::Residual(Expr::and(
+                            e,
+                            self.run_to_error(right.as_ref(), slots).0.into(),
+                        )))
+                    }
                     // Full eval case
                     PartialValue::Value(v) => {
                         if v.get_as_bool()? {
@@ -327,6 +515,7 @@ impl<'e> Evaluator<'e> {
                                 // <residual> must be boolean, or else it needs to type error. So return `true && <residual>` to ensure
                                 // type check happens
                                 PartialValue::Residual(right) => {
+                                    right.check_unknown_type(nonempty![Type::Bool])?;
                                     Ok(PartialValue::Residual(Expr::and(Expr::val(true), right)))
                                 }
                                 // If it's an actual value, compute and
@@ -334,6 +523,7 @@ impl<'e> Evaluator<'e> {
                             }
                         } else {
                             // We can short circuit here
+                            self.trace_short_circuit();
                             Ok(false.into())
                         }
                     }
@@ -342,14 +532,18 @@ impl<'e> Evaluator<'e> {
             ExprKind::Or { left, right } => {
                 match self.partial_interpret(left, slots)? {
                     // PE cases
-                    PartialValue::Residual(r) => Ok(PartialValue::Residual(Expr::or(
-                        r,
-                        self.run_to_error(right, slots).0.into(),
-                    ))),
+                    PartialValue::Residual(r) => {
+                        r.check_unknown_type(nonempty![Type::Bool])?;
+                        Ok(PartialValue::Residual(Expr::or(
+                            r,
+                            self.run_to_error(right, slots).0.into(),
+                        )))
+                    }
                     // Full eval case
                     PartialValue::Value(lhs) => {
                         if lhs.get_as_bool()? {
                             // We can short circuit here
+                            self.trace_short_circuit();
                             Ok(true.into())
                         } else {
                             match self.partial_interpret(right, slots)? {
@@ -358,6 +552,7 @@ impl<'e> Evaluator<'e> {
                                 // <residual> must be boolean, or else it needs to type error. So return `false || <residual>` to ensure
                                 // type check happens
                                 {
+                                    rhs.check_unknown_type(nonempty![Type::Bool])?;
                                     Ok(PartialValue::Residual(Expr::or(Expr::val(false), rhs)))
                                 }
                                 PartialValue::Value(v) => Ok(v.get_as_bool()?.into()),
@@ -385,40 +580,64 @@ impl<'e> Evaluator<'e> {
                 },
                 // NOTE, there was a bug here found during manual review. (I forgot to wrap in unary_app call)
                 // Could be a nice target for fault injection
-                PartialValue::Residual(r) => Ok(PartialValue::Residual(Expr::unary_app(*op, r))),
+                PartialValue::Residual(r) => {
+                    r.check_unknown_type(match op {
+                        UnaryOp::Not => nonempty![Type::Bool],
+                        UnaryOp::Neg => nonempty![Type::Long],
+                    })?;
+                    Ok(PartialValue::Residual(Expr::unary_app(*op, r)))
+                }
             },
             ExprKind::BinaryApp { op, arg1, arg2 } => {
                 // NOTE: There are more precise partial eval opportunities here, esp w/ typed unknowns
                 // Current limitations:
-                //   Operators are not partially evaluated.
+                //   Operators are not partially evaluated, except that
+                //   operands which are typed unknowns are checked against
+                //   the types the operator expects.
                 let (arg1, arg2) = match (
                     self.partial_interpret(arg1, slots)?,
                     self.partial_interpret(arg2, slots)?,
                 ) {
                     (PartialValue::Value(v1), PartialValue::Value(v2)) => (v1, v2),
-                    (PartialValue::Value(v1), PartialValue::Residual(e2)) => {
-                        return Ok(PartialValue::Residual(Expr::binary_app(*op, v1.into(), e2)))
-                    }
-                    (PartialValue::Residual(e1), PartialValue::Value(v2)) => {
-                        return Ok(PartialValue::Residual(Expr::binary_app(*op, e1, v2.into())))
-                    }
-                    (PartialValue::Residual(e1), PartialValue::Residual(e2)) => {
-                        return Ok(PartialValue::Residual(Expr::binary_app(*op, e1, e2)))
-                    }
+                    (arg1, arg2) => return Self::binary_app_residual(*op, arg1, arg2),
                 };
                 match op {
                     BinaryOp::Eq => Ok((arg1 == arg2).into()),
-                    // comparison and arithmetic operators, which only work on Longs
-                    BinaryOp::Less
-                    | BinaryOp::LessEq
-                    | BinaryOp::Add
-                    | BinaryOp::Sub
-                    | BinaryOp::Mul => {
+                    // comparison operators, which work on Longs and on
+                    // extension values that support operator overloading
+                    BinaryOp::Less | BinaryOp::LessEq => {
+                        let ordering = match &arg1.value {
+                            ValueKind::ExtensionValue(x) if x.supports_operator_overloading() => {
+                                match &arg2.value {
+                                    ValueKind::ExtensionValue(y)
+                                        if x.typename() == y.typename() =>
+                                    {
+                                        x.cmp(y)
+                                    }
+                                    _ => {
+                                        return Err(EvaluationError::type_error_single(
+                                            Type::Extension { name: x.typename() },
+                                            &arg2,
+                                        ))
+                                    }
+                                }
+                            }
+                            _ => {
+                                let i1 = arg1.get_as_long()?;
+                                let i2 = arg2.get_as_long()?;
+                                i1.cmp(&i2)
+                            }
+                        };
+                        match op {
+                            BinaryOp::Less => Ok(ordering.is_lt().into()),
+                            _ => Ok(ordering.is_le().into()),
+                        }
+                    }
+                    // arithmetic operators, which only work on Longs
+                    BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul => {
                         let i1 = arg1.get_as_long()?;
                         let i2 = arg2.get_as_long()?;
                         match op {
-                            BinaryOp::Less => Ok((i1 < i2).into()),
-                            BinaryOp::LessEq => Ok((i1 <= i2).into()),
                             BinaryOp::Add => match i1.checked_add(i2) {
                                 Some(sum) => Ok(sum.into()),
                                 None => Err(EvaluationError::integer_overflow(
@@ -475,7 +694,7 @@ impl<'e> Evaluator<'e> {
                                 };
                                 e
                             })?;
-                        match self.entities.entity(uid1) {
+                        match self.entity(uid1) {
                             Dereference::Residual(r) => Ok(PartialValue::Residual(
                                 Expr::binary_app(BinaryOp::In, r, arg2.into()),
                             )),
@@ -544,6 +763,49 @@ impl<'e> Evaluator<'e> {
                             }
                         }
                     }
+                    // GetTag and HasTag, which work on an entity and a string
+                    BinaryOp::GetTag | BinaryOp::HasTag => {
+                        let uid = arg1.get_as_entity()?;
+                        let tag = arg2.get_as_string()?;
+                        match op {
+                            BinaryOp::GetTag => match self.entity(uid) {
+                                Dereference::NoSuchEntity => {
+                                    // intentionally using the location of the euid (the LHS) and not the entire GetTag expression
+                                    Err(EvaluationError::entity_does_not_exist(
+                                        Arc::new(uid.clone()),
+                                        arg1.source_loc().cloned(),
+                                    ))
+                                }
+                                Dereference::Residual(r) => {
+                                    Ok(PartialValue::Residual(Expr::get_tag(r, arg2.into())))
+                                }
+                                Dereference::Data(entity) => entity
+                                    .get_tag(tag)
+                                    .ok_or_else(|| {
+                                        EvaluationError::entity_tag_does_not_exist(
+                                            Arc::new(uid.clone()),
+                                            tag.clone(),
+                                            loc.cloned(),
+                                        )
+                                    })
+                                    .cloned(),
+                            },
+                            BinaryOp::HasTag => match self.entity(uid) {
+                                Dereference::NoSuchEntity => Ok(false.into()),
+                                Dereference::Residual(r) => {
+                                    Ok(PartialValue::Residual(Expr::has_tag(r, arg2.into())))
+                                }
+                                Dereference::Data(entity) => {
+                                    Ok(entity.get_tag(tag).is_some().into())
+                                }
+                            },
+                            // PANIC SAFETY `op` is checked to be one of these two above
+                            #[allow(clippy::unreachable)]
+                            _ => {
+                                unreachable!("Should have already checked that op was one of these")
+                            }
+                        }
+                    }
                 }
             }
             ExprKind::ExtensionFunctionApp { fn_name, args } => {
@@ -573,7 +835,7 @@ impl<'e> Evaluator<'e> {
                 PartialValue::Value(Value {
                     value: ValueKind::Lit(Literal::EntityUID(uid)),
                     ..
-                }) => match self.entities.entity(&uid) {
+                }) => match self.entity(&uid) {
                     Dereference::NoSuchEntity => Ok(false.into()),
                     Dereference::Residual(r) => {
                         Ok(PartialValue::Residual(Expr::has_attr(r, attr.clone())))
@@ -587,7 +849,13 @@ impl<'e> Evaluator<'e> {
                     ],
                     &val,
                 )),
-                PartialValue::Residual(r) => Ok(Expr::has_attr(r, attr.clone()).into()),
+                PartialValue::Residual(r) => {
+                    r.check_unknown_type(nonempty![
+                        Type::Record,
+                        Type::entity_type(names::ANY_ENTITY_TYPE.clone())
+                    ])?;
+                    Ok(Expr::has_attr(r, attr.clone()).into())
+                }
             },
             ExprKind::Like { expr, pattern } => {
                 let v = self.partial_interpret(expr, slots)?;
@@ -595,7 +863,10 @@ impl<'e> Evaluator<'e> {
                     PartialValue::Value(v) => {
                         Ok((pattern.wildcard_match(v.get_as_string()?)).into())
                     }
-                    PartialValue::Residual(r) => Ok(Expr::like(r, pattern.iter().cloned()).into()),
+                    PartialValue::Residual(r) => {
+                        r.check_unknown_type(nonempty![Type::String])?;
+                        Ok(Expr::like(r, pattern.iter().cloned()).into())
+                    }
                 }
             }
             ExprKind::Is { expr, entity_type } => {
@@ -607,11 +878,24 @@ impl<'e> Evaluator<'e> {
                     }
                     .into()),
                     PartialValue::Residual(r) => {
-                        Ok(Expr::is_entity_type(r, entity_type.clone()).into())
+                        r.check_unknown_type(nonempty![Type::entity_type(
+                            names::ANY_ENTITY_TYPE.clone()
+                        )])?;
+                        // an unknown annotated with an entity type can only
+                        // be filled in with an entity of that type
+                        match r.unknown_type() {
+                            Some(Type::Entity { ty }) => Ok(match ty {
+                                EntityType::Specified(ty) => ty == entity_type,
+                                EntityType::Unspecified => false,
+                            }
+                            .into()),
+                            _ => Ok(Expr::is_entity_type(r, entity_type.clone()).into()),
+                        }
                     }
                 }
             }
             ExprKind::Set(items) => {
+                self.check_collection_size(items.len(), loc)?;
                 let vals = items
                     .iter()
                     .map(|item| self.partial_interpret(item, slots))
@@ -622,6 +906,7 @@ impl<'e> Evaluator<'e> {
                 }
             }
             ExprKind::Record(map) => {
+                self.check_collection_size(map.len(), loc)?;
                 let map = map
                     .iter()
                     .map(|(k, v)| Ok((k.clone(), self.partial_interpret(v, slots)?)))
@@ -668,18 +953,138 @@ impl<'e> Evaluator<'e> {
                 ))
             }
         };
+        if let Some(cache) = self.cache() {
+            let cached = cache
+                .ins
+                .borrow()
+                .get(uid1)
+                .and_then(|ins| ins.get(rhs.as_slice()).cloned());
+            if let Some(result) = cached {
+                return Ok(result);
+            }
+            let result = self.eval_in_uncached(uid1, entity1, &rhs);
+            cache
+                .ins
+                .borrow_mut()
+                .entry(uid1.clone())
+                .or_default()
+                .insert(rhs, result.clone());
+            return Ok(result);
+        }
+        Ok(self.eval_in_uncached(uid1, entity1, &rhs))
+    }
+
+    /// Decide whether `uid1`, whose entity is `entity1`, is in any of the
+    /// entities `rhs`
+    fn eval_in_uncached(
+        &self,
+        uid1: &EntityUID,
+        entity1: Option<&Entity>,
+        rhs: &[EntityUID],
+    ) -> PartialValue {
         for uid2 in rhs {
-            if uid1 == &uid2
-                || entity1
-                    .map(|e1| e1.is_descendant_of(&uid2))
-                    .unwrap_or(false)
-            {
-                return Ok(true.into());
+            if uid1 == uid2 || entity1.map(|e1| e1.is_descendant_of(uid2)).unwrap_or(false) {
+                return true.into();
             }
         }
-        // if we get here, `uid1` is not a descendant of (or equal to)
-        // any UID in `rhs`
-        Ok(false.into())
+        // if we get here, `uid1` is not a known descendant of (or equal to)
+        // any UID in `rhs`. That's only conclusive if neither `uid1` nor any
+        // of its ancestors has ancestors that are unknown.
+        let mut unknowns: Vec<&Unknown> = Vec::new();
+        if let Some(entity1) = entity1 {
+            let ancestors = entity1
+                .ancestors()
+                .filter_map(|uid| match self.entity(uid) {
+                    Dereference::Data(e) => e.unknown_ancestors(),
+                    Dereference::NoSuchEntity | Dereference::Residual(_) => None,
+                });
+            for unknown in entity1.unknown_ancestors().into_iter().chain(ancestors) {
+                if !unknowns.contains(&unknown) {
+                    unknowns.push(unknown);
+                }
+            }
+        }
+        let residual = unknowns
+            .into_iter()
+            .map(|unknown| {
+                let unknown = Expr::unknown(unknown.clone());
+                match rhs {
+                    [uid2] => Expr::contains(unknown, Expr::val(uid2.clone())),
+                    _ => Expr::contains_any(unknown, Expr::set(rhs.iter().cloned().map(Expr::val))),
+                }
+            })
+            .reduce(Expr::or);
+        match residual {
+            Some(residual) => PartialValue::Residual(residual),
+            None => false.into(),
+        }
+    }
+
+    /// Build the residual for a binary operator at least one of whose operands
+    /// is a residual.
+    ///
+    /// Operands that are typed unknowns are checked against the types the
+    /// operator expects, and `==` is decided when the annotated types show
+    /// that the operands can never be equal.
+    fn binary_app_residual(
+        op: BinaryOp,
+        arg1: PartialValue,
+        arg2: PartialValue,
+    ) -> Result<PartialValue> {
+        let entity = || Type::entity_type(names::ANY_ENTITY_TYPE.clone());
+        let type_of = |arg: &PartialValue| match arg {
+            PartialValue::Value(v) => Some(v.type_of()),
+            PartialValue::Residual(r) => r.unknown_type().cloned(),
+        };
+        let (expected1, expected2) = match op {
+            BinaryOp::Eq => {
+                if let (Some(ty1), Some(ty2)) = (type_of(&arg1), type_of(&arg2)) {
+                    if ty1 != ty2 {
+                        return Ok(false.into());
+                    }
+                }
+                (None, None)
+            }
+            // comparisons also work on extension types supporting operator
+            // overloading, so the expected type of each operand is a long
+            // unless the other operand is an extension value
+            BinaryOp::Less | BinaryOp::LessEq => match (type_of(&arg1), type_of(&arg2)) {
+                (Some(ty @ Type::Extension { .. }), _) | (_, Some(ty @ Type::Extension { .. })) => {
+                    (Some(nonempty![ty.clone()]), Some(nonempty![ty]))
+                }
+                _ => (Some(nonempty![Type::Long]), Some(nonempty![Type::Long])),
+            },
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul => {
+                (Some(nonempty![Type::Long]), Some(nonempty![Type::Long]))
+            }
+            BinaryOp::In => (
+                Some(nonempty![entity()]),
+                Some(nonempty![Type::Set, entity()]),
+            ),
+            BinaryOp::Contains => (Some(nonempty![Type::Set]), None),
+            BinaryOp::ContainsAll | BinaryOp::ContainsAny => {
+                (Some(nonempty![Type::Set]), Some(nonempty![Type::Set]))
+            }
+            BinaryOp::GetTag | BinaryOp::HasTag => {
+                (Some(nonempty![entity()]), Some(nonempty![Type::String]))
+            }
+        };
+        let residual = |arg: PartialValue, expected: Option<NonEmpty<Type>>| -> Result<Expr> {
+            match arg {
+                PartialValue::Value(v) => Ok(Expr::from(v)),
+                PartialValue::Residual(r) => {
+                    if let Some(expected) = expected {
+                        r.check_unknown_type(expected)?;
+                    }
+                    Ok(r)
+                }
+            }
+        };
+        Ok(PartialValue::Residual(Expr::binary_app(
+            op,
+            residual(arg1, expected1)?,
+            residual(arg2, expected2)?,
+        )))
     }
 
     /// Evaluation of conditionals
@@ -700,6 +1105,7 @@ impl<'e> Evaluator<'e> {
                 }
             }
             PartialValue::Residual(guard) => {
+                guard.check_unknown_type(nonempty![Type::Bool])?;
                 let (consequent, consequent_errored) = self.run_to_error(consequent, slots);
                 let (alternative, alternative_errored) = self.run_to_error(alternative, slots);
                 // If both branches errored, the expression will always error
@@ -757,7 +1163,13 @@ impl<'e> Evaluator<'e> {
                         }
                     }
                     // We got a residual, that is not a record at the top level
-                    _ => Ok(PartialValue::Residual(Expr::get_attr(res, attr.clone()))),
+                    _ => {
+                        res.check_unknown_type(nonempty![
+                            Type::Record,
+                            Type::entity_type(names::ANY_ENTITY_TYPE.clone())
+                        ])?;
+                        Ok(PartialValue::Residual(Expr::get_attr(res, attr.clone())))
+                    }
                 }
             }
             PartialValue::Value(Value {
@@ -777,31 +1189,45 @@ impl<'e> Evaluator<'e> {
             PartialValue::Value(Value {
                 value: ValueKind::Lit(Literal::EntityUID(uid)),
                 loc,
-            }) => match self.entities.entity(uid.as_ref()) {
-                Dereference::NoSuchEntity => Err(match *uid.entity_type() {
-                    EntityType::Unspecified => EvaluationError::unspecified_entity_access(
-                        attr.clone(),
-                        source_loc.cloned(),
-                    ),
-                    EntityType::Specified(_) => {
-                        // intentionally using the location of the euid (the LHS) and not the entire GetAttr expression
-                        EvaluationError::entity_does_not_exist(uid.clone(), loc)
-                    }
-                }),
-                Dereference::Residual(r) => {
-                    Ok(PartialValue::Residual(Expr::get_attr(r, attr.clone())))
+            }) => {
+                if let Some(value) = self.cached_attr(&uid, attr) {
+                    return Ok(value);
                 }
-                Dereference::Data(entity) => entity
-                    .get(attr)
-                    .ok_or_else(|| {
-                        EvaluationError::entity_attr_does_not_exist(
-                            uid,
+                let value = match self.entity(uid.as_ref()) {
+                    Dereference::NoSuchEntity => Err(match *uid.entity_type() {
+                        EntityType::Unspecified => EvaluationError::unspecified_entity_access(
                             attr.clone(),
                             source_loc.cloned(),
-                        )
-                    })
-                    .cloned(),
-            },
+                        ),
+                        EntityType::Specified(_) => {
+                            // intentionally using the location of the euid (the LHS) and not the entire GetAttr expression
+                            EvaluationError::entity_does_not_exist(uid.clone(), loc)
+                        }
+                    }),
+                    Dereference::Residual(r) => {
+                        Ok(PartialValue::Residual(Expr::get_attr(r, attr.clone())))
+                    }
+                    Dereference::Data(entity) => entity
+                        .get(attr)
+                        .ok_or_else(|| {
+                            EvaluationError::entity_attr_does_not_exist(
+                                uid.clone(),
+                                attr.clone(),
+                                source_loc.cloned(),
+                            )
+                        })
+                        .cloned(),
+                }?;
+                if let Some(cache) = self.cache() {
+                    cache
+                        .attrs
+                        .borrow_mut()
+                        .entry((*uid).clone())
+                        .or_default()
+                        .insert(attr.clone(), value.clone());
+                }
+                Ok(value)
+            }
             PartialValue::Value(v) => {
                 // PANIC SAFETY Entity type name is fully static and a valid unqualified `Name`
                 #[allow(clippy::unwrap_used)]
@@ -905,6 +1331,40 @@ impl Value {
     }
 }
 
+impl Expr {
+    /// If this residual is an unknown annotated with a type, get that type
+    fn unknown_type(&self) -> Option<&Type> {
+        match self.expr_kind() {
+            ExprKind::Unknown(Unknown {
+                type_annotation, ..
+            }) => type_annotation.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Throw a type error if this residual is an unknown annotated with a
+    /// type that isn't one of the `expected` types, since then the residual
+    /// would error no matter what the unknown is filled in with. Entity types
+    /// are not distinguished from each other here.
+    fn check_unknown_type(&self, expected: NonEmpty<Type>) -> Result<()> {
+        match self.unknown_type() {
+            Some(ty)
+                if !expected.iter().any(|expected| match (expected, ty) {
+                    (Type::Entity { .. }, Type::Entity { .. }) => true,
+                    (expected, ty) => expected == ty,
+                }) =>
+            {
+                Err(EvaluationError::unknown_type_error(
+                    expected,
+                    ty.clone(),
+                    self.source_loc().cloned(),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
 #[inline(always)]
 fn stack_size_check() -> Result<()> {
     #[cfg(not(target_arch = "wasm32"))]
@@ -1010,6 +1470,21 @@ pub mod test {
                 &Extensions::none(),
             )
             .unwrap();
+        let mut entity_with_tags = Entity::with_uid(EntityUID::with_eid("entity_with_tags"));
+        entity_with_tags
+            .set_tag(
+                "spoon".into(),
+                RestrictedExpr::val(-787),
+                &Extensions::none(),
+            )
+            .unwrap();
+        entity_with_tags
+            .set_tag(
+                "owner".into(),
+                RestrictedExpr::val(EntityUID::with_eid("parent")),
+                &Extensions::none(),
+            )
+            .unwrap();
         let mut child = Entity::with_uid(EntityUID::with_eid("child"));
         let mut parent = Entity::with_uid(EntityUID::with_eid("parent"));
         let grandparent = Entity::with_uid(EntityUID::with_eid("grandparent"));
@@ -1028,6 +1503,7 @@ pub mod test {
             vec![
                 entity_no_attrs_no_parents,
                 entity_with_attrs,
+                entity_with_tags,
                 child,
                 child_diff_type,
                 parent,
@@ -1070,22 +1546,12 @@ pub mod test {
         let r = eval.partial_eval_expr(&e).unwrap();
         let expected_residual = Expr::binary_app(
             BinaryOp::In,
-            Expr::unknown(Unknown::new_with_type(
-                format!("{missing}"),
-                Type::Entity {
-                    ty: EntityUID::test_entity_type(),
-                },
-            )),
+            Expr::unknown(Unknown::missing_entity(missing.clone())),
             Expr::set([Expr::val(parent.clone()), Expr::val(second.clone())]),
         );
         let expected_residual2 = Expr::binary_app(
             BinaryOp::In,
-            Expr::unknown(Unknown::new_with_type(
-                format!("{missing}"),
-                Type::Entity {
-                    ty: EntityUID::test_entity_type(),
-                },
-            )),
+            Expr::unknown(Unknown::missing_entity(missing.clone())),
             Expr::set([Expr::val(second), Expr::val(parent)]),
         );
 
@@ -1116,12 +1582,7 @@ pub mod test {
         let r = eval.partial_eval_expr(&e).unwrap();
         let expected_residual = Expr::binary_app(
             BinaryOp::In,
-            Expr::unknown(Unknown::new_with_type(
-                format!("{missing}"),
-                Type::Entity {
-                    ty: EntityUID::test_entity_type(),
-                },
-            )),
+            Expr::unknown(Unknown::missing_entity(missing.clone())),
             Expr::val(parent),
         );
         assert_eq!(r, Either::Right(expected_residual));
@@ -1144,17 +1605,42 @@ pub mod test {
         let e = Expr::has_attr(Expr::val(missing.clone()), "spoon".into());
         let r = eval.partial_eval_expr(&e).unwrap();
         let expected_residual = Expr::has_attr(
-            Expr::unknown(Unknown::new_with_type(
-                format!("{missing}"),
-                Type::Entity {
-                    ty: EntityUID::test_entity_type(),
-                },
-            )),
+            Expr::unknown(Unknown::missing_entity(missing.clone())),
             "spoon".into(),
         );
         assert_eq!(r, Either::Right(expected_residual));
     }
 
+    #[cfg(feature = "partial-eval")]
+    #[test]
+    fn partial_entity_stores_tags() {
+        let q = basic_request();
+        let entities = rich_entities().partial();
+        let exts = Extensions::none();
+        let has_tags = EntityUID::with_eid("entity_with_tags");
+        let missing = EntityUID::with_eid("missing");
+        let eval = Evaluator::new(q, &entities, &exts);
+
+        let e = Expr::has_tag(Expr::val(has_tags.clone()), Expr::val("spoon"));
+        let r = eval.partial_eval_expr(&e).unwrap();
+        assert_eq!(r, Either::Left(true.into()));
+
+        let e = Expr::get_tag(Expr::val(has_tags), Expr::val("spoon"));
+        let r = eval.partial_eval_expr(&e).unwrap();
+        assert_eq!(r, Either::Left((-787).into()));
+
+        let missing_unknown = Expr::unknown(Unknown::missing_entity(missing.clone()));
+        let e = Expr::has_tag(Expr::val(missing.clone()), Expr::val("spoon"));
+        let r = eval.partial_eval_expr(&e).unwrap();
+        let expected_residual = Expr::has_tag(missing_unknown.clone(), Expr::val("spoon"));
+        assert_eq!(r, Either::Right(expected_residual));
+
+        let e = Expr::get_tag(Expr::val(missing), Expr::val("spoon"));
+        let r = eval.partial_eval_expr(&e).unwrap();
+        let expected_residual = Expr::get_tag(missing_unknown, Expr::val("spoon"));
+        assert_eq!(r, Either::Right(expected_residual));
+    }
+
     #[cfg(feature = "partial-eval")]
     #[test]
     fn partial_entity_stores_getattr() {
@@ -1172,12 +1658,7 @@ pub mod test {
         let e = Expr::get_attr(Expr::val(missing.clone()), "spoon".into());
         let r = eval.partial_eval_expr(&e).unwrap();
         let expected_residual = Expr::get_attr(
-            Expr::unknown(Unknown::new_with_type(
-                format!("{missing}"),
-                Type::Entity {
-                    ty: EntityUID::test_entity_type(),
-                },
-            )),
+            Expr::unknown(Unknown::missing_entity(missing.clone())),
             "spoon".into(),
         );
         assert_eq!(r, Either::Right(expected_residual));
@@ -1402,6 +1883,150 @@ pub mod test {
         );
     }
 
+    #[test]
+    fn interpret_entity_tags() {
+        let request = basic_request();
+        let entities = rich_entities();
+        let exts = Extensions::none();
+        let eval = Evaluator::new(request, &entities, &exts);
+        // has_tag on an entity with no tags
+        assert_eq!(
+            eval.interpret_inline_policy(&Expr::has_tag(
+                Expr::val(EntityUID::with_eid("entity_with_attrs")),
+                Expr::val("spoon")
+            )),
+            Ok(Value::from(false))
+        );
+        // has_tag where the response is true, with a computed tag name
+        assert_eq!(
+            eval.interpret_inline_policy(&Expr::has_tag(
+                Expr::val(EntityUID::with_eid("entity_with_tags")),
+                Expr::ite(Expr::val(true), Expr::val("owner"), Expr::val("other"))
+            )),
+            Ok(Value::from(true))
+        );
+        // tags and attributes are separate namespaces
+        assert_eq!(
+            eval.interpret_inline_policy(&Expr::has_attr(
+                Expr::val(EntityUID::with_eid("entity_with_tags")),
+                "spoon".into()
+            )),
+            Ok(Value::from(false))
+        );
+        // get_tag on a tag which does exist
+        assert_eq!(
+            eval.interpret_inline_policy(&Expr::get_tag(
+                Expr::val(EntityUID::with_eid("entity_with_tags")),
+                Expr::val("spoon")
+            )),
+            Ok(Value::from(-787))
+        );
+        assert_eq!(
+            eval.interpret_inline_policy(&Expr::is_in(
+                Expr::val(EntityUID::with_eid("child")),
+                Expr::get_tag(
+                    Expr::val(EntityUID::with_eid("entity_with_tags")),
+                    Expr::val("owner")
+                )
+            )),
+            Ok(Value::from(true))
+        );
+        // get_tag on a tag which doesn't exist
+        assert_eq!(
+            eval.interpret_inline_policy(&Expr::get_tag(
+                Expr::val(EntityUID::with_eid("entity_with_attrs")),
+                Expr::val("spoon")
+            )),
+            Err(EvaluationError::entity_tag_does_not_exist(
+                Arc::new(EntityUID::with_eid("entity_with_attrs")),
+                "spoon".into(),
+                None,
+            ))
+        );
+        // has_tag and get_tag on an entity which doesn't exist
+        assert_eq!(
+            eval.interpret_inline_policy(&Expr::has_tag(
+                Expr::val(EntityUID::with_eid("doesnotexist")),
+                Expr::val("foo")
+            )),
+            Ok(Value::from(false))
+        );
+        assert_eq!(
+            eval.interpret_inline_policy(&Expr::get_tag(
+                Expr::val(EntityUID::with_eid("doesnotexist")),
+                Expr::val("foo")
+            )),
+            Err(EvaluationError::entity_does_not_exist(
+                Arc::new(EntityUID::with_eid("doesnotexist")),
+                None
+            ))
+        );
+        // type errors: tags are only on entities, and tag names are strings
+        assert_matches!(
+            eval.interpret_inline_policy(&Expr::has_tag(
+                Expr::record([("spoon".into(), Expr::val(1))]).unwrap(),
+                Expr::val("spoon")
+            )),
+            Err(e) => assert_matches!(e.error_kind(), EvaluationErrorKind::TypeError { expected, .. } => {
+                assert_eq!(expected, &nonempty![Type::entity_type(names::ANY_ENTITY_TYPE.clone())]);
+            })
+        );
+        assert_matches!(
+            eval.interpret_inline_policy(&Expr::get_tag(
+                Expr::val(EntityUID::with_eid("entity_with_tags")),
+                Expr::val(1)
+            )),
+            Err(e) => assert_matches!(e.error_kind(), EvaluationErrorKind::TypeError { expected, .. } => {
+                assert_eq!(expected, &nonempty![Type::String]);
+            })
+        );
+    }
+
+    #[test]
+    fn shared_cache() {
+        let entities = rich_entities();
+        let exts = Extensions::none();
+        let cache = EvaluationCache::default();
+        let spoon = Expr::get_attr(
+            Expr::val(EntityUID::with_eid("entity_with_attrs")),
+            "spoon".into(),
+        );
+        let in_expr = Expr::is_in(
+            Expr::val(EntityUID::with_eid("entity_with_attrs")),
+            Expr::val(EntityUID::with_eid("entity_no_attrs_no_parents")),
+        );
+        let eval = Evaluator::new(basic_request(), &entities, &exts).with_cache(&cache);
+        assert_eq!(eval.interpret_inline_policy(&spoon), Ok(Value::from(787)));
+        assert_eq!(
+            eval.interpret_inline_policy(&in_expr),
+            Ok(Value::from(false))
+        );
+        assert_eq!(cache.attrs.borrow().len(), 1);
+        assert_eq!(cache.ins.borrow().len(), 1);
+
+        // another `Evaluator` sharing the cache answers from it, without
+        // looking the entity up again
+        let other_entities = basic_entities();
+        let eval = Evaluator::new(basic_request(), &other_entities, &exts).with_cache(&cache);
+        assert_eq!(eval.interpret_inline_policy(&spoon), Ok(Value::from(787)));
+        assert_eq!(
+            eval.interpret_inline_policy(&in_expr),
+            Ok(Value::from(false))
+        );
+
+        // unless it records its entity reads
+        let eval = Evaluator::new(basic_request(), &other_entities, &exts)
+            .with_cache(&cache)
+            .record_entity_reads();
+        assert_matches!(eval.interpret_inline_policy(&spoon), Err(e) => {
+            assert_matches!(e.error_kind(), EvaluationErrorKind::EntityDoesNotExist(_));
+        });
+        assert_eq!(
+            eval.take_entity_reads(),
+            Some(HashSet::from([EntityUID::with_eid("entity_with_attrs")]))
+        );
+    }
+
     #[test]
     fn interpret_ternaries() {
         let request = basic_request();
@@ -1586,6 +2211,73 @@ pub mod test {
         );
     }
 
+    #[test]
+    fn evaluation_limits() {
+        let entities = basic_entities();
+        let exts = Extensions::none();
+        let expr = Expr::and(
+            Expr::is_eq(Expr::val(1), Expr::val(1)),
+            Expr::set([Expr::val(1), Expr::val(2), Expr::val(3)]),
+        );
+
+        // `1 == 1` and its operands, then the set and its three elements
+        let eval = Evaluator::new(basic_request(), &entities, &exts)
+            .with_limits(EvaluationLimits::new().with_max_steps(8));
+        assert_matches!(eval.interpret_inline_policy(&expr), Err(e) => {
+            assert_matches!(e.error_kind(), EvaluationErrorKind::TypeError { .. });
+        });
+        let eval = Evaluator::new(basic_request(), &entities, &exts)
+            .with_limits(EvaluationLimits::new().with_max_steps(7));
+        assert_matches!(eval.interpret_inline_policy(&expr), Err(e) => {
+            assert_eq!(
+                e.error_kind(),
+                &EvaluationErrorKind::LimitExceeded(LimitExceededError::Steps { limit: 7 })
+            );
+        });
+        // the budget is shared by everything the evaluator evaluates
+        let eval = Evaluator::new(basic_request(), &entities, &exts)
+            .with_limits(EvaluationLimits::new().with_max_steps(3));
+        assert_eq!(
+            eval.interpret_inline_policy(&Expr::is_eq(Expr::val(1), Expr::val(1))),
+            Ok(Value::from(true))
+        );
+        assert_matches!(
+            eval.interpret_inline_policy(&Expr::val(true)),
+            Err(e) => assert_matches!(
+                e.error_kind(),
+                EvaluationErrorKind::LimitExceeded(LimitExceededError::Steps { .. })
+            )
+        );
+
+        let eval = Evaluator::new(basic_request(), &entities, &exts)
+            .with_limits(EvaluationLimits::new().with_max_collection_size(2));
+        assert_matches!(eval.interpret_inline_policy(&expr), Err(e) => {
+            assert_eq!(
+                e.error_kind(),
+                &EvaluationErrorKind::LimitExceeded(LimitExceededError::CollectionSize {
+                    size: 3,
+                    limit: 2
+                })
+            );
+        });
+        assert_matches!(
+            eval.interpret_inline_policy(
+                &Expr::record([("a".into(), Expr::val(1)), ("b".into(), Expr::val(2))]).unwrap()
+            ),
+            Ok(_)
+        );
+
+        let eval = Evaluator::new(basic_request(), &entities, &exts)
+            .with_limits(EvaluationLimits::new().with_timeout(std::time::Duration::ZERO));
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        assert_matches!(eval.interpret_inline_policy(&expr), Err(e) => {
+            assert_matches!(
+                e.error_kind(),
+                EvaluationErrorKind::LimitExceeded(LimitExceededError::Timeout { .. })
+            );
+        });
+    }
+
     #[test]
     fn interpret_sets() {
         let request = basic_request();
@@ -5540,6 +6232,8 @@ pub mod test {
             BinaryOp::ContainsAll,
             BinaryOp::ContainsAny,
             BinaryOp::Eq,
+            BinaryOp::GetTag,
+            BinaryOp::HasTag,
             BinaryOp::In,
             BinaryOp::Less,
             BinaryOp::LessEq,
@@ -5689,6 +6383,133 @@ pub mod test {
         assert_eq!(r, PartialValue::Residual(e));
     }
 
+    #[test]
+    fn partial_typed_unknowns() {
+        let es = Entities::new();
+        let exts = Extensions::none();
+        let eval = Evaluator::new(empty_request(), &es, &exts);
+        let unknown = |ty| Expr::unknown(Unknown::new_with_type("a", ty));
+        let user = || Type::Entity {
+            ty: EntityType::Specified("User".parse().unwrap()),
+        };
+
+        // operands whose annotated type is wrong error without waiting for
+        // the unknown to be filled in
+        for e in [
+            Expr::add(unknown(Type::Bool), Expr::val(1)),
+            Expr::and(unknown(Type::Long), Expr::val(true)),
+            Expr::or(Expr::val(false), unknown(Type::String)),
+            Expr::not(unknown(Type::Long)),
+            Expr::ite(unknown(Type::Set), Expr::val(1), Expr::val(2)),
+            Expr::contains(unknown(Type::Record), Expr::val(1)),
+            Expr::less(Expr::val(1), unknown(Type::String)),
+            Expr::get_attr(unknown(Type::Long), "attr".into()),
+            Expr::like(unknown(user()), vec![PatternElem::Wildcard]),
+            Expr::is_entity_type(unknown(Type::String), "User".parse().unwrap()),
+        ] {
+            assert_matches!(
+                eval.partial_interpret(&e, &HashMap::new()),
+                Err(err) => assert_matches!(err.error_kind(), EvaluationErrorKind::TypeError { .. })
+            );
+        }
+
+        // well-typed operands are left as residuals
+        for e in [
+            Expr::add(unknown(Type::Long), Expr::val(1)),
+            Expr::get_attr(unknown(user()), "attr".into()),
+            Expr::binary_app(
+                BinaryOp::In,
+                unknown(user()),
+                Expr::val(EntityUID::with_eid("g")),
+            ),
+        ] {
+            assert_eq!(
+                eval.partial_interpret(&e, &HashMap::new()).unwrap(),
+                PartialValue::Residual(e)
+            );
+        }
+
+        // annotated types can decide `==` and `is`
+        let e = Expr::is_eq(unknown(Type::Long), Expr::val("x"));
+        assert_eq!(
+            eval.partial_interpret(&e, &HashMap::new()).unwrap(),
+            false.into()
+        );
+        let e = Expr::is_eq(unknown(Type::Long), Expr::val(3));
+        assert_eq!(
+            eval.partial_interpret(&e, &HashMap::new()).unwrap(),
+            PartialValue::Residual(e)
+        );
+        let e = Expr::is_entity_type(unknown(user()), "User".parse().unwrap());
+        assert_eq!(
+            eval.partial_interpret(&e, &HashMap::new()).unwrap(),
+            true.into()
+        );
+        let e = Expr::is_entity_type(unknown(user()), "Group".parse().unwrap());
+        assert_eq!(
+            eval.partial_interpret(&e, &HashMap::new()).unwrap(),
+            false.into()
+        );
+    }
+
+    #[test]
+    fn partial_unknown_ancestors() {
+        let mut child = Entity::with_uid(EntityUID::with_eid("child"));
+        let parent =
+            Entity::with_uid(EntityUID::with_eid("parent")).with_unknown_ancestors("parents");
+        let unrelated = Entity::with_uid(EntityUID::with_eid("unrelated"));
+        child.add_ancestor(parent.uid().clone());
+        let es = Entities::from_entities(
+            [child, parent, unrelated],
+            None::<&NoEntitiesSchema>,
+            TCComputation::ComputeNow,
+            Extensions::none(),
+        )
+        .unwrap();
+        let exts = Extensions::none();
+        let eval = Evaluator::new(empty_request(), &es, &exts);
+        let parents = || Expr::unknown(Unknown::new_with_type("parents", Type::Set));
+        let group = EntityUID::with_eid("group");
+        let other = EntityUID::with_eid("other");
+
+        // known ancestors still decide membership
+        let e = Expr::is_in(
+            Expr::val(EntityUID::with_eid("child")),
+            Expr::val(EntityUID::with_eid("parent")),
+        );
+        assert_eq!(
+            eval.partial_interpret(&e, &HashMap::new()).unwrap(),
+            true.into()
+        );
+        let e = Expr::is_in(
+            Expr::val(EntityUID::with_eid("unrelated")),
+            Expr::val(group.clone()),
+        );
+        assert_eq!(
+            eval.partial_interpret(&e, &HashMap::new()).unwrap(),
+            false.into()
+        );
+
+        // otherwise, unknown ancestors, including those of ancestors, leave a
+        // residual
+        let e = Expr::is_in(
+            Expr::val(EntityUID::with_eid("child")),
+            Expr::val(group.clone()),
+        );
+        assert_eq!(
+            eval.partial_interpret(&e, &HashMap::new()).unwrap(),
+            PartialValue::Residual(Expr::contains(parents(), Expr::val(group.clone())))
+        );
+        let e = Expr::is_in(
+            Expr::val(EntityUID::with_eid("parent")),
+            Expr::set([Expr::val(group.clone()), Expr::val(other.clone())]),
+        );
+        assert_matches!(
+            eval.partial_interpret(&e, &HashMap::new()).unwrap(),
+            PartialValue::Residual(r) => assert_matches!(r.expr_kind(), ExprKind::BinaryApp { op: BinaryOp::ContainsAny, .. })
+        );
+    }
+
     #[test]
     fn partial_hasattr() {
         let es = Entities::new();
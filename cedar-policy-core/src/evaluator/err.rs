@@ -120,6 +120,18 @@ impl EvaluationError {
         }
     }
 
+    /// Construct a [`EntityTagDoesNotExist`] error
+    pub(crate) fn entity_tag_does_not_exist(
+        entity: Arc<EntityUID>,
+        tag: SmolStr,
+        source_loc: Option<Loc>,
+    ) -> Self {
+        Self {
+            error_kind: EvaluationErrorKind::EntityTagDoesNotExist { entity, tag },
+            source_loc,
+        }
+    }
+
     /// Construct a [`UnspecifiedEntityAccess`] error
     pub(crate) fn unspecified_entity_access(attr: SmolStr, source_loc: Option<Loc>) -> Self {
         Self {
@@ -156,6 +168,23 @@ impl EvaluationError {
         Self::type_error(nonempty![expected], actual)
     }
 
+    /// Construct a [`TypeError`] error for an unknown annotated with type
+    /// `actual`, used where a value of one of the `expected` types is required
+    pub(crate) fn unknown_type_error(
+        expected: NonEmpty<Type>,
+        actual: Type,
+        source_loc: Option<Loc>,
+    ) -> Self {
+        Self {
+            error_kind: EvaluationErrorKind::TypeError {
+                expected,
+                actual,
+                advice: None,
+            },
+            source_loc,
+        }
+    }
+
     /// Construct a [`TypeError`] error with the advice field set
     pub(crate) fn type_error_with_advice(
         expected: NonEmpty<Type>,
@@ -254,6 +283,14 @@ impl EvaluationError {
             source_loc,
         }
     }
+
+    /// Construct a [`LimitExceeded`] error
+    pub(crate) fn limit_exceeded(err: LimitExceededError, source_loc: Option<Loc>) -> Self {
+        Self {
+            error_kind: err.into(),
+            source_loc,
+        }
+    }
 }
 
 impl From<RestrictedExprError> for EvaluationError {
@@ -283,6 +320,18 @@ pub enum EvaluationErrorKind {
         attr: SmolStr,
     },
 
+    /// Tried to get this tag, but the specified entity didn't have that tag
+    #[error("`{}` does not have the tag `{}`", &.entity, &.tag)]
+    #[diagnostic(help(
+        "use `.hasTag()` to check whether an entity has a tag before accessing it"
+    ))]
+    EntityTagDoesNotExist {
+        /// Entity that didn't have the tag
+        entity: Arc<EntityUID>,
+        /// Name of the tag it didn't have
+        tag: SmolStr,
+    },
+
     /// Tried to access an attribute of an unspecified entity
     #[error("cannot access attribute `{0}` of unspecified entity")]
     UnspecifiedEntityAccess(SmolStr),
@@ -356,6 +405,12 @@ pub enum EvaluationErrorKind {
     /// Maximum recursion limit reached for expression evaluation
     #[error("recursion limit reached")]
     RecursionLimit,
+
+    /// Evaluation exceeded one of the configured
+    /// [`super::EvaluationLimits`]
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    LimitExceeded(#[from] LimitExceededError),
 }
 
 /// helper function for pretty-printing type errors
@@ -393,5 +448,33 @@ pub enum IntegerOverflowError {
     },
 }
 
+/// Errors raised when evaluation exceeds one of the configured
+/// [`super::EvaluationLimits`]
+#[derive(Debug, PartialEq, Eq, Clone, Diagnostic, Error)]
+pub enum LimitExceededError {
+    /// Evaluated more (sub-)expressions than allowed for a request
+    #[error("evaluation exceeded the limit of {limit} steps")]
+    Steps {
+        /// the configured maximum number of steps
+        limit: u64,
+    },
+    /// Tried to create a set or record with more elements than allowed
+    #[error(
+        "evaluation tried to create a collection with {size} elements, but the limit is {limit}"
+    )]
+    CollectionSize {
+        /// number of elements in the set or record
+        size: usize,
+        /// the configured maximum number of elements
+        limit: usize,
+    },
+    /// Evaluation of a request didn't finish before the timeout
+    #[error("evaluation did not finish within the timeout of {}ms", .timeout.as_millis())]
+    Timeout {
+        /// the configured timeout
+        timeout: std::time::Duration,
+    },
+}
+
 /// Type alias for convenience
 pub type Result<T> = std::result::Result<T, EvaluationError>;
@@ -0,0 +1,86 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains the limits that can be placed on the cost of
+//! evaluation.
+
+use std::time::Duration;
+
+/// Upper bounds on the work the evaluator will do for a single request.
+///
+/// All limits are off by default. When a limit is hit, evaluation of the
+/// current policy fails with [`super::LimitExceededError`], and so does
+/// evaluation of any later policy for the same request once the step budget
+/// or the timeout is used up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EvaluationLimits {
+    max_steps: Option<u64>,
+    max_collection_size: Option<usize>,
+    timeout: Option<Duration>,
+}
+
+impl EvaluationLimits {
+    /// No limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the number of (sub-)expressions evaluated for a request, across
+    /// all policies
+    pub fn with_max_steps(self, max_steps: u64) -> Self {
+        Self {
+            max_steps: Some(max_steps),
+            ..self
+        }
+    }
+
+    /// Limit the number of elements in a set or record literal evaluated for
+    /// a request
+    pub fn with_max_collection_size(self, max_collection_size: usize) -> Self {
+        Self {
+            max_collection_size: Some(max_collection_size),
+            ..self
+        }
+    }
+
+    /// Limit the wall-clock time spent evaluating a request, measured from
+    /// when evaluation of the request starts.
+    ///
+    /// The timeout is not enforced on `wasm32` targets, which have no clock
+    /// available to the evaluator.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// The maximum number of (sub-)expressions evaluated for a request, if
+    /// limited
+    pub fn max_steps(&self) -> Option<u64> {
+        self.max_steps
+    }
+
+    /// The maximum number of elements in a set or record literal, if limited
+    pub fn max_collection_size(&self) -> Option<usize> {
+        self.max_collection_size
+    }
+
+    /// The maximum wall-clock time spent evaluating a request, if limited
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+}
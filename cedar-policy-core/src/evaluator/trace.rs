@@ -0,0 +1,171 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains the types recorded by the evaluator when tracing is
+//! enabled with [`super::Evaluator::with_trace()`].
+
+use super::{EvaluationError, Result};
+use crate::ast::{Expr, PartialValue, Value};
+use std::fmt::Display;
+
+/// The sub-expressions evaluated while tracing, in the order the evaluator
+/// started evaluating them.
+///
+/// Each step records how deeply nested it is in the traced expression, so the
+/// steps form a pre-order walk of the part of the expression tree that was
+/// actually evaluated.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EvaluationTrace {
+    steps: Vec<TraceStep>,
+}
+
+impl EvaluationTrace {
+    /// Iterate over the steps of this trace, in evaluation order
+    pub fn steps(&self) -> impl Iterator<Item = &TraceStep> {
+        self.steps.iter()
+    }
+
+    /// Is the trace empty?
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+impl Display for EvaluationTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "{:indent$}{step}", "", indent = 2 * step.depth)?;
+        }
+        Ok(())
+    }
+}
+
+/// A single sub-expression evaluated while tracing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceStep {
+    /// The sub-expression that was evaluated
+    expr: Expr,
+    /// Number of enclosing steps; the traced expression itself has depth 0
+    depth: usize,
+    /// What `expr` evaluated to
+    outcome: TraceOutcome,
+    /// Whether `expr` is an `&&` or `||` whose right operand was never
+    /// evaluated, because the left operand determined the result
+    short_circuited: bool,
+}
+
+impl TraceStep {
+    /// The sub-expression that was evaluated
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    /// Number of enclosing steps; the traced expression itself has depth 0
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// What the sub-expression evaluated to
+    pub fn outcome(&self) -> &TraceOutcome {
+        &self.outcome
+    }
+
+    /// Whether the sub-expression is an `&&` or `||` that short-circuited,
+    /// i.e., whose right operand was never evaluated
+    pub fn short_circuited(&self) -> bool {
+        self.short_circuited
+    }
+}
+
+impl Display for TraceStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} => {}", self.expr, self.outcome)?;
+        if self.short_circuited {
+            write!(f, " (short-circuited)")?;
+        }
+        Ok(())
+    }
+}
+
+/// What a traced sub-expression evaluated to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceOutcome {
+    /// The sub-expression evaluated to a value
+    Value(Value),
+    /// The sub-expression evaluated to a residual, because it depends on
+    /// unknowns (only possible with partial evaluation)
+    Residual(Expr),
+    /// Evaluating the sub-expression produced an error
+    Error(EvaluationError),
+}
+
+impl Display for TraceOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Value(v) => write!(f, "{v}"),
+            Self::Residual(r) => write!(f, "residual {r}"),
+            Self::Error(e) => write!(f, "error: {e}"),
+        }
+    }
+}
+
+/// Accumulates an [`EvaluationTrace`] while the evaluator runs
+#[derive(Debug, Default)]
+pub(crate) struct Tracer {
+    trace: EvaluationTrace,
+    /// Indices into `trace.steps` of the steps that are still being evaluated,
+    /// innermost last
+    open: Vec<usize>,
+}
+
+impl Tracer {
+    /// Record that evaluation of `expr` is starting
+    pub(crate) fn enter(&mut self, expr: &Expr) {
+        self.open.push(self.trace.steps.len());
+        self.trace.steps.push(TraceStep {
+            expr: expr.clone(),
+            depth: self.open.len() - 1,
+            // placeholder until `exit()` is called
+            outcome: TraceOutcome::Value(Value::from(false)),
+            short_circuited: false,
+        });
+    }
+
+    /// Record the result of the innermost step that is still being evaluated
+    pub(crate) fn exit(&mut self, res: &Result<PartialValue>) {
+        if let Some(step) = self.open.pop().and_then(|i| self.trace.steps.get_mut(i)) {
+            step.outcome = match res {
+                Ok(PartialValue::Value(v)) => TraceOutcome::Value(v.clone()),
+                Ok(PartialValue::Residual(r)) => TraceOutcome::Residual(r.clone()),
+                Err(e) => TraceOutcome::Error(e.clone()),
+            };
+        }
+    }
+
+    /// Mark the innermost step that is still being evaluated as having
+    /// short-circuited
+    pub(crate) fn short_circuit(&mut self) {
+        if let Some(step) = self.open.last().and_then(|i| self.trace.steps.get_mut(*i)) {
+            step.short_circuited = true;
+        }
+    }
+
+    /// Take the steps recorded so far, leaving this `Tracer` empty
+    pub(crate) fn take(&mut self) -> EvaluationTrace {
+        self.open.clear();
+        std::mem::take(&mut self.trace)
+    }
+}
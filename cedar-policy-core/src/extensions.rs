@@ -21,6 +21,9 @@ pub mod ipaddr;
 
 #[cfg(feature = "decimal")]
 pub mod decimal;
+
+#[cfg(feature = "datetime")]
+pub mod datetime;
 pub mod partial_evaluation;
 
 use crate::ast::{Extension, ExtensionFunction, Name};
@@ -34,6 +37,8 @@ lazy_static::lazy_static! {
         ipaddr::extension(),
         #[cfg(feature = "decimal")]
         decimal::extension(),
+        #[cfg(feature = "datetime")]
+        datetime::extension(),
         partial_evaluation::extension(),
     ];
 }
@@ -107,7 +112,7 @@ impl<'a> Extensions<'a> {
     ///
     /// `Ok(None)` means no constructor has that signature.
     /// `Err` is returned in the case that multiple constructors have that signature.
-    pub(crate) fn lookup_single_arg_constructor(
+    pub fn lookup_single_arg_constructor(
         &self,
         return_type: &SchemaType,
         arg_type: &SchemaType,
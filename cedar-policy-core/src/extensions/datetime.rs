@@ -0,0 +1,950 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains the Cedar 'datetime' extension, which provides the
+//! `datetime` and `duration` types.
+
+use crate::ast::{
+    CallStyle, Extension, ExtensionFunction, ExtensionOutputValue, ExtensionValue,
+    ExtensionValueWithArgs, InternalExtensionValue, Literal, Name, Type, Value, ValueKind,
+};
+use crate::entities::SchemaType;
+use crate::evaluator;
+use miette::Diagnostic;
+use std::sync::Arc;
+use thiserror::Error;
+
+const MILLISECONDS_PER_SECOND: i64 = 1000;
+const MILLISECONDS_PER_MINUTE: i64 = 60 * MILLISECONDS_PER_SECOND;
+const MILLISECONDS_PER_HOUR: i64 = 60 * MILLISECONDS_PER_MINUTE;
+const MILLISECONDS_PER_DAY: i64 = 24 * MILLISECONDS_PER_HOUR;
+
+/// A point in time, represented internally as the (signed) number of
+/// milliseconds since the Unix epoch, `1970-01-01T00:00:00Z`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+struct DateTime {
+    epoch: i64,
+}
+
+/// A span of time, represented internally as a (signed) number of
+/// milliseconds.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+struct Duration {
+    ms: i64,
+}
+
+// PANIC SAFETY The `Name`s here are valid
+#[allow(clippy::expect_used)]
+mod constants {
+    use super::Name;
+
+    // PANIC SAFETY all of the names here are valid names
+    lazy_static::lazy_static! {
+        pub static ref EXTENSION_NAME : Name = Name::parse_unqualified_name("datetime").expect("should be a valid identifier");
+        pub static ref DATETIME_CONSTRUCTOR_NAME : Name = Name::parse_unqualified_name("datetime").expect("should be a valid identifier");
+        pub static ref DURATION_CONSTRUCTOR_NAME : Name = Name::parse_unqualified_name("duration").expect("should be a valid identifier");
+        pub static ref OFFSET : Name = Name::parse_unqualified_name("offset").expect("should be a valid identifier");
+        pub static ref DURATION_SINCE : Name = Name::parse_unqualified_name("durationSince").expect("should be a valid identifier");
+        pub static ref TO_DATE : Name = Name::parse_unqualified_name("toDate").expect("should be a valid identifier");
+        pub static ref TO_TIME : Name = Name::parse_unqualified_name("toTime").expect("should be a valid identifier");
+        pub static ref TO_MILLISECONDS : Name = Name::parse_unqualified_name("toMilliseconds").expect("should be a valid identifier");
+        pub static ref TO_SECONDS : Name = Name::parse_unqualified_name("toSeconds").expect("should be a valid identifier");
+        pub static ref TO_MINUTES : Name = Name::parse_unqualified_name("toMinutes").expect("should be a valid identifier");
+        pub static ref TO_HOURS : Name = Name::parse_unqualified_name("toHours").expect("should be a valid identifier");
+        pub static ref TO_DAYS : Name = Name::parse_unqualified_name("toDays").expect("should be a valid identifier");
+    }
+}
+
+/// Help message to display when a String was provided where a datetime value was expected.
+/// This error is likely due to confusion between "2024-01-01" and datetime("2024-01-01").
+const DATETIME_ADVICE_MSG: &str = "maybe you forgot to apply the `datetime` constructor?";
+
+/// Help message to display when a String was provided where a duration value was expected.
+/// This error is likely due to confusion between "1h" and duration("1h").
+const DURATION_ADVICE_MSG: &str = "maybe you forgot to apply the `duration` constructor?";
+
+/// Potential errors when working with datetime and duration values. Note that
+/// these are converted to evaluator::Err::ExtensionErr (which takes a string
+/// argument) before being reported to users.
+#[derive(Debug, Diagnostic, Error)]
+enum Error {
+    /// Error parsing the input string as a datetime value
+    #[error("`{0}` is not a well-formed datetime value")]
+    #[diagnostic(help(
+        "expected one of the formats `YYYY-MM-DD`, `YYYY-MM-DDThh:mm:ssZ`, `YYYY-MM-DDThh:mm:ss.SSSZ`, `YYYY-MM-DDThh:mm:ss(+|-)hhmm`, or `YYYY-MM-DDThh:mm:ss.SSS(+|-)hhmm`"
+    ))]
+    FailedDateTimeParse(String),
+
+    /// Error parsing the input string as a duration value
+    #[error("`{0}` is not a well-formed duration value")]
+    #[diagnostic(help(
+        "a duration is an optional `-` followed by one or more of `<n>d`, `<n>h`, `<n>m`, `<n>s`, `<n>ms`, in that order"
+    ))]
+    FailedDurationParse(String),
+
+    /// Overflow occurred when constructing or computing with a datetime or
+    /// duration value
+    #[error("overflow when computing a datetime or duration value")]
+    Overflow,
+}
+
+/// Number of days from the Unix epoch to the given (proleptic Gregorian)
+/// civil date. `month` is in `1..=12` and `day` is in `1..=31`.
+///
+/// This is the `days_from_civil` algorithm described by Howard Hinnant in
+/// `<http://howardhinnant.github.io/date_algorithms.html>`.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Inverse of [`days_from_civil`]: the civil date `(year, month, day)` which
+/// is the given number of days from the Unix epoch.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Parse exactly `n` ASCII digits from the front of `s`, returning the parsed
+/// number and the remainder of the string.
+fn parse_fixed_digits(s: &str, n: usize) -> Option<(i64, &str)> {
+    let digits = s.get(..n)?;
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some((digits.parse().ok()?, &s[n..]))
+}
+
+/// Strip the given `prefix` from `s`, if present
+fn expect_char(s: &str, prefix: char) -> Option<&str> {
+    s.strip_prefix(prefix)
+}
+
+impl DateTime {
+    /// The Cedar typename of datetime values
+    fn typename() -> Name {
+        constants::DATETIME_CONSTRUCTOR_NAME.clone()
+    }
+
+    /// Convert a string into a `DateTime` value.
+    ///
+    /// Accepts the following subset of ISO 8601:
+    ///   - `YYYY-MM-DD` (midnight UTC on that day)
+    ///   - `YYYY-MM-DDThh:mm:ssZ`
+    ///   - `YYYY-MM-DDThh:mm:ss.SSSZ`
+    ///   - `YYYY-MM-DDThh:mm:ss(+|-)hhmm`
+    ///   - `YYYY-MM-DDThh:mm:ss.SSS(+|-)hhmm`
+    fn from_str(str: impl AsRef<str>) -> Result<Self, Error> {
+        let s = str.as_ref();
+        Self::parse(s).ok_or_else(|| Error::FailedDateTimeParse(s.to_owned()))
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        let (year, rest) = parse_fixed_digits(s, 4)?;
+        let rest = expect_char(rest, '-')?;
+        let (month, rest) = parse_fixed_digits(rest, 2)?;
+        let rest = expect_char(rest, '-')?;
+        let (day, rest) = parse_fixed_digits(rest, 2)?;
+        if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+            return None;
+        }
+        let date_ms = days_from_civil(year, month, day).checked_mul(MILLISECONDS_PER_DAY)?;
+        if rest.is_empty() {
+            return Some(Self { epoch: date_ms });
+        }
+
+        let rest = expect_char(rest, 'T')?;
+        let (hour, rest) = parse_fixed_digits(rest, 2)?;
+        let rest = expect_char(rest, ':')?;
+        let (minute, rest) = parse_fixed_digits(rest, 2)?;
+        let rest = expect_char(rest, ':')?;
+        let (second, rest) = parse_fixed_digits(rest, 2)?;
+        if hour >= 24 || minute >= 60 || second >= 60 {
+            return None;
+        }
+        let (millis, rest) = match expect_char(rest, '.') {
+            Some(rest) => parse_fixed_digits(rest, 3)?,
+            None => (0, rest),
+        };
+        let offset_ms = match rest.chars().next()? {
+            'Z' if rest.len() == 1 => 0,
+            sign @ ('+' | '-') => {
+                let (offset_hour, offset_rest) = parse_fixed_digits(&rest[1..], 2)?;
+                let (offset_minute, offset_rest) = parse_fixed_digits(offset_rest, 2)?;
+                if !offset_rest.is_empty() || offset_hour >= 24 || offset_minute >= 60 {
+                    return None;
+                }
+                let offset =
+                    offset_hour * MILLISECONDS_PER_HOUR + offset_minute * MILLISECONDS_PER_MINUTE;
+                if sign == '+' {
+                    offset
+                } else {
+                    -offset
+                }
+            }
+            _ => return None,
+        };
+        let time_ms = hour * MILLISECONDS_PER_HOUR
+            + minute * MILLISECONDS_PER_MINUTE
+            + second * MILLISECONDS_PER_SECOND
+            + millis;
+        // a local time of `hh:mm` at offset `+hhmm` is `hh:mm - offset` in UTC
+        date_ms
+            .checked_add(time_ms)?
+            .checked_sub(offset_ms)
+            .map(|epoch| Self { epoch })
+    }
+}
+
+impl std::fmt::Display for DateTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let days = self.epoch.div_euclid(MILLISECONDS_PER_DAY);
+        let time = self.epoch.rem_euclid(MILLISECONDS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        write!(
+            f,
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+            time / MILLISECONDS_PER_HOUR,
+            (time % MILLISECONDS_PER_HOUR) / MILLISECONDS_PER_MINUTE,
+            (time % MILLISECONDS_PER_MINUTE) / MILLISECONDS_PER_SECOND,
+            time % MILLISECONDS_PER_SECOND,
+        )
+    }
+}
+
+impl ExtensionValue for DateTime {
+    fn typename(&self) -> Name {
+        Self::typename()
+    }
+
+    fn supports_operator_overloading(&self) -> bool {
+        true
+    }
+}
+
+/// The units accepted in a duration string, from largest to smallest, along
+/// with the number of milliseconds in each
+const DURATION_UNITS: [(&str, i64); 5] = [
+    ("d", MILLISECONDS_PER_DAY),
+    ("h", MILLISECONDS_PER_HOUR),
+    ("ms", 1),
+    ("m", MILLISECONDS_PER_MINUTE),
+    ("s", MILLISECONDS_PER_SECOND),
+];
+
+impl Duration {
+    /// The Cedar typename of duration values
+    fn typename() -> Name {
+        constants::DURATION_CONSTRUCTOR_NAME.clone()
+    }
+
+    /// Convert a string into a `Duration` value.
+    ///
+    /// A duration string is an optional `-` followed by one or more
+    /// components `<n>d`, `<n>h`, `<n>m`, `<n>s`, and `<n>ms`, each appearing
+    /// at most once and in that order. For instance, `1d2h`, `-30m`, and
+    /// `1h30m15s500ms` are all valid.
+    fn from_str(str: impl AsRef<str>) -> Result<Self, Error> {
+        let s = str.as_ref();
+        match Self::parse(s) {
+            Some(Ok(d)) => Ok(d),
+            Some(Err(e)) => Err(e),
+            None => Err(Error::FailedDurationParse(s.to_owned())),
+        }
+    }
+
+    /// Returns `None` if the string is malformed, and `Some(Err)` if it is
+    /// well-formed but overflows
+    fn parse(s: &str) -> Option<Result<Self, Error>> {
+        let (negative, mut rest) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        if rest.is_empty() {
+            return None;
+        }
+        // Milliseconds per unit of the last component parsed; each component
+        // must be strictly smaller than the one before it
+        let mut previous_unit = i64::MAX;
+        // Accumulate in `i128` so that `i64::MIN` milliseconds is representable
+        // before the sign is applied
+        let mut total: i128 = 0;
+        while !rest.is_empty() {
+            let num_digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+            if num_digits == 0 {
+                return None;
+            }
+            let (digits, after_digits) = rest.split_at(num_digits);
+            let (unit, unit_ms) = DURATION_UNITS
+                .iter()
+                .find(|(unit, _)| after_digits.starts_with(unit))?;
+            if *unit_ms >= previous_unit {
+                return None;
+            }
+            previous_unit = *unit_ms;
+            rest = &after_digits[unit.len()..];
+            let component = digits
+                .parse::<i128>()
+                .ok()
+                .and_then(|n| n.checked_mul(i128::from(*unit_ms)))
+                .and_then(|n| total.checked_add(n));
+            match component {
+                Some(n) => total = n,
+                None => return Some(Err(Error::Overflow)),
+            }
+        }
+        let total = if negative { -total } else { total };
+        Some(
+            i64::try_from(total)
+                .map(|ms| Self { ms })
+                .map_err(|_| Error::Overflow),
+        )
+    }
+}
+
+impl std::fmt::Display for Duration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.ms == 0 {
+            return write!(f, "0ms");
+        }
+        if self.ms < 0 {
+            write!(f, "-")?;
+        }
+        let mut remaining = self.ms.unsigned_abs();
+        for (unit, unit_ms) in [
+            ("d", MILLISECONDS_PER_DAY),
+            ("h", MILLISECONDS_PER_HOUR),
+            ("m", MILLISECONDS_PER_MINUTE),
+            ("s", MILLISECONDS_PER_SECOND),
+            ("ms", 1),
+        ] {
+            let unit_ms = unit_ms.unsigned_abs();
+            let n = remaining / unit_ms;
+            if n > 0 {
+                write!(f, "{n}{unit}")?;
+            }
+            remaining %= unit_ms;
+        }
+        Ok(())
+    }
+}
+
+impl ExtensionValue for Duration {
+    fn typename(&self) -> Name {
+        Self::typename()
+    }
+
+    fn supports_operator_overloading(&self) -> bool {
+        true
+    }
+}
+
+fn extension_err(msg: impl Into<String>) -> evaluator::EvaluationError {
+    evaluator::EvaluationError::failed_extension_function_application(
+        constants::EXTENSION_NAME.clone(),
+        msg.into(),
+        None, // source loc will be added by the evaluator
+    )
+}
+
+/// Wrap a `DateTime` or `Duration` up as a Cedar value, recording the
+/// constructor call that reproduces it
+fn construct(
+    value: Arc<dyn InternalExtensionValue + Send + Sync>,
+    constructor: Name,
+    arg: Value,
+) -> ExtensionOutputValue {
+    let arg_source_loc = arg.source_loc().cloned();
+    let e = ExtensionValueWithArgs::new(value, constructor, vec![arg.into()]);
+    Value {
+        value: ValueKind::ExtensionValue(Arc::new(e)),
+        loc: arg_source_loc, // this gives the loc of the arg. We could perhaps give instead the loc of the entire `datetime("...")` call, but that is hard to do at this program point
+    }
+    .into()
+}
+
+/// Wrap a `DateTime` up as a Cedar value which was computed (rather than
+/// constructed directly from a string). The value is reproduced by the
+/// `datetime` constructor applied to its canonical string representation.
+///
+/// Fails with an overflow error if the year (in UTC) falls outside
+/// `0000..=9999`, as the constructor accepts no representation of the value.
+fn datetime_value(dt: DateTime) -> evaluator::Result<ExtensionOutputValue> {
+    let (year, _, _) = civil_from_days(dt.epoch.div_euclid(MILLISECONDS_PER_DAY));
+    if !(0..=9999).contains(&year) {
+        return Err(extension_err(Error::Overflow.to_string()));
+    }
+    let arg = Value::from(dt.to_string());
+    Ok(construct(
+        Arc::new(dt),
+        constants::DATETIME_CONSTRUCTOR_NAME.clone(),
+        arg,
+    ))
+}
+
+/// Wrap a `Duration` up as a Cedar value which was computed (rather than
+/// constructed directly from a string). The value is reproduced by the
+/// `duration` constructor applied to its canonical string representation.
+fn duration_value(d: Duration) -> ExtensionOutputValue {
+    let arg = Value::from(d.to_string());
+    construct(
+        Arc::new(d),
+        constants::DURATION_CONSTRUCTOR_NAME.clone(),
+        arg,
+    )
+}
+
+/// Cedar function that constructs a `datetime` Cedar type from a
+/// Cedar string
+fn datetime_from_str(arg: Value) -> evaluator::Result<ExtensionOutputValue> {
+    let str = arg.get_as_string()?;
+    let dt = DateTime::from_str(str.as_str()).map_err(|e| extension_err(e.to_string()))?;
+    Ok(construct(
+        Arc::new(dt),
+        constants::DATETIME_CONSTRUCTOR_NAME.clone(),
+        arg,
+    ))
+}
+
+/// Cedar function that constructs a `duration` Cedar type from a
+/// Cedar string
+fn duration_from_str(arg: Value) -> evaluator::Result<ExtensionOutputValue> {
+    let str = arg.get_as_string()?;
+    let d = Duration::from_str(str.as_str()).map_err(|e| extension_err(e.to_string()))?;
+    Ok(construct(
+        Arc::new(d),
+        constants::DURATION_CONSTRUCTOR_NAME.clone(),
+        arg,
+    ))
+}
+
+/// Check that `v` is a datetime type and, if it is, return the wrapped value
+fn as_datetime(v: &Value) -> Result<&DateTime, evaluator::EvaluationError> {
+    match &v.value {
+        ValueKind::ExtensionValue(ev) if ev.typename() == DateTime::typename() => {
+            // PANIC SAFETY Conditional above performs a typecheck
+            #[allow(clippy::expect_used)]
+            let dt = ev
+                .value()
+                .as_any()
+                .downcast_ref::<DateTime>()
+                .expect("already typechecked, so this downcast should succeed");
+            Ok(dt)
+        }
+        ValueKind::Lit(Literal::String(_)) => {
+            Err(evaluator::EvaluationError::type_error_with_advice_single(
+                Type::Extension {
+                    name: DateTime::typename(),
+                },
+                v,
+                DATETIME_ADVICE_MSG.into(),
+            ))
+        }
+        _ => Err(evaluator::EvaluationError::type_error_single(
+            Type::Extension {
+                name: DateTime::typename(),
+            },
+            v,
+        )),
+    }
+}
+
+/// Check that `v` is a duration type and, if it is, return the wrapped value
+fn as_duration(v: &Value) -> Result<&Duration, evaluator::EvaluationError> {
+    match &v.value {
+        ValueKind::ExtensionValue(ev) if ev.typename() == Duration::typename() => {
+            // PANIC SAFETY Conditional above performs a typecheck
+            #[allow(clippy::expect_used)]
+            let d = ev
+                .value()
+                .as_any()
+                .downcast_ref::<Duration>()
+                .expect("already typechecked, so this downcast should succeed");
+            Ok(d)
+        }
+        ValueKind::Lit(Literal::String(_)) => {
+            Err(evaluator::EvaluationError::type_error_with_advice_single(
+                Type::Extension {
+                    name: Duration::typename(),
+                },
+                v,
+                DURATION_ADVICE_MSG.into(),
+            ))
+        }
+        _ => Err(evaluator::EvaluationError::type_error_single(
+            Type::Extension {
+                name: Duration::typename(),
+            },
+            v,
+        )),
+    }
+}
+
+/// Cedar function that offsets a `datetime` by a `duration`, returning a
+/// new `datetime`
+fn offset(dt: Value, d: Value) -> evaluator::Result<ExtensionOutputValue> {
+    let dt = as_datetime(&dt)?;
+    let d = as_duration(&d)?;
+    let epoch = dt
+        .epoch
+        .checked_add(d.ms)
+        .ok_or_else(|| extension_err(Error::Overflow.to_string()))?;
+    datetime_value(DateTime { epoch })
+}
+
+/// Cedar function that computes the `duration` from the second `datetime` to
+/// the first; i.e., `a.durationSince(b)` is `a - b`
+fn duration_since(a: Value, b: Value) -> evaluator::Result<ExtensionOutputValue> {
+    let a = as_datetime(&a)?;
+    let b = as_datetime(&b)?;
+    let ms = a
+        .epoch
+        .checked_sub(b.epoch)
+        .ok_or_else(|| extension_err(Error::Overflow.to_string()))?;
+    Ok(duration_value(Duration { ms }))
+}
+
+/// Cedar function that truncates a `datetime` to midnight (UTC) of the same
+/// day
+fn to_date(dt: Value) -> evaluator::Result<ExtensionOutputValue> {
+    let dt = as_datetime(&dt)?;
+    let epoch = dt.epoch - dt.epoch.rem_euclid(MILLISECONDS_PER_DAY);
+    datetime_value(DateTime { epoch })
+}
+
+/// Cedar function that returns the time of day of a `datetime` (UTC), as a
+/// `duration` since midnight
+fn to_time(dt: Value) -> evaluator::Result<ExtensionOutputValue> {
+    let dt = as_datetime(&dt)?;
+    Ok(duration_value(Duration {
+        ms: dt.epoch.rem_euclid(MILLISECONDS_PER_DAY),
+    }))
+}
+
+/// Convert a `duration` to a `Long` count of the given unit, truncating
+/// towards zero
+fn duration_in_units(d: Value, unit_ms: i64) -> evaluator::Result<ExtensionOutputValue> {
+    let d = as_duration(&d)?;
+    Ok(Value::from(d.ms / unit_ms).into())
+}
+
+/// Construct the extension
+pub fn extension() -> Extension {
+    let datetime_type = SchemaType::Extension {
+        name: DateTime::typename(),
+    };
+    let duration_type = SchemaType::Extension {
+        name: Duration::typename(),
+    };
+    let duration_accessor = |name: &Name, unit_ms: i64| {
+        ExtensionFunction::unary(
+            name.clone(),
+            CallStyle::MethodStyle,
+            Box::new(move |d| duration_in_units(d, unit_ms)),
+            SchemaType::Long,
+            Some(duration_type.clone()),
+        )
+    };
+    Extension::new(
+        constants::EXTENSION_NAME.clone(),
+        vec![
+            ExtensionFunction::unary(
+                constants::DATETIME_CONSTRUCTOR_NAME.clone(),
+                CallStyle::FunctionStyle,
+                Box::new(datetime_from_str),
+                datetime_type.clone(),
+                Some(SchemaType::String),
+            ),
+            ExtensionFunction::unary(
+                constants::DURATION_CONSTRUCTOR_NAME.clone(),
+                CallStyle::FunctionStyle,
+                Box::new(duration_from_str),
+                duration_type.clone(),
+                Some(SchemaType::String),
+            ),
+            ExtensionFunction::binary(
+                constants::OFFSET.clone(),
+                CallStyle::MethodStyle,
+                Box::new(offset),
+                datetime_type.clone(),
+                (Some(datetime_type.clone()), Some(duration_type.clone())),
+            ),
+            ExtensionFunction::binary(
+                constants::DURATION_SINCE.clone(),
+                CallStyle::MethodStyle,
+                Box::new(duration_since),
+                duration_type.clone(),
+                (Some(datetime_type.clone()), Some(datetime_type.clone())),
+            ),
+            ExtensionFunction::unary(
+                constants::TO_DATE.clone(),
+                CallStyle::MethodStyle,
+                Box::new(to_date),
+                datetime_type.clone(),
+                Some(datetime_type.clone()),
+            ),
+            ExtensionFunction::unary(
+                constants::TO_TIME.clone(),
+                CallStyle::MethodStyle,
+                Box::new(to_time),
+                duration_type.clone(),
+                Some(datetime_type),
+            ),
+            duration_accessor(&constants::TO_MILLISECONDS, 1),
+            duration_accessor(&constants::TO_SECONDS, MILLISECONDS_PER_SECOND),
+            duration_accessor(&constants::TO_MINUTES, MILLISECONDS_PER_MINUTE),
+            duration_accessor(&constants::TO_HOURS, MILLISECONDS_PER_HOUR),
+            duration_accessor(&constants::TO_DAYS, MILLISECONDS_PER_DAY),
+        ],
+    )
+}
+
+#[cfg(test)]
+// PANIC SAFETY: Unit Test Code
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::ast::{Expr, Type, Value};
+    use crate::evaluator::test::{basic_entities, basic_request};
+    use crate::evaluator::{EvaluationErrorKind, Evaluator};
+    use crate::extensions::Extensions;
+    use crate::parser::parse_expr;
+    use cool_asserts::assert_matches;
+    use nonempty::nonempty;
+
+    /// Asserts that a `Result` is an `Err::ExtensionErr` with our extension name
+    #[track_caller] // report the caller's location as the location of the panic, not the location in this function
+    fn assert_datetime_err<T: std::fmt::Debug>(res: evaluator::Result<T>) {
+        assert_matches!(res, Err(e) => {
+            assert_matches!(e.error_kind(), evaluator::EvaluationErrorKind::FailedExtensionFunctionApplication {
+                extension_name,
+                ..
+            } => {
+                assert_eq!(*extension_name, constants::EXTENSION_NAME.clone())
+            });
+        });
+    }
+
+    /// Evaluate `src` with only this extension enabled
+    #[track_caller]
+    fn eval(src: &str) -> evaluator::Result<Value> {
+        let ext_array = [extension()];
+        let exts = Extensions::specific_extensions(&ext_array);
+        let request = basic_request();
+        let entities = basic_entities();
+        let eval = Evaluator::new(request, &entities, &exts);
+        eval.interpret_inline_policy(&parse_expr(src).expect("parsing error"))
+    }
+
+    #[track_caller]
+    fn assert_true(src: &str) {
+        assert_eq!(eval(src), Ok(Value::from(true)), "for `{src}`");
+    }
+
+    /// this test just ensures that the right functions are marked constructors
+    #[test]
+    fn constructors() {
+        let ext = extension();
+        for name in ["datetime", "duration"] {
+            assert!(ext
+                .get_func(
+                    &Name::parse_unqualified_name(name).expect("should be a valid identifier")
+                )
+                .expect("function should exist")
+                .is_constructor());
+        }
+        for name in [
+            "offset",
+            "durationSince",
+            "toDate",
+            "toTime",
+            "toMilliseconds",
+            "toSeconds",
+            "toMinutes",
+            "toHours",
+            "toDays",
+        ] {
+            assert!(!ext
+                .get_func(
+                    &Name::parse_unqualified_name(name).expect("should be a valid identifier")
+                )
+                .expect("function should exist")
+                .is_constructor());
+        }
+    }
+
+    #[test]
+    fn datetime_parsing() {
+        let epoch = |s: &str| DateTime::from_str(s).map(|dt| dt.epoch).ok();
+        assert_eq!(epoch("1970-01-01"), Some(0));
+        assert_eq!(epoch("1970-01-02"), Some(MILLISECONDS_PER_DAY));
+        assert_eq!(epoch("1969-12-31"), Some(-MILLISECONDS_PER_DAY));
+        assert_eq!(epoch("2024-10-15"), Some(1_728_950_400_000));
+        assert_eq!(epoch("2024-10-15T11:38:02Z"), Some(1_728_992_282_000));
+        assert_eq!(epoch("2024-10-15T11:38:02.101Z"), Some(1_728_992_282_101));
+        assert_eq!(epoch("2024-10-15T11:38:02+0100"), Some(1_728_988_682_000));
+        assert_eq!(
+            epoch("2024-10-15T11:38:02.101-0130"),
+            Some(1_728_997_682_101)
+        );
+        assert_eq!(epoch("2024-02-29"), Some(1_709_164_800_000));
+        assert_eq!(epoch("0000-01-01"), Some(-62_167_219_200_000));
+
+        for bad in [
+            "",
+            "2024",
+            "2024-1-01",
+            "2024-01-1",
+            "24-01-01",
+            "2024-00-01",
+            "2024-13-01",
+            "2024-01-00",
+            "2024-01-32",
+            "2023-02-29",
+            "2024-01-01T",
+            "2024-01-01 00:00:00Z",
+            "2024-01-01T00:00:00",
+            "2024-01-01T24:00:00Z",
+            "2024-01-01T00:60:00Z",
+            "2024-01-01T00:00:60Z",
+            "2024-01-01T00:00:00.1Z",
+            "2024-01-01T00:00:00.0001Z",
+            "2024-01-01T00:00:00+01",
+            "2024-01-01T00:00:00+01:00",
+            "2024-01-01T00:00:00+2400",
+            "2024-01-01T00:00:00ZZ",
+            "2024-01-01T00:00:00z",
+            "+2024-01-01",
+            "２０２４-01-01",
+        ] {
+            assert_matches!(
+                DateTime::from_str(bad),
+                Err(Error::FailedDateTimeParse(_)),
+                "`{bad}` should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn duration_parsing() {
+        let ms = |s: &str| Duration::from_str(s).map(|d| d.ms).ok();
+        assert_eq!(ms("0ms"), Some(0));
+        assert_eq!(ms("1ms"), Some(1));
+        assert_eq!(ms("1s"), Some(1000));
+        assert_eq!(ms("1m"), Some(60_000));
+        assert_eq!(ms("1h"), Some(3_600_000));
+        assert_eq!(ms("1d"), Some(86_400_000));
+        assert_eq!(ms("1d2h3m4s5ms"), Some(93_784_005));
+        assert_eq!(ms("-1d2h3m4s5ms"), Some(-93_784_005));
+        assert_eq!(ms("2h5ms"), Some(7_200_005));
+        assert_eq!(ms("90m"), Some(5_400_000));
+        assert_eq!(ms("-9223372036854775808ms"), Some(i64::MIN));
+
+        for bad in [
+            "", "-", "1", "d", "1x", "1h1d", "1m1h", "1s1s", "1ms1s", "1 h", "+1h", "1h-1m",
+            "1.5h", "--1h",
+        ] {
+            assert_matches!(
+                Duration::from_str(bad),
+                Err(Error::FailedDurationParse(_)),
+                "`{bad}` should not parse"
+            );
+        }
+        assert_matches!(
+            Duration::from_str("9223372036854775808ms"),
+            Err(Error::Overflow)
+        );
+        assert_matches!(Duration::from_str("106751991168d"), Err(Error::Overflow));
+    }
+
+    #[test]
+    fn display_round_trip() {
+        for s in [
+            "2024-10-15T11:38:02.101Z",
+            "1970-01-01T00:00:00.000Z",
+            "1969-12-31T23:59:59.999Z",
+            "0000-03-01T00:00:00.000Z",
+        ] {
+            assert_eq!(DateTime::from_str(s).expect("valid").to_string(), s);
+        }
+        for s in ["0ms", "1d2h3m4s5ms", "-1d", "59m59s999ms"] {
+            assert_eq!(Duration::from_str(s).expect("valid").to_string(), s);
+        }
+        assert_eq!(
+            Duration::from_str("90m").expect("valid").to_string(),
+            "1h30m"
+        );
+    }
+
+    #[test]
+    fn constructor_errors() {
+        assert_datetime_err(eval(r#"datetime("2024-13-01")"#));
+        assert_datetime_err(eval(r#"duration("1h1d")"#));
+        assert_datetime_err(eval(r#"duration("9223372036854775808ms")"#));
+        assert_matches!(eval(r#"datetime(1)"#), Err(e) => {
+            assert_matches!(e.error_kind(), EvaluationErrorKind::TypeError { expected, .. } => {
+                assert_eq!(expected, &nonempty![Type::String]);
+            });
+        });
+        // bad use of `datetime` as method
+        parse_expr(r#" "2024-01-01".datetime() "#).expect_err("should fail");
+    }
+
+    #[test]
+    fn equality() {
+        assert_true(r#"datetime("2024-10-15") == datetime("2024-10-15T00:00:00Z")"#);
+        assert_true(r#"datetime("2024-10-15T01:00:00+0100") == datetime("2024-10-15")"#);
+        assert_true(r#"datetime("2024-10-15") != datetime("2024-10-16")"#);
+        assert_true(r#"duration("90m") == duration("1h30m")"#);
+        assert_true(r#"duration("1d") != duration("1h")"#);
+        assert_true(r#"duration("0ms") != datetime("1970-01-01")"#);
+        assert_true(r#"datetime("2024-10-15") != "2024-10-15""#);
+    }
+
+    #[test]
+    fn comparison() {
+        assert_true(r#"datetime("2024-10-15") < datetime("2024-10-16")"#);
+        assert_true(r#"datetime("2024-10-15") <= datetime("2024-10-15")"#);
+        assert_true(r#"!(datetime("2024-10-15") > datetime("2024-10-15"))"#);
+        assert_true(r#"datetime("2024-10-16") >= datetime("2024-10-15T23:59:59.999Z")"#);
+        assert_true(r#"datetime("1969-12-31") < datetime("1970-01-01")"#);
+        assert_true(r#"duration("-1ms") < duration("0ms")"#);
+        assert_true(r#"duration("1h") > duration("59m59s999ms")"#);
+        assert_true(r#"duration("1d") >= duration("24h")"#);
+
+        assert_matches!(eval(r#"datetime("2024-10-15") < duration("1d")"#), Err(e) => {
+            assert_eq!(e.error_kind(), &EvaluationErrorKind::TypeError {
+                expected: nonempty![Type::Extension { name: DateTime::typename() }],
+                actual: Type::Extension { name: Duration::typename() },
+                advice: None,
+            });
+        });
+        assert_matches!(eval(r#"datetime("2024-10-15") < 1"#), Err(e) => {
+            assert_eq!(e.error_kind(), &EvaluationErrorKind::TypeError {
+                expected: nonempty![Type::Extension { name: DateTime::typename() }],
+                actual: Type::Long,
+                advice: None,
+            });
+        });
+        assert_matches!(eval(r#"1 < duration("1d")"#), Err(e) => {
+            assert_eq!(e.error_kind(), &EvaluationErrorKind::TypeError {
+                expected: nonempty![Type::Long],
+                actual: Type::Extension { name: Duration::typename() },
+                advice: None,
+            });
+        });
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_true(
+            r#"datetime("2024-10-15").offset(duration("1d2h")) == datetime("2024-10-16T02:00:00Z")"#,
+        );
+        assert_true(
+            r#"datetime("2024-10-15").offset(duration("-1ms")) == datetime("2024-10-14T23:59:59.999Z")"#,
+        );
+        assert_true(
+            r#"datetime("2024-10-16").durationSince(datetime("2024-10-15T12:00:00Z")) == duration("12h")"#,
+        );
+        assert_true(
+            r#"datetime("2024-10-15").durationSince(datetime("2024-10-16")) == duration("-1d")"#,
+        );
+        assert_true(r#"datetime("2024-10-15T11:38:02Z").toDate() == datetime("2024-10-15")"#);
+        assert_true(r#"datetime("1969-12-31T23:00:00Z").toDate() == datetime("1969-12-31")"#);
+        assert_true(r#"datetime("2024-10-15T11:38:02Z").toTime() == duration("11h38m2s")"#);
+        assert_true(r#"datetime("1969-12-31T23:00:00Z").toTime() == duration("23h")"#);
+
+        assert_datetime_err(eval(
+            r#"datetime("9999-12-31").offset(duration("9223372036854775807ms"))"#,
+        ));
+        assert_datetime_err(eval(
+            r#"datetime("0000-01-01").offset(duration("-9223372036854775808ms"))"#,
+        ));
+        // results must have a year the constructor accepts
+        assert_true(
+            r#"datetime("9999-12-31").offset(duration("23h59m59s999ms")) == datetime("9999-12-31T23:59:59.999Z")"#,
+        );
+        assert_datetime_err(eval(r#"datetime("9999-12-31").offset(duration("1d"))"#));
+        assert_datetime_err(eval(r#"datetime("0000-01-01").offset(duration("-1ms"))"#));
+        assert_datetime_err(eval(r#"datetime("0000-01-01T00:00:00+0100").toDate()"#));
+        assert_matches!(eval(r#"datetime("2024-10-15").offset("1d")"#), Err(e) => {
+            assert_matches!(e.error_kind(), EvaluationErrorKind::TypeError { advice, .. } => {
+                assert_eq!(advice.as_deref(), Some(DURATION_ADVICE_MSG));
+            });
+        });
+    }
+
+    #[test]
+    fn duration_conversions() {
+        assert_eq!(
+            eval(r#"duration("1d2h3m4s5ms").toMilliseconds()"#),
+            Ok(Value::from(93_784_005))
+        );
+        assert_eq!(
+            eval(r#"duration("1d2h3m4s5ms").toSeconds()"#),
+            Ok(Value::from(93_784))
+        );
+        assert_eq!(
+            eval(r#"duration("1d2h3m4s5ms").toMinutes()"#),
+            Ok(Value::from(1_563))
+        );
+        assert_eq!(
+            eval(r#"duration("1d2h3m4s5ms").toHours()"#),
+            Ok(Value::from(26))
+        );
+        assert_eq!(
+            eval(r#"duration("1d2h3m4s5ms").toDays()"#),
+            Ok(Value::from(1))
+        );
+        // conversions truncate towards zero
+        assert_eq!(eval(r#"duration("-1d23h").toDays()"#), Ok(Value::from(-1)));
+    }
+
+    #[test]
+    fn computed_values_round_trip() {
+        let v = eval(r#"datetime("2024-10-15T11:38:02+0100").offset(duration("1ms"))"#)
+            .expect("should evaluate");
+        let e = Expr::from(v.clone());
+        assert_eq!(e.to_string(), r#"datetime("2024-10-15T10:38:02.001Z")"#);
+        assert_eq!(eval(&e.to_string()), Ok(v));
+    }
+}
@@ -0,0 +1,754 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains a backend-neutral filter language that the residuals
+//! of partial evaluation can be translated into, so that authorization can be
+//! pushed down into a data store, and a reference renderer from filters to
+//! SQL.
+//!
+//! The unknowns in a residual (and any variables left in it) become the roots
+//! of [`AttributePath`]s: e.g., with an unknown resource, the residual
+//! `resource.owner == User::"alice"` becomes a comparison between the path
+//! `resource.owner` and the entity literal `User::"alice"`.
+//!
+//! Filters can also be viewed as [`Constraints`] on the values of each
+//! unknown, e.g. to explain what it would take for a request to be allowed.
+
+use crate::ast::{self, BinaryOp, Expr, ExprKind, UnaryOp};
+use crate::authorizer::PartialResponse;
+use itertools::Itertools;
+use smol_str::SmolStr;
+use std::fmt::Display;
+
+mod constraints;
+pub use constraints::{Alternative, Constraint, Constraints, MAX_ALTERNATIVES};
+mod err;
+pub use err::FilterError;
+pub mod sql;
+
+/// A boolean condition over the unknowns of a residual
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    /// Constant `true` or `false`
+    Const(bool),
+    /// All of the filters hold
+    And(Vec<Filter>),
+    /// Any of the filters holds
+    Or(Vec<Filter>),
+    /// The filter does not hold
+    Not(Box<Filter>),
+    /// The filter holds, as opposed to being `false` or failing to evaluate
+    /// (e.g., because it reads a missing attribute).
+    ///
+    /// A policy only applies when its condition holds in this sense, so
+    /// [`Filter::from_response()`] wraps the condition of each residual
+    /// policy in `IsTrue`.
+    IsTrue(Box<Filter>),
+    /// Comparison of two operands
+    Compare {
+        /// The comparison
+        op: CompareOp,
+        /// Left operand
+        left: Operand,
+        /// Right operand
+        right: Operand,
+    },
+    /// Set operation on two operands
+    Set {
+        /// The operation
+        op: SetOp,
+        /// Left operand, which is a set
+        left: Operand,
+        /// Right operand, which is an element for [`SetOp::Contains`] and a
+        /// set otherwise
+        right: Operand,
+    },
+    /// The entity `entity` is `ancestor` or one of its descendants in the
+    /// entity hierarchy
+    In {
+        /// The descendant
+        entity: Operand,
+        /// The ancestor
+        ancestor: Operand,
+    },
+    /// The attribute exists
+    Has(AttributePath),
+    /// The entity `entity` has type `entity_type`
+    Is {
+        /// The entity
+        entity: Operand,
+        /// The entity type, e.g. `Namespace::Photo`
+        entity_type: SmolStr,
+    },
+}
+
+/// A comparison between two [`Operand`]s. Other comparisons are expressed
+/// with [`Filter::Not`], e.g. `a > b` as `!(a <= b)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    /// `==`
+    Eq,
+    /// `<`
+    Less,
+    /// `<=`
+    LessEq,
+}
+
+/// A set operation between two [`Operand`]s
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetOp {
+    /// `.contains()`
+    Contains,
+    /// `.containsAll()`
+    ContainsAll,
+    /// `.containsAny()`
+    ContainsAny,
+}
+
+/// A value a [`Filter`] is about
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operand {
+    /// An unknown or one of its (nested) attributes
+    Attribute(AttributePath),
+    /// A literal value
+    Literal(Literal),
+}
+
+/// An unknown, or a chain of attribute accesses starting from an unknown
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributePath {
+    /// Name of the unknown, e.g. `resource`
+    pub root: SmolStr,
+    /// Attributes accessed, outermost last; empty for the unknown itself
+    pub attrs: Vec<SmolStr>,
+    /// The entity the unknown stands for, when it was created for an entity
+    /// missing from a partial entity store
+    pub entity: Option<ast::EntityUID>,
+}
+
+/// A literal value in a [`Filter`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Literal {
+    /// Boolean
+    Bool(bool),
+    /// Integer
+    Long(i64),
+    /// String
+    String(SmolStr),
+    /// Entity
+    Entity {
+        /// The entity type, e.g. `Namespace::Photo`
+        entity_type: SmolStr,
+        /// The entity id
+        id: SmolStr,
+    },
+    /// Set of literals
+    Set(Vec<Literal>),
+}
+
+impl Filter {
+    /// Translate a residual (or any other boolean expression) into a filter.
+    ///
+    /// Fails if the expression uses a construct with no counterpart in the
+    /// filter language: `like`, arithmetic, extension functions, entity tags,
+    /// template slots, or records.
+    pub fn from_expr(expr: &Expr) -> Result<Self, FilterError> {
+        match expr.expr_kind() {
+            ExprKind::Lit(ast::Literal::Bool(b)) => Ok(Self::Const(*b)),
+            // Cedar only evaluates the right operand if it needs to, so if
+            // the left operand fails, so does the whole expression. A filter
+            // `a && b` would be `false` rather than fail when `b` is `false`
+            // (and `a || b` would hold when `b` does), which matters under a
+            // negation, so an operand that may fail is only taken into account
+            // when the left operand doesn't fail.
+            ExprKind::And { left, right } => {
+                let (left, right) = (Self::from_expr(left)?, Self::from_expr(right)?);
+                if left.may_fail() {
+                    Ok(Self::and([
+                        left.clone(),
+                        Self::or([Self::negate(left), right]),
+                    ]))
+                } else {
+                    Ok(Self::and([left, right]))
+                }
+            }
+            ExprKind::Or { left, right } => {
+                let (left, right) = (Self::from_expr(left)?, Self::from_expr(right)?);
+                if left.may_fail() {
+                    Ok(Self::or([
+                        left.clone(),
+                        Self::and([Self::negate(left), right]),
+                    ]))
+                } else {
+                    Ok(Self::or([left, right]))
+                }
+            }
+            ExprKind::If {
+                test_expr,
+                then_expr,
+                else_expr,
+            } => {
+                let test = Self::from_expr(test_expr)?;
+                // `test && !test` is `false`, unless `test` fails, in which
+                // case so does the whole filter
+                let fails = if test.may_fail() {
+                    Self::and([test.clone(), Self::negate(test.clone())])
+                } else {
+                    Self::Const(false)
+                };
+                Ok(Self::or([
+                    Self::and([test.clone(), Self::from_expr(then_expr)?]),
+                    Self::and([Self::negate(test), Self::from_expr(else_expr)?]),
+                    fails,
+                ]))
+            }
+            ExprKind::UnaryApp {
+                op: UnaryOp::Not,
+                arg,
+            } => Ok(Self::negate(Self::from_expr(arg)?)),
+            ExprKind::BinaryApp { op, arg1, arg2 } => {
+                let (left, right) = (Operand::from_expr(arg1)?, Operand::from_expr(arg2)?);
+                match op {
+                    BinaryOp::Eq => Ok(Self::compare(CompareOp::Eq, left, right)),
+                    BinaryOp::Less => Ok(Self::compare(CompareOp::Less, left, right)),
+                    BinaryOp::LessEq => Ok(Self::compare(CompareOp::LessEq, left, right)),
+                    BinaryOp::Contains => Ok(Self::set(SetOp::Contains, left, right)),
+                    BinaryOp::ContainsAll => Ok(Self::set(SetOp::ContainsAll, left, right)),
+                    BinaryOp::ContainsAny => Ok(Self::set(SetOp::ContainsAny, left, right)),
+                    BinaryOp::In => Ok(match right {
+                        // `e in [a, b]` is `e in a || e in b`
+                        Operand::Literal(Literal::Set(ancestors)) => Self::or(
+                            ancestors
+                                .into_iter()
+                                .map(|a| Self::is_in(left.clone(), Operand::Literal(a))),
+                        ),
+                        ancestor => Self::is_in(left, ancestor),
+                    }),
+                    BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul => {
+                        Err(FilterError::unsupported("arithmetic", expr))
+                    }
+                    BinaryOp::GetTag | BinaryOp::HasTag => {
+                        Err(FilterError::unsupported("entity tags", expr))
+                    }
+                }
+            }
+            ExprKind::HasAttr { expr: inner, attr } => match Operand::from_expr(inner)? {
+                Operand::Attribute(mut path) => {
+                    path.attrs.push(attr.clone());
+                    Ok(Self::Has(path))
+                }
+                Operand::Literal(_) => Err(FilterError::unsupported(
+                    "`has` on a value that is not an unknown or an attribute of one",
+                    expr,
+                )),
+            },
+            ExprKind::Is {
+                expr: inner,
+                entity_type,
+            } => Ok(Self::Is {
+                entity: Operand::from_expr(inner)?,
+                entity_type: entity_type.to_string().into(),
+            }),
+            ExprKind::Like { .. } => Err(FilterError::unsupported("`like` patterns", expr)),
+            // a boolean unknown or attribute
+            ExprKind::Var(_) | ExprKind::Unknown(_) | ExprKind::GetAttr { .. } => {
+                Ok(Self::compare(
+                    CompareOp::Eq,
+                    Operand::from_expr(expr)?,
+                    Literal::Bool(true).into(),
+                ))
+            }
+            _ => {
+                // report the construct that can't be translated, if any
+                Operand::from_expr(expr)?;
+                Err(FilterError::unsupported("a non-boolean value", expr))
+            }
+        }
+    }
+
+    /// Translate a partial response into a single filter that holds exactly
+    /// when the request is allowed: some permit applies and no forbid does,
+    /// and no policy exceeded the evaluation limits. The residuals are taken
+    /// in order of policy id, so the filter is the same each time.
+    ///
+    /// Fails if any of the residual policies can't be translated.
+    pub fn from_response(response: &PartialResponse) -> Result<Self, FilterError> {
+        if !response.satisfied_forbids.is_empty() || response.limit_exceeded() {
+            return Ok(Self::Const(false));
+        }
+        let residual = |expr: &Expr| Ok(Self::is_true(Self::from_expr(expr)?));
+        let permits = response
+            .residual_permits
+            .iter()
+            .sorted_by_key(|(id, _)| *id)
+            .map(|(_, (expr, _))| residual(expr))
+            .collect::<Result<Vec<_>, _>>()?;
+        let forbids = response
+            .residual_forbids
+            .iter()
+            .sorted_by_key(|(id, _)| *id)
+            .map(|(_, (expr, _))| residual(expr))
+            .collect::<Result<Vec<_>, _>>()?;
+        let satisfied = Self::Const(!response.satisfied_permits.is_empty());
+        Ok(Self::and([
+            Self::or(std::iter::once(satisfied).chain(permits)),
+            Self::negate(Self::or(forbids)),
+        ]))
+    }
+
+    /// Conjunction of `filters`, dropping `true`s and flattening nested
+    /// conjunctions
+    pub fn and(filters: impl IntoIterator<Item = Self>) -> Self {
+        let mut conjuncts = Vec::new();
+        for filter in filters {
+            match filter {
+                Self::Const(true) => (),
+                Self::Const(false) => return Self::Const(false),
+                Self::And(fs) => conjuncts.extend(fs),
+                f => conjuncts.push(f),
+            }
+        }
+        match conjuncts.len() {
+            0 => Self::Const(true),
+            1 => conjuncts.remove(0),
+            _ => Self::And(conjuncts),
+        }
+    }
+
+    /// Disjunction of `filters`, dropping `false`s and flattening nested
+    /// disjunctions
+    pub fn or(filters: impl IntoIterator<Item = Self>) -> Self {
+        let mut disjuncts = Vec::new();
+        for filter in filters {
+            match filter {
+                Self::Const(false) => (),
+                Self::Const(true) => return Self::Const(true),
+                Self::Or(fs) => disjuncts.extend(fs),
+                f => disjuncts.push(f),
+            }
+        }
+        match disjuncts.len() {
+            0 => Self::Const(false),
+            1 => disjuncts.remove(0),
+            _ => Self::Or(disjuncts),
+        }
+    }
+
+    /// Negation of `filter`
+    pub fn negate(filter: Self) -> Self {
+        match filter {
+            Self::Const(b) => Self::Const(!b),
+            Self::Not(f) => *f,
+            f => Self::Not(Box::new(f)),
+        }
+    }
+
+    /// `filter` holds, as opposed to being `false` or failing to evaluate
+    pub fn is_true(filter: Self) -> Self {
+        match filter {
+            Self::Const(b) => Self::Const(b),
+            f @ Self::IsTrue(_) => f,
+            f => Self::IsTrue(Box::new(f)),
+        }
+    }
+
+    /// Can evaluating this filter fail, because it reads an attribute that
+    /// may be missing?
+    fn may_fail(&self) -> bool {
+        match self {
+            Self::Const(_) | Self::Has(_) | Self::IsTrue(_) => false,
+            Self::And(fs) | Self::Or(fs) => fs.iter().any(Self::may_fail),
+            Self::Not(f) => f.may_fail(),
+            Self::Compare { left, right, .. } | Self::Set { left, right, .. } => {
+                left.may_fail() || right.may_fail()
+            }
+            Self::In { entity, ancestor } => entity.may_fail() || ancestor.may_fail(),
+            Self::Is { entity, .. } => entity.may_fail(),
+        }
+    }
+
+    fn compare(op: CompareOp, left: Operand, right: Operand) -> Self {
+        Self::Compare { op, left, right }
+    }
+
+    fn set(op: SetOp, left: Operand, right: Operand) -> Self {
+        Self::Set { op, left, right }
+    }
+
+    fn is_in(entity: Operand, ancestor: Operand) -> Self {
+        Self::In { entity, ancestor }
+    }
+}
+
+impl Operand {
+    /// Translate a non-boolean subexpression of a residual
+    fn from_expr(expr: &Expr) -> Result<Self, FilterError> {
+        match expr.expr_kind() {
+            ExprKind::Lit(_) | ExprKind::Set(_) => Literal::from_expr(expr).map(Self::Literal),
+            ExprKind::Unknown(ast::Unknown { name, missing, .. }) => {
+                Ok(Self::Attribute(AttributePath {
+                    root: name.clone(),
+                    attrs: Vec::new(),
+                    entity: match missing {
+                        Some(ast::MissingData::Entity(uid)) => Some(uid.clone()),
+                        _ => None,
+                    },
+                }))
+            }
+            ExprKind::Var(var) => Ok(Self::Attribute(AttributePath {
+                root: var.to_string().into(),
+                attrs: Vec::new(),
+                entity: None,
+            })),
+            ExprKind::GetAttr { expr: inner, attr } => match Self::from_expr(inner)? {
+                Self::Attribute(mut path) => {
+                    path.attrs.push(attr.clone());
+                    Ok(Self::Attribute(path))
+                }
+                Self::Literal(_) => Err(FilterError::unsupported(
+                    "attribute access on a value that is not an unknown or an attribute of one",
+                    expr,
+                )),
+            },
+            ExprKind::Slot(_) => Err(FilterError::unsupported("template slots", expr)),
+            ExprKind::ExtensionFunctionApp { .. } => {
+                Err(FilterError::unsupported("extension functions", expr))
+            }
+            ExprKind::Record(_) => Err(FilterError::unsupported("records", expr)),
+            ExprKind::UnaryApp {
+                op: UnaryOp::Neg, ..
+            }
+            | ExprKind::BinaryApp {
+                op: BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul,
+                ..
+            } => Err(FilterError::unsupported("arithmetic", expr)),
+            ExprKind::BinaryApp {
+                op: BinaryOp::GetTag,
+                ..
+            } => Err(FilterError::unsupported("entity tags", expr)),
+            // boolean subexpressions used as values
+            _ => Err(FilterError::unsupported(
+                "a boolean expression used as a value",
+                expr,
+            )),
+        }
+    }
+
+    /// Can reading this operand fail, because it's an attribute that may be
+    /// missing?
+    fn may_fail(&self) -> bool {
+        match self {
+            Self::Attribute(path) => !path.attrs.is_empty(),
+            Self::Literal(_) => false,
+        }
+    }
+}
+
+impl From<Literal> for Operand {
+    fn from(lit: Literal) -> Self {
+        Self::Literal(lit)
+    }
+}
+
+impl Literal {
+    fn from_expr(expr: &Expr) -> Result<Self, FilterError> {
+        match expr.expr_kind() {
+            ExprKind::Lit(ast::Literal::Bool(b)) => Ok(Self::Bool(*b)),
+            ExprKind::Lit(ast::Literal::Long(i)) => Ok(Self::Long(*i)),
+            ExprKind::Lit(ast::Literal::String(s)) => Ok(Self::String(s.clone())),
+            ExprKind::Lit(ast::Literal::EntityUID(uid)) => Ok(Self::Entity {
+                entity_type: uid.entity_type().to_string().into(),
+                id: AsRef::<str>::as_ref(uid.eid()).into(),
+            }),
+            ExprKind::Set(elems) => elems
+                .iter()
+                .map(Self::from_expr)
+                .collect::<Result<_, _>>()
+                .map(Self::Set),
+            _ => Err(FilterError::unsupported(
+                "a set containing an expression that is not a literal",
+                expr,
+            )),
+        }
+    }
+}
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Const(b) => write!(f, "{b}"),
+            Self::And(fs) => write!(f, "({})", fs.iter().join(" && ")),
+            Self::Or(fs) => write!(f, "({})", fs.iter().join(" || ")),
+            Self::Not(filter) => write!(f, "!({filter})"),
+            Self::IsTrue(filter) => write!(f, "holds({filter})"),
+            Self::Compare { op, left, right } => {
+                let op = match op {
+                    CompareOp::Eq => "==",
+                    CompareOp::Less => "<",
+                    CompareOp::LessEq => "<=",
+                };
+                write!(f, "{left} {op} {right}")
+            }
+            Self::Set { op, left, right } => {
+                let op = match op {
+                    SetOp::Contains => "contains",
+                    SetOp::ContainsAll => "containsAll",
+                    SetOp::ContainsAny => "containsAny",
+                };
+                write!(f, "{left}.{op}({right})")
+            }
+            Self::In { entity, ancestor } => write!(f, "{entity} in {ancestor}"),
+            Self::Has(path) => write!(f, "{path} exists"),
+            Self::Is {
+                entity,
+                entity_type,
+            } => write!(f, "{entity} is {entity_type}"),
+        }
+    }
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Attribute(path) => write!(f, "{path}"),
+            Self::Literal(lit) => write!(f, "{lit}"),
+        }
+    }
+}
+
+impl Display for AttributePath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.root)?;
+        for attr in &self.attrs {
+            write!(f, ".{attr}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Literal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Long(i) => write!(f, "{i}"),
+            Self::String(s) => write!(f, "\"{}\"", s.escape_debug()),
+            Self::Entity { entity_type, id } => {
+                write!(f, "{entity_type}::\"{}\"", id.escape_debug())
+            }
+            Self::Set(elems) => write!(f, "[{}]", elems.iter().join(", ")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::{Context, EntityUIDEntry, PolicyID, PolicySet, Request};
+    use crate::authorizer::Authorizer;
+    use crate::entities::Entities;
+    use crate::evaluator::EvaluationLimits;
+    use crate::parser::{self, parse_expr};
+    use cool_asserts::assert_matches;
+
+    fn path(root: &str, attrs: &[&str]) -> Operand {
+        Operand::Attribute(AttributePath {
+            root: root.into(),
+            attrs: attrs.iter().map(|a| (*a).into()).collect(),
+            entity: None,
+        })
+    }
+
+    fn entity(ty: &str, id: &str) -> Operand {
+        Literal::Entity {
+            entity_type: ty.into(),
+            id: id.into(),
+        }
+        .into()
+    }
+
+    #[test]
+    fn translates_residuals() {
+        let filter = |src: &str| Filter::from_expr(&parse_expr(src).unwrap());
+        assert_eq!(
+            filter(r#"true && resource.owner == User::"alice""#).unwrap(),
+            Filter::Compare {
+                op: CompareOp::Eq,
+                left: path("resource", &["owner"]),
+                right: entity("User", "alice"),
+            }
+        );
+        assert_eq!(
+            filter(r#"resource in [Folder::"a", Folder::"b"] && !(resource.size > 10)"#).unwrap(),
+            Filter::And(vec![
+                Filter::Or(vec![
+                    Filter::In {
+                        entity: path("resource", &[]),
+                        ancestor: entity("Folder", "a"),
+                    },
+                    Filter::In {
+                        entity: path("resource", &[]),
+                        ancestor: entity("Folder", "b"),
+                    },
+                ]),
+                // `a > b` is parsed as `!(a <= b)`, so the negations cancel
+                Filter::Compare {
+                    op: CompareOp::LessEq,
+                    left: path("resource", &["size"]),
+                    right: Literal::Long(10).into(),
+                },
+            ])
+        );
+        assert_eq!(
+            filter(r#"resource is Doc && resource has tags && resource.tags.contains("x")"#)
+                .unwrap(),
+            Filter::And(vec![
+                Filter::Is {
+                    entity: path("resource", &[]),
+                    entity_type: "Doc".into(),
+                },
+                Filter::Has(AttributePath {
+                    root: "resource".into(),
+                    attrs: vec!["tags".into()],
+                    entity: None,
+                }),
+                Filter::Set {
+                    op: SetOp::Contains,
+                    left: path("resource", &["tags"]),
+                    right: Literal::String("x".into()).into(),
+                },
+            ])
+        );
+        assert_eq!(
+            filter("if resource.public then true else resource.shared").unwrap(),
+            Filter::Or(vec![
+                Filter::Compare {
+                    op: CompareOp::Eq,
+                    left: path("resource", &["public"]),
+                    right: Literal::Bool(true).into(),
+                },
+                Filter::And(vec![
+                    Filter::Not(Box::new(Filter::Compare {
+                        op: CompareOp::Eq,
+                        left: path("resource", &["public"]),
+                        right: Literal::Bool(true).into(),
+                    })),
+                    Filter::Compare {
+                        op: CompareOp::Eq,
+                        left: path("resource", &["shared"]),
+                        right: Literal::Bool(true).into(),
+                    },
+                ]),
+                // fails when `resource.public` does
+                Filter::And(vec![
+                    Filter::Compare {
+                        op: CompareOp::Eq,
+                        left: path("resource", &["public"]),
+                        right: Literal::Bool(true).into(),
+                    },
+                    Filter::Not(Box::new(Filter::Compare {
+                        op: CompareOp::Eq,
+                        left: path("resource", &["public"]),
+                        right: Literal::Bool(true).into(),
+                    })),
+                ]),
+            ])
+        );
+    }
+
+    #[test]
+    fn reports_untranslatable_constructs() {
+        let error = |src: &str| Filter::from_expr(&parse_expr(src).unwrap()).unwrap_err();
+        assert_matches!(
+            error(r#"resource.name like "*.txt""#),
+            FilterError::Unsupported { construct, expr } => {
+                assert_eq!(construct, "`like` patterns");
+                assert_eq!(expr, r#"(resource["name"]) like "*.txt""#);
+            }
+        );
+        assert_matches!(
+            error(r#"resource.ip.isLoopback()"#),
+            FilterError::Unsupported { construct, .. } => assert_eq!(construct, "extension functions")
+        );
+        assert_matches!(
+            error(r#"resource.size + 1 < 10"#),
+            FilterError::Unsupported { construct, .. } => assert_eq!(construct, "arithmetic")
+        );
+    }
+
+    #[test]
+    fn translates_partial_responses() {
+        let mut pset = PolicySet::new();
+        for (id, src) in [
+            (
+                "owner",
+                r#"permit(principal, action, resource) when { resource.owner == principal };"#,
+            ),
+            (
+                "archived",
+                r#"forbid(principal, action, resource) when { resource.archived };"#,
+            ),
+            (
+                "other",
+                r#"permit(principal == User::"bob", action, resource);"#,
+            ),
+        ] {
+            pset.add_static(parser::parse_policy(Some(id.into()), src).unwrap())
+                .unwrap();
+        }
+        let q = Request::new_unchecked(
+            EntityUIDEntry::concrete(r#"User::"alice""#.parse().unwrap(), None),
+            EntityUIDEntry::concrete(r#"Action::"view""#.parse().unwrap(), None),
+            EntityUIDEntry::Unknown { loc: None },
+            Some(Context::empty()),
+        );
+        let response = Authorizer::new().is_authorized_core(q.clone(), &pset, &Entities::new());
+        assert_eq!(
+            Filter::from_response(&response).unwrap(),
+            Filter::And(vec![
+                Filter::IsTrue(Box::new(Filter::Compare {
+                    op: CompareOp::Eq,
+                    left: path("resource", &["owner"]),
+                    right: entity("User", "alice"),
+                })),
+                Filter::Not(Box::new(Filter::IsTrue(Box::new(Filter::Compare {
+                    op: CompareOp::Eq,
+                    left: path("resource", &["archived"]),
+                    right: Literal::Bool(true).into(),
+                })))),
+            ])
+        );
+        assert!(response
+            .false_permits
+            .contains_key(&PolicyID::from_string("other")));
+
+        // a policy that exceeds the limits denies everything
+        pset.add_static(
+            parser::parse_policy(
+                Some("big".into()),
+                r#"forbid(principal, action, resource) when { [1, 2, 3].contains(resource.n) };"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let response = Authorizer::new()
+            .with_limits(EvaluationLimits::new().with_max_collection_size(2))
+            .is_authorized_core(q, &pset, &Entities::new());
+        assert_eq!(
+            Filter::from_response(&response).unwrap(),
+            Filter::Const(false)
+        );
+    }
+}
@@ -0,0 +1,611 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains a view of [`Filter`]s as constraints on the values of
+//! individual unknowns.
+
+use super::{AttributePath, CompareOp, Filter, FilterError, Literal, Operand, SetOp};
+use crate::authorizer::PartialResponse;
+use itertools::Itertools;
+use smol_str::SmolStr;
+use std::collections::BTreeSet;
+use std::fmt::Display;
+
+/// The most alternatives [`Constraints::from_filter()`] will produce. Putting
+/// a filter in disjunctive normal form can multiply the number of
+/// alternatives, so larger filters are rejected rather than expanded.
+pub const MAX_ALTERNATIVES: usize = 1024;
+
+/// The ways a filter can hold, as constraints on its unknowns.
+///
+/// The filter holds exactly when all the constraints of at least one of the
+/// [`Alternative`]s do. Conditions are taken to hold when they evaluate to
+/// `true`: the constraints don't describe values on which a residual would
+/// fail to evaluate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraints {
+    /// The alternatives, none of which is known to be impossible
+    pub alternatives: Vec<Alternative>,
+}
+
+/// Constraints that all have to hold at once
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Alternative {
+    /// The constraints
+    pub constraints: Vec<Constraint>,
+}
+
+/// A constraint on an unknown, or on one of its (nested) attributes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Constraint {
+    /// The value is one of `values`
+    OneOf {
+        /// The constrained value
+        path: AttributePath,
+        /// The allowed values
+        values: Vec<Literal>,
+    },
+    /// The value is none of `values`
+    NoneOf {
+        /// The constrained value
+        path: AttributePath,
+        /// The disallowed values
+        values: Vec<Literal>,
+    },
+    /// The value is a long between `min` and `max`, inclusive
+    Range {
+        /// The constrained value
+        path: AttributePath,
+        /// Lower bound, if any
+        min: Option<i64>,
+        /// Upper bound, if any
+        max: Option<i64>,
+    },
+    /// The value is an entity that is `ancestor` or one of its descendants
+    In {
+        /// The constrained value
+        path: AttributePath,
+        /// The entity
+        ancestor: Literal,
+    },
+    /// The value is an entity that is neither `ancestor` nor one of its
+    /// descendants
+    NotIn {
+        /// The constrained value
+        path: AttributePath,
+        /// The entity
+        ancestor: Literal,
+    },
+    /// The value is an entity of type `entity_type`
+    Is {
+        /// The constrained value
+        path: AttributePath,
+        /// The entity type, e.g. `Namespace::Photo`
+        entity_type: SmolStr,
+    },
+    /// The value is an entity of a type other than `entity_type`
+    IsNot {
+        /// The constrained value
+        path: AttributePath,
+        /// The entity type, e.g. `Namespace::Photo`
+        entity_type: SmolStr,
+    },
+    /// The attribute exists
+    Has(AttributePath),
+    /// The attribute does not exist
+    Missing(AttributePath),
+    /// A condition that doesn't constrain a single value on its own, e.g. a
+    /// comparison between two attributes
+    Other(Filter),
+}
+
+impl Constraints {
+    /// Get the constraints under which `filter` holds.
+    ///
+    /// Fails if that takes more than [`MAX_ALTERNATIVES`] alternatives.
+    pub fn from_filter(filter: &Filter) -> Result<Self, FilterError> {
+        let alternatives = dnf(filter, false)?
+            .into_iter()
+            .filter_map(|atoms| Alternative::from_atoms(atoms.into_iter()))
+            .collect();
+        Ok(Self { alternatives })
+    }
+
+    /// Get the constraints under which the request is allowed, i.e., those
+    /// under which [`Filter::from_response()`] holds
+    pub fn from_response(response: &PartialResponse) -> Result<Self, FilterError> {
+        Self::from_filter(&Filter::from_response(response)?)
+    }
+
+    /// Is the filter known to never hold?
+    pub fn is_unsatisfiable(&self) -> bool {
+        self.alternatives.is_empty()
+    }
+
+    /// Does the filter hold whatever the unknowns are?
+    pub fn is_unconditional(&self) -> bool {
+        self.alternatives
+            .iter()
+            .any(|alternative| alternative.constraints.is_empty())
+    }
+}
+
+impl Alternative {
+    /// Names of the unknowns constrained by this alternative
+    pub fn unknowns(&self) -> BTreeSet<&SmolStr> {
+        self.constraints
+            .iter()
+            .flat_map(Constraint::unknowns)
+            .collect()
+    }
+
+    /// The constraints of this alternative that involve the unknown `name`
+    pub fn constraints_on<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Constraint> {
+        self.constraints
+            .iter()
+            .filter(move |constraint| constraint.unknowns().any(|root| root == name))
+    }
+
+    /// Combine the atoms of a conjunction into constraints, or return `None`
+    /// if they contradict each other
+    fn from_atoms(atoms: impl Iterator<Item = Constraint>) -> Option<Self> {
+        let mut constraints: Vec<Constraint> = Vec::new();
+        for atom in atoms {
+            if !constraints.contains(&atom) && !merge(&mut constraints, &atom)? {
+                constraints.push(atom);
+            }
+        }
+        // excluded values that the allowed values already rule out are noise
+        let excluded = constraints
+            .iter()
+            .filter_map(|constraint| match constraint {
+                Constraint::NoneOf { path, values } => Some((path.clone(), values.clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for (excluded_path, excluded_values) in excluded {
+            let mut allowed_elsewhere = false;
+            for constraint in &mut constraints {
+                if let Constraint::OneOf { path, values } = constraint {
+                    if *path == excluded_path {
+                        values.retain(|value| !excluded_values.contains(value));
+                        if values.is_empty() {
+                            return None;
+                        }
+                        allowed_elsewhere = true;
+                    }
+                }
+            }
+            if allowed_elsewhere {
+                constraints.retain(|constraint| {
+                    !matches!(constraint, Constraint::NoneOf { path, .. } if *path == excluded_path)
+                });
+            }
+        }
+        Some(Self { constraints })
+    }
+}
+
+/// Try to merge `atom` into an existing constraint on the same value.
+/// Returns `Some(true)` if it was merged, `Some(false)` if there is nothing
+/// to merge it with, and `None` if the result is unsatisfiable.
+fn merge(constraints: &mut [Constraint], atom: &Constraint) -> Option<bool> {
+    for constraint in constraints.iter_mut() {
+        match (constraint, atom) {
+            (
+                Constraint::OneOf { path, values },
+                Constraint::OneOf {
+                    path: other_path,
+                    values: other_values,
+                },
+            ) if path == other_path => {
+                values.retain(|value| other_values.contains(value));
+                return if values.is_empty() { None } else { Some(true) };
+            }
+            (
+                Constraint::NoneOf { path, values },
+                Constraint::NoneOf {
+                    path: other_path,
+                    values: other_values,
+                },
+            ) if path == other_path => {
+                for value in other_values {
+                    if !values.contains(value) {
+                        values.push(value.clone());
+                    }
+                }
+                return Some(true);
+            }
+            (
+                Constraint::Range { path, min, max },
+                Constraint::Range {
+                    path: other_path,
+                    min: other_min,
+                    max: other_max,
+                },
+            ) if path == other_path => {
+                *min = (*min).max(*other_min);
+                *max = match (*max, *other_max) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+                return match (*min, *max) {
+                    (Some(min), Some(max)) if min > max => None,
+                    _ => Some(true),
+                };
+            }
+            (Constraint::Has(path), Constraint::Missing(other_path))
+            | (Constraint::Missing(path), Constraint::Has(other_path))
+                if path == other_path =>
+            {
+                return None;
+            }
+            _ => (),
+        }
+    }
+    Some(false)
+}
+
+/// Put `filter` (negated if `negated` is set) in disjunctive normal form, as
+/// a list of conjunctions of atomic constraints
+fn dnf(filter: &Filter, negated: bool) -> Result<Vec<Vec<Constraint>>, FilterError> {
+    match (filter, negated) {
+        (Filter::Const(b), _) => Ok(if *b != negated {
+            vec![Vec::new()]
+        } else {
+            Vec::new()
+        }),
+        (Filter::Not(f), _) => dnf(f, !negated),
+        (Filter::IsTrue(f), _) => dnf(f, negated),
+        (Filter::And(fs), false) | (Filter::Or(fs), true) => {
+            fs.iter().try_fold(vec![Vec::new()], |acc, f| {
+                let alternatives = dnf(f, negated)?;
+                if acc.len() * alternatives.len() > MAX_ALTERNATIVES {
+                    return Err(FilterError::TooManyAlternatives {
+                        limit: MAX_ALTERNATIVES,
+                    });
+                }
+                Ok(acc
+                    .iter()
+                    .cartesian_product(&alternatives)
+                    .map(|(a, b)| a.iter().chain(b).cloned().collect())
+                    .collect())
+            })
+        }
+        (Filter::Or(fs), false) | (Filter::And(fs), true) => {
+            let mut alternatives = Vec::new();
+            for f in fs {
+                alternatives.extend(dnf(f, negated)?);
+                if alternatives.len() > MAX_ALTERNATIVES {
+                    return Err(FilterError::TooManyAlternatives {
+                        limit: MAX_ALTERNATIVES,
+                    });
+                }
+            }
+            Ok(alternatives)
+        }
+        (atom, negated) => Ok(Constraint::from_atom(atom, negated)
+            .map(|constraint| vec![vec![constraint]])
+            .unwrap_or_default()),
+    }
+}
+
+impl Constraint {
+    /// The constraint expressed by the atomic filter `atom`, or by its
+    /// negation if `negated` is set. `None` if it can never hold.
+    fn from_atom(atom: &Filter, negated: bool) -> Option<Self> {
+        let other = || {
+            Self::Other(if negated {
+                Filter::negate(atom.clone())
+            } else {
+                atom.clone()
+            })
+        };
+        Some(match atom {
+            Filter::Compare {
+                op: CompareOp::Eq,
+                left: Operand::Attribute(path),
+                right: Operand::Literal(value),
+            }
+            | Filter::Compare {
+                op: CompareOp::Eq,
+                left: Operand::Literal(value),
+                right: Operand::Attribute(path),
+            } => one_of(path, vec![value.clone()], negated),
+            Filter::Set {
+                op: SetOp::Contains,
+                left: Operand::Literal(Literal::Set(values)),
+                right: Operand::Attribute(path),
+            } => {
+                if values.is_empty() && !negated {
+                    return None;
+                }
+                one_of(path, values.clone(), negated)
+            }
+            Filter::Compare {
+                op: op @ (CompareOp::Less | CompareOp::LessEq),
+                left,
+                right,
+            } => match (left, right) {
+                (Operand::Attribute(path), Operand::Literal(Literal::Long(bound))) => {
+                    // `path < bound`, or `path >= bound` when negated
+                    let strict = matches!(op, CompareOp::Less);
+                    if negated {
+                        range(
+                            path,
+                            Some(if strict {
+                                *bound
+                            } else {
+                                bound.checked_add(1)?
+                            }),
+                            None,
+                        )
+                    } else {
+                        range(
+                            path,
+                            None,
+                            Some(if strict {
+                                bound.checked_sub(1)?
+                            } else {
+                                *bound
+                            }),
+                        )
+                    }
+                }
+                (Operand::Literal(Literal::Long(bound)), Operand::Attribute(path)) => {
+                    // `bound < path`, or `path <= bound` when negated
+                    let strict = matches!(op, CompareOp::Less);
+                    if negated {
+                        range(
+                            path,
+                            None,
+                            Some(if strict {
+                                *bound
+                            } else {
+                                bound.checked_sub(1)?
+                            }),
+                        )
+                    } else {
+                        range(
+                            path,
+                            Some(if strict {
+                                bound.checked_add(1)?
+                            } else {
+                                *bound
+                            }),
+                            None,
+                        )
+                    }
+                }
+                _ => other(),
+            },
+            Filter::In {
+                entity: Operand::Attribute(path),
+                ancestor: Operand::Literal(ancestor @ Literal::Entity { .. }),
+            } => {
+                let (path, ancestor) = (path.clone(), ancestor.clone());
+                if negated {
+                    Self::NotIn { path, ancestor }
+                } else {
+                    Self::In { path, ancestor }
+                }
+            }
+            Filter::Is {
+                entity: Operand::Attribute(path),
+                entity_type,
+            } => {
+                let (path, entity_type) = (path.clone(), entity_type.clone());
+                if negated {
+                    Self::IsNot { path, entity_type }
+                } else {
+                    Self::Is { path, entity_type }
+                }
+            }
+            Filter::Has(path) => {
+                if negated {
+                    Self::Missing(path.clone())
+                } else {
+                    Self::Has(path.clone())
+                }
+            }
+            _ => other(),
+        })
+    }
+
+    /// Names of the unknowns this constraint involves
+    pub fn unknowns(&self) -> Box<dyn Iterator<Item = &SmolStr> + '_> {
+        match self {
+            Self::OneOf { path, .. }
+            | Self::NoneOf { path, .. }
+            | Self::Range { path, .. }
+            | Self::In { path, .. }
+            | Self::NotIn { path, .. }
+            | Self::Is { path, .. }
+            | Self::IsNot { path, .. }
+            | Self::Has(path)
+            | Self::Missing(path) => Box::new(std::iter::once(&path.root)),
+            Self::Other(filter) => Box::new(filter.paths().map(|path| &path.root).unique()),
+        }
+    }
+}
+
+fn one_of(path: &AttributePath, values: Vec<Literal>, negated: bool) -> Constraint {
+    let path = path.clone();
+    if negated {
+        Constraint::NoneOf { path, values }
+    } else {
+        Constraint::OneOf { path, values }
+    }
+}
+
+fn range(path: &AttributePath, min: Option<i64>, max: Option<i64>) -> Constraint {
+    Constraint::Range {
+        path: path.clone(),
+        min,
+        max,
+    }
+}
+
+impl Filter {
+    /// Iterate over the attribute paths this filter mentions
+    fn paths(&self) -> Box<dyn Iterator<Item = &AttributePath> + '_> {
+        fn operand(operand: &Operand) -> Option<&AttributePath> {
+            match operand {
+                Operand::Attribute(path) => Some(path),
+                Operand::Literal(_) => None,
+            }
+        }
+        match self {
+            Self::Const(_) => Box::new(std::iter::empty()),
+            Self::And(fs) | Self::Or(fs) => Box::new(fs.iter().flat_map(Self::paths)),
+            Self::Not(f) | Self::IsTrue(f) => f.paths(),
+            Self::Compare { left, right, .. } | Self::Set { left, right, .. } => {
+                Box::new(operand(left).into_iter().chain(operand(right)))
+            }
+            Self::In { entity, ancestor } => {
+                Box::new(operand(entity).into_iter().chain(operand(ancestor)))
+            }
+            Self::Has(path) => Box::new(std::iter::once(path)),
+            Self::Is { entity, .. } => Box::new(operand(entity).into_iter()),
+        }
+    }
+}
+
+impl Display for Constraints {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.alternatives.as_slice() {
+            [] => write!(f, "never"),
+            alternatives => write!(f, "{}", alternatives.iter().join(", or ")),
+        }
+    }
+}
+
+impl Display for Alternative {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.constraints.as_slice() {
+            [] => write!(f, "always"),
+            constraints => write!(f, "{}", constraints.iter().join(" and ")),
+        }
+    }
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OneOf { path, values } => match values.as_slice() {
+                [value] => write!(f, "{path} == {value}"),
+                values => write!(f, "{path} is one of [{}]", values.iter().join(", ")),
+            },
+            Self::NoneOf { path, values } => match values.as_slice() {
+                [value] => write!(f, "{path} != {value}"),
+                values => write!(f, "{path} is none of [{}]", values.iter().join(", ")),
+            },
+            Self::Range { path, min, max } => match (min, max) {
+                (Some(min), Some(max)) => write!(f, "{min} <= {path} <= {max}"),
+                (Some(min), None) => write!(f, "{path} >= {min}"),
+                (None, Some(max)) => write!(f, "{path} <= {max}"),
+                (None, None) => write!(f, "{path} is a long"),
+            },
+            Self::In { path, ancestor } => write!(f, "{path} in {ancestor}"),
+            Self::NotIn { path, ancestor } => write!(f, "{path} not in {ancestor}"),
+            Self::Is { path, entity_type } => write!(f, "{path} is {entity_type}"),
+            Self::IsNot { path, entity_type } => write!(f, "{path} is not {entity_type}"),
+            Self::Has(path) => write!(f, "{path} exists"),
+            Self::Missing(path) => write!(f, "{path} does not exist"),
+            Self::Other(filter) => write!(f, "{filter}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::parse_expr;
+    use cool_asserts::assert_matches;
+
+    fn constraints(src: &str) -> Constraints {
+        Constraints::from_filter(&Filter::from_expr(&parse_expr(src).unwrap()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn groups_constraints_by_alternative() {
+        let c = constraints(
+            r#"resource in Folder::"x" && (resource.size < 10 || resource.owner == principal)"#,
+        );
+        assert_eq!(
+            c.to_string(),
+            r#"resource in Folder::"x" and resource.size <= 9, or resource in Folder::"x" and resource.size >= 10 and resource.owner == principal"#
+        );
+        assert_eq!(
+            c.alternatives[1]
+                .unknowns()
+                .into_iter()
+                .map(SmolStr::as_str)
+                .collect::<Vec<_>>(),
+            vec!["principal", "resource"]
+        );
+        assert_eq!(c.alternatives[1].constraints_on("principal").count(), 1);
+        assert_eq!(c.alternatives[1].constraints_on("resource").count(), 3);
+    }
+
+    #[test]
+    fn merges_constraints_on_the_same_value() {
+        assert_eq!(
+            constraints("resource.size > 2 && resource.size <= 10 && !(resource.size < 4)")
+                .to_string(),
+            "4 <= resource.size <= 10"
+        );
+        assert_eq!(
+            constraints(r#"["a", "b", "c"].contains(resource.kind) && resource.kind != "b""#)
+                .to_string(),
+            r#"resource.kind is one of ["a", "c"]"#
+        );
+        assert_eq!(
+            constraints(r#"resource.kind != "a" && !(["b", "c"].contains(resource.kind))"#)
+                .to_string(),
+            r#"resource.kind is none of ["a", "b", "c"]"#
+        );
+        assert_eq!(
+            constraints(r#"!(resource is Folder) && !(resource has owner)"#).to_string(),
+            "resource is not Folder and resource.owner does not exist"
+        );
+    }
+
+    #[test]
+    fn drops_contradictions() {
+        let c = constraints(
+            r#"(resource.size > 10 && resource.size < 5) || (resource.kind == "a" && resource.kind == "b") || (resource has x && !(resource has x))"#,
+        );
+        assert!(c.is_unsatisfiable());
+        assert!(constraints("resource has size || true").is_unconditional());
+        // `resource.size < 5 || true` fails if there is no `size`
+        assert_eq!(
+            constraints("resource.size < 5 || true").to_string(),
+            "resource.size <= 4, or resource.size >= 5"
+        );
+    }
+
+    #[test]
+    fn limits_alternatives() {
+        let src = (0..11)
+            .map(|i| format!("(resource.a{i} == 1 || resource.b{i} == 1)"))
+            .join(" && ");
+        assert_matches!(
+            Constraints::from_filter(&Filter::from_expr(&parse_expr(&src).unwrap()).unwrap()),
+            Err(FilterError::TooManyAlternatives { .. })
+        );
+    }
+}
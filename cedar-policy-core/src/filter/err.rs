@@ -0,0 +1,59 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::ast::Expr;
+use miette::Diagnostic;
+use thiserror::Error;
+
+/// Errors that can occur when translating a residual into a
+/// [`super::Filter`], or a filter into SQL
+#[derive(Debug, PartialEq, Eq, Clone, Diagnostic, Error)]
+pub enum FilterError {
+    /// The residual uses a construct that has no counterpart in the filter
+    /// language
+    #[error("cannot translate `{expr}` into a filter: {construct} is not supported")]
+    Unsupported {
+        /// Description of the construct
+        construct: String,
+        /// The (sub-)expression using the construct
+        expr: String,
+    },
+    /// The filter uses a construct that the SQL renderer can't express
+    #[error("cannot render `{filter}` as SQL: {construct} is not supported")]
+    #[diagnostic(help("only literal sets can be rendered as SQL, as `IN` lists"))]
+    UnsupportedBySql {
+        /// Description of the construct
+        construct: String,
+        /// The part of the filter using the construct
+        filter: String,
+    },
+    /// Expressing the filter as constraints on its unknowns takes too many
+    /// alternatives
+    #[error("cannot express the filter as constraints: it needs more than {limit} alternatives")]
+    TooManyAlternatives {
+        /// The maximum number of alternatives
+        limit: usize,
+    },
+}
+
+impl FilterError {
+    pub(crate) fn unsupported(construct: impl Into<String>, expr: &Expr) -> Self {
+        Self::Unsupported {
+            construct: construct.into(),
+            expr: expr.to_string(),
+        }
+    }
+}
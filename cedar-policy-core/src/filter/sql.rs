@@ -0,0 +1,336 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! This module contains a reference renderer from [`Filter`]s to SQL.
+
+use super::{AttributePath, CompareOp, Filter, FilterError, Literal, Operand, SetOp};
+use itertools::Itertools;
+use std::collections::HashMap;
+
+/// Renders [`Filter`]s as SQL boolean expressions, for use in a `WHERE`
+/// clause.
+///
+/// The renderer assumes the following layout, which can be adjusted with the
+/// `with_*` methods:
+/// - Each unknown is a table (or alias) of the same name, e.g. `"resource"`.
+/// - An attribute is a column of that table, named after the attribute, with
+///   nested attributes joined by `.`: `resource.address.city` is
+///   `"resource"."address.city"`. A missing attribute is `NULL`.
+/// - Entities are stored as the string form of their UID, e.g.
+///   `'User::"alice"'`, and the UID of the unknown itself is in the `"uid"`
+///   column.
+/// - The entity hierarchy is in a table `"cedar_hierarchy"` with columns
+///   `"child"` and `"ancestor"`, holding one row for every entity and each
+///   of its ancestors (i.e., the transitive closure).
+///
+/// Sets can only be rendered as literals on the left of `.contains()`, as
+/// `IN` lists; set-valued attributes are not supported.
+#[derive(Debug, Clone)]
+pub struct SqlRenderer {
+    /// Column holding the UID of each unknown
+    uid_column: String,
+    /// Table holding the transitive closure of the entity hierarchy
+    hierarchy_table: String,
+    /// Column of `hierarchy_table` holding the descendant
+    child_column: String,
+    /// Column of `hierarchy_table` holding the ancestor
+    ancestor_column: String,
+    /// SQL for attribute paths that don't follow the default layout, keyed
+    /// by the path as written in Cedar, e.g. `resource.owner`
+    columns: HashMap<String, String>,
+}
+
+impl Default for SqlRenderer {
+    fn default() -> Self {
+        Self {
+            uid_column: "uid".into(),
+            hierarchy_table: "cedar_hierarchy".into(),
+            child_column: "child".into(),
+            ancestor_column: "ancestor".into(),
+            columns: HashMap::new(),
+        }
+    }
+}
+
+impl SqlRenderer {
+    /// Create a renderer for the default layout
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the name of the column holding the UID of each unknown
+    pub fn with_uid_column(self, uid_column: impl Into<String>) -> Self {
+        Self {
+            uid_column: uid_column.into(),
+            ..self
+        }
+    }
+
+    /// Set the names of the table holding the entity hierarchy and of its
+    /// descendant and ancestor columns
+    pub fn with_hierarchy_table(
+        self,
+        table: impl Into<String>,
+        child_column: impl Into<String>,
+        ancestor_column: impl Into<String>,
+    ) -> Self {
+        Self {
+            hierarchy_table: table.into(),
+            child_column: child_column.into(),
+            ancestor_column: ancestor_column.into(),
+            ..self
+        }
+    }
+
+    /// Render the attribute path `path` (as written in Cedar, e.g.
+    /// `resource.owner`) as the SQL expression `sql`, which is used verbatim
+    pub fn with_column(mut self, path: impl Into<String>, sql: impl Into<String>) -> Self {
+        self.columns.insert(path.into(), sql.into());
+        self
+    }
+
+    /// Render `filter` as a SQL boolean expression
+    pub fn render(&self, filter: &Filter) -> Result<String, FilterError> {
+        match filter {
+            Filter::Const(true) => Ok("TRUE".into()),
+            Filter::Const(false) => Ok("FALSE".into()),
+            Filter::And(fs) => self.render_all(fs, " AND "),
+            Filter::Or(fs) => self.render_all(fs, " OR "),
+            Filter::Not(f) => Ok(format!("NOT ({})", self.render(f)?)),
+            Filter::IsTrue(f) => Ok(format!("({}) IS TRUE", self.render(f)?)),
+            Filter::Compare { op, left, right } => {
+                let op = match op {
+                    CompareOp::Eq => "=",
+                    CompareOp::Less => "<",
+                    CompareOp::LessEq => "<=",
+                };
+                Ok(format!(
+                    "{} {op} {}",
+                    self.operand(left)?,
+                    self.operand(right)?
+                ))
+            }
+            Filter::Set {
+                op: SetOp::Contains,
+                left: Operand::Literal(Literal::Set(elems)),
+                right,
+            } => {
+                if elems.is_empty() {
+                    Ok("FALSE".into())
+                } else {
+                    Ok(format!(
+                        "{} IN ({})",
+                        self.operand(right)?,
+                        elems
+                            .iter()
+                            .map(literal)
+                            .collect::<Result<Vec<_>, _>>()?
+                            .join(", ")
+                    ))
+                }
+            }
+            Filter::Set { .. } => Err(FilterError::UnsupportedBySql {
+                construct: "a set-valued attribute".into(),
+                filter: filter.to_string(),
+            }),
+            Filter::In { entity, ancestor } => {
+                let (entity, ancestor) = (self.operand(entity)?, self.operand(ancestor)?);
+                Ok(format!(
+                    "({entity} = {ancestor} OR EXISTS (SELECT 1 FROM {table} WHERE {table}.{child} = {entity} AND {table}.{anc} = {ancestor}))",
+                    table = identifier(&self.hierarchy_table),
+                    child = identifier(&self.child_column),
+                    anc = identifier(&self.ancestor_column),
+                ))
+            }
+            Filter::Has(path) => Ok(format!("{} IS NOT NULL", self.column(path))),
+            Filter::Is {
+                entity,
+                entity_type,
+            } => {
+                let prefix = format!("{entity_type}::\"");
+                Ok(format!(
+                    "SUBSTR({}, 1, {}) = {}",
+                    self.operand(entity)?,
+                    prefix.chars().count(),
+                    string(&prefix)
+                ))
+            }
+        }
+    }
+
+    fn render_all(&self, filters: &[Filter], sep: &str) -> Result<String, FilterError> {
+        Ok(format!(
+            "({})",
+            filters
+                .iter()
+                .map(|f| self.render(f))
+                .collect::<Result<Vec<_>, _>>()?
+                .join(sep)
+        ))
+    }
+
+    fn operand(&self, operand: &Operand) -> Result<String, FilterError> {
+        match operand {
+            Operand::Attribute(path) => Ok(self.column(path)),
+            Operand::Literal(lit) => literal(lit),
+        }
+    }
+
+    fn column(&self, path: &AttributePath) -> String {
+        if let Some(sql) = self.columns.get(&path.to_string()) {
+            return sql.clone();
+        }
+        let column = if path.attrs.is_empty() {
+            identifier(&self.uid_column)
+        } else {
+            identifier(&path.attrs.iter().join("."))
+        };
+        format!("{}.{column}", identifier(&path.root))
+    }
+}
+
+impl Filter {
+    /// Render this filter as a SQL boolean expression, using the default
+    /// layout described in [`SqlRenderer`]
+    pub fn to_sql(&self) -> Result<String, FilterError> {
+        SqlRenderer::new().render(self)
+    }
+}
+
+fn literal(lit: &Literal) -> Result<String, FilterError> {
+    match lit {
+        Literal::Bool(true) => Ok("TRUE".into()),
+        Literal::Bool(false) => Ok("FALSE".into()),
+        Literal::Long(i) => Ok(i.to_string()),
+        Literal::String(s) => Ok(string(s)),
+        Literal::Entity { .. } => Ok(string(&lit.to_string())),
+        Literal::Set(_) => Err(FilterError::UnsupportedBySql {
+            construct: "a set outside of `.contains()`".into(),
+            filter: lit.to_string(),
+        }),
+    }
+}
+
+/// Quote `s` as a SQL string literal
+fn string(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// Quote `s` as a SQL identifier
+fn identifier(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::parse_expr;
+    use cool_asserts::assert_matches;
+
+    fn sql(renderer: &SqlRenderer, src: &str) -> Result<String, FilterError> {
+        renderer.render(&Filter::from_expr(&parse_expr(src).unwrap()).unwrap())
+    }
+
+    #[test]
+    fn renders_filters() {
+        let renderer = SqlRenderer::new();
+        assert_eq!(
+            sql(
+                &renderer,
+                r#"resource.owner == User::"alice" || resource.size <= 10"#
+            )
+            .unwrap(),
+            r#"("resource"."owner" = 'User::"alice"' OR (NOT ("resource"."owner" = 'User::"alice"') AND "resource"."size" <= 10))"#
+        );
+        assert_eq!(
+            sql(&renderer, r#"["a", "b's"].contains(resource.address.city)"#).unwrap(),
+            r#""resource"."address.city" IN ('a', 'b''s')"#
+        );
+        assert_eq!(
+            sql(
+                &renderer,
+                r#"resource in Folder::"f" && !(resource has name)"#
+            )
+            .unwrap(),
+            r#"(("resource"."uid" = 'Folder::"f"' OR EXISTS (SELECT 1 FROM "cedar_hierarchy" WHERE "cedar_hierarchy"."child" = "resource"."uid" AND "cedar_hierarchy"."ancestor" = 'Folder::"f"')) AND NOT ("resource"."name" IS NOT NULL))"#
+        );
+        assert_eq!(
+            sql(&renderer, "resource is Doc").unwrap(),
+            r#"SUBSTR("resource"."uid", 1, 6) = 'Doc::"'"#
+        );
+        assert_eq!(
+            Filter::is_true(Filter::from_expr(&parse_expr("resource.public").unwrap()).unwrap())
+                .to_sql()
+                .unwrap(),
+            r#"("resource"."public" = TRUE) IS TRUE"#
+        );
+
+        let renderer = SqlRenderer::new()
+            .with_uid_column("id")
+            .with_hierarchy_table("parents", "entity", "parent")
+            .with_column("resource.owner", "owner_id");
+        assert_eq!(
+            sql(
+                &renderer,
+                r#"resource in Folder::"f" && resource.owner == User::"alice""#
+            )
+            .unwrap(),
+            r#"(("resource"."id" = 'Folder::"f"' OR EXISTS (SELECT 1 FROM "parents" WHERE "parents"."entity" = "resource"."id" AND "parents"."parent" = 'Folder::"f"')) AND owner_id = 'User::"alice"')"#
+        );
+    }
+
+    #[test]
+    fn missing_attributes_fail() {
+        // If `secret` is missing, Cedar fails without looking at `public`,
+        // so the row must not match even if `public` is `TRUE`: the guard
+        // makes the whole condition `NULL` rather than `NULL OR TRUE`
+        assert_eq!(
+            Filter::is_true(
+                Filter::from_expr(&parse_expr("resource.secret || resource.public").unwrap())
+                    .unwrap()
+            )
+            .to_sql()
+            .unwrap(),
+            r#"(("resource"."secret" = TRUE OR (NOT ("resource"."secret" = TRUE) AND "resource"."public" = TRUE))) IS TRUE"#
+        );
+        // Likewise `NOT (NULL AND FALSE)` would be `TRUE`
+        assert_eq!(
+            sql(&SqlRenderer::new(), "!(resource.secret && resource.public)").unwrap(),
+            r#"NOT (("resource"."secret" = TRUE AND (NOT ("resource"."secret" = TRUE) OR "resource"."public" = TRUE)))"#
+        );
+        // and `if` fails when its condition does
+        assert_eq!(
+            sql(
+                &SqlRenderer::new(),
+                "!(if resource.secret then false else resource.public)"
+            )
+            .unwrap(),
+            r#"NOT (((NOT ("resource"."secret" = TRUE) AND "resource"."public" = TRUE) OR ("resource"."secret" = TRUE AND NOT ("resource"."secret" = TRUE))))"#
+        );
+    }
+
+    #[test]
+    fn reports_unsupported_filters() {
+        assert_matches!(
+            sql(&SqlRenderer::new(), r#"resource.tags.contains("x")"#),
+            Err(FilterError::UnsupportedBySql { .. })
+        );
+        assert_matches!(
+            sql(&SqlRenderer::new(), r#"resource.tags == ["x"]"#),
+            Err(FilterError::UnsupportedBySql { .. })
+        );
+    }
+}
@@ -29,6 +29,7 @@ pub mod entities;
 pub mod est;
 pub mod evaluator;
 pub mod extensions;
+pub mod filter;
 pub mod jsonvalue;
 pub mod parser;
 pub mod transitive_closure;
@@ -534,6 +534,14 @@ impl ast::Id {
                 .map(|arg| construct_method_contains_any(e, arg, loc.clone()))
                 .map_err(|err| errs.push(err))
                 .ok(),
+            "getTag" => extract_single_argument(args.into_iter(), "getTag", loc)
+                .map(|arg| construct_method_get_tag(e, arg, loc.clone()))
+                .map_err(|err| errs.push(err))
+                .ok(),
+            "hasTag" => extract_single_argument(args.into_iter(), "hasTag", loc)
+                .map(|arg| construct_method_has_tag(e, arg, loc.clone()))
+                .map_err(|err| errs.push(err))
+                .ok(),
             id => {
                 if EXTENSION_STYLES.methods.contains(&id) {
                     args.insert(0, e);
@@ -2183,7 +2191,10 @@ impl ast::Name {
         if self.path.is_empty() {
             let id = self.id.as_ref();
             if EXTENSION_STYLES.methods.contains(id)
-                || matches!(id, "contains" | "containsAll" | "containsAny")
+                || matches!(
+                    id,
+                    "contains" | "containsAll" | "containsAny" | "getTag" | "hasTag"
+                )
             {
                 errs.push(ToASTError::new(
                     ToASTErrorKind::FunctionCallOnMethod(self.id),
@@ -2498,6 +2509,12 @@ fn construct_method_contains_any(e0: ast::Expr, e1: ast::Expr, loc: Loc) -> ast:
         .with_source_loc(loc)
         .contains_any(e0, e1)
 }
+fn construct_method_get_tag(e0: ast::Expr, e1: ast::Expr, loc: Loc) -> ast::Expr {
+    ast::ExprBuilder::new().with_source_loc(loc).get_tag(e0, e1)
+}
+fn construct_method_has_tag(e0: ast::Expr, e1: ast::Expr, loc: Loc) -> ast::Expr {
+    ast::ExprBuilder::new().with_source_loc(loc).has_tag(e0, e1)
+}
 
 // INVARIANT (MethodStyleArgs), args must be non-empty
 fn construct_ext_meth(n: String, args: Vec<ast::Expr>, loc: Loc) -> ast::Expr {
@@ -4936,6 +4953,21 @@ mod tests {
                 .exactly_one_underline("[].containsAny(1, 2)")
                 .build(),
             ),
+            (
+                r#"hasTag(principal, "foo")"#,
+                ExpectedErrorMessageBuilder::error("`hasTag` is a method, not a function")
+                    .help("use a method-style call: `e.hasTag(..)`")
+                    .exactly_one_underline(r#"hasTag(principal, "foo")"#)
+                    .build(),
+            ),
+            (
+                r#"principal.getTag()"#,
+                ExpectedErrorMessageBuilder::error(
+                    "call to `getTag` requires exactly 1 argument, but got 0 arguments",
+                )
+                .exactly_one_underline("principal.getTag()")
+                .build(),
+            ),
             (
                 r#""1.1.1.1".ip()"#,
                 ExpectedErrorMessageBuilder::error("`ip` is a function, not a method")
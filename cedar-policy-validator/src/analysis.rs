@@ -0,0 +1,724 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Symbolic analysis of policies: deciding whether two policy sets make the
+//! same decisions, whether one policy applies whenever another does, and
+//! whether a `forbid` policy is redundant or shadows every `permit`.
+//!
+//! For each type of request the schema allows, the policies are partially
+//! evaluated with an unknown principal, resource, and context, and the
+//! residuals are translated into [`Filter`]s. A question about the policies
+//! becomes a formula over those filters that holds exactly on the requests
+//! that answer it negatively, e.g. those on which two policy sets make
+//! different decisions. A bundled solver then searches for a request
+//! satisfying the formula. Any request it finds is checked by authorizing
+//! it, so counterexamples are always genuine.
+//!
+//! The analysis considers requests that conform to the schema, together with
+//! entity data that conforms to the schema and contains every entity whose
+//! attributes the policies read. Under those assumptions, policies that pass
+//! strict validation can't fail to evaluate, except for overflows and
+//! extension function errors, and policies using arithmetic or extension
+//! functions are not supported.
+//!
+//! The solver is incomplete: when it can neither rule out every request nor
+//! build a concrete counterexample, the result is
+//! [`AnalysisOutcome::Unknown`].
+
+use crate::entity_manifest::request_types;
+use crate::{
+    ValidationMode, ValidationResult, ValidationWarning, ValidationWarningKind, Validator,
+    ValidatorSchema,
+};
+use cedar_policy_core::ast::{
+    Annotations, Effect, EntityType, EntityUIDEntry, Expr, ExprKind, Literal, Policy, PolicyID,
+    PolicySet, Request, StaticPolicy,
+};
+use cedar_policy_core::authorizer::{Authorizer, PartialResponse};
+use cedar_policy_core::entities::Entities;
+use cedar_policy_core::filter::{Filter, FilterError};
+use miette::Diagnostic;
+use thiserror::Error;
+
+mod formula;
+use formula::{Formula, Typing};
+mod model;
+mod solver;
+use solver::Outcome;
+
+/// Answers questions about policies, for requests allowed by a schema
+#[derive(Debug)]
+pub struct PolicyAnalyzer {
+    validator: Validator,
+}
+
+/// The answer to a question about policies
+#[derive(Debug, Clone)]
+pub enum AnalysisOutcome {
+    /// The property holds for every request
+    Holds,
+    /// The property fails for the request in the counterexample
+    Fails(Box<Counterexample>),
+    /// The analysis could not decide whether the property holds
+    Unknown,
+}
+
+/// A request, with entity data, on which a property fails
+#[derive(Debug, Clone)]
+pub struct Counterexample {
+    /// The request
+    pub request: Request,
+    /// The entity data, including the schema's action entities
+    pub entities: Entities,
+}
+
+/// Errors analyzing policies
+#[derive(Debug, Diagnostic, Error)]
+pub enum AnalysisError {
+    /// The policies must pass strict validation against the schema
+    #[error("policies must pass strict validation to be analyzed")]
+    Validation(ValidationResult),
+    /// A policy uses a construct the analysis doesn't support
+    #[error("policy `{policy}` cannot be analyzed: {source}")]
+    Unsupported {
+        /// The policy
+        policy: PolicyID,
+        /// The construct that isn't supported
+        source: FilterError,
+    },
+    /// The policy is not in the policy set
+    #[error("policy `{0}` is not in the policy set")]
+    MissingPolicy(PolicyID),
+    /// The policy is not a `forbid` policy
+    #[error("policy `{0}` is not a forbid policy")]
+    NotAForbid(PolicyID),
+}
+
+/// A condition on the policies that apply to a request. The analysis looks
+/// for requests on which the goal holds.
+#[derive(Debug, Clone)]
+enum Goal {
+    /// The policy with this id, in the policy set with this index, applies
+    Applies(usize, PolicyID),
+    /// The policy set with this index allows the request
+    Allowed(usize),
+    Not(Box<Goal>),
+    And(Vec<Goal>),
+    Or(Vec<Goal>),
+}
+
+impl PolicyAnalyzer {
+    /// Create an analyzer for requests allowed by `schema`
+    pub fn new(schema: ValidatorSchema) -> Self {
+        Self {
+            validator: Validator::new(schema),
+        }
+    }
+
+    /// Do `first` and `second` make the same decision on every request?
+    ///
+    /// A counterexample is a request that one allows and the other denies.
+    pub fn equivalent(
+        &self,
+        first: &PolicySet,
+        second: &PolicySet,
+    ) -> Result<AnalysisOutcome, AnalysisError> {
+        self.check(&[first, second], &Goal::differ(0, 1))
+    }
+
+    /// Does `general` apply to every request `specific` applies to?
+    ///
+    /// A counterexample is a request `specific` applies to and `general`
+    /// doesn't.
+    pub fn subsumes(
+        &self,
+        general: &Policy,
+        specific: &Policy,
+    ) -> Result<AnalysisOutcome, AnalysisError> {
+        self.check(
+            &[&singleton(general), &singleton(specific)],
+            &Goal::And(vec![
+                Goal::Applies(1, specific.id().clone()),
+                Goal::Not(Box::new(Goal::Applies(0, general.id().clone()))),
+            ]),
+        )
+    }
+
+    /// Does removing the `forbid` policy `forbid` from `policies` leave
+    /// every decision unchanged?
+    ///
+    /// A counterexample is a request that `policies` denies only because of
+    /// `forbid`.
+    pub fn forbid_is_redundant(
+        &self,
+        policies: &PolicySet,
+        forbid: &PolicyID,
+    ) -> Result<AnalysisOutcome, AnalysisError> {
+        check_forbid(policies, forbid)?;
+        let mut without = PolicySet::new();
+        for policy in policies.policies().filter(|p| p.id() != forbid) {
+            // PANIC SAFETY: the policies come from a valid policy set, so
+            // their ids and templates don't conflict
+            #[allow(clippy::expect_used)]
+            without
+                .add(policy.clone())
+                .expect("policies from a valid policy set should not conflict");
+        }
+        self.check(&[policies, &without], &Goal::differ(0, 1))
+    }
+
+    /// Does the `forbid` policy `forbid` apply to every request any `permit`
+    /// policy in `policies` applies to? If so, `policies` allows nothing.
+    ///
+    /// A counterexample is a request some `permit` applies to and `forbid`
+    /// doesn't.
+    pub fn forbid_shadows_permits(
+        &self,
+        policies: &PolicySet,
+        forbid: &PolicyID,
+    ) -> Result<AnalysisOutcome, AnalysisError> {
+        check_forbid(policies, forbid)?;
+        let permits = policies
+            .policies()
+            .filter(|p| p.effect() == Effect::Permit)
+            .map(|p| Goal::Applies(0, p.id().clone()))
+            .collect();
+        self.check(
+            &[policies],
+            &Goal::And(vec![
+                Goal::Or(permits),
+                Goal::Not(Box::new(Goal::Applies(0, forbid.clone()))),
+            ]),
+        )
+    }
+
+    /// Look for a request on which `goal` holds, for the policy sets `sets`
+    fn check(&self, sets: &[&PolicySet], goal: &Goal) -> Result<AnalysisOutcome, AnalysisError> {
+        for set in sets {
+            let result = self.validator.validate(set, ValidationMode::Strict);
+            if !result.validation_passed() {
+                return Err(AnalysisError::Validation(result));
+            }
+        }
+        find_request(&self.validator.schema, sets, goal)
+    }
+}
+
+/// Warnings about static policies in `policies`, which must pass strict
+/// validation against `schema`: policies that apply to no request, unless
+/// `existing` already reports them, and policies whose `when` and `unless`
+/// clauses hold on every request their scope matches.
+pub(crate) fn policy_warnings(
+    schema: &ValidatorSchema,
+    policies: &PolicySet,
+    existing: &[ValidationWarning],
+) -> Vec<ValidationWarning> {
+    let mut warnings = Vec::new();
+    for policy in policies.policies().filter(|p| p.is_static()) {
+        let id = policy.id();
+        let warning =
+            |kind| ValidationWarning::with_policy_id(id.clone(), policy.loc().clone(), kind);
+        let impossible = warning(ValidationWarningKind::ImpossiblePolicy);
+        let set = singleton(policy);
+        match find_request(schema, &[&set], &Goal::Applies(0, id.clone())) {
+            Ok(AnalysisOutcome::Holds) => {
+                if !existing.contains(&impossible) {
+                    warnings.push(impossible);
+                }
+                continue;
+            }
+            Ok(AnalysisOutcome::Fails(_)) => (),
+            // nothing to report for policies the analysis can't handle
+            Ok(AnalysisOutcome::Unknown) | Err(_) => continue,
+        }
+        if matches!(
+            policy.non_scope_constraints().expr_kind(),
+            ExprKind::Lit(Literal::Bool(true))
+        ) {
+            continue;
+        }
+        // PANIC SAFETY: the components come from a static policy, so have no slots
+        #[allow(clippy::expect_used)]
+        let scope = StaticPolicy::new(
+            id.clone(),
+            None,
+            Annotations::new(),
+            policy.effect(),
+            policy.principal_constraint(),
+            policy.action_constraint().clone(),
+            policy.resource_constraint(),
+            Expr::val(true),
+        )
+        .expect("the scope of a static policy should not contain slots");
+        let scope = singleton(&scope.into());
+        let goal = Goal::And(vec![
+            Goal::Applies(0, id.clone()),
+            Goal::Not(Box::new(Goal::Applies(1, id.clone()))),
+        ]);
+        if let Ok(AnalysisOutcome::Holds) = find_request(schema, &[&scope, &set], &goal) {
+            warnings.push(warning(ValidationWarningKind::TautologicalCondition));
+        }
+    }
+    warnings
+}
+
+/// Look for a request on which `goal` holds, for the policy sets `sets`,
+/// which must pass strict validation against `schema`
+fn find_request(
+    schema: &ValidatorSchema,
+    sets: &[&PolicySet],
+    goal: &Goal,
+) -> Result<AnalysisOutcome, AnalysisError> {
+    // PANIC SAFETY: the action entities of a schema have distinct ids
+    #[allow(clippy::expect_used)]
+    let actions = schema
+        .action_entities()
+        .expect("action entities of a schema should be valid")
+        .partial();
+    let authorizer = Authorizer::new();
+    let mut unknown = false;
+    for request_type in request_types(schema) {
+        let request = Request::new_unchecked(
+            EntityUIDEntry::Unknown { loc: None },
+            EntityUIDEntry::concrete(request_type.action.clone(), None),
+            EntityUIDEntry::Unknown { loc: None },
+            None,
+        );
+        let responses = sets
+            .iter()
+            .map(|set| authorizer.is_authorized_core(request.clone(), set, &actions))
+            .collect::<Vec<_>>();
+        let filter = goal.to_filter(sets, &responses)?;
+        if filter == Filter::Const(false) {
+            continue;
+        }
+        let (EntityType::Specified(_), EntityType::Specified(_)) =
+            (&request_type.principal, &request_type.resource)
+        else {
+            // requests without a principal or resource type aren't
+            // supported
+            unknown = true;
+            continue;
+        };
+        let (Some(formula), Some(typing)) = (
+            Formula::from_filter(&filter),
+            Typing::new(
+                schema,
+                request_type.principal,
+                request_type.action,
+                request_type.resource,
+            ),
+        ) else {
+            unknown = true;
+            continue;
+        };
+        let outcome = solver::search(&formula, &typing, |theory| {
+            let model = model::build(theory)?;
+            let responses = sets
+                .iter()
+                .map(|set| {
+                    authorizer.is_authorized_core(model.request.clone(), set, &model.entities)
+                })
+                .collect::<Vec<_>>();
+            match goal.to_filter(sets, &responses) {
+                Ok(Filter::Const(true)) => Some(Counterexample {
+                    request: model.request,
+                    entities: model.entities,
+                }),
+                _ => None,
+            }
+        });
+        match outcome {
+            Outcome::Found(counterexample) => {
+                return Ok(AnalysisOutcome::Fails(Box::new(counterexample)))
+            }
+            Outcome::Unsat => (),
+            Outcome::Unknown => unknown = true,
+        }
+    }
+    Ok(if unknown {
+        AnalysisOutcome::Unknown
+    } else {
+        AnalysisOutcome::Holds
+    })
+}
+
+impl Goal {
+    /// The policy sets with indices `first` and `second` make different
+    /// decisions
+    fn differ(first: usize, second: usize) -> Self {
+        let (first, second) = (Self::Allowed(first), Self::Allowed(second));
+        Self::Or(vec![
+            Self::And(vec![first.clone(), Self::Not(Box::new(second.clone()))]),
+            Self::And(vec![Self::Not(Box::new(first)), second]),
+        ])
+    }
+
+    /// The filter that holds when the goal does, given the responses of the
+    /// policy sets `sets` to a (partial) request. For a concrete request,
+    /// this is a constant.
+    // PANIC SAFETY: goals only refer to sets by their index in `sets`, and
+    // `responses` has one response per set
+    #[allow(clippy::indexing_slicing)]
+    fn to_filter(
+        &self,
+        sets: &[&PolicySet],
+        responses: &[PartialResponse],
+    ) -> Result<Filter, AnalysisError> {
+        let all = |goals: &[Self]| {
+            goals
+                .iter()
+                .map(|goal| goal.to_filter(sets, responses))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(match self {
+            Self::Applies(set, policy) => applies(&responses[*set], policy)?,
+            Self::Allowed(set) => {
+                let with_effect = |effect| {
+                    sets[*set]
+                        .policies()
+                        .filter(move |p| p.effect() == effect)
+                        .map(|p| applies(&responses[*set], p.id()))
+                        .collect::<Result<Vec<_>, _>>()
+                };
+                Filter::and([
+                    Filter::or(with_effect(Effect::Permit)?),
+                    Filter::negate(Filter::or(with_effect(Effect::Forbid)?)),
+                ])
+            }
+            Self::Not(goal) => Filter::negate(goal.to_filter(sets, responses)?),
+            Self::And(goals) => Filter::and(all(goals)?),
+            Self::Or(goals) => Filter::or(all(goals)?),
+        })
+    }
+}
+
+/// The filter that holds when policy `id` applies, given a response
+fn applies(response: &PartialResponse, id: &PolicyID) -> Result<Filter, AnalysisError> {
+    if response.satisfied_permits.contains_key(id) || response.satisfied_forbids.contains_key(id) {
+        return Ok(Filter::Const(true));
+    }
+    match response
+        .residual_permits
+        .get(id)
+        .or_else(|| response.residual_forbids.get(id))
+    {
+        Some((residual, _)) => Filter::from_expr(residual)
+            .map(Filter::is_true)
+            .map_err(|source| AnalysisError::Unsupported {
+                policy: id.clone(),
+                source,
+            }),
+        // the policy evaluated to `false` or failed to evaluate
+        None => Ok(Filter::Const(false)),
+    }
+}
+
+/// A policy set containing just `policy`
+fn singleton(policy: &Policy) -> PolicySet {
+    let mut set = PolicySet::new();
+    // PANIC SAFETY: adding a policy to an empty set can't conflict
+    #[allow(clippy::expect_used)]
+    set.add(policy.clone())
+        .expect("adding a policy to an empty policy set should not fail");
+    set
+}
+
+/// Check that `forbid` is a `forbid` policy in `policies`
+fn check_forbid(policies: &PolicySet, forbid: &PolicyID) -> Result<(), AnalysisError> {
+    match policies.get(forbid) {
+        Some(policy) if policy.effect() == Effect::Forbid => Ok(()),
+        Some(_) => Err(AnalysisError::NotAForbid(forbid.clone())),
+        None => Err(AnalysisError::MissingPolicy(forbid.clone())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cedar_policy_core::authorizer::Decision;
+    use cedar_policy_core::parser::{parse_policy, parse_policyset};
+    use cool_asserts::assert_matches;
+
+    fn schema() -> ValidatorSchema {
+        ValidatorSchema::from_str_natural(
+            r#"
+            entity Group;
+            entity User in [Group] {
+                level: Long,
+                department: String,
+                nickname?: String,
+            };
+            entity Document {
+                owner: User,
+                public: Bool,
+                tags: Set<String>,
+            };
+            action view, edit appliesTo {
+                principal: [User],
+                resource: [Document],
+                context: { authenticated: Bool }
+            };
+            "#,
+            cedar_policy_core::extensions::Extensions::all_available(),
+        )
+        .unwrap()
+        .0
+    }
+
+    fn analyzer() -> PolicyAnalyzer {
+        PolicyAnalyzer::new(schema())
+    }
+
+    fn policies(src: &str) -> PolicySet {
+        parse_policyset(src).unwrap()
+    }
+
+    /// Check that the sets really decide the counterexample differently
+    fn assert_differ(first: &PolicySet, second: &PolicySet, counterexample: &Counterexample) {
+        let authorizer = Authorizer::new();
+        let decide = |set| {
+            authorizer
+                .is_authorized(
+                    counterexample.request.clone(),
+                    set,
+                    &counterexample.entities,
+                )
+                .decision
+        };
+        assert_ne!(decide(first), decide(second));
+    }
+
+    #[test]
+    fn equivalent_refactors() {
+        let analyzer = analyzer();
+        let first = policies(
+            r#"
+            permit(principal, action == Action::"view", resource)
+            when { principal.level > 3 && resource.owner == principal };
+            permit(principal in Group::"admins", action in [Action::"view", Action::"edit"], resource)
+            unless { !context.authenticated };
+            "#,
+        );
+        let second = policies(
+            r#"
+            permit(principal, action == Action::"view", resource)
+            when { resource.owner == principal && 4 <= principal.level };
+            permit(principal in Group::"admins", action == Action::"view", resource)
+            when { context.authenticated };
+            permit(principal in Group::"admins", action == Action::"edit", resource)
+            when { context.authenticated == true };
+            "#,
+        );
+        assert_matches!(
+            analyzer.equivalent(&first, &second),
+            Ok(AnalysisOutcome::Holds)
+        );
+    }
+
+    #[test]
+    fn counterexample() {
+        let analyzer = analyzer();
+        let first = policies(
+            r#"
+            permit(principal, action, resource)
+            when { principal.level > 3 && resource.tags.contains("shared") };
+            forbid(principal, action, resource) when { principal has nickname && principal.nickname == "root" };
+            "#,
+        );
+        let second = policies(
+            r#"
+            permit(principal, action, resource)
+            when { principal.level > 4 && resource.tags.contains("shared") };
+            forbid(principal, action, resource) when { principal has nickname && principal.nickname == "root" };
+            "#,
+        );
+        assert_matches!(
+            analyzer.equivalent(&first, &second),
+            Ok(AnalysisOutcome::Fails(counterexample)) => {
+                assert_differ(&first, &second, &counterexample);
+                let authorizer = Authorizer::new();
+                let response = authorizer.is_authorized(
+                    counterexample.request.clone(),
+                    &first,
+                    &counterexample.entities,
+                );
+                assert_eq!(response.decision, Decision::Allow);
+            }
+        );
+    }
+
+    #[test]
+    fn entity_literal_attributes() {
+        let analyzer = analyzer();
+        let first =
+            policies(r#"permit(principal, action, resource) when { User::"root".level > 3 };"#);
+        let second =
+            policies(r#"permit(principal, action, resource) when { 3 < User::"root".level };"#);
+        let third =
+            policies(r#"permit(principal, action, resource) when { User::"root".level > 4 };"#);
+        assert_matches!(
+            analyzer.equivalent(&first, &second),
+            Ok(AnalysisOutcome::Holds)
+        );
+        assert_matches!(
+            analyzer.equivalent(&first, &third),
+            Ok(AnalysisOutcome::Fails(counterexample)) => {
+                assert_differ(&first, &third, &counterexample);
+            }
+        );
+    }
+
+    #[test]
+    fn subsumption() {
+        let analyzer = analyzer();
+        let general = Policy::from(
+            parse_policy(
+                Some("general".into()),
+                r#"permit(principal, action, resource) when { principal.level > 2 };"#,
+            )
+            .unwrap(),
+        );
+        let specific = Policy::from(
+            parse_policy(
+                Some("specific".into()),
+                r#"permit(principal, action == Action::"view", resource)
+                when { principal.level > 5 && principal.department == "eng" };"#,
+            )
+            .unwrap(),
+        );
+        assert_matches!(
+            analyzer.subsumes(&general, &specific),
+            Ok(AnalysisOutcome::Holds)
+        );
+        assert_matches!(
+            analyzer.subsumes(&specific, &general),
+            Ok(AnalysisOutcome::Fails(_))
+        );
+    }
+
+    #[test]
+    fn forbids() {
+        let analyzer = analyzer();
+        let set = policies(
+            r#"
+            @id("permit")
+            permit(principal, action == Action::"view", resource) when { principal.level > 3 };
+            @id("redundant")
+            forbid(principal, action, resource) when { principal.level < 2 };
+            @id("shadowing")
+            forbid(principal, action, resource) when { principal.level > 1 };
+            "#,
+        );
+        let id = |id: &str| {
+            set.policies()
+                .find(|p| p.annotation(&"id".parse().unwrap()).map(|a| a.as_ref()) == Some(id))
+                .unwrap()
+                .id()
+                .clone()
+        };
+        assert_matches!(
+            analyzer.forbid_is_redundant(&set, &id("redundant")),
+            Ok(AnalysisOutcome::Holds)
+        );
+        assert_matches!(
+            analyzer.forbid_is_redundant(&set, &id("shadowing")),
+            Ok(AnalysisOutcome::Fails(_))
+        );
+        assert_matches!(
+            analyzer.forbid_shadows_permits(&set, &id("shadowing")),
+            Ok(AnalysisOutcome::Holds)
+        );
+        assert_matches!(
+            analyzer.forbid_shadows_permits(&set, &id("redundant")),
+            Ok(AnalysisOutcome::Fails(_))
+        );
+        assert_matches!(
+            analyzer.forbid_shadows_permits(&set, &id("permit")),
+            Err(AnalysisError::NotAForbid(_))
+        );
+    }
+
+    #[test]
+    fn invalid_policies() {
+        let analyzer = analyzer();
+        let set = policies(r#"permit(principal, action, resource) when { principal.salary > 3 };"#);
+        assert_matches!(
+            analyzer.equivalent(&set, &set),
+            Err(AnalysisError::Validation(_))
+        );
+    }
+
+    #[test]
+    fn validation_warnings() {
+        let set = policies(
+            r#"
+            @id("contradiction")
+            permit(principal, action, resource)
+            when { principal.level > 5 && principal.level < 3 };
+            @id("disjoint")
+            permit(principal, action == Action::"view", resource)
+            when { resource.owner == principal }
+            unless { principal == resource.owner || !resource.public };
+            @id("tautology")
+            permit(principal, action, resource)
+            when { principal.level > 3 || principal.level <= 3 };
+            @id("guarded")
+            forbid(principal, action, resource)
+            when { principal has nickname || !(principal has nickname) };
+            @id("ordinary")
+            permit(principal, action, resource)
+            when { principal.level > 3 && context.authenticated };
+            @id("unconditional")
+            permit(principal, action, resource);
+            "#,
+        );
+        let validator = Validator::new(schema());
+        let result = validator.validate_with_analysis(&set, ValidationMode::Strict);
+        assert!(result.validation_passed());
+        let warned = |kind: ValidationWarningKind| {
+            let mut ids = result
+                .validation_warnings()
+                .filter(|w| *w.kind() == kind)
+                .map(|w| {
+                    set.get(w.location().policy_id())
+                        .unwrap()
+                        .annotation(&"id".parse().unwrap())
+                        .unwrap()
+                        .as_ref()
+                        .to_string()
+                })
+                .collect::<Vec<_>>();
+            ids.sort();
+            ids
+        };
+        assert_eq!(
+            warned(ValidationWarningKind::ImpossiblePolicy),
+            ["contradiction", "disjoint"]
+        );
+        assert_eq!(
+            warned(ValidationWarningKind::TautologicalCondition),
+            ["guarded", "tautology"]
+        );
+
+        // the analysis is opt-in
+        let result = validator.validate(&set, ValidationMode::Strict);
+        assert_eq!(result.validation_warnings().count(), 0);
+        // and only runs with strict validation
+        let result = validator.validate_with_analysis(&set, ValidationMode::Permissive);
+        assert_eq!(result.validation_warnings().count(), 0);
+    }
+}
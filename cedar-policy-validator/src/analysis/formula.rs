@@ -0,0 +1,360 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Formulas the solver works on, translated from [`Filter`]s, and the types
+//! the schema gives to the terms in them.
+
+use crate::{CoreSchema, ValidatorSchema};
+use cedar_policy_core::ast::{EntityType, EntityUID, Var};
+use cedar_policy_core::entities::{
+    AttributeType, ContextSchema, EntityTypeDescription, Schema, SchemaType,
+};
+use cedar_policy_core::filter::{CompareOp, Filter, Literal, Operand, SetOp};
+use smol_str::SmolStr;
+use std::fmt::Display;
+
+/// A value a formula is about: a request variable, a literal, or an
+/// attribute of another term
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Term {
+    Var(Var),
+    Lit(Literal),
+    Attr(Box<Term>, SmolStr),
+}
+
+/// An atomic condition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Atom {
+    Eq(Term, Term),
+    Less(Term, Term),
+    LessEq(Term, Term),
+    /// The set on the left contains the element on the right
+    Contains(Term, Term),
+    /// The set on the left contains every element of the set on the right
+    ContainsAll(Term, Term),
+    /// The sets have an element in common
+    ContainsAny(Term, Term),
+    /// The entity on the left is the one on the right or a descendant of it
+    /// (or of an element of it, for a set)
+    In(Term, Term),
+    /// The attribute exists
+    Has(Term),
+    /// The entity has the given type
+    Is(Term, SmolStr),
+}
+
+/// A formula in negation normal form
+#[derive(Debug, Clone)]
+pub(super) enum Formula {
+    Const(bool),
+    /// The atom, or its negation if the flag is `false`
+    Atom(Atom, bool),
+    And(Vec<Formula>),
+    Or(Vec<Formula>),
+}
+
+impl Formula {
+    /// Translate `filter`. Returns `None` if it refers to an unknown that
+    /// is neither a request variable nor a missing entity.
+    ///
+    /// `IsTrue` is dropped: the filter is assumed to come from policies that
+    /// passed strict validation, evaluated on data where it can't fail.
+    pub(super) fn from_filter(filter: &Filter) -> Option<Self> {
+        Some(match filter {
+            Filter::Const(b) => Self::Const(*b),
+            Filter::And(fs) => Self::and(fs.iter().map(Self::from_filter).collect::<Option<_>>()?),
+            Filter::Or(fs) => Self::or(fs.iter().map(Self::from_filter).collect::<Option<_>>()?),
+            Filter::Not(f) => Self::from_filter(f)?.negate(),
+            Filter::IsTrue(f) => Self::from_filter(f)?,
+            Filter::Compare { op, left, right } => {
+                let (left, right) = (Term::from_operand(left)?, Term::from_operand(right)?);
+                match (op, &left, &right) {
+                    (CompareOp::Eq, Term::Lit(a), Term::Lit(b)) => Self::Const(lit_eq(a, b)),
+                    (
+                        CompareOp::Less | CompareOp::LessEq,
+                        Term::Lit(Literal::Long(a)),
+                        Term::Lit(Literal::Long(b)),
+                    ) => Self::Const(if *op == CompareOp::Less {
+                        a < b
+                    } else {
+                        a <= b
+                    }),
+                    (CompareOp::Eq, _, _) => Self::atom(Atom::Eq(left, right)),
+                    (CompareOp::Less, _, _) => Self::atom(Atom::Less(left, right)),
+                    (CompareOp::LessEq, _, _) => Self::atom(Atom::LessEq(left, right)),
+                }
+            }
+            Filter::Set { op, left, right } => {
+                Self::set(*op, Term::from_operand(left)?, Term::from_operand(right)?)
+            }
+            Filter::In { entity, ancestor } => Self::atom(Atom::In(
+                Term::from_operand(entity)?,
+                Term::from_operand(ancestor)?,
+            )),
+            Filter::Has(path) => Self::atom(Atom::Has(Term::from_operand(&Operand::Attribute(
+                path.clone(),
+            ))?)),
+            Filter::Is {
+                entity,
+                entity_type,
+            } => match Term::from_operand(entity)? {
+                Term::Lit(Literal::Entity {
+                    entity_type: ty, ..
+                }) => Self::Const(ty == *entity_type),
+                entity => Self::atom(Atom::Is(entity, entity_type.clone())),
+            },
+        })
+    }
+
+    /// Set operations, with those involving a set literal expanded into
+    /// (dis)equalities and `Contains` atoms
+    fn set(op: SetOp, left: Term, right: Term) -> Self {
+        let contains = |set: &Term, elems: &[Literal], all: bool| {
+            let atoms = elems
+                .iter()
+                .map(|elem| Self::atom(Atom::Contains(set.clone(), Term::Lit(elem.clone()))));
+            if all {
+                Self::and(atoms.collect())
+            } else {
+                Self::or(atoms.collect())
+            }
+        };
+        match (op, &left, &right) {
+            (SetOp::Contains, Term::Lit(Literal::Set(elems)), Term::Lit(elem)) => {
+                Self::Const(elems.iter().any(|e| lit_eq(e, elem)))
+            }
+            (SetOp::Contains, Term::Lit(Literal::Set(elems)), _) => Self::or(
+                elems
+                    .iter()
+                    .map(|elem| Self::atom(Atom::Eq(right.clone(), Term::Lit(elem.clone()))))
+                    .collect(),
+            ),
+            (SetOp::Contains, _, _) => Self::atom(Atom::Contains(left, right)),
+            (
+                SetOp::ContainsAll | SetOp::ContainsAny,
+                Term::Lit(Literal::Set(lefts)),
+                Term::Lit(Literal::Set(rights)),
+            ) => {
+                let contained = |elem: &Literal| lefts.iter().any(|e| lit_eq(e, elem));
+                Self::Const(if op == SetOp::ContainsAll {
+                    rights.iter().all(contained)
+                } else {
+                    rights.iter().any(contained)
+                })
+            }
+            (SetOp::ContainsAll, _, Term::Lit(Literal::Set(elems))) => contains(&left, elems, true),
+            (SetOp::ContainsAll, _, _) => Self::atom(Atom::ContainsAll(left, right)),
+            (SetOp::ContainsAny, _, Term::Lit(Literal::Set(elems))) => {
+                contains(&left, elems, false)
+            }
+            (SetOp::ContainsAny, Term::Lit(Literal::Set(elems)), _) => {
+                contains(&right, elems, false)
+            }
+            (SetOp::ContainsAny, _, _) => Self::atom(Atom::ContainsAny(left, right)),
+        }
+    }
+
+    fn atom(atom: Atom) -> Self {
+        Self::Atom(atom, true)
+    }
+
+    fn and(formulas: Vec<Self>) -> Self {
+        let mut conjuncts = Vec::new();
+        for formula in formulas {
+            match formula {
+                Self::Const(true) => (),
+                Self::Const(false) => return Self::Const(false),
+                Self::And(fs) => conjuncts.extend(fs),
+                f => conjuncts.push(f),
+            }
+        }
+        match conjuncts.len() {
+            0 => Self::Const(true),
+            1 => conjuncts.remove(0),
+            _ => Self::And(conjuncts),
+        }
+    }
+
+    fn or(formulas: Vec<Self>) -> Self {
+        let mut disjuncts = Vec::new();
+        for formula in formulas {
+            match formula {
+                Self::Const(false) => (),
+                Self::Const(true) => return Self::Const(true),
+                Self::Or(fs) => disjuncts.extend(fs),
+                f => disjuncts.push(f),
+            }
+        }
+        match disjuncts.len() {
+            0 => Self::Const(false),
+            1 => disjuncts.remove(0),
+            _ => Self::Or(disjuncts),
+        }
+    }
+
+    fn negate(self) -> Self {
+        match self {
+            Self::Const(b) => Self::Const(!b),
+            Self::Atom(atom, positive) => Self::Atom(atom, !positive),
+            Self::And(fs) => Self::or(fs.into_iter().map(Self::negate).collect()),
+            Self::Or(fs) => Self::and(fs.into_iter().map(Self::negate).collect()),
+        }
+    }
+}
+
+impl Term {
+    /// Translate an operand. Unknowns for missing entities become entity
+    /// literals, and those for request variables become variables.
+    fn from_operand(operand: &Operand) -> Option<Self> {
+        match operand {
+            Operand::Literal(lit) => Some(Self::Lit(lit.clone())),
+            Operand::Attribute(path) => {
+                let root = match (&path.entity, path.root.as_str()) {
+                    (Some(uid), _) => Self::Lit(Literal::Entity {
+                        entity_type: uid.entity_type().to_string().into(),
+                        id: AsRef::<str>::as_ref(uid.eid()).into(),
+                    }),
+                    (None, "principal") => Self::Var(Var::Principal),
+                    (None, "resource") => Self::Var(Var::Resource),
+                    (None, "context") => Self::Var(Var::Context),
+                    (None, _) => return None,
+                };
+                Some(
+                    path.attrs
+                        .iter()
+                        .fold(root, |term, attr| Self::Attr(Box::new(term), attr.clone())),
+                )
+            }
+        }
+    }
+}
+
+impl Display for Term {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Var(var) => write!(f, "{var}"),
+            Self::Lit(lit) => write!(f, "{lit}"),
+            Self::Attr(term, attr) => write!(f, "{term}.{attr}"),
+        }
+    }
+}
+
+/// Equality of literal values, where sets are equal if they have the same
+/// elements
+pub(super) fn lit_eq(a: &Literal, b: &Literal) -> bool {
+    match (a, b) {
+        (Literal::Set(xs), Literal::Set(ys)) => {
+            xs.iter().all(|x| ys.iter().any(|y| lit_eq(x, y)))
+                && ys.iter().all(|y| xs.iter().any(|x| lit_eq(x, y)))
+        }
+        (a, b) => a == b,
+    }
+}
+
+/// The schema types of the terms in requests of one type
+pub(super) struct Typing<'a> {
+    pub(super) schema: &'a ValidatorSchema,
+    pub(super) core: CoreSchema<'a>,
+    pub(super) principal: EntityType,
+    pub(super) action: EntityUID,
+    pub(super) resource: EntityType,
+    context: SchemaType,
+}
+
+impl<'a> Typing<'a> {
+    /// Typing for requests with the given principal type, action, and
+    /// resource type. `None` if the action isn't in the schema.
+    pub(super) fn new(
+        schema: &'a ValidatorSchema,
+        principal: EntityType,
+        action: EntityUID,
+        resource: EntityType,
+    ) -> Option<Self> {
+        let context = crate::context_schema_for_action(schema, &action)?.context_type();
+        Some(Self {
+            schema,
+            core: CoreSchema::new(schema),
+            principal,
+            action,
+            resource,
+            context,
+        })
+    }
+
+    /// The type of `term`, if the schema gives it one
+    pub(super) fn type_of(&self, term: &Term) -> Option<SchemaType> {
+        match term {
+            Term::Var(Var::Principal) => Some(SchemaType::Entity {
+                ty: self.principal.clone(),
+            }),
+            Term::Var(Var::Resource) => Some(SchemaType::Entity {
+                ty: self.resource.clone(),
+            }),
+            Term::Var(Var::Context) => Some(self.context.clone()),
+            Term::Var(Var::Action) => None,
+            Term::Lit(Literal::Entity { entity_type, .. }) => Some(SchemaType::Entity {
+                ty: EntityType::Specified(entity_type.parse().ok()?),
+            }),
+            Term::Lit(Literal::Bool(_)) => Some(SchemaType::Bool),
+            Term::Lit(Literal::Long(_)) => Some(SchemaType::Long),
+            Term::Lit(Literal::String(_)) => Some(SchemaType::String),
+            Term::Lit(Literal::Set(_)) => None,
+            Term::Attr(parent, attr) => self
+                .attr_type(&self.type_of(parent)?, attr)
+                .map(|ty| ty.schema_type().clone()),
+        }
+    }
+
+    /// Is `term` an attribute that every value of its parent's type has?
+    pub(super) fn is_required(&self, term: &Term) -> bool {
+        match term {
+            Term::Attr(parent, attr) => self
+                .type_of(parent)
+                .and_then(|ty| self.attr_type(&ty, attr))
+                .is_some_and(|ty| ty.is_required()),
+            Term::Var(_) | Term::Lit(_) => false,
+        }
+    }
+
+    /// The type of attribute `attr` of values of type `ty`
+    pub(super) fn attr_type(&self, ty: &SchemaType, attr: &str) -> Option<AttributeType> {
+        match ty {
+            SchemaType::Record { attrs, .. } => attrs.get(attr).cloned(),
+            SchemaType::Entity { ty } => {
+                let description = self.core.entity_type(ty)?;
+                let attr_type = description.attr_type(attr)?;
+                Some(if description.required_attrs().any(|a| a == attr) {
+                    AttributeType::required(attr_type)
+                } else {
+                    AttributeType::optional(attr_type)
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Can an entity of type `ancestor` be an ancestor of one of type
+    /// `descendant`?
+    pub(super) fn may_be_ancestor(&self, descendant: &EntityType, ancestor: &EntityType) -> bool {
+        match (descendant, ancestor) {
+            (EntityType::Specified(descendant), EntityType::Specified(ancestor)) => self
+                .schema
+                .get_entity_type(ancestor)
+                .is_some_and(|ty| ty.descendants.contains(descendant)),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,446 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Building a concrete request and entities from a consistent branch of the
+//! search.
+//!
+//! Each class of equal terms gets one value: its literal if it has one, and
+//! otherwise a value chosen to satisfy the branch's atoms where that's easy
+//! (fresh entities and strings, integers solving the comparisons, sets
+//! containing what the branch says they contain). Nothing here guarantees
+//! the result satisfies every atom, so callers check the model by
+//! evaluating the policies on it.
+
+use super::formula::Term;
+use super::solver::{Difference, NodeAtom, Theory};
+use cedar_policy_core::ast::{
+    Context, Eid, Entity, EntityType, EntityUID, Name, Request, RestrictedExpr, Var,
+};
+use cedar_policy_core::entities::{
+    AttributeType, Entities, EntityTypeDescription, Schema, SchemaType, TCComputation,
+};
+use cedar_policy_core::extensions::Extensions;
+use cedar_policy_core::filter::Literal;
+use smol_str::SmolStr;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+/// How many times to adjust integer values that come out equal when the
+/// branch says they differ
+const MAX_ADJUSTMENTS: usize = 16;
+
+/// A concrete request, with entity data for it
+pub(super) struct Model {
+    pub(super) request: Request,
+    pub(super) entities: Entities,
+}
+
+/// Try to build a model of the branch `theory` describes
+pub(super) fn build(theory: &Theory<'_>) -> Option<Model> {
+    Builder::new(theory)?.build()
+}
+
+struct Builder<'t, 'a> {
+    theory: &'t Theory<'a>,
+    longs: HashMap<usize, i128>,
+    uids: HashMap<usize, EntityUID>,
+    strings: HashMap<usize, SmolStr>,
+    /// Entities that are literals in the branch or already assigned to a
+    /// class
+    taken_uids: HashSet<EntityUID>,
+    /// Strings that are literals in the branch or already assigned to a
+    /// class
+    taken_strings: HashSet<SmolStr>,
+}
+
+impl<'t, 'a> Builder<'t, 'a> {
+    fn new(theory: &'t Theory<'a>) -> Option<Self> {
+        let mut builder = Self {
+            theory,
+            longs: theory.longs.clone(),
+            uids: HashMap::new(),
+            strings: HashMap::new(),
+            taken_uids: HashSet::new(),
+            taken_strings: HashSet::new(),
+        };
+        for class in theory.all_classes() {
+            match theory.literal(class) {
+                Some(Literal::Entity { entity_type, id }) => {
+                    builder.taken_uids.insert(lit_uid(entity_type, id)?);
+                }
+                Some(Literal::String(s)) => {
+                    builder.taken_strings.insert(s.clone());
+                }
+                _ => (),
+            }
+        }
+        builder.separate_longs()?;
+        Some(builder)
+    }
+
+    /// Adjust the integer values until the ones the branch says differ do
+    fn separate_longs(&mut self) -> Option<()> {
+        let mut extra: Vec<Difference> = Vec::new();
+        for _ in 0..MAX_ADJUSTMENTS {
+            let equal = self
+                .theory
+                .atoms
+                .iter()
+                .find_map(|(atom, positive)| match atom {
+                    NodeAtom::Eq(x, y) if !positive => {
+                        let (x, y) = (self.theory.class(*x), self.theory.class(*y));
+                        let (vx, vy) = (self.long_value(x)?, self.long_value(y)?);
+                        (vx == vy).then_some((x, y))
+                    }
+                    _ => None,
+                });
+            let Some((x, y)) = equal else {
+                return Some(());
+            };
+            extra.push((Some(x), Some(y), -1));
+            self.longs = match self.theory.solve_longs(&extra) {
+                Some(longs) => longs,
+                None => {
+                    extra.pop();
+                    extra.push((Some(y), Some(x), -1));
+                    self.theory.solve_longs(&extra)?
+                }
+            };
+        }
+        None
+    }
+
+    fn long_value(&self, class: usize) -> Option<i128> {
+        match self.theory.literal(class) {
+            Some(Literal::Long(value)) => Some(i128::from(*value)),
+            Some(_) => None,
+            None => match self.theory.type_of(class) {
+                Some(SchemaType::Long) => Some(self.longs.get(&class).copied().unwrap_or(0)),
+                _ => None,
+            },
+        }
+    }
+
+    fn build(mut self) -> Option<Model> {
+        let typing = self.theory.typing;
+        let mut entities = Vec::new();
+        let mut built = HashSet::new();
+        for class in self.theory.all_classes().collect::<Vec<_>>() {
+            if let Some(SchemaType::Entity { ty }) = self.theory.type_of(class) {
+                let uid = self.uid(class, &ty)?;
+                if typing.core.action(&uid).is_none() && built.insert(uid.clone()) {
+                    entities.push(self.entity(Some(class), uid, &ty)?);
+                }
+            }
+        }
+        let mut request_entity = |builder: &mut Self, var: Var, ty: &EntityType| match builder
+            .theory
+            .class_of_term(&Term::Var(var))
+        {
+            Some(class) => builder.uid(class, ty),
+            None => {
+                let uid = builder.fresh_uid(&var.to_string(), ty)?;
+                entities.push(builder.entity(None, uid.clone(), ty)?);
+                Some(uid)
+            }
+        };
+        let principal = request_entity(&mut self, Var::Principal, &typing.principal)?;
+        let resource = request_entity(&mut self, Var::Resource, &typing.resource)?;
+        let SchemaType::Record { attrs, .. } = typing.type_of(&Term::Var(Var::Context))? else {
+            return None;
+        };
+        let context_class = self.theory.class_of_term(&Term::Var(Var::Context));
+        let context = Context::from_pairs(
+            self.record_pairs(context_class, &attrs)?,
+            Extensions::all_available(),
+        )
+        .ok()?;
+        let request = Request::new(
+            (principal, None),
+            (typing.action.clone(), None),
+            (resource, None),
+            context,
+            Some(typing.schema),
+            Extensions::all_available(),
+        )
+        .ok()?;
+        let entities = Entities::from_entities(
+            entities,
+            Some(&typing.core),
+            TCComputation::ComputeNow,
+            Extensions::all_available(),
+        )
+        .ok()?;
+        Some(Model { request, entities })
+    }
+
+    /// The entity `uid` of type `ty`, with the attributes and parents the
+    /// branch gives class `class`, and defaults for its other required
+    /// attributes
+    fn entity(&mut self, class: Option<usize>, uid: EntityUID, ty: &EntityType) -> Option<Entity> {
+        let description = self.theory.typing.core.entity_type(ty)?;
+        let mut attrs = HashMap::new();
+        let mut parents = HashSet::new();
+        if let Some(class) = class {
+            let entity_type = SchemaType::Entity { ty: ty.clone() };
+            for (attr, child) in self.theory.attrs(class) {
+                let attr_type = self.theory.typing.attr_type(&entity_type, &attr)?;
+                if attr_type.is_required() || self.theory.has(class, &attr).unwrap_or(true) {
+                    let value = self.value(child, attr_type.schema_type())?;
+                    attrs.insert(attr, value);
+                }
+            }
+            let theory = self.theory;
+            for (atom, positive) in &theory.atoms {
+                if let (NodeAtom::In(x, y), true) = (atom, positive) {
+                    if self.theory.class(*x) != class {
+                        continue;
+                    }
+                    let ancestor = self.theory.class(*y);
+                    if let Some(SchemaType::Entity { ty: ancestor_ty }) =
+                        self.theory.type_of(ancestor)
+                    {
+                        let parent = self.uid(ancestor, &ancestor_ty)?;
+                        if parent != uid {
+                            if !description.allowed_parent_types().contains(&ancestor_ty) {
+                                return None;
+                            }
+                            parents.insert(parent);
+                        }
+                    }
+                }
+            }
+        }
+        for attr in description.required_attrs().collect::<Vec<_>>() {
+            if let Entry::Vacant(entry) = attrs.entry(attr) {
+                let value = self.default(&description.attr_type(entry.key())?)?;
+                entry.insert(value);
+            }
+        }
+        Entity::new(
+            uid,
+            attrs,
+            parents,
+            HashMap::new(),
+            &Extensions::all_available(),
+        )
+        .ok()
+    }
+
+    /// The value of class `class`, which has type `ty`
+    fn value(&mut self, class: usize, ty: &SchemaType) -> Option<RestrictedExpr> {
+        if let Some(lit) = self.theory.literal(class) {
+            return lit_value(lit);
+        }
+        Some(match ty {
+            SchemaType::Bool => RestrictedExpr::val(self.bool_value(class)),
+            SchemaType::Long => RestrictedExpr::val(i64::try_from(self.long_value(class)?).ok()?),
+            SchemaType::String => RestrictedExpr::val(self.string_value(class)),
+            SchemaType::Entity { ty } => RestrictedExpr::val(self.uid(class, ty)?),
+            SchemaType::Set { element_ty } => self.set_value(class, element_ty)?,
+            SchemaType::EmptySet => RestrictedExpr::set([]),
+            SchemaType::Record { attrs, .. } => {
+                RestrictedExpr::record(self.record_pairs(Some(class), attrs)?).ok()?
+            }
+            SchemaType::Extension { name } => extension_default(name)?,
+        })
+    }
+
+    /// The fields of a record of type `attrs`: those the branch mentions for
+    /// class `class` (unless it says they're missing), and defaults for the
+    /// other required ones
+    fn record_pairs(
+        &mut self,
+        class: Option<usize>,
+        attrs: &HashMap<SmolStr, AttributeType>,
+    ) -> Option<Vec<(SmolStr, RestrictedExpr)>> {
+        let mentioned = class.map(|c| self.theory.attrs(c)).unwrap_or_default();
+        let mut pairs = Vec::new();
+        for (attr, attr_type) in attrs {
+            match (class, mentioned.get(attr)) {
+                (Some(class), Some(&child))
+                    if attr_type.is_required() || self.theory.has(class, attr).unwrap_or(true) =>
+                {
+                    pairs.push((attr.clone(), self.value(child, attr_type.schema_type())?));
+                }
+                _ if attr_type.is_required() => {
+                    pairs.push((attr.clone(), self.default(attr_type.schema_type())?));
+                }
+                _ => (),
+            }
+        }
+        Some(pairs)
+    }
+
+    /// A set containing the elements the branch says class `class` contains
+    fn set_value(&mut self, class: usize, element_ty: &SchemaType) -> Option<RestrictedExpr> {
+        let theory = self.theory;
+        let mut elements = Vec::new();
+        for (atom, positive) in &theory.atoms {
+            match (atom, positive) {
+                (NodeAtom::Contains(set, element), true) if self.theory.class(*set) == class => {
+                    elements.push(self.value(self.theory.class(*element), element_ty)?);
+                }
+                // an entity is `in` a set containing it
+                (NodeAtom::In(entity, set), true) if self.theory.class(*set) == class => {
+                    elements.push(self.value(self.theory.class(*entity), element_ty)?);
+                }
+                _ => (),
+            }
+        }
+        Some(RestrictedExpr::set(elements))
+    }
+
+    /// A boolean other than any literal the branch says class `class`
+    /// differs from
+    fn bool_value(&self, class: usize) -> bool {
+        let excluded = self
+            .theory
+            .atoms
+            .iter()
+            .find_map(|(atom, positive)| match atom {
+                NodeAtom::Eq(x, y) if !positive => {
+                    let (x, y) = (self.theory.class(*x), self.theory.class(*y));
+                    let other = if x == class {
+                        y
+                    } else if y == class {
+                        x
+                    } else {
+                        return None;
+                    };
+                    match self.theory.literal(other) {
+                        Some(Literal::Bool(b)) => Some(*b),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            });
+        excluded == Some(false)
+    }
+
+    /// A string for class `class`, distinct from every other string
+    fn string_value(&mut self, class: usize) -> SmolStr {
+        if let Some(s) = self.strings.get(&class) {
+            return s.clone();
+        }
+        let name = self.name(class);
+        let s = (0..)
+            .map(|n| -> SmolStr {
+                if n == 0 {
+                    name.clone().into()
+                } else {
+                    format!("{name}{n}").into()
+                }
+            })
+            .find(|s| !self.taken_strings.contains(s))
+            .unwrap_or_default();
+        self.taken_strings.insert(s.clone());
+        self.strings.insert(class, s.clone());
+        s
+    }
+
+    /// The entity for class `class`, which has type `ty`
+    fn uid(&mut self, class: usize, ty: &EntityType) -> Option<EntityUID> {
+        if let Some(uid) = self.uids.get(&class) {
+            return Some(uid.clone());
+        }
+        let uid = match self.theory.literal(class) {
+            Some(Literal::Entity { entity_type, id }) => lit_uid(entity_type, id)?,
+            _ => self.fresh_uid(&self.name(class), ty)?,
+        };
+        self.uids.insert(class, uid.clone());
+        Some(uid)
+    }
+
+    /// An entity of type `ty` distinct from every other entity, with an id
+    /// based on `name`
+    fn fresh_uid(&mut self, name: &str, ty: &EntityType) -> Option<EntityUID> {
+        let EntityType::Specified(ty) = ty else {
+            return None;
+        };
+        let uid = (0..)
+            .map(|n| {
+                let id = if n == 0 {
+                    name.to_string()
+                } else {
+                    format!("{name}{n}")
+                };
+                EntityUID::from_components(ty.clone(), Eid::new(id), None)
+            })
+            .find(|uid| !self.taken_uids.contains(uid))?;
+        self.taken_uids.insert(uid.clone());
+        Some(uid)
+    }
+
+    /// A name for the values of class `class`, from one of its terms
+    fn name(&self, class: usize) -> String {
+        self.theory
+            .members(class)
+            .map(ToString::to_string)
+            .next()
+            .unwrap_or_default()
+    }
+
+    /// Some value of type `ty`
+    fn default(&mut self, ty: &SchemaType) -> Option<RestrictedExpr> {
+        Some(match ty {
+            SchemaType::Bool => RestrictedExpr::val(false),
+            SchemaType::Long => RestrictedExpr::val(0),
+            SchemaType::String => RestrictedExpr::val(""),
+            SchemaType::Entity { ty } => RestrictedExpr::val(self.fresh_uid("default", ty)?),
+            SchemaType::Set { .. } | SchemaType::EmptySet => RestrictedExpr::set([]),
+            SchemaType::Record { attrs, .. } => {
+                RestrictedExpr::record(self.record_pairs(None, attrs)?).ok()?
+            }
+            SchemaType::Extension { name } => extension_default(name)?,
+        })
+    }
+}
+
+fn lit_uid(entity_type: &str, id: &str) -> Option<EntityUID> {
+    Some(EntityUID::from_components(
+        entity_type.parse().ok()?,
+        Eid::new(id),
+        None,
+    ))
+}
+
+fn lit_value(lit: &Literal) -> Option<RestrictedExpr> {
+    Some(match lit {
+        Literal::Bool(b) => RestrictedExpr::val(*b),
+        Literal::Long(i) => RestrictedExpr::val(*i),
+        Literal::String(s) => RestrictedExpr::val(s.clone()),
+        Literal::Entity { entity_type, id } => RestrictedExpr::val(lit_uid(entity_type, id)?),
+        Literal::Set(elements) => {
+            RestrictedExpr::set(elements.iter().map(lit_value).collect::<Option<Vec<_>>>()?)
+        }
+    })
+}
+
+/// Some value of the extension type `name`
+fn extension_default(name: &Name) -> Option<RestrictedExpr> {
+    let (constructor, arg) = match name.to_string().as_str() {
+        "decimal" => ("decimal", "0.0"),
+        "ipaddr" => ("ip", "0.0.0.0"),
+        "datetime" => ("datetime", "1970-01-01"),
+        "duration" => ("duration", "0ms"),
+        _ => return None,
+    };
+    Some(RestrictedExpr::call_extension_fn(
+        Name::parse_unqualified_name(constructor).ok()?,
+        [RestrictedExpr::val(arg)],
+    ))
+}
@@ -0,0 +1,594 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! A search for assignments satisfying a [`Formula`].
+//!
+//! The search splits on disjunctions, collecting the atoms each branch
+//! assumes, and prunes a branch as soon as its atoms contradict each other.
+//! The contradictions it detects ([`Theory::new()`]) are all genuine, so if
+//! every branch is pruned the formula can't be satisfied. The converse
+//! doesn't hold: a branch that survives is only a candidate, which the
+//! caller tries to turn into a concrete model.
+
+use super::formula::{lit_eq, Atom, Formula, Term, Typing};
+use cedar_policy_core::ast::{EntityType, Var};
+use cedar_policy_core::entities::SchemaType;
+use cedar_policy_core::filter::Literal;
+use smol_str::SmolStr;
+use std::collections::{HashMap, HashSet};
+
+/// How many steps the search takes before giving up
+pub(super) const MAX_STEPS: usize = 100_000;
+
+/// The outcome of a search
+pub(super) enum Outcome<T> {
+    /// No assignment satisfies the formula
+    Unsat,
+    /// A model was found
+    Found(T),
+    /// The search ran out of steps, or couldn't build a model for some
+    /// candidate assignment
+    Unknown,
+}
+
+/// Search for a model of `formula`. `model` is called on each consistent
+/// branch and returns `None` if it fails to build a model from it.
+pub(super) fn search<T>(
+    formula: &Formula,
+    typing: &Typing<'_>,
+    mut model: impl FnMut(&Theory<'_>) -> Option<T>,
+) -> Outcome<T> {
+    let mut search = Search {
+        typing,
+        steps: 0,
+        incomplete: false,
+    };
+    match search.expand(vec![formula], &mut Vec::new(), &mut model) {
+        Some(found) => Outcome::Found(found),
+        None if search.incomplete => Outcome::Unknown,
+        None => Outcome::Unsat,
+    }
+}
+
+struct Search<'a, 't> {
+    typing: &'a Typing<'t>,
+    steps: usize,
+    /// Some branch was consistent but yielded no model, or the search was
+    /// cut short
+    incomplete: bool,
+}
+
+impl<'a, 't> Search<'a, 't> {
+    /// Explore the branches in which all of `pending` and `branch` hold
+    fn expand<'f, T>(
+        &mut self,
+        mut pending: Vec<&'f Formula>,
+        branch: &mut Vec<(Atom, bool)>,
+        model: &mut impl FnMut(&Theory<'_>) -> Option<T>,
+    ) -> Option<T> {
+        let mark = branch.len();
+        let result = self.expand_from(&mut pending, branch, model);
+        branch.truncate(mark);
+        result
+    }
+
+    fn expand_from<'f, T>(
+        &mut self,
+        pending: &mut Vec<&'f Formula>,
+        branch: &mut Vec<(Atom, bool)>,
+        model: &mut impl FnMut(&Theory<'_>) -> Option<T>,
+    ) -> Option<T> {
+        loop {
+            self.steps += 1;
+            if self.steps > MAX_STEPS {
+                self.incomplete = true;
+                return None;
+            }
+            // split on disjunctions last, so that atoms prune branches early
+            let Some(next) = pending
+                .iter()
+                .rposition(|f| !matches!(f, Formula::Or(_)))
+                .or_else(|| pending.len().checked_sub(1))
+            else {
+                let theory = Theory::new(self.typing, branch)?;
+                let found = model(&theory);
+                self.incomplete |= found.is_none();
+                return found;
+            };
+            match pending.swap_remove(next) {
+                Formula::Const(true) => (),
+                Formula::Const(false) => return None,
+                Formula::And(fs) => pending.extend(fs),
+                Formula::Or(fs) => {
+                    for f in fs {
+                        let mut pending = pending.clone();
+                        pending.push(f);
+                        if let Some(found) = self.expand(pending, branch, model) {
+                            return Some(found);
+                        }
+                        if self.steps > MAX_STEPS {
+                            return None;
+                        }
+                    }
+                    return None;
+                }
+                Formula::Atom(atom, positive) => {
+                    if branch.iter().any(|(a, p)| a == atom && p == positive) {
+                        continue;
+                    }
+                    branch.push((atom.clone(), *positive));
+                    Theory::new(self.typing, branch)?;
+                }
+            }
+        }
+    }
+}
+
+/// A term, with its subterms replaced by their indices
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Var(Var),
+    Lit(Literal),
+    Attr(usize, SmolStr),
+}
+
+/// An atom over node indices
+#[derive(Debug, Clone)]
+pub(super) enum NodeAtom {
+    Eq(usize, usize),
+    Less(usize, usize),
+    LessEq(usize, usize),
+    Contains(usize, usize),
+    ContainsAll(usize, usize),
+    ContainsAny(usize, usize),
+    In(usize, usize),
+    Has(usize),
+    Is(usize, SmolStr),
+}
+
+/// An atom over equivalence classes, so that atoms that are the same up to
+/// known equalities compare equal
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum Key {
+    Eq(usize, usize),
+    Less(usize, usize),
+    LessEq(usize, usize),
+    Contains(usize, usize),
+    ContainsAll(usize, usize),
+    ContainsAny(usize, usize),
+    In(usize, usize),
+    Has(usize, SmolStr),
+    Is(usize, SmolStr),
+}
+
+/// A difference constraint `x - y <= c` between the classes `x` and `y`,
+/// where `None` stands for zero
+pub(super) type Difference = (Option<usize>, Option<usize>, i128);
+
+/// The atoms of a consistent branch, with the terms they mention
+/// partitioned into classes of terms known to be equal
+pub(super) struct Theory<'a> {
+    pub(super) typing: &'a Typing<'a>,
+    nodes: Vec<(Node, Term)>,
+    classes: Vec<usize>,
+    pub(super) atoms: Vec<(NodeAtom, bool)>,
+    /// The entity type of each class that has one
+    entity_types: HashMap<usize, EntityType>,
+    /// Values for the classes compared with `<` or `<=`
+    pub(super) longs: HashMap<usize, i128>,
+}
+
+impl<'a> Theory<'a> {
+    /// Build the theory of `branch`, or return `None` if its atoms
+    /// contradict each other
+    pub(super) fn new(typing: &'a Typing<'a>, branch: &[(Atom, bool)]) -> Option<Self> {
+        let mut theory = Self {
+            typing,
+            nodes: Vec::new(),
+            classes: Vec::new(),
+            atoms: Vec::new(),
+            entity_types: HashMap::new(),
+            longs: HashMap::new(),
+        };
+        for (atom, positive) in branch {
+            let atom = theory.intern_atom(atom);
+            theory.atoms.push((atom, *positive));
+        }
+        theory.close();
+        theory.check_classes()?;
+        theory.check_keys()?;
+        theory.check_hierarchy()?;
+        theory.longs = theory.solve_longs(&[])?;
+        Some(theory)
+    }
+
+    /// The class of node `node`
+    // PANIC SAFETY: `classes` has an entry for every node, and its entries
+    // are node indices
+    #[allow(clippy::indexing_slicing)]
+    pub(super) fn class(&self, node: usize) -> usize {
+        let mut node = node;
+        while self.classes[node] != node {
+            node = self.classes[node];
+        }
+        node
+    }
+
+    /// The distinct classes
+    pub(super) fn all_classes(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.nodes.len()).filter(|&node| self.class(node) == node)
+    }
+
+    /// The terms in class `class`
+    pub(super) fn members(&self, class: usize) -> impl Iterator<Item = &Term> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(move |(node, _)| self.class(*node) == class)
+            .map(|(_, (_, term))| term)
+    }
+
+    /// The literal class `class` is known to be equal to, if any
+    pub(super) fn literal(&self, class: usize) -> Option<&Literal> {
+        self.members(class).find_map(|term| match term {
+            Term::Lit(lit) => Some(lit),
+            _ => None,
+        })
+    }
+
+    /// The type of the values in class `class`, if the schema gives one
+    pub(super) fn type_of(&self, class: usize) -> Option<SchemaType> {
+        match self.entity_types.get(&class) {
+            Some(ty) => Some(SchemaType::Entity { ty: ty.clone() }),
+            None => self
+                .members(class)
+                .find_map(|term| self.typing.type_of(term)),
+        }
+    }
+
+    /// The class of the node for `term`, if the branch mentions it
+    pub(super) fn class_of_term(&self, term: &Term) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|(_, t)| t == term)
+            .map(|node| self.class(node))
+    }
+
+    /// The attributes of values in class `class` that the branch mentions,
+    /// with the classes of their values
+    pub(super) fn attrs(&self, class: usize) -> HashMap<SmolStr, usize> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(node, (n, _))| match n {
+                Node::Attr(parent, attr) if self.class(*parent) == class => {
+                    Some((attr.clone(), self.class(node)))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Whether the branch says attribute `attr` of class `class` exists
+    pub(super) fn has(&self, class: usize, attr: &str) -> Option<bool> {
+        self.atoms.iter().find_map(|(atom, positive)| match atom {
+            NodeAtom::Has(node) => match self.nodes.get(*node) {
+                Some((Node::Attr(parent, a), _)) if self.class(*parent) == class && a == attr => {
+                    Some(*positive)
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+
+    /// Find values for the classes compared with `<` and `<=` that satisfy
+    /// those comparisons and the `extra` constraints, if there are any
+    pub(super) fn solve_longs(&self, extra: &[Difference]) -> Option<HashMap<usize, i128>> {
+        let mut constraints = extra.to_vec();
+        let mut classes = HashSet::new();
+        for (atom, positive) in &self.atoms {
+            let (x, y, strict) = match (atom, positive) {
+                (NodeAtom::Less(x, y), true) => (*x, *y, true),
+                (NodeAtom::LessEq(x, y), true) => (*x, *y, false),
+                // !(x < y) is y <= x
+                (NodeAtom::Less(x, y), false) => (*y, *x, false),
+                // !(x <= y) is y < x
+                (NodeAtom::LessEq(x, y), false) => (*y, *x, true),
+                _ => continue,
+            };
+            let (x, y) = (self.class(x), self.class(y));
+            constraints.push((Some(x), Some(y), if strict { -1 } else { 0 }));
+            classes.extend([x, y]);
+        }
+        if classes.is_empty() && extra.is_empty() {
+            return Some(HashMap::new());
+        }
+        for class in constraints
+            .iter()
+            .flat_map(|(x, y, _)| [*x, *y])
+            .flatten()
+            .collect::<Vec<_>>()
+        {
+            classes.insert(class);
+        }
+        for &class in &classes {
+            match self.literal(class) {
+                Some(Literal::Long(value)) => {
+                    constraints.push((Some(class), None, i128::from(*value)));
+                    constraints.push((None, Some(class), -i128::from(*value)));
+                }
+                _ => {
+                    constraints.push((Some(class), None, i128::from(i64::MAX)));
+                    constraints.push((None, Some(class), -i128::from(i64::MIN)));
+                }
+            }
+        }
+        // Bellman-Ford from a virtual source with an edge of weight 0 to
+        // every node, where `x - y <= c` is an edge from `y` to `x`
+        let mut dist: HashMap<Option<usize>, i128> = classes
+            .iter()
+            .map(|class| (Some(*class), 0))
+            .chain(std::iter::once((None, 0)))
+            .collect();
+        // PANIC SAFETY: `dist` has an entry for `None` and for every class
+        // the constraints mention
+        #[allow(clippy::indexing_slicing)]
+        for _ in 0..=dist.len() {
+            let mut changed = false;
+            for (x, y, c) in &constraints {
+                let candidate = dist[y] + c;
+                if candidate < dist[x] {
+                    dist.insert(*x, candidate);
+                    changed = true;
+                }
+            }
+            if !changed {
+                let zero = dist[&None];
+                return Some(
+                    dist.into_iter()
+                        .filter_map(|(class, d)| class.map(|class| (class, d - zero)))
+                        .collect(),
+                );
+            }
+        }
+        // still relaxing after as many rounds as there are nodes means
+        // there is a negative cycle
+        None
+    }
+
+    fn intern(&mut self, term: &Term) -> usize {
+        let node = match term {
+            Term::Var(var) => Node::Var(*var),
+            Term::Lit(lit) => {
+                if let Some(node) = self.nodes.iter().position(|(n, _)| match n {
+                    Node::Lit(l) => lit_eq(l, lit),
+                    _ => false,
+                }) {
+                    return node;
+                }
+                Node::Lit(lit.clone())
+            }
+            Term::Attr(parent, attr) => Node::Attr(self.intern(parent), attr.clone()),
+        };
+        match self.nodes.iter().position(|(n, _)| *n == node) {
+            Some(index) => index,
+            None => {
+                self.nodes.push((node, term.clone()));
+                self.classes.push(self.classes.len());
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn intern_atom(&mut self, atom: &Atom) -> NodeAtom {
+        match atom {
+            Atom::Eq(x, y) => NodeAtom::Eq(self.intern(x), self.intern(y)),
+            Atom::Less(x, y) => NodeAtom::Less(self.intern(x), self.intern(y)),
+            Atom::LessEq(x, y) => NodeAtom::LessEq(self.intern(x), self.intern(y)),
+            Atom::Contains(x, y) => NodeAtom::Contains(self.intern(x), self.intern(y)),
+            Atom::ContainsAll(x, y) => NodeAtom::ContainsAll(self.intern(x), self.intern(y)),
+            Atom::ContainsAny(x, y) => NodeAtom::ContainsAny(self.intern(x), self.intern(y)),
+            Atom::In(x, y) => NodeAtom::In(self.intern(x), self.intern(y)),
+            Atom::Has(x) => NodeAtom::Has(self.intern(x)),
+            Atom::Is(x, ty) => NodeAtom::Is(self.intern(x), ty.clone()),
+        }
+    }
+
+    fn union(&mut self, x: usize, y: usize) -> bool {
+        let (x, y) = (self.class(x), self.class(y));
+        if x == y {
+            return false;
+        }
+        // keep the smaller index as the representative
+        let (rep, other) = if x < y { (x, y) } else { (y, x) };
+        // PANIC SAFETY: classes are node indices
+        #[allow(clippy::indexing_slicing)]
+        {
+            self.classes[other] = rep;
+        }
+        true
+    }
+
+    /// Merge the classes of terms known to be equal: those compared with
+    /// `==`, and the same attribute of equal values
+    fn close(&mut self) {
+        let equalities = self
+            .atoms
+            .iter()
+            .filter_map(|(atom, positive)| match atom {
+                NodeAtom::Eq(x, y) if *positive => Some((*x, *y)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for (x, y) in equalities {
+            self.union(x, y);
+        }
+        loop {
+            let mut changed = false;
+            for i in 0..self.nodes.len() {
+                for j in (i + 1)..self.nodes.len() {
+                    if let (Some((Node::Attr(p, a), _)), Some((Node::Attr(q, b), _))) =
+                        (self.nodes.get(i), self.nodes.get(j))
+                    {
+                        if a == b && self.class(*p) == self.class(*q) {
+                            changed |= self.union(i, j);
+                        }
+                    }
+                }
+            }
+            if !changed {
+                return;
+            }
+        }
+    }
+
+    /// Check that no class contains two different literals or values of two
+    /// different entity types, and that `is` agrees with those types
+    fn check_classes(&mut self) -> Option<()> {
+        // PANIC SAFETY: `node` ranges over the indices of `nodes`
+        #[allow(clippy::indexing_slicing)]
+        for node in 0..self.nodes.len() {
+            let class = self.class(node);
+            if let Node::Lit(lit) = &self.nodes[node].0 {
+                if self.literal(class).is_some_and(|other| !lit_eq(lit, other)) {
+                    return None;
+                }
+            }
+            if let Some(SchemaType::Entity { ty }) = self.typing.type_of(&self.nodes[node].1) {
+                self.set_entity_type(class, ty)?;
+            }
+        }
+        let positive_is = self
+            .atoms
+            .iter()
+            .filter_map(|(atom, positive)| match atom {
+                NodeAtom::Is(x, ty) if *positive => Some((*x, ty.clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for (x, ty) in positive_is {
+            if let Ok(name) = ty.parse() {
+                self.set_entity_type(self.class(x), EntityType::Specified(name))?;
+            }
+        }
+        for (atom, positive) in &self.atoms {
+            match atom {
+                NodeAtom::Is(x, ty)
+                    if !positive
+                        && self
+                            .entity_types
+                            .get(&self.class(*x))
+                            .is_some_and(|t| t.to_string() == ty.as_str()) =>
+                {
+                    return None;
+                }
+                NodeAtom::Eq(x, y) if !positive && self.class(*x) == self.class(*y) => {
+                    return None;
+                }
+                _ => (),
+            }
+        }
+        Some(())
+    }
+
+    fn set_entity_type(&mut self, class: usize, ty: EntityType) -> Option<()> {
+        match self.entity_types.get(&class) {
+            Some(existing) if *existing != ty => None,
+            _ => {
+                self.entity_types.insert(class, ty);
+                Some(())
+            }
+        }
+    }
+
+    /// Check that no atom holds and fails at once, up to known equalities,
+    /// and that no attribute the schema requires is missing
+    fn check_keys(&self) -> Option<()> {
+        let mut seen: HashMap<Key, bool> = HashMap::new();
+        for (atom, positive) in &self.atoms {
+            let c = |node: &usize| self.class(*node);
+            let key = match atom {
+                NodeAtom::Eq(x, y) => Key::Eq(c(x).min(c(y)), c(x).max(c(y))),
+                NodeAtom::Less(x, y) => Key::Less(c(x), c(y)),
+                NodeAtom::LessEq(x, y) => Key::LessEq(c(x), c(y)),
+                NodeAtom::Contains(x, y) => Key::Contains(c(x), c(y)),
+                NodeAtom::ContainsAll(x, y) => Key::ContainsAll(c(x), c(y)),
+                NodeAtom::ContainsAny(x, y) => Key::ContainsAny(c(x).min(c(y)), c(x).max(c(y))),
+                NodeAtom::In(x, y) => Key::In(c(x), c(y)),
+                NodeAtom::Is(x, ty) => Key::Is(c(x), ty.clone()),
+                NodeAtom::Has(x) => {
+                    // values of the schema's types always have their
+                    // required attributes
+                    let node = self.nodes.get(*x);
+                    if !positive && node.is_some_and(|(_, term)| self.typing.is_required(term)) {
+                        return None;
+                    }
+                    match node {
+                        Some((Node::Attr(parent, attr), _)) => Key::Has(c(parent), attr.clone()),
+                        _ => continue,
+                    }
+                }
+            };
+            if seen.insert(key, *positive).is_some_and(|p| p != *positive) {
+                return None;
+            }
+        }
+        Some(())
+    }
+
+    /// Check `in` atoms against each other and against the entity types the
+    /// schema allows to be ancestors of one another
+    fn check_hierarchy(&self) -> Option<()> {
+        let edges = self
+            .atoms
+            .iter()
+            .filter_map(|(atom, positive)| match atom {
+                NodeAtom::In(x, y) if *positive => Some((self.class(*x), self.class(*y))),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for &(x, y) in &edges {
+            if let (Some(descendant), Some(ancestor)) =
+                (self.entity_types.get(&x), self.entity_types.get(&y))
+            {
+                if descendant != ancestor && !self.typing.may_be_ancestor(descendant, ancestor) {
+                    return None;
+                }
+            }
+        }
+        for (atom, positive) in &self.atoms {
+            if let (NodeAtom::In(x, y), false) = (atom, positive) {
+                let (x, y) = (self.class(*x), self.class(*y));
+                // `in` is reflexive and transitive
+                let mut reached = vec![x];
+                let mut todo = vec![x];
+                while let Some(node) = todo.pop() {
+                    for &(from, to) in &edges {
+                        if from == node && !reached.contains(&to) {
+                            reached.push(to);
+                            todo.push(to);
+                        }
+                    }
+                }
+                if reached.contains(&y) {
+                    return None;
+                }
+            }
+        }
+        Some(())
+    }
+}
@@ -15,6 +15,7 @@
  */
 
 use crate::{ValidatorEntityType, ValidatorSchema};
+use cedar_policy_core::entities::conformance::find_invalid_enum_entity;
 use cedar_policy_core::entities::json::GetSchemaTypeError;
 use cedar_policy_core::extensions::Extensions;
 use cedar_policy_core::{ast, entities};
@@ -132,6 +133,21 @@ impl entities::EntityTypeDescription for EntityTypeDescription {
         Some(core_schema_type)
     }
 
+    fn tag_type(&self) -> Option<entities::SchemaType> {
+        let tag_type: &crate::types::Type = self.validator_type.tag_type()?;
+        // This converts a type from a schema into the representation of schema
+        // types used by core, just like in `attr_type()` above.
+        // PANIC SAFETY: `tag_type` is taken from a `ValidatorEntityType` which
+        // was constructed from a schema
+        #[allow(clippy::expect_used)]
+        let core_schema_type: entities::SchemaType = tag_type
+            .clone()
+            .try_into()
+            .expect("failed to convert validator type into Core SchemaType");
+        debug_assert!(tag_type.is_consistent_with(&core_schema_type));
+        Some(core_schema_type)
+    }
+
     fn required_attrs<'s>(&'s self) -> Box<dyn Iterator<Item = SmolStr> + 's> {
         Box::new(
             self.validator_type
@@ -149,6 +165,12 @@ impl entities::EntityTypeDescription for EntityTypeDescription {
     fn open_attributes(&self) -> bool {
         self.validator_type.open_attributes.is_open()
     }
+
+    fn enum_entity_eids(&self) -> Option<Vec<ast::Eid>> {
+        self.validator_type
+            .enum_choices()
+            .map(|choices| choices.iter().cloned().map(ast::Eid::new).collect())
+    }
 }
 
 impl ast::RequestSchema for ValidatorSchema {
@@ -167,25 +189,37 @@ impl ast::RequestSchema for ValidatorSchema {
         } = request.principal()
         {
             match principal.entity_type() {
-                ast::EntityType::Specified(name) => {
-                    if self.get_entity_type(name).is_none() {
+                ast::EntityType::Specified(name) => match self.get_entity_type(name) {
+                    None => {
                         return Err(RequestValidationError::UndeclaredPrincipalType {
                             principal_ty: principal.entity_type().clone(),
                         });
                     }
-                }
+                    Some(ety) if !ety.allows_eid(principal.eid()) => {
+                        return Err(RequestValidationError::InvalidEnumEntity {
+                            uid: Arc::clone(principal),
+                        });
+                    }
+                    Some(_) => {}
+                },
                 ast::EntityType::Unspecified => {} // unspecified principal is allowed, unless we find it is not allowed for this action, which we will check below
             }
         }
         if let EntityUIDEntry::Known { euid: resource, .. } = request.resource() {
             match resource.entity_type() {
-                ast::EntityType::Specified(name) => {
-                    if self.get_entity_type(name).is_none() {
+                ast::EntityType::Specified(name) => match self.get_entity_type(name) {
+                    None => {
                         return Err(RequestValidationError::UndeclaredResourceType {
                             resource_ty: resource.entity_type().clone(),
                         });
                     }
-                }
+                    Some(ety) if !ety.allows_eid(resource.eid()) => {
+                        return Err(RequestValidationError::InvalidEnumEntity {
+                            uid: Arc::clone(resource),
+                        });
+                    }
+                    Some(_) => {}
+                },
                 ast::EntityType::Unspecified => {} // unspecified resource is allowed, unless we find it is not allowed for this action, which we will check below
             }
         }
@@ -234,6 +268,13 @@ impl ast::RequestSchema for ValidatorSchema {
                             action: Arc::clone(action),
                         });
                     }
+                    if let Some((uid, _)) =
+                        find_invalid_enum_entity(&CoreSchema::new(self), context.as_ref())
+                    {
+                        return Err(RequestValidationError::InvalidEnumEntity {
+                            uid: Arc::new(uid.clone()),
+                        });
+                    }
                 }
             }
             EntityUIDEntry::Unknown { .. } => {
@@ -280,6 +321,16 @@ pub enum RequestValidationError {
         /// Resource type which was not declared in the schema
         resource_ty: ast::EntityType,
     },
+    /// Request principal or resource, or an entity in the request context, is
+    /// of an enumerated entity type, but its entity ID is not one of the IDs
+    /// declared in the schema
+    #[error(
+        "entity `{uid}` is of an enumerated entity type, but its ID is not one of the declared IDs"
+    )]
+    InvalidEnumEntity {
+        /// Entity with the invalid ID
+        uid: Arc<ast::EntityUID>,
+    },
     /// Request principal is of a type that is declared in the schema, but is
     /// not valid for the request action
     #[error("principal type `{principal_ty}` is not valid for `{action}`")]
@@ -870,4 +921,89 @@ mod test {
             }
         );
     }
+
+    fn enum_schema() -> ValidatorSchema {
+        ValidatorSchema::from_str_natural(
+            r#"
+            entity Role enum ["admin", "viewer"];
+            entity User in [Role];
+            action view appliesTo { principal: [User, Role], resource: [User] };
+            "#,
+            Extensions::all_available(),
+        )
+        .unwrap()
+        .0
+    }
+
+    /// request principal is of an enumerated entity type
+    #[test]
+    fn enum_principal() {
+        let request = |eid: &str| {
+            ast::Request::new(
+                (
+                    ast::EntityUID::with_eid_and_type("Role", eid).unwrap(),
+                    None,
+                ),
+                (
+                    ast::EntityUID::with_eid_and_type("Action", "view").unwrap(),
+                    None,
+                ),
+                (
+                    ast::EntityUID::with_eid_and_type("User", "alice").unwrap(),
+                    None,
+                ),
+                ast::Context::empty(),
+                Some(&enum_schema()),
+                Extensions::all_available(),
+            )
+        };
+        assert_matches!(request("admin"), Ok(_));
+        assert_matches!(
+            request("root"),
+            Err(RequestValidationError::InvalidEnumEntity { uid }) => {
+                assert_eq!(&*uid, &ast::EntityUID::with_eid_and_type("Role", "root").unwrap());
+            }
+        );
+    }
+
+    /// entities of, or in, an enumerated entity type
+    #[test]
+    fn enum_entities() {
+        let schema = enum_schema();
+        let entity = |ty: &str, eid: &str, parents: &[&str]| {
+            ast::Entity::new(
+                ast::EntityUID::with_eid_and_type(ty, eid).unwrap(),
+                HashMap::new(),
+                parents
+                    .iter()
+                    .map(|p| ast::EntityUID::with_eid_and_type("Role", p).unwrap())
+                    .collect(),
+                HashMap::new(),
+                &Extensions::all_available(),
+            )
+            .unwrap()
+        };
+        let validate = |entity: ast::Entity| {
+            entities::Entities::from_entities(
+                [entity],
+                Some(&CoreSchema::new(&schema)),
+                entities::TCComputation::ComputeNow,
+                Extensions::all_available(),
+            )
+        };
+        assert_matches!(validate(entity("Role", "viewer", &[])), Ok(_));
+        assert_matches!(validate(entity("User", "alice", &["admin"])), Ok(_));
+        assert_matches!(
+            validate(entity("Role", "editor", &[])),
+            Err(entities::err::EntitiesError::InvalidEntity(
+                entities::conformance::err::EntitySchemaConformanceError::InvalidEnumEntity(_)
+            ))
+        );
+        assert_matches!(
+            validate(entity("User", "alice", &["editor"])),
+            Err(entities::err::EntitiesError::InvalidEntity(
+                entities::conformance::err::EntitySchemaConformanceError::InvalidEnumEntity(_)
+            ))
+        );
+    }
 }
@@ -0,0 +1,823 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Entity manifests describe the entity data that evaluating a policy set
+//! could need for each type of request allowed by a schema.
+//!
+//! A manifest is computed once for a policy set and schema with
+//! [`compute_entity_manifest`]. Before authorizing a request, the caller looks
+//! up the [`RootAccessTrie`] for the request's type and loads only the
+//! entities, attributes, tags, and ancestors it lists, or uses
+//! [`EntityManifest::slice_entities`] to cut an in-memory [`Entities`] down
+//! to that data. Authorizing the request against the sliced entities gives
+//! the same response as authorizing it against the full entity data.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use cedar_policy_core::{
+    ast::{
+        BinaryOp, Entity, EntityType, EntityUID, Expr, ExprKind, Literal, PartialValue, Policy,
+        PolicySet, Request, SlotEnv, Value, ValueKind, Var,
+    },
+    entities::{err::EntitiesError, Dereference, Entities, NoEntitiesSchema, TCComputation},
+    extensions::Extensions,
+};
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use smol_str::SmolStr;
+use thiserror::Error;
+
+use crate::ValidatorSchema;
+
+/// The entity data needed to authorize requests against a policy set, for
+/// every type of request allowed by the schema.
+#[serde_as]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntityManifest {
+    /// Data needed for each request type
+    #[serde_as(as = "Vec<(_, _)>")]
+    per_request_type: HashMap<RequestType, RootAccessTrie>,
+}
+
+/// The type of a request: the entity types of its principal and resource,
+/// and its action.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RequestType {
+    /// Entity type of the principal
+    pub principal: EntityType,
+    /// The action
+    pub action: EntityUID,
+    /// Entity type of the resource
+    pub resource: EntityType,
+}
+
+/// Where a chain of entity data accesses starts: either a request variable or
+/// an entity literal appearing in a policy.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EntityRoot {
+    /// An entity literal, or a template slot filled by a template link
+    Literal(EntityUID),
+    /// A request variable
+    Var(Var),
+}
+
+/// The data needed starting from each [`EntityRoot`] for one request type.
+#[serde_as]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RootAccessTrie {
+    #[serde_as(as = "Vec<(_, _)>")]
+    trie: HashMap<EntityRoot, AccessTrie>,
+}
+
+/// The data needed from a value. When the value is an entity, this names the
+/// attributes and tags of that entity which are needed, and whether its
+/// ancestors are. When the value is a record, only `attributes` is used, and
+/// names the needed fields of the record.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessTrie {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    attributes: BTreeMap<SmolStr, AccessTrie>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    tags: BTreeMap<SmolStr, AccessTrie>,
+    /// Set when tags are accessed with a key that isn't a literal, so every
+    /// tag is needed, along with this data from each tag's value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    all_tags: Option<Box<AccessTrie>>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    ancestors_required: bool,
+}
+
+/// Errors slicing entities with an [`EntityManifest`]
+#[derive(Debug, Error, Diagnostic)]
+pub enum EntityManifestError {
+    /// Entities can only be sliced for requests whose principal, action, and
+    /// resource are all known
+    #[error("cannot slice entities for a request with an unknown `{0}`")]
+    PartialRequest(Var),
+    /// Error constructing the sliced entities
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Entities(#[from] EntitiesError),
+}
+
+/// Compute the [`EntityManifest`] for `policies` under `schema`.
+///
+/// A policy contributes to the data needed for every request type its scope
+/// and the action's `appliesTo` declaration allow it to apply to (see
+/// [`ValidatorSchema::policy_may_apply`]). The analysis follows every
+/// attribute, tag, and `in` that evaluation of the policy could reach, so it
+/// never omits needed data, but it may include data that a particular
+/// request ends up not touching.
+pub fn compute_entity_manifest(schema: &ValidatorSchema, policies: &PolicySet) -> EntityManifest {
+    let policy_tries = policies
+        .policies()
+        .map(|policy| (policy, policy_accesses(policy)))
+        .collect::<Vec<_>>();
+    let per_request_type = request_types(schema)
+        .map(|request_type| {
+            let mut root_trie = RootAccessTrie::default();
+            for (policy, policy_trie) in &policy_tries {
+                if request_type.may_apply(schema, policy) {
+                    root_trie.union_with(policy_trie);
+                }
+            }
+            (request_type, root_trie)
+        })
+        .collect();
+    EntityManifest { per_request_type }
+}
+
+impl EntityManifest {
+    /// Iterate over the request types this manifest covers
+    pub fn request_types(&self) -> impl Iterator<Item = &RequestType> {
+        self.per_request_type.keys()
+    }
+
+    /// Get the data needed for requests of type `request_type`, or `None` if
+    /// the schema does not allow requests of that type.
+    pub fn get(&self, request_type: &RequestType) -> Option<&RootAccessTrie> {
+        self.per_request_type.get(request_type)
+    }
+
+    /// Restrict `entities` to the data needed to authorize `request`.
+    ///
+    /// Entities which are not needed are dropped, and the needed entities
+    /// keep only the needed attributes and tags, and their ancestors only
+    /// when those are needed. A request of a type the schema doesn't allow
+    /// gets an empty set of entities.
+    pub fn slice_entities(
+        &self,
+        entities: &Entities,
+        request: &Request,
+    ) -> Result<Entities, EntityManifestError> {
+        let uid_of = |var: Var| {
+            let entry = match var {
+                Var::Principal => request.principal(),
+                Var::Action => request.action(),
+                _ => request.resource(),
+            };
+            entry.uid().ok_or(EntityManifestError::PartialRequest(var))
+        };
+        let (principal, action, resource) = (
+            uid_of(Var::Principal)?,
+            uid_of(Var::Action)?,
+            uid_of(Var::Resource)?,
+        );
+        let request_type = RequestType {
+            principal: principal.entity_type().clone(),
+            action: action.clone(),
+            resource: resource.entity_type().clone(),
+        };
+
+        let mut slicer = Slicer {
+            entities,
+            needed: HashMap::new(),
+        };
+        if let Some(root_trie) = self.get(&request_type) {
+            for (root, trie) in &root_trie.trie {
+                match root {
+                    EntityRoot::Literal(uid) => slicer.visit_entity(uid, trie),
+                    EntityRoot::Var(Var::Principal) => slicer.visit_entity(principal, trie),
+                    EntityRoot::Var(Var::Action) => slicer.visit_entity(action, trie),
+                    EntityRoot::Var(Var::Resource) => slicer.visit_entity(resource, trie),
+                    EntityRoot::Var(Var::Context) => {
+                        if let Some(PartialValue::Value(context)) =
+                            request.context().map(AsRef::as_ref)
+                        {
+                            slicer.visit_value(context, trie);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(Entities::from_entities(
+            slicer.into_entities(),
+            None::<&NoEntitiesSchema>,
+            TCComputation::AssumeAlreadyComputed,
+            Extensions::none(),
+        )?)
+    }
+}
+
+impl RequestType {
+    /// Could `policy` apply to requests of this type?
+    fn may_apply(&self, schema: &ValidatorSchema, policy: &Policy) -> bool {
+        match (&self.principal, &self.resource) {
+            (EntityType::Specified(principal), EntityType::Specified(resource)) => {
+                schema.policy_may_apply(policy, principal, &self.action, resource)
+            }
+            // Scope constraints can't rule out requests without a principal
+            // or resource type, so be conservative.
+            _ => true,
+        }
+    }
+}
+
+impl RootAccessTrie {
+    /// Iterate over the roots data is needed from, along with the data needed
+    /// starting from each one
+    pub fn roots(&self) -> impl Iterator<Item = (&EntityRoot, &AccessTrie)> {
+        self.trie.iter()
+    }
+
+    /// Get the data needed starting from `root`, or `None` if no data is
+    /// needed starting from it
+    pub fn get(&self, root: &EntityRoot) -> Option<&AccessTrie> {
+        self.trie.get(root)
+    }
+
+    /// Get the node for the end of `path`, creating any missing nodes
+    fn node_mut(&mut self, path: &AccessPath) -> &mut AccessTrie {
+        let mut node = self.trie.entry(path.root.clone()).or_default();
+        for step in &path.steps {
+            node = match step {
+                AccessStep::Attr(attr) => node.attributes.entry(attr.clone()).or_default(),
+                AccessStep::Tag(tag) => node.tags.entry(tag.clone()).or_default(),
+                AccessStep::AnyTag => node.all_tags.get_or_insert_with(Default::default),
+            };
+        }
+        node
+    }
+
+    fn union_with(&mut self, other: &RootAccessTrie) {
+        for (root, trie) in &other.trie {
+            self.trie.entry(root.clone()).or_default().union_with(trie);
+        }
+    }
+}
+
+impl AccessTrie {
+    /// Attributes (or record fields) which are needed, along with the data
+    /// needed from each attribute's value
+    pub fn attributes(&self) -> impl Iterator<Item = (&SmolStr, &AccessTrie)> {
+        self.attributes.iter()
+    }
+
+    /// Tags which are needed, along with the data needed from each tag's value
+    pub fn tags(&self) -> impl Iterator<Item = (&SmolStr, &AccessTrie)> {
+        self.tags.iter()
+    }
+
+    /// If every tag is needed, the data needed from each tag's value
+    pub fn all_tags(&self) -> Option<&AccessTrie> {
+        self.all_tags.as_deref()
+    }
+
+    /// Are the entity's ancestors needed?
+    pub fn ancestors_required(&self) -> bool {
+        self.ancestors_required
+    }
+
+    fn is_empty(&self) -> bool {
+        self.attributes.is_empty()
+            && self.tags.is_empty()
+            && self.all_tags.is_none()
+            && !self.ancestors_required
+    }
+
+    fn union_with(&mut self, other: &AccessTrie) {
+        for (attr, trie) in &other.attributes {
+            self.attributes
+                .entry(attr.clone())
+                .or_default()
+                .union_with(trie);
+        }
+        for (tag, trie) in &other.tags {
+            self.tags.entry(tag.clone()).or_default().union_with(trie);
+        }
+        if let Some(trie) = &other.all_tags {
+            self.all_tags
+                .get_or_insert_with(Default::default)
+                .union_with(trie);
+        }
+        self.ancestors_required |= other.ancestors_required;
+    }
+}
+
+/// Every request type allowed by the `appliesTo` declarations in `schema`
+pub(crate) fn request_types(schema: &ValidatorSchema) -> impl Iterator<Item = RequestType> + '_ {
+    schema
+        .known_action_ids()
+        .filter_map(|action| schema.get_action_id(action))
+        .flat_map(|action| {
+            let applies_to = &action.applies_to;
+            applies_to
+                .applicable_principal_types()
+                .flat_map(move |principal| {
+                    applies_to
+                        .applicable_resource_types()
+                        .map(move |resource| RequestType {
+                            principal: principal.clone(),
+                            action: action.name.clone(),
+                            resource: resource.clone(),
+                        })
+                })
+        })
+}
+
+/// One step in a chain of accesses starting from an [`EntityRoot`]
+#[derive(Debug, Clone)]
+enum AccessStep {
+    Attr(SmolStr),
+    Tag(SmolStr),
+    /// A tag whose key isn't known statically
+    AnyTag,
+}
+
+#[derive(Debug, Clone)]
+struct AccessPath {
+    root: EntityRoot,
+    steps: Vec<AccessStep>,
+}
+
+impl AccessPath {
+    fn then(&self, step: AccessStep) -> Self {
+        let mut steps = self.steps.clone();
+        steps.push(step);
+        Self {
+            root: self.root.clone(),
+            steps,
+        }
+    }
+}
+
+/// The entity data an expression could evaluate to. `paths` are the accesses
+/// whose result the expression may evaluate to, and `fields` are the values
+/// of the fields of a record literal the expression may evaluate to.
+#[derive(Debug, Default)]
+struct Values {
+    paths: Vec<AccessPath>,
+    fields: BTreeMap<SmolStr, Values>,
+}
+
+impl Values {
+    fn path(root: EntityRoot) -> Self {
+        Self {
+            paths: vec![AccessPath {
+                root,
+                steps: Vec::new(),
+            }],
+            fields: BTreeMap::new(),
+        }
+    }
+
+    fn union(mut self, other: Values) -> Self {
+        self.paths.extend(other.paths);
+        for (field, values) in other.fields {
+            let merged = match self.fields.remove(&field) {
+                Some(existing) => existing.union(values),
+                None => values,
+            };
+            self.fields.insert(field, merged);
+        }
+        self
+    }
+}
+
+/// Compute the data evaluating `policy` could need, for any request.
+fn policy_accesses(policy: &Policy) -> RootAccessTrie {
+    let mut analysis = Analysis {
+        slots: policy.env(),
+        accesses: RootAccessTrie::default(),
+    };
+    analysis.values(&policy.condition());
+    analysis.accesses
+}
+
+struct Analysis<'a> {
+    /// Values of the template slots, for template-linked policies
+    slots: &'a SlotEnv,
+    accesses: RootAccessTrie,
+}
+
+impl Analysis<'_> {
+    /// Record the accesses made while evaluating `expr` and return the values
+    /// it could evaluate to
+    fn values(&mut self, expr: &Expr) -> Values {
+        match expr.expr_kind() {
+            ExprKind::Lit(Literal::EntityUID(uid)) => {
+                Values::path(EntityRoot::Literal(uid.as_ref().clone()))
+            }
+            ExprKind::Lit(_) | ExprKind::Unknown(_) => Values::default(),
+            ExprKind::Var(var) => Values::path(EntityRoot::Var(*var)),
+            ExprKind::Slot(slot) => match self.slots.get(slot) {
+                Some(uid) => Values::path(EntityRoot::Literal(uid.clone())),
+                None => Values::default(),
+            },
+            ExprKind::If {
+                test_expr,
+                then_expr,
+                else_expr,
+            } => {
+                self.values(test_expr);
+                let then_values = self.values(then_expr);
+                then_values.union(self.values(else_expr))
+            }
+            ExprKind::And { left, right } | ExprKind::Or { left, right } => {
+                self.values(left);
+                self.values(right);
+                Values::default()
+            }
+            ExprKind::UnaryApp { arg, .. } => {
+                self.values(arg);
+                Values::default()
+            }
+            ExprKind::BinaryApp { op, arg1, arg2 } => {
+                let lhs = self.values(arg1);
+                self.values(arg2);
+                match op {
+                    BinaryOp::In => {
+                        for path in &lhs.paths {
+                            self.accesses.node_mut(path).ancestors_required = true;
+                        }
+                        Values::default()
+                    }
+                    BinaryOp::GetTag | BinaryOp::HasTag => {
+                        let step = match arg2.expr_kind() {
+                            ExprKind::Lit(Literal::String(tag)) => AccessStep::Tag(tag.clone()),
+                            _ => AccessStep::AnyTag,
+                        };
+                        let paths = self.access(&lhs, step);
+                        match op {
+                            BinaryOp::GetTag => Values {
+                                paths,
+                                fields: BTreeMap::new(),
+                            },
+                            _ => Values::default(),
+                        }
+                    }
+                    _ => Values::default(),
+                }
+            }
+            ExprKind::ExtensionFunctionApp { args, .. } => {
+                for arg in args.iter() {
+                    self.values(arg);
+                }
+                Values::default()
+            }
+            ExprKind::GetAttr { expr, attr } => {
+                let mut values = self.values(expr);
+                let paths = self.access(&values, AccessStep::Attr(attr.clone()));
+                let field = values.fields.remove(attr).unwrap_or_default();
+                Values {
+                    paths,
+                    fields: BTreeMap::new(),
+                }
+                .union(field)
+            }
+            ExprKind::HasAttr { expr, attr } => {
+                let values = self.values(expr);
+                self.access(&values, AccessStep::Attr(attr.clone()));
+                Values::default()
+            }
+            ExprKind::Like { expr, .. } | ExprKind::Is { expr, .. } => {
+                self.values(expr);
+                Values::default()
+            }
+            ExprKind::Set(elements) => {
+                for element in elements.iter() {
+                    self.values(element);
+                }
+                Values::default()
+            }
+            ExprKind::Record(fields) => Values {
+                paths: Vec::new(),
+                fields: fields
+                    .iter()
+                    .map(|(field, value)| (field.clone(), self.values(value)))
+                    .collect(),
+            },
+        }
+    }
+
+    /// Record taking `step` from each of the paths in `values`, returning the
+    /// extended paths
+    fn access(&mut self, values: &Values, step: AccessStep) -> Vec<AccessPath> {
+        values
+            .paths
+            .iter()
+            .map(|path| {
+                let path = path.then(step.clone());
+                self.accesses.node_mut(&path);
+                path
+            })
+            .collect()
+    }
+}
+
+/// What to keep of an entity when slicing
+#[derive(Debug, Default)]
+struct Needed {
+    attributes: HashSet<SmolStr>,
+    tags: HashSet<SmolStr>,
+    all_tags: bool,
+    ancestors: bool,
+}
+
+struct Slicer<'a> {
+    entities: &'a Entities,
+    needed: HashMap<EntityUID, Needed>,
+}
+
+impl<'a> Slicer<'a> {
+    fn visit_entity(&mut self, uid: &EntityUID, trie: &AccessTrie) {
+        // Missing entities don't need to be loaded: evaluation treats them
+        // the same way whether or not the entities were sliced.
+        // An entity that's only compared against needs no data at all.
+        if trie.is_empty() {
+            return;
+        }
+        let Dereference::Data(entity) = self.entities.entity(uid) else {
+            return;
+        };
+        let needed = self.needed.entry(uid.clone()).or_default();
+        needed.attributes.extend(trie.attributes.keys().cloned());
+        needed.tags.extend(trie.tags.keys().cloned());
+        needed.all_tags |= trie.all_tags.is_some();
+        needed.ancestors |= trie.ancestors_required;
+
+        for (attr, child) in &trie.attributes {
+            if let Some(PartialValue::Value(value)) = entity.get(attr) {
+                self.visit_value(value, child);
+            }
+        }
+        for (tag, child) in &trie.tags {
+            if let Some(PartialValue::Value(value)) = entity.get_tag(tag) {
+                self.visit_value(value, child);
+            }
+        }
+        if let Some(child) = &trie.all_tags {
+            for (_, value) in entity.tags() {
+                if let PartialValue::Value(value) = value {
+                    self.visit_value(value, child);
+                }
+            }
+        }
+    }
+
+    fn visit_value(&mut self, value: &Value, trie: &AccessTrie) {
+        match &value.value {
+            ValueKind::Lit(Literal::EntityUID(uid)) => self.visit_entity(uid, trie),
+            ValueKind::Record(record) => {
+                for (field, child) in &trie.attributes {
+                    if let Some(value) = record.get(field) {
+                        self.visit_value(value, child);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn into_entities(self) -> impl Iterator<Item = Entity> + 'a {
+        let entities = self.entities;
+        self.needed.into_iter().filter_map(move |(uid, needed)| {
+            let Dereference::Data(entity) = entities.entity(&uid) else {
+                return None;
+            };
+            let attrs = entity
+                .attrs()
+                .filter(|(attr, _)| needed.attributes.contains(*attr))
+                .map(|(attr, value)| (attr.clone(), value.clone()))
+                .collect();
+            let tags = entity
+                .tags()
+                .filter(|(tag, _)| needed.all_tags || needed.tags.contains(*tag))
+                .map(|(tag, value)| (tag.clone(), value.clone()))
+                .collect();
+            let ancestors = if needed.ancestors {
+                entity.ancestors().cloned().collect()
+            } else {
+                HashSet::new()
+            };
+            Some(Entity::new_with_partial_values(uid, attrs, ancestors, tags))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cedar_policy_core::{
+        ast::{Context, EntityUID, RequestSchemaAllPass, Var},
+        entities::{EntityJsonParser, NoEntitiesSchema, TCComputation},
+        extensions::Extensions,
+        parser::parse_policyset,
+    };
+    use serde_json::json;
+
+    use super::*;
+
+    fn schema() -> ValidatorSchema {
+        ValidatorSchema::from_json_value(
+            json!({ "": {
+                "entityTypes": {
+                    "User": {
+                        "memberOfTypes": ["Group"],
+                        "shape": { "type": "Record", "attributes": {
+                            "name": { "type": "String" },
+                            "manager": { "type": "Entity", "name": "User" },
+                        }},
+                    },
+                    "Group": {},
+                    "Document": {
+                        "shape": { "type": "Record", "attributes": {
+                            "owner": { "type": "Entity", "name": "User" },
+                            "title": { "type": "String" },
+                        }},
+                        "tags": { "type": "Entity", "name": "User" },
+                    },
+                },
+                "actions": {
+                    "view": { "appliesTo": {
+                        "principalTypes": ["User"],
+                        "resourceTypes": ["Document"],
+                        "context": { "type": "Record", "attributes": {
+                            "key": { "type": "String" },
+                            "delegate": { "type": "Entity", "name": "User" },
+                        }},
+                    }},
+                    "share": { "appliesTo": {
+                        "principalTypes": ["User"],
+                        "resourceTypes": ["Document"],
+                    }},
+                }
+            }}),
+            Extensions::all_available(),
+        )
+        .unwrap()
+    }
+
+    fn request_type(action: &str) -> RequestType {
+        RequestType {
+            principal: EntityType::Specified("User".parse().unwrap()),
+            action: EntityUID::with_eid_and_type("Action", action).unwrap(),
+            resource: EntityType::Specified("Document".parse().unwrap()),
+        }
+    }
+
+    fn trie_json(manifest: &EntityManifest, action: &str, root: EntityRoot) -> serde_json::Value {
+        serde_json::to_value(manifest.get(&request_type(action)).unwrap().get(&root)).unwrap()
+    }
+
+    #[test]
+    fn attributes_and_ancestors() {
+        let policies = parse_policyset(
+            r#"
+            permit(principal in Group::"admins", action == Action::"view", resource)
+            when { resource.owner.manager == principal && principal.name like "a*" };
+            permit(principal, action == Action::"share", resource)
+            when { resource has title };
+            "#,
+        )
+        .unwrap();
+        let manifest = compute_entity_manifest(&schema(), &policies);
+        assert_eq!(manifest.request_types().count(), 2);
+
+        assert_eq!(
+            trie_json(&manifest, "view", EntityRoot::Var(Var::Principal)),
+            json!({ "attributes": { "name": {} }, "ancestors_required": true })
+        );
+        assert_eq!(
+            trie_json(&manifest, "view", EntityRoot::Var(Var::Resource)),
+            json!({ "attributes": { "owner": { "attributes": { "manager": {} } } } })
+        );
+        assert_eq!(
+            trie_json(&manifest, "share", EntityRoot::Var(Var::Resource)),
+            json!({ "attributes": { "title": {} } })
+        );
+        assert_eq!(
+            trie_json(&manifest, "share", EntityRoot::Var(Var::Principal)),
+            serde_json::Value::Null
+        );
+    }
+
+    #[test]
+    fn tags_records_and_conditionals() {
+        let policies = parse_policyset(
+            r#"
+            permit(principal, action == Action::"view", resource)
+            when {
+                resource.getTag("editor").name == "alice" &&
+                resource.getTag(context.key) in Group::"staff" &&
+                (if resource.title == "" then principal else context.delegate).name == "bob" &&
+                { doc: resource }.doc.owner == principal
+            };
+            "#,
+        )
+        .unwrap();
+        let manifest = compute_entity_manifest(&schema(), &policies);
+        assert_eq!(
+            trie_json(&manifest, "view", EntityRoot::Var(Var::Resource)),
+            json!({
+                "attributes": { "owner": {}, "title": {} },
+                "tags": { "editor": { "attributes": { "name": {} } } },
+                "all_tags": { "ancestors_required": true },
+            })
+        );
+        assert_eq!(
+            trie_json(&manifest, "view", EntityRoot::Var(Var::Principal)),
+            json!({ "attributes": { "name": {} } })
+        );
+        assert_eq!(
+            trie_json(&manifest, "view", EntityRoot::Var(Var::Context)),
+            json!({ "attributes": { "delegate": { "attributes": { "name": {} } }, "key": {} } })
+        );
+    }
+
+    #[test]
+    fn slice_entities() {
+        let policies = parse_policyset(
+            r#"
+            permit(principal, action == Action::"view", resource)
+            when { resource.owner.manager == principal };
+            "#,
+        )
+        .unwrap();
+        let manifest = compute_entity_manifest(&schema(), &policies);
+        let entities = EntityJsonParser::new(
+            None::<&NoEntitiesSchema>,
+            Extensions::all_available(),
+            TCComputation::ComputeNow,
+        )
+        .from_json_value(json!([
+            {
+                "uid": { "type": "Document", "id": "plan" },
+                "attrs": { "owner": { "__entity": { "type": "User", "id": "alice" } }, "title": "Plan" },
+                "parents": [],
+                "tags": { "editor": { "__entity": { "type": "User", "id": "carol" } } },
+            },
+            {
+                "uid": { "type": "User", "id": "alice" },
+                "attrs": { "name": "Alice", "manager": { "__entity": { "type": "User", "id": "bob" } } },
+                "parents": [{ "type": "Group", "id": "staff" }],
+            },
+            {
+                "uid": { "type": "User", "id": "bob" },
+                "attrs": { "name": "Bob", "manager": { "__entity": { "type": "User", "id": "bob" } } },
+                "parents": [],
+            },
+        ]))
+        .unwrap();
+        let request = Request::new(
+            (EntityUID::with_eid_and_type("User", "bob").unwrap(), None),
+            (
+                EntityUID::with_eid_and_type("Action", "view").unwrap(),
+                None,
+            ),
+            (
+                EntityUID::with_eid_and_type("Document", "plan").unwrap(),
+                None,
+            ),
+            Context::empty(),
+            None::<&RequestSchemaAllPass>,
+            Extensions::all_available(),
+        )
+        .unwrap();
+        let sliced = manifest.slice_entities(&entities, &request).unwrap();
+
+        let document = sliced.entity(&EntityUID::with_eid_and_type("Document", "plan").unwrap());
+        let Dereference::Data(document) = document else {
+            panic!("expected the resource to be kept");
+        };
+        assert_eq!(
+            document
+                .attrs()
+                .map(|(attr, _)| attr.as_str())
+                .collect::<Vec<_>>(),
+            vec!["owner"]
+        );
+        assert_eq!(document.tags().count(), 0);
+        let Dereference::Data(alice) =
+            sliced.entity(&EntityUID::with_eid_and_type("User", "alice").unwrap())
+        else {
+            panic!("expected the owner to be kept");
+        };
+        assert_eq!(
+            alice
+                .attrs()
+                .map(|(attr, _)| attr.as_str())
+                .collect::<Vec<_>>(),
+            vec!["manager"]
+        );
+        assert_eq!(alice.ancestors().count(), 0);
+        // `bob` is only compared against, never dereferenced
+        assert!(matches!(
+            sliced.entity(&EntityUID::with_eid_and_type("User", "bob").unwrap()),
+            Dereference::NoSuchEntity
+        ));
+    }
+}
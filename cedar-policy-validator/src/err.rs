@@ -197,6 +197,17 @@ pub enum SchemaError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     ActionAttrEval(EntityAttrEvaluationError),
+    /// An entity type declared as an enumeration of entity IDs has no IDs.
+    #[error("enumerated entity type `{0}` does not declare any entity IDs")]
+    EmptyEnumEntityType(Name),
+    /// An entity type declared as an enumeration of entity IDs also declares
+    /// parent types, attributes, or tags, which enumerated entity types may
+    /// not have.
+    #[error("enumerated entity type `{0}` declares parent types, attributes, or tags")]
+    #[diagnostic(help(
+        "enumerated entity types may not declare `memberOfTypes`, `shape`, or `tags`"
+    ))]
+    InvalidEnumEntityType(Name),
     /// Error thrown when the schema contains the `__expr` escape.
     /// Support for this escape form has been dropped.
     #[error("the `__expr` escape is no longer supported")]
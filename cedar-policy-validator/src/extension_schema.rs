@@ -16,7 +16,7 @@
 
 use crate::types::Type;
 use cedar_policy_core::ast::{Expr, Name};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 /// Type information for a Cedar extension.
 pub struct ExtensionSchema {
@@ -24,6 +24,9 @@ pub struct ExtensionSchema {
     name: Name,
     /// Type information for extension functions
     function_types: HashMap<Name, ExtensionFunctionType>,
+    /// Extension types which may be compared with the operators `<`, `<=`,
+    /// `>`, and `>=`
+    types_with_operator_overloading: BTreeSet<Name>,
 }
 
 impl std::fmt::Debug for ExtensionSchema {
@@ -37,6 +40,7 @@ impl ExtensionSchema {
     pub fn new(
         name: Name,
         function_types: impl IntoIterator<Item = ExtensionFunctionType>,
+        types_with_operator_overloading: impl IntoIterator<Item = Name>,
     ) -> Self {
         Self {
             name,
@@ -44,6 +48,7 @@ impl ExtensionSchema {
                 .into_iter()
                 .map(|f| (f.name.clone(), f))
                 .collect(),
+            types_with_operator_overloading: types_with_operator_overloading.into_iter().collect(),
         }
     }
 
@@ -55,6 +60,11 @@ impl ExtensionSchema {
     pub fn get_function_type(&self, name: &Name) -> Option<&ExtensionFunctionType> {
         self.function_types.get(name)
     }
+
+    /// Get the extension types which support the comparison operators
+    pub fn types_with_operator_overloading(&self) -> impl Iterator<Item = &Name> {
+        self.types_with_operator_overloading.iter()
+    }
 }
 
 /// The type of a function used to perform custom argument validation on an
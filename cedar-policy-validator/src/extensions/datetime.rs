@@ -0,0 +1,140 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+//! Note on panic safety
+//! If any of the panics in this file are triggered, that means that this file has become
+//! out-of-date with the datetime extension definition in CedarCore.
+//! This is tested by the `extension_schema_correctness()` test
+
+use crate::extension_schema::{ArgumentCheckFn, ExtensionFunctionType, ExtensionSchema};
+use crate::types::{self, Type};
+use cedar_policy_core::ast::{Expr, ExprKind, Literal, Name, RestrictedExpr};
+use cedar_policy_core::evaluator::RestrictedEvaluator;
+use cedar_policy_core::extensions::{datetime, Extensions};
+use std::str::FromStr;
+
+/// Note on safety:
+/// This module depends on the Cedar parser only constructing AST with valid extension calls
+/// If any of the panics in this file are triggered, that means that this file has become
+/// out-of-date with the datetime extension definition in CedarCore.
+
+// PANIC SAFETY see `Note on safety` above
+#[allow(clippy::panic)]
+fn get_argument_types(fname: &str, datetime_ty: &Type, duration_ty: &Type) -> Vec<types::Type> {
+    match fname {
+        "datetime" | "duration" => vec![Type::primitive_string()],
+        "offset" => vec![datetime_ty.clone(), duration_ty.clone()],
+        "durationSince" => vec![datetime_ty.clone(), datetime_ty.clone()],
+        "toDate" | "toTime" => vec![datetime_ty.clone()],
+        "toMilliseconds" | "toSeconds" | "toMinutes" | "toHours" | "toDays" => {
+            vec![duration_ty.clone()]
+        }
+        _ => panic!("unexpected datetime extension function name: {fname}"),
+    }
+}
+
+// PANIC SAFETY see `Note on safety` above
+#[allow(clippy::panic)]
+fn get_return_type(fname: &str, datetime_ty: &Type, duration_ty: &Type) -> Type {
+    match fname {
+        "datetime" | "offset" | "toDate" => datetime_ty.clone(),
+        "duration" | "durationSince" | "toTime" => duration_ty.clone(),
+        "toMilliseconds" | "toSeconds" | "toMinutes" | "toHours" | "toDays" => {
+            Type::primitive_long()
+        }
+        _ => panic!("unexpected datetime extension function name: {fname}"),
+    }
+}
+
+// PANIC SAFETY see `Note on safety` above
+#[allow(clippy::panic)]
+fn get_argument_check(fname: &str) -> Option<ArgumentCheckFn> {
+    match fname {
+        "datetime" => Some(Box::new(|exprs| validate_string_literal("datetime", exprs))),
+        "duration" => Some(Box::new(|exprs| validate_string_literal("duration", exprs))),
+        "offset" | "durationSince" | "toDate" | "toTime" | "toMilliseconds" | "toSeconds"
+        | "toMinutes" | "toHours" | "toDays" => None,
+        _ => panic!("unexpected datetime extension function name: {fname}"),
+    }
+}
+
+/// Construct the extension schema
+pub fn extension_schema() -> ExtensionSchema {
+    let datetime_ext = datetime::extension();
+    // PANIC SAFETY: these are valid names
+    #[allow(clippy::expect_used)]
+    let datetime_name =
+        Name::parse_unqualified_name("datetime").expect("should be a valid identifier");
+    // PANIC SAFETY: these are valid names
+    #[allow(clippy::expect_used)]
+    let duration_name =
+        Name::parse_unqualified_name("duration").expect("should be a valid identifier");
+    let datetime_ty = Type::extension(datetime_name.clone());
+    let duration_ty = Type::extension(duration_name.clone());
+
+    let fun_tys: Vec<ExtensionFunctionType> = datetime_ext
+        .funcs()
+        .map(|f| {
+            let fname = f.name();
+            let fstring = fname.to_string();
+            let return_type = get_return_type(&fstring, &datetime_ty, &duration_ty);
+            debug_assert!(f
+                .return_type()
+                .map(|ty| return_type.is_consistent_with(ty))
+                .unwrap_or_else(|| return_type == Type::Never));
+            ExtensionFunctionType::new(
+                fname.clone(),
+                get_argument_types(&fstring, &datetime_ty, &duration_ty),
+                return_type,
+                get_argument_check(&fstring),
+            )
+        })
+        .collect();
+    ExtensionSchema::new(
+        datetime_ext.name().clone(),
+        fun_tys,
+        [datetime_name, duration_name],
+    )
+}
+
+/// Extra validation step for the `datetime` and `duration` constructors.
+/// Note that `exprs` will have already been checked to contain the correct number of arguments.
+fn validate_string_literal(constructor: &str, exprs: &[Expr]) -> Result<(), String> {
+    match exprs.first() {
+        Some(arg) if matches!(arg.expr_kind(), ExprKind::Lit(Literal::String(_))) => {
+            let exts = Extensions::all_available();
+            let evaluator = RestrictedEvaluator::new(&exts);
+            match RestrictedExpr::from_str(&format!("{constructor}({arg})")) {
+                Ok(expr) => match evaluator.interpret(expr.as_borrowed()) {
+                    Ok(_) => Ok(()),
+                    Err(_) => Err(format!("Failed to parse as a {constructor} value: `{arg}`")),
+                },
+                Err(_) => Err(format!("Failed to parse as a {constructor} value: `{arg}`")),
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Ensures that `extension_schema()` does not panic
+    #[test]
+    fn extension_schema_correctness() {
+        let _ = extension_schema();
+    }
+}
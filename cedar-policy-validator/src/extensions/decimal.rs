@@ -87,7 +87,7 @@ pub fn extension_schema() -> ExtensionSchema {
             )
         })
         .collect();
-    ExtensionSchema::new(decimal_ext.name().clone(), fun_tys)
+    ExtensionSchema::new(decimal_ext.name().clone(), fun_tys, std::iter::empty())
 }
 
 /// Extra validation step for the `decimal` function.
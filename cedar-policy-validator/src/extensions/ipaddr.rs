@@ -86,7 +86,7 @@ pub fn extension_schema() -> ExtensionSchema {
             )
         })
         .collect();
-    ExtensionSchema::new(ipaddr_ext.name().clone(), fun_tys)
+    ExtensionSchema::new(ipaddr_ext.name().clone(), fun_tys, std::iter::empty())
 }
 
 /// Extra validation step for the `ip` function.
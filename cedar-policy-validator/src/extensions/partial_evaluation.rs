@@ -69,7 +69,7 @@ pub fn extension_schema() -> ExtensionSchema {
             )
         })
         .collect();
-    ExtensionSchema::new(pe_ext.name().clone(), fun_tys)
+    ExtensionSchema::new(pe_ext.name().clone(), fun_tys, std::iter::empty())
 }
 
 #[cfg(test)]
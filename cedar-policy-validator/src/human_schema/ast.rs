@@ -31,7 +31,14 @@ use crate::SchemaTypeVariant;
 
 const IPADDR_EXTENSION: &str = "ipaddr";
 const DECIMAL_EXTENSION: &str = "decimal";
-pub const EXTENSIONS: [&str; 2] = [IPADDR_EXTENSION, DECIMAL_EXTENSION];
+const DATETIME_EXTENSION: &str = "datetime";
+const DURATION_EXTENSION: &str = "duration";
+pub const EXTENSIONS: [&str; 4] = [
+    IPADDR_EXTENSION,
+    DECIMAL_EXTENSION,
+    DATETIME_EXTENSION,
+    DURATION_EXTENSION,
+];
 pub const BUILTIN_TYPES: [&str; 3] = ["Long", "String", "Bool"];
 
 pub(super) const CEDAR_NAMESPACE: &str = "__cedar";
@@ -187,6 +194,70 @@ impl QualName {
     }
 }
 
+/// The `@key("value")` annotations on a declaration, along with the comment
+/// attached to it
+#[derive(Debug, Clone, Default)]
+pub struct Annotations {
+    /// The annotations, in the order they are written
+    pub annotations: Vec<(Node<Id>, Node<SmolStr>)>,
+    /// The comment lines directly above the declaration, followed by the
+    /// comment after it on its last line, if any
+    pub comment: Option<SmolStr>,
+}
+
+impl Annotations {
+    /// Create the [`Annotations`] for the declaration at `start..end` in
+    /// `src`, which is annotated with `annotations`
+    pub fn new(
+        annotations: Vec<(Node<Id>, Node<SmolStr>)>,
+        src: &str,
+        start: usize,
+        end: usize,
+    ) -> Self {
+        Self {
+            annotations,
+            comment: attached_comment(src, start, end),
+        }
+    }
+}
+
+/// The comment attached to the declaration at `start..end` in `src`: the
+/// `//` comment lines directly above it, if nothing but whitespace precedes
+/// it on its first line, and the `//` comment after it on its last line
+fn attached_comment(src: &str, start: usize, end: usize) -> Option<SmolStr> {
+    let before = src.get(..start)?;
+    let line_start = before.rfind(['\n', '\r']).map_or(0, |i| i + 1);
+    let mut lines = Vec::new();
+    if before.get(line_start..)?.trim().is_empty() {
+        lines = before
+            .get(..line_start)?
+            .lines()
+            .rev()
+            .map_while(|line| line.trim_start().strip_prefix("//"))
+            .collect();
+        lines.reverse();
+    }
+    let after = src.get(end..)?;
+    let rest_of_line = after.split(['\n', '\r']).next().unwrap_or_default();
+    if let Some(comment) = rest_of_line
+        .trim_start_matches(|c: char| c == ',' || c.is_whitespace())
+        .strip_prefix("//")
+    {
+        lines.push(comment);
+    }
+    if lines.is_empty() {
+        None
+    } else {
+        Some(
+            lines
+                .iter()
+                .map(|line| line.strip_prefix(' ').unwrap_or(line).trim_end())
+                .join("\n")
+                .into(),
+        )
+    }
+}
+
 /// A [`Namespace`] has a name and a collection declaration
 /// A schema is made up of a series of fragments
 /// A fragment is a series of namespaces
@@ -196,6 +267,8 @@ pub struct Namespace {
     pub name: Option<Node<Path>>,
     /// The [`Declaration`]s contained in this namespace
     pub decls: Vec<Node<Declaration>>,
+    /// The annotations on this namespace
+    pub annotations: Annotations,
 }
 
 impl Namespace {
@@ -222,6 +295,7 @@ pub enum Declaration {
 pub struct TypeDecl {
     pub name: Node<Id>,
     pub def: Node<Type>,
+    pub annotations: Annotations,
 }
 
 impl Decl for TypeDecl {
@@ -240,6 +314,13 @@ pub struct EntityDecl {
     pub member_of_types: Vec<Path>,
     /// Attributes this entity has
     pub attrs: Vec<Node<AttrDecl>>,
+    /// Tag type for this entity (`None` means no tags on this entity)
+    pub tags: Option<Node<Type>>,
+    /// Entity IDs allowed for entities of this type, if it is an enumerated
+    /// entity type (`None` means any entity ID is allowed)
+    pub choices: Option<Vec<Node<SmolStr>>>,
+    /// The annotations on this declaration
+    pub annotations: Annotations,
 }
 
 /// Type definitions
@@ -283,6 +364,8 @@ pub struct AttrDecl {
     pub required: bool,
     /// The type of this attribute
     pub ty: Node<Type>,
+    /// The annotations on this attribute
+    pub annotations: Annotations,
 }
 
 /// The target of a [`PRAppDecl`]
@@ -331,6 +414,8 @@ pub struct ActionDecl {
     pub parents: Option<NonEmpty<Node<QualName>>>,
     /// The constraining clauses in this declarations
     pub app_decls: Option<Node<NonEmpty<Node<AppDecl>>>>,
+    /// The annotations on this declaration
+    pub annotations: Annotations,
 }
 
 impl Decl for ActionDecl {
@@ -349,6 +434,29 @@ mod test {
         Loc::new((1, 1), Arc::from("foo"))
     }
 
+    #[test]
+    fn attached_comments() {
+        let src = "// first\n  //  second\n\n// a\n//\n// b\nentity A; // c\nentity B, C;\n";
+        let a = src.find("entity A").unwrap();
+        assert_eq!(
+            attached_comment(src, a, a + "entity A;".len()).as_deref(),
+            Some("a\n\nb\nc")
+        );
+        let b = src.find("entity B").unwrap();
+        assert_eq!(attached_comment(src, b, b + "entity B, C;".len()), None);
+        let src = "// first\n  //  second  \nentity A { x: Long, y: Long, // y\n};";
+        let a = src.find("entity A").unwrap();
+        assert_eq!(
+            attached_comment(src, a, src.len()).as_deref(),
+            Some("first\n second")
+        );
+        let y = src.find("y:").unwrap();
+        assert_eq!(
+            attached_comment(src, y, y + "y: Long".len()).as_deref(),
+            Some("y")
+        );
+    }
+
     #[test]
     fn in_unqual() {
         let p = Path::single("foo".parse().unwrap(), loc());
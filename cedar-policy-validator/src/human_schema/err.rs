@@ -65,6 +65,8 @@ lazy_static! {
             ("NAMESPACE", "`namespace`"),
             ("TYPE", "`type`"),
             ("SET", "`Set`"),
+            ("TAGS", "`tags`"),
+            ("ENUM", "`enum`"),
             ("IDENTIFIER", "identifier"),
         ]),
         impossible_tokens: HashSet::new(),
@@ -79,6 +81,8 @@ lazy_static! {
             "RESOURCE",
             "CONTEXT",
             "ATTRIBUTES",
+            "TAGS",
+            "ENUM",
             "LONG",
             "STRING",
             "BOOL",
@@ -356,6 +360,9 @@ pub enum ToJsonSchemaError {
     DuplicateContext { loc1: Loc, loc2: Loc },
     #[error("Duplicate {kind} decleration. Action may have at most once {kind} declaration")]
     DuplicatePR { kind: PR, loc1: Loc, loc2: Loc },
+    /// Error raised when a declaration has the same annotation twice
+    #[error("Duplicate annotations: `{key}`")]
+    DuplicateAnnotations { key: SmolStr, loc1: Loc, loc2: Loc },
 
     /// Error raised when there are duplicate namespace IDs
     #[error("Duplicate namespace IDs: `{namespace_id}`")]
@@ -393,6 +400,7 @@ impl Diagnostic for ToJsonSchemaError {
             ToJsonSchemaError::DuplicateDeclarations { loc1, loc2, .. }
             | ToJsonSchemaError::DuplicateContext { loc1, loc2 }
             | ToJsonSchemaError::DuplicatePR { loc1, loc2, .. }
+            | ToJsonSchemaError::DuplicateAnnotations { loc1, loc2, .. }
             | ToJsonSchemaError::DuplicateKeys { loc1, loc2, .. } => Some(Box::new(
                 vec![
                     LabeledSpan::underline(loc1.span),
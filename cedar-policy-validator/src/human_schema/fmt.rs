@@ -24,15 +24,21 @@ use smol_str::{SmolStr, ToSmolStr};
 use thiserror::Error;
 
 use crate::{
-    ActionType, EntityType, NamespaceDefinition, SchemaFragment, SchemaType, SchemaTypeVariant,
+    ActionType, Annotations, EntityType, NamespaceDefinition, SchemaFragment, SchemaType,
+    SchemaTypeVariant, COMMENT_ANNOTATION,
 };
 
 impl Display for SchemaFragment {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for (ns, def) in &self.0 {
             match ns {
+                // The natural syntax has nowhere to put annotations on the
+                // unqualified namespace
                 None => write!(f, "{def}")?,
-                Some(ns) => write!(f, "namespace {ns} {{\n{def}}}")?,
+                Some(ns) => {
+                    fmt_annotations(f, &def.annotations, "")?;
+                    writeln!(f, "namespace {ns} {{\n{def}}}")?
+                }
             }
         }
         Ok(())
@@ -42,51 +48,112 @@ impl Display for SchemaFragment {
 impl Display for NamespaceDefinition {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for (n, ty) in &self.common_types {
-            writeln!(f, "type {n} = {ty};")?
+            fmt_annotations(f, &ty.annotations, "")?;
+            writeln!(f, "type {n} = {};", ty.ty)?
         }
         for (n, ty) in &self.entity_types {
+            fmt_annotations(f, &ty.annotations, "")?;
             writeln!(f, "entity {n}{ty};")?
         }
         for (n, a) in &self.actions {
+            fmt_annotations(f, &a.annotations, "")?;
             writeln!(f, "action \"{}\"{a};", n.escape_debug())?
         }
         Ok(())
     }
 }
 
+/// Write `annotations` on the lines before a declaration, starting each line
+/// with `indent`. The comment annotation is written as `//` comments, which
+/// are attached to the declaration when it is parsed again.
+fn fmt_annotations(
+    f: &mut std::fmt::Formatter<'_>,
+    annotations: &Annotations,
+    indent: &str,
+) -> std::fmt::Result {
+    if let Some(comment) = annotations.get(COMMENT_ANNOTATION) {
+        for line in comment.lines() {
+            if line.is_empty() {
+                writeln!(f, "{indent}//")?;
+            } else {
+                writeln!(f, "{indent}// {line}")?;
+            }
+        }
+    }
+    for (key, value) in annotations.iter() {
+        if key != COMMENT_ANNOTATION {
+            writeln!(f, "{indent}@{key}(\"{}\")", value.escape_debug())?;
+        }
+    }
+    Ok(())
+}
+
 impl Display for SchemaType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            SchemaType::Type(ty) => match ty {
-                SchemaTypeVariant::Boolean => write!(f, "__cedar::Bool"),
-                SchemaTypeVariant::Entity { name } => write!(f, "{name}"),
-                SchemaTypeVariant::Extension { name } => write!(f, "__cedar::{name}"),
-                SchemaTypeVariant::Long => write!(f, "__cedar::Long"),
-                SchemaTypeVariant::Record {
-                    attributes,
-                    additional_attributes: _,
-                } => {
-                    write!(f, "{{")?;
-                    for (i, (n, ty)) in attributes.iter().enumerate() {
-                        write!(
-                            f,
-                            "\"{}\"{}: {}",
-                            n.escape_debug(),
-                            if ty.required { "" } else { "?" },
-                            ty.ty
-                        )?;
-                        if i < (attributes.len() - 1) {
-                            write!(f, ", ")?;
-                        }
+        fmt_type(f, self, 0)
+    }
+}
+
+/// Write `ty`, which starts on a line indented `depth` levels. Records with
+/// annotated attributes are written with one attribute per line, so that
+/// the annotations can precede them.
+fn fmt_type(f: &mut std::fmt::Formatter<'_>, ty: &SchemaType, depth: usize) -> std::fmt::Result {
+    match ty {
+        SchemaType::Type(ty) => match ty {
+            SchemaTypeVariant::Boolean => write!(f, "__cedar::Bool"),
+            SchemaTypeVariant::Entity { name } => write!(f, "{name}"),
+            SchemaTypeVariant::Extension { name } => write!(f, "__cedar::{name}"),
+            SchemaTypeVariant::Long => write!(f, "__cedar::Long"),
+            SchemaTypeVariant::Record {
+                attributes,
+                additional_attributes: _,
+            } if attributes.values().any(|ty| !ty.annotations.is_empty()) => {
+                let indent = "  ".repeat(depth + 1);
+                writeln!(f, "{{")?;
+                for (i, (n, ty)) in attributes.iter().enumerate() {
+                    fmt_annotations(f, &ty.annotations, &indent)?;
+                    write!(
+                        f,
+                        "{indent}\"{}\"{}: ",
+                        n.escape_debug(),
+                        if ty.required { "" } else { "?" },
+                    )?;
+                    fmt_type(f, &ty.ty, depth + 1)?;
+                    if i < (attributes.len() - 1) {
+                        write!(f, ",")?;
                     }
-                    write!(f, "}}")?;
-                    Ok(())
+                    writeln!(f)?;
                 }
-                SchemaTypeVariant::Set { element } => write!(f, "Set < {element} >"),
-                SchemaTypeVariant::String => write!(f, "__cedar::String"),
-            },
-            SchemaType::TypeDef { type_name } => write!(f, "{type_name}"),
-        }
+                write!(f, "{}}}", "  ".repeat(depth))
+            }
+            SchemaTypeVariant::Record {
+                attributes,
+                additional_attributes: _,
+            } => {
+                write!(f, "{{")?;
+                for (i, (n, ty)) in attributes.iter().enumerate() {
+                    write!(
+                        f,
+                        "\"{}\"{}: ",
+                        n.escape_debug(),
+                        if ty.required { "" } else { "?" },
+                    )?;
+                    fmt_type(f, &ty.ty, depth)?;
+                    if i < (attributes.len() - 1) {
+                        write!(f, ", ")?;
+                    }
+                }
+                write!(f, "}}")?;
+                Ok(())
+            }
+            SchemaTypeVariant::Set { element } => {
+                write!(f, "Set < ")?;
+                fmt_type(f, element, depth)?;
+                write!(f, " >")
+            }
+            SchemaTypeVariant::String => write!(f, "__cedar::String"),
+        },
+        SchemaType::TypeDef { type_name } => write!(f, "{type_name}"),
     }
 }
 
@@ -103,6 +170,14 @@ fn fmt_vec<T: Display>(f: &mut std::fmt::Formatter<'_>, ets: NonEmpty<T>) -> std
 
 impl Display for EntityType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(choices) = &self.choices {
+            let contents = choices
+                .iter()
+                .map(|choice| format!("\"{}\"", choice.escape_debug()))
+                .join(", ");
+            return write!(f, " enum [{contents}]");
+        }
+
         if let Some(non_empty) = non_empty_slice(&self.member_of_types) {
             write!(f, " in ")?;
             fmt_vec(f, non_empty)?;
@@ -114,6 +189,10 @@ impl Display for EntityType {
             write!(f, " = {ty}")?;
         }
 
+        if let Some(tags) = &self.tags {
+            write!(f, " tags {tags}")?;
+        }
+
         Ok(())
     }
 }
@@ -150,7 +229,8 @@ impl Display for ActionType {
                     fmt_vec(f, ps)?;
                     write!(f, ",\n  resource: ")?;
                     fmt_vec(f, rs)?;
-                    write!(f, ",\n  context: {}", &spec.context.0)?;
+                    write!(f, ",\n  context: ")?;
+                    fmt_type(f, &spec.context.0, 1)?;
                     write!(f, "\n}}")?;
                 }
                 // Only principals are present, resource is unspecified
@@ -158,7 +238,8 @@ impl Display for ActionType {
                     write!(f, " appliesTo {{")?;
                     write!(f, "\n  principal: ")?;
                     fmt_vec(f, ps)?;
-                    write!(f, ",\n  context: {}", &spec.context.0)?;
+                    write!(f, ",\n  context: ")?;
+                    fmt_type(f, &spec.context.0, 1)?;
                     write!(f, "\n}}")?;
                 }
                 // Only resources is present, principal is unspecified
@@ -166,13 +247,15 @@ impl Display for ActionType {
                     write!(f, " appliesTo {{")?;
                     write!(f, "\n  resource: ")?;
                     fmt_vec(f, rs)?;
-                    write!(f, ",\n  context: {}", &spec.context.0)?;
+                    write!(f, ",\n  context: ")?;
+                    fmt_type(f, &spec.context.0, 1)?;
                     write!(f, "\n}}")?;
                 }
                 // Neither are present, both principal and resource are unspecified
                 (None, None) => {
                     write!(f, " appliesTo {{")?;
-                    write!(f, "\n  context: {}", &spec.context.0)?;
+                    write!(f, "\n  context: ")?;
+                    fmt_type(f, &spec.context.0, 1)?;
                     write!(f, "\n}}")?;
                 }
             }
@@ -29,7 +29,7 @@ mod demo_tests {
 
     use crate::{
         human_schema::{self, ast::PR, err::ToJsonSchemaError},
-        ActionType, ApplySpec, AttributesOrContext, EntityType, HumanSchemaError,
+        ActionType, Annotations, ApplySpec, AttributesOrContext, EntityType, HumanSchemaError,
         NamespaceDefinition, SchemaFragment, SchemaTypeVariant, TypeOfAttribute,
     };
 
@@ -325,6 +325,7 @@ mod demo_tests {
             attributes: None,
             applies_to: None,
             member_of: None,
+            annotations: Annotations::default(),
         };
         let namespace = NamespaceDefinition::new(empty(), once(("foo".to_smolstr(), action)));
         let fragment = SchemaFragment(HashMap::from([(Some("bar".parse().unwrap()), namespace)]));
@@ -401,6 +402,9 @@ namespace Baz {action "Foo" appliesTo {
                 EntityType {
                     member_of_types: vec![],
                     shape: AttributesOrContext::default(),
+                    tags: None,
+                    choices: None,
+                    annotations: Annotations::default(),
                 },
             )]),
             actions: HashMap::from([(
@@ -413,8 +417,10 @@ namespace Baz {action "Foo" appliesTo {
                         context: AttributesOrContext::default(),
                     }),
                     member_of: None,
+                    annotations: Annotations::default(),
                 },
             )]),
+            annotations: Annotations::default(),
         };
         let fragment = SchemaFragment(HashMap::from([(None, namespace)]));
         let src = fragment.as_natural_schema().unwrap();
@@ -865,7 +871,7 @@ namespace Baz {action "Foo" appliesTo {
                 additional_attributes,
             }) => {
                 assert!(!additional_attributes);
-                let TypeOfAttribute { ty, required } = attributes.get("tag").unwrap();
+                let TypeOfAttribute { ty, required, .. } = attributes.get("tag").unwrap();
                 assert!(required);
                 match ty {
                     crate::SchemaType::TypeDef { type_name } => {
@@ -901,7 +907,7 @@ namespace Baz {action "Foo" appliesTo {
         assert_labeled_span("type t =", "expected `{`, identifier, or `Set`");
         assert_labeled_span(
             "entity User {",
-            "expected `}`, identifier, or string literal",
+            "expected `@`, `}`, identifier, or string literal",
         );
         assert_labeled_span("entity User { name:", "expected `{`, identifier, or `Set`");
     }
@@ -1140,10 +1146,11 @@ mod parser_tests {
 #[cfg(test)]
 mod translator_tests {
     use cedar_policy_core::FromNormalizedStr;
+    use cool_asserts::assert_matches;
 
     use crate::{
         types::{EntityLUB, Type},
-        SchemaFragment, SchemaTypeVariant, TypeOfAttribute, ValidatorSchema,
+        SchemaFragment, SchemaType, SchemaTypeVariant, TypeOfAttribute, ValidatorSchema,
     };
 
     #[test]
@@ -1390,7 +1397,7 @@ mod translator_tests {
                 additional_attributes,
             }) => {
                 assert!(!additional_attributes);
-                let TypeOfAttribute { ty, required } = attributes.get("name").unwrap();
+                let TypeOfAttribute { ty, required, .. } = attributes.get("name").unwrap();
                 {
                     assert!(required);
                     let expected = crate::SchemaType::TypeDef {
@@ -1398,7 +1405,7 @@ mod translator_tests {
                     };
                     assert_eq!(ty, &expected);
                 }
-                let TypeOfAttribute { ty, required } = attributes.get("email").unwrap();
+                let TypeOfAttribute { ty, required, .. } = attributes.get("email").unwrap();
                 {
                     assert!(required);
                     let expected = crate::SchemaType::Type(SchemaTypeVariant::Entity {
@@ -1621,6 +1628,52 @@ mod translator_tests {
         assert_eq!(foo.member_of_types, vec!["String".parse().unwrap()]);
     }
 
+    #[test]
+    fn entity_named_tags() {
+        let src = r#"
+        entity tags = {};
+        entity Foo in [tags] = {} tags tags;
+        "#;
+
+        let (schema, _) = SchemaFragment::from_str_natural(src).unwrap();
+        let ns = schema.0.get(&None).unwrap();
+        let foo = ns.entity_types.get(&"Foo".parse().unwrap()).unwrap();
+        assert_eq!(foo.member_of_types, vec!["tags".parse().unwrap()]);
+        assert_matches!(
+            &foo.tags,
+            Some(SchemaType::Type(SchemaTypeVariant::Entity { name })) if name == &"tags".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn entity_with_tags() {
+        let src = r#"
+        entity User = { name: String } tags Set<String>;
+        entity Photo;
+        "#;
+
+        let (schema, _) = SchemaFragment::from_str_natural(src).unwrap();
+        // The printed schema should parse back to the same tag type
+        let printed = schema.as_natural_schema().unwrap();
+        let (reparsed, _) = SchemaFragment::from_str_natural(&printed).unwrap();
+        let user_tags = |schema: &SchemaFragment| {
+            schema.0.get(&None).unwrap().entity_types[&"User".parse().unwrap()]
+                .tags
+                .clone()
+        };
+        assert_eq!(user_tags(&schema), user_tags(&reparsed));
+        let validator_schema: ValidatorSchema =
+            schema.try_into().expect("should be a valid schema");
+        let user = validator_schema
+            .get_entity_type(&"User".parse().unwrap())
+            .unwrap();
+        assert_eq!(user.tag_type(), Some(&Type::set(Type::primitive_string())));
+        let photo = validator_schema
+            .get_entity_type(&"Photo".parse().unwrap())
+            .unwrap();
+        assert_eq!(photo.tag_type(), None);
+    }
+
     #[test]
     fn entity_named_if() {
         let src = r#"
@@ -2019,3 +2072,264 @@ mod common_type_references {
         );
     }
 }
+
+#[cfg(test)]
+mod annotations {
+    use cool_asserts::assert_matches;
+
+    use crate::{
+        human_schema::{err::ToJsonSchemaError, parser::HumanSyntaxParseErrors},
+        HumanSchemaError, SchemaFragment,
+    };
+
+    const SRC: &str = r#"
+    // Photo sharing
+    @doc("photos")
+    namespace PhotoApp {
+        // A short name
+        type Name = String;
+
+        // Someone who uses the app
+        @doc("user")
+        entity User {
+            // Full name
+            // as displayed
+            @doc("name")
+            name: Name,
+            age: Long, // in years
+        };
+
+        @doc("viewing")
+        action view appliesTo {
+            principal: [User],
+            resource: [User],
+        };
+    }
+    "#;
+
+    fn pairs(fragment: Option<&crate::Annotations>) -> Vec<(&str, &str)> {
+        fragment.unwrap().iter().collect()
+    }
+
+    #[test]
+    fn natural_to_json() {
+        let (fragment, _) = SchemaFragment::from_str_natural(SRC).unwrap();
+        let ns: cedar_policy_core::ast::Name = "PhotoApp".parse().unwrap();
+        assert_eq!(
+            pairs(fragment.namespace_annotations(Some(&ns))),
+            [("comment", "Photo sharing"), ("doc", "photos")]
+        );
+        assert_eq!(
+            pairs(fragment.common_type_annotations(Some(&ns), "Name")),
+            [("comment", "A short name")]
+        );
+        let user = "PhotoApp::User".parse().unwrap();
+        assert_eq!(
+            pairs(fragment.entity_type_annotations(&user)),
+            [("comment", "Someone who uses the app"), ("doc", "user")]
+        );
+        assert_eq!(
+            pairs(fragment.attribute_annotations(&user, "name")),
+            [("comment", "Full name\nas displayed"), ("doc", "name")]
+        );
+        assert_eq!(
+            pairs(fragment.attribute_annotations(&user, "age")),
+            [("comment", "in years")]
+        );
+        assert_eq!(
+            pairs(fragment.action_annotations(Some(&ns), "view")),
+            [("doc", "viewing")]
+        );
+        assert_eq!(fragment.action_annotations(None, "view"), None);
+        assert_eq!(fragment.attribute_annotations(&user, "email"), None);
+
+        let json = serde_json::to_value(&fragment).unwrap();
+        assert_eq!(
+            json["PhotoApp"]["entityTypes"]["User"]["shape"]["attributes"]["age"]["annotations"],
+            serde_json::json!({ "comment": "in years" })
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let (fragment, _) = SchemaFragment::from_str_natural(SRC).unwrap();
+        let src = fragment.as_natural_schema().unwrap();
+        let (reparsed, _) = SchemaFragment::from_str_natural(&src).unwrap();
+        assert_eq!(fragment, reparsed);
+
+        let json = serde_json::to_value(&fragment).unwrap();
+        let from_json = SchemaFragment::from_json_value(json).unwrap();
+        assert_eq!(fragment, from_json);
+    }
+
+    #[test]
+    fn json_to_natural() {
+        let fragment = SchemaFragment::from_json_value(serde_json::json!({
+            "": {
+                "entityTypes": {
+                    "User": {
+                        "annotations": { "comment": "line one\nline two", "doc": "a \"user\"" },
+                        "shape": {
+                            "type": "Record",
+                            "attributes": {
+                                "name": { "type": "String", "annotations": { "doc": "name" } }
+                            }
+                        }
+                    }
+                },
+                "actions": {}
+            }
+        }))
+        .unwrap();
+        let src = fragment.as_natural_schema().unwrap();
+        assert!(src.contains("// line one\n// line two\n@doc(\"a \\\"user\\\"\")\nentity User"));
+        assert!(src.contains("@doc(\"name\")"));
+        let (reparsed, _) = SchemaFragment::from_str_natural(&src).unwrap();
+        assert_eq!(fragment, reparsed);
+    }
+
+    #[test]
+    fn explicit_comment_annotation() {
+        let (fragment, _) = SchemaFragment::from_str_natural(
+            r#"
+            // ignored
+            @comment("kept")
+            entity User;
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            pairs(fragment.entity_type_annotations(&"User".parse().unwrap())),
+            [("comment", "kept")]
+        );
+    }
+
+    #[test]
+    fn duplicate_annotations() {
+        let err = match SchemaFragment::from_str_natural(r#"@doc("a") @doc("b") entity User;"#) {
+            Err(e) => e,
+            _ => panic!("Should have failed to parse"),
+        };
+        assert_matches!(err, HumanSchemaError::Parsing(err) => {
+            assert_matches!(err.inner(), HumanSyntaxParseErrors::JsonError(json_errs) => {
+                assert!(json_errs.iter().any(|err| matches!(
+                    err,
+                    ToJsonSchemaError::DuplicateAnnotations { key, .. } if key == "doc"
+                )));
+            });
+        });
+    }
+
+    #[test]
+    fn detached_comments() {
+        let (fragment, _) = SchemaFragment::from_str_natural(
+            r#"
+            // not attached
+
+            entity User; entity Group;
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            fragment.entity_type_annotations(&"User".parse().unwrap()),
+            Some(&crate::Annotations::default())
+        );
+        assert_eq!(
+            fragment.entity_type_annotations(&"Group".parse().unwrap()),
+            Some(&crate::Annotations::default())
+        );
+    }
+}
+
+#[cfg(test)]
+mod enumerated_entity_types {
+    use cool_asserts::assert_matches;
+    use smol_str::SmolStr;
+
+    use crate::{SchemaError, SchemaFragment, ValidatorSchema};
+
+    #[test]
+    fn natural_to_json() {
+        let (fragment, _) = SchemaFragment::from_str_natural(
+            r#"
+            entity Role enum ["admin", "viewer", "a \"quoted\" id"];
+            entity User { enum: Role };
+            "#,
+        )
+        .unwrap();
+        let json = serde_json::to_value(&fragment).unwrap();
+        assert_eq!(
+            json[""]["entityTypes"]["Role"],
+            serde_json::json!({ "enum": ["admin", "viewer", "a \"quoted\" id"] })
+        );
+        let src = fragment.as_natural_schema().unwrap();
+        assert!(
+            src.contains(r#"entity Role enum ["admin", "viewer", "a \"quoted\" id"];"#),
+            "{src}"
+        );
+        let (reparsed, _) = SchemaFragment::from_str_natural(&src).unwrap();
+        assert_eq!(fragment, reparsed);
+
+        let schema: ValidatorSchema = fragment.try_into().unwrap();
+        let role = schema.get_entity_type(&"Role".parse().unwrap()).unwrap();
+        assert_eq!(
+            role.enum_choices(),
+            Some(
+                [
+                    SmolStr::from("admin"),
+                    "viewer".into(),
+                    "a \"quoted\" id".into()
+                ]
+                .as_slice()
+            )
+        );
+        let user = schema.get_entity_type(&"User".parse().unwrap()).unwrap();
+        assert_eq!(user.enum_choices(), None);
+    }
+
+    #[test]
+    fn natural_syntax_errors() {
+        for src in [
+            r#"entity Role enum [];"#,
+            r#"entity Role in [Group] enum ["admin"];"#,
+            r#"entity Role enum ["admin"] { name: String };"#,
+        ] {
+            assert!(SchemaFragment::from_str_natural(src).is_err(), "{src}");
+        }
+    }
+
+    #[test]
+    fn invalid_json() {
+        let schema = |role: serde_json::Value| -> Result<ValidatorSchema, SchemaError> {
+            SchemaFragment::from_json_value(serde_json::json!({
+                "": {
+                    "entityTypes": { "Role": role, "Group": {} },
+                    "actions": {}
+                }
+            }))?
+            .try_into()
+        };
+        assert_matches!(schema(serde_json::json!({ "enum": ["admin"] })), Ok(_));
+        assert_matches!(
+            schema(serde_json::json!({ "enum": [] })),
+            Err(SchemaError::EmptyEnumEntityType(name)) => {
+                assert_eq!(name, "Role".parse().unwrap());
+            }
+        );
+        assert_matches!(
+            schema(serde_json::json!({ "enum": ["admin"], "memberOfTypes": ["Group"] })),
+            Err(SchemaError::InvalidEnumEntityType(_))
+        );
+        assert_matches!(
+            schema(serde_json::json!({
+                "enum": ["admin"],
+                "shape": { "type": "Record", "attributes": { "name": { "type": "String" } } }
+            })),
+            Err(SchemaError::InvalidEnumEntityType(_))
+        );
+        assert_matches!(
+            schema(serde_json::json!({ "enum": ["admin"], "tags": { "type": "String" } })),
+            Err(SchemaError::InvalidEnumEntityType(_))
+        );
+    }
+}
@@ -17,7 +17,7 @@
 use std::collections::HashMap;
 
 use cedar_policy_core::{
-    ast::{Id, Name},
+    ast::{AnyId, Id, Name},
     parser::{Loc, Node},
 };
 use itertools::Either;
@@ -26,14 +26,14 @@ use smol_str::{SmolStr, ToSmolStr};
 use std::collections::hash_map::Entry;
 
 use crate::{
-    human_schema::ast::Path, ActionEntityUID, ActionType, ApplySpec, AttributesOrContext,
-    EntityType, NamespaceDefinition, SchemaFragment, SchemaType, SchemaTypeVariant,
-    TypeOfAttribute,
+    human_schema::ast::Path, ActionEntityUID, ActionType, Annotations, ApplySpec,
+    AttributesOrContext, CommonType, EntityType, NamespaceDefinition, SchemaFragment, SchemaType,
+    SchemaTypeVariant, TypeOfAttribute, COMMENT_ANNOTATION,
 };
 
 use super::{
     ast::{
-        ActionDecl, AppDecl, AttrDecl, Decl, Declaration, EntityDecl, Namespace, PRAppDecl,
+        self, ActionDecl, AppDecl, AttrDecl, Decl, Declaration, EntityDecl, Namespace, PRAppDecl,
         QualName, Schema, Type, TypeDecl, BUILTIN_TYPES, CEDAR_NAMESPACE, EXTENSIONS, PR,
     },
     err::{SchemaWarning, ToJsonSchemaError, ToJsonSchemaErrors},
@@ -75,6 +75,7 @@ pub fn custom_type_to_json_type(ty: Node<Type>) -> Result<SchemaType, ToJsonSche
         &Namespace {
             name: None,
             decls: vec![],
+            annotations: ast::Annotations::default(),
         },
     );
     context.convert_type(ty)
@@ -96,6 +97,7 @@ fn split_unqualified_namespace(
         let unqual = Namespace {
             name: None,
             decls: unqualified_decls,
+            annotations: ast::Annotations::default(),
         };
         (qualified.into_iter(), Some(unqual))
     }
@@ -147,6 +149,7 @@ impl<'a> ConversionContext<'a> {
             _ => Ok(()),
         }?;
 
+        let annotations = convert_annotations(n.annotations)?;
         // Partition the decls into entities, actions, and common types
         let (entity_types, action, common_types) = into_partition_decls(n.decls);
         // Convert entity type decls, collecting all errors
@@ -179,14 +182,20 @@ impl<'a> ConversionContext<'a> {
             common_types,
             entity_types,
             actions,
+            annotations,
         })
     }
 
     /// Converts common type decls
-    fn convert_common_types(&self, decl: TypeDecl) -> Result<(Id, SchemaType), ToJsonSchemaErrors> {
-        let TypeDecl { name, def } = decl;
+    fn convert_common_types(&self, decl: TypeDecl) -> Result<(Id, CommonType), ToJsonSchemaErrors> {
+        let TypeDecl {
+            name,
+            def,
+            annotations,
+        } = decl;
         let ty = self.convert_type(def)?;
-        Ok((name.node, ty))
+        let annotations = convert_annotations(annotations)?;
+        Ok((name.node, CommonType { ty, annotations }))
     }
 
     /// Converts action type decls
@@ -198,6 +207,7 @@ impl<'a> ConversionContext<'a> {
             names,
             parents,
             app_decls,
+            annotations,
         } = a;
         // Create the internal type from the 'applies_to' clause and 'member_of'
         let applies_to = app_decls
@@ -213,6 +223,7 @@ impl<'a> ConversionContext<'a> {
             attributes: None, // Action attributes are currently unsupported in the natural schema
             applies_to: Some(applies_to),
             member_of,
+            annotations: convert_annotations(annotations)?,
         };
         // Then map that type across all of the bound names
         Ok(names.into_iter().map(move |name| (name.node, ty.clone())))
@@ -332,13 +343,20 @@ impl<'a> ConversionContext<'a> {
             names,
             member_of_types,
             attrs,
+            tags,
+            choices,
+            annotations,
         } = e;
         // First build up the defined entity type
         let member_of_types = member_of_types.into_iter().map(|p| p.into()).collect();
         let shape = self.convert_attr_decls(attrs)?;
+        let tags = tags.map(|tags| self.convert_type(tags)).transpose()?;
         let etype = EntityType {
             member_of_types,
             shape,
+            tags,
+            choices: choices.map(|choices| choices.into_iter().map(|c| c.node).collect()),
+            annotations: convert_annotations(annotations)?,
         };
 
         // Then map over all of the bound names
@@ -387,12 +405,18 @@ impl<'a> ConversionContext<'a> {
         &self,
         attr: Node<AttrDecl>,
     ) -> Result<(SmolStr, TypeOfAttribute), ToJsonSchemaErrors> {
-        let AttrDecl { name, required, ty } = attr.node;
+        let AttrDecl {
+            name,
+            required,
+            ty,
+            annotations,
+        } = attr.node;
         Ok((
             name.node,
             TypeOfAttribute {
                 ty: self.convert_type(ty)?,
                 required,
+                annotations: convert_annotations(annotations)?,
             },
         ))
     }
@@ -484,6 +508,36 @@ impl<'a> ConversionContext<'a> {
     }
 }
 
+/// Convert the annotations on a declaration. The attached comment is kept
+/// under [`COMMENT_ANNOTATION`], unless that annotation is given explicitly.
+fn convert_annotations(annotations: ast::Annotations) -> Result<Annotations, ToJsonSchemaError> {
+    let mut keys: HashMap<Id, Loc> = HashMap::new();
+    let mut converted = Vec::new();
+    for (key, value) in annotations.annotations {
+        match keys.entry(key.node.clone()) {
+            Entry::Occupied(entry) => {
+                return Err(ToJsonSchemaError::DuplicateAnnotations {
+                    key: key.node.to_smolstr(),
+                    loc1: entry.get().clone(),
+                    loc2: key.loc,
+                })
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(key.loc);
+            }
+        }
+        converted.push((AnyId::from(key.node), value.node));
+    }
+    if let Some(comment) = annotations.comment {
+        if !keys.keys().any(|key| key.as_ref() == COMMENT_ANNOTATION) {
+            // PANIC SAFETY: `comment` is a valid identifier
+            #[allow(clippy::unwrap_used)]
+            converted.push((COMMENT_ANNOTATION.parse().unwrap(), comment));
+        }
+    }
+    Ok(converted.into_iter().collect())
+}
+
 /// Takes a collection of results returning multiple errors
 /// Behaves similarly to `::collect()` over results, except instead of failing
 /// on the first error, keeps going to ensure all of the errors are accumulated
@@ -0,0 +1,515 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Exporting a schema as [JSON Schema](https://json-schema.org/draft/2020-12)
+//! documents, so that producers of entity and context data can check it with
+//! standard JSON Schema tooling before passing it to Cedar.
+//!
+//! [`entities_json_schema`] describes the JSON format for entities, and
+//! [`context_json_schema`] describes the JSON format for the context of a
+//! request for one action. Both accept the forms of values that Cedar accepts
+//! when parsing JSON with a schema: an entity reference may be written as
+//! `{ "type": ..., "id": ... }` or with the `__entity` escape, and an
+//! extension value may be written as the string argument of its constructor,
+//! as `{ "fn": ..., "arg": ... }`, or with the `__extn` escape.
+//!
+//! The documents are stricter than Cedar in two ways. They don't describe
+//! action entities, which Cedar takes from the schema, and they don't allow
+//! the `unknown` values used for partial evaluation.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use cedar_policy_core::ast::{EntityUID, Name};
+use cedar_policy_core::entities::SchemaType;
+use cedar_policy_core::extensions::Extensions;
+use serde_json::{json, Map, Value};
+
+use crate::types::{Attributes, EntityRecordKind, OpenTag, Primitive, Type};
+use crate::{ValidatorEntityType, ValidatorSchema};
+
+/// The JSON Schema dialect of the exported documents
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// A JSON Schema document describing the JSON format for a list of entities
+/// conforming to `schema`, as read by `Entities::from_json_value` and
+/// friends. Each entity must have one of the entity types in `schema`, and
+/// attributes, parents, and tags allowed by the declaration of that type.
+pub fn entities_json_schema(schema: &ValidatorSchema) -> Value {
+    let mut exporter = Exporter::new(schema);
+    let mut entity_types = schema.entity_types().collect::<Vec<_>>();
+    entity_types.sort_by_key(|(name, _)| *name);
+    let entities = any_of(entity_types.into_iter().map(|(name, entity_type)| {
+        let def = format!("entity:{name}");
+        let entity = exporter.entity(name, entity_type);
+        exporter.defs.insert(def.clone(), entity);
+        reference(&def)
+    }));
+    exporter.document(
+        "Cedar entities".to_string(),
+        json!({
+            "type": "array",
+            "items": entities,
+        }),
+    )
+}
+
+/// A JSON Schema document describing the JSON format for the context of a
+/// request for `action`, as read by `Context::from_json_value` and friends,
+/// or `None` if `action` is not declared in `schema`
+pub fn context_json_schema(schema: &ValidatorSchema, action: &EntityUID) -> Option<Value> {
+    let context = &schema.get_action_id(action)?.context;
+    let mut exporter = Exporter::new(schema);
+    let context = exporter.value(context);
+    Some(exporter.document(format!("Context for action {action}"), context))
+}
+
+/// Builds the JSON Schema for the values of types in a schema, collecting
+/// the definitions which those refer to
+struct Exporter<'a> {
+    schema: &'a ValidatorSchema,
+    /// Definitions of the document, by name
+    defs: BTreeMap<String, Value>,
+    /// Entity types which entity references have been exported for
+    uid_types: BTreeSet<Name>,
+}
+
+impl<'a> Exporter<'a> {
+    fn new(schema: &'a ValidatorSchema) -> Self {
+        Self {
+            schema,
+            defs: BTreeMap::new(),
+            uid_types: BTreeSet::new(),
+        }
+    }
+
+    /// A document with the title `title`, which describes the values allowed
+    /// by `root`, along with the definitions collected while building `root`
+    fn document(mut self, title: String, root: Value) -> Value {
+        for name in std::mem::take(&mut self.uid_types) {
+            let id = match self
+                .schema
+                .get_entity_type(&name)
+                .and_then(ValidatorEntityType::enum_choices)
+            {
+                Some(choices) => json!({ "enum": choices }),
+                None => json!({ "type": "string" }),
+            };
+            self.defs.insert(
+                format!("uid:{name}"),
+                entity_reference(json!({ "const": name.to_string() }), id),
+            );
+        }
+        let mut doc = Map::new();
+        doc.insert("$schema".to_string(), json!(DIALECT));
+        doc.insert("title".to_string(), json!(title));
+        match root {
+            Value::Object(root) => doc.extend(root),
+            root => {
+                doc.insert("allOf".to_string(), json!([root]));
+            }
+        }
+        if !self.defs.is_empty() {
+            doc.insert("$defs".to_string(), json!(self.defs));
+        }
+        Value::Object(doc)
+    }
+
+    /// The schema for an entity of type `name`, declared as `entity_type`
+    fn entity(&mut self, name: &Name, entity_type: &ValidatorEntityType) -> Value {
+        let mut parents = self
+            .schema
+            .entity_types()
+            .filter(|(_, parent)| parent.descendants.contains(name))
+            .map(|(parent, _)| parent)
+            .collect::<Vec<_>>();
+        parents.sort();
+        let parents = any_of(parents.into_iter().map(|parent| self.uid(parent)));
+        let tags = match entity_type.tag_type() {
+            Some(ty) => json!({
+                "type": "object",
+                "additionalProperties": self.value(ty),
+            }),
+            None => json!({
+                "type": "object",
+                "maxProperties": 0,
+            }),
+        };
+        json!({
+            "type": "object",
+            "properties": {
+                "uid": self.uid(name),
+                "attrs": self.record(&entity_type.attributes, entity_type.open_attributes),
+                "parents": {
+                    "type": "array",
+                    "items": parents,
+                },
+                "tags": tags,
+            },
+            "required": ["uid", "attrs", "parents"],
+        })
+    }
+
+    /// The schema for values of type `ty`
+    fn value(&mut self, ty: &Type) -> Value {
+        match ty {
+            Type::Never => Value::Bool(false),
+            Type::True => json!({ "const": true }),
+            Type::False => json!({ "const": false }),
+            Type::Primitive {
+                primitive_type: Primitive::Bool,
+            } => json!({ "type": "boolean" }),
+            Type::Primitive {
+                primitive_type: Primitive::Long,
+            } => json!({
+                "type": "integer",
+                "minimum": i64::MIN,
+                "maximum": i64::MAX,
+            }),
+            Type::Primitive {
+                primitive_type: Primitive::String,
+            } => json!({ "type": "string" }),
+            Type::Set { element_type } => match element_type {
+                Some(element_type) => json!({
+                    "type": "array",
+                    "items": self.value(element_type),
+                }),
+                None => json!({ "type": "array" }),
+            },
+            Type::EntityOrRecord(EntityRecordKind::Record {
+                attrs,
+                open_attributes,
+            }) => self.record(attrs, *open_attributes),
+            Type::EntityOrRecord(EntityRecordKind::Entity(lub)) => {
+                any_of(lub.iter().map(|name| self.uid(name)))
+            }
+            Type::EntityOrRecord(EntityRecordKind::ActionEntity { name, .. }) => entity_reference(
+                json!({ "const": name.to_string() }),
+                json!({ "type": "string" }),
+            ),
+            Type::EntityOrRecord(EntityRecordKind::AnyEntity) => {
+                entity_reference(json!({ "type": "string" }), json!({ "type": "string" }))
+            }
+            Type::ExtensionType { name } => extension_value(name),
+        }
+    }
+
+    /// The schema for records with the attributes `attrs`
+    fn record(&mut self, attrs: &Attributes, open_attributes: OpenTag) -> Value {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for (attr, attr_type) in attrs.iter() {
+            properties.insert(attr.to_string(), self.value(&attr_type.attr_type));
+            if attr_type.is_required {
+                required.push(attr.to_string());
+            }
+        }
+        json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": open_attributes.is_open(),
+        })
+    }
+
+    /// A reference to the definition of the schema for references to
+    /// entities of type `name`
+    fn uid(&mut self, name: &Name) -> Value {
+        self.uid_types.insert(name.clone());
+        reference(&format!("uid:{name}"))
+    }
+}
+
+/// A reference to the definition `def`
+fn reference(def: &str) -> Value {
+    json!({ "$ref": format!("#/$defs/{def}") })
+}
+
+/// The schema for values matching any of `schemas`
+fn any_of(schemas: impl IntoIterator<Item = Value>) -> Value {
+    let mut schemas = schemas.into_iter().collect::<Vec<_>>();
+    match schemas.len() {
+        0 => Value::Bool(false),
+        1 => schemas.remove(0),
+        _ => json!({ "anyOf": schemas }),
+    }
+}
+
+/// The schema for an entity reference, with or without the `__entity`
+/// escape, whose type and ID are described by `entity_type` and `id`
+fn entity_reference(entity_type: Value, id: Value) -> Value {
+    let type_and_id = json!({
+        "type": "object",
+        "properties": {
+            "type": entity_type,
+            "id": id,
+        },
+        "required": ["type", "id"],
+    });
+    json!({
+        "anyOf": [
+            type_and_id,
+            {
+                "type": "object",
+                "properties": { "__entity": type_and_id },
+                "required": ["__entity"],
+            },
+        ],
+    })
+}
+
+/// The schema for a value of the extension type `name`. Cedar calls the
+/// constructor which takes a string and returns a `name` when it is given
+/// just a string, so that is allowed as well as the `__extn` escape, which
+/// must call that constructor.
+fn extension_value(name: &Name) -> Value {
+    let extensions = Extensions::all_available();
+    let constructor = extensions
+        .lookup_single_arg_constructor(
+            &SchemaType::Extension { name: name.clone() },
+            &SchemaType::String,
+        )
+        .ok()
+        .flatten();
+    let call = |ext_fn: Value| {
+        json!({
+            "type": "object",
+            "properties": {
+                "fn": ext_fn,
+                "arg": { "type": "string" },
+            },
+            "required": ["fn", "arg"],
+        })
+    };
+    let call = match constructor {
+        Some(constructor) => call(json!({ "const": constructor.name().to_string() })),
+        None => call(json!({ "type": "string" })),
+    };
+    let escape = json!({
+        "type": "object",
+        "properties": { "__extn": call },
+        "required": ["__extn"],
+    });
+    match constructor {
+        Some(_) => any_of([json!({ "type": "string" }), call, escape]),
+        None => any_of([call, escape]),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn schema(src: &str) -> ValidatorSchema {
+        ValidatorSchema::from_str_natural(src, Extensions::all_available())
+            .unwrap()
+            .0
+    }
+
+    const SCHEMA: &str = r#"
+        entity Role enum ["admin", "viewer"];
+        entity Team in [Role];
+        entity User in [Team] {
+            name: String,
+            age?: Long,
+            manager?: User,
+        } tags Set<String>;
+        action view appliesTo {
+            principal: [User],
+            resource: [Team],
+            context: { mfa: Bool, source: ipaddr },
+        };
+    "#;
+
+    fn uid(entity_type: &str, id: Value) -> Value {
+        let type_and_id = json!({
+            "type": "object",
+            "properties": {
+                "type": { "const": entity_type },
+                "id": id,
+            },
+            "required": ["type", "id"],
+        });
+        json!({
+            "anyOf": [
+                type_and_id,
+                {
+                    "type": "object",
+                    "properties": { "__entity": type_and_id },
+                    "required": ["__entity"],
+                },
+            ],
+        })
+    }
+
+    #[test]
+    fn entities() {
+        let doc = entities_json_schema(&schema(SCHEMA));
+        assert_eq!(doc["$schema"], json!(DIALECT));
+        assert_eq!(doc["type"], json!("array"));
+        assert_eq!(
+            doc["items"],
+            json!({
+                "anyOf": [
+                    { "$ref": "#/$defs/entity:Role" },
+                    { "$ref": "#/$defs/entity:Team" },
+                    { "$ref": "#/$defs/entity:User" },
+                ],
+            })
+        );
+        assert_eq!(
+            doc["$defs"]["entity:User"],
+            json!({
+                "type": "object",
+                "properties": {
+                    "uid": { "$ref": "#/$defs/uid:User" },
+                    "attrs": {
+                        "type": "object",
+                        "properties": {
+                            "age": {
+                                "type": "integer",
+                                "minimum": i64::MIN,
+                                "maximum": i64::MAX,
+                            },
+                            "manager": { "$ref": "#/$defs/uid:User" },
+                            "name": { "type": "string" },
+                        },
+                        "required": ["name"],
+                        "additionalProperties": false,
+                    },
+                    "parents": {
+                        "type": "array",
+                        "items": {
+                            "anyOf": [
+                                { "$ref": "#/$defs/uid:Role" },
+                                { "$ref": "#/$defs/uid:Team" },
+                            ],
+                        },
+                    },
+                    "tags": {
+                        "type": "object",
+                        "additionalProperties": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                        },
+                    },
+                },
+                "required": ["uid", "attrs", "parents"],
+            })
+        );
+        assert_eq!(
+            doc["$defs"]["entity:Role"]["properties"]["parents"]["items"],
+            json!(false)
+        );
+        assert_eq!(
+            doc["$defs"]["entity:Role"]["properties"]["tags"],
+            json!({ "type": "object", "maxProperties": 0 })
+        );
+        assert_eq!(
+            doc["$defs"]["uid:Role"],
+            uid("Role", json!({ "enum": ["admin", "viewer"] }))
+        );
+        assert_eq!(
+            doc["$defs"]["uid:User"],
+            uid("User", json!({ "type": "string" }))
+        );
+    }
+
+    #[test]
+    fn context() {
+        let doc = context_json_schema(&schema(SCHEMA), &r#"Action::"view""#.parse().unwrap());
+        let ip = json!({
+            "type": "object",
+            "properties": {
+                "fn": { "const": "ip" },
+                "arg": { "type": "string" },
+            },
+            "required": ["fn", "arg"],
+        });
+        assert_eq!(
+            doc,
+            Some(json!({
+                "$schema": DIALECT,
+                "title": r#"Context for action Action::"view""#,
+                "type": "object",
+                "properties": {
+                    "mfa": { "type": "boolean" },
+                    "source": {
+                        "anyOf": [
+                            { "type": "string" },
+                            ip,
+                            {
+                                "type": "object",
+                                "properties": { "__extn": ip },
+                                "required": ["__extn"],
+                            },
+                        ],
+                    },
+                },
+                "required": ["mfa", "source"],
+                "additionalProperties": false,
+            }))
+        );
+    }
+
+    #[test]
+    fn context_references() {
+        let schema = schema(
+            r#"
+            entity User;
+            entity Group;
+            action view appliesTo {
+                principal: [User],
+                resource: [User],
+                context: { owners: Set<User> },
+            };
+            "#,
+        );
+        let doc = context_json_schema(&schema, &r#"Action::"view""#.parse().unwrap()).unwrap();
+        assert_eq!(
+            doc["properties"]["owners"],
+            json!({
+                "type": "array",
+                "items": { "$ref": "#/$defs/uid:User" },
+            })
+        );
+        assert_eq!(
+            doc["$defs"],
+            json!({ "uid:User": uid("User", json!({ "type": "string" })) })
+        );
+    }
+
+    #[test]
+    fn undeclared_action() {
+        assert_eq!(
+            context_json_schema(&schema(SCHEMA), &r#"Action::"edit""#.parse().unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn no_entity_types() {
+        let doc = entities_json_schema(&schema(""));
+        assert_eq!(
+            doc,
+            json!({
+                "$schema": DIALECT,
+                "title": "Cedar entities",
+                "type": "array",
+                "items": false,
+            })
+        );
+    }
+}
@@ -0,0 +1,230 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Checks that policies dereference entities at most a given number of
+//! levels away from the request.
+//!
+//! Reading the attributes, tags, or ancestors of `principal`, `resource`, an
+//! entity in `context`, or an entity literal is a level-1 dereference.
+//! Dereferencing an entity obtained by a level-`n` dereference, as in the
+//! `.dept` of `principal.manager.dept`, is a level-`n + 1` dereference.
+//! Action entities are defined by the schema, so reading their attributes
+//! or ancestors doesn't count as a dereference.
+
+use cedar_policy_core::ast::{BinaryOp, Expr, ExprKind, Template};
+use cedar_policy_core::parser::Loc;
+
+use crate::typecheck::{PolicyCheck, Typechecker};
+use crate::types::{EntityRecordKind, Type};
+use crate::{ValidationError, ValidationErrorKind, ValidationMode, Validator};
+
+impl Validator {
+    /// Check that `t` dereferences entities at most `max_deref_level` levels
+    /// from the request, in every request environment where it typechecks
+    pub(crate) fn check_deref_levels(
+        &self,
+        t: &Template,
+        mode: ValidationMode,
+        max_deref_level: u32,
+    ) -> Vec<ValidationError> {
+        let typechecker = Typechecker::new(&self.schema, mode);
+        let mut violations = Vec::new();
+        for (_, check) in typechecker.typecheck_by_request_env(t) {
+            if let PolicyCheck::Success(e) = check {
+                deref_level(&e, max_deref_level, &mut violations);
+            }
+        }
+        violations
+            .into_iter()
+            .map(|(loc, level)| {
+                ValidationError::with_policy_id(
+                    t.id().clone(),
+                    loc,
+                    ValidationErrorKind::entity_deref_level_violation(level, max_deref_level),
+                )
+            })
+            .collect()
+    }
+}
+
+/// The number of dereferences needed to compute the value of `e`. Any
+/// dereference beyond level `max` is added to `violations`, with its level.
+fn deref_level(e: &Expr<Option<Type>>, max: u32, violations: &mut Vec<(Option<Loc>, u32)>) -> u32 {
+    let mut level = |e| deref_level(e, max, violations);
+    match e.expr_kind() {
+        ExprKind::Lit(_) | ExprKind::Var(_) | ExprKind::Slot(_) | ExprKind::Unknown(_) => 0,
+        ExprKind::If {
+            test_expr,
+            then_expr,
+            else_expr,
+        } => {
+            level(test_expr);
+            level(then_expr).max(level(else_expr))
+        }
+        ExprKind::And { left, right } | ExprKind::Or { left, right } => {
+            level(left);
+            level(right);
+            0
+        }
+        ExprKind::UnaryApp { arg, .. }
+        | ExprKind::Like { expr: arg, .. }
+        | ExprKind::Is { expr: arg, .. } => {
+            level(arg);
+            0
+        }
+        ExprKind::BinaryApp { op, arg1, arg2 } => {
+            let arg1_level = level(arg1);
+            level(arg2);
+            match op {
+                BinaryOp::In | BinaryOp::HasTag => {
+                    dereference(e, arg1, arg1_level, max, violations);
+                    0
+                }
+                BinaryOp::GetTag => dereference(e, arg1, arg1_level, max, violations),
+                _ => 0,
+            }
+        }
+        ExprKind::ExtensionFunctionApp { args, .. } => {
+            for arg in args.iter() {
+                level(arg);
+            }
+            0
+        }
+        ExprKind::GetAttr { expr, .. } => {
+            let expr_level = level(expr);
+            dereference(e, expr, expr_level, max, violations)
+        }
+        ExprKind::HasAttr { expr, .. } => {
+            let expr_level = level(expr);
+            dereference(e, expr, expr_level, max, violations);
+            0
+        }
+        ExprKind::Set(elements) => elements.iter().map(level).max().unwrap_or(0),
+        ExprKind::Record(fields) => fields.values().map(level).max().unwrap_or(0),
+    }
+}
+
+/// The level of `e`, which reads data from `operand`, a value computed with
+/// `operand_level` dereferences. If `operand` is an entity other than an
+/// action, this is a dereference, which is checked against `max`.
+fn dereference(
+    e: &Expr<Option<Type>>,
+    operand: &Expr<Option<Type>>,
+    operand_level: u32,
+    max: u32,
+    violations: &mut Vec<(Option<Loc>, u32)>,
+) -> u32 {
+    match operand.data() {
+        Some(Type::EntityOrRecord(
+            EntityRecordKind::Record { .. } | EntityRecordKind::ActionEntity { .. },
+        )) => operand_level,
+        _ => {
+            let level = operand_level.saturating_add(1);
+            let violation = (e.source_loc().cloned(), level);
+            // the same violation is found in each request environment
+            if level > max && !violations.contains(&violation) {
+                violations.push(violation);
+            }
+            level
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ValidationResult, ValidatorSchema};
+    use cedar_policy_core::parser::parse_policyset;
+
+    fn validate(src: &str, max_deref_level: u32) -> ValidationResult {
+        let schema = ValidatorSchema::from_str_natural(
+            r#"
+            entity Department { name: String };
+            entity User in [Department] {
+                manager: User,
+                dept: Department,
+                profile: { team: Department, level: Long },
+            } tags String;
+            entity Document { owner: User };
+            action view appliesTo {
+                principal: [User],
+                resource: [Document],
+                context: { reviewer: User },
+            };
+            "#,
+            cedar_policy_core::extensions::Extensions::all_available(),
+        )
+        .unwrap()
+        .0;
+        Validator::new(schema).validate_with_level(
+            &parse_policyset(src).unwrap(),
+            ValidationMode::Strict,
+            max_deref_level,
+        )
+    }
+
+    /// The levels of the violations found
+    fn violations(src: &str, max_deref_level: u32) -> Vec<u32> {
+        validate(src, max_deref_level)
+            .validation_errors()
+            .map(|e| match e.error_kind() {
+                ValidationErrorKind::EntityDerefLevelViolation(v) => v.actual_level(),
+                kind => panic!("unexpected error {kind}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn attribute_chains() {
+        let policy =
+            r#"permit(principal, action, resource) when { principal.manager.dept.name == "eng" };"#;
+        assert_eq!(violations(policy, 3), Vec::<u32>::new());
+        assert_eq!(violations(policy, 2), [3]);
+        assert_eq!(violations(policy, 1), [2, 3]);
+    }
+
+    #[test]
+    fn records_and_context() {
+        // reading a record attribute is not a dereference
+        let policy = r#"permit(principal, action, resource) when { principal.profile.level > 3 && context.reviewer == principal };"#;
+        assert!(validate(policy, 1).validation_passed());
+        let policy =
+            r#"permit(principal, action, resource) when { principal.profile.team.name == "eng" };"#;
+        assert_eq!(violations(policy, 1), [2]);
+        let policy = r#"permit(principal, action, resource) when { context.reviewer.dept == principal.dept };"#;
+        assert!(validate(policy, 1).validation_passed());
+        assert_eq!(violations(policy, 0), [1, 1]);
+    }
+
+    #[test]
+    fn ancestors_and_tags() {
+        let policy = r#"permit(principal in Department::"eng", action, resource);"#;
+        assert!(validate(policy, 1).validation_passed());
+        assert_eq!(violations(policy, 0), [1]);
+        let policy =
+            r#"permit(principal, action, resource) when { resource.owner in principal.dept };"#;
+        assert_eq!(violations(policy, 1), [2]);
+        let policy = r#"permit(principal, action, resource) when { resource.owner.hasTag("x") && resource.owner.getTag("x") == "y" };"#;
+        assert_eq!(violations(policy, 1), [2, 2]);
+        assert!(validate(policy, 2).validation_passed());
+    }
+
+    #[test]
+    fn actions_are_free() {
+        let policy = r#"permit(principal == User::"alice", action in [Action::"view"], resource == Document::"d");"#;
+        assert!(validate(policy, 0).validation_passed());
+    }
+}
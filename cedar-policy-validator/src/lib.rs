@@ -29,6 +29,7 @@ mod expr_iterator;
 mod extension_schema;
 mod extensions;
 mod fuzzy_match;
+mod level_validate;
 mod validation_result;
 pub use validation_result::*;
 mod rbac;
@@ -40,7 +41,13 @@ mod str_checks;
 pub use str_checks::confusable_string_checks;
 mod type_error;
 pub use type_error::*;
+#[cfg(feature = "partial-eval")]
+pub mod analysis;
+pub mod entity_manifest;
 pub mod human_schema;
+pub mod json_schema_export;
+pub mod lint;
+pub mod schema_diff;
 pub mod typecheck;
 use typecheck::Typechecker;
 pub mod types;
@@ -110,6 +117,64 @@ impl Validator {
         )
     }
 
+    /// Validate all templates, links, and static policies in a policy set,
+    /// and if they pass strict validation, also analyze each static policy
+    /// symbolically, adding warnings for policies that apply to no request
+    /// or whose conditions always hold.
+    ///
+    /// The analysis searches the requests of every action in the schema for
+    /// each policy, so it can be much slower than [`Validator::validate()`].
+    #[cfg(feature = "partial-eval")]
+    pub fn validate_with_analysis(
+        &self,
+        policies: &PolicySet,
+        mode: ValidationMode,
+    ) -> ValidationResult {
+        let result = self.validate(policies, mode);
+        if mode != ValidationMode::Strict || !result.validation_passed() {
+            return result;
+        }
+        let (errors, warnings) = result.into_errors_and_warnings();
+        let mut warnings = warnings.collect::<Vec<_>>();
+        warnings.extend(analysis::policy_warnings(&self.schema, policies, &warnings));
+        ValidationResult::new(errors, warnings)
+    }
+
+    /// Validate all templates, links, and static policies in a policy set,
+    /// and check the static policies and templates against the rules enabled
+    /// in `linter`.
+    pub fn validate_with_linter(
+        &self,
+        policies: &PolicySet,
+        mode: ValidationMode,
+        linter: &lint::Linter,
+    ) -> ValidationResult {
+        let (errors, warnings) = self.validate(policies, mode).into_errors_and_warnings();
+        let (lint_errors, lint_warnings) = linter.lint(policies);
+        ValidationResult::new(errors.chain(lint_errors), warnings.chain(lint_warnings))
+    }
+
+    /// Validate all templates, links, and static policies in a policy set,
+    /// and also check that no static policy or template dereferences entities
+    /// more than `max_deref_level` levels from the request. For instance,
+    /// `principal.manager` is a level-1 dereference and
+    /// `principal.manager.dept` a level-2 one.
+    ///
+    /// The level check only applies to policies that typecheck, so it adds
+    /// errors only where `validate` reports none.
+    pub fn validate_with_level(
+        &self,
+        policies: &PolicySet,
+        mode: ValidationMode,
+        max_deref_level: u32,
+    ) -> ValidationResult {
+        let (errors, warnings) = self.validate(policies, mode).into_errors_and_warnings();
+        let level_errors = policies
+            .all_templates()
+            .flat_map(|t| self.check_deref_levels(t, mode, max_deref_level));
+        ValidationResult::new(errors.chain(level_errors), warnings)
+    }
+
     /// Run all validations against a single static policy or template (note
     /// that Core `Template` includes static policies as well), gathering all
     /// validation errors and warnings in the returned iterators.
@@ -227,6 +292,9 @@ mod test {
                     EntityType {
                         member_of_types: vec![],
                         shape: AttributesOrContext::default(),
+                        tags: None,
+                        choices: None,
+                        annotations: Annotations::default(),
                     },
                 ),
                 (
@@ -234,6 +302,9 @@ mod test {
                     EntityType {
                         member_of_types: vec![],
                         shape: AttributesOrContext::default(),
+                        tags: None,
+                        choices: None,
+                        annotations: Annotations::default(),
                     },
                 ),
             ],
@@ -247,6 +318,7 @@ mod test {
                     }),
                     member_of: None,
                     attributes: None,
+                    annotations: Annotations::default(),
                 },
             )],
         );
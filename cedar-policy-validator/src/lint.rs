@@ -0,0 +1,452 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Configurable lint rules for policies.
+//!
+//! A [`Linter`] holds a list of [`LintRule`]s, each enabled with a
+//! [`LintSeverity`]. [`crate::Validator::validate_with_linter`] runs them on
+//! every static policy and template, reporting what they find as
+//! [`crate::ValidationWarningKind::Lint`] warnings or
+//! [`crate::ValidationErrorKind::Lint`] errors. Besides implementing
+//! [`LintRule`], callers can enable the rules defined here by name with
+//! [`Linter::add_builtin`].
+
+use cedar_policy_core::ast::{
+    AnyId, Effect, ExprKind, PatternElem, PolicySet, PrincipalOrResourceConstraint, Template,
+};
+use cedar_policy_core::parser::Loc;
+use miette::Diagnostic;
+use smol_str::SmolStr;
+use thiserror::Error;
+
+use crate::{ValidationError, ValidationErrorKind, ValidationWarning, ValidationWarningKind};
+
+/// A check on individual policies
+pub trait LintRule: std::fmt::Debug + Send + Sync {
+    /// Identifier for the rule, included in the violations it reports
+    fn id(&self) -> &str;
+
+    /// Check a static policy or template, returning a message for each
+    /// problem found, with the location of the problem if it is narrower
+    /// than the whole policy
+    fn check(&self, policy: &Template) -> Vec<(String, Option<Loc>)>;
+}
+
+/// How violations of a lint rule are reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintSeverity {
+    /// Report violations as validation warnings
+    Warning,
+    /// Report violations as validation errors, failing validation
+    Error,
+}
+
+/// A problem a lint rule found in a policy
+#[derive(Debug, Clone, Diagnostic, Error, PartialEq, Eq, Hash)]
+#[error("policy violates lint rule `{rule}`: {message}")]
+pub struct LintViolation {
+    rule: SmolStr,
+    message: String,
+}
+
+impl LintViolation {
+    /// The id of the rule that was violated
+    pub fn rule(&self) -> &str {
+        &self.rule
+    }
+
+    /// Description of the problem
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// Errors enabling a built-in lint rule
+#[derive(Debug, Diagnostic, Error)]
+pub enum LintConfigError {
+    /// There is no built-in rule with this id
+    #[error("unknown lint rule `{0}`")]
+    #[diagnostic(help(
+        "the available rules are `require-annotation`, `unscoped-permit`, `max-complexity`, and `leading-wildcard-like`"
+    ))]
+    UnknownRule(String),
+    /// The configuration given for a rule is not valid
+    #[error("invalid configuration `{config}` for lint rule `{rule}`: {reason}")]
+    InvalidConfig {
+        /// The rule
+        rule: String,
+        /// The configuration given
+        config: String,
+        /// Why the configuration is not valid
+        reason: String,
+    },
+}
+
+/// A set of lint rules, each with the severity its violations are reported
+/// with
+#[derive(Debug, Default)]
+pub struct Linter {
+    rules: Vec<(Box<dyn LintRule>, LintSeverity)>,
+}
+
+impl Linter {
+    /// A linter with no rules enabled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable `rule`
+    pub fn add_rule(&mut self, rule: impl LintRule + 'static, severity: LintSeverity) {
+        self.rules.push((Box::new(rule), severity));
+    }
+
+    /// Enable a built-in rule given as `ID` or `ID=CONFIG`, e.g.
+    /// `max-complexity=50`
+    pub fn add_builtin(
+        &mut self,
+        spec: &str,
+        severity: LintSeverity,
+    ) -> Result<(), LintConfigError> {
+        let (id, config) = match spec.split_once('=') {
+            Some((id, config)) => (id.trim(), Some(config.trim())),
+            None => (spec.trim(), None),
+        };
+        let invalid = |reason: &str| LintConfigError::InvalidConfig {
+            rule: id.to_string(),
+            config: config.unwrap_or_default().to_string(),
+            reason: reason.to_string(),
+        };
+        match id {
+            "require-annotation" => {
+                let key = config
+                    .unwrap_or("id")
+                    .parse()
+                    .map_err(|_| invalid("expected an annotation key"))?;
+                self.add_rule(RequireAnnotation { key }, severity);
+            }
+            "unscoped-permit" => {
+                if config.is_some() {
+                    return Err(invalid("this rule takes no configuration"));
+                }
+                self.add_rule(UnscopedPermit, severity);
+            }
+            "max-complexity" => {
+                let max = config
+                    .map_or(Ok(100), str::parse)
+                    .map_err(|_| invalid("expected a number of expression nodes"))?;
+                self.add_rule(MaxComplexity { max }, severity);
+            }
+            "leading-wildcard-like" => {
+                if config.is_some() {
+                    return Err(invalid("this rule takes no configuration"));
+                }
+                self.add_rule(LeadingWildcardLike, severity);
+            }
+            _ => return Err(LintConfigError::UnknownRule(id.to_string())),
+        }
+        Ok(())
+    }
+
+    /// Run every rule on the static policies and templates in `policies`
+    pub(crate) fn lint(
+        &self,
+        policies: &PolicySet,
+    ) -> (Vec<ValidationError>, Vec<ValidationWarning>) {
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        for policy in policies.all_templates() {
+            for (rule, severity) in &self.rules {
+                for (message, loc) in rule.check(policy) {
+                    let violation = LintViolation {
+                        rule: rule.id().into(),
+                        message,
+                    };
+                    let loc = loc.or_else(|| policy.loc().clone());
+                    match severity {
+                        LintSeverity::Warning => warnings.push(ValidationWarning::with_policy_id(
+                            policy.id().clone(),
+                            loc,
+                            ValidationWarningKind::Lint(violation),
+                        )),
+                        LintSeverity::Error => errors.push(ValidationError::with_policy_id(
+                            policy.id().clone(),
+                            loc,
+                            ValidationErrorKind::Lint(violation),
+                        )),
+                    }
+                }
+            }
+        }
+        (errors, warnings)
+    }
+}
+
+/// Every policy must have the annotation `key`
+#[derive(Debug, Clone)]
+pub struct RequireAnnotation {
+    /// The annotation key
+    pub key: AnyId,
+}
+
+impl LintRule for RequireAnnotation {
+    fn id(&self) -> &str {
+        "require-annotation"
+    }
+
+    fn check(&self, policy: &Template) -> Vec<(String, Option<Loc>)> {
+        match policy.annotation(&self.key) {
+            Some(_) => Vec::new(),
+            None => vec![(
+                format!("policy is missing the `@{}` annotation", self.key),
+                None,
+            )],
+        }
+    }
+}
+
+/// `permit` policies must constrain their principal or resource in their
+/// scope
+#[derive(Debug, Clone)]
+pub struct UnscopedPermit;
+
+impl LintRule for UnscopedPermit {
+    fn id(&self) -> &str {
+        "unscoped-permit"
+    }
+
+    fn check(&self, policy: &Template) -> Vec<(String, Option<Loc>)> {
+        let unconstrained =
+            |c: &PrincipalOrResourceConstraint| matches!(c, PrincipalOrResourceConstraint::Any);
+        if policy.effect() == Effect::Permit
+            && unconstrained(policy.principal_constraint().as_inner())
+            && unconstrained(policy.resource_constraint().as_inner())
+        {
+            vec![(
+                "`permit` policy constrains neither the principal nor the resource in its scope"
+                    .to_string(),
+                None,
+            )]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Policies, including their scope, may contain at most `max` expression
+/// nodes
+#[derive(Debug, Clone)]
+pub struct MaxComplexity {
+    /// The largest allowed number of expression nodes
+    pub max: usize,
+}
+
+impl LintRule for MaxComplexity {
+    fn id(&self) -> &str {
+        "max-complexity"
+    }
+
+    fn check(&self, policy: &Template) -> Vec<(String, Option<Loc>)> {
+        let size = policy.condition().subexpressions().count();
+        if size > self.max {
+            vec![(
+                format!(
+                    "policy has {size} expression nodes, more than the limit of {}",
+                    self.max
+                ),
+                None,
+            )]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// `like` patterns may not start with a wildcard, which makes them hard to
+/// match efficiently and easy to match by accident
+#[derive(Debug, Clone)]
+pub struct LeadingWildcardLike;
+
+impl LintRule for LeadingWildcardLike {
+    fn id(&self) -> &str {
+        "leading-wildcard-like"
+    }
+
+    fn check(&self, policy: &Template) -> Vec<(String, Option<Loc>)> {
+        policy
+            .condition()
+            .subexpressions()
+            .filter_map(|e| match e.expr_kind() {
+                ExprKind::Like { pattern, .. }
+                    if pattern.get_elems().first() == Some(&PatternElem::Wildcard) =>
+                {
+                    Some((
+                        format!("`like` pattern \"{pattern}\" starts with a wildcard"),
+                        e.source_loc().cloned(),
+                    ))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ValidationMode, Validator, ValidatorSchema};
+    use cedar_policy_core::parser::parse_policyset;
+
+    fn validate(linter: &Linter, src: &str) -> crate::ValidationResult {
+        let schema = ValidatorSchema::from_str_natural(
+            r#"
+            entity User { name: String };
+            entity Photo;
+            action view appliesTo { principal: [User], resource: [Photo] };
+            "#,
+            cedar_policy_core::extensions::Extensions::all_available(),
+        )
+        .unwrap()
+        .0;
+        Validator::new(schema).validate_with_linter(
+            &parse_policyset(src).unwrap(),
+            ValidationMode::Strict,
+            linter,
+        )
+    }
+
+    fn violations<'a>(kinds: impl Iterator<Item = &'a LintViolation>) -> Vec<(String, String)> {
+        let mut violations = kinds
+            .map(|v| (v.rule().to_string(), v.message().to_string()))
+            .collect::<Vec<_>>();
+        violations.sort();
+        violations
+    }
+
+    #[test]
+    fn builtin_rules() {
+        let mut linter = Linter::new();
+        linter
+            .add_builtin("require-annotation", LintSeverity::Warning)
+            .unwrap();
+        linter
+            .add_builtin("unscoped-permit", LintSeverity::Error)
+            .unwrap();
+        linter
+            .add_builtin("max-complexity = 10", LintSeverity::Warning)
+            .unwrap();
+        linter
+            .add_builtin("leading-wildcard-like", LintSeverity::Warning)
+            .unwrap();
+        let result = validate(
+            &linter,
+            r#"
+            @id("scoped")
+            permit(principal == User::"alice", action, resource);
+            permit(principal, action, resource) when { principal.name like "*admin" };
+            @id("template")
+            forbid(principal == ?principal, action, resource)
+            when { principal.name == "a" || principal.name == "b" || principal.name == "c" };
+            "#,
+        );
+        assert!(!result.validation_passed());
+        let errors = violations(
+            result
+                .validation_errors()
+                .filter_map(|e| match e.error_kind() {
+                    ValidationErrorKind::Lint(v) => Some(v),
+                    _ => None,
+                }),
+        );
+        assert_eq!(
+            errors,
+            [(
+                "unscoped-permit".to_string(),
+                "`permit` policy constrains neither the principal nor the resource in its scope"
+                    .to_string()
+            )]
+        );
+        let warnings = violations(result.validation_warnings().filter_map(|w| match w.kind() {
+            ValidationWarningKind::Lint(v) => Some(v),
+            _ => None,
+        }));
+        assert_eq!(
+            warnings,
+            [
+                (
+                    "leading-wildcard-like".to_string(),
+                    "`like` pattern \"*admin\" starts with a wildcard".to_string()
+                ),
+                (
+                    "max-complexity".to_string(),
+                    "policy has 22 expression nodes, more than the limit of 10".to_string()
+                ),
+                (
+                    "require-annotation".to_string(),
+                    "policy is missing the `@id` annotation".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_rules() {
+        #[derive(Debug)]
+        struct NoForbid;
+        impl LintRule for NoForbid {
+            fn id(&self) -> &str {
+                "no-forbid"
+            }
+            fn check(&self, policy: &Template) -> Vec<(String, Option<Loc>)> {
+                match policy.effect() {
+                    Effect::Forbid => vec![("forbid policies are not allowed".to_string(), None)],
+                    Effect::Permit => Vec::new(),
+                }
+            }
+        }
+        let mut linter = Linter::new();
+        linter.add_rule(NoForbid, LintSeverity::Warning);
+        let result = validate(
+            &linter,
+            r#"
+            permit(principal, action, resource);
+            forbid(principal, action, resource);
+            "#,
+        );
+        assert!(result.validation_passed());
+        assert_eq!(result.validation_warnings().count(), 1);
+    }
+
+    #[test]
+    fn invalid_config() {
+        let mut linter = Linter::new();
+        assert!(matches!(
+            linter.add_builtin("no-such-rule", LintSeverity::Warning),
+            Err(LintConfigError::UnknownRule(_))
+        ));
+        assert!(matches!(
+            linter.add_builtin("max-complexity=lots", LintSeverity::Warning),
+            Err(LintConfigError::InvalidConfig { .. })
+        ));
+        assert!(matches!(
+            linter.add_builtin("unscoped-permit=yes", LintSeverity::Warning),
+            Err(LintConfigError::InvalidConfig { .. })
+        ));
+        assert!(matches!(
+            linter.add_builtin("require-annotation=not a key", LintSeverity::Warning),
+            Err(LintConfigError::InvalidConfig { .. })
+        ));
+    }
+}
@@ -34,7 +34,9 @@ use super::{
 
 impl Validator {
     /// Generate `UnrecognizedEntityType` error for every entity type in the
-    /// expression that could not also be found in the schema.
+    /// expression that could not also be found in the schema, and
+    /// `InvalidEnumEntity` error for every entity of an enumerated entity type
+    /// with an undeclared entity ID.
     pub(crate) fn validate_entity_types<'a>(
         &'a self,
         template: &'a Template,
@@ -78,11 +80,40 @@ impl Validator {
                             ValidationErrorKind::unspecified_entity(euid.eid().to_string()),
                         ))
                     }
-                    cedar_policy_core::ast::EntityType::Specified(_) => None,
+                    cedar_policy_core::ast::EntityType::Specified(_) => {
+                        self.validate_enum_entity(euid).map(|kind| {
+                            ValidationError::with_policy_id(
+                                template.id().clone(),
+                                euid.loc().cloned(),
+                                kind,
+                            )
+                        })
+                    }
                 }
             }))
     }
 
+    /// Generate an `InvalidEnumEntity` error if `euid` has an enumerated
+    /// entity type and an entity ID that type doesn't declare.
+    fn validate_enum_entity(&self, euid: &EntityUID) -> Option<ValidationErrorKind> {
+        let name = match euid.entity_type() {
+            ast::EntityType::Specified(name) => name,
+            ast::EntityType::Unspecified => return None,
+        };
+        let entity_type = self.schema.get_entity_type(name)?;
+        if entity_type.allows_eid(euid.eid()) {
+            return None;
+        }
+        // Suggest the declared entity ID closest to the one in the policy
+        let choices = entity_type.enum_choices().unwrap_or_default();
+        let suggested_entity = fuzzy_search(euid.eid().as_ref(), choices)
+            .map(|eid| EntityUID::from_components(name.clone(), ast::Eid::new(eid), None));
+        Some(ValidationErrorKind::invalid_enum_entity(
+            euid.to_string(),
+            suggested_entity.map(|euid| euid.to_string()),
+        ))
+    }
+
     /// Generate `UnrecognizedActionId` error for every entity id with an action
     /// entity type where the id could not be found in the actions list from the
     /// schema.
@@ -125,7 +156,8 @@ impl Validator {
 
     /// Generate `UnrecognizedEntityType` or `UnspecifiedEntityError` error for
     /// every entity type in the slot environment that is either not in the schema,
-    /// or unspecified.
+    /// or unspecified, and `InvalidEnumEntity` error for every entity with an
+    /// entity ID its enumerated entity type doesn't declare.
     pub(crate) fn validate_entity_types_in_slots<'a>(
         &'a self,
         slots: &'a SlotEnv,
@@ -154,7 +186,7 @@ impl Validator {
                             suggested_entity_type,
                         ))
                     } else {
-                        None
+                        self.validate_enum_entity(euid)
                     }
                 }
             }
@@ -551,6 +583,9 @@ mod test {
                 EntityType {
                     member_of_types: vec![],
                     shape: AttributesOrContext::default(),
+                    tags: None,
+                    choices: None,
+                    annotations: crate::Annotations::default(),
                 },
             )],
             [],
@@ -587,6 +622,9 @@ mod test {
                 EntityType {
                     member_of_types: vec![],
                     shape: AttributesOrContext::default(),
+                    tags: None,
+                    choices: None,
+                    annotations: crate::Annotations::default(),
                 },
             )],
             [],
@@ -639,6 +677,7 @@ mod test {
                     applies_to: None,
                     member_of: None,
                     attributes: None,
+                    annotations: crate::Annotations::default(),
                 },
             )],
         );
@@ -674,6 +713,9 @@ mod test {
                 EntityType {
                     member_of_types: vec![],
                     shape: AttributesOrContext::default(),
+                    tags: None,
+                    choices: None,
+                    annotations: crate::Annotations::default(),
                 },
             )],
             [],
@@ -698,6 +740,9 @@ mod test {
                 EntityType {
                     member_of_types: vec![],
                     shape: AttributesOrContext::default(),
+                    tags: None,
+                    choices: None,
+                    annotations: crate::Annotations::default(),
                 },
             )],
             [],
@@ -722,6 +767,9 @@ mod test {
                 EntityType {
                     member_of_types: vec![],
                     shape: AttributesOrContext::default(),
+                    tags: None,
+                    choices: None,
+                    annotations: crate::Annotations::default(),
                 },
             )],
             [],
@@ -767,6 +815,7 @@ mod test {
                     applies_to: None,
                     member_of: None,
                     attributes: None,
+                    annotations: crate::Annotations::default(),
                 },
             )],
         );
@@ -942,6 +991,7 @@ mod test {
                     applies_to: None,
                     member_of: None,
                     attributes: None,
+                    annotations: crate::Annotations::default(),
                 },
             )],
         );
@@ -971,6 +1021,7 @@ mod test {
                     applies_to: None,
                     member_of: None,
                     attributes: None,
+                    annotations: crate::Annotations::default(),
                 },
             )],
         );
@@ -1000,6 +1051,7 @@ mod test {
                     applies_to: None,
                     member_of: None,
                     attributes: None,
+                    annotations: crate::Annotations::default(),
                 },
             )],
         );
@@ -1027,6 +1079,9 @@ mod test {
                 EntityType {
                     member_of_types: vec![],
                     shape: AttributesOrContext::default(),
+                    tags: None,
+                    choices: None,
+                    annotations: crate::Annotations::default(),
                 },
             )],
             [],
@@ -1063,6 +1118,9 @@ mod test {
                     EntityType {
                         member_of_types: vec![],
                         shape: AttributesOrContext::default(),
+                        tags: None,
+                        choices: None,
+                        annotations: crate::Annotations::default(),
                     },
                 ),
                 (
@@ -1070,6 +1128,9 @@ mod test {
                     EntityType {
                         member_of_types: vec![],
                         shape: AttributesOrContext::default(),
+                        tags: None,
+                        choices: None,
+                        annotations: crate::Annotations::default(),
                     },
                 ),
             ],
@@ -1083,6 +1144,7 @@ mod test {
                     }),
                     member_of: Some(vec![]),
                     attributes: None,
+                    annotations: crate::Annotations::default(),
                 },
             )],
         )
@@ -1420,6 +1482,9 @@ mod test {
                     EntityType {
                         member_of_types: vec![],
                         shape: AttributesOrContext::default(),
+                        tags: None,
+                        choices: None,
+                        annotations: crate::Annotations::default(),
                     },
                 ),
                 (
@@ -1427,6 +1492,9 @@ mod test {
                     EntityType {
                         member_of_types: vec![resource_parent_type.parse().unwrap()],
                         shape: AttributesOrContext::default(),
+                        tags: None,
+                        choices: None,
+                        annotations: crate::Annotations::default(),
                     },
                 ),
                 (
@@ -1434,6 +1502,9 @@ mod test {
                     EntityType {
                         member_of_types: vec![resource_grandparent_type.parse().unwrap()],
                         shape: AttributesOrContext::default(),
+                        tags: None,
+                        choices: None,
+                        annotations: crate::Annotations::default(),
                     },
                 ),
                 (
@@ -1441,6 +1512,9 @@ mod test {
                     EntityType {
                         member_of_types: vec![],
                         shape: AttributesOrContext::default(),
+                        tags: None,
+                        choices: None,
+                        annotations: crate::Annotations::default(),
                     },
                 ),
             ],
@@ -1458,6 +1532,7 @@ mod test {
                             id: action_parent_name.into(),
                         }]),
                         attributes: None,
+                        annotations: crate::Annotations::default(),
                     },
                 ),
                 (
@@ -1469,6 +1544,7 @@ mod test {
                             id: action_grandparent_name.into(),
                         }]),
                         attributes: None,
+                        annotations: crate::Annotations::default(),
                     },
                 ),
                 (
@@ -1477,6 +1553,7 @@ mod test {
                         applies_to: None,
                         member_of: Some(vec![]),
                         attributes: None,
+                        annotations: crate::Annotations::default(),
                     },
                 ),
             ],
@@ -1673,6 +1750,64 @@ mod test {
         let (template, _) = Template::link_static_policy(policy);
         assert_validate_policy_flags_impossible_policy(&validator, &template);
     }
+
+    fn enum_schema() -> ValidatorSchema {
+        ValidatorSchema::from_str_natural(
+            r#"
+            entity Role enum ["admin", "viewer"];
+            entity User { role: Role };
+            action view appliesTo { principal: [User, Role], resource: [User] };
+            "#,
+            cedar_policy_core::extensions::Extensions::all_available(),
+        )
+        .unwrap()
+        .0
+    }
+
+    #[test]
+    fn enum_entities_in_policy() {
+        let validator = Validator::new(enum_schema());
+        let src = r#"permit(principal == Role::"admin", action, resource) when { resource.role == Role::"viewer" };"#;
+        let policy = parse_policy_template(None, src).unwrap();
+        assert_eq!(validator.validate_entity_types(&policy).count(), 0);
+
+        let src =
+            r#"permit(principal, action, resource) when { principal.role == Role::"admni" };"#;
+        let policy = parse_policy_template(None, src).unwrap();
+        let notes: Vec<ValidationError> = validator.validate_entity_types(&policy).collect();
+        assert_eq!(notes.len(), 1, "{:?}", notes);
+        expect_err(
+            src,
+            &Report::new(notes[0].clone()),
+            &ExpectedErrorMessageBuilder::error(r#"entity `Role::"admni"` is not one of the entities declared by its enumerated entity type"#)
+                .help(r#"did you mean `Role::"admin"`?"#)
+                .exactly_one_underline(r#"Role::"admni""#)
+                .build(),
+        );
+    }
+
+    #[test]
+    fn enum_entities_in_slots() {
+        let validator = Validator::new(enum_schema());
+        let env = HashMap::from([(
+            ast::SlotId::principal(),
+            EntityUID::with_eid_and_type("Role", "viewer").unwrap(),
+        )]);
+        assert_eq!(validator.validate_entity_types_in_slots(&env).count(), 0);
+
+        let env = HashMap::from([(
+            ast::SlotId::principal(),
+            EntityUID::with_eid_and_type("Role", "editor").unwrap(),
+        )]);
+        let notes: Vec<ValidationErrorKind> =
+            validator.validate_entity_types_in_slots(&env).collect();
+        assert_matches!(
+            notes.as_slice(),
+            [ValidationErrorKind::InvalidEnumEntity(e)] => {
+                assert_eq!(e.actual_entity, r#"Role::"editor""#);
+            }
+        );
+    }
 }
 
 #[cfg(test)]
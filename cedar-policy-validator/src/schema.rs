@@ -23,7 +23,10 @@
 use std::collections::{hash_map::Entry, BTreeMap, HashMap, HashSet};
 
 use cedar_policy_core::{
-    ast::{Entity, EntityType, EntityUID, Name},
+    ast::{
+        ActionConstraint, Entity, EntityReference, EntityType, EntityUID, Name, Policy,
+        PrincipalOrResourceConstraint,
+    },
     entities::{err::EntitiesError, Entities, TCComputation},
     extensions::Extensions,
     transitive_closure::compute_tc,
@@ -304,6 +307,10 @@ impl ValidatorSchema {
                 .ok_or(SchemaError::ContextOrShapeNotRecord(
                     ContextOrShape::EntityTypeShape(name.clone()),
                 ))?;
+                let tags = entity_type
+                    .tags
+                    .map(|tags| tags.resolve_type_defs(&type_defs))
+                    .transpose()?;
                 Ok((
                     name.clone(),
                     ValidatorEntityType {
@@ -311,6 +318,8 @@ impl ValidatorSchema {
                         descendants,
                         attributes,
                         open_attributes,
+                        tags,
+                        choices: entity_type.choices,
                     },
                 ))
             })
@@ -408,6 +417,9 @@ impl ValidatorSchema {
                     &mut undeclared_e,
                 );
             }
+            if let Some(tag_type) = entity_type.tag_type() {
+                Self::check_undeclared_in_type(tag_type, entity_types, &mut undeclared_e);
+            }
         }
 
         // Undeclared actions in a `memberOf` list.
@@ -594,6 +606,88 @@ impl ValidatorSchema {
             .map(|v| v.into_iter().flatten().collect::<Vec<_>>())
     }
 
+    /// Could `policy` apply to a request whose principal has type
+    /// `principal_type`, whose action is `action`, and whose resource has type
+    /// `resource_type`?
+    ///
+    /// This looks only at the policy scope and at the `appliesTo` declaration
+    /// for `action`, so it over-approximates: a `true` result says nothing
+    /// about whether the policy's `when` and `unless` conditions can hold.
+    /// A `false` result means the policy cannot be satisfied by any request
+    /// of this shape which conforms to the schema. When `action` is not
+    /// declared in the schema, or does not apply to the given principal and
+    /// resource types, this is `false` for every policy.
+    pub fn policy_may_apply(
+        &self,
+        policy: &Policy,
+        principal_type: &Name,
+        action: &EntityUID,
+        resource_type: &Name,
+    ) -> bool {
+        let Some(action_id) = self.get_action_id(action) else {
+            return false;
+        };
+        action_id
+            .applies_to
+            .is_applicable_principal_type(&EntityType::Specified(principal_type.clone()))
+            && action_id
+                .applies_to
+                .is_applicable_resource_type(&EntityType::Specified(resource_type.clone()))
+            && self.action_constraint_may_match(policy.action_constraint(), action)
+            && self.scope_constraint_may_match(
+                policy.principal_constraint().as_inner(),
+                principal_type,
+            )
+            && self
+                .scope_constraint_may_match(policy.resource_constraint().as_inner(), resource_type)
+    }
+
+    /// Can `action` satisfy the action scope constraint `constraint`?
+    fn action_constraint_may_match(
+        &self,
+        constraint: &ActionConstraint,
+        action: &EntityUID,
+    ) -> bool {
+        match constraint {
+            ActionConstraint::Any => true,
+            ActionConstraint::Eq(euid) => euid.as_ref() == action,
+            ActionConstraint::In(euids) => euids.iter().any(|euid| {
+                euid.as_ref() == action
+                    || self
+                        .get_action_id(euid)
+                        .is_some_and(|group| group.descendants.contains(action))
+            }),
+        }
+    }
+
+    /// Can some entity of type `ety` satisfy the principal or resource scope
+    /// constraint `constraint`? An unfilled slot could be linked to anything,
+    /// so it does not rule out any entity type.
+    fn scope_constraint_may_match(
+        &self,
+        constraint: &PrincipalOrResourceConstraint,
+        ety: &Name,
+    ) -> bool {
+        match constraint {
+            PrincipalOrResourceConstraint::Any
+            | PrincipalOrResourceConstraint::Eq(EntityReference::Slot)
+            | PrincipalOrResourceConstraint::In(EntityReference::Slot) => true,
+            PrincipalOrResourceConstraint::Eq(EntityReference::EUID(euid)) => {
+                matches!(euid.entity_type(), EntityType::Specified(name) if name == ety)
+            }
+            PrincipalOrResourceConstraint::In(EntityReference::EUID(euid)) => {
+                self.get_entity_types_in(euid).contains(&ety)
+            }
+            PrincipalOrResourceConstraint::Is(entity_type)
+            | PrincipalOrResourceConstraint::IsIn(entity_type, EntityReference::Slot) => {
+                entity_type == ety
+            }
+            PrincipalOrResourceConstraint::IsIn(entity_type, EntityReference::EUID(euid)) => {
+                entity_type == ety && self.get_entity_types_in(euid).contains(&ety)
+            }
+        }
+    }
+
     /// Get the `Type` of context expected for the given `action`.
     /// This always reutrns a closed record type.
     ///
@@ -807,6 +901,7 @@ impl<'a> CommonTypeResolver<'a> {
                                 TypeOfAttribute {
                                     required: attr_ty.required,
                                     ty: Self::resolve_type(resolve_table, attr_ty.ty)?,
+                                    annotations: attr_ty.annotations,
                                 },
                             ))
                         })
@@ -2030,6 +2125,7 @@ mod test {
                 action_uid,
                 HashMap::from([("attr".into(), RestrictedExpr::val("foo"))]),
                 HashSet::new(),
+                HashMap::new(),
                 &Extensions::none(),
             )
             .unwrap(),
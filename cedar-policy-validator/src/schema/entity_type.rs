@@ -21,11 +21,11 @@ use smol_str::SmolStr;
 use std::collections::HashSet;
 
 use cedar_policy_core::{
-    ast::{EntityType, Name},
+    ast::{Eid, EntityType, Name},
     transitive_closure::TCNode,
 };
 
-use crate::types::{AttributeType, Attributes, OpenTag};
+use crate::types::{AttributeType, Attributes, OpenTag, Type};
 
 /// Contains entity type information for use by the validator. The contents of
 /// the struct are the same as the schema entity type structure, but the
@@ -51,6 +51,15 @@ pub struct ValidatorEntityType {
     /// their type when they are present. Attempting to access an undeclared
     /// attribute under standard validation is an error regardless of this flag.
     pub(crate) open_attributes: OpenTag,
+
+    /// Type of the tags on this entity type, or `None` if entities of this
+    /// type may not have tags. Tag keys are arbitrary strings; all tag values
+    /// share this one type.
+    pub(crate) tags: Option<Type>,
+
+    /// The entity IDs that entities of this type may have if it is an
+    /// enumerated entity type, or `None` if any entity ID is allowed.
+    pub(crate) choices: Option<Vec<SmolStr>>,
 }
 
 impl ValidatorEntityType {
@@ -64,6 +73,26 @@ impl ValidatorEntityType {
         self.attributes.iter()
     }
 
+    /// Get the type of tags on this entity, or `None` if entities of this type
+    /// may not have tags
+    pub fn tag_type(&self) -> Option<&Type> {
+        self.tags.as_ref()
+    }
+
+    /// Get the entity IDs that entities of this type may have, or `None` if
+    /// this is not an enumerated entity type
+    pub fn enum_choices(&self) -> Option<&[SmolStr]> {
+        self.choices.as_deref()
+    }
+
+    /// Return `true` if entities of this type may have the entity ID `eid`
+    pub fn allows_eid(&self, eid: &Eid) -> bool {
+        match &self.choices {
+            Some(choices) => choices.iter().any(|choice| choice == eid.as_ref() as &str),
+            None => true,
+        }
+    }
+
     /// Return `true` if this entity type has an `EntityType` declared as a
     /// possible descendant in the schema. This takes an `EntityType` rather
     /// than a `Name`, It's not possible to declare the unspecified entity type
@@ -37,7 +37,7 @@ use crate::{
     err::*,
     schema_file_format,
     types::{AttributeType, Attributes, Type},
-    ActionBehavior, ActionEntityUID, ActionType, NamespaceDefinition, SchemaType,
+    ActionBehavior, ActionEntityUID, ActionType, CommonType, NamespaceDefinition, SchemaType,
     SchemaTypeVariant, TypeOfAttribute, SCHEMA_TYPE_VARIANT_TAGS,
 };
 
@@ -113,6 +113,13 @@ pub struct EntityTypeFragment {
     /// namespace, so we will check if they are declared in any fragment when
     /// constructing a `ValidatorSchema`.
     pub(super) parents: HashSet<Name>,
+    /// The type of tags on this entity type, if it has tags. As with
+    /// `attributes`, this may refer to common types which are not defined in
+    /// this schema fragment.
+    pub(super) tags: Option<WithUnresolvedTypeDefs<Type>>,
+    /// The entity IDs allowed for entities of this type, if it is an
+    /// enumerated entity type.
+    pub(super) choices: Option<Vec<SmolStr>>,
 }
 
 /// Action declarations held in a `ValidatorNamespaceDef`. Entity types
@@ -237,11 +244,11 @@ impl ValidatorNamespaceDef {
     }
 
     fn build_type_defs(
-        schema_file_type_def: HashMap<Id, SchemaType>,
+        schema_file_type_def: HashMap<Id, CommonType>,
         schema_namespace: Option<&Name>,
     ) -> Result<TypeDefs> {
         let mut type_defs = HashMap::with_capacity(schema_file_type_def.len());
-        for (id, schema_ty) in schema_file_type_def {
+        for (id, CommonType { ty: schema_ty, .. }) in schema_file_type_def {
             if Self::is_builtin_type_name(id.as_ref()) {
                 return Err(SchemaError::DuplicateCommonType(id.to_string()));
             }
@@ -270,6 +277,17 @@ impl ValidatorNamespaceDef {
         let mut entity_types = HashMap::with_capacity(schema_files_types.len());
         for (id, entity_type) in schema_files_types {
             let name = Name::from(id.clone()).prefix_namespace_if_unqualified(schema_namespace);
+            if let Some(choices) = &entity_type.choices {
+                if choices.is_empty() {
+                    return Err(SchemaError::EmptyEnumEntityType(name));
+                }
+                if !entity_type.member_of_types.is_empty()
+                    || !entity_type.shape.is_empty_record()
+                    || entity_type.tags.is_some()
+                {
+                    return Err(SchemaError::InvalidEnumEntityType(name));
+                }
+            }
             match entity_types.entry(name) {
                 Entry::Vacant(ventry) => {
                     ventry.insert(EntityTypeFragment {
@@ -282,6 +300,13 @@ impl ValidatorNamespaceDef {
                             .into_iter()
                             .map(|ty| ty.prefix_namespace_if_unqualified(schema_namespace))
                             .collect(),
+                        tags: entity_type
+                            .tags
+                            .map(|tags| {
+                                Self::try_schema_type_into_validator_type(schema_namespace, tags)
+                            })
+                            .transpose()?,
+                        choices: entity_type.choices,
                     });
                 }
                 Entry::Occupied(_) => {
@@ -389,7 +414,8 @@ impl ValidatorNamespaceDef {
                 .map_err(|err| {
                     SchemaError::ActionAttrEval(EntityAttrEvaluationError {
                         uid: action_id.clone(),
-                        attr: k.clone(),
+                        attr_or_tag: k.clone(),
+                        was_attr: true,
                         err,
                     })
                 })?;
@@ -0,0 +1,893 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Comparing two schemas, to check whether a schema change is backward
+//! compatible.
+//!
+//! A change is backward compatible when every request and entity that
+//! conforms to the old schema also conforms to the new one. Adding an entity
+//! type or an optional attribute is compatible, while removing an entity
+//! type, narrowing the type of an attribute, or making an optional attribute
+//! required is breaking. Even a compatible change can make policies fail to
+//! validate, e.g. when a required attribute becomes optional, so
+//! [`broken_policies`] re-validates a policy set against the new schema.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt::Display;
+
+use cedar_policy_core::ast::{EntityType, EntityUID, Name, PolicySet};
+use itertools::Itertools;
+use smol_str::SmolStr;
+
+use crate::types::{Attributes, EntityRecordKind, Type};
+use crate::{ValidationError, ValidationMode, Validator, ValidatorSchema};
+
+/// The changes between two schemas
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaDiff {
+    changes: Vec<SchemaChange>,
+}
+
+/// Where an attribute is declared
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttributeOwner {
+    /// Attributes of this entity type
+    EntityType(Name),
+    /// The context of this action
+    Context(EntityUID),
+}
+
+impl Display for AttributeOwner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EntityType(name) => write!(f, "entity type `{name}`"),
+            Self::Context(action) => write!(f, "the context of action `{action}`"),
+        }
+    }
+}
+
+/// A single difference between two schemas
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaChange {
+    /// An entity type was added
+    EntityTypeAdded(Name),
+    /// An entity type was removed
+    EntityTypeRemoved(Name),
+    /// Entities of type `entity_type` may now be members of entities of type
+    /// `parent`, directly or transitively
+    MemberOfTypeAdded {
+        /// The member entity type
+        entity_type: Name,
+        /// The parent entity type
+        parent: Name,
+    },
+    /// Entities of type `entity_type` may no longer be members of entities
+    /// of type `parent`
+    MemberOfTypeRemoved {
+        /// The member entity type
+        entity_type: Name,
+        /// The parent entity type
+        parent: Name,
+    },
+    /// An attribute was added
+    AttributeAdded {
+        /// Where the attribute is declared
+        owner: AttributeOwner,
+        /// The path to the attribute, through any enclosing records
+        path: Vec<SmolStr>,
+        /// Whether the attribute is required
+        required: bool,
+    },
+    /// An attribute was removed
+    AttributeRemoved {
+        /// Where the attribute was declared
+        owner: AttributeOwner,
+        /// The path to the attribute, through any enclosing records
+        path: Vec<SmolStr>,
+    },
+    /// An optional attribute became required
+    AttributeMadeRequired {
+        /// Where the attribute is declared
+        owner: AttributeOwner,
+        /// The path to the attribute, through any enclosing records
+        path: Vec<SmolStr>,
+    },
+    /// A required attribute became optional
+    AttributeMadeOptional {
+        /// Where the attribute is declared
+        owner: AttributeOwner,
+        /// The path to the attribute, through any enclosing records
+        path: Vec<SmolStr>,
+    },
+    /// The type of an attribute changed
+    AttributeTypeChanged {
+        /// Where the attribute is declared
+        owner: AttributeOwner,
+        /// The path to the attribute, through any enclosing records
+        path: Vec<SmolStr>,
+        /// The old type
+        old: Type,
+        /// The new type
+        new: Type,
+        /// Whether every value of the old type has the new type
+        widened: bool,
+    },
+    /// The type of the tags of an entity type changed, where `None` means
+    /// that entities of the type may not have tags
+    TagTypeChanged {
+        /// The entity type
+        entity_type: Name,
+        /// The old tag type
+        old: Option<Type>,
+        /// The new tag type
+        new: Option<Type>,
+        /// Whether every tag allowed by the old schema is allowed by the new
+        /// one
+        widened: bool,
+    },
+    /// The entity IDs allowed for an entity type changed, where `None` means
+    /// that the type is not enumerated and any entity ID is allowed
+    EnumEntitiesChanged {
+        /// The entity type
+        entity_type: Name,
+        /// The old entity IDs
+        old: Option<Vec<SmolStr>>,
+        /// The new entity IDs
+        new: Option<Vec<SmolStr>>,
+        /// Whether every entity ID allowed by the old schema is allowed by
+        /// the new one
+        widened: bool,
+    },
+    /// An action was added
+    ActionAdded(EntityUID),
+    /// An action was removed
+    ActionRemoved(EntityUID),
+    /// The action may no longer be applied to principals of this type
+    PrincipalTypeRemoved {
+        /// The action
+        action: EntityUID,
+        /// The principal type
+        principal_type: EntityType,
+    },
+    /// The action may now be applied to principals of this type
+    PrincipalTypeAdded {
+        /// The action
+        action: EntityUID,
+        /// The principal type
+        principal_type: EntityType,
+    },
+    /// The action may no longer be applied to resources of this type
+    ResourceTypeRemoved {
+        /// The action
+        action: EntityUID,
+        /// The resource type
+        resource_type: EntityType,
+    },
+    /// The action may now be applied to resources of this type
+    ResourceTypeAdded {
+        /// The action
+        action: EntityUID,
+        /// The resource type
+        resource_type: EntityType,
+    },
+    /// The action is now in the action group `parent`, directly or
+    /// transitively
+    ActionParentAdded {
+        /// The action
+        action: EntityUID,
+        /// The action group
+        parent: EntityUID,
+    },
+    /// The action is no longer in the action group `parent`
+    ActionParentRemoved {
+        /// The action
+        action: EntityUID,
+        /// The action group
+        parent: EntityUID,
+    },
+}
+
+impl SchemaChange {
+    /// Can a request or entity that conforms to the old schema fail to
+    /// conform to the new one? Removing an action from a group is breaking
+    /// as well, since it changes the meaning of policies.
+    pub fn is_breaking(&self) -> bool {
+        match self {
+            Self::EntityTypeAdded(_)
+            | Self::MemberOfTypeAdded { .. }
+            | Self::AttributeMadeOptional { .. }
+            | Self::ActionAdded(_)
+            | Self::PrincipalTypeAdded { .. }
+            | Self::ResourceTypeAdded { .. }
+            | Self::ActionParentAdded { .. } => false,
+            Self::EntityTypeRemoved(_)
+            | Self::MemberOfTypeRemoved { .. }
+            | Self::AttributeRemoved { .. }
+            | Self::AttributeMadeRequired { .. }
+            | Self::ActionRemoved(_)
+            | Self::PrincipalTypeRemoved { .. }
+            | Self::ResourceTypeRemoved { .. }
+            | Self::ActionParentRemoved { .. } => true,
+            Self::AttributeAdded { required, .. } => *required,
+            Self::AttributeTypeChanged { widened, .. }
+            | Self::TagTypeChanged { widened, .. }
+            | Self::EnumEntitiesChanged { widened, .. } => !widened,
+        }
+    }
+}
+
+impl Display for SchemaChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = |path: &[SmolStr]| path.iter().join(".");
+        let tags = |ty: &Option<Type>| match ty {
+            Some(ty) => ty.to_string(),
+            None => "no tags".to_string(),
+        };
+        let eids = |eids: &Option<Vec<SmolStr>>| match eids {
+            Some(eids) => format!("[{}]", eids.iter().map(|eid| format!("{eid:?}")).join(", ")),
+            None => "any entity ID".to_string(),
+        };
+        match self {
+            Self::EntityTypeAdded(name) => write!(f, "entity type `{name}` was added"),
+            Self::EntityTypeRemoved(name) => write!(f, "entity type `{name}` was removed"),
+            Self::MemberOfTypeAdded {
+                entity_type,
+                parent,
+            } => write!(
+                f,
+                "`{entity_type}` entities may now be members of `{parent}`"
+            ),
+            Self::MemberOfTypeRemoved {
+                entity_type,
+                parent,
+            } => write!(
+                f,
+                "`{entity_type}` entities may no longer be members of `{parent}`"
+            ),
+            Self::AttributeAdded {
+                owner,
+                path: p,
+                required,
+            } => {
+                let kind = if *required { "required" } else { "optional" };
+                write!(f, "{kind} attribute `{}` was added to {owner}", path(p))
+            }
+            Self::AttributeRemoved { owner, path: p } => {
+                write!(f, "attribute `{}` was removed from {owner}", path(p))
+            }
+            Self::AttributeMadeRequired { owner, path: p } => {
+                write!(f, "attribute `{}` of {owner} is now required", path(p))
+            }
+            Self::AttributeMadeOptional { owner, path: p } => {
+                write!(f, "attribute `{}` of {owner} is now optional", path(p))
+            }
+            Self::AttributeTypeChanged {
+                owner,
+                path: p,
+                old,
+                new,
+                ..
+            } => write!(
+                f,
+                "type of attribute `{}` of {owner} changed from `{old}` to `{new}`",
+                path(p)
+            ),
+            Self::TagTypeChanged {
+                entity_type,
+                old,
+                new,
+                ..
+            } => write!(
+                f,
+                "tags of entity type `{entity_type}` changed from {} to {}",
+                tags(old),
+                tags(new)
+            ),
+            Self::EnumEntitiesChanged {
+                entity_type,
+                old,
+                new,
+                ..
+            } => write!(
+                f,
+                "entity IDs of entity type `{entity_type}` changed from {} to {}",
+                eids(old),
+                eids(new)
+            ),
+            Self::ActionAdded(action) => write!(f, "action `{action}` was added"),
+            Self::ActionRemoved(action) => write!(f, "action `{action}` was removed"),
+            Self::PrincipalTypeRemoved {
+                action,
+                principal_type,
+            } => write!(
+                f,
+                "action `{action}` no longer applies to principals of type `{principal_type}`"
+            ),
+            Self::PrincipalTypeAdded {
+                action,
+                principal_type,
+            } => write!(
+                f,
+                "action `{action}` now applies to principals of type `{principal_type}`"
+            ),
+            Self::ResourceTypeRemoved {
+                action,
+                resource_type,
+            } => write!(
+                f,
+                "action `{action}` no longer applies to resources of type `{resource_type}`"
+            ),
+            Self::ResourceTypeAdded {
+                action,
+                resource_type,
+            } => write!(
+                f,
+                "action `{action}` now applies to resources of type `{resource_type}`"
+            ),
+            Self::ActionParentAdded { action, parent } => {
+                write!(f, "action `{action}` is now in `{parent}`")
+            }
+            Self::ActionParentRemoved { action, parent } => {
+                write!(f, "action `{action}` is no longer in `{parent}`")
+            }
+        }
+    }
+}
+
+impl SchemaDiff {
+    /// Compare `old` to `new`
+    pub fn new(old: &ValidatorSchema, new: &ValidatorSchema) -> Self {
+        let mut diff = Differ {
+            old,
+            new,
+            changes: Vec::new(),
+        };
+        diff.entity_types();
+        diff.actions();
+        Self {
+            changes: diff.changes,
+        }
+    }
+
+    /// All the changes, breaking or not
+    pub fn changes(&self) -> impl Iterator<Item = &SchemaChange> {
+        self.changes.iter()
+    }
+
+    /// The changes that are not backward compatible
+    pub fn breaking_changes(&self) -> impl Iterator<Item = &SchemaChange> {
+        self.changes.iter().filter(|c| c.is_breaking())
+    }
+
+    /// Does every request and entity that conforms to the old schema conform
+    /// to the new one?
+    pub fn is_backward_compatible(&self) -> bool {
+        self.breaking_changes().next().is_none()
+    }
+}
+
+/// The validation errors, against `new`, of the policies in `policies` that
+/// pass validation against `old` but not against `new`
+pub fn broken_policies(
+    old: &ValidatorSchema,
+    new: &ValidatorSchema,
+    policies: &PolicySet,
+    mode: ValidationMode,
+) -> Vec<ValidationError> {
+    let errors = |schema: &ValidatorSchema| {
+        let (errors, _) = Validator::new(schema.clone())
+            .validate(policies, mode)
+            .into_errors_and_warnings();
+        errors.collect::<Vec<_>>()
+    };
+    let already_failing = errors(old)
+        .into_iter()
+        .map(|e| e.location().policy_id().clone())
+        .collect::<HashSet<_>>();
+    errors(new)
+        .into_iter()
+        .filter(|e| !already_failing.contains(e.location().policy_id()))
+        .collect()
+}
+
+struct Differ<'a> {
+    old: &'a ValidatorSchema,
+    new: &'a ValidatorSchema,
+    changes: Vec<SchemaChange>,
+}
+
+impl<'a> Differ<'a> {
+    fn entity_types(&mut self) {
+        let names = |schema: &'a ValidatorSchema| {
+            schema
+                .entity_types()
+                .map(|(name, _)| name)
+                .collect::<BTreeSet<_>>()
+        };
+        let (old_names, new_names) = (names(self.old), names(self.new));
+        for name in old_names.difference(&new_names) {
+            self.changes
+                .push(SchemaChange::EntityTypeRemoved((*name).clone()));
+        }
+        for name in new_names.difference(&old_names) {
+            self.changes
+                .push(SchemaChange::EntityTypeAdded((*name).clone()));
+        }
+        // a parent's descendants are the types that may be its members
+        let parents = |schema: &'a ValidatorSchema, name: &Name| {
+            schema
+                .entity_types()
+                .filter(|(_, parent)| parent.descendants.contains(name))
+                .map(|(parent, _)| parent)
+                .collect::<BTreeSet<_>>()
+        };
+        for name in old_names.intersection(&new_names) {
+            let (old_parents, new_parents) = (parents(self.old, name), parents(self.new, name));
+            for parent in old_parents.difference(&new_parents) {
+                self.changes.push(SchemaChange::MemberOfTypeRemoved {
+                    entity_type: (*name).clone(),
+                    parent: (*parent).clone(),
+                });
+            }
+            for parent in new_parents.difference(&old_parents) {
+                self.changes.push(SchemaChange::MemberOfTypeAdded {
+                    entity_type: (*name).clone(),
+                    parent: (*parent).clone(),
+                });
+            }
+            if let (Some(old), Some(new)) = (
+                self.old.get_entity_type(name),
+                self.new.get_entity_type(name),
+            ) {
+                let owner = AttributeOwner::EntityType((*name).clone());
+                self.attributes(&owner, &[], &old.attributes, &new.attributes);
+                if old.tags != new.tags {
+                    let widened = match (&old.tags, &new.tags) {
+                        (None, _) => true,
+                        (Some(_), None) => false,
+                        (Some(old), Some(new)) => self.widens(old, new),
+                    };
+                    self.changes.push(SchemaChange::TagTypeChanged {
+                        entity_type: (*name).clone(),
+                        old: old.tags.clone(),
+                        new: new.tags.clone(),
+                        widened,
+                    });
+                }
+                let eids = |choices: &'a Option<Vec<SmolStr>>| {
+                    choices
+                        .as_ref()
+                        .map(|choices| choices.iter().collect::<BTreeSet<_>>())
+                };
+                let (old_eids, new_eids) = (eids(&old.choices), eids(&new.choices));
+                if old_eids != new_eids {
+                    let widened = match (old_eids, new_eids) {
+                        (_, None) => true,
+                        (None, Some(_)) => false,
+                        (Some(old), Some(new)) => old.is_subset(&new),
+                    };
+                    self.changes.push(SchemaChange::EnumEntitiesChanged {
+                        entity_type: (*name).clone(),
+                        old: old.choices.clone(),
+                        new: new.choices.clone(),
+                        widened,
+                    });
+                }
+            }
+        }
+    }
+
+    fn actions(&mut self) {
+        let actions = |schema: &'a ValidatorSchema| {
+            schema
+                .known_action_ids()
+                .map(|a| (a.to_string(), a))
+                .collect::<BTreeMap<_, _>>()
+        };
+        let (old_actions, new_actions) = (actions(self.old), actions(self.new));
+        for (key, action) in &old_actions {
+            if !new_actions.contains_key(key) {
+                self.changes
+                    .push(SchemaChange::ActionRemoved((*action).clone()));
+            }
+        }
+        for (key, action) in &new_actions {
+            if !old_actions.contains_key(key) {
+                self.changes
+                    .push(SchemaChange::ActionAdded((*action).clone()));
+            }
+        }
+        for (key, action) in &old_actions {
+            let (Some(old), Some(new)) = (
+                self.old.get_action_id(action),
+                new_actions.get(key).and_then(|a| self.new.get_action_id(a)),
+            ) else {
+                continue;
+            };
+            let types = |types: &mut dyn Iterator<Item = &EntityType>| {
+                types
+                    .map(|ty| (ty.to_string(), ty.clone()))
+                    .collect::<BTreeMap<_, _>>()
+            };
+            let (old_principals, new_principals) = (
+                types(&mut old.applies_to.applicable_principal_types()),
+                types(&mut new.applies_to.applicable_principal_types()),
+            );
+            for (key, ty) in &old_principals {
+                if !new_principals.contains_key(key) {
+                    self.changes.push(SchemaChange::PrincipalTypeRemoved {
+                        action: (*action).clone(),
+                        principal_type: ty.clone(),
+                    });
+                }
+            }
+            for (key, ty) in &new_principals {
+                if !old_principals.contains_key(key) {
+                    self.changes.push(SchemaChange::PrincipalTypeAdded {
+                        action: (*action).clone(),
+                        principal_type: ty.clone(),
+                    });
+                }
+            }
+            let (old_resources, new_resources) = (
+                types(&mut old.applies_to.applicable_resource_types()),
+                types(&mut new.applies_to.applicable_resource_types()),
+            );
+            for (key, ty) in &old_resources {
+                if !new_resources.contains_key(key) {
+                    self.changes.push(SchemaChange::ResourceTypeRemoved {
+                        action: (*action).clone(),
+                        resource_type: ty.clone(),
+                    });
+                }
+            }
+            for (key, ty) in &new_resources {
+                if !old_resources.contains_key(key) {
+                    self.changes.push(SchemaChange::ResourceTypeAdded {
+                        action: (*action).clone(),
+                        resource_type: ty.clone(),
+                    });
+                }
+            }
+            let parents = |schema: &'a ValidatorSchema| {
+                schema
+                    .known_action_ids()
+                    .filter_map(|group| schema.get_action_id(group))
+                    .filter(|group| group.descendants.contains(action))
+                    .map(|group| (group.name.to_string(), &group.name))
+                    .collect::<BTreeMap<_, _>>()
+            };
+            let (old_parents, new_parents) = (parents(self.old), parents(self.new));
+            for (key, parent) in &old_parents {
+                if !new_parents.contains_key(key) {
+                    self.changes.push(SchemaChange::ActionParentRemoved {
+                        action: (*action).clone(),
+                        parent: (*parent).clone(),
+                    });
+                }
+            }
+            for (key, parent) in &new_parents {
+                if !old_parents.contains_key(key) {
+                    self.changes.push(SchemaChange::ActionParentAdded {
+                        action: (*action).clone(),
+                        parent: (*parent).clone(),
+                    });
+                }
+            }
+            let owner = AttributeOwner::Context((*action).clone());
+            match (&old.context, &new.context) {
+                (
+                    Type::EntityOrRecord(EntityRecordKind::Record { attrs: old, .. }),
+                    Type::EntityOrRecord(EntityRecordKind::Record { attrs: new, .. }),
+                ) => self.attributes(&owner, &[], old, new),
+                (old, new) if old != new => {
+                    let widened = self.widens(old, new);
+                    self.changes.push(SchemaChange::AttributeTypeChanged {
+                        owner,
+                        path: Vec::new(),
+                        old: old.clone(),
+                        new: new.clone(),
+                        widened,
+                    });
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Compare the attributes `old` and `new` of records at `path` in
+    /// `owner`
+    fn attributes(
+        &mut self,
+        owner: &AttributeOwner,
+        path: &[SmolStr],
+        old: &Attributes,
+        new: &Attributes,
+    ) {
+        let at = |attr: &SmolStr| {
+            let mut path = path.to_vec();
+            path.push(attr.clone());
+            path
+        };
+        for (attr, _) in old.iter() {
+            if new.get_attr(attr).is_none() {
+                self.changes.push(SchemaChange::AttributeRemoved {
+                    owner: owner.clone(),
+                    path: at(attr),
+                });
+            }
+        }
+        for (attr, ty) in new.iter() {
+            if old.get_attr(attr).is_none() {
+                self.changes.push(SchemaChange::AttributeAdded {
+                    owner: owner.clone(),
+                    path: at(attr),
+                    required: ty.is_required,
+                });
+            }
+        }
+        for (attr, old_ty) in old.iter() {
+            let Some(new_ty) = new.get_attr(attr) else {
+                continue;
+            };
+            match (old_ty.is_required, new_ty.is_required) {
+                (false, true) => self.changes.push(SchemaChange::AttributeMadeRequired {
+                    owner: owner.clone(),
+                    path: at(attr),
+                }),
+                (true, false) => self.changes.push(SchemaChange::AttributeMadeOptional {
+                    owner: owner.clone(),
+                    path: at(attr),
+                }),
+                _ => (),
+            }
+            match (&old_ty.attr_type, &new_ty.attr_type) {
+                (
+                    Type::EntityOrRecord(EntityRecordKind::Record { attrs: old, .. }),
+                    Type::EntityOrRecord(EntityRecordKind::Record { attrs: new, .. }),
+                ) => self.attributes(owner, &at(attr), old, new),
+                (old, new) if old != new => {
+                    let widened = self.widens(old, new);
+                    self.changes.push(SchemaChange::AttributeTypeChanged {
+                        owner: owner.clone(),
+                        path: at(attr),
+                        old: old.clone(),
+                        new: new.clone(),
+                        widened,
+                    });
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Does every value of type `old` have type `new`?
+    fn widens(&self, old: &Type, new: &Type) -> bool {
+        Type::is_subtype(self.new, old, new, ValidationMode::Strict)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cedar_policy_core::parser::parse_policyset;
+
+    fn schema(src: &str) -> ValidatorSchema {
+        ValidatorSchema::from_str_natural(
+            src,
+            cedar_policy_core::extensions::Extensions::all_available(),
+        )
+        .unwrap()
+        .0
+    }
+
+    const OLD: &str = r#"
+        entity Team;
+        entity Org;
+        entity User in [Team, Org] {
+            name: String,
+            age?: Long,
+            address: { city: String, zip: String },
+            roles: Set<String>,
+        } tags String;
+        entity Document;
+        action read, write appliesTo {
+            principal: [User],
+            resource: [Document],
+            context: { ip: String }
+        };
+        action edit in [write] appliesTo { principal: [User], resource: [Document] };
+    "#;
+
+    fn changes(old: &str, new: &str) -> Vec<(String, bool)> {
+        SchemaDiff::new(&schema(old), &schema(new))
+            .changes()
+            .map(|c| (c.to_string(), c.is_breaking()))
+            .collect()
+    }
+
+    #[test]
+    fn identical() {
+        assert!(changes(OLD, OLD).is_empty());
+    }
+
+    #[test]
+    fn enum_entities() {
+        let old = r#"entity Role enum ["admin", "viewer"]; entity Team;"#;
+        assert!(changes(old, r#"entity Role enum ["viewer", "admin"]; entity Team;"#).is_empty());
+        assert_eq!(
+            changes(
+                old,
+                r#"entity Role enum ["admin", "viewer", "editor"]; entity Team enum ["eng"];"#
+            ),
+            [
+                (
+                    r#"entity IDs of entity type `Role` changed from ["admin", "viewer"] to ["admin", "viewer", "editor"]"#.to_string(),
+                    false
+                ),
+                (
+                    r#"entity IDs of entity type `Team` changed from any entity ID to ["eng"]"#
+                        .to_string(),
+                    true
+                ),
+            ]
+        );
+        assert_eq!(
+            changes(old, r#"entity Role enum ["admin"]; entity Team;"#),
+            [(
+                r#"entity IDs of entity type `Role` changed from ["admin", "viewer"] to ["admin"]"#
+                    .to_string(),
+                true
+            )]
+        );
+    }
+
+    #[test]
+    fn compatible_changes() {
+        let new = r#"
+            entity Team in [Org];
+            entity Org;
+            entity Folder;
+            entity User in [Team, Org] {
+                name: String,
+                age?: Long,
+                address: { city: String, zip?: String, country?: String },
+                roles: Set<String>,
+                nickname?: String,
+            } tags String;
+            entity Document;
+            action read, write appliesTo {
+                principal: [User],
+                resource: [Document, Folder],
+                context: { ip: String }
+            };
+            action edit in [write] appliesTo { principal: [User], resource: [Document] };
+            action share appliesTo { principal: [User], resource: [Document] };
+        "#;
+        let diff = SchemaDiff::new(&schema(OLD), &schema(new));
+        assert!(diff.is_backward_compatible(), "{:?}", diff);
+        assert_eq!(
+            changes(OLD, new),
+            [
+                ("entity type `Folder` was added".to_string(), false),
+                (
+                    "`Team` entities may now be members of `Org`".to_string(),
+                    false
+                ),
+                (
+                    "optional attribute `nickname` was added to entity type `User`".to_string(),
+                    false
+                ),
+                (
+                    "optional attribute `address.country` was added to entity type `User`"
+                        .to_string(),
+                    false
+                ),
+                (
+                    "attribute `address.zip` of entity type `User` is now optional".to_string(),
+                    false
+                ),
+                (r#"action `Action::"share"` was added"#.to_string(), false),
+                (
+                    r#"action `Action::"read"` now applies to resources of type `Folder`"#
+                        .to_string(),
+                    false
+                ),
+                (
+                    r#"action `Action::"write"` now applies to resources of type `Folder`"#
+                        .to_string(),
+                    false
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn breaking_changes() {
+        let new = r#"
+            entity Team;
+            entity User in [Team] {
+                name: String,
+                age: Long,
+                address: { city: String, zip: Long },
+                roles: Set<Long>,
+                manager: User,
+            };
+            entity Document;
+            action read, write appliesTo {
+                principal: [User],
+                resource: [Document],
+                context: { ip: String, mfa: Bool }
+            };
+            action edit appliesTo { principal: [User, Team], resource: [Document] };
+        "#;
+        let diff = SchemaDiff::new(&schema(OLD), &schema(new));
+        assert!(!diff.is_backward_compatible());
+        let breaking = diff
+            .breaking_changes()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            breaking,
+            [
+                "entity type `Org` was removed",
+                "`User` entities may no longer be members of `Org`",
+                "required attribute `manager` was added to entity type `User`",
+                "type of attribute `address.zip` of entity type `User` changed from `String` to `Long`",
+                "attribute `age` of entity type `User` is now required",
+                "type of attribute `roles` of entity type `User` changed from `Set<String>` to `Set<Long>`",
+                "tags of entity type `User` changed from String to no tags",
+                r#"action `Action::"edit"` is no longer in `Action::"write"`"#,
+                r#"required attribute `mfa` was added to the context of action `Action::"read"`"#,
+                r#"required attribute `mfa` was added to the context of action `Action::"write"`"#,
+            ]
+        );
+        // adding a principal type is compatible
+        assert_eq!(diff.changes().count(), breaking.len() + 1);
+    }
+
+    #[test]
+    fn policies_broken_by_change() {
+        let new = r#"
+            entity Team;
+            entity User in [Team] { name: String, age?: Long };
+            entity Document;
+            action read, write appliesTo { principal: [User], resource: [Document] };
+        "#;
+        let policies = parse_policyset(
+            r#"
+            permit(principal, action == Action::"read", resource) when { principal.name == "alice" };
+            permit(principal in Org::"acme", action, resource);
+            permit(principal, action == Action::"read", resource) when { principal.age > 21 };
+            permit(principal, action, resource) when { principal.bogus };
+            "#,
+        )
+        .unwrap();
+        let broken = broken_policies(
+            &schema(OLD),
+            &schema(new),
+            &policies,
+            ValidationMode::Strict,
+        );
+        let ids = broken
+            .iter()
+            .map(|e| e.location().policy_id().to_string())
+            .collect::<BTreeSet<_>>();
+        // `policy3` fails against both schemas, and `policy2` checks that
+        // `age` is present in neither
+        assert_eq!(ids, BTreeSet::from(["policy1".to_string()]));
+    }
+}
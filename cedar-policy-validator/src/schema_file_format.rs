@@ -15,7 +15,7 @@
  */
 
 use cedar_policy_core::{
-    ast::{Id, Name},
+    ast::{AnyId, Id, Name},
     entities::CedarValueJson,
     FromNormalizedStr,
 };
@@ -135,6 +135,74 @@ impl SchemaFragment {
         let src = human_schema::json_schema_to_custom_schema_str(self)?;
         Ok(src)
     }
+
+    /// The annotations on the namespace `namespace`, where `None` is the
+    /// unqualified namespace, or `None` if the fragment doesn't declare it
+    pub fn namespace_annotations(&self, namespace: Option<&Name>) -> Option<&Annotations> {
+        self.0.get(&namespace.cloned()).map(|def| &def.annotations)
+    }
+
+    /// The annotations on the common type `name` in the namespace
+    /// `namespace`, or `None` if the fragment doesn't declare it
+    pub fn common_type_annotations(
+        &self,
+        namespace: Option<&Name>,
+        name: &str,
+    ) -> Option<&Annotations> {
+        self.0
+            .get(&namespace.cloned())?
+            .common_types
+            .get(&name.parse().ok()?)
+            .map(|ty| &ty.annotations)
+    }
+
+    /// The annotations on the entity type `name`, or `None` if the fragment
+    /// doesn't declare it
+    pub fn entity_type_annotations(&self, name: &Name) -> Option<&Annotations> {
+        let (namespace, basename) = split_name(name);
+        self.0
+            .get(&namespace)?
+            .entity_types
+            .get(basename)
+            .map(|ty| &ty.annotations)
+    }
+
+    /// The annotations on the attribute `attr` of the entity type
+    /// `entity_type`, or `None` if the fragment doesn't declare it. Only
+    /// attributes declared in the entity type itself, rather than in a
+    /// common type, are found.
+    pub fn attribute_annotations(&self, entity_type: &Name, attr: &str) -> Option<&Annotations> {
+        let (namespace, basename) = split_name(entity_type);
+        match &self.0.get(&namespace)?.entity_types.get(basename)?.shape.0 {
+            SchemaType::Type(SchemaTypeVariant::Record { attributes, .. }) => {
+                attributes.get(attr).map(|ty| &ty.annotations)
+            }
+            _ => None,
+        }
+    }
+
+    /// The annotations on the action `action` in the namespace `namespace`,
+    /// or `None` if the fragment doesn't declare it
+    pub fn action_annotations(
+        &self,
+        namespace: Option<&Name>,
+        action: &str,
+    ) -> Option<&Annotations> {
+        self.0
+            .get(&namespace.cloned())?
+            .actions
+            .get(action)
+            .map(|ty| &ty.annotations)
+    }
+}
+
+/// Split `name` into its namespace, if any, and its basename
+fn split_name(name: &Name) -> (Option<Name>, &Id) {
+    let mut namespace = name.namespace_components().cloned().collect::<Vec<_>>();
+    let namespace = namespace
+        .pop()
+        .map(|basename| Name::new(basename, namespace, None));
+    (namespace, name.basename())
 }
 
 /// A single namespace definition from a SchemaFragment.
@@ -149,12 +217,15 @@ pub struct NamespaceDefinition {
     #[serde(skip_serializing_if = "HashMap::is_empty")]
     #[serde(with = "::serde_with::rust::maps_duplicate_key_is_error")]
     #[serde(rename = "commonTypes")]
-    pub common_types: HashMap<Id, SchemaType>,
+    pub common_types: HashMap<Id, CommonType>,
     #[serde(rename = "entityTypes")]
     #[serde(with = "::serde_with::rust::maps_duplicate_key_is_error")]
     pub entity_types: HashMap<Id, EntityType>,
     #[serde(with = "::serde_with::rust::maps_duplicate_key_is_error")]
     pub actions: HashMap<SmolStr, ActionType>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Annotations::is_empty")]
+    pub annotations: Annotations,
 }
 
 impl NamespaceDefinition {
@@ -166,10 +237,76 @@ impl NamespaceDefinition {
             common_types: HashMap::new(),
             entity_types: entity_types.into_iter().collect(),
             actions: actions.into_iter().collect(),
+            annotations: Annotations::default(),
+        }
+    }
+}
+
+/// A common type declaration. The type is flattened for serialization, so,
+/// in JSON format, this appears as a regular type with one extra property
+/// `annotations`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct CommonType {
+    #[serde(flatten)]
+    pub ty: SchemaType,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Annotations::is_empty")]
+    pub annotations: Annotations,
+}
+
+impl From<SchemaType> for CommonType {
+    fn from(ty: SchemaType) -> Self {
+        Self {
+            ty,
+            annotations: Annotations::default(),
         }
     }
 }
 
+/// The key under which the comments documenting a declaration in the
+/// natural schema syntax are kept in its [`Annotations`]
+pub const COMMENT_ANNOTATION: &str = "comment";
+
+/// Annotations on a namespace, common type, entity type, attribute, or
+/// action, mapping each key to its value. In the natural schema syntax,
+/// these are written `@key("value")` before the declaration, and comments
+/// attached to the declaration are kept under the key
+/// [`COMMENT_ANNOTATION`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct Annotations(
+    #[serde(with = "::serde_with::rust::maps_duplicate_key_is_error")]
+    #[cfg_attr(feature = "wasm", tsify(type = "Record<string, string>"))]
+    BTreeMap<AnyId, SmolStr>,
+);
+
+impl Annotations {
+    /// Are there no annotations?
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Get the value of the annotation with key `key`
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(&key.parse().ok()?).map(SmolStr::as_str)
+    }
+
+    /// Iterate over the annotations as key-value pairs, in order of key
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_ref(), v.as_str()))
+    }
+}
+
+impl FromIterator<(AnyId, SmolStr)> for Annotations {
+    fn from_iter<T: IntoIterator<Item = (AnyId, SmolStr)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
 /// Entity types describe the relationships in the entity store, including what
 /// entities can be members of groups of what types, and what attributes
 /// can/should be included on entities of each type.
@@ -185,6 +322,21 @@ pub struct EntityType {
     #[serde(default)]
     #[serde(skip_serializing_if = "AttributesOrContext::is_empty_record")]
     pub shape: AttributesOrContext,
+    /// Type of the values of tags on entities of this type. If omitted,
+    /// entities of this type may not have tags.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<SchemaType>,
+    /// The entity IDs that entities of this type may have. If omitted, any
+    /// entity ID is allowed. An enumerated entity type may not have parent
+    /// types, attributes, or tags.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "enum")]
+    pub choices: Option<Vec<SmolStr>>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Annotations::is_empty")]
+    pub annotations: Annotations,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -237,6 +389,9 @@ pub struct ActionType {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "memberOf")]
     pub member_of: Option<Vec<ActionEntityUID>>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Annotations::is_empty")]
+    pub annotations: Annotations,
 }
 
 /// The apply spec specifies what principals and resources an action can be used
@@ -342,12 +497,20 @@ impl SchemaType {
                 additional_attributes,
             }) => Self::Type(SchemaTypeVariant::Record {
                 attributes: BTreeMap::from_iter(attributes.into_iter().map(
-                    |(attr, TypeOfAttribute { ty, required })| {
+                    |(
+                        attr,
+                        TypeOfAttribute {
+                            ty,
+                            required,
+                            annotations,
+                        },
+                    )| {
                         (
                             attr,
                             TypeOfAttribute {
                                 ty: ty.prefix_common_type_references_with_namespace(ns.clone()),
                                 required,
+                                annotations,
                             },
                         )
                     },
@@ -738,7 +901,7 @@ impl<'a> arbitrary::Arbitrary<'a> for SchemaType {
     fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<SchemaType> {
         use std::collections::BTreeSet;
 
-        Ok(SchemaType::Type(match u.int_in_range::<u8>(1..=8)? {
+        Ok(SchemaType::Type(match u.int_in_range::<u8>(1..=10)? {
             1 => SchemaTypeVariant::String,
             2 => SchemaTypeVariant::Long,
             3 => SchemaTypeVariant::Boolean,
@@ -772,6 +935,16 @@ impl<'a> arbitrary::Arbitrary<'a> for SchemaType {
                 #[allow(clippy::unwrap_used)]
                 name: "decimal".parse().unwrap(),
             },
+            9 => SchemaTypeVariant::Extension {
+                // PANIC SAFETY: `datetime` is a valid `Id`
+                #[allow(clippy::unwrap_used)]
+                name: "datetime".parse().unwrap(),
+            },
+            10 => SchemaTypeVariant::Extension {
+                // PANIC SAFETY: `duration` is a valid `Id`
+                #[allow(clippy::unwrap_used)]
+                name: "duration".parse().unwrap(),
+            },
             n => panic!("bad index: {n}"),
         }))
     }
@@ -783,7 +956,7 @@ impl<'a> arbitrary::Arbitrary<'a> for SchemaType {
 /// Used to describe the type of a record or entity attribute. It contains a the
 /// type of the attribute and whether the attribute is required. The type is
 /// flattened for serialization, so, in JSON format, this appears as a regular
-/// type with one extra property `required`.
+/// type with extra properties `required` and `annotations`.
 ///
 /// Note that we can't add #[serde(deny_unknown_fields)] here because we are
 /// using #[serde(tag = "type")] in ty:SchemaType which is flattened here.
@@ -802,6 +975,10 @@ pub struct TypeOfAttribute {
     #[serde(default = "record_attribute_required_default")]
     #[serde(skip_serializing_if = "is_record_attribute_required_default")]
     pub required: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Annotations::is_empty")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub annotations: Annotations,
 }
 
 // Only used for serialization
@@ -1512,6 +1689,7 @@ mod test_json_roundtrip {
                 common_types: HashMap::new(),
                 entity_types: HashMap::new(),
                 actions: HashMap::new(),
+                annotations: Annotations::default(),
             },
         )]));
         roundtrip(fragment);
@@ -1525,6 +1703,7 @@ mod test_json_roundtrip {
                 common_types: HashMap::new(),
                 entity_types: HashMap::new(),
                 actions: HashMap::new(),
+                annotations: Annotations::default(),
             },
         )]));
         roundtrip(fragment);
@@ -1544,6 +1723,9 @@ mod test_json_roundtrip {
                             attributes: BTreeMap::new(),
                             additional_attributes: false,
                         })),
+                        tags: None,
+                        choices: None,
+                        annotations: Annotations::default(),
                     },
                 )]),
                 actions: HashMap::from([(
@@ -1561,8 +1743,10 @@ mod test_json_roundtrip {
                             )),
                         }),
                         member_of: None,
+                        annotations: Annotations::default(),
                     },
                 )]),
+                annotations: Annotations::default(),
             },
         )]));
         roundtrip(fragment);
@@ -1585,9 +1769,13 @@ mod test_json_roundtrip {
                                     additional_attributes: false,
                                 },
                             )),
+                            tags: None,
+                            choices: None,
+                            annotations: Annotations::default(),
                         },
                     )]),
                     actions: HashMap::new(),
+                    annotations: Annotations::default(),
                 },
             ),
             (
@@ -1610,8 +1798,10 @@ mod test_json_roundtrip {
                                 )),
                             }),
                             member_of: None,
+                            annotations: Annotations::default(),
                         },
                     )]),
+                    annotations: Annotations::default(),
                 },
             ),
         ]));
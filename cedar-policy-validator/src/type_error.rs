@@ -174,6 +174,25 @@ impl TypeError {
         }
     }
 
+    pub(crate) fn unsafe_tag_access(on_expr: Expr, entity: &Expr, tag: &Expr) -> Self {
+        Self {
+            on_expr: Some(on_expr),
+            source_loc: None,
+            kind: TypeErrorKind::UnsafeTagAccess(UnsafeTagAccess {
+                entity: entity.to_string(),
+                tag: tag.to_string(),
+            }),
+        }
+    }
+
+    pub(crate) fn no_tags_allowed(on_expr: Expr, entity_ty: Option<Type>) -> Self {
+        Self {
+            on_expr: Some(on_expr),
+            source_loc: None,
+            kind: TypeErrorKind::NoTagsAllowed(NoTagsAllowed { entity_ty }),
+        }
+    }
+
     pub(crate) fn undefined_extension(on_expr: Expr, name: String) -> Self {
         Self {
             on_expr: Some(on_expr),
@@ -261,6 +280,15 @@ pub enum TypeErrorKind {
     #[error(transparent)]
     #[diagnostic(transparent)]
     UnsafeOptionalAttributeAccess(UnsafeOptionalAttributeAccess),
+    /// The typechecker could not conclude that an access to a tag was safe.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnsafeTagAccess(UnsafeTagAccess),
+    /// The typechecker found a `getTag` on an entity type which does not
+    /// declare any tags in the schema.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    NoTagsAllowed(NoTagsAllowed),
     /// The typechecker found that a policy condition will always evaluate to false.
     #[error(
         "policy is impossible: the policy expression evaluates to false for all valid requests"
@@ -382,6 +410,8 @@ pub(crate) enum LubContext {
     Contains,
     #[error("elements of both set operands to a `containsAll` or `containsAny` expression")]
     ContainsAnyAll,
+    #[error("tag types of the entity types in a `getTag` expression")]
+    GetTag,
 }
 
 /// Structure containing details about a missing attribute error.
@@ -414,6 +444,29 @@ pub struct UnsafeOptionalAttributeAccess {
     attribute_access: AttributeAccess,
 }
 
+/// Structure containing details about an unsafe tag access error.
+#[derive(Error, Diagnostic, Debug, Clone, Hash, Eq, PartialEq)]
+#[error("unable to guarantee safety of access to tag `{tag}` on `{entity}`")]
+#[diagnostic(help("try testing for the tag with `{entity}.hasTag({tag}) && ..`"))]
+pub struct UnsafeTagAccess {
+    /// The expression the tag was accessed on, as it appears in the policy
+    entity: String,
+    /// The expression computing the tag key, as it appears in the policy
+    tag: String,
+}
+
+/// Structure containing details about an error for a `getTag` on an entity
+/// type which may not have tags.
+#[derive(Error, Diagnostic, Debug, Clone, Hash, Eq, PartialEq)]
+#[error("`getTag` is not allowed on {}", match .entity_ty {
+    Some(ty) => format!("entities of type `{ty}`"),
+    None => "entities of an unknown type".to_string(),
+})]
+#[diagnostic(help("tags can only be accessed on entity types that declare `tags` in the schema"))]
+pub struct NoTagsAllowed {
+    entity_ty: Option<Type>,
+}
+
 /// Structure containing details about an undefined function error.
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub struct UndefinedFunction {
@@ -25,13 +25,14 @@ mod test_optional_attributes;
 mod test_partial;
 mod test_policy;
 mod test_strict;
+mod test_tags;
 mod test_type_annotation;
 mod test_unspecified_entity;
 mod test_utils;
 
 use std::{
     borrow::Cow,
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet},
     iter::zip,
 };
 
@@ -594,9 +595,16 @@ impl<'a> Typechecker<'a> {
                     .with_same_source_loc(e)
                     .var(Var::Context),
             ),
-            ExprKind::Unknown(u) => {
-                TypecheckAnswer::fail(ExprBuilder::with_data(None).unknown(u.clone()))
-            }
+            // Typed unknowns have the type they are annotated with; untyped
+            // unknowns could be anything, so they fail to typecheck.
+            ExprKind::Unknown(u) => match &u.type_annotation {
+                Some(ty) => TypecheckAnswer::success(
+                    ExprBuilder::with_data(Some(Type::from_unknown_annotation(ty)))
+                        .with_same_source_loc(e)
+                        .unknown(u.clone()),
+                ),
+                None => TypecheckAnswer::fail(ExprBuilder::with_data(None).unknown(u.clone())),
+            },
             // Template Slots, always has to be an entity.
             ExprKind::Slot(slotid) => TypecheckAnswer::success(
                 ExprBuilder::with_data(Some(if slotid.is_principal() {
@@ -1405,23 +1413,59 @@ impl<'a> Typechecker<'a> {
             }
 
             BinaryOp::Less | BinaryOp::LessEq => {
-                let ans_arg1 = self.expect_type(
+                // The comparison operators apply to `Long`s and to extension
+                // types which support operator overloading. Both operands
+                // must have the same type.
+                let comparable_types = std::iter::once(Type::primitive_long())
+                    .chain(
+                        self.extensions
+                            .values()
+                            .flat_map(ExtensionSchema::types_with_operator_overloading)
+                            .collect::<BTreeSet<_>>()
+                            .into_iter()
+                            .map(|name| Type::extension(name.clone())),
+                    )
+                    .collect::<Vec<_>>();
+                let ans_arg1 = self.expect_one_of_types(
                     request_env,
                     prior_eff,
                     arg1,
-                    Type::primitive_long(),
+                    &comparable_types,
                     type_errors,
                     |_| None,
                 );
                 ans_arg1.then_typecheck(|expr_ty_arg1, _| {
-                    let ans_arg2 = self.expect_type(
-                        request_env,
-                        prior_eff,
-                        arg2,
-                        Type::primitive_long(),
-                        type_errors,
-                        |_| None,
-                    );
+                    // When the first operand has a comparable type, the second
+                    // must have the same one. When the first operand is a
+                    // subtype of every comparable type (e.g., an unknown
+                    // attribute during partial validation), the second may
+                    // have any comparable type.
+                    let ans_arg2 = match expr_ty_arg1.data() {
+                        None => self.expect_type(
+                            request_env,
+                            prior_eff,
+                            arg2,
+                            Type::primitive_long(),
+                            type_errors,
+                            |_| None,
+                        ),
+                        Some(ty) if comparable_types.contains(ty) => self.expect_type(
+                            request_env,
+                            prior_eff,
+                            arg2,
+                            ty.clone(),
+                            type_errors,
+                            |_| None,
+                        ),
+                        _ => self.expect_one_of_types(
+                            request_env,
+                            prior_eff,
+                            arg2,
+                            &comparable_types,
+                            type_errors,
+                            |_| None,
+                        ),
+                    };
                     ans_arg2.then_typecheck(|expr_ty_arg2, _| {
                         TypecheckAnswer::success(
                             ExprBuilder::with_data(Some(Type::primitive_boolean()))
@@ -1474,6 +1518,144 @@ impl<'a> Typechecker<'a> {
                 self.typecheck_in(request_env, prior_eff, bin_expr, arg1, arg2, type_errors)
             }
 
+            BinaryOp::HasTag => {
+                // `hasTag` applies to an entity and a string naming the tag
+                self.expect_type(
+                    request_env,
+                    prior_eff,
+                    arg1,
+                    Type::any_entity_reference(),
+                    type_errors,
+                    |actual| match actual {
+                        Type::EntityOrRecord(EntityRecordKind::Record { .. }) => {
+                            Some(UnexpectedTypeHelp::TryUsingHas)
+                        }
+                        _ => None,
+                    },
+                )
+                .then_typecheck(|expr_ty_arg1, _| {
+                    self.expect_type(
+                        request_env,
+                        prior_eff,
+                        arg2,
+                        Type::primitive_string(),
+                        type_errors,
+                        |_| None,
+                    )
+                    .then_typecheck(|expr_ty_arg2, _| {
+                        // As with `has`, this is where effect information is
+                        // generated: when `hasTag` is `true`, it is safe to
+                        // access the same tag on the same expression.
+                        let effect = Effect::new_tag(arg1, arg2);
+                        let type_of_has_tag = if prior_eff.contains(&effect) {
+                            Type::singleton_boolean(true)
+                        } else {
+                            match expr_ty_arg1.data() {
+                                // None of the entity types may have tags, so
+                                // this is always `false`.
+                                Some(Type::EntityOrRecord(EntityRecordKind::Entity(lub)))
+                                    if !lub.may_have_tags(self.schema) =>
+                                {
+                                    Type::singleton_boolean(false)
+                                }
+                                _ => Type::primitive_boolean(),
+                            }
+                        };
+                        TypecheckAnswer::success_with_effect(
+                            ExprBuilder::with_data(Some(type_of_has_tag))
+                                .with_same_source_loc(bin_expr)
+                                .binary_app(*op, expr_ty_arg1, expr_ty_arg2),
+                            EffectSet::singleton(effect),
+                        )
+                    })
+                })
+            }
+
+            BinaryOp::GetTag => {
+                // `getTag` applies to an entity and a string naming the tag
+                self.expect_type(
+                    request_env,
+                    prior_eff,
+                    arg1,
+                    Type::any_entity_reference(),
+                    type_errors,
+                    |_| None,
+                )
+                .then_typecheck(|expr_ty_arg1, _| {
+                    self.expect_type(
+                        request_env,
+                        prior_eff,
+                        arg2,
+                        Type::primitive_string(),
+                        type_errors,
+                        |_| None,
+                    )
+                    .then_typecheck(|expr_ty_arg2, _| {
+                        let tag_ty = match expr_ty_arg1.data() {
+                            Some(Type::EntityOrRecord(EntityRecordKind::Entity(lub))) => {
+                                match lub.get_tag_type(self.schema, self.mode) {
+                                    Ok(Some(tag_ty)) => Some(tag_ty),
+                                    Ok(None) => {
+                                        type_errors.push(TypeError::no_tags_allowed(
+                                            bin_expr.clone(),
+                                            expr_ty_arg1.data().clone(),
+                                        ));
+                                        None
+                                    }
+                                    Err((tag_tys, hint)) => {
+                                        type_errors.push(TypeError::incompatible_types(
+                                            bin_expr.clone(),
+                                            tag_tys,
+                                            hint,
+                                            LubContext::GetTag,
+                                        ));
+                                        None
+                                    }
+                                }
+                            }
+                            // In partial schema validation, an entity of
+                            // unknown type may have any tags, so we give the
+                            // access the bottom type (`Never`) like we do for
+                            // unknown attributes.
+                            Some(Type::EntityOrRecord(EntityRecordKind::AnyEntity))
+                                if self.mode.is_partial() =>
+                            {
+                                Some(Type::Never)
+                            }
+                            _ => {
+                                type_errors.push(TypeError::no_tags_allowed(
+                                    bin_expr.clone(),
+                                    expr_ty_arg1.data().clone(),
+                                ));
+                                None
+                            }
+                        };
+                        let annot_expr = ExprBuilder::with_data(tag_ty.clone())
+                            .with_same_source_loc(bin_expr)
+                            .binary_app(*op, expr_ty_arg1, expr_ty_arg2);
+                        match tag_ty {
+                            // A safe access to a tag requires that the
+                            // tag is in the prior effect set, i.e., the
+                            // access is guarded by a `hasTag` on the same
+                            // entity and tag expressions.
+                            Some(_) if prior_eff.contains(&Effect::new_tag(arg1, arg2)) => {
+                                TypecheckAnswer::success(annot_expr)
+                            }
+                            Some(Type::Never) => TypecheckAnswer::success(annot_expr),
+                            Some(_) => {
+                                type_errors.push(TypeError::unsafe_tag_access(
+                                    bin_expr.clone(),
+                                    arg1,
+                                    arg2,
+                                ));
+                                TypecheckAnswer::fail(annot_expr)
+                            }
+                            None => TypecheckAnswer::fail(annot_expr),
+                        }
+                    })
+                })
+            }
+
             BinaryOp::Contains => {
                 // The first argument must be a set.
                 self.expect_type(
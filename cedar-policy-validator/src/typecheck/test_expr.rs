@@ -26,8 +26,9 @@ use serde_json::json;
 use smol_str::SmolStr;
 
 use crate::{
-    type_error::TypeError, types::Type, AttributeAccess, AttributesOrContext, EntityType,
-    LubContext, LubHelp, NamespaceDefinition, SchemaFragment, UnexpectedTypeHelp, ValidationMode,
+    type_error::TypeError, types::Type, Annotations, AttributeAccess, AttributesOrContext,
+    EntityType, LubContext, LubHelp, NamespaceDefinition, SchemaFragment, UnexpectedTypeHelp,
+    ValidationMode,
 };
 
 use super::test_utils::{
@@ -59,6 +60,9 @@ fn slot_in_typechecks() {
     let etype = EntityType {
         member_of_types: vec![],
         shape: AttributesOrContext::default(),
+        tags: None,
+        choices: None,
+        annotations: Annotations::default(),
     };
     let schema = NamespaceDefinition::new([("typename".parse().unwrap(), etype)], []);
     assert_typechecks_for_mode(
@@ -88,6 +92,9 @@ fn slot_equals_typechecks() {
     let etype = EntityType {
         member_of_types: vec![],
         shape: AttributesOrContext::default(),
+        tags: None,
+        choices: None,
+        annotations: Annotations::default(),
     };
     // These don't typecheck in strict mode because the test_util expression
     // typechecker doesn't have access to a schema, so it can't link
@@ -957,9 +964,9 @@ fn less_than_typecheck_fails() {
         Expr::less(Expr::val(true), Expr::val(false)),
         Type::primitive_boolean(),
         vec![
-            TypeError::expected_type(
+            TypeError::expected_one_of_types(
                 Expr::val(true),
-                Type::primitive_long(),
+                Type::comparable_types(),
                 Type::singleton_boolean(true),
                 None,
             ),
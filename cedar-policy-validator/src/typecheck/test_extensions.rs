@@ -144,3 +144,131 @@ fn decimal_extension_typecheck_fails() {
         )],
     );
 }
+
+#[test]
+#[cfg(feature = "datetime")]
+fn datetime_extension_typechecks() {
+    let datetime_name =
+        Name::parse_unqualified_name("datetime").expect("should be a valid identifier");
+    let duration_name =
+        Name::parse_unqualified_name("duration").expect("should be a valid identifier");
+    let expr = Expr::from_str(r#"datetime("2024-10-15")"#).expect("parsing should succeed");
+    assert_typechecks_empty_schema(expr, Type::extension(datetime_name.clone()));
+    let expr = Expr::from_str(r#"duration("1d2h")"#).expect("parsing should succeed");
+    assert_typechecks_empty_schema(expr, Type::extension(duration_name.clone()));
+    let expr = Expr::from_str(r#"datetime("2024-10-15").offset(duration("1h"))"#)
+        .expect("parsing should succeed");
+    assert_typechecks_empty_schema(expr, Type::extension(datetime_name.clone()));
+    let expr =
+        Expr::from_str(r#"datetime("2024-10-15").toDate()"#).expect("parsing should succeed");
+    assert_typechecks_empty_schema(expr, Type::extension(datetime_name));
+    let expr = Expr::from_str(r#"datetime("2024-10-15").durationSince(datetime("2024-10-14"))"#)
+        .expect("parsing should succeed");
+    assert_typechecks_empty_schema(expr, Type::extension(duration_name.clone()));
+    let expr = Expr::from_str(r#"datetime("2024-10-15T11:38:02Z").toTime()"#)
+        .expect("parsing should succeed");
+    assert_typechecks_empty_schema(expr, Type::extension(duration_name));
+    let expr = Expr::from_str(r#"duration("1d").toHours()"#).expect("parsing should succeed");
+    assert_typechecks_empty_schema(expr, Type::primitive_long());
+    for src in [
+        r#"datetime("2024-10-15") < datetime("2024-10-16")"#,
+        r#"datetime("2024-10-15") <= datetime("2024-10-16")"#,
+        r#"datetime("2024-10-15") > datetime("2024-10-16")"#,
+        r#"datetime("2024-10-15") >= datetime("2024-10-16")"#,
+        r#"duration("1h") < duration("1d")"#,
+        r#"duration("1h") >= duration("1d")"#,
+    ] {
+        let expr = Expr::from_str(src).expect("parsing should succeed");
+        assert_typechecks_empty_schema(expr, Type::primitive_boolean());
+    }
+}
+
+#[test]
+#[cfg(feature = "datetime")]
+fn datetime_extension_typecheck_fails() {
+    let datetime_name =
+        Name::parse_unqualified_name("datetime").expect("should be a valid identifier");
+    let duration_name =
+        Name::parse_unqualified_name("duration").expect("should be a valid identifier");
+    let expr = Expr::from_str(r#"datetime("2024-13-01")"#).expect("parsing should succeed");
+    assert_typecheck_fails_empty_schema(
+        expr.clone(),
+        Type::extension(datetime_name.clone()),
+        vec![TypeError::arg_validation_error(
+            expr,
+            r#"Failed to parse as a datetime value: `"2024-13-01"`"#.into(),
+        )],
+    );
+    let expr = Expr::from_str(r#"duration("1h1d")"#).expect("parsing should succeed");
+    assert_typecheck_fails_empty_schema(
+        expr.clone(),
+        Type::extension(duration_name.clone()),
+        vec![TypeError::arg_validation_error(
+            expr,
+            r#"Failed to parse as a duration value: `"1h1d"`"#.into(),
+        )],
+    );
+    let expr = Expr::from_str(r#"datetime("2024-10-15").offset(datetime("2024-10-15"))"#)
+        .expect("parsing should succeed");
+    assert_typecheck_fails_empty_schema(
+        expr,
+        Type::extension(datetime_name.clone()),
+        vec![TypeError::expected_type(
+            Expr::from_str(r#"datetime("2024-10-15")"#).expect("parsing should succeed"),
+            Type::extension(duration_name.clone()),
+            Type::extension(datetime_name.clone()),
+            None,
+        )],
+    );
+    // comparison operands must have the same type
+    let expr = Expr::from_str(r#"datetime("2024-10-15") < duration("1d")"#)
+        .expect("parsing should succeed");
+    assert_typecheck_fails_empty_schema(
+        expr,
+        Type::primitive_boolean(),
+        vec![TypeError::expected_type(
+            Expr::from_str(r#"duration("1d")"#).expect("parsing should succeed"),
+            Type::extension(datetime_name.clone()),
+            Type::extension(duration_name),
+            None,
+        )],
+    );
+    let expr = Expr::from_str(r#"1 <= datetime("2024-10-15")"#).expect("parsing should succeed");
+    assert_typecheck_fails_empty_schema(
+        expr,
+        Type::primitive_boolean(),
+        vec![TypeError::expected_type(
+            Expr::from_str(r#"datetime("2024-10-15")"#).expect("parsing should succeed"),
+            Type::primitive_long(),
+            Type::extension(datetime_name),
+            None,
+        )],
+    );
+}
+
+#[test]
+#[cfg(all(feature = "datetime", feature = "decimal"))]
+fn decimal_does_not_support_comparison_operators() {
+    let decimal_name =
+        Name::parse_unqualified_name("decimal").expect("should be a valid identifier");
+    let expr =
+        Expr::from_str(r#"decimal("1.23") < decimal("1.24")"#).expect("parsing should succeed");
+    assert_typecheck_fails_empty_schema(
+        expr,
+        Type::primitive_boolean(),
+        vec![
+            TypeError::expected_one_of_types(
+                Expr::from_str(r#"decimal("1.23")"#).expect("parsing should succeed"),
+                Type::comparable_types(),
+                Type::extension(decimal_name.clone()),
+                None,
+            ),
+            TypeError::expected_type(
+                Expr::from_str(r#"decimal("1.24")"#).expect("parsing should succeed"),
+                Type::primitive_long(),
+                Type::extension(decimal_name),
+                None,
+            ),
+        ],
+    );
+}
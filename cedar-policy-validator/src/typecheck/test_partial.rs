@@ -412,9 +412,9 @@ mod fails_empty_schema {
                 r#"permit(principal, action, resource) when { principal.foo > "a" };"#,
             )
             .unwrap(),
-            vec![TypeError::expected_type(
+            vec![TypeError::expected_one_of_types(
                 Expr::val("a"),
-                Type::primitive_long(),
+                Type::comparable_types(),
                 Type::primitive_string(),
                 None,
             )],
@@ -665,9 +665,9 @@ mod fail_partial_schema {
                 r#"permit(principal == User::"alice", action, resource) when { principal.name > principal.unknown };"#,
             )
             .unwrap(),
-            vec![TypeError::expected_type(
+            vec![TypeError::expected_one_of_types(
                 Expr::get_attr(Expr::var(Var::Principal), "name".into()),
-                Type::primitive_long(),
+                Type::comparable_types(),
                 Type::primitive_string(),
                 None,
             )],
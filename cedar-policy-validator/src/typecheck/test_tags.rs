@@ -0,0 +1,199 @@
+/*
+ * Copyright Cedar Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Contains tests for typechecking entity tag operations (`hasTag` and
+//! `getTag`), including the effects that make a `getTag` access safe.
+#![cfg(test)]
+// GRCOV_STOP_COVERAGE
+
+use cedar_policy_core::{
+    ast::{Expr, StaticPolicy, Var},
+    parser::parse_policy,
+};
+
+use crate::{
+    type_error::{TypeError, UnexpectedTypeHelp},
+    types::{OpenTag, Type},
+    NamespaceDefinition, ValidationWarningKind,
+};
+
+use super::test_utils::{
+    assert_policy_typecheck_fails, assert_policy_typecheck_warns, assert_policy_typechecks,
+};
+
+fn schema_with_tags() -> NamespaceDefinition {
+    serde_json::from_str::<NamespaceDefinition>(
+        r#"
+{
+    "entityTypes": {
+        "User": {
+            "shape": {
+                "type": "Record",
+                "attributes": {
+                    "team": { "type": "String" }
+                }
+            },
+            "tags": { "type": "Set", "element": { "type": "String" } }
+        },
+        "Photo": { }
+    },
+    "actions": {
+        "view_photo": {
+            "appliesTo": {
+                "principalTypes": ["User"],
+                "resourceTypes": ["Photo"],
+                "context": {
+                    "type": "Record",
+                    "attributes": {
+                        "key": { "type": "String" }
+                    }
+                }
+            }
+        }
+    }
+}
+    "#,
+    )
+    .expect("Expected valid schema.")
+}
+
+#[track_caller] // report the caller's location as the location of the panic, not the location in this function
+fn assert_policy_typechecks_tags_schema(p: StaticPolicy) {
+    assert_policy_typechecks(schema_with_tags(), p);
+}
+
+#[track_caller] // report the caller's location as the location of the panic, not the location in this function
+fn assert_policy_typecheck_fails_tags_schema(
+    p: StaticPolicy,
+    expected_type_errors: Vec<TypeError>,
+) {
+    assert_policy_typecheck_fails(schema_with_tags(), p, expected_type_errors);
+}
+
+#[test]
+fn guarded_literal_tag_access() {
+    let policy = parse_policy(
+        Some("0".to_string()),
+        r#"permit(principal, action, resource) when { principal.hasTag("projects") && principal.getTag("projects").contains("cedar") };"#,
+    )
+    .expect("Policy should parse.");
+    assert_policy_typechecks_tags_schema(policy);
+}
+
+#[test]
+fn guarded_computed_tag_access() {
+    let policy = parse_policy(
+        Some("0".to_string()),
+        r#"permit(principal, action, resource) when { principal.hasTag(context.key) && principal.getTag(context.key).contains(principal.team) };"#,
+    )
+    .expect("Policy should parse.");
+    assert_policy_typechecks_tags_schema(policy);
+}
+
+#[test]
+fn guarded_in_if_condition() {
+    let policy = parse_policy(
+        Some("0".to_string()),
+        r#"permit(principal, action, resource) when { if principal.hasTag("projects") then principal.getTag("projects").contains("cedar") else false };"#,
+    )
+    .expect("Policy should parse.");
+    assert_policy_typechecks_tags_schema(policy);
+}
+
+#[test]
+fn unguarded_tag_access_fails() {
+    let policy = parse_policy(
+        Some("0".to_string()),
+        r#"permit(principal, action, resource) when { principal.getTag("projects").contains("cedar") };"#,
+    )
+    .expect("Policy should parse.");
+    assert_policy_typecheck_fails_tags_schema(
+        policy,
+        vec![TypeError::unsafe_tag_access(
+            Expr::get_tag(Expr::var(Var::Principal), Expr::val("projects")),
+            &Expr::var(Var::Principal),
+            &Expr::val("projects"),
+        )],
+    );
+}
+
+#[test]
+fn guard_on_different_tag_fails() {
+    let policy = parse_policy(
+        Some("0".to_string()),
+        r#"permit(principal, action, resource) when { principal.hasTag("teams") && principal.getTag("projects").contains("cedar") };"#,
+    )
+    .expect("Policy should parse.");
+    assert_policy_typecheck_fails_tags_schema(
+        policy,
+        vec![TypeError::unsafe_tag_access(
+            Expr::get_tag(Expr::var(Var::Principal), Expr::val("projects")),
+            &Expr::var(Var::Principal),
+            &Expr::val("projects"),
+        )],
+    );
+}
+
+#[test]
+fn tag_access_without_tags_fails() {
+    let policy = parse_policy(
+        Some("0".to_string()),
+        r#"permit(principal, action, resource) when { resource.getTag("owner") == "alice" };"#,
+    )
+    .expect("Policy should parse.");
+    assert_policy_typecheck_fails_tags_schema(
+        policy,
+        vec![TypeError::no_tags_allowed(
+            Expr::get_tag(Expr::var(Var::Resource), Expr::val("owner")),
+            Some(Type::named_entity_reference("Photo".parse().unwrap())),
+        )],
+    );
+}
+
+#[test]
+fn tag_access_on_record_fails() {
+    let policy = parse_policy(
+        Some("0".to_string()),
+        r#"permit(principal, action, resource) when { context.hasTag("key") };"#,
+    )
+    .expect("Policy should parse.");
+    assert_policy_typecheck_fails_tags_schema(
+        policy,
+        vec![TypeError::expected_type(
+            Expr::var(Var::Context),
+            Type::any_entity_reference(),
+            Type::record_with_required_attributes(
+                [("key".into(), Type::primitive_string())],
+                OpenTag::ClosedAttributes,
+            ),
+            Some(UnexpectedTypeHelp::TryUsingHas),
+        )],
+    );
+}
+
+#[test]
+fn has_tag_without_tags_is_impossible() {
+    let policy = parse_policy(
+        Some("0".to_string()),
+        r#"permit(principal, action, resource) when { resource.hasTag("owner") };"#,
+    )
+    .expect("Policy should parse.");
+    assert_policy_typecheck_warns(
+        schema_with_tags(),
+        policy,
+        vec![ValidationWarningKind::ImpossiblePolicy],
+    );
+}
@@ -24,7 +24,7 @@ use cool_asserts::assert_matches;
 use serde_json::json;
 use std::collections::HashSet;
 
-use cedar_policy_core::ast::{EntityUID, Expr, ExprBuilder};
+use cedar_policy_core::ast::{self, EntityUID, Expr, ExprBuilder, Unknown};
 
 use super::test_utils::{empty_schema_file, with_typechecker_from_schema};
 use crate::{types::Type, SchemaFragment};
@@ -162,3 +162,31 @@ fn expr_typechecks_with_correct_annotation() {
         },
     );
 }
+
+#[test]
+fn typed_unknowns_typecheck_with_annotated_type() {
+    let x = Unknown::new_with_type("x", ast::Type::Long);
+    assert_expr_has_annotated_ast(
+        &Expr::add(Expr::unknown(x.clone()), Expr::val(1)),
+        &ExprBuilder::with_data(Some(Type::primitive_long())).add(
+            ExprBuilder::with_data(Some(Type::primitive_long())).unknown(x),
+            ExprBuilder::with_data(Some(Type::primitive_long())).val(1),
+        ),
+    );
+
+    with_typechecker_from_schema(empty_schema_file(), |tc| {
+        let mut errs = HashSet::new();
+        for e in [
+            Expr::unknown(Unknown::new_untyped("x")),
+            Expr::add(
+                Expr::unknown(Unknown::new_with_type("x", ast::Type::Bool)),
+                Expr::val(1),
+            ),
+        ] {
+            assert_matches!(
+                tc.typecheck_expr(&e, &mut errs),
+                super::TypecheckAnswer::TypecheckFail { .. }
+            );
+        }
+    });
+}
@@ -53,6 +53,20 @@ impl TypeError {
 }
 
 impl Type {
+    /// The types which may be operands of `<`, `<=`, `>`, and `>=`, given the
+    /// extensions enabled in this build.
+    pub(crate) fn comparable_types() -> Vec<Type> {
+        #[allow(unused_mut)]
+        let mut types = vec![Type::primitive_long()];
+        #[cfg(feature = "datetime")]
+        types.extend(
+            ["datetime", "duration"]
+                .into_iter()
+                .map(|name| Type::extension(name.parse().expect("should be a valid identifier"))),
+        );
+        types
+    }
+
     /// Construct a named entity reference type using the `Name` resulting from
     /// parsing the `name` string. This function will panic on a parse error.
     pub(crate) fn named_entity_reference_from_str(name: &str) -> Type {
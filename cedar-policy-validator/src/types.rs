@@ -27,8 +27,8 @@ use std::{
 
 use cedar_policy_core::{
     ast::{
-        BorrowedRestrictedExpr, EntityType, EntityUID, Expr, ExprShapeOnly, Name, PartialValue,
-        RestrictedExpr, Value,
+        self, BorrowedRestrictedExpr, EntityType, EntityUID, Expr, ExprShapeOnly, Name,
+        PartialValue, RestrictedExpr, Value,
     },
     entities::{conformance::typecheck_restricted_expr_against_schematype, GetSchemaTypeError},
     extensions::Extensions,
@@ -282,6 +282,21 @@ impl Type {
         Type::ExtensionType { name }
     }
 
+    /// The type of the values that can be substituted for an unknown with
+    /// the type annotation `ty`. Annotations don't carry element or attribute
+    /// types, so sets and records are typed as any set and any record.
+    pub(crate) fn from_unknown_annotation(ty: &ast::Type) -> Type {
+        match ty {
+            ast::Type::Bool => Type::primitive_boolean(),
+            ast::Type::Long => Type::primitive_long(),
+            ast::Type::String => Type::primitive_string(),
+            ast::Type::Set => Type::any_set(),
+            ast::Type::Record => Type::any_record(),
+            ast::Type::Entity { ty } => Type::possibly_unspecified_entity_reference(ty.clone()),
+            ast::Type::Extension { name } => Type::extension(name.clone()),
+        }
+    }
+
     /// Implements a subtype relation for the type structure. This requires a
     /// `schema` so that the declared attributes for named entity types can be
     /// retrieved. This is used to determine subtyping between a named entity
@@ -663,6 +678,20 @@ impl Type {
         restricted_expr: BorrowedRestrictedExpr<'_>,
         extensions: Extensions<'_>,
     ) -> Result<bool, GetSchemaTypeError> {
+        // An unknown has this type if some value it may be filled in with
+        // does: always for untyped unknowns, and for typed unknowns whenever
+        // the annotated type is consistent with this type.
+        if let Some(unknown) = restricted_expr.as_unknown() {
+            return Ok(match &unknown.type_annotation {
+                None => true,
+                Some(ast::Type::Set) => matches!(self, Type::Set { .. }),
+                Some(ast::Type::Record) => {
+                    matches!(self, Type::EntityOrRecord(EntityRecordKind::Record { .. }))
+                }
+                Some(ty) => cedar_policy_core::entities::SchemaType::from_ty(ty.clone())
+                    .is_some_and(|ty| self.is_consistent_with(&ty)),
+            });
+        }
         match self {
             Type::Never => Ok(false), // no expr has type Never
             Type::Primitive {
@@ -1000,6 +1029,49 @@ impl EntityLUB {
         })
     }
 
+    /// Retrieve the type of the tags on the entity types in this LUB. Returns
+    /// `Ok(None)` if any of the entity types does not declare tags (or is not
+    /// in the schema), since we then can't give a type to a tag access. When
+    /// there are several entity types, their tag types must have a least upper
+    /// bound, otherwise the error from computing it is returned.
+    pub(crate) fn get_tag_type(
+        &self,
+        schema: &ValidatorSchema,
+        mode: ValidationMode,
+    ) -> Result<Option<Type>, (Vec<Type>, LubHelp)> {
+        let tag_types = match self
+            .lub_elements
+            .iter()
+            .map(|name| schema.get_entity_type(name)?.tag_type().cloned())
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(tag_types) => tag_types,
+            None => return Ok(None),
+        };
+        let mut iter = tag_types.iter();
+        // PANIC SAFETY: Invariant on `lub_elements` guarantees the set is non-empty.
+        #[allow(clippy::expect_used)]
+        let first = iter
+            .next()
+            .expect("Invariant violated: EntityLUB set must be non-empty.")
+            .clone();
+        iter.try_fold(first, |acc, ty| {
+            Type::least_upper_bound(schema, &acc, ty, mode)
+        })
+        .map(Some)
+        .map_err(|hint| (tag_types.clone(), hint))
+    }
+
+    /// Check whether any of the entity types in this LUB may have tags, i.e.,
+    /// it declares tags or it is not in the schema.
+    pub(crate) fn may_have_tags(&self, schema: &ValidatorSchema) -> bool {
+        self.lub_elements.iter().any(|name| {
+            schema
+                .get_entity_type(name)
+                .map_or(true, |ety| ety.tag_type().is_some())
+        })
+    }
+
     /// Generate the least upper bound of this EntityLUB and another. This
     /// returns an EntityLUB for the union of the entity types in both argument
     /// LUBs. The attributes of the LUB are not computed.
@@ -1526,8 +1598,8 @@ pub enum Primitive {
     String,
 }
 
-/// A set of effects. Used to represent knowledge about attribute existence
-/// before and after evaluating an expression.
+/// A set of effects. Used to represent knowledge about attribute and tag
+/// existence before and after evaluating an expression.
 #[derive(Eq, PartialEq, Debug, Clone, Default)]
 pub struct EffectSet<'a>(HashSet<Effect<'a>>);
 
@@ -1555,19 +1627,37 @@ impl<'a> EffectSet<'a> {
     }
 }
 
-/// Represent a single effect, which is an expression and some attribute that is
-/// known to exist for that expression.
+/// Represent a single effect, which is an expression and some attribute or tag
+/// that is known to exist for that expression.
 #[derive(Hash, Eq, PartialEq, Debug, Clone)]
 pub struct Effect<'a> {
     on_expr: ExprShapeOnly<'a>,
-    attribute: &'a str,
+    attr_or_tag: EffectTarget<'a>,
+}
+
+/// What an [`Effect`] records as existing on its expression.
+#[derive(Hash, Eq, PartialEq, Debug, Clone)]
+enum EffectTarget<'a> {
+    /// An attribute with this name
+    Attr(&'a str),
+    /// A tag whose key is computed by this expression. Tag keys need not be
+    /// literals, so we compare the key expressions by their shape just like we
+    /// do for the expression the tag is on.
+    Tag(ExprShapeOnly<'a>),
 }
 
 impl<'a> Effect<'a> {
     pub fn new(on_expr: &'a Expr, attribute: &'a str) -> Self {
         Self {
             on_expr: ExprShapeOnly::new(on_expr),
-            attribute,
+            attr_or_tag: EffectTarget::Attr(attribute),
+        }
+    }
+
+    pub fn new_tag(on_expr: &'a Expr, tag: &'a Expr) -> Self {
+        Self {
+            on_expr: ExprShapeOnly::new(on_expr),
+            attr_or_tag: EffectTarget::Tag(ExprShapeOnly::new(tag)),
         }
     }
 }
@@ -19,6 +19,7 @@ use cedar_policy_core::parser::Loc;
 use miette::Diagnostic;
 use thiserror::Error;
 
+use crate::lint::LintViolation;
 use crate::TypeErrorKind;
 
 /// Contains the result of policy validation. The result includes the list of
@@ -186,6 +187,11 @@ pub enum ValidationErrorKind {
     #[error(transparent)]
     #[diagnostic(transparent)]
     UnrecognizedActionId(#[from] UnrecognizedActionId),
+    /// A policy contains an entity of an enumerated entity type whose entity
+    /// ID is not one of the IDs declared in the schema.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidEnumEntity(#[from] InvalidEnumEntity),
     /// There is no action satisfying the action scope constraint that can be
     /// applied to a principal and resources that both satisfy their respective
     /// scope conditions.
@@ -201,6 +207,16 @@ pub enum ValidationErrorKind {
     #[error(transparent)]
     #[diagnostic(transparent)]
     UnspecifiedEntity(#[from] UnspecifiedEntityError),
+    /// A policy dereferences entities more levels from the request than
+    /// [`crate::Validator::validate_with_level`] allows.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    EntityDerefLevelViolation(#[from] EntityDerefLevelViolation),
+    /// A lint rule enabled with [`crate::lint::LintSeverity::Error`] found a
+    /// problem.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Lint(LintViolation),
 }
 
 impl ValidationErrorKind {
@@ -226,6 +242,17 @@ impl ValidationErrorKind {
         .into()
     }
 
+    pub(crate) fn invalid_enum_entity(
+        actual_entity: String,
+        suggested_entity: Option<String>,
+    ) -> ValidationErrorKind {
+        InvalidEnumEntity {
+            actual_entity,
+            suggested_entity,
+        }
+        .into()
+    }
+
     pub(crate) fn invalid_action_application(
         would_in_fix_principal: bool,
         would_in_fix_resource: bool,
@@ -244,6 +271,17 @@ impl ValidationErrorKind {
     pub(crate) fn unspecified_entity(entity_id: String) -> ValidationErrorKind {
         UnspecifiedEntityError { entity_id }.into()
     }
+
+    pub(crate) fn entity_deref_level_violation(
+        actual_level: u32,
+        allowed_level: u32,
+    ) -> ValidationErrorKind {
+        EntityDerefLevelViolation {
+            actual_level,
+            allowed_level,
+        }
+        .into()
+    }
 }
 
 /// Structure containing details about an unrecognized entity type error.
@@ -286,6 +324,29 @@ impl Diagnostic for UnrecognizedActionId {
     }
 }
 
+/// Structure containing details about an entity of an enumerated entity type
+/// with an undeclared entity ID.
+#[derive(Debug, Clone, Error, Eq, PartialEq)]
+#[error(
+    "entity `{actual_entity}` is not one of the entities declared by its enumerated entity type"
+)]
+pub struct InvalidEnumEntity {
+    /// The entity seen in the policy.
+    pub(crate) actual_entity: String,
+    /// An entity of the same type that the user might reasonably have
+    /// intended to write.
+    pub(crate) suggested_entity: Option<String>,
+}
+
+impl Diagnostic for InvalidEnumEntity {
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        match &self.suggested_entity {
+            Some(s) => Some(Box::new(format!("did you mean `{s}`?"))),
+            None => None,
+        }
+    }
+}
+
 /// Structure containing details about an invalid action application error.
 #[derive(Debug, Clone, Error, Eq, PartialEq)]
 #[error("unable to find an applicable action given the policy scope constraints")]
@@ -311,6 +372,36 @@ impl Diagnostic for InvalidActionApplication {
     }
 }
 
+/// Structure containing details about an entity dereference level violation.
+#[derive(Debug, Clone, Error, Eq, PartialEq)]
+#[error("entity dereference at level {actual_level} exceeds the maximum allowed level of {allowed_level}")]
+pub struct EntityDerefLevelViolation {
+    /// The level of the dereference
+    pub(crate) actual_level: u32,
+    /// The maximum level allowed
+    pub(crate) allowed_level: u32,
+}
+
+impl Diagnostic for EntityDerefLevelViolation {
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(
+            "consider passing the data this expression reads in the request context",
+        ))
+    }
+}
+
+impl EntityDerefLevelViolation {
+    /// The level of the dereference
+    pub fn actual_level(&self) -> u32 {
+        self.actual_level
+    }
+
+    /// The maximum level allowed
+    pub fn allowed_level(&self) -> u32 {
+        self.allowed_level
+    }
+}
+
 /// Structure containing details about an unspecified entity error.
 #[derive(Debug, Clone, Diagnostic, Error, Eq, PartialEq)]
 #[error("unspecified entity with id `{entity_id}`")]
@@ -427,4 +518,12 @@ pub enum ValidationWarningKind {
         "policy is impossible: the policy expression evaluates to false for all valid requests"
     )]
     ImpossiblePolicy,
+    /// Symbolic analysis found that the `when` and `unless` clauses of a
+    /// policy hold on every valid request its scope matches.
+    #[error("policy condition is always true: the policy applies to every valid request its scope matches")]
+    TautologicalCondition,
+    /// A lint rule enabled with [`crate::lint::LintSeverity::Warning`] found
+    /// a problem.
+    #[error("{0}")]
+    Lint(LintViolation),
 }
@@ -39,13 +39,18 @@ use cedar_policy_core::authorizer;
 use cedar_policy_core::entities::{ContextSchema, Dereference};
 use cedar_policy_core::est;
 use cedar_policy_core::est::{Link, PolicyEntry};
-use cedar_policy_core::evaluator::Evaluator;
+pub use cedar_policy_core::evaluator::EvaluationLimits;
 #[cfg(feature = "partial-eval")]
 use cedar_policy_core::evaluator::RestrictedEvaluator;
+use cedar_policy_core::evaluator::{self, Evaluator};
 pub use cedar_policy_core::extensions;
 use cedar_policy_core::extensions::Extensions;
+#[doc = include_str!("../experimental_warning.md")]
+#[cfg(feature = "partial-eval")]
+pub use cedar_policy_core::filter;
 use cedar_policy_core::parser;
 use cedar_policy_core::FromNormalizedStr;
+pub use cedar_policy_validator::lint::LintSeverity;
 use cedar_policy_validator::RequestValidationError; // this type is unsuitable for `pub use` because it contains internal types like `EntityUID` and `EntityType`
 use itertools::{Either, Itertools};
 use miette::Diagnostic;
@@ -77,7 +82,7 @@ pub mod entities {
     /// Errors around entities
     pub mod err {
         pub use cedar_policy_core::entities::err::{
-            Duplicate, EntitiesError, TransitiveClosureError,
+            Duplicate, EntitiesError, EntityNotFound, TransitiveClosureError,
         };
     }
 
@@ -103,8 +108,9 @@ pub mod entities {
         pub mod err {
             pub use cedar_policy_core::entities::conformance::err::{
                 ActionDeclarationMismatch, EntitySchemaConformanceError, ExtensionFunctionLookup,
-                HeterogeneousSet, InvalidAncestorType, MissingRequiredEntityAttr, TypeMismatch,
-                UndeclaredAction, UnexpectedEntityAttr, UnexpectedEntityTypeError,
+                HeterogeneousSet, InvalidAncestorType, InvalidEnumEntity,
+                MissingRequiredEntityAttr, TypeMismatch, UndeclaredAction, UnexpectedEntityAttr,
+                UnexpectedEntityTag, UnexpectedEntityTypeError,
             };
         }
     }
@@ -144,6 +150,30 @@ impl Entity {
         uid: EntityUid,
         attrs: HashMap<String, RestrictedExpression>,
         parents: HashSet<EntityUid>,
+    ) -> Result<Self, EntityAttrEvaluationError> {
+        Self::new_with_tags(uid, attrs, parents, HashMap::new())
+    }
+
+    /// Create a new `Entity` with this Uid, attributes, parents, and tags.
+    ///
+    /// Attribute and tag values are specified here as "restricted expressions".
+    /// See docs on `RestrictedExpression`
+    /// ```
+    /// # use cedar_policy::{Entity, EntityUid, EvalResult, RestrictedExpression};
+    /// # use std::collections::{HashMap, HashSet};
+    /// # use std::str::FromStr;
+    /// let euid = EntityUid::from_str(r#"User::"alice""#).unwrap();
+    /// let tags = HashMap::from([
+    ///     ("clearance".to_string(), RestrictedExpression::from_str("3").unwrap()),
+    /// ]);
+    /// let entity = Entity::new_with_tags(euid, HashMap::new(), HashSet::new(), tags).unwrap();
+    /// assert_eq!(entity.tag("clearance").unwrap().unwrap(), EvalResult::Long(3));
+    /// ```
+    pub fn new_with_tags(
+        uid: EntityUid,
+        attrs: HashMap<String, RestrictedExpression>,
+        parents: HashSet<EntityUid>,
+        tags: HashMap<String, RestrictedExpression>,
     ) -> Result<Self, EntityAttrEvaluationError> {
         // note that we take a "parents" parameter here; we will compute TC when
         // the `Entities` object is created
@@ -155,6 +185,9 @@ impl Entity {
                 .map(|(k, v)| (SmolStr::from(k), v.0))
                 .collect(),
             parents.into_iter().map(EntityUid::into).collect(),
+            tags.into_iter()
+                .map(|(k, v)| (SmolStr::from(k), v.0))
+                .collect(),
             &Extensions::all_available(),
         )?))
     }
@@ -189,6 +222,21 @@ impl Entity {
         Self(ast::Entity::with_uid(uid.into()))
     }
 
+    /// Mark the ancestors of this entity as only partially known, for partial
+    /// evaluation.
+    ///
+    /// Checking whether this entity (or a descendant of it) is `in` another
+    /// entity that isn't one of its known ancestors produces a residual over
+    /// an unknown named `name`. That unknown stands for the set of this
+    /// entity's remaining ancestors, and should be filled in with a set of
+    /// `EntityUid`s.
+    #[doc = include_str!("../experimental_warning.md")]
+    #[cfg(feature = "partial-eval")]
+    #[must_use]
+    pub fn with_unknown_ancestors(self, name: impl AsRef<str>) -> Self {
+        Self(self.0.with_unknown_ancestors(name.as_ref()))
+    }
+
     /// Get the Uid of this entity
     /// ```
     /// # use cedar_policy::{Entity, EntityId, EntityTypeName, EntityUid};
@@ -232,7 +280,20 @@ impl Entity {
         Some(Ok(EvalResult::from(v)))
     }
 
+    /// Get the value for the given tag, or `None` if not present.
+    ///
+    /// This can also return Some(Err) if the tag is not a value (i.e., is
+    /// unknown due to partial evaluation).
+    pub fn tag(&self, tag: &str) -> Option<Result<EvalResult, impl miette::Diagnostic>> {
+        let v = match ast::Value::try_from(self.0.get_tag(tag)?.clone()) {
+            Ok(v) => v,
+            Err(e) => return Some(Err(e)),
+        };
+        Some(Ok(EvalResult::from(v)))
+    }
+
     /// Consume the entity and return the entity's owned Uid, attributes and parents.
+    /// Any tags on the entity are dropped.
     pub fn into_inner(
         self,
     ) -> (
@@ -240,7 +301,7 @@ impl Entity {
         HashMap<String, RestrictedExpression>,
         HashSet<EntityUid>,
     ) {
-        let (uid, attrs, ancestors) = self.0.into_inner();
+        let (uid, attrs, ancestors, _) = self.0.into_inner();
 
         let attrs = attrs
             .into_iter()
@@ -371,6 +432,103 @@ impl Entities {
         ))
     }
 
+    /// Add all of the [`Entity`]s in the collection to this [`Entities`]
+    /// structure, replacing any existing entities with the same UIDs. If the
+    /// collection contains several entities with the same UID, the last one
+    /// wins.
+    ///
+    /// Unlike [`Entities::add_entities()`], this updates the [`Entities`] in
+    /// place and does not re-compute the transitive closure from scratch.
+    /// Only the ancestors of the upserted entities and of their descendants
+    /// are updated, which makes this suitable for keeping a long-lived
+    /// [`Entities`] up to date. Returns an error if the update would introduce
+    /// a cycle in the entity hierarchy.
+    ///
+    /// If a `schema` is provided, this method will ensure that the upserted
+    /// entities fully conform to the schema, as in [`Entities::add_entities()`].
+    /// (This method will not add action entities from the `schema`.)
+    ///
+    /// If this returns an error, this [`Entities`] is left unchanged.
+    pub fn upsert_entities(
+        &mut self,
+        entities: impl IntoIterator<Item = Entity>,
+        schema: Option<&Schema>,
+    ) -> Result<(), EntitiesError> {
+        self.0.upsert_entities(
+            entities.into_iter().map(|e| e.0),
+            schema
+                .map(|s| cedar_policy_validator::CoreSchema::new(&s.0))
+                .as_ref(),
+            Extensions::all_available(),
+        )
+    }
+
+    /// Remove the entities with the given UIDs from this [`Entities`]
+    /// structure. UIDs of entities which don't exist are ignored.
+    ///
+    /// Entities which had a removed entity as a parent keep it as a parent,
+    /// but no longer have that entity's ancestors as ancestors (unless they
+    /// are reachable in some other way). Note that an entity's parents are
+    /// whatever it was created with: an entity parsed from JSON written by
+    /// [`Entities::write_to_json()`], which lists all of its ancestors as
+    /// parents, keeps all of those ancestors. As with
+    /// [`Entities::upsert_entities()`], the transitive closure is updated
+    /// incrementally, and this [`Entities`] is left unchanged if this returns
+    /// an error.
+    /// ```
+    /// # use cedar_policy::{Entities, Entity, EntityUid};
+    /// # use std::collections::HashSet;
+    /// # use std::str::FromStr;
+    /// let alice = EntityUid::from_str(r#"User::"alice""#).unwrap();
+    /// let admins = EntityUid::from_str(r#"Group::"admins""#).unwrap();
+    /// let staff = EntityUid::from_str(r#"Group::"staff""#).unwrap();
+    /// let mut entities = Entities::from_entities(
+    ///     [
+    ///         Entity::new_no_attrs(alice.clone(), HashSet::from([admins.clone()])),
+    ///         Entity::new_no_attrs(admins.clone(), HashSet::from([staff.clone()])),
+    ///     ],
+    ///     None,
+    /// )
+    /// .unwrap();
+    /// assert!(entities.is_ancestor_of(&staff, &alice));
+    /// entities.remove_entities([admins]).unwrap();
+    /// assert!(!entities.is_ancestor_of(&staff, &alice));
+    /// ```
+    pub fn remove_entities(
+        &mut self,
+        entity_ids: impl IntoIterator<Item = EntityUid>,
+    ) -> Result<(), EntitiesError> {
+        self.0
+            .remove_entities(entity_ids.into_iter().map(EntityUid::into))
+    }
+
+    /// Replace the parents of the entity with the given UID, keeping its
+    /// attributes and tags. The transitive closure is updated incrementally,
+    /// as in [`Entities::upsert_entities()`].
+    ///
+    /// Returns an error if there is no entity with the given UID, or if the
+    /// new parents would introduce a cycle in the entity hierarchy.
+    /// If a `schema` is provided, this method will also ensure that the
+    /// updated entity conforms to the schema, e.g., that the new parents have
+    /// types allowed by the schema.
+    ///
+    /// If this returns an error, this [`Entities`] is left unchanged.
+    pub fn set_parents(
+        &mut self,
+        uid: &EntityUid,
+        parents: impl IntoIterator<Item = EntityUid>,
+        schema: Option<&Schema>,
+    ) -> Result<(), EntitiesError> {
+        self.0.set_parents(
+            uid.as_ref(),
+            parents.into_iter().map(EntityUid::into),
+            schema
+                .map(|s| cedar_policy_validator::CoreSchema::new(&s.0))
+                .as_ref(),
+            Extensions::all_available(),
+        )
+    }
+
     /// Parse an entities JSON file (in [&str] form) and add them into this
     /// [`Entities`] structure, re-computing the transitive closure
     ///
@@ -719,6 +877,30 @@ impl Authorizer {
         Self(authorizer::Authorizer::new())
     }
 
+    /// Enforce the given limits when evaluating the policies for each request,
+    /// for instance to bound the cost of evaluating customer-authored
+    /// policies.
+    ///
+    /// A policy whose evaluation exceeds a limit is reported in the response
+    /// diagnostics with a [`LimitExceededError`]. Unlike other errors, this
+    /// doesn't just skip the policy: since it might have been a satisfied
+    /// `forbid`, the decision is `Deny` whatever the other policies evaluate
+    /// to, and no policy is reported as determining it.
+    /// ```
+    /// # use cedar_policy::{Authorizer, EvaluationLimits};
+    /// # use std::time::Duration;
+    /// let authorizer = Authorizer::new().with_limits(
+    ///     EvaluationLimits::new()
+    ///         .with_max_steps(10_000)
+    ///         .with_max_collection_size(1_000)
+    ///         .with_timeout(Duration::from_millis(50)),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_limits(self, limits: EvaluationLimits) -> Self {
+        Self(self.0.with_limits(limits))
+    }
+
     /// Returns an authorization response for `r` with respect to the given
     /// `PolicySet` and `Entities`.
     ///
@@ -776,6 +958,43 @@ impl Authorizer {
         self.0.is_authorized(r.0.clone(), &p.ast, &e.0).into()
     }
 
+    /// Returns authorization responses for each of the `requests`, with
+    /// respect to the same `PolicySet` and `Entities`.
+    ///
+    /// The responses are in the same order as `requests`, and are the same as
+    /// calling [`Authorizer::is_authorized()`] on each request. The work that
+    /// doesn't depend on the request is shared across the batch: the
+    /// condition of each policy is built once, the policies whose scope may
+    /// match a principal, action or resource are only looked up the first
+    /// time it appears, and each entity attribute access and `in` test in a
+    /// policy condition is only evaluated the first time it is reached.
+    pub fn is_authorized_batch(
+        &self,
+        requests: &[Request],
+        p: &PolicySet,
+        e: &Entities,
+    ) -> Vec<Response> {
+        self.0
+            .is_authorized_batch(requests.iter().map(|r| r.0.clone()), &p.ast, &e.0)
+            .into_iter()
+            .map(Response::from)
+            .collect()
+    }
+
+    /// Returns an authorization response for `r` with respect to the given
+    /// `PolicySet` and `Entities`, like [`Authorizer::is_authorized()`], but
+    /// with a trace of how each policy was evaluated available from
+    /// [`Diagnostics::trace()`].
+    ///
+    /// Every policy in `p` is evaluated and traced, including policies whose
+    /// scope doesn't match the request, so this is slower than
+    /// [`Authorizer::is_authorized()`] and meant for debugging decisions.
+    pub fn is_authorized_with_trace(&self, r: &Request, p: &PolicySet, e: &Entities) -> Response {
+        self.0
+            .is_authorized_with_trace(r.0.clone(), &p.ast, &e.0)
+            .into()
+    }
+
     /// A partially evaluated authorization request.
     /// The Authorizer will attempt to make as much progress as possible in the presence of unknowns.
     /// If the Authorizer can reach a response, it will return that response.
@@ -793,6 +1012,188 @@ impl Authorizer {
             .is_authorized_core(query.0.clone(), &policy_set.ast, &entities.0);
         PartialResponse(response)
     }
+
+    /// Returns an authorization response for `query` with respect to the given
+    /// `PolicySet`, fetching entities from `loader` only as evaluation reaches
+    /// them.
+    ///
+    /// Entities that evaluation needs are requested in batches, and each
+    /// loaded entity is kept for the rest of the request, so no entity is
+    /// requested twice. The parents of a loaded entity are only requested
+    /// when an `in` test can't be decided without them. Any entity the
+    /// loader doesn't return is handled as with [`Entities::partial()`]: the
+    /// policies that depend on it are left as residuals in the response.
+    ///
+    /// Fails if the loader returns an error, or if the loaded entities form
+    /// a cycle in the entity hierarchy.
+    #[doc = include_str!("../experimental_warning.md")]
+    #[cfg(feature = "partial-eval")]
+    pub fn is_authorized_with_loader(
+        &self,
+        query: &Request,
+        policy_set: &PolicySet,
+        loader: &mut impl EntityLoader,
+    ) -> Result<PartialResponse, EntitiesError> {
+        let response = self.0.is_authorized_with_loader(
+            query.0.clone(),
+            &policy_set.ast,
+            &mut LoaderAdapter(loader),
+        )?;
+        Ok(PartialResponse(response))
+    }
+
+    /// Returns the UIDs of all entities of type `resource_type` in `entities`
+    /// that `principal` may perform `action` on, i.e., those for which
+    /// [`Authorizer::is_authorized()`] would return [`Decision::Allow`].
+    ///
+    /// Instead of authorizing a request for every candidate, this partially
+    /// evaluates `policy_set` once with an unknown resource, and then only
+    /// evaluates what is left of the policies that depend on the resource for
+    /// each candidate. If `entities` is partial, resources that aren't
+    /// definitely allowed are left out.
+    #[doc = include_str!("../experimental_warning.md")]
+    #[cfg(feature = "partial-eval")]
+    pub fn list_resources(
+        &self,
+        principal: &EntityUid,
+        action: &EntityUid,
+        resource_type: &EntityTypeName,
+        context: Context,
+        policy_set: &PolicySet,
+        entities: &Entities,
+    ) -> HashSet<EntityUid> {
+        self.0
+            .list_resources(
+                principal.as_ref().clone(),
+                action.as_ref().clone(),
+                resource_type.as_ref(),
+                context.0,
+                &policy_set.ast,
+                &entities.0,
+            )
+            .into_iter()
+            .map(EntityUid::new)
+            .collect()
+    }
+}
+
+/// A source of entities for [`Authorizer::is_authorized_with_loader()`],
+/// which is queried on demand instead of materializing an [`Entities`] for
+/// the request up front.
+#[doc = include_str!("../experimental_warning.md")]
+#[cfg(feature = "partial-eval")]
+pub trait EntityLoader {
+    /// Error produced when loading fails
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Load the entities with the given UIDs.
+    ///
+    /// UIDs for which no entity exists should be left out of the result.
+    /// The parents of returned entities don't need to be transitively closed.
+    fn load_entities(&mut self, uids: &[&EntityUid]) -> Result<Vec<Entity>, Self::Error>;
+}
+
+/// Adapts an [`EntityLoader`] to the core loader interface
+#[cfg(feature = "partial-eval")]
+struct LoaderAdapter<'a, L>(&'a mut L);
+
+#[cfg(feature = "partial-eval")]
+impl<L: EntityLoader> cedar_policy_core::entities::EntityLoader for LoaderAdapter<'_, L> {
+    type Error = L::Error;
+
+    fn load_entities(&mut self, uids: &[&ast::EntityUID]) -> Result<Vec<ast::Entity>, Self::Error> {
+        let uids = uids
+            .iter()
+            .map(|uid| EntityUid::ref_cast(uid))
+            .collect::<Vec<_>>();
+        Ok(self
+            .0
+            .load_entities(&uids)?
+            .into_iter()
+            .map(|entity| entity.0)
+            .collect())
+    }
+}
+
+/// An [`Authorizer`] for a fixed [`PolicySet`] that caches its responses, for
+/// applications that see the same requests repeatedly.
+///
+/// A cached response is only reused if the request has the same principal,
+/// action, resource and context, and every entity that was looked up while
+/// computing the response (including entities that didn't exist) is unchanged
+/// in the `Entities` given with the new request. So responses are always the
+/// same as from [`Authorizer::is_authorized()`]. Replacing the policies with
+/// [`CachingAuthorizer::set_policies()`] clears the cache.
+/// ```
+/// # use cedar_policy::{Authorizer, CachingAuthorizer, Context, Decision, Entities, PolicySet, Request};
+/// # use std::str::FromStr;
+/// let policies = PolicySet::from_str(
+///     r#"permit(principal, action, resource) when { resource.owner == principal };"#,
+/// ).unwrap();
+/// let entities = Entities::from_json_value(serde_json::json!([
+///     { "uid": { "type": "Doc", "id": "a" }, "attrs": { "owner": { "__entity": { "type": "User", "id": "alice" } } }, "parents": [] },
+/// ]), None).unwrap();
+/// let request = Request::new(
+///     Some(r#"User::"alice""#.parse().unwrap()),
+///     Some(r#"Action::"view""#.parse().unwrap()),
+///     Some(r#"Doc::"a""#.parse().unwrap()),
+///     Context::empty(),
+///     None,
+/// ).unwrap();
+///
+/// let authorizer = CachingAuthorizer::new(Authorizer::new(), policies);
+/// assert_eq!(authorizer.is_authorized(&request, &entities).decision(), Decision::Allow);
+/// // answered from the cache
+/// assert_eq!(authorizer.is_authorized(&request, &entities).decision(), Decision::Allow);
+/// ```
+#[repr(transparent)]
+#[derive(Debug, RefCast)]
+pub struct CachingAuthorizer(authorizer::CachingAuthorizer);
+
+impl CachingAuthorizer {
+    /// Create a `CachingAuthorizer` which answers requests with `authorizer`
+    /// against `policies`, caching up to 10,000 responses
+    pub fn new(authorizer: Authorizer, policies: PolicySet) -> Self {
+        Self(authorizer::CachingAuthorizer::new(
+            authorizer.0,
+            policies.ast,
+        ))
+    }
+
+    /// Set the maximum number of cached responses. When the cache is full,
+    /// it is emptied before the next response is added.
+    #[must_use]
+    pub fn with_capacity(self, capacity: usize) -> Self {
+        Self(self.0.with_capacity(capacity))
+    }
+
+    /// Replace the policies requests are authorized against, clearing the
+    /// cache
+    pub fn set_policies(&mut self, policies: PolicySet) {
+        self.0.set_policies(policies.ast);
+    }
+
+    /// Remove all cached responses
+    pub fn clear(&self) {
+        self.0.clear();
+    }
+
+    /// Number of cached responses
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Is the cache empty?
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an authorization response for `r` with respect to the policies
+    /// of this `CachingAuthorizer` and `e`, reusing a cached response if it is
+    /// still valid
+    pub fn is_authorized(&self, r: &Request, e: &Entities) -> Response {
+        self.0.is_authorized(r.0.clone(), &e.0).into()
+    }
 }
 
 /// Authorization response returned from the `Authorizer`
@@ -875,6 +1276,38 @@ impl PartialResponse {
         self.0.get(id.as_ref()).map(Policy::from_ast)
     }
 
+    /// Simplify every residual into an equivalent, more readable form, e.g.
+    /// removing redundant `true &&`s and collapsing nested `if`s on the same
+    /// condition. Residuals that simplify to `true` or `false` are no longer
+    /// residuals, so the simplified response may reach a decision.
+    #[must_use]
+    pub fn simplified(&self) -> Self {
+        Self(self.0.simplified())
+    }
+
+    /// Translate this response into a [`filter::Filter`] that holds exactly
+    /// when the request is allowed, e.g. to render it as a SQL `WHERE` clause
+    /// with [`filter::Filter::to_sql()`] and let a database find the
+    /// resources a principal may access.
+    ///
+    /// Fails with [`filter::FilterError::Unsupported`] if a residual uses a
+    /// construct with no counterpart in the filter language, such as a `like`
+    /// pattern or an extension function call.
+    pub fn to_filter(&self) -> Result<filter::Filter, filter::FilterError> {
+        filter::Filter::from_response(&self.0)
+    }
+
+    /// Describe what the unknowns would have to be for the request to be
+    /// allowed, as [`filter::Constraints`]: the request is allowed exactly when
+    /// all the constraints of one of its alternatives hold, e.g. `resource`
+    /// being `in Folder::"x"` and `resource.size` being at most 10.
+    ///
+    /// Fails in the same cases as [`PartialResponse::to_filter()`], or if there
+    /// are too many alternatives to list.
+    pub fn constraints(&self) -> Result<filter::Constraints, filter::FilterError> {
+        filter::Constraints::from_response(&self.0)
+    }
+
     /// Attempt to re-authorize this response given a mapping from unknowns to values
     pub fn reauthorize(
         &self,
@@ -915,6 +1348,9 @@ pub struct Diagnostics {
     /// Errors that occurred during authorization. The errors should be
     /// treated as unordered, since policies may be evaluated in any order.
     errors: Vec<AuthorizationError>,
+    /// Trace of the evaluation of each policy, if requested with
+    /// [`Authorizer::is_authorized_with_trace()`]
+    trace: Option<HashMap<PolicyId, EvaluationTrace>>,
 }
 
 #[doc(hidden)]
@@ -923,6 +1359,12 @@ impl From<authorizer::Diagnostics> for Diagnostics {
         Self {
             reason: diagnostics.reason.into_iter().map(PolicyId::new).collect(),
             errors: diagnostics.errors.into_iter().map(Into::into).collect(),
+            trace: diagnostics.trace.map(|trace| {
+                trace
+                    .into_iter()
+                    .map(|(id, trace)| (PolicyId::new(id), EvaluationTrace(trace)))
+                    .collect()
+            }),
         }
     }
 }
@@ -1047,14 +1489,104 @@ impl Diagnostics {
         self.errors.iter()
     }
 
-    /// Consume the `Diagnostics`, producing owned versions of `reason()` and `errors()`
+    /// Get the trace of how the policy with the given `PolicyId` was
+    /// evaluated.
+    ///
+    /// Traces are only recorded by [`Authorizer::is_authorized_with_trace()`];
+    /// this returns `None` for responses from other authorization methods.
+    pub fn trace(&self, policy: &PolicyId) -> Option<&EvaluationTrace> {
+        self.trace.as_ref().and_then(|trace| trace.get(policy))
+    }
+
+    /// Iterate over the traces of how each policy was evaluated. This is
+    /// empty unless the response came from
+    /// [`Authorizer::is_authorized_with_trace()`].
+    pub fn traces(&self) -> impl Iterator<Item = (&PolicyId, &EvaluationTrace)> {
+        self.trace.iter().flatten()
+    }
+
+    /// Consume the `Diagnostics`, producing owned versions of `reason()`,
+    /// `errors()` and the evaluation traces, if any
     pub(crate) fn into_components(
         self,
     ) -> (
         impl Iterator<Item = PolicyId>,
         impl Iterator<Item = AuthorizationError>,
+        Option<HashMap<PolicyId, EvaluationTrace>>,
     ) {
-        (self.reason.into_iter(), self.errors.into_iter())
+        (self.reason.into_iter(), self.errors.into_iter(), self.trace)
+    }
+}
+
+/// Trace of how a policy was evaluated, recorded by
+/// [`Authorizer::is_authorized_with_trace()`].
+///
+/// The `Display` implementation renders the trace with one line per step,
+/// indented by depth.
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, RefCast)]
+pub struct EvaluationTrace(evaluator::EvaluationTrace);
+
+impl EvaluationTrace {
+    /// Iterate over the sub-expressions of the policy that were evaluated, in
+    /// the order evaluation reached them. The first step is the policy's
+    /// whole condition, including its scope constraints.
+    pub fn steps(&self) -> impl Iterator<Item = &TraceStep> {
+        self.0.steps().map(TraceStep::ref_cast)
+    }
+}
+
+impl std::fmt::Display for EvaluationTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A sub-expression evaluated while authorizing with
+/// [`Authorizer::is_authorized_with_trace()`], along with its result
+#[repr(transparent)]
+#[derive(Debug, Clone, PartialEq, Eq, RefCast)]
+pub struct TraceStep(evaluator::TraceStep);
+
+impl TraceStep {
+    /// The sub-expression that was evaluated
+    pub fn expr(&self) -> &Expression {
+        Expression::ref_cast(self.0.expr())
+    }
+
+    /// How deeply nested this sub-expression is; the policy's whole condition
+    /// has depth 0
+    pub fn depth(&self) -> usize {
+        self.0.depth()
+    }
+
+    /// The value the sub-expression evaluated to, or `None` if evaluating it
+    /// produced an error (or a residual, in partial evaluation)
+    pub fn value(&self) -> Option<EvalResult> {
+        match self.0.outcome() {
+            evaluator::TraceOutcome::Value(v) => Some(v.clone().into()),
+            _ => None,
+        }
+    }
+
+    /// The error evaluating the sub-expression produced, if any
+    pub fn error(&self) -> Option<&EvaluationError> {
+        match self.0.outcome() {
+            evaluator::TraceOutcome::Error(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Whether the sub-expression is an `&&` or `||` whose right operand was
+    /// not evaluated, because the left operand already determined the result
+    pub fn short_circuited(&self) -> bool {
+        self.0.short_circuited()
+    }
+}
+
+impl std::fmt::Display for TraceStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
     }
 }
 
@@ -1067,7 +1599,11 @@ impl Response {
     ) -> Self {
         Self {
             decision,
-            diagnostics: Diagnostics { reason, errors },
+            diagnostics: Diagnostics {
+                reason,
+                errors,
+                trace: None,
+            },
         }
     }
 
@@ -1145,6 +1681,124 @@ impl Validator {
     pub fn validate(&self, pset: &PolicySet, mode: ValidationMode) -> ValidationResult {
         ValidationResult::from(self.0.validate(&pset.ast, mode.into()))
     }
+
+    /// Validate all policies in a policy set, as with [`Validator::validate`],
+    /// and if they pass strict validation, also analyze each static policy
+    /// symbolically. Policies that apply to no valid request are reported as
+    /// [`ValidationWarningKind::ImpossiblePolicy`] warnings, and those whose
+    /// `when` and `unless` clauses hold on every request their scope matches
+    /// as [`ValidationWarningKind::TautologicalCondition`] warnings.
+    ///
+    /// The analysis searches the requests of every action in the schema for
+    /// each policy, so it can be much slower than [`Validator::validate`].
+    #[cfg(feature = "partial-eval")]
+    pub fn validate_with_analysis(
+        &self,
+        pset: &PolicySet,
+        mode: ValidationMode,
+    ) -> ValidationResult {
+        ValidationResult::from(self.0.validate_with_analysis(&pset.ast, mode.into()))
+    }
+
+    /// Validate all policies in a policy set, as with [`Validator::validate`],
+    /// and also check that no policy dereferences entities more than
+    /// `max_deref_level` levels from the request. For instance,
+    /// `principal.manager` is a level-1 dereference and
+    /// `principal.manager.dept` a level-2 one, while reading the attributes
+    /// or ancestors of actions is not a dereference. Violations are reported
+    /// as [`ValidationErrorKind::EntityDerefLevelViolation`] errors.
+    pub fn validate_with_level(
+        &self,
+        pset: &PolicySet,
+        mode: ValidationMode,
+        max_deref_level: u32,
+    ) -> ValidationResult {
+        ValidationResult::from(
+            self.0
+                .validate_with_level(&pset.ast, mode.into(), max_deref_level),
+        )
+    }
+
+    /// Validate all policies in a policy set, as with [`Validator::validate`],
+    /// and also check the static policies and templates against the rules
+    /// enabled in `linter`. Violations are reported as
+    /// [`ValidationWarningKind::Lint`] warnings or
+    /// [`ValidationErrorKind::Lint`] errors, depending on the severity the
+    /// rule was enabled with.
+    pub fn validate_with_linter(
+        &self,
+        pset: &PolicySet,
+        mode: ValidationMode,
+        linter: &Linter,
+    ) -> ValidationResult {
+        ValidationResult::from(
+            self.0
+                .validate_with_linter(&pset.ast, mode.into(), &linter.0),
+        )
+    }
+}
+
+/// A check on individual policies, for use with a [`Linter`]
+pub trait LintRule: std::fmt::Debug + Send + Sync {
+    /// Identifier for the rule, included in the violations it reports
+    fn id(&self) -> &str;
+
+    /// Check a static policy or template, returning a message for each
+    /// problem found. Static policies are passed as templates without slots.
+    fn check(&self, template: &Template) -> Vec<String>;
+}
+
+/// Adapts a [`LintRule`] to the validator's rule interface
+#[derive(Debug)]
+struct LintRuleAdapter<R>(R);
+
+impl<R: LintRule> cedar_policy_validator::lint::LintRule for LintRuleAdapter<R> {
+    fn id(&self) -> &str {
+        self.0.id()
+    }
+
+    fn check(&self, policy: &ast::Template) -> Vec<(String, Option<parser::Loc>)> {
+        self.0
+            .check(&Template::from_ast(policy.clone()))
+            .into_iter()
+            .map(|message| (message, None))
+            .collect()
+    }
+}
+
+/// A set of lint rules for [`Validator::validate_with_linter`], each enabled
+/// with the severity its violations are reported with
+#[derive(Debug, Default)]
+pub struct Linter(cedar_policy_validator::lint::Linter);
+
+impl Linter {
+    /// A linter with no rules enabled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable a built-in rule given as `ID` or `ID=CONFIG`. The built-in
+    /// rules are:
+    ///
+    /// - `require-annotation[=KEY]`: every policy has the annotation `KEY`,
+    ///   by default `id`
+    /// - `unscoped-permit`: `permit` policies constrain their principal or
+    ///   resource in their scope
+    /// - `max-complexity[=N]`: policies have at most `N` expression nodes,
+    ///   by default 100
+    /// - `leading-wildcard-like`: `like` patterns don't start with `*`
+    pub fn add_builtin(
+        &mut self,
+        spec: &str,
+        severity: LintSeverity,
+    ) -> Result<(), LintConfigError> {
+        self.0.add_builtin(spec, severity)
+    }
+
+    /// Enable a custom rule
+    pub fn add_rule(&mut self, rule: impl LintRule + 'static, severity: LintSeverity) {
+        self.0.add_rule(LintRuleAdapter(rule), severity);
+    }
 }
 
 /// Contains all the type information used to construct a `Schema` that can be
@@ -1227,6 +1881,78 @@ impl SchemaFragment {
         let str = self.lossless.as_natural_schema()?;
         Ok(str)
     }
+
+    /// Iterate through the annotations on a namespace declared in this
+    /// `SchemaFragment` as key-value pairs, where `None` is the unqualified
+    /// namespace. Comments on a declaration in the natural syntax are
+    /// reported as a `comment` annotation.
+    ///
+    /// Returns `None` if the namespace isn't declared in this fragment.
+    pub fn namespace_annotations(
+        &self,
+        namespace: Option<&EntityNamespace>,
+    ) -> Option<impl Iterator<Item = (&str, &str)>> {
+        self.lossless
+            .namespace_annotations(namespace.map(|ns| &ns.0))
+            .map(cedar_policy_validator::Annotations::iter)
+    }
+
+    /// Iterate through the annotations on the common type `name` in the
+    /// namespace `namespace` as key-value pairs.
+    ///
+    /// Returns `None` if the common type isn't declared in this fragment.
+    pub fn common_type_annotations(
+        &self,
+        namespace: Option<&EntityNamespace>,
+        name: &str,
+    ) -> Option<impl Iterator<Item = (&str, &str)>> {
+        self.lossless
+            .common_type_annotations(namespace.map(|ns| &ns.0), name)
+            .map(cedar_policy_validator::Annotations::iter)
+    }
+
+    /// Iterate through the annotations on the entity type `entity_type` as
+    /// key-value pairs.
+    ///
+    /// Returns `None` if the entity type isn't declared in this fragment.
+    pub fn entity_type_annotations(
+        &self,
+        entity_type: &EntityTypeName,
+    ) -> Option<impl Iterator<Item = (&str, &str)>> {
+        self.lossless
+            .entity_type_annotations(entity_type.as_ref())
+            .map(cedar_policy_validator::Annotations::iter)
+    }
+
+    /// Iterate through the annotations on the attribute `attr` of the entity
+    /// type `entity_type` as key-value pairs.
+    ///
+    /// Returns `None` if the attribute isn't declared directly in the entity
+    /// type's shape in this fragment (for instance, if the shape is a common
+    /// type).
+    pub fn attribute_annotations(
+        &self,
+        entity_type: &EntityTypeName,
+        attr: &str,
+    ) -> Option<impl Iterator<Item = (&str, &str)>> {
+        self.lossless
+            .attribute_annotations(entity_type.as_ref(), attr)
+            .map(cedar_policy_validator::Annotations::iter)
+    }
+
+    /// Iterate through the annotations on the action `action` in the
+    /// namespace `namespace` as key-value pairs.
+    ///
+    /// Returns `None` if the action isn't declared in this fragment.
+    pub fn action_annotations(
+        &self,
+        namespace: Option<&EntityNamespace>,
+        action: &str,
+    ) -> Option<impl Iterator<Item = (&str, &str)>> {
+        self.lossless
+            .action_annotations(namespace.map(|ns| &ns.0), action)
+            .map(cedar_policy_validator::Annotations::iter)
+    }
 }
 
 impl TryInto<Schema> for SchemaFragment {
@@ -1351,6 +2077,106 @@ impl Schema {
     pub fn action_entities(&self) -> Result<Entities, EntitiesError> {
         Ok(Entities(self.0.action_entities()?))
     }
+
+    /// Export a [JSON Schema](https://json-schema.org/draft/2020-12) document
+    /// describing the JSON format for entities which conform to this schema,
+    /// as read by [`Entities::from_json_value`]. This lets producers of
+    /// entity data check it with standard JSON Schema tooling.
+    ///
+    /// Entity references may be written with or without the `__entity`
+    /// escape, and extension values as the string argument of their
+    /// constructor or with the `__extn` escape. Action entities are not
+    /// described, since they are taken from the schema.
+    pub fn entities_json_schema(&self) -> serde_json::Value {
+        cedar_policy_validator::json_schema_export::entities_json_schema(&self.0)
+    }
+
+    /// Export a [JSON Schema](https://json-schema.org/draft/2020-12) document
+    /// describing the JSON format for the context of a request for `action`,
+    /// as read by [`Context::from_json_value`], or `None` if `action` is not
+    /// declared in this schema. Values are written as for
+    /// [`Schema::entities_json_schema`].
+    pub fn context_json_schema(&self, action: &EntityUid) -> Option<serde_json::Value> {
+        cedar_policy_validator::json_schema_export::context_json_schema(&self.0, action.as_ref())
+    }
+}
+
+/// The differences between an old and a new version of a schema.
+///
+/// A schema change is backward compatible if every request and entity that
+/// conforms to the old schema still conforms to the new one. Adding entity
+/// types, actions, or optional attributes is compatible; removing them,
+/// narrowing an attribute's type, or making an optional attribute required
+/// is breaking.
+#[derive(Debug)]
+pub struct SchemaDiff<'a> {
+    old: &'a Schema,
+    new: &'a Schema,
+    diff: cedar_policy_validator::schema_diff::SchemaDiff,
+}
+
+impl<'a> SchemaDiff<'a> {
+    /// Compare `old` to `new`
+    pub fn new(old: &'a Schema, new: &'a Schema) -> Self {
+        Self {
+            old,
+            new,
+            diff: cedar_policy_validator::schema_diff::SchemaDiff::new(&old.0, &new.0),
+        }
+    }
+
+    /// All the changes from the old schema to the new one
+    pub fn changes(&self) -> impl Iterator<Item = &SchemaChange> {
+        self.diff.changes().map(SchemaChange::ref_cast)
+    }
+
+    /// The changes that are not backward compatible
+    pub fn breaking_changes(&self) -> impl Iterator<Item = &SchemaChange> {
+        self.diff.breaking_changes().map(SchemaChange::ref_cast)
+    }
+
+    /// Return `true` if none of the changes is breaking
+    pub fn is_backward_compatible(&self) -> bool {
+        self.diff.is_backward_compatible()
+    }
+
+    /// Validate `pset` against both schemas, returning the errors for the
+    /// policies that pass validation against the old schema but not against
+    /// the new one. Policies that already failed to validate are not
+    /// reported.
+    pub fn broken_policies(
+        &self,
+        pset: &PolicySet,
+        mode: ValidationMode,
+    ) -> impl Iterator<Item = ValidationError> {
+        cedar_policy_validator::schema_diff::broken_policies(
+            &self.old.0,
+            &self.new.0,
+            &pset.ast,
+            mode.into(),
+        )
+        .into_iter()
+        .map(ValidationError::from)
+    }
+}
+
+/// A single change found by [`SchemaDiff`]
+#[derive(Debug, Clone, PartialEq, Eq, RefCast)]
+#[repr(transparent)]
+pub struct SchemaChange(cedar_policy_validator::schema_diff::SchemaChange);
+
+impl SchemaChange {
+    /// Return `true` if a request or entity that conforms to the old schema
+    /// may not conform to the new one
+    pub fn is_breaking(&self) -> bool {
+        self.0.is_breaking()
+    }
+}
+
+impl std::fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
 }
 
 /// Contains the result of policy validation. The result includes the list of
@@ -1932,6 +2758,56 @@ impl PolicySet {
             }
         }
     }
+
+    /// Get the subset of this `PolicySet` that could apply to a request whose
+    /// principal has type `principal_type`, whose action is `action`, and
+    /// whose resource has type `resource_type`.
+    ///
+    /// A policy is kept if its scope and the `appliesTo` declaration for
+    /// `action` in `schema` allow it to match requests of this shape; its
+    /// `when` and `unless` conditions are not examined. Every request of this
+    /// shape that conforms to `schema` gets the same authorization response
+    /// from the slice as from the full `PolicySet`. Template-linked policies
+    /// are kept together with their templates, but a template whose links
+    /// are all dropped is not included.
+    #[must_use]
+    pub fn slice(
+        &self,
+        principal_type: &EntityTypeName,
+        action: &EntityUid,
+        resource_type: &EntityTypeName,
+        schema: &Schema,
+    ) -> Self {
+        let mut sliced = Self::new();
+        for (id, policy) in &self.policies {
+            if !schema.0.policy_may_apply(
+                &policy.ast,
+                principal_type.as_ref(),
+                action.as_ref(),
+                resource_type.as_ref(),
+            ) {
+                continue;
+            }
+            // PANIC SAFETY: every policy in `self` has a distinct id and was
+            // already added to `self.ast` alongside its template, so adding it
+            // to a subset of `self.ast` cannot conflict.
+            #[allow(clippy::expect_used)]
+            sliced
+                .ast
+                .add(policy.ast.clone())
+                .expect("policies from a valid policy set should not conflict");
+            if let Some(template_id) = policy.template_id() {
+                if let Some(template) = self.templates.get(template_id) {
+                    sliced
+                        .templates
+                        .entry(template_id.clone())
+                        .or_insert_with(|| template.clone());
+                }
+            }
+            sliced.policies.insert(id.clone(), policy.clone());
+        }
+        sliced
+    }
 }
 
 impl std::fmt::Display for PolicySet {
@@ -1941,6 +2817,188 @@ impl std::fmt::Display for PolicySet {
     }
 }
 
+/// The entity data needed to authorize requests against a [`PolicySet`], for
+/// each type of request a [`Schema`] allows.
+///
+/// For each combination of principal type, action, and resource type, the
+/// manifest lists the entities evaluation could reach starting from the
+/// request and from entity literals in the policies, and which attributes,
+/// tags, and ancestors are needed from each. Its JSON form can be used to
+/// decide what to load from an entity store before calling
+/// [`Authorizer::is_authorized`], and [`EntityManifest::slice_entities`]
+/// applies it to an [`Entities`] already in memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityManifest(cedar_policy_validator::entity_manifest::EntityManifest);
+
+impl EntityManifest {
+    /// Compute the manifest for `policies` under `schema`
+    pub fn new(policies: &PolicySet, schema: &Schema) -> Self {
+        Self(
+            cedar_policy_validator::entity_manifest::compute_entity_manifest(
+                &schema.0,
+                &policies.ast,
+            ),
+        )
+    }
+
+    /// Serialize this manifest as a JSON value
+    pub fn to_json_value(&self) -> Result<serde_json::Value, EntityManifestError> {
+        Ok(serde_json::to_value(&self.0)?)
+    }
+
+    /// Deserialize a manifest from the JSON produced by
+    /// [`EntityManifest::to_json_value`]
+    pub fn from_json_value(json: serde_json::Value) -> Result<Self, EntityManifestError> {
+        Ok(Self(serde_json::from_value(json)?))
+    }
+
+    /// Restrict `entities` to the data needed to authorize `request`.
+    ///
+    /// Authorizing `request` against the result gives the same response as
+    /// authorizing it against `entities`, provided `request` conforms to the
+    /// schema the manifest was computed with.
+    pub fn slice_entities(
+        &self,
+        entities: &Entities,
+        request: &Request,
+    ) -> Result<Entities, EntityManifestError> {
+        Ok(Entities(self.0.slice_entities(&entities.0, &request.0)?))
+    }
+}
+
+/// Answers questions about policies, for every request a [`Schema`] allows.
+///
+/// The analyzer decides whether two [`PolicySet`]s make the same decisions,
+/// whether one policy applies whenever another does, and whether a `forbid`
+/// policy is redundant or shadows every `permit`.
+///
+/// Policies must pass strict validation against the schema, and may not use
+/// arithmetic or extension functions. When the analysis finds that a
+/// property fails, it returns a [`Counterexample`] that has been checked by
+/// authorizing it. It may also give up, returning
+/// [`AnalysisOutcome::Unknown`].
+#[doc = include_str!("../experimental_warning.md")]
+#[cfg(feature = "partial-eval")]
+#[derive(Debug)]
+pub struct PolicyAnalyzer(cedar_policy_validator::analysis::PolicyAnalyzer);
+
+#[cfg(feature = "partial-eval")]
+impl PolicyAnalyzer {
+    /// Create an analyzer for requests allowed by `schema`
+    pub fn new(schema: Schema) -> Self {
+        Self(cedar_policy_validator::analysis::PolicyAnalyzer::new(
+            schema.0,
+        ))
+    }
+
+    /// Do `first` and `second` make the same decision on every request?
+    ///
+    /// A counterexample is a request that one allows and the other denies.
+    pub fn equivalent(
+        &self,
+        first: &PolicySet,
+        second: &PolicySet,
+    ) -> Result<AnalysisOutcome, AnalysisError> {
+        Ok(self.0.equivalent(&first.ast, &second.ast)?.into())
+    }
+
+    /// Does `general` apply to every request `specific` applies to?
+    ///
+    /// A counterexample is a request `specific` applies to and `general`
+    /// doesn't.
+    pub fn subsumes(
+        &self,
+        general: &Policy,
+        specific: &Policy,
+    ) -> Result<AnalysisOutcome, AnalysisError> {
+        Ok(self.0.subsumes(&general.ast, &specific.ast)?.into())
+    }
+
+    /// Does removing the `forbid` policy `forbid` from `policies` leave
+    /// every decision unchanged?
+    ///
+    /// A counterexample is a request that `policies` denies only because of
+    /// `forbid`.
+    pub fn forbid_is_redundant(
+        &self,
+        policies: &PolicySet,
+        forbid: &PolicyId,
+    ) -> Result<AnalysisOutcome, AnalysisError> {
+        Ok(self
+            .0
+            .forbid_is_redundant(&policies.ast, forbid.as_ref())?
+            .into())
+    }
+
+    /// Does the `forbid` policy `forbid` apply to every request any `permit`
+    /// policy in `policies` applies to? If so, `policies` allows nothing.
+    ///
+    /// A counterexample is a request some `permit` applies to and `forbid`
+    /// doesn't.
+    pub fn forbid_shadows_permits(
+        &self,
+        policies: &PolicySet,
+        forbid: &PolicyId,
+    ) -> Result<AnalysisOutcome, AnalysisError> {
+        Ok(self
+            .0
+            .forbid_shadows_permits(&policies.ast, forbid.as_ref())?
+            .into())
+    }
+}
+
+/// The answer a [`PolicyAnalyzer`] gives to a question about policies
+#[doc = include_str!("../experimental_warning.md")]
+#[cfg(feature = "partial-eval")]
+#[derive(Debug)]
+pub enum AnalysisOutcome {
+    /// The property holds for every request
+    Holds,
+    /// The property fails for the request in the counterexample
+    Fails(Box<Counterexample>),
+    /// The analysis could not decide whether the property holds
+    Unknown,
+}
+
+#[cfg(feature = "partial-eval")]
+#[doc(hidden)]
+impl From<cedar_policy_validator::analysis::AnalysisOutcome> for AnalysisOutcome {
+    fn from(outcome: cedar_policy_validator::analysis::AnalysisOutcome) -> Self {
+        match outcome {
+            cedar_policy_validator::analysis::AnalysisOutcome::Holds => Self::Holds,
+            cedar_policy_validator::analysis::AnalysisOutcome::Fails(c) => {
+                Self::Fails(Box::new(Counterexample {
+                    request: Request(c.request),
+                    entities: Entities(c.entities),
+                }))
+            }
+            cedar_policy_validator::analysis::AnalysisOutcome::Unknown => Self::Unknown,
+        }
+    }
+}
+
+/// A request, with entity data, on which a property fails
+#[doc = include_str!("../experimental_warning.md")]
+#[cfg(feature = "partial-eval")]
+#[derive(Debug)]
+pub struct Counterexample {
+    request: Request,
+    entities: Entities,
+}
+
+#[cfg(feature = "partial-eval")]
+impl Counterexample {
+    /// The request
+    pub fn request(&self) -> &Request {
+        &self.request
+    }
+
+    /// The entity data, including the schema's action entities
+    pub fn entities(&self) -> &Entities {
+        &self.entities
+    }
+}
+
 /// Given a [`PolicyId`] and a [`Policy`], determine if the policy represents a static policy or a
 /// link
 fn is_static_or_link(
@@ -2030,6 +3088,17 @@ impl Template {
         })
     }
 
+    /// Create a `Template` from its AST representation only. As with
+    /// [`Policy::from_ast`], the lossless representation is the pretty-printed
+    /// AST.
+    pub(crate) fn from_ast(ast: ast::Template) -> Self {
+        let text = ast.to_string();
+        Self {
+            ast,
+            lossless: LosslessPolicy::policy_or_template_text(text),
+        }
+    }
+
     /// Get the `PolicyId` of this `Template`
     pub fn id(&self) -> &PolicyId {
         PolicyId::ref_cast(self.ast.id())
@@ -2594,6 +3663,7 @@ impl Policy {
                 |ast::Unknown {
                      name,
                      type_annotation,
+                     ..
                  }| {
                     if matches!(type_annotation, Some(ast::Type::Entity { .. })) {
                         EntityUid::from_str(name.as_str()).ok()
@@ -2790,6 +3860,28 @@ impl Expression {
         ))
     }
 
+    /// Create an expression representing a point in time.
+    /// This function does not perform error checking on the source string,
+    /// it creates an expression that calls the `datetime` constructor.
+    pub fn new_datetime(src: impl AsRef<str>) -> Self {
+        let src_expr = ast::Expr::val(src.as_ref());
+        Self(ast::Expr::call_extension_fn(
+            datetime_extension_name(),
+            vec![src_expr],
+        ))
+    }
+
+    /// Create an expression representing a span of time.
+    /// This function does not perform error checking on the source string,
+    /// it creates an expression that calls the `duration` constructor.
+    pub fn new_duration(src: impl AsRef<str>) -> Self {
+        let src_expr = ast::Expr::val(src.as_ref());
+        Self(ast::Expr::call_extension_fn(
+            duration_extension_name(),
+            vec![src_expr],
+        ))
+    }
+
     /// Deconstruct an [`Expression`] to get the internal type.
     /// This function is only intended to be used internally.
     #[cfg(test)]
@@ -2807,6 +3899,12 @@ impl FromStr for Expression {
     }
 }
 
+impl std::fmt::Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 /// "Restricted" expressions are used for attribute values and `context`.
 ///
 /// Restricted expressions can contain only the following:
@@ -2885,6 +3983,54 @@ impl RestrictedExpression {
         ))
     }
 
+    /// Create an expression representing a point in time.
+    /// This function does not perform error checking on the source string,
+    /// it creates an expression that calls the `datetime` constructor.
+    pub fn new_datetime(src: impl AsRef<str>) -> Self {
+        let src_expr = ast::RestrictedExpr::val(src.as_ref());
+        Self(ast::RestrictedExpr::call_extension_fn(
+            datetime_extension_name(),
+            [src_expr],
+        ))
+    }
+
+    /// Create an expression representing a span of time.
+    /// This function does not perform error checking on the source string,
+    /// it creates an expression that calls the `duration` constructor.
+    pub fn new_duration(src: impl AsRef<str>) -> Self {
+        let src_expr = ast::RestrictedExpr::val(src.as_ref());
+        Self(ast::RestrictedExpr::call_extension_fn(
+            duration_extension_name(),
+            [src_expr],
+        ))
+    }
+
+    /// Create an unknown for partial evaluation, which can be filled in with
+    /// a value of any type.
+    #[doc = include_str!("../experimental_warning.md")]
+    #[cfg(feature = "partial-eval")]
+    pub fn new_unknown(name: impl AsRef<str>) -> Self {
+        Self(ast::RestrictedExpr::unknown(ast::Unknown::new_untyped(
+            name.as_ref(),
+        )))
+    }
+
+    /// Create an unknown for partial evaluation, which can only be filled in
+    /// with a value of type `ty`.
+    ///
+    /// Evaluation uses the type to report type errors involving the unknown
+    /// without waiting for its value, and to decide `==` and `is` where the
+    /// type alone determines the result. Validation checks it against the
+    /// schema.
+    #[doc = include_str!("../experimental_warning.md")]
+    #[cfg(feature = "partial-eval")]
+    pub fn new_typed_unknown(name: impl AsRef<str>, ty: UnknownType) -> Self {
+        Self(ast::RestrictedExpr::unknown(ast::Unknown::new_with_type(
+            name.as_ref(),
+            ty.into(),
+        )))
+    }
+
     /// Deconstruct an [`RestrictedExpression`] to get the internal type.
     /// This function is only intended to be used internally.
     #[cfg(test)]
@@ -2893,6 +4039,63 @@ impl RestrictedExpression {
     }
 }
 
+/// The type of the values an unknown created with
+/// [`RestrictedExpression::new_typed_unknown()`] can be filled in with
+#[doc = include_str!("../experimental_warning.md")]
+#[cfg(feature = "partial-eval")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UnknownType {
+    /// Boolean
+    Bool,
+    /// Long
+    Long,
+    /// String
+    String,
+    /// Set, with elements of any type
+    Set,
+    /// Record, with any attributes
+    Record,
+    /// Entity of the given type
+    Entity(EntityTypeName),
+    /// `ipaddr` extension type
+    IpAddr,
+    /// `decimal` extension type
+    Decimal,
+    /// `datetime` extension type
+    Datetime,
+    /// `duration` extension type
+    Duration,
+}
+
+#[cfg(feature = "partial-eval")]
+impl From<UnknownType> for ast::Type {
+    fn from(ty: UnknownType) -> Self {
+        match ty {
+            UnknownType::Bool => Self::Bool,
+            UnknownType::Long => Self::Long,
+            UnknownType::String => Self::String,
+            UnknownType::Set => Self::Set,
+            UnknownType::Record => Self::Record,
+            UnknownType::Entity(ty) => Self::Entity {
+                ty: ast::EntityType::Specified(ty.as_ref().clone()),
+            },
+            UnknownType::IpAddr => Self::Extension {
+                name: ipaddr_type_name(),
+            },
+            UnknownType::Decimal => Self::Extension {
+                name: decimal_extension_name(),
+            },
+            UnknownType::Datetime => Self::Extension {
+                name: datetime_extension_name(),
+            },
+            UnknownType::Duration => Self::Extension {
+                name: duration_extension_name(),
+            },
+        }
+    }
+}
+
 fn decimal_extension_name() -> ast::Name {
     // PANIC SAFETY: This is a constant and is known to be safe, verified by a test
     #[allow(clippy::unwrap_used)]
@@ -2905,6 +4108,25 @@ fn ip_extension_name() -> ast::Name {
     ast::Name::unqualified_name("ip".parse().unwrap())
 }
 
+#[cfg(feature = "partial-eval")]
+fn ipaddr_type_name() -> ast::Name {
+    // PANIC SAFETY: This is a constant and is known to be safe, verified by a test
+    #[allow(clippy::unwrap_used)]
+    ast::Name::unqualified_name("ipaddr".parse().unwrap())
+}
+
+fn datetime_extension_name() -> ast::Name {
+    // PANIC SAFETY: This is a constant and is known to be safe, verified by a test
+    #[allow(clippy::unwrap_used)]
+    ast::Name::unqualified_name("datetime".parse().unwrap())
+}
+
+fn duration_extension_name() -> ast::Name {
+    // PANIC SAFETY: This is a constant and is known to be safe, verified by a test
+    #[allow(clippy::unwrap_used)]
+    ast::Name::unqualified_name("duration".parse().unwrap())
+}
+
 impl FromStr for RestrictedExpression {
     type Err = RestrictedExprParseError;
 
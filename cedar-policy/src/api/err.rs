@@ -23,10 +23,11 @@ use cedar_policy_core::ast;
 use cedar_policy_core::ast::Name;
 use cedar_policy_core::authorizer;
 use cedar_policy_core::est;
-pub use cedar_policy_core::evaluator::{EvaluationError, EvaluationErrorKind};
+pub use cedar_policy_core::evaluator::{EvaluationError, EvaluationErrorKind, LimitExceededError};
 use cedar_policy_core::parser;
 pub use cedar_policy_core::parser::err::ParseErrors;
 pub use cedar_policy_validator::human_schema::SchemaWarning;
+pub use cedar_policy_validator::lint::{LintConfigError, LintViolation};
 pub use cedar_policy_validator::{
     TypeErrorKind, UnsupportedFeature, ValidationErrorKind, ValidationWarningKind,
 };
@@ -184,6 +185,14 @@ pub enum SchemaError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     ActionAttrEval(EntityAttrEvaluationError),
+    /// An entity type declared as an enumeration of entity IDs has no IDs.
+    #[error("enumerated entity type `{0}` does not declare any entity IDs")]
+    EmptyEnumEntityType(EntityTypeName),
+    /// An entity type declared as an enumeration of entity IDs also declares
+    /// parent types, attributes, or tags, which enumerated entity types may
+    /// not have.
+    #[error("enumerated entity type `{0}` declares parent types, attributes, or tags")]
+    InvalidEnumEntityType(EntityTypeName),
     /// Error thrown when the schema contains the `__expr` escape.
     /// Support for this escape form has been dropped.
     #[error("schema contained the non-supported `__expr` escape")]
@@ -273,14 +282,16 @@ impl From<cedar_policy_validator::SchemaError> for HumanSchemaError {
     }
 }
 
-/// Error when evaluating an entity attribute
+/// Error when evaluating an entity attribute or tag
 #[derive(Debug, Diagnostic, Error)]
-#[error("in attribute `{attr}` of `{uid}`: {err}")]
+#[error("in {} `{attr}` of `{uid}`: {err}", if *.was_attr { "attribute" } else { "tag" })]
 pub struct EntityAttrEvaluationError {
     /// Action that had the attribute with the error
     uid: EntityUid,
-    /// Attribute that had the error
+    /// Attribute or tag that had the error
     attr: SmolStr,
+    /// Is `attr` an attribute (as opposed to a tag)?
+    was_attr: bool,
     /// Underlying evaluation error
     #[diagnostic(transparent)]
     err: EvaluationError,
@@ -292,11 +303,17 @@ impl EntityAttrEvaluationError {
         &self.uid
     }
 
-    /// Get the name of the attribute that had the error
+    /// Get the name of the attribute or tag that had the error
     pub fn attr(&self) -> &SmolStr {
         &self.attr
     }
 
+    /// Returns `true` if the error was in an attribute, and `false` if it was
+    /// in a tag
+    pub fn was_attr(&self) -> bool {
+        self.was_attr
+    }
+
     /// Get the underlying evaluation error
     pub fn inner(&self) -> &EvaluationError {
         &self.err
@@ -308,7 +325,8 @@ impl From<ast::EntityAttrEvaluationError> for EntityAttrEvaluationError {
     fn from(err: ast::EntityAttrEvaluationError) -> Self {
         Self {
             uid: EntityUid::new(err.uid),
-            attr: err.attr,
+            attr: err.attr_or_tag,
+            was_attr: err.was_attr,
             err: err.err,
         }
     }
@@ -402,6 +420,12 @@ impl From<cedar_policy_validator::SchemaError> for SchemaError {
             cedar_policy_validator::SchemaError::ActionAttrEval(err) => {
                 Self::ActionAttrEval(err.into())
             }
+            cedar_policy_validator::SchemaError::EmptyEnumEntityType(name) => {
+                Self::EmptyEnumEntityType(EntityTypeName::new(name))
+            }
+            cedar_policy_validator::SchemaError::InvalidEnumEntityType(name) => {
+                Self::InvalidEnumEntityType(EntityTypeName::new(name))
+            }
             cedar_policy_validator::SchemaError::ExprEscapeUsed => Self::ExprEscapeUsed,
         }
     }
@@ -696,3 +720,80 @@ pub mod context_json_errors {
         }
     }
 }
+
+/// Errors converting or applying an [`crate::EntityManifest`]
+#[derive(Debug, Diagnostic, Error)]
+pub enum EntityManifestError {
+    /// Entities can only be sliced for a request whose principal, action,
+    /// and resource are all known
+    #[error("cannot slice entities for a request with an unknown {0}")]
+    PartialRequest(SmolStr),
+    /// Error constructing the sliced entities
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Entities(#[from] cedar_policy_core::entities::err::EntitiesError),
+    /// Error serializing or deserializing the manifest as JSON
+    #[error("error serializing or deserializing entity manifest: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+#[doc(hidden)]
+impl From<cedar_policy_validator::entity_manifest::EntityManifestError> for EntityManifestError {
+    fn from(e: cedar_policy_validator::entity_manifest::EntityManifestError) -> Self {
+        match e {
+            cedar_policy_validator::entity_manifest::EntityManifestError::PartialRequest(var) => {
+                Self::PartialRequest(var.to_string().into())
+            }
+            cedar_policy_validator::entity_manifest::EntityManifestError::Entities(e) => {
+                Self::Entities(e)
+            }
+        }
+    }
+}
+
+/// Errors analyzing policies with a [`crate::PolicyAnalyzer`]
+#[cfg(feature = "partial-eval")]
+#[derive(Debug, Diagnostic, Error)]
+pub enum AnalysisError {
+    /// The policies must pass strict validation against the schema
+    #[error("policies must pass strict validation to be analyzed")]
+    Validation(crate::ValidationResult),
+    /// A policy uses a construct the analysis doesn't support
+    #[error("policy `{policy}` cannot be analyzed: {source}")]
+    Unsupported {
+        /// The policy
+        policy: PolicyId,
+        /// The construct that isn't supported
+        source: cedar_policy_core::filter::FilterError,
+    },
+    /// The policy is not in the policy set
+    #[error("policy `{0}` is not in the policy set")]
+    MissingPolicy(PolicyId),
+    /// The policy is not a `forbid` policy
+    #[error("policy `{0}` is not a forbid policy")]
+    NotAForbid(PolicyId),
+}
+
+#[cfg(feature = "partial-eval")]
+#[doc(hidden)]
+impl From<cedar_policy_validator::analysis::AnalysisError> for AnalysisError {
+    fn from(e: cedar_policy_validator::analysis::AnalysisError) -> Self {
+        match e {
+            cedar_policy_validator::analysis::AnalysisError::Validation(result) => {
+                Self::Validation(result.into())
+            }
+            cedar_policy_validator::analysis::AnalysisError::Unsupported { policy, source } => {
+                Self::Unsupported {
+                    policy: PolicyId::ref_cast(&policy).clone(),
+                    source,
+                }
+            }
+            cedar_policy_validator::analysis::AnalysisError::MissingPolicy(policy) => {
+                Self::MissingPolicy(PolicyId::ref_cast(&policy).clone())
+            }
+            cedar_policy_validator::analysis::AnalysisError::NotAForbid(policy) => {
+                Self::NotAForbid(PolicyId::ref_cast(&policy).clone())
+            }
+        }
+    }
+}
@@ -141,6 +141,13 @@ impl EntityTypeName {
 
 /// This `FromStr` implementation requires the _normalized_ representation of the
 /// type name. See <https://github.com/cedar-policy/rfcs/pull/9/>.
+#[doc(hidden)]
+impl AsRef<ast::Name> for EntityTypeName {
+    fn as_ref(&self) -> &ast::Name {
+        &self.0
+    }
+}
+
 impl FromStr for EntityTypeName {
     type Err = ParseErrors;
 
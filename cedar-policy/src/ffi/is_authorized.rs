@@ -44,15 +44,19 @@ thread_local!(
 
 /// Basic interface, using [`AuthorizationCall`] and [`AuthorizationAnswer`] types
 pub fn is_authorized(call: AuthorizationCall) -> AuthorizationAnswer {
+    let trace = call.trace;
     match call.get_components() {
         WithWarnings {
             t: Ok((request, policies, entities)),
             warnings,
         } => AuthorizationAnswer::Success {
             response: AUTHORIZER.with(|authorizer| {
-                authorizer
-                    .is_authorized(&request, &policies, &entities)
-                    .into()
+                if trace {
+                    authorizer.is_authorized_with_trace(&request, &policies, &entities)
+                } else {
+                    authorizer.is_authorized(&request, &policies, &entities)
+                }
+                .into()
             }),
             warnings: warnings.into_iter().map(Into::into).collect(),
         },
@@ -160,6 +164,52 @@ pub struct Diagnostics {
     reason: HashSet<PolicyId>,
     /// Set of errors that occurred
     errors: HashSet<AuthorizationError>,
+    /// Trace of how each policy was evaluated, present only if the
+    /// `AuthorizationCall` set `trace`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(
+        feature = "wasm",
+        tsify(optional, type = "Record<string, TraceStep[]>")
+    )]
+    trace: Option<HashMap<PolicyId, Vec<TraceStep>>>,
+}
+
+/// Interface version of a [`crate::TraceStep`], with the sub-expression and
+/// its result rendered as Cedar syntax
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+#[cfg_attr(feature = "wasm", tsify(into_wasm_abi, from_wasm_abi))]
+#[serde(rename_all = "camelCase")]
+pub struct TraceStep {
+    /// The sub-expression that was evaluated
+    pub expr: String,
+    /// How deeply nested the sub-expression is; the policy's whole condition
+    /// has depth 0
+    pub depth: usize,
+    /// The value the sub-expression evaluated to. Absent if evaluating it
+    /// produced an error.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub value: Option<String>,
+    /// The error evaluating the sub-expression produced, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    pub error: Option<String>,
+    /// Whether the sub-expression is an `&&` or `||` whose right operand was
+    /// not evaluated
+    pub short_circuited: bool,
+}
+
+impl From<&crate::TraceStep> for TraceStep {
+    fn from(step: &crate::TraceStep) -> Self {
+        Self {
+            expr: step.expr().to_string(),
+            depth: step.depth(),
+            value: step.value().map(|v| v.to_string()),
+            error: step.error().map(ToString::to_string),
+            short_circuited: step.short_circuited(),
+        }
+    }
 }
 
 impl Response {
@@ -171,7 +221,11 @@ impl Response {
     ) -> Self {
         Self {
             decision,
-            diagnostics: Diagnostics { reason, errors },
+            diagnostics: Diagnostics {
+                reason,
+                errors,
+                trace: None,
+            },
         }
     }
 
@@ -188,12 +242,19 @@ impl Response {
 
 impl From<crate::Response> for Response {
     fn from(response: crate::Response) -> Self {
-        let (reason, errors) = response.diagnostics.into_components();
-        Self::new(
+        let (reason, errors, trace) = response.diagnostics.into_components();
+        let mut ffi_response = Self::new(
             response.decision,
             reason.collect(),
             errors.map(Into::into).collect(),
-        )
+        );
+        ffi_response.diagnostics.trace = trace.map(|trace| {
+            trace
+                .into_iter()
+                .map(|(id, trace)| (id, trace.steps().map(Into::into).collect()))
+                .collect()
+        });
+        ffi_response
     }
 }
 
@@ -216,6 +277,15 @@ impl Diagnostics {
     pub fn errors(&self) -> impl Iterator<Item = &AuthorizationError> + '_ {
         self.errors.iter()
     }
+
+    /// Get the trace of how the given policy was evaluated, if tracing was
+    /// requested
+    pub fn trace(&self, policy: &PolicyId) -> Option<&[TraceStep]> {
+        self.trace
+            .as_ref()
+            .and_then(|trace| trace.get(policy))
+            .map(Vec::as_slice)
+    }
 }
 
 /// Error (or warning) which occurred in a particular policy during authorization
@@ -468,6 +538,11 @@ pub struct AuthorizationCall {
     /// If a schema is not provided, this option has no effect.
     #[serde(default = "constant_true")]
     enable_request_validation: bool,
+    /// If this is `true`, the response diagnostics include a trace of how
+    /// each policy was evaluated. Only used by [`is_authorized()`].
+    #[serde(default)]
+    #[cfg_attr(feature = "wasm", tsify(optional))]
+    trace: bool,
     /// The slice containing entities and policies
     slice: RecvdSlice,
 }
@@ -975,6 +1050,46 @@ mod test {
         assert_is_not_authorized_json(call);
     }
 
+    #[test]
+    fn test_trace() {
+        let call = |trace: bool| {
+            json!({
+                "principal": { "type": "User", "id": "alice" },
+                "action": { "type": "Photo", "id": "view" },
+                "resource": { "type": "Photo", "id": "door" },
+                "context": {},
+                "trace": trace,
+                "slice": {
+                    "policies": {
+                        "ID1": "permit(principal, action, resource) when { principal == User::\"bob\" && context.admin };"
+                    },
+                    "entities": []
+                }
+            })
+        };
+
+        let ans_val = is_authorized_json(call(false)).unwrap();
+        assert!(ans_val["response"]["diagnostics"].get("trace").is_none());
+
+        let ans_val = is_authorized_json(call(true)).unwrap();
+        let result: Result<AuthorizationAnswer, _> = serde_json::from_value(ans_val);
+        assert_matches!(result, Ok(AuthorizationAnswer::Success { response, .. }) => {
+            assert_eq!(response.decision(), Decision::Deny);
+            let trace = response
+                .diagnostics()
+                .trace(&PolicyId::from_str("ID1").unwrap())
+                .expect("expected a trace for ID1");
+            assert_matches!(trace.first(), Some(step) => {
+                assert_eq!(step.depth, 0);
+                assert_eq!(step.value.as_deref(), Some("false"));
+            });
+            let short_circuited = trace.iter().filter(|s| s.short_circuited).collect::<Vec<_>>();
+            assert_matches!(short_circuited.as_slice(), [step] => {
+                assert!(step.expr.contains(r#"User::"bob""#), "{}", step.expr);
+            });
+        });
+    }
+
     #[test]
     fn test_not_authorized_on_unspecified() {
         let call = json!({
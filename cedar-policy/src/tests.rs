@@ -1483,6 +1483,8 @@ mod schema_tests {
 
 mod ancestors_tests {
     use super::*;
+    use cool_asserts::assert_matches;
+    use entities::err::EntitiesError;
 
     #[test]
     fn test_ancestors() {
@@ -1498,6 +1500,64 @@ mod ancestors_tests {
         assert!(ans.contains(&b_euid));
         assert!(ans.contains(&a_euid));
     }
+
+    #[test]
+    fn incremental_updates() {
+        let (schema, _) = Schema::from_str_natural(
+            r#"
+            entity Group in [Group];
+            entity User in [Group];
+            "#,
+        )
+        .unwrap();
+        let alice = EntityUid::from_strs("User", "alice");
+        let eng = EntityUid::from_strs("Group", "eng");
+        let staff = EntityUid::from_strs("Group", "staff");
+        let all = EntityUid::from_strs("Group", "all");
+        let mut es = Entities::from_entities(
+            [
+                Entity::new_no_attrs(alice.clone(), HashSet::from([eng.clone()])),
+                Entity::new_no_attrs(eng.clone(), HashSet::from([staff.clone()])),
+                Entity::new_no_attrs(staff.clone(), HashSet::new()),
+            ],
+            Some(&schema),
+        )
+        .unwrap();
+
+        es.upsert_entities(
+            [Entity::new_no_attrs(all.clone(), HashSet::new())],
+            Some(&schema),
+        )
+        .unwrap();
+        es.set_parents(&staff, [all.clone()], Some(&schema))
+            .unwrap();
+        assert!(es.is_ancestor_of(&all, &alice));
+        assert!(es.is_ancestor_of(&all, &eng));
+
+        // `User`s can't be parents
+        assert_matches!(
+            es.set_parents(&eng, [alice.clone()], Some(&schema)),
+            Err(EntitiesError::InvalidEntity(_))
+        );
+        // and the hierarchy can't have cycles
+        assert_matches!(
+            es.set_parents(&all, [eng.clone()], Some(&schema)),
+            Err(EntitiesError::TransitiveClosureError(_))
+        );
+        assert_matches!(
+            es.set_parents(&EntityUid::from_strs("Group", "none"), [], None),
+            Err(EntitiesError::EntityNotFound(_))
+        );
+        // failed updates leave the entities unchanged
+        assert!(es.is_ancestor_of(&all, &alice));
+        assert!(!es.is_ancestor_of(&eng, &all));
+
+        es.remove_entities([eng.clone()]).unwrap();
+        assert!(es.is_ancestor_of(&eng, &alice));
+        assert!(!es.is_ancestor_of(&staff, &alice));
+        assert!(!es.is_ancestor_of(&all, &alice));
+        assert!(es.is_ancestor_of(&all, &staff));
+    }
 }
 
 /// A few tests of validating entities.
@@ -3745,6 +3805,107 @@ mod decimal_ip_constructors {
     }
 }
 
+mod datetime_constructors {
+    use cool_asserts::assert_matches;
+
+    use super::*;
+
+    fn evaluate_empty(expr: &Expression) -> Result<EvalResult, EvaluationError> {
+        let r = Request::new(None, None, None, Context::empty(), None).unwrap();
+        let e = Entities::empty();
+        eval_expression(&r, &e, expr)
+    }
+
+    #[test]
+    fn expr_datetime_constructor() {
+        let datetime = Expression::new_datetime("2024-10-15");
+        assert_matches!(datetime.into_inner().expr_kind(),
+            ast::ExprKind::ExtensionFunctionApp { fn_name, args} => {
+                assert_eq!(fn_name, &("datetime".parse().unwrap()));
+                assert_eq!(args.as_ref().len(), 1);
+                let arg = args.first().unwrap();
+                assert_matches!(arg.expr_kind(),
+                ast::ExprKind::Lit(ast::Literal::String(s)) => s.as_str() == "2024-10-15");
+            }
+        );
+    }
+
+    #[test]
+    fn rexpr_duration_constructor() {
+        let duration = RestrictedExpression::new_duration("1h30m");
+        assert_matches!(duration.into_inner().expr_kind(),
+            ast::ExprKind::ExtensionFunctionApp { fn_name, args} => {
+                assert_eq!(fn_name, &("duration".parse().unwrap()));
+                assert_eq!(args.as_ref().len(), 1);
+                let arg = args.first().unwrap();
+                assert_matches!(arg.expr_kind(),
+                ast::ExprKind::Lit(ast::Literal::String(s)) => s.as_str() == "1h30m");
+            }
+        );
+    }
+
+    #[test]
+    fn valid_datetime() {
+        let datetime = Expression::new_datetime("2024-10-15T11:38:02+0100");
+        assert_matches!(evaluate_empty(&datetime),
+            Ok(EvalResult::ExtensionValue(s)) => assert_eq!(s, "2024-10-15T10:38:02.000Z"));
+    }
+
+    #[test]
+    fn invalid_duration() {
+        let duration = Expression::new_duration("1h1d");
+        assert_matches!(evaluate_empty(&duration),
+                Err(e) => assert_matches!(e.error_kind(),
+                    EvaluationErrorKind::FailedExtensionFunctionApplication {
+                        extension_name, ..
+                    } => assert_eq!(extension_name, &("datetime".parse().unwrap()))
+                )
+        );
+    }
+
+    #[test]
+    fn datetime_attribute_from_schema() {
+        let schema = Schema::from_str_natural(
+            r#"
+            entity User { lastLogin: datetime, sessionLength: duration };
+            action view appliesTo { principal: User, resource: User };
+            "#,
+        )
+        .unwrap()
+        .0;
+        let entities = Entities::from_json_value(
+            serde_json::json!([{
+                "uid": { "type": "User", "id": "alice" },
+                "attrs": {
+                    "lastLogin": "2024-10-15T11:38:02Z",
+                    "sessionLength": { "__extn": { "fn": "duration", "arg": "1h" } },
+                },
+                "parents": [],
+            }]),
+            Some(&schema),
+        )
+        .unwrap();
+        let policies = PolicySet::from_str(
+            r#"permit(principal, action, resource) when {
+                principal.lastLogin.offset(principal.sessionLength) > datetime("2024-10-15T12:00:00Z")
+            };"#,
+        )
+        .unwrap();
+        let validation = Validator::new(schema).validate(&policies, ValidationMode::Strict);
+        assert!(validation.validation_passed(), "{validation:?}");
+        let request = Request::new(
+            Some(r#"User::"alice""#.parse().unwrap()),
+            Some(r#"Action::"view""#.parse().unwrap()),
+            Some(r#"User::"alice""#.parse().unwrap()),
+            Context::empty(),
+            None,
+        )
+        .unwrap();
+        let response = Authorizer::new().is_authorized(&request, &policies, &entities);
+        assert_eq!(response.decision(), Decision::Allow);
+    }
+}
+
 mod into_iter_entities {
     use super::*;
 
@@ -3778,6 +3939,1732 @@ mod into_iter_entities {
     }
 }
 
+mod entity_tags {
+    use super::*;
+
+    fn schema() -> Schema {
+        Schema::from_str_natural(
+            r#"
+            entity User { team: String } tags Set<String>;
+            entity Document;
+            action view appliesTo { principal: User, resource: Document };
+            "#,
+        )
+        .unwrap()
+        .0
+    }
+
+    fn request(principal: &str) -> Request {
+        Request::new(
+            Some(format!(r#"User::"{principal}""#).parse().unwrap()),
+            Some(r#"Action::"view""#.parse().unwrap()),
+            Some(r#"Document::"plan""#.parse().unwrap()),
+            Context::empty(),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn authorize_with_tags() {
+        let schema = schema();
+        let entities = Entities::from_json_value(
+            serde_json::json!([
+                {
+                    "uid": { "type": "User", "id": "alice" },
+                    "attrs": { "team": "eng" },
+                    "parents": [],
+                    "tags": { "documents": ["plan"] },
+                },
+                {
+                    "uid": { "type": "User", "id": "bob" },
+                    "attrs": { "team": "eng" },
+                    "parents": [],
+                },
+                {
+                    "uid": { "type": "Document", "id": "plan" },
+                    "attrs": {},
+                    "parents": [],
+                },
+            ]),
+            Some(&schema),
+        )
+        .unwrap();
+        let policies = PolicySet::from_str(
+            r#"permit(principal, action, resource) when {
+                principal.hasTag("documents") && principal.getTag("documents").contains("plan")
+            };"#,
+        )
+        .unwrap();
+        let validation = Validator::new(schema).validate(&policies, ValidationMode::Strict);
+        assert!(validation.validation_passed(), "{validation:?}");
+
+        let authorizer = Authorizer::new();
+        let response = authorizer.is_authorized(&request("alice"), &policies, &entities);
+        assert_eq!(response.decision(), Decision::Allow);
+        let response = authorizer.is_authorized(&request("bob"), &policies, &entities);
+        assert_eq!(response.decision(), Decision::Deny);
+    }
+
+    #[test]
+    fn tags_on_entity_without_tags() {
+        let err = Entities::from_json_value(
+            serde_json::json!([
+                {
+                    "uid": { "type": "Document", "id": "plan" },
+                    "attrs": {},
+                    "parents": [],
+                    "tags": { "owner": "alice" },
+                },
+            ]),
+            Some(&schema()),
+        )
+        .unwrap_err();
+        expect_err(
+            "",
+            &Report::new(err),
+            &ExpectedErrorMessageBuilder::error(
+                r#"error during entity deserialization: found a tag `owner` on `Document::"plan"`, but no tags should exist on `Document::"plan"` according to the schema"#,
+            )
+            .build(),
+        );
+    }
+
+    #[test]
+    fn entity_new_with_tags() {
+        let entity = Entity::new_with_tags(
+            r#"User::"alice""#.parse().unwrap(),
+            HashMap::new(),
+            HashSet::new(),
+            HashMap::from([(
+                "level".to_string(),
+                RestrictedExpression::from_str("3").unwrap(),
+            )]),
+        )
+        .unwrap();
+        assert_eq!(entity.tag("level").unwrap().unwrap(), EvalResult::Long(3));
+        assert!(entity.tag("other").is_none());
+        assert!(entity.attr("level").is_none());
+    }
+}
+
+mod batch_authorization {
+    use super::*;
+
+    #[test]
+    fn responses_in_request_order() {
+        let policies = PolicySet::from_str(
+            r#"
+            permit(principal in Team::"eng", action, resource);
+            forbid(principal, action, resource) when { resource.locked };
+            "#,
+        )
+        .unwrap();
+        let entities = Entities::from_json_value(
+            serde_json::json!([
+                {
+                    "uid": { "type": "User", "id": "alice" },
+                    "attrs": {},
+                    "parents": [{ "type": "Team", "id": "eng" }]
+                },
+                {
+                    "uid": { "type": "Doc", "id": "open" },
+                    "attrs": { "locked": false },
+                    "parents": []
+                },
+                {
+                    "uid": { "type": "Doc", "id": "vault" },
+                    "attrs": { "locked": true },
+                    "parents": []
+                },
+            ]),
+            None,
+        )
+        .unwrap();
+        let request = |principal: &str, resource: &str| {
+            Request::new(
+                Some(format!(r#"User::"{principal}""#).parse().unwrap()),
+                Some(r#"Action::"view""#.parse().unwrap()),
+                Some(format!(r#"Doc::"{resource}""#).parse().unwrap()),
+                Context::empty(),
+                None,
+            )
+            .unwrap()
+        };
+        let requests = [
+            request("alice", "open"),
+            request("alice", "vault"),
+            request("bob", "open"),
+            request("alice", "missing"),
+        ];
+
+        let authorizer = Authorizer::new();
+        let responses = authorizer.is_authorized_batch(&requests, &policies, &entities);
+        assert_eq!(
+            responses.iter().map(Response::decision).collect::<Vec<_>>(),
+            vec![
+                Decision::Allow,
+                Decision::Deny,
+                Decision::Deny,
+                Decision::Allow
+            ]
+        );
+        // the forbid policy errors for the missing document
+        assert_eq!(responses[3].diagnostics().errors().count(), 1);
+        for (request, response) in requests.iter().zip(&responses) {
+            assert_eq!(
+                response,
+                &authorizer.is_authorized(request, &policies, &entities)
+            );
+        }
+    }
+}
+
+mod evaluation_trace {
+    use super::*;
+
+    #[test]
+    fn traces_every_policy() {
+        let policies = PolicySet::from_str(
+            r#"
+            permit(principal in Team::"eng", action, resource) when { resource.public || context.override };
+            forbid(principal, action, resource) when { resource.locked };
+            "#,
+        )
+        .unwrap();
+        let entities = Entities::from_json_value(
+            serde_json::json!([
+                {
+                    "uid": { "type": "User", "id": "alice" },
+                    "attrs": {},
+                    "parents": [{ "type": "Team", "id": "eng" }]
+                },
+                {
+                    "uid": { "type": "Doc", "id": "open" },
+                    "attrs": { "public": true },
+                    "parents": []
+                },
+            ]),
+            None,
+        )
+        .unwrap();
+        let request = Request::new(
+            Some(r#"User::"alice""#.parse().unwrap()),
+            Some(r#"Action::"view""#.parse().unwrap()),
+            Some(r#"Doc::"open""#.parse().unwrap()),
+            Context::empty(),
+            None,
+        )
+        .unwrap();
+
+        let authorizer = Authorizer::new();
+        let untraced = authorizer.is_authorized(&request, &policies, &entities);
+        assert_eq!(untraced.diagnostics().traces().count(), 0);
+        let response = authorizer.is_authorized_with_trace(&request, &policies, &entities);
+        assert_eq!(response.decision(), untraced.decision());
+        assert_eq!(response.diagnostics().traces().count(), 2);
+
+        // `resource.public` is true, so `context.override` is never evaluated
+        let permit = response
+            .diagnostics()
+            .trace(&PolicyId::from_str("policy0").unwrap())
+            .unwrap();
+        let first = permit.steps().next().unwrap();
+        assert_eq!(first.depth(), 0);
+        assert_eq!(first.value(), Some(EvalResult::Bool(true)));
+        assert_eq!(permit.steps().filter(|s| s.short_circuited()).count(), 1);
+        assert!(permit
+            .steps()
+            .all(|s| !s.expr().to_string().starts_with("context")));
+
+        // the forbid policy errors on the missing attribute
+        let forbid = response
+            .diagnostics()
+            .trace(&PolicyId::from_str("policy1").unwrap())
+            .unwrap();
+        assert!(forbid.steps().next().unwrap().error().is_some());
+        assert!(forbid.to_string().contains("error: "));
+    }
+}
+
+mod evaluation_limits {
+    use super::*;
+    use cool_asserts::assert_matches;
+
+    #[test]
+    fn exceeded_limits_deny() {
+        let policies = PolicySet::from_str(
+            r#"
+            permit(principal, action, resource);
+            forbid(principal, action, resource) when { [1, 2, 3, 4, 5].contains(principal.level) };
+            "#,
+        )
+        .unwrap();
+        let request = Request::new(
+            Some(r#"User::"alice""#.parse().unwrap()),
+            Some(r#"Action::"view""#.parse().unwrap()),
+            Some(r#"Doc::"a""#.parse().unwrap()),
+            Context::empty(),
+            None,
+        )
+        .unwrap();
+        let entities = Entities::from_json_value(
+            serde_json::json!([
+                {
+                    "uid": { "type": "User", "id": "alice" },
+                    "attrs": { "level": 3 },
+                    "parents": []
+                },
+            ]),
+            None,
+        )
+        .unwrap();
+
+        let response = Authorizer::new().is_authorized(&request, &policies, &entities);
+        assert_eq!(response.decision(), Decision::Deny);
+
+        let authorizer =
+            Authorizer::new().with_limits(EvaluationLimits::new().with_max_collection_size(4));
+        let response = authorizer.is_authorized(&request, &policies, &entities);
+        // the permit is satisfied, but the forbid might have been too
+        assert_eq!(response.decision(), Decision::Deny);
+        assert_eq!(response.diagnostics().reason().count(), 0);
+        let errors = response.diagnostics().errors().collect::<Vec<_>>();
+        assert_matches!(errors.as_slice(), [AuthorizationError::PolicyEvaluationError(e)] => {
+            assert_eq!(e.id(), &PolicyId::from_str("policy1").unwrap());
+            assert_matches!(
+                e.inner().error_kind(),
+                EvaluationErrorKind::LimitExceeded(LimitExceededError::CollectionSize { size: 5, limit: 4 })
+            );
+        });
+    }
+}
+
+#[cfg(feature = "partial-eval")]
+mod entity_loader {
+    use super::*;
+    use cool_asserts::assert_matches;
+    use entities::err::EntitiesError;
+
+    /// Loads entities from an in-memory `Entities`, counting the UIDs it was
+    /// asked for
+    struct CountingLoader {
+        entities: HashMap<EntityUid, Entity>,
+        requested: Vec<EntityUid>,
+    }
+
+    impl EntityLoader for CountingLoader {
+        type Error = std::convert::Infallible;
+
+        fn load_entities(&mut self, uids: &[&EntityUid]) -> Result<Vec<Entity>, Self::Error> {
+            self.requested.extend(uids.iter().map(|uid| (*uid).clone()));
+            Ok(uids
+                .iter()
+                .filter_map(|uid| self.entities.get(uid).cloned())
+                .collect())
+        }
+    }
+
+    fn counting_loader() -> CountingLoader {
+        let entities = Entities::from_json_value(
+            serde_json::json!([
+                {
+                    "uid": { "type": "User", "id": "alice" },
+                    "attrs": { "department": "eng" },
+                    "parents": [{ "type": "Team", "id": "platform" }]
+                },
+                {
+                    "uid": { "type": "User", "id": "bob" },
+                    "attrs": { "department": "sales" },
+                    "parents": []
+                },
+                {
+                    "uid": { "type": "Team", "id": "platform" },
+                    "attrs": {},
+                    "parents": [{ "type": "Team", "id": "engineering" }]
+                },
+                {
+                    "uid": { "type": "Team", "id": "engineering" },
+                    "attrs": {},
+                    "parents": []
+                },
+            ]),
+            None,
+        )
+        .unwrap();
+        CountingLoader {
+            entities: entities.into_iter().map(|e| (e.uid(), e)).collect(),
+            requested: Vec::new(),
+        }
+    }
+
+    fn request(principal: &str) -> Request {
+        Request::new(
+            Some(format!(r#"User::"{principal}""#).parse().unwrap()),
+            Some(r#"Action::"view""#.parse().unwrap()),
+            Some(r#"Document::"plan""#.parse().unwrap()),
+            Context::empty(),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn loads_only_reached_entities() {
+        let policies = PolicySet::from_str(
+            r#"permit(principal in Team::"engineering", action, resource) when { principal.department == "eng" };"#,
+        )
+        .unwrap();
+        let mut loader = counting_loader();
+        let response = Authorizer::new()
+            .is_authorized_with_loader(&request("alice"), &policies, &mut loader)
+            .unwrap();
+        assert_eq!(response.decision(), Some(Decision::Allow));
+        let mut requested = loader
+            .requested
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        requested.sort();
+        // `Team::"engineering"` is a declared parent of `Team::"platform"`, so
+        // it doesn't need to be loaded to decide the `in`
+        assert_eq!(requested, vec![r#"Team::"platform""#, r#"User::"alice""#]);
+
+        let mut loader = counting_loader();
+        let response = Authorizer::new()
+            .is_authorized_with_loader(&request("bob"), &policies, &mut loader)
+            .unwrap();
+        assert_eq!(response.decision(), Some(Decision::Deny));
+        assert_eq!(loader.requested.len(), 1);
+    }
+
+    #[test]
+    fn missing_entities_are_residuals() {
+        let policies =
+            PolicySet::from_str(r#"permit(principal, action, resource) when { resource.public };"#)
+                .unwrap();
+        let response = Authorizer::new()
+            .is_authorized_with_loader(&request("alice"), &policies, &mut counting_loader())
+            .unwrap();
+        assert_eq!(response.decision(), None);
+        assert_eq!(response.nontrivial_residuals().count(), 1);
+    }
+
+    #[test]
+    fn cycles_are_errors() {
+        let mut loader = counting_loader();
+        let alice = EntityUid::from_strs("User", "alice");
+        let team = EntityUid::from_strs("Team", "platform");
+        loader.entities = HashMap::from([
+            (
+                alice.clone(),
+                Entity::new_no_attrs(alice.clone(), HashSet::from([team.clone()])),
+            ),
+            (
+                team.clone(),
+                Entity::new_no_attrs(team, HashSet::from([alice])),
+            ),
+        ]);
+        let policies =
+            PolicySet::from_str(r#"permit(principal in Team::"engineering", action, resource);"#)
+                .unwrap();
+        assert_matches!(
+            Authorizer::new().is_authorized_with_loader(&request("alice"), &policies, &mut loader),
+            Err(EntitiesError::TransitiveClosureError(_))
+        );
+    }
+}
+
+#[cfg(feature = "partial-eval")]
+mod residual_filters {
+    use super::*;
+    use cool_asserts::assert_matches;
+
+    fn partial_response(src: &str) -> PartialResponse {
+        let request = Request::builder()
+            .principal(Some(EntityUid::from_strs("User", "alice")))
+            .action(Some(EntityUid::from_strs("Action", "view")))
+            .context(Context::empty())
+            .build();
+        Authorizer::new().is_authorized_partial(
+            &request,
+            &PolicySet::from_str(src).unwrap(),
+            &Entities::empty(),
+        )
+    }
+
+    #[test]
+    fn residuals_to_sql() {
+        let response = partial_response(
+            r#"
+            permit(principal, action, resource in Folder::"shared");
+            forbid(principal, action, resource) when { resource.owner != principal };
+            "#,
+        );
+        let filter = response.to_filter().unwrap();
+        assert_eq!(
+            filter.to_sql().unwrap(),
+            r#"((("resource"."uid" = 'Folder::"shared"' OR EXISTS (SELECT 1 FROM "cedar_hierarchy" WHERE "cedar_hierarchy"."child" = "resource"."uid" AND "cedar_hierarchy"."ancestor" = 'Folder::"shared"'))) IS TRUE AND NOT ((NOT ("resource"."owner" = 'User::"alice"')) IS TRUE))"#
+        );
+
+        // a decision that doesn't depend on the resource is a constant
+        let response = partial_response(r#"permit(principal == User::"alice", action, resource);"#);
+        assert_eq!(response.to_filter().unwrap(), filter::Filter::Const(true));
+    }
+
+    #[test]
+    fn simplified_residuals() {
+        let response = partial_response(
+            r#"permit(principal, action, resource) when { resource.size < 10 && true };"#,
+        );
+        let residual = response.simplified().nontrivial_residuals().next().unwrap();
+        assert_eq!(
+            residual.to_string(),
+            "permit(\n  principal,\n  action,\n  resource\n) when {\n  (unknown(\"resource\")[\"size\"]) < 10\n};"
+        );
+    }
+
+    #[test]
+    fn residual_constraints() {
+        let response = partial_response(
+            r#"
+            permit(principal, action, resource in Folder::"shared") when { resource.size <= 10 };
+            permit(principal, action, resource) when { resource.owner == principal };
+            forbid(principal, action, resource) when { resource.locked };
+            "#,
+        );
+        let constraints = response.constraints().unwrap();
+        assert_eq!(
+            constraints.to_string(),
+            r#"resource in Folder::"shared" and resource.size <= 10 and resource.locked != true, or resource.owner == User::"alice" and resource.locked != true"#
+        );
+        let on_resource = constraints.alternatives[0]
+            .constraints_on("resource")
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_matches!(
+            on_resource.as_slice(),
+            [
+                filter::Constraint::In { .. },
+                filter::Constraint::Range {
+                    min: None,
+                    max: Some(10),
+                    ..
+                },
+                filter::Constraint::NoneOf { .. }
+            ]
+        );
+
+        // requests that can't be allowed have no alternatives
+        let response = partial_response(r#"forbid(principal == User::"alice", action, resource);"#);
+        assert!(response.constraints().unwrap().is_unsatisfiable());
+    }
+
+    #[test]
+    fn untranslatable_residuals() {
+        let response = partial_response(
+            r#"permit(principal, action, resource) when { resource.name like "*.pdf" };"#,
+        );
+        assert_matches!(
+            response.to_filter(),
+            Err(filter::FilterError::Unsupported { construct, .. }) => assert_eq!(construct, "`like` patterns")
+        );
+    }
+}
+
+#[cfg(feature = "partial-eval")]
+mod list_resources {
+    use super::*;
+
+    #[test]
+    fn matches_is_authorized() {
+        let entities = Entities::from_json_value(
+            serde_json::json!([
+                {
+                    "uid": { "type": "Document", "id": "plan" },
+                    "attrs": { "owner": { "__entity": { "type": "User", "id": "alice" } } },
+                    "parents": [{ "type": "Folder", "id": "shared" }]
+                },
+                {
+                    "uid": { "type": "Document", "id": "budget" },
+                    "attrs": { "owner": { "__entity": { "type": "User", "id": "bob" } } },
+                    "parents": [{ "type": "Folder", "id": "shared" }]
+                },
+                {
+                    "uid": { "type": "Document", "id": "salaries" },
+                    "attrs": { "owner": { "__entity": { "type": "User", "id": "bob" } } },
+                    "parents": []
+                },
+                {
+                    "uid": { "type": "Folder", "id": "shared" },
+                    "attrs": {},
+                    "parents": []
+                },
+            ]),
+            None,
+        )
+        .unwrap();
+        let policies = PolicySet::from_str(
+            r#"
+            permit(principal, action == Action::"view", resource in Folder::"shared");
+            permit(principal, action, resource) when { resource.owner == principal };
+            forbid(principal, action == Action::"view", resource) when { context.blocked.contains(resource) };
+            "#,
+        )
+        .unwrap();
+        let document = EntityTypeName::from_str("Document").unwrap();
+        let context = Context::from_json_value(
+            serde_json::json!({ "blocked": [{ "__entity": { "type": "Document", "id": "budget" } }] }),
+            None,
+        )
+        .unwrap();
+        let authorizer = Authorizer::new();
+        for principal in ["alice", "bob"] {
+            for action in ["view", "edit"] {
+                let principal = EntityUid::from_strs("User", principal);
+                let action = EntityUid::from_strs("Action", action);
+                let listed = authorizer.list_resources(
+                    &principal,
+                    &action,
+                    &document,
+                    context.clone(),
+                    &policies,
+                    &entities,
+                );
+                let expected = ["plan", "budget", "salaries"]
+                    .into_iter()
+                    .map(|id| EntityUid::from_strs("Document", id))
+                    .filter(|resource| {
+                        let request = Request::new(
+                            Some(principal.clone()),
+                            Some(action.clone()),
+                            Some(resource.clone()),
+                            context.clone(),
+                            None,
+                        )
+                        .unwrap();
+                        authorizer
+                            .is_authorized(&request, &policies, &entities)
+                            .decision()
+                            == Decision::Allow
+                    })
+                    .collect::<HashSet<_>>();
+                assert_eq!(listed, expected, "{principal} {action}");
+            }
+        }
+        assert_eq!(
+            authorizer.list_resources(
+                &EntityUid::from_strs("User", "alice"),
+                &EntityUid::from_strs("Action", "view"),
+                &document,
+                context,
+                &policies,
+                &entities,
+            ),
+            HashSet::from([EntityUid::from_strs("Document", "plan")])
+        );
+    }
+}
+
+#[cfg(feature = "partial-eval")]
+mod typed_unknowns {
+    use super::*;
+    use cool_asserts::assert_matches;
+
+    fn residual_ids(response: &PartialResponse) -> HashSet<String> {
+        response
+            .nontrivial_residuals()
+            .map(|p| p.id().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn unknown_context_and_attrs() {
+        let policies = PolicySet::from_str(
+            r#"
+            permit(principal, action, resource) when { context.level > 3 };
+            permit(principal, action, resource) when { principal.department == "eng" };
+            permit(principal, action, resource) when { context.level like "admin*" };
+            permit(principal, action, resource) when { principal.manager is Group };
+            "#,
+        )
+        .unwrap();
+        let alice = Entity::new(
+            EntityUid::from_strs("User", "alice"),
+            HashMap::from([
+                (
+                    "department".into(),
+                    RestrictedExpression::new_typed_unknown("department", UnknownType::Long),
+                ),
+                (
+                    "manager".into(),
+                    RestrictedExpression::new_typed_unknown(
+                        "manager",
+                        UnknownType::Entity(EntityTypeName::from_str("User").unwrap()),
+                    ),
+                ),
+            ]),
+            HashSet::new(),
+        )
+        .unwrap();
+        let entities = Entities::from_entities([alice], None).unwrap();
+        let context = Context::from_pairs([(
+            "level".into(),
+            RestrictedExpression::new_typed_unknown("level", UnknownType::Long),
+        )])
+        .unwrap();
+        let request = Request::builder()
+            .principal(Some(EntityUid::from_strs("User", "alice")))
+            .action(Some(EntityUid::from_strs("Action", "view")))
+            .resource(Some(EntityUid::from_strs("Doc", "plan")))
+            .context(context)
+            .build();
+        let response = Authorizer::new().is_authorized_partial(&request, &policies, &entities);
+
+        // the annotated types decide `policy1` and `policy3`, and show that
+        // `policy2` errors, so only `policy0` is left
+        assert_eq!(residual_ids(&response), HashSet::from(["policy0".into()]));
+        assert_eq!(
+            response
+                .definitely_errored()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec!["policy2"]
+        );
+    }
+
+    #[test]
+    fn untyped_unknowns() {
+        let context =
+            Context::from_pairs([("level".into(), RestrictedExpression::new_unknown("level"))])
+                .unwrap();
+        let request = Request::builder()
+            .principal(Some(EntityUid::from_strs("User", "alice")))
+            .action(Some(EntityUid::from_strs("Action", "view")))
+            .resource(Some(EntityUid::from_strs("Doc", "plan")))
+            .context(context)
+            .build();
+        let policies = PolicySet::from_str(
+            r#"permit(principal, action, resource) when { context.level like "admin*" };"#,
+        )
+        .unwrap();
+        let response =
+            Authorizer::new().is_authorized_partial(&request, &policies, &Entities::empty());
+        assert_eq!(residual_ids(&response), HashSet::from(["policy0".into()]));
+    }
+
+    #[test]
+    fn unknown_ancestors() {
+        let entities = Entities::from_entities(
+            [
+                Entity::new_no_attrs(
+                    EntityUid::from_strs("User", "alice"),
+                    HashSet::from([EntityUid::from_strs("Team", "web")]),
+                ),
+                Entity::with_uid(EntityUid::from_strs("Team", "web"))
+                    .with_unknown_ancestors("web_groups"),
+            ],
+            None,
+        )
+        .unwrap();
+        let policies = PolicySet::from_str(
+            r#"
+            permit(principal in Team::"web", action, resource);
+            permit(principal in Group::"admins", action, resource);
+            "#,
+        )
+        .unwrap();
+        let request = Request::builder()
+            .principal(Some(EntityUid::from_strs("User", "alice")))
+            .action(Some(EntityUid::from_strs("Action", "view")))
+            .resource(Some(EntityUid::from_strs("Doc", "plan")))
+            .context(Context::empty())
+            .build();
+        let response = Authorizer::new().is_authorized_partial(&request, &policies, &entities);
+        assert_eq!(response.decision(), Some(Decision::Allow));
+        assert_eq!(residual_ids(&response), HashSet::from(["policy1".into()]));
+
+        // the residual checks the unknown ancestors of `Team::"web"`
+        let residual = response.get(&PolicyId::new("policy1")).unwrap();
+        assert!(residual
+            .to_string()
+            .contains(r#"unknown("web_groups").contains(Group::"admins")"#));
+
+        // concrete authorization doesn't rule out the policy on its scope
+        let response = Authorizer::new().is_authorized(&request, &policies, &entities);
+        assert_eq!(response.decision(), Decision::Allow);
+        assert_eq!(
+            response
+                .diagnostics()
+                .errors()
+                .map(|e| e.id().to_string())
+                .collect::<Vec<_>>(),
+            vec!["policy1"]
+        );
+    }
+
+    #[test]
+    fn typed_unknowns_are_validated() {
+        let (schema, _) = Schema::from_str_natural(
+            "entity User; action view appliesTo { principal: User, resource: User, context: { level: Long } };",
+        )
+        .unwrap();
+        let request = |ty| {
+            Request::builder()
+                .principal(Some(EntityUid::from_strs("User", "alice")))
+                .action(Some(EntityUid::from_strs("Action", "view")))
+                .resource(Some(EntityUid::from_strs("User", "bob")))
+                .context(
+                    Context::from_pairs([(
+                        "level".into(),
+                        RestrictedExpression::new_typed_unknown("level", ty),
+                    )])
+                    .unwrap(),
+                )
+                .schema(&schema)
+                .build()
+        };
+        assert_matches!(request(UnknownType::Long), Ok(_));
+        assert_matches!(request(UnknownType::String), Err(_));
+        assert_matches!(request(UnknownType::Set), Err(_));
+    }
+}
+
+mod policy_set_slice {
+    use super::*;
+
+    fn schema() -> Schema {
+        Schema::from_json_value(serde_json::json!({ "": {
+            "entityTypes": {
+                "User": { "memberOfTypes": ["Group"] },
+                "Group": {},
+                "Document": { "memberOfTypes": ["Folder"] },
+                "Folder": {},
+                "Photo": {},
+            },
+            "actions": {
+                "read": {},
+                "view": {
+                    "memberOf": [{ "id": "read" }],
+                    "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Document", "Photo"] }
+                },
+                "upload": {
+                    "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Photo"] }
+                },
+            }
+        }}))
+        .unwrap()
+    }
+
+    fn sliced_ids(policies: &PolicySet, resource_type: &str) -> HashSet<String> {
+        policies
+            .slice(
+                &EntityTypeName::from_str("User").unwrap(),
+                &EntityUid::from_strs("Action", "view"),
+                &EntityTypeName::from_str(resource_type).unwrap(),
+                &schema(),
+            )
+            .policies()
+            .map(|p| p.id().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn static_policies() {
+        let policies = PolicySet::from_str(
+            r#"
+            @id("any") permit(principal, action, resource);
+            @id("view") permit(principal, action == Action::"view", resource);
+            @id("read") permit(principal, action in [Action::"read"], resource);
+            @id("upload") permit(principal, action == Action::"upload", resource);
+            @id("group") permit(principal in Group::"admins", action, resource);
+            @id("folder") permit(principal, action, resource in Folder::"shared");
+            @id("photo") permit(principal, action, resource is Photo);
+            @id("doc") permit(principal, action, resource == Document::"plan");
+            @id("doc_in_folder") forbid(principal, action, resource is Document in Folder::"shared");
+            @id("group_principal") permit(principal is Group, action, resource);
+            "#,
+        )
+        .unwrap();
+        let policies = PolicySet::from_policies(
+            policies
+                .policies()
+                .map(|p| p.new_id(PolicyId::new(p.annotation("id").unwrap()))),
+        )
+        .unwrap();
+
+        assert_eq!(
+            sliced_ids(&policies, "Document"),
+            HashSet::from(
+                [
+                    "any",
+                    "view",
+                    "read",
+                    "group",
+                    "folder",
+                    "doc",
+                    "doc_in_folder"
+                ]
+                .map(String::from)
+            )
+        );
+        assert_eq!(
+            sliced_ids(&policies, "Photo"),
+            HashSet::from(["any", "view", "read", "group", "photo"].map(String::from))
+        );
+        // `view` does not apply to `Folder` resources, so nothing can apply
+        assert!(sliced_ids(&policies, "Folder").is_empty());
+    }
+
+    #[test]
+    fn template_links() {
+        let mut policies = PolicySet::new();
+        policies
+            .add_template(
+                Template::parse(
+                    Some("in_folder".into()),
+                    "permit(principal == ?principal, action, resource in ?resource);",
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        policies
+            .add_template(
+                Template::parse(
+                    Some("unlinked".into()),
+                    "permit(principal == ?principal, action, resource);",
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        for (id, resource) in [
+            ("folder", EntityUid::from_strs("Folder", "shared")),
+            ("photo", EntityUid::from_strs("Photo", "cat")),
+        ] {
+            policies
+                .link(
+                    PolicyId::new("in_folder"),
+                    PolicyId::new(id),
+                    HashMap::from([
+                        (SlotId::principal(), EntityUid::from_strs("User", "alice")),
+                        (SlotId::resource(), resource),
+                    ]),
+                )
+                .unwrap();
+        }
+
+        let sliced = policies.slice(
+            &EntityTypeName::from_str("User").unwrap(),
+            &EntityUid::from_strs("Action", "view"),
+            &EntityTypeName::from_str("Document").unwrap(),
+            &schema(),
+        );
+        assert_eq!(
+            sliced
+                .policies()
+                .map(|p| p.id().to_string())
+                .collect::<Vec<_>>(),
+            vec!["folder".to_string()]
+        );
+        assert_eq!(
+            sliced
+                .templates()
+                .map(|t| t.id().to_string())
+                .collect::<Vec<_>>(),
+            vec!["in_folder".to_string()]
+        );
+
+        let request = Request::new(
+            Some(EntityUid::from_strs("User", "alice")),
+            Some(EntityUid::from_strs("Action", "view")),
+            Some(EntityUid::from_strs("Document", "plan")),
+            Context::empty(),
+            None,
+        )
+        .unwrap();
+        let entities = Entities::from_json_value(
+            serde_json::json!([{
+                "uid": { "type": "Document", "id": "plan" },
+                "attrs": {},
+                "parents": [{ "type": "Folder", "id": "shared" }]
+            }]),
+            None,
+        )
+        .unwrap();
+        let authorizer = Authorizer::new();
+        assert_eq!(
+            authorizer
+                .is_authorized(&request, &sliced, &entities)
+                .decision(),
+            authorizer
+                .is_authorized(&request, &policies, &entities)
+                .decision()
+        );
+    }
+
+    #[test]
+    fn undeclared_action() {
+        let policies = PolicySet::from_str("permit(principal, action, resource);").unwrap();
+        let sliced = policies.slice(
+            &EntityTypeName::from_str("User").unwrap(),
+            &EntityUid::from_strs("Action", "delete"),
+            &EntityTypeName::from_str("Document").unwrap(),
+            &schema(),
+        );
+        assert!(sliced.is_empty());
+    }
+}
+
+mod entity_manifest {
+    use super::*;
+
+    #[test]
+    fn sliced_entities_give_same_decisions() {
+        let schema = Schema::from_json_value(serde_json::json!({ "": {
+            "entityTypes": {
+                "User": {
+                    "memberOfTypes": ["Group"],
+                    "shape": { "type": "Record", "attributes": {
+                        "department": { "type": "String" },
+                        "manager": { "type": "Entity", "name": "User" },
+                    }},
+                },
+                "Group": {},
+                "Document": {
+                    "memberOfTypes": ["Folder"],
+                    "shape": { "type": "Record", "attributes": {
+                        "owner": { "type": "Entity", "name": "User" },
+                        "department": { "type": "String" },
+                    }},
+                },
+                "Folder": {},
+            },
+            "actions": {
+                "view": { "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Document"] } },
+                "edit": { "appliesTo": { "principalTypes": ["User"], "resourceTypes": ["Document"] } },
+            }
+        }}))
+        .unwrap();
+        let policies = PolicySet::from_str(
+            r#"
+            permit(principal, action == Action::"view", resource in Folder::"shared")
+            when { principal.department == resource.department };
+            permit(principal, action == Action::"edit", resource)
+            when { resource.owner == principal || resource.owner.manager == principal };
+            forbid(principal in Group::"suspended", action, resource);
+            "#,
+        )
+        .unwrap();
+        let entities = Entities::from_json_value(
+            serde_json::json!([
+                {
+                    "uid": { "type": "User", "id": "alice" },
+                    "attrs": { "department": "sales", "manager": { "__entity": { "type": "User", "id": "bob" } } },
+                    "parents": []
+                },
+                {
+                    "uid": { "type": "User", "id": "bob" },
+                    "attrs": { "department": "sales", "manager": { "__entity": { "type": "User", "id": "bob" } } },
+                    "parents": [{ "type": "Group", "id": "suspended" }]
+                },
+                {
+                    "uid": { "type": "Document", "id": "plan" },
+                    "attrs": { "owner": { "__entity": { "type": "User", "id": "alice" } }, "department": "sales" },
+                    "parents": [{ "type": "Folder", "id": "shared" }]
+                },
+            ]),
+            None,
+        )
+        .unwrap();
+
+        let manifest = EntityManifest::new(&policies, &schema);
+        let manifest = EntityManifest::from_json_value(manifest.to_json_value().unwrap()).unwrap();
+        let authorizer = Authorizer::new();
+        for principal in ["alice", "bob"] {
+            for action in ["view", "edit"] {
+                let request = Request::new(
+                    Some(EntityUid::from_strs("User", principal)),
+                    Some(EntityUid::from_strs("Action", action)),
+                    Some(EntityUid::from_strs("Document", "plan")),
+                    Context::empty(),
+                    Some(&schema),
+                )
+                .unwrap();
+                let sliced = manifest.slice_entities(&entities, &request).unwrap();
+                assert!(sliced.iter().count() <= entities.iter().count());
+                assert_eq!(
+                    authorizer
+                        .is_authorized(&request, &policies, &sliced)
+                        .decision(),
+                    authorizer
+                        .is_authorized(&request, &policies, &entities)
+                        .decision(),
+                    "{principal} {action}"
+                );
+            }
+        }
+
+        // `edit` needs `owner` and the owner's `manager`, but not departments
+        let request = Request::new(
+            Some(EntityUid::from_strs("User", "bob")),
+            Some(EntityUid::from_strs("Action", "edit")),
+            Some(EntityUid::from_strs("Document", "plan")),
+            Context::empty(),
+            Some(&schema),
+        )
+        .unwrap();
+        let sliced = manifest.slice_entities(&entities, &request).unwrap();
+        let alice = sliced.get(&EntityUid::from_strs("User", "alice")).unwrap();
+        assert!(alice.attr("manager").is_some());
+        assert!(alice.attr("department").is_none());
+        let plan = sliced
+            .get(&EntityUid::from_strs("Document", "plan"))
+            .unwrap();
+        assert!(plan.attr("owner").is_some());
+        assert!(plan.attr("department").is_none());
+    }
+}
+
+#[cfg(feature = "partial-eval")]
+mod policy_analysis {
+    use super::*;
+    use cool_asserts::assert_matches;
+
+    fn analyzer() -> PolicyAnalyzer {
+        let (schema, _) = Schema::from_str_natural(
+            r#"
+            entity Team;
+            entity User in [Team] { clearance: Long, suspended: Bool };
+            entity Document { owner: User, classification: Long };
+            action read, write appliesTo {
+                principal: [User],
+                resource: [Document],
+            };
+            "#,
+        )
+        .unwrap();
+        PolicyAnalyzer::new(schema)
+    }
+
+    #[test]
+    fn equivalence_and_counterexamples() {
+        let analyzer = analyzer();
+        let original = PolicySet::from_str(
+            r#"
+            permit(principal, action == Action::"read", resource)
+            when { principal.clearance >= resource.classification };
+            forbid(principal, action, resource) when { principal.suspended };
+            "#,
+        )
+        .unwrap();
+        let refactored = PolicySet::from_str(
+            r#"
+            permit(principal, action == Action::"read", resource)
+            when { !(resource.classification > principal.clearance) && !principal.suspended };
+            "#,
+        )
+        .unwrap();
+        assert_matches!(
+            analyzer.equivalent(&original, &refactored),
+            Ok(AnalysisOutcome::Holds)
+        );
+
+        let loosened = PolicySet::from_str(
+            r#"
+            permit(principal, action, resource)
+            when { principal.clearance >= resource.classification };
+            forbid(principal, action, resource) when { principal.suspended };
+            "#,
+        )
+        .unwrap();
+        assert_matches!(
+            analyzer.equivalent(&original, &loosened),
+            Ok(AnalysisOutcome::Fails(counterexample)) => {
+                let authorizer = Authorizer::new();
+                let decide = |policies| {
+                    authorizer
+                        .is_authorized(counterexample.request(), policies, counterexample.entities())
+                        .decision()
+                };
+                assert_eq!(decide(&original), Decision::Deny);
+                assert_eq!(decide(&loosened), Decision::Allow);
+                assert_eq!(
+                    counterexample.request().action(),
+                    Some(&EntityUid::from_strs("Action", "write"))
+                );
+            }
+        );
+    }
+
+    #[test]
+    fn subsumption_and_forbids() {
+        let analyzer = analyzer();
+        let general = Policy::parse(
+            Some("general".into()),
+            r#"permit(principal in Team::"staff", action, resource);"#,
+        )
+        .unwrap();
+        let specific = Policy::parse(
+            Some("specific".into()),
+            r#"permit(principal in Team::"staff", action == Action::"read", resource)
+            when { principal.clearance > 2 };"#,
+        )
+        .unwrap();
+        assert_matches!(
+            analyzer.subsumes(&general, &specific),
+            Ok(AnalysisOutcome::Holds)
+        );
+        assert_matches!(
+            analyzer.subsumes(&specific, &general),
+            Ok(AnalysisOutcome::Fails(_))
+        );
+
+        let policies = PolicySet::from_str(
+            r#"
+            @id("permit")
+            permit(principal, action, resource) when { principal.clearance > 3 };
+            @id("suspended")
+            forbid(principal, action, resource) when { principal.suspended };
+            @id("low")
+            forbid(principal, action, resource) when { principal.clearance < 2 };
+            @id("everyone")
+            forbid(principal, action, resource);
+            "#,
+        )
+        .unwrap();
+        let id = |name: &str| {
+            policies
+                .policies()
+                .find(|p| p.annotation("id") == Some(name))
+                .unwrap()
+                .id()
+                .clone()
+        };
+        assert_matches!(
+            analyzer.forbid_is_redundant(&policies, &id("low")),
+            Ok(AnalysisOutcome::Holds)
+        );
+        assert_matches!(
+            analyzer.forbid_shadows_permits(&policies, &id("everyone")),
+            Ok(AnalysisOutcome::Holds)
+        );
+        assert_matches!(
+            analyzer.forbid_shadows_permits(&policies, &id("suspended")),
+            Ok(AnalysisOutcome::Fails(_))
+        );
+        assert_matches!(
+            analyzer.forbid_is_redundant(&policies, &id("permit")),
+            Err(AnalysisError::NotAForbid(_))
+        );
+        assert_matches!(
+            analyzer.equivalent(
+                &PolicySet::from_str(
+                    r#"permit(principal, action, resource) when { principal.clearance + 1 > 3 };"#
+                )
+                .unwrap(),
+                &policies,
+            ),
+            Err(AnalysisError::Unsupported { .. })
+        );
+    }
+}
+
+mod lints {
+    use super::*;
+    use cool_asserts::assert_matches;
+
+    /// Every `forbid` policy must say why it exists
+    #[derive(Debug)]
+    struct ForbidReason;
+
+    impl LintRule for ForbidReason {
+        fn id(&self) -> &str {
+            "forbid-reason"
+        }
+
+        fn check(&self, template: &Template) -> Vec<String> {
+            if template.effect() == Effect::Forbid && template.annotation("reason").is_none() {
+                vec!["forbid policies need a `@reason` annotation".to_string()]
+            } else {
+                Vec::new()
+            }
+        }
+    }
+
+    #[test]
+    fn builtin_and_custom_rules() {
+        let (schema, _) = Schema::from_str_natural(
+            r#"
+            entity User;
+            entity Photo { name: String };
+            action view appliesTo { principal: [User], resource: [Photo] };
+            "#,
+        )
+        .unwrap();
+        let policies = PolicySet::from_str(
+            r#"
+            @id("public")
+            permit(principal, action, resource) when { resource.name like "*.public.jpg" };
+            @id("blocked")
+            forbid(principal == ?principal, action, resource);
+            "#,
+        )
+        .unwrap();
+        let validator = Validator::new(schema);
+
+        let mut linter = Linter::new();
+        linter
+            .add_builtin("require-annotation", LintSeverity::Error)
+            .unwrap();
+        linter
+            .add_builtin("leading-wildcard-like", LintSeverity::Warning)
+            .unwrap();
+        linter.add_rule(ForbidReason, LintSeverity::Warning);
+        let result = validator.validate_with_linter(&policies, ValidationMode::Strict, &linter);
+        assert!(result.validation_passed());
+        let mut warnings = result
+            .validation_warnings()
+            .map(|w| match w.warning_kind() {
+                ValidationWarningKind::Lint(violation) => (
+                    w.location().policy_id().to_string(),
+                    violation.rule().to_string(),
+                ),
+                kind => panic!("unexpected warning {kind}"),
+            })
+            .collect::<Vec<_>>();
+        warnings.sort();
+        assert_eq!(
+            warnings,
+            [
+                ("policy0".to_string(), "leading-wildcard-like".to_string()),
+                ("policy1".to_string(), "forbid-reason".to_string()),
+            ]
+        );
+
+        linter
+            .add_builtin("unscoped-permit", LintSeverity::Error)
+            .unwrap();
+        let result = validator.validate_with_linter(&policies, ValidationMode::Strict, &linter);
+        assert_eq!(result.validation_errors().count(), 1);
+        assert_matches!(
+            linter.add_builtin("max-complexity=many", LintSeverity::Error),
+            Err(LintConfigError::InvalidConfig { .. })
+        );
+    }
+}
+
+mod deref_levels {
+    use super::*;
+
+    #[test]
+    fn validate_with_level() {
+        let (schema, _) = Schema::from_str_natural(
+            r#"
+            entity Team { name: String };
+            entity User in [Team] { manager: User, team: Team };
+            entity Photo;
+            action view appliesTo { principal: [User], resource: [Photo] };
+            "#,
+        )
+        .unwrap();
+        let validator = Validator::new(schema);
+        let policies = PolicySet::from_str(
+            r#"
+            permit(principal in Team::"admins", action, resource);
+            permit(principal, action, resource) when { principal.manager.team.name == "eng" };
+            "#,
+        )
+        .unwrap();
+
+        let result = validator.validate_with_level(&policies, ValidationMode::Strict, 3);
+        assert!(result.validation_passed());
+        let result = validator.validate_with_level(&policies, ValidationMode::Strict, 1);
+        let mut levels = result
+            .validation_errors()
+            .map(|e| match e.error_kind() {
+                ValidationErrorKind::EntityDerefLevelViolation(v) => {
+                    assert_eq!(v.allowed_level(), 1);
+                    (e.location().policy_id().to_string(), v.actual_level())
+                }
+                kind => panic!("unexpected error {kind}"),
+            })
+            .collect::<Vec<_>>();
+        levels.sort();
+        assert_eq!(
+            levels,
+            [("policy1".to_string(), 2), ("policy1".to_string(), 3)]
+        );
+    }
+}
+
+mod schema_diff {
+    use super::*;
+
+    #[test]
+    fn diff_and_broken_policies() {
+        let (old, _) = Schema::from_str_natural(
+            r#"
+            entity User { name: String, email?: String };
+            entity Photo;
+            action view appliesTo { principal: [User], resource: [Photo] };
+            "#,
+        )
+        .unwrap();
+        let (new, _) = Schema::from_str_natural(
+            r#"
+            entity User { name: String, email: String, admin?: Bool };
+            entity Photo;
+            entity Album;
+            action view appliesTo { principal: [User], resource: [Photo, Album] };
+            "#,
+        )
+        .unwrap();
+        let diff = SchemaDiff::new(&old, &old);
+        assert!(diff.is_backward_compatible());
+        assert_eq!(diff.changes().count(), 0);
+
+        let diff = SchemaDiff::new(&old, &new);
+        assert!(!diff.is_backward_compatible());
+        assert_eq!(diff.changes().count(), 4);
+        let breaking = diff
+            .breaking_changes()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            breaking,
+            ["attribute `email` of entity type `User` is now required"]
+        );
+
+        let policies = PolicySet::from_str(
+            r#"
+            permit(principal, action, resource) when { principal has email };
+            permit(principal, action, resource) when { principal.name == "alice" };
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            diff.broken_policies(&policies, ValidationMode::Strict)
+                .count(),
+            0
+        );
+        // reverting the change makes `email` optional again, so reading it
+        // without a `has` check no longer validates
+        let policies = PolicySet::from_str(
+            r#"
+            permit(principal, action, resource) when { principal.email == "a@example.com" };
+            permit(principal, action, resource) when { principal has admin };
+            "#,
+        )
+        .unwrap();
+        let broken = SchemaDiff::new(&new, &old)
+            .broken_policies(&policies, ValidationMode::Strict)
+            .map(|e| e.location().policy_id().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(broken, ["policy0"]);
+    }
+}
+
+mod schema_annotations {
+    use super::*;
+
+    #[test]
+    fn annotations_survive_translation() {
+        let (fragment, _) = SchemaFragment::from_str_natural(
+            r#"
+            @doc("the app")
+            namespace App {
+                // A display name
+                type Name = String;
+                @doc("a user")
+                entity User {
+                    name: Name, // shown in the UI
+                };
+                // Read a user
+                action view appliesTo { principal: [User], resource: [User] };
+            }
+            "#,
+        )
+        .unwrap();
+        let ns = EntityNamespace::from_str("App").unwrap();
+        let user = EntityTypeName::from_str("App::User").unwrap();
+        let check = |fragment: &SchemaFragment| {
+            assert_eq!(
+                fragment
+                    .namespace_annotations(Some(&ns))
+                    .unwrap()
+                    .collect::<Vec<_>>(),
+                [("doc", "the app")]
+            );
+            assert_eq!(
+                fragment
+                    .common_type_annotations(Some(&ns), "Name")
+                    .unwrap()
+                    .collect::<Vec<_>>(),
+                [("comment", "A display name")]
+            );
+            assert_eq!(
+                fragment
+                    .entity_type_annotations(&user)
+                    .unwrap()
+                    .collect::<Vec<_>>(),
+                [("doc", "a user")]
+            );
+            assert_eq!(
+                fragment
+                    .attribute_annotations(&user, "name")
+                    .unwrap()
+                    .collect::<Vec<_>>(),
+                [("comment", "shown in the UI")]
+            );
+            assert_eq!(
+                fragment
+                    .action_annotations(Some(&ns), "view")
+                    .unwrap()
+                    .collect::<Vec<_>>(),
+                [("comment", "Read a user")]
+            );
+            assert!(fragment.action_annotations(None, "view").is_none());
+        };
+        check(&fragment);
+
+        let json = SchemaFragment::from_json_value(
+            serde_json::from_str(&fragment.as_json_string().unwrap()).unwrap(),
+        )
+        .unwrap();
+        check(&json);
+        let (natural, _) = SchemaFragment::from_str_natural(&json.as_natural().unwrap()).unwrap();
+        check(&natural);
+    }
+}
+
+mod enum_entities {
+    use super::*;
+    use cool_asserts::assert_matches;
+    use serde_json::json;
+
+    fn schema() -> Schema {
+        Schema::from_str_natural(
+            r#"
+            entity Role enum ["admin", "viewer", "editor"];
+            entity User in [Role] {
+                role?: Role,
+                grants?: { roles: Set<Role> },
+            } tags Role;
+            action view appliesTo {
+                principal: [User],
+                resource: [Role],
+                context: { granted?: Role },
+            };
+            "#,
+        )
+        .unwrap()
+        .0
+    }
+
+    #[test]
+    fn policies() {
+        let validator = Validator::new(schema());
+        let pset = PolicySet::from_str(
+            r#"permit(principal in Role::"admin", action, resource == Role::"viewer");"#,
+        )
+        .unwrap();
+        assert!(validator
+            .validate(&pset, ValidationMode::default())
+            .validation_passed());
+
+        let pset = PolicySet::from_str(
+            r#"permit(principal in Role::"admn", action, resource) when { resource == Role::"viewer" };"#,
+        )
+        .unwrap();
+        let result = validator.validate(&pset, ValidationMode::default());
+        let errors = result.validation_errors().collect::<Vec<_>>();
+        assert_matches!(
+            errors.as_slice(),
+            [e] => assert_matches!(e.error_kind(), ValidationErrorKind::InvalidEnumEntity(_))
+        );
+        assert!(
+            errors[0].to_string().contains(r#"Role::"admn""#),
+            "actual error message was: `{}`",
+            errors[0]
+        );
+    }
+
+    #[test]
+    fn entities() {
+        let schema = schema();
+        let entities = |json| Entities::from_json_value(json, Some(&schema));
+        assert_matches!(
+            entities(json!([
+                { "uid": { "type": "User", "id": "alice" }, "attrs": {}, "parents": [{ "type": "Role", "id": "editor" }] }
+            ])),
+            Ok(_)
+        );
+        let err = entities(json!([
+            { "uid": { "type": "User", "id": "alice" }, "attrs": {}, "parents": [{ "type": "Role", "id": "owner" }] }
+        ]))
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains(r#"entity `Role::"owner"` is of an enumerated entity type"#),
+            "actual error message was: `{err}`"
+        );
+    }
+
+    #[test]
+    fn requests() {
+        let schema = schema();
+        let request = |resource: &str| {
+            Request::new(
+                Some(EntityUid::from_str(r#"User::"alice""#).unwrap()),
+                Some(EntityUid::from_str(r#"Action::"view""#).unwrap()),
+                Some(EntityUid::from_str(resource).unwrap()),
+                Context::empty(),
+                Some(&schema),
+            )
+        };
+        assert_matches!(request(r#"Role::"viewer""#), Ok(_));
+        let err = request(r#"Role::"owner""#).unwrap_err();
+        assert!(
+            err.to_string().contains(r#"Role::"owner""#),
+            "actual error message was: `{err}`"
+        );
+    }
+
+    #[test]
+    fn attribute_and_tag_values() {
+        let schema = schema();
+        let entities = |json| Entities::from_json_value(json, Some(&schema));
+        assert_matches!(
+            entities(json!([{
+                "uid": { "type": "User", "id": "alice" },
+                "attrs": {
+                    "role": { "type": "Role", "id": "admin" },
+                    "grants": { "roles": [{ "type": "Role", "id": "viewer" }] }
+                },
+                "parents": [],
+                "tags": { "team": { "type": "Role", "id": "editor" } }
+            }])),
+            Ok(_)
+        );
+        for (attrs, tags) in [
+            (
+                json!({ "role": { "type": "Role", "id": "superadmin" } }),
+                json!({}),
+            ),
+            (
+                json!({ "grants": { "roles": [
+                    { "type": "Role", "id": "viewer" },
+                    { "type": "Role", "id": "superadmin" }
+                ] } }),
+                json!({}),
+            ),
+            (
+                json!({}),
+                json!({ "team": { "type": "Role", "id": "superadmin" } }),
+            ),
+        ] {
+            let err = entities(json!([{
+                "uid": { "type": "User", "id": "alice" },
+                "attrs": attrs,
+                "parents": [],
+                "tags": tags
+            }]))
+            .unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains(r#"entity `Role::"superadmin"` is of an enumerated entity type"#),
+                "actual error message was: `{err}`"
+            );
+        }
+    }
+
+    #[test]
+    fn context_values() {
+        let schema = schema();
+        let request = |context| {
+            Request::new(
+                Some(EntityUid::from_str(r#"User::"alice""#).unwrap()),
+                Some(EntityUid::from_str(r#"Action::"view""#).unwrap()),
+                Some(EntityUid::from_str(r#"Role::"viewer""#).unwrap()),
+                Context::from_json_value(context, None).unwrap(),
+                Some(&schema),
+            )
+        };
+        assert_matches!(
+            request(json!({ "granted": { "__entity": { "type": "Role", "id": "admin" } } })),
+            Ok(_)
+        );
+        let err =
+            request(json!({ "granted": { "__entity": { "type": "Role", "id": "superadmin" } } }))
+                .unwrap_err();
+        assert!(
+            err.to_string().contains(r#"Role::"superadmin""#),
+            "actual error message was: `{err}`"
+        );
+    }
+}
+
+mod json_schema_export {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Schema {
+        Schema::from_str_natural(
+            r#"
+            entity User { name: String, manager?: User };
+            entity Photo;
+            action view appliesTo {
+                principal: [User],
+                resource: [Photo],
+                context: { ip: ipaddr },
+            };
+            "#,
+        )
+        .unwrap()
+        .0
+    }
+
+    #[test]
+    fn entities() {
+        let doc = schema().entities_json_schema();
+        assert_eq!(
+            doc["$schema"],
+            json!("https://json-schema.org/draft/2020-12/schema")
+        );
+        assert_eq!(
+            doc["items"],
+            json!({
+                "anyOf": [
+                    { "$ref": "#/$defs/entity:Photo" },
+                    { "$ref": "#/$defs/entity:User" },
+                ],
+            })
+        );
+        assert_eq!(
+            doc["$defs"]["entity:User"]["properties"]["attrs"]["properties"]["manager"],
+            json!({ "$ref": "#/$defs/uid:User" })
+        );
+        assert_eq!(
+            doc["$defs"]["uid:User"]["anyOf"][1]["required"],
+            json!(["__entity"])
+        );
+    }
+
+    #[test]
+    fn context() {
+        let schema = schema();
+        let view = EntityUid::from_str(r#"Action::"view""#).unwrap();
+        let doc = schema.context_json_schema(&view).unwrap();
+        assert_eq!(doc["required"], json!(["ip"]));
+        assert_eq!(
+            doc["properties"]["ip"]["anyOf"][2]["required"],
+            json!(["__extn"])
+        );
+        let edit = EntityUid::from_str(r#"Action::"edit""#).unwrap();
+        assert!(schema.context_json_schema(&edit).is_none());
+    }
+}
+
 mod policy_set_est_tests {
     use cool_asserts::assert_matches;
     use itertools::{Either, Itertools};